@@ -1,7 +1,10 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
 use std::borrow::Cow;
+use std::error::Error;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::Deref;
+use std::panic::Location;
 use thiserror::Error;
 
 #[derive(Debug)]
@@ -40,6 +43,142 @@ impl Display for ErrString {
 pub enum QLabError {
     #[error(transparent)]
     ComputeError(#[from] ComputeError),
+    #[error(transparent)]
+    #[non_exhaustive]
+    Context(Box<ContextError>),
+}
+
+impl QLabError {
+    /// Wraps the error with a description of what was being done, recording the caller's source location.
+    ///
+    /// The innermost context also captures a backtrace if enabled by `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`.
+    #[must_use]
+    #[track_caller]
+    pub fn context(self, context: impl Into<ErrString>) -> Self {
+        let backtrace = match &self {
+            Self::ComputeError(_) => Some(Backtrace::capture())
+                .filter(|backtrace| backtrace.status() == BacktraceStatus::Captured),
+            Self::Context(_) => None,
+        };
+        Self::Context(Box::new(ContextError {
+            context: context.into(),
+            location: Location::caller(),
+            source: self,
+            backtrace,
+        }))
+    }
+
+    /// Returns the error that caused the chain of contexts.
+    #[must_use]
+    pub fn root_cause(&self) -> &ComputeError {
+        match self {
+            Self::ComputeError(err) => err,
+            Self::Context(err) => err.source.root_cause(),
+        }
+    }
+
+    /// Returns the backtrace captured with the innermost context, if any.
+    #[must_use]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match self {
+            Self::ComputeError(_) => None,
+            Self::Context(err) => err.source.backtrace().or(err.backtrace.as_ref()),
+        }
+    }
+
+    /// Formats the whole chain from the outermost context to the root cause on one line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_error::ComputeError::InvalidInput;
+    /// use qlab_error::{Context, QLabResult};
+    ///
+    /// let result: QLabResult<()> = Err(InvalidInput("negative maturity".into()).into());
+    /// let err = result.context("bootstrapping EUR curve pillar 7").unwrap_err();
+    /// assert!(err.report().starts_with("bootstrapping EUR curve pillar 7 (at "));
+    /// assert!(err.report().ends_with(": Invalid inputs are passed by: negative maturity"));
+    /// ```
+    #[must_use]
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = self.source();
+        while let Some(err) = source {
+            report.push_str(": ");
+            report.push_str(&err.to_string());
+            source = err.source();
+        }
+        report
+    }
+}
+
+/// An error together with a description of what was being done when it occurred.
+#[derive(Debug)]
+pub struct ContextError {
+    context: ErrString,
+    location: &'static Location<'static>,
+    source: QLabError,
+    backtrace: Option<Backtrace>,
+}
+
+impl ContextError {
+    #[must_use]
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    /// Returns the source location at which the context was attached.
+    #[must_use]
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl Display for ContextError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at {})", self.context, self.location)
+    }
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Attaches context to the error of a result.
+pub trait Context<T> {
+    /// Wraps the error, if any, with `context` and the caller's source location.
+    ///
+    /// # Errors
+    /// Returns the wrapped error.
+    #[track_caller]
+    fn context(self, context: impl Into<ErrString>) -> QLabResult<T>;
+
+    /// Wraps the error, if any, with the context built by `f`, which is only called on error.
+    ///
+    /// # Errors
+    /// Returns the wrapped error.
+    #[track_caller]
+    fn with_context<C: Into<ErrString>>(self, f: impl FnOnce() -> C) -> QLabResult<T>;
+}
+
+impl<T, E: Into<QLabError>> Context<T> for Result<T, E> {
+    #[track_caller]
+    fn context(self, context: impl Into<ErrString>) -> QLabResult<T> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(err.into().context(context)),
+        }
+    }
+
+    #[track_caller]
+    fn with_context<C: Into<ErrString>>(self, f: impl FnOnce() -> C) -> QLabResult<T> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(err.into().context(f())),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -73,3 +212,30 @@ impl<T> From<InterpolationError<T>> for QLabError {
 }
 
 pub type QLabResult<T> = Result<T, QLabError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interpolate() -> QLabResult<f64> {
+        Err(InterpolationError::OutOfUpperBound(2.0).into())
+    }
+
+    #[test]
+    fn test_context() {
+        let err = interpolate()
+            .context("interpolating the 30Y pillar")
+            .with_context(|| format!("bootstrapping {} curve", "EUR"))
+            .unwrap_err();
+        assert!(matches!(err.root_cause(), ComputeError::InterpolationError));
+        let QLabError::Context(outer) = &err else {
+            panic!("the error must carry a context");
+        };
+        assert_eq!(outer.context(), "bootstrapping EUR curve");
+        assert_eq!(outer.location().file(), file!());
+        let report = err.report();
+        assert!(report.starts_with("bootstrapping EUR curve (at "));
+        assert!(report.contains(": interpolating the 30Y pillar (at "));
+        assert!(report.ends_with(": interpolation failed"));
+    }
+}
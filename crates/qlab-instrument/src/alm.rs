@@ -0,0 +1,508 @@
+use crate::cash_flow_engine::CashFlowValuation;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_termstructure::report::Table;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use qlab_time::period::tenor::Tenor;
+use qlab_time::period::Period;
+
+/// Time buckets measured from a valuation date by increasing tenors, as in "0-1M", "1M-3M", ..., "10Y+".
+///
+/// A date falls in the first bucket whose end is on or after it; dates after the last end fall in an open last bucket.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::alm::TimeBuckets;
+/// use qlab_time::date::Date;
+///
+/// let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let buckets = TimeBuckets::new(valuation_date, &["3M".parse().unwrap(), "1Y".parse().unwrap()]).unwrap();
+/// assert_eq!(buckets.labels(), ["0-3M", "3M-1Y", "1Y+"]);
+/// assert_eq!(buckets.index(Date::from_ymd(2024, 6, 28).unwrap()), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeBuckets {
+    valuation_date: Date,
+    tenors: Vec<Tenor>,
+    ends: Vec<Date>,
+}
+
+impl TimeBuckets {
+    /// # Errors
+    /// Returns an `InvalidInput` error if no tenor is given or the bucket ends do not increase after `valuation_date`.
+    pub fn new(valuation_date: Date, tenors: &[Tenor]) -> QLabResult<Self> {
+        if tenors.is_empty() {
+            return Err(InvalidInput("at least one tenor is required".into()).into());
+        }
+        let ends = tenors
+            .iter()
+            .map(|tenor| {
+                tenor.checked_add(valuation_date).ok_or_else(|| {
+                    InvalidInput(format!("{tenor} overflows from {valuation_date}").into()).into()
+                })
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        if ends[0] <= valuation_date || ends.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(InvalidInput(
+                format!("bucket ends must increase after {valuation_date}").into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            valuation_date,
+            tenors: tenors.to_vec(),
+            ends,
+        })
+    }
+
+    #[must_use]
+    pub fn valuation_date(&self) -> Date {
+        self.valuation_date
+    }
+
+    /// Returns the number of buckets, one more than the number of tenors.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ends.len() + 1
+    }
+
+    /// Always `false`, since the open last bucket exists.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the index of the bucket containing `date`; dates on or before the valuation date fall in the first.
+    #[must_use]
+    pub fn index(&self, date: Date) -> usize {
+        self.ends.partition_point(|&end| end < date)
+    }
+
+    #[must_use]
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = Vec::with_capacity(self.len());
+        let mut previous = "0".to_string();
+        for tenor in &self.tenors {
+            labels.push(format!("{previous}-{tenor}"));
+            previous = tenor.to_string();
+        }
+        labels.push(format!("{previous}+"));
+        labels
+    }
+}
+
+/// The cash flows of one currency falling in a time bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LadderBucket<V> {
+    pub label: String,
+    pub amount: V,
+    pub present_value: V,
+    /// The amount of the flows in this and the earlier buckets.
+    pub cumulative_amount: V,
+}
+
+/// Builds the maturity ladder of the flows of `valuation` in `currency`, i.e. their undiscounted
+/// and discounted amounts in each bucket.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::alm::{maturity_ladder, TimeBuckets};
+/// use qlab_instrument::cash_flow_engine::{CashFlowValuation, FlowValue};
+/// use qlab_time::date::Date;
+///
+/// let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let flow = |year, amount: f64| FlowValue {
+///     payment_date: Date::from_ymd(year, 1, 2).unwrap(),
+///     amount,
+///     currency: "USD".to_string(),
+///     discount_factor: 1.0,
+///     survival_probability: 1.0,
+///     present_value: amount,
+/// };
+/// let valuation = CashFlowValuation { flows: vec![flow(2025, 5.0), flow(2026, 105.0)] };
+/// let buckets = TimeBuckets::new(valuation_date, &["1Y".parse().unwrap()]).unwrap();
+/// let ladder = maturity_ladder(&valuation, "usd", &buckets);
+/// assert!((ladder[1].amount - 105.0).abs() < 1e-12);
+/// assert!((ladder[1].cumulative_amount - 110.0).abs() < 1e-12);
+/// ```
+#[must_use]
+pub fn maturity_ladder<V: Value>(
+    valuation: &CashFlowValuation<V>,
+    currency: &str,
+    buckets: &TimeBuckets,
+) -> Vec<LadderBucket<V>> {
+    let currency = currency.to_uppercase();
+    let mut ladder: Vec<_> = buckets
+        .labels()
+        .into_iter()
+        .map(|label| LadderBucket {
+            label,
+            amount: V::zero(),
+            present_value: V::zero(),
+            cumulative_amount: V::zero(),
+        })
+        .collect();
+    for flow in valuation
+        .flows
+        .iter()
+        .filter(|flow| flow.currency == currency)
+    {
+        let bucket = &mut ladder[buckets.index(flow.payment_date)];
+        bucket.amount += flow.amount;
+        bucket.present_value += flow.present_value;
+    }
+    let mut cumulative = V::zero();
+    for bucket in &mut ladder {
+        cumulative += bucket.amount;
+        bucket.cumulative_amount = cumulative;
+    }
+    ladder
+}
+
+/// A notional whose rate is reset on a date, positive for an asset and negative for a liability.
+///
+/// A fixed rate instrument reprices at maturity and a floating rate one at its next reset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RepricingPosition<V> {
+    pub repricing_date: Date,
+    pub notional: V,
+}
+
+/// The repricing assets and liabilities falling in a time bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapBucket<V> {
+    pub label: String,
+    pub assets: V,
+    /// The notional of the liabilities as a positive amount.
+    pub liabilities: V,
+    /// The assets less the liabilities.
+    pub gap: V,
+    pub cumulative_gap: V,
+}
+
+/// Builds the repricing gap of `positions`, i.e. the notionals of the assets and liabilities whose rates
+/// are reset in each bucket.
+#[must_use]
+pub fn repricing_gap<V: Value>(
+    positions: &[RepricingPosition<V>],
+    buckets: &TimeBuckets,
+) -> Vec<GapBucket<V>> {
+    let mut gaps: Vec<_> = buckets
+        .labels()
+        .into_iter()
+        .map(|label| GapBucket {
+            label,
+            assets: V::zero(),
+            liabilities: V::zero(),
+            gap: V::zero(),
+            cumulative_gap: V::zero(),
+        })
+        .collect();
+    for position in positions {
+        let bucket = &mut gaps[buckets.index(position.repricing_date)];
+        if position.notional >= V::zero() {
+            bucket.assets += position.notional;
+        } else {
+            bucket.liabilities -= position.notional;
+        }
+    }
+    let mut cumulative = V::zero();
+    for bucket in &mut gaps {
+        bucket.gap = bucket.assets - bucket.liabilities;
+        cumulative += bucket.gap;
+        bucket.cumulative_gap = cumulative;
+    }
+    gaps
+}
+
+/// The interest rate sensitivity of the flows falling in a time bucket.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DurationBucket<V> {
+    pub label: String,
+    pub present_value: V,
+    /// The sum of the present values times their year fractions from the valuation date, i.e. the loss
+    /// for a unit parallel rise of continuously compounded zero rates.
+    pub dollar_duration: V,
+}
+
+/// Builds the duration buckets of the flows of `valuation` in `currency`, measuring year fractions with `D`.
+///
+/// # Errors
+/// Returns an error if a day count fraction cannot be calculated.
+pub fn duration_buckets<V: Value, D: DayCount>(
+    valuation: &CashFlowValuation<V>,
+    currency: &str,
+    buckets: &TimeBuckets,
+) -> QLabResult<Vec<DurationBucket<V>>> {
+    let currency = currency.to_uppercase();
+    let mut durations: Vec<_> = buckets
+        .labels()
+        .into_iter()
+        .map(|label| DurationBucket {
+            label,
+            present_value: V::zero(),
+            dollar_duration: V::zero(),
+        })
+        .collect();
+    for flow in valuation
+        .flows
+        .iter()
+        .filter(|flow| flow.currency == currency)
+    {
+        let t: V = D::calculate_day_count_fraction(buckets.valuation_date, flow.payment_date)?;
+        let bucket = &mut durations[buckets.index(flow.payment_date)];
+        bucket.present_value += flow.present_value;
+        bucket.dollar_duration += flow.present_value * t;
+    }
+    Ok(durations)
+}
+
+/// Builds a table of a maturity ladder; amounts are printed with 2 decimals.
+///
+/// # Errors
+/// Returns an error if the report cannot be assembled.
+pub fn ladder_report<V: Value>(ladder: &[LadderBucket<V>]) -> QLabResult<Table> {
+    let mut table = Table::new(&["bucket", "amount", "present value", "cumulative amount"]);
+    for bucket in ladder {
+        table.push_row(vec![
+            bucket.label.clone(),
+            format!("{:.2?}", bucket.amount),
+            format!("{:.2?}", bucket.present_value),
+            format!("{:.2?}", bucket.cumulative_amount),
+        ])?;
+    }
+    Ok(table)
+}
+
+/// Builds a table of a repricing gap; amounts are printed with 2 decimals.
+///
+/// # Errors
+/// Returns an error if the report cannot be assembled.
+pub fn gap_report<V: Value>(gaps: &[GapBucket<V>]) -> QLabResult<Table> {
+    let mut table = Table::new(&["bucket", "assets", "liabilities", "gap", "cumulative gap"]);
+    for bucket in gaps {
+        table.push_row(vec![
+            bucket.label.clone(),
+            format!("{:.2?}", bucket.assets),
+            format!("{:.2?}", bucket.liabilities),
+            format!("{:.2?}", bucket.gap),
+            format!("{:.2?}", bucket.cumulative_gap),
+        ])?;
+    }
+    Ok(table)
+}
+
+/// Builds a table of duration buckets; amounts are printed with 2 decimals.
+///
+/// # Errors
+/// Returns an error if the report cannot be assembled.
+pub fn duration_report<V: Value>(durations: &[DurationBucket<V>]) -> QLabResult<Table> {
+    let mut table = Table::new(&["bucket", "present value", "dollar duration"]);
+    for bucket in durations {
+        table.push_row(vec![
+            bucket.label.clone(),
+            format!("{:.2?}", bucket.present_value),
+            format!("{:.2?}", bucket.dollar_duration),
+        ])?;
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bond::Bond;
+    use crate::cash_flow_engine::{value, CashFlow, FlowValue, MarketSnapshot};
+    use crate::swap::{Swap, SwapDirection};
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_termstructure::discount_curves::DiscountCurves;
+    use qlab_termstructure::yield_curve::YieldCurve;
+    use qlab_time::date_rolling::DateRolling;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::frequency::Frequency;
+
+    #[test]
+    fn test_reports() {
+        let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let buckets = TimeBuckets::new(
+            valuation_date,
+            &["6M".parse().unwrap(), "2Y".parse().unwrap()],
+        )
+        .unwrap();
+        assert!(TimeBuckets::new(
+            valuation_date,
+            &["1Y".parse().unwrap(), "6M".parse().unwrap()]
+        )
+        .is_err());
+
+        let bond = Bond::new(
+            "bond",
+            Date::from_ymd(2023, 7, 1).unwrap(),
+            Date::from_ymd(2024, 1, 1).unwrap(),
+            Date::from_ymd(2026, 7, 1).unwrap(),
+            Date::from_ymd(2027, 1, 1).unwrap(),
+            Frequency::SA,
+            &WeekendsOnly,
+            DateRolling::Following,
+            0.04,
+            100.0,
+        )
+        .unwrap();
+        let curve: YieldCurve<Act365, Linear<f64>> = YieldCurve::new(
+            valuation_date,
+            &[
+                Date::from_ymd(2024, 1, 3).unwrap(),
+                Date::from_ymd(2034, 1, 2).unwrap(),
+            ],
+            &[0.04, 0.04],
+        )
+        .unwrap();
+        let curves = DiscountCurves::new(curve);
+        let flows = bond
+            .cash_flows()
+            .into_iter()
+            .map(|(payment_date, amount)| CashFlow {
+                payment_date,
+                amount,
+                currency: "USD",
+                credit_curve: None,
+            });
+        let valuation = value(
+            flows,
+            &MarketSnapshot {
+                valuation_date,
+                discount_curves: &curves,
+            },
+        )
+        .unwrap();
+        let ladder = maturity_ladder(&valuation, "USD", &buckets);
+        for (bucket, amount) in ladder.iter().zip([2.0, 6.0, 104.0]) {
+            assert!((bucket.amount - amount).abs() < 1e-12);
+        }
+        assert!((ladder[2].cumulative_amount - 112.0).abs() < 1e-12);
+        assert_eq!(ladder_report(&ladder).unwrap().rows().len(), 3);
+
+        let durations = duration_buckets::<_, Act365>(&valuation, "USD", &buckets).unwrap();
+        let present_value: f64 = durations.iter().map(|bucket| bucket.present_value).sum();
+        assert!((present_value - valuation.present_value("USD")).abs() < 1e-12);
+        assert!(durations[2].dollar_duration > 2.5 * durations[2].present_value);
+
+        // hedging the bond with a payer swap offsets its fixed rate and reprices within six months
+        let swap = Swap::new::<Act360, Act360>(
+            "swap",
+            "usd",
+            "SOFR",
+            SwapDirection::Payer,
+            Date::from_ymd(2024, 1, 4).unwrap(),
+            Date::from_ymd(2027, 1, 4).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.04,
+            100.0,
+        )
+        .unwrap();
+        let mut positions = swap.repricing_positions(valuation_date);
+        positions.push(RepricingPosition {
+            repricing_date: bond.maturity_date(),
+            notional: bond.face_value(),
+        });
+        let gaps = repricing_gap(&positions, &buckets);
+        assert!((gaps[0].assets - 100.0).abs() < 1e-12);
+        assert!((gaps[2].liabilities - 100.0).abs() < 1e-12);
+        assert!((gaps[2].assets - 100.0).abs() < 1e-12);
+        assert!((gaps[2].cumulative_gap - 100.0).abs() < 1e-12);
+        assert_eq!(gap_report(&gaps).unwrap().rows().len(), 3);
+    }
+
+    #[test]
+    fn test_time_buckets_edges() {
+        let valuation_date = Date::from_ymd(2024, 1, 31).unwrap();
+        assert!(TimeBuckets::new(valuation_date, &[]).is_err());
+        assert!(TimeBuckets::new(valuation_date, &["0M".parse().unwrap()]).is_err());
+        assert!(TimeBuckets::new(
+            valuation_date,
+            &["1M".parse().unwrap(), "1M".parse().unwrap()]
+        )
+        .is_err());
+
+        let buckets = TimeBuckets::new(
+            valuation_date,
+            &["1M".parse().unwrap(), "1Y".parse().unwrap()],
+        )
+        .unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert!(!buckets.is_empty());
+        assert_eq!(buckets.labels(), ["0-1M", "1M-1Y", "1Y+"]);
+        // a bucket includes its end, and the month end is kept
+        let one_month = Date::from_ymd(2024, 2, 29).unwrap();
+        assert_eq!(buckets.index(one_month), 0);
+        assert_eq!(buckets.index(Date::from_ymd(2024, 3, 1).unwrap()), 1);
+        assert_eq!(buckets.index(Date::from_ymd(2023, 1, 1).unwrap()), 0);
+        assert_eq!(buckets.index(Date::from_ymd(2025, 1, 31).unwrap()), 1);
+        assert_eq!(buckets.index(Date::from_ymd(2060, 1, 1).unwrap()), 2);
+    }
+
+    #[test]
+    fn test_ladder_edges() {
+        let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let buckets = TimeBuckets::new(valuation_date, &["1Y".parse().unwrap()]).unwrap();
+        let flow = |payment_date: Date, currency: &str, amount: f64| FlowValue {
+            payment_date,
+            amount,
+            currency: currency.to_string(),
+            discount_factor: 1.0,
+            survival_probability: 1.0,
+            present_value: amount,
+        };
+        let valuation = CashFlowValuation {
+            flows: vec![
+                flow(valuation_date, "USD", 1.0),
+                flow(Date::from_ymd(2024, 6, 3).unwrap(), "EUR", 10.0),
+                flow(Date::from_ymd(2026, 1, 2).unwrap(), "USD", -4.0),
+            ],
+        };
+
+        // other currencies are left out and the currency is matched in upper case
+        let ladder = maturity_ladder(&valuation, "usd", &buckets);
+        assert!((ladder[0].amount - 1.0).abs() < f64::EPSILON);
+        assert!((ladder[1].amount + 4.0).abs() < f64::EPSILON);
+        assert!((ladder[1].cumulative_amount + 3.0).abs() < f64::EPSILON);
+        let ladder = maturity_ladder(&valuation, "JPY", &buckets);
+        assert!(ladder.iter().all(|bucket| bucket.amount == 0.0));
+        assert_eq!(ladder.len(), 2);
+
+        // a flow on the valuation date has no duration
+        let durations = duration_buckets::<_, Act365>(&valuation, "USD", &buckets).unwrap();
+        assert!(durations[0].dollar_duration.abs() < f64::EPSILON);
+        assert!(durations[1].dollar_duration < -7.9);
+        assert_eq!(duration_report(&durations).unwrap().rows().len(), 2);
+        assert!(duration_buckets::<f64, Act365>(
+            &CashFlowValuation { flows: Vec::new() },
+            "USD",
+            &buckets
+        )
+        .unwrap()
+        .iter()
+        .all(|bucket| bucket.present_value == 0.0));
+
+        let gaps = repricing_gap::<f64>(&[], &buckets);
+        assert!(gaps.iter().all(|bucket| bucket.cumulative_gap == 0.0));
+        let gaps = repricing_gap::<f64>(
+            &[RepricingPosition {
+                repricing_date: valuation_date,
+                notional: -50.0,
+            }],
+            &buckets,
+        );
+        assert!((gaps[0].liabilities - 50.0).abs() < f64::EPSILON);
+        assert!((gaps[1].cumulative_gap + 50.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::fixing_store::FixingStore;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use qlab_time::observation::{ObservationSchedule, TimeGrid};
+
+/// Whether an [`AsianOption`] pays the average above or below the strike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AsianOptionType {
+    Call,
+    Put,
+}
+
+/// An option paying at expiry the arithmetic average of its underlying over the dates of an
+/// [`ObservationSchedule`] less the strike for a call, or the strike less the average for a put.
+///
+/// The option builds the time grid of a simulation from its own schedule, so the simulated observations
+/// are always points of the grid; the observations on or before the valuation date are past fixings.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::asian_option::{AsianOption, AsianOptionType};
+/// use qlab_instrument::fixing_store::FixingStore;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+/// use qlab_time::observation::ObservationSchedule;
+///
+/// let schedule = ObservationSchedule::new(&[
+///     Date::from_ymd(2024, 3, 1).unwrap(),
+///     Date::from_ymd(2024, 6, 3).unwrap(),
+///     Date::from_ymd(2024, 9, 2).unwrap(),
+/// ])
+/// .unwrap();
+/// let option = AsianOption::new("SPX", AsianOptionType::Call, 100.0_f64, schedule);
+/// let valuation_date = Date::from_ymd(2024, 4, 1).unwrap();
+/// let fixings = FixingStore::new().with_fixing("SPX", Date::from_ymd(2024, 3, 1).unwrap(), 103.0);
+/// let grid = option.time_grid::<Act365>(valuation_date, 1.0 / 12.0).unwrap();
+/// // a path with the underlying at 100 at every step of the grid
+/// let path = vec![100.0; grid.step_times().len()];
+/// let payoff = option.payoff(valuation_date, &grid, &path, &fixings).unwrap();
+/// assert!((payoff - 1.0).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsianOption<V> {
+    underlying: String,
+    option_type: AsianOptionType,
+    strike: V,
+    schedule: ObservationSchedule,
+}
+
+impl<V: Value> AsianOption<V> {
+    #[must_use]
+    pub fn new(
+        underlying: &str,
+        option_type: AsianOptionType,
+        strike: V,
+        schedule: ObservationSchedule,
+    ) -> Self {
+        Self {
+            underlying: underlying.to_string(),
+            option_type,
+            strike,
+            schedule,
+        }
+    }
+
+    /// Returns the index whose fixings are the past observations.
+    #[must_use]
+    pub fn underlying(&self) -> &str {
+        &self.underlying
+    }
+
+    #[must_use]
+    pub fn option_type(&self) -> AsianOptionType {
+        self.option_type
+    }
+
+    #[must_use]
+    pub fn strike(&self) -> V {
+        self.strike
+    }
+
+    #[must_use]
+    pub fn schedule(&self) -> &ObservationSchedule {
+        &self.schedule
+    }
+
+    /// Builds the time grid of a simulation from `valuation_date` over the remaining observation dates,
+    /// see [`ObservationSchedule::time_grid`].
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`ObservationSchedule::time_grid`].
+    pub fn time_grid<DC: DayCount>(
+        &self,
+        valuation_date: Date,
+        max_step: V,
+    ) -> QLabResult<TimeGrid<V>> {
+        self.schedule.time_grid::<V, DC>(valuation_date, max_step)
+    }
+
+    /// Calculates the payoff of a simulated `path`, which holds the underlying at each of the
+    /// [`TimeGrid::step_times`] of `grid`, with the observations on or before `valuation_date` taken from `fixings`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `grid` was not built for `valuation_date` from the schedule,
+    /// if `path` does not match the steps of `grid`, or if a past fixing is missing.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn payoff(
+        &self,
+        valuation_date: Date,
+        grid: &TimeGrid<V>,
+        path: &[V],
+        fixings: &FixingStore<V>,
+    ) -> QLabResult<V> {
+        let remaining = self.schedule.remaining(valuation_date).len();
+        if grid.observation_indices().len() != remaining {
+            return Err(InvalidInput(
+                format!(
+                    "the grid has {} observations but {remaining} remain after {valuation_date}",
+                    grid.observation_indices().len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        if path.len() != grid.step_times().len() {
+            return Err(InvalidInput(
+                format!(
+                    "a path needs {} values, not {}",
+                    grid.step_times().len(),
+                    path.len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        let past = &self.schedule.dates()[..self.schedule.len() - remaining];
+        let mut sum = V::zero();
+        for &date in past {
+            sum += fixings.fixing(&self.underlying, date).ok_or_else(|| {
+                InvalidInput(format!("no fixing of {} on {date}", self.underlying).into())
+            })?;
+        }
+        for step in grid.observation_steps() {
+            sum += path[step];
+        }
+        let average = sum / V::from_usize(self.schedule.len()).unwrap();
+        Ok(match self.option_type {
+            AsianOptionType::Call => (average - self.strike).max(V::zero()),
+            AsianOptionType::Put => (self.strike - average).max(V::zero()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_model::monte_carlo::SimulationConfig;
+    use qlab_model::multi_asset::{CorrelatedProcess, Marginal};
+    use qlab_time::date_rolling::DateRolling;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::frequency::Frequency;
+
+    #[test]
+    fn test_put_call_parity_on_grid() {
+        let trade_date = Date::from_ymd(2024, 1, 31).unwrap();
+        let schedule = ObservationSchedule::from_frequency(
+            trade_date,
+            Date::from_ymd(2025, 1, 31).unwrap(),
+            Frequency::M,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+        )
+        .unwrap();
+        let valuation_date = schedule.dates()[2];
+        let fixings = schedule.dates()[..3]
+            .iter()
+            .fold(FixingStore::new(), |store, &date| {
+                store.with_fixing("SPX", date, 90.0)
+            });
+        let call = AsianOption::new("SPX", AsianOptionType::Call, 100.0, schedule.clone());
+        let put = AsianOption::new("SPX", AsianOptionType::Put, 100.0, schedule);
+        let grid = call
+            .time_grid::<Act365>(valuation_date, 7.0 / 365.0)
+            .unwrap();
+        assert_eq!(grid.observation_indices().len(), 9);
+        let marginals = [Marginal::Lognormal {
+            spot: 100.0,
+            drift: 0.0,
+            volatility: 0.2,
+        }];
+        let process = CorrelatedProcess::on_time_grid(&marginals, &[vec![1.0]], &grid).unwrap();
+        let config = SimulationConfig::new(5, 20_000).with_antithetic(true);
+        let price = |option: &AsianOption<f64>| {
+            process
+                .simulate(&config, |paths| {
+                    option
+                        .payoff(valuation_date, &grid, &paths[0], &fixings)
+                        .unwrap()
+                })
+                .unwrap()
+        };
+        let (call_price, put_price) = (price(&call), price(&put));
+        // the driftless underlying averages to 100 over the remaining nine observations
+        let expected = (3.0 * 90.0 + 9.0 * 100.0) / 12.0 - 100.0;
+        let error = call_price.std_error() + put_price.std_error();
+        assert!((call_price.mean() - put_price.mean() - expected).abs() < 4.0 * error);
+        assert!(call_price.mean() > 0.0);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let dates = [
+            Date::from_ymd(2024, 3, 1).unwrap(),
+            Date::from_ymd(2024, 6, 3).unwrap(),
+        ];
+        let option = AsianOption::new(
+            "SPX",
+            AsianOptionType::Put,
+            100.0_f64,
+            ObservationSchedule::new(&dates).unwrap(),
+        );
+        let valuation_date = Date::from_ymd(2024, 4, 1).unwrap();
+        let grid = option.time_grid::<Act365>(valuation_date, 1.0).unwrap();
+        let path = vec![95.0; grid.step_times().len()];
+        // the fixing on 1 March is missing
+        assert!(option
+            .payoff(valuation_date, &grid, &path, &FixingStore::new())
+            .is_err());
+        let fixings = FixingStore::new().with_fixing("SPX", dates[0], 97.0);
+        let payoff = option
+            .payoff(valuation_date, &grid, &path, &fixings)
+            .unwrap();
+        assert!((payoff - 4.0).abs() < 1e-12);
+        assert!(option
+            .payoff(valuation_date, &grid, &path[1..], &fixings)
+            .is_err());
+        // a grid built from an earlier valuation date simulates the past observation too
+        let stale = option
+            .time_grid::<Act365>(Date::from_ymd(2024, 1, 2).unwrap(), 1.0)
+            .unwrap();
+        assert!(option
+            .payoff(valuation_date, &stale, &[95.0; 2], &fixings)
+            .is_err());
+    }
+}
@@ -1,32 +1,75 @@
+use crate::csa::{select_discount_curve, Csa};
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
 use qlab_error::QLabResult;
 use qlab_math::interpolation::Interpolator;
 use qlab_math::value::Value;
+use qlab_termstructure::discount_curves::DiscountCurves;
+use qlab_termstructure::repo_curve::RepoCurve;
+use qlab_termstructure::report::Table;
 use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::calendar::Calendar;
 use qlab_time::date::Date;
+use qlab_time::date_rolling::DateRolling;
+use qlab_time::day_count::nl_365::Nl365;
+use qlab_time::day_count::thirty_360_us::Thirty360Us;
 use qlab_time::day_count::DayCount;
 use qlab_time::frequency::Frequency;
 use qlab_time::period::months::Months;
-use std::cmp::Ordering;
+use qlab_time::schedule::{regular_periods, AccrualSchedule, SchedulePeriod};
 
 struct BondCashFlow<V> {
+    /// The unadjusted end of the coupon period, on which accrual is based.
     due_date: Date,
+    /// The due date adjusted to a business day, on which the cash flow is paid.
     payment_date: Date,
     payment_amount: V,
 }
 
+/// How the accrued interest of a bond is calculated, so that it reproduces the figures published
+/// by the market the bond trades in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccruedInterestConvention {
+    /// The coupon of each period accrues linearly in calendar days over the period (ACT/ACT ICMA), unrounded.
+    #[default]
+    Generic,
+    /// The US street convention of corporate and agency bonds: the coupon of each period accrues by the share
+    /// of the period elapsed, both counted on the US 30/360 basis, unrounded.
+    UsStreet,
+    /// Japanese government bonds: the annual coupon accrues over the actual days, not counting 29 February,
+    /// on a 365-day year, and is truncated to 7 decimals per 100 of face value.
+    Japanese,
+    /// Italian BTPs: the coupon of each period accrues as in [`AccruedInterestConvention::Generic`]
+    /// and is rounded to 5 decimals per 100 of face value.
+    ItalianBtp,
+}
+
 /// A generic struct representing a bond.
 ///
 /// # Fields
 ///
 /// * `bond_id`: A unique identifier for the bond.
 /// * `bond_cash_flows`: A vector of bond cash flows.
+/// * `accruals`: The coupon period of each cash flow, accruing from the issue date or the previous due date.
+/// * `issue_date`: The date from which the first coupon accrues.
+/// * `face_value`: The principal repaid at maturity.
+/// * `coupon_rate`: The annual coupon rate.
+/// * `accrued_interest_convention`: How the accrued interest is calculated.
+/// * `csa`: The collateral agreement of the trade, if any.
 ///
 /// # Generic Parameters
 ///
 /// * `V`: The type of value associated with each bond cash flow.
+#[allow(clippy::struct_field_names)]
 pub struct Bond<V> {
     bond_id: String,
     bond_cash_flows: Vec<BondCashFlow<V>>,
+    /// The coupon periods of the cash flows, with their fractions on the US 30/360 basis cached.
+    accruals: AccrualSchedule<V>,
+    issue_date: Date,
+    face_value: V,
+    coupon_rate: V,
+    accrued_interest_convention: AccruedInterestConvention,
+    csa: Option<Csa<V>>,
 }
 
 impl<V: Value> Bond<V> {
@@ -43,6 +86,8 @@ impl<V: Value> Bond<V> {
     /// * `penultimate_coupon_date` - The date of the penultimate coupon payment.
     /// * `maturity_date` - The maturity date of the bond.
     /// * `coupon_frequency` - The frequency at which the bond pays coupon payments (e.g. Annually, Semi-Annually, Quarterly).
+    /// * `calendar` - The calendar whose business days the payment dates are adjusted to.
+    /// * `rolling` - The convention by which due dates falling on holidays are adjusted.
     /// * `coupon_rate` - The coupon rate of the bond.
     /// * `face_value` - The face value or principal amount of the bond.
     ///
@@ -61,6 +106,8 @@ impl<V: Value> Bond<V> {
         penultimate_coupon_date: Date,
         maturity_date: Date,
         coupon_frequency: Frequency,
+        calendar: &impl Calendar,
+        rolling: DateRolling,
         coupon_rate: V,
         face_value: V,
     ) -> Option<Self> {
@@ -71,7 +118,7 @@ impl<V: Value> Bond<V> {
         let mut bond_cash_flows = Vec::new();
 
         while regular_due_date <= penultimate_coupon_date {
-            let payment_date = regular_due_date.weekend_roll()?;
+            let payment_date = calendar.adjust(regular_due_date, rolling)?;
             bond_cash_flows.push(BondCashFlow {
                 due_date: regular_due_date,
                 payment_date,
@@ -83,7 +130,7 @@ impl<V: Value> Bond<V> {
         Self::first_cash_flow(
             issue_date,
             first_coupon_date,
-            months_in_regular_coupon_period,
+            coupon_frequency,
             regular_coupon_payment,
             &mut bond_cash_flows,
         )?;
@@ -91,39 +138,75 @@ impl<V: Value> Bond<V> {
             penultimate_coupon_date,
             maturity_date,
             face_value,
-            months_in_regular_coupon_period,
+            coupon_frequency,
             regular_coupon_payment,
+            calendar,
+            rolling,
         )?;
         bond_cash_flows.push(final_cash_flow);
+        let accruals = Self::accrual_schedule(
+            issue_date,
+            face_value,
+            regular_coupon_payment,
+            &bond_cash_flows,
+        )
+        .ok()?;
         Some(Self {
             bond_id: bond_id.to_string(),
             bond_cash_flows,
+            accruals,
+            issue_date,
+            face_value,
+            coupon_rate,
+            accrued_interest_convention: AccruedInterestConvention::default(),
+            csa: None,
         })
     }
 
+    /// Builds the coupon period of each cash flow from the unadjusted due dates, on which accrual is based,
+    /// with the US 30/360 fractions which [`AccruedInterestConvention::UsStreet`] divides by.
+    fn accrual_schedule(
+        issue_date: Date,
+        face_value: V,
+        regular_coupon_payment: V,
+        bond_cash_flows: &[BondCashFlow<V>],
+    ) -> QLabResult<AccrualSchedule<V>> {
+        let last = bond_cash_flows.len() - 1;
+        let mut accrual_start = issue_date;
+        let periods: Vec<_> = bond_cash_flows
+            .iter()
+            .enumerate()
+            .map(|(i, cash_flow)| {
+                let coupon = if i == last {
+                    cash_flow.payment_amount - face_value
+                } else {
+                    cash_flow.payment_amount
+                };
+                let period = SchedulePeriod {
+                    unadjusted_start: accrual_start,
+                    unadjusted_end: cash_flow.due_date,
+                    accrual_start,
+                    accrual_end: cash_flow.due_date,
+                    payment_date: cash_flow.payment_date,
+                    is_stub: coupon != regular_coupon_payment,
+                };
+                accrual_start = cash_flow.due_date;
+                period
+            })
+            .collect();
+        AccrualSchedule::new::<Thirty360Us>(&periods)
+    }
+
     fn first_cash_flow(
         issue_date: Date,
         first_coupon_date: Date,
-        months_in_regular_coupon_period: Months,
+        coupon_frequency: Frequency,
         regular_coupon_payment: V,
         bond_cash_flows: &mut [BondCashFlow<V>],
     ) -> Option<()> {
-        let first_prior = first_coupon_date.checked_sub_months(months_in_regular_coupon_period)?;
-        match first_prior.cmp(&issue_date) {
-            Ordering::Less => {
-                let coupon_fraction = V::from_i64(first_coupon_date - issue_date)?
-                    / V::from_i64(first_coupon_date - first_prior)?;
-                bond_cash_flows[0].payment_amount *= coupon_fraction;
-            }
-            Ordering::Greater => {
-                let second_prior =
-                    first_prior.checked_sub_months(months_in_regular_coupon_period)?;
-                let coupon_fraction = V::from_i64(first_prior - issue_date)?
-                    / V::from_i64(first_prior - second_prior)?;
-                bond_cash_flows[0].payment_amount += coupon_fraction * regular_coupon_payment;
-            }
-            Ordering::Equal => {}
-        }
+        let coupon_fraction: V =
+            regular_periods(issue_date, first_coupon_date, coupon_frequency, true).ok()?;
+        bond_cash_flows[0].payment_amount = coupon_fraction * regular_coupon_payment;
         Some(())
     }
 
@@ -131,31 +214,22 @@ impl<V: Value> Bond<V> {
         penultimate_coupon_date: Date,
         maturity_date: Date,
         face_value: V,
-        months_in_regular_coupon_period: Months,
+        coupon_frequency: Frequency,
         regular_coupon_payment: V,
+        calendar: &impl Calendar,
+        rolling: DateRolling,
     ) -> Option<BondCashFlow<V>> {
-        let mut final_coupon = regular_coupon_payment;
-        let maturity_regular_date =
-            penultimate_coupon_date.checked_add_months(months_in_regular_coupon_period)?;
-        match maturity_date.cmp(&maturity_regular_date) {
-            Ordering::Less => {
-                let coupon_fraction = V::from_i64(maturity_date - penultimate_coupon_date)?
-                    / V::from_i64(maturity_regular_date - penultimate_coupon_date)?;
-                final_coupon *= coupon_fraction;
-            }
-            Ordering::Greater => {
-                let next_regular_date =
-                    maturity_regular_date.checked_add_months(months_in_regular_coupon_period)?;
-                let extra_coupon_fraction = V::from_i64(maturity_date - maturity_regular_date)?
-                    / V::from_i64(next_regular_date - maturity_regular_date)?;
-                final_coupon += extra_coupon_fraction * regular_coupon_payment;
-            }
-            Ordering::Equal => {}
-        }
+        let coupon_fraction: V = regular_periods(
+            penultimate_coupon_date,
+            maturity_date,
+            coupon_frequency,
+            false,
+        )
+        .ok()?;
         Some(BondCashFlow {
             due_date: maturity_date,
-            payment_date: maturity_date,
-            payment_amount: face_value + final_coupon,
+            payment_date: calendar.adjust(maturity_date, rolling)?,
+            payment_amount: face_value + coupon_fraction * regular_coupon_payment,
         })
     }
     /// Calculates the discounted value of the bond's cash flows.
@@ -194,8 +268,525 @@ impl<V: Value> Bond<V> {
         }
         Ok(pv)
     }
+
+    /// Calculates the coupon accrued from the start of the current coupon period up to `settle_date`
+    /// under the bond's [`AccruedInterestConvention`].
+    ///
+    /// Under the generic convention, the coupon of each period, including irregular first and last coupons,
+    /// accrues linearly in calendar days from the issue date or the previous due date to its due date.
+    /// No interest accrues before the issue date or after the maturity date.
+    ///
+    /// # Errors
+    /// Returns an error if the day counts cannot be cast into `V`.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn accrued_interest(&self, settle_date: Date) -> QLabResult<V> {
+        let last = self.bond_cash_flows.len() - 1;
+        for (i, (cash_flow, accrual)) in self
+            .bond_cash_flows
+            .iter()
+            .zip(self.accruals.periods())
+            .enumerate()
+        {
+            let (accrual_start, due_date) = accrual.period.accrual_dates(false);
+            if accrual_start <= settle_date && settle_date < due_date {
+                let coupon = if i == last {
+                    cash_flow.payment_amount - self.face_value
+                } else {
+                    cash_flow.payment_amount
+                };
+                let annual_coupon = self.coupon_rate * self.face_value;
+                let per_hundred = V::from_u8(100).unwrap() / self.face_value;
+                return match self.accrued_interest_convention {
+                    AccruedInterestConvention::Generic => {
+                        Self::period_accrual(coupon, accrual_start, settle_date, due_date)
+                    }
+                    AccruedInterestConvention::UsStreet => {
+                        let elapsed = Thirty360Us::calculate_day_count_fraction::<V>(
+                            accrual_start,
+                            settle_date,
+                        )?;
+                        Ok(coupon * elapsed / accrual.unadjusted_fraction)
+                    }
+                    AccruedInterestConvention::Japanese => {
+                        let accrued = annual_coupon
+                            * Nl365::calculate_day_count_fraction::<V>(accrual_start, settle_date)?;
+                        // the tolerance keeps figures which are exact in decimal from being truncated down
+                        let scale = V::from_f64(1e7).unwrap();
+                        let truncated =
+                            (accrued * per_hundred * scale + V::from_f64(1e-6).unwrap()).trunc()
+                                / scale;
+                        Ok(truncated / per_hundred)
+                    }
+                    AccruedInterestConvention::ItalianBtp => {
+                        let accrued =
+                            Self::period_accrual(coupon, accrual_start, settle_date, due_date)?;
+                        let scale = V::from_f64(1e5).unwrap();
+                        Ok((accrued * per_hundred * scale).round() / scale / per_hundred)
+                    }
+                };
+            }
+        }
+        Ok(V::zero())
+    }
+
+    /// Calculates the simple yield by which Japanese government bonds are quoted,
+    /// `(c + (100 - P) / T) / P` for the annual coupon `c` and the clean price `P` per 100 of face value
+    /// and the years `T` to maturity, counted on the NL/365 basis of the Japanese accrual.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `clean_price` is not positive or `settle_date` is not before maturity,
+    /// or an error if the day count fails.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_instrument::bond::Bond;
+    /// use qlab_time::date::Date;
+    /// use qlab_time::date_rolling::DateRolling;
+    /// use qlab_time::frequency::Frequency;
+    ///
+    /// let jgb = Bond::new(
+    ///     "JGB",
+    ///     Date::from_ymd(2023, 3, 20).unwrap(),
+    ///     Date::from_ymd(2023, 9, 20).unwrap(),
+    ///     Date::from_ymd(2032, 9, 20).unwrap(),
+    ///     Date::from_ymd(2033, 3, 20).unwrap(),
+    ///     Frequency::SA,
+    ///     &WeekendsOnly,
+    ///     DateRolling::Following,
+    ///     0.005_f64,
+    ///     100.0,
+    /// )
+    /// .unwrap();
+    /// // five years before maturity at 99, i.e. (0.5 + 1 / 5) / 99
+    /// let simple_yield = jgb.japanese_simple_yield(Date::from_ymd(2028, 3, 21).unwrap(), 99.0).unwrap();
+    /// assert!((simple_yield - 0.7 / 99.0).abs() < 1e-5);
+    /// ```
+    pub fn japanese_simple_yield(&self, settle_date: Date, clean_price: V) -> QLabResult<V> {
+        let maturity_date = self.maturity_date();
+        if clean_price <= V::zero() || settle_date >= maturity_date {
+            return Err(InvalidInput(
+                format!(
+                    "clean_price: {clean_price:?} must be positive and settle_date: {settle_date} precede the maturity: {maturity_date}"
+                )
+                .into(),
+            )
+            .into());
+        }
+        let hundred = V::from_u8(100).unwrap();
+        let years = Nl365::calculate_day_count_fraction::<V>(settle_date, maturity_date)?;
+        Ok((self.coupon_rate * hundred + (hundred - clean_price) / years) / clean_price)
+    }
+
+    fn period_accrual(
+        coupon: V,
+        accrual_start: Date,
+        settle_date: Date,
+        due_date: Date,
+    ) -> QLabResult<V> {
+        let elapsed = V::from_i64(settle_date - accrual_start)
+            .ok_or_else(|| CastNumberError(format!("{settle_date}").into()))?;
+        let period = V::from_i64(due_date - accrual_start)
+            .ok_or_else(|| CastNumberError(format!("{due_date}").into()))?;
+        Ok(coupon * elapsed / period)
+    }
+
+    /// Calculates the clean price, i.e. the discounted value of the cash flows less the accrued interest.
+    ///
+    /// # Errors
+    /// Error occurs if a discount factor calculation fails
+    pub fn clean_price<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        bond_settle_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        Ok(self.discounted_value(bond_settle_date, yield_curve)?
+            - self.accrued_interest(bond_settle_date)?)
+    }
+
+    /// Calculates the dirty price for delivery on `forward_date` of the bond bought on `bond_settle_date`,
+    /// e.g. the price of a bond forward or of the delivery into a bond future.
+    ///
+    /// The cash flows are discounted on the risk-free `yield_curve`, while the forward leg is financed on
+    /// `repo_curve`: the value on `bond_settle_date` less the coupons due up to `forward_date`, each carried at repo,
+    /// is grown at repo to `forward_date`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `forward_date` precedes `bond_settle_date`, or an error if a discount factor
+    /// calculation fails, e.g. if a curve settles after `bond_settle_date`.
+    pub fn forward_dirty_price<
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        RD: DayCount,
+        RI: Interpolator<Value = V>,
+    >(
+        &self,
+        bond_settle_date: Date,
+        forward_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+        repo_curve: &RepoCurve<RD, RI>,
+    ) -> QLabResult<V> {
+        if forward_date < bond_settle_date {
+            return Err(InvalidInput(
+                format!(
+                    "forward_date: {forward_date} precedes bond_settle_date: {bond_settle_date}"
+                )
+                .into(),
+            )
+            .into());
+        }
+        let mut value = self.discounted_value(bond_settle_date, yield_curve)?;
+        for cash_flow in &self.bond_cash_flows {
+            if bond_settle_date < cash_flow.due_date && cash_flow.due_date <= forward_date {
+                value -= cash_flow.payment_amount
+                    * repo_curve.discount_factor(bond_settle_date, cash_flow.payment_date)?;
+            }
+        }
+        Ok(value / repo_curve.discount_factor(bond_settle_date, forward_date)?)
+    }
+
+    /// Calculates the clean price for delivery on `forward_date`, i.e. [`Bond::forward_dirty_price`]
+    /// less the interest accrued on `forward_date`.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`Bond::forward_dirty_price`].
+    pub fn forward_clean_price<
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        RD: DayCount,
+        RI: Interpolator<Value = V>,
+    >(
+        &self,
+        bond_settle_date: Date,
+        forward_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+        repo_curve: &RepoCurve<RD, RI>,
+    ) -> QLabResult<V> {
+        Ok(
+            self.forward_dirty_price(bond_settle_date, forward_date, yield_curve, repo_curve)?
+                - self.accrued_interest(forward_date)?,
+        )
+    }
+
+    /// Calculates the implied repo rate accruing on `DC` of buying the bond at `dirty_price` on `bond_settle_date`
+    /// and delivering it at `forward_clean_price` on `forward_date`, e.g. a futures price times its conversion factor
+    /// when searching for the cheapest to deliver.
+    ///
+    /// Coupons due in between are assumed to be reinvested at the implied rate until `forward_date`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `forward_date` is not after `bond_settle_date`, or an error if the day counts
+    /// cannot be calculated.
+    pub fn implied_repo_rate<DC: DayCount>(
+        &self,
+        bond_settle_date: Date,
+        forward_date: Date,
+        dirty_price: V,
+        forward_clean_price: V,
+    ) -> QLabResult<V> {
+        if forward_date <= bond_settle_date {
+            return Err(InvalidInput(
+                format!("forward_date: {forward_date} must be after bond_settle_date: {bond_settle_date}")
+                    .into(),
+            )
+            .into());
+        }
+        let mut proceeds = forward_clean_price + self.accrued_interest(forward_date)?;
+        let mut invested =
+            dirty_price * DC::calculate_day_count_fraction::<V>(bond_settle_date, forward_date)?;
+        for cash_flow in &self.bond_cash_flows {
+            if bond_settle_date < cash_flow.due_date && cash_flow.due_date <= forward_date {
+                proceeds += cash_flow.payment_amount;
+                invested -= cash_flow.payment_amount
+                    * DC::calculate_day_count_fraction::<V>(cash_flow.payment_date, forward_date)?;
+            }
+        }
+        Ok((proceeds - dirty_price) / invested)
+    }
+
+    /// Calculates the discounted value of the bond's cash flows on the curve selected by its CSA.
+    ///
+    /// # Errors
+    /// Error occurs if no curve matches the CSA or if a discount factor calculation fails
+    pub fn discounted_value_with<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        bond_settle_date: Date,
+        discount_curves: &DiscountCurves<D, I>,
+    ) -> QLabResult<V> {
+        let yield_curve = select_discount_curve(self.csa.as_ref(), discount_curves)?;
+        self.discounted_value(bond_settle_date, yield_curve)
+    }
+
+    /// Builds a table of the bond's cash flows with their due dates, payment dates and amounts.
+    ///
+    /// Amounts are printed with 6 decimals; render the table with `Display` or as CSV.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn cash_flow_report(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&["due date", "payment date", "amount"]);
+        for cash_flow in &self.bond_cash_flows {
+            table.push_row(vec![
+                cash_flow.due_date.to_string(),
+                cash_flow.payment_date.to_string(),
+                format!("{:.6?}", cash_flow.payment_amount),
+            ])?;
+        }
+        Ok(table)
+    }
+
+    /// Sets how the accrued interest is calculated, [`AccruedInterestConvention::Generic`] by default.
+    #[must_use]
+    pub fn with_accrued_interest_convention(
+        mut self,
+        accrued_interest_convention: AccruedInterestConvention,
+    ) -> Self {
+        self.accrued_interest_convention = accrued_interest_convention;
+        self
+    }
+
+    /// Attaches a collateral agreement to the bond.
+    #[must_use]
+    pub fn with_csa(mut self, csa: Csa<V>) -> Self {
+        self.csa = Some(csa);
+        self
+    }
+
+    /// Returns the payment date and amount of each cash flow, including the face value repaid at maturity.
+    #[must_use]
+    pub fn cash_flows(&self) -> Vec<(Date, V)> {
+        self.bond_cash_flows
+            .iter()
+            .map(|cash_flow| (cash_flow.payment_date, cash_flow.payment_amount))
+            .collect()
+    }
+
+    /// Returns the payment date and amount of each cash flow due after `bond_settle_date`,
+    /// i.e. of the flows valued by [`Bond::discounted_value`].
+    #[must_use]
+    pub fn remaining_cash_flows(&self, bond_settle_date: Date) -> Vec<(Date, V)> {
+        self.bond_cash_flows
+            .iter()
+            .filter(|cash_flow| bond_settle_date < cash_flow.due_date)
+            .map(|cash_flow| (cash_flow.payment_date, cash_flow.payment_amount))
+            .collect()
+    }
+
+    /// Returns the due date of the final cash flow.
+    #[must_use]
+    pub fn maturity_date(&self) -> Date {
+        self.bond_cash_flows
+            .last()
+            .map_or(self.issue_date, |cash_flow| cash_flow.due_date)
+    }
+
+    /// Returns the coupon period of each cash flow, from the issue date or the previous due date to its due date,
+    /// with the day count fractions on the US 30/360 basis.
+    #[must_use]
+    pub fn accruals(&self) -> &AccrualSchedule<V> {
+        &self.accruals
+    }
+
+    #[must_use]
+    pub fn face_value(&self) -> V {
+        self.face_value
+    }
+
     #[must_use]
     pub fn bond_id(&self) -> &str {
         &self.bond_id
     }
+
+    #[must_use]
+    pub fn coupon_rate(&self) -> V {
+        self.coupon_rate
+    }
+
+    #[must_use]
+    pub fn accrued_interest_convention(&self) -> AccruedInterestConvention {
+        self.accrued_interest_convention
+    }
+
+    #[must_use]
+    pub fn csa(&self) -> Option<&Csa<V>> {
+        self.csa.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+
+    #[test]
+    fn test_accrued_interest_conventions() {
+        let bond = || {
+            Bond::new(
+                "JGB",
+                Date::from_ymd(2023, 7, 10).unwrap(),
+                Date::from_ymd(2024, 1, 10).unwrap(),
+                Date::from_ymd(2025, 1, 10).unwrap(),
+                Date::from_ymd(2025, 7, 10).unwrap(),
+                Frequency::SA,
+                &WeekendsOnly,
+                DateRolling::Following,
+                0.04,
+                100.0,
+            )
+            .unwrap()
+        };
+        let settle_date = Date::from_ymd(2023, 10, 13).unwrap();
+        let accrued = |convention| {
+            bond()
+                .with_accrued_interest_convention(convention)
+                .accrued_interest(settle_date)
+                .unwrap()
+        };
+        let generic: f64 = accrued(AccruedInterestConvention::Generic);
+        assert!((generic - 2.0 * 95.0 / 184.0).abs() < 1e-12);
+        assert!((accrued(AccruedInterestConvention::UsStreet) - 4.0 * 93.0 / 360.0).abs() < 1e-12);
+        assert!((accrued(AccruedInterestConvention::Japanese) - 1.041_095_8).abs() < 1e-12);
+        assert!((accrued(AccruedInterestConvention::ItalianBtp) - 1.032_61).abs() < 1e-12);
+
+        // an odd first period accrues its own coupon by the 30/360 share of the period
+        let long_first = Bond::new(
+            "CORP",
+            Date::from_ymd(2023, 1, 15).unwrap(),
+            Date::from_ymd(2023, 9, 30).unwrap(),
+            Date::from_ymd(2024, 3, 30).unwrap(),
+            Date::from_ymd(2024, 9, 30).unwrap(),
+            Frequency::SA,
+            &WeekendsOnly,
+            DateRolling::Unadjusted,
+            0.06_f64,
+            100.0,
+        )
+        .unwrap()
+        .with_accrued_interest_convention(AccruedInterestConvention::UsStreet);
+        let first_coupon = long_first.cash_flows()[0].1;
+        let accrued = long_first
+            .accrued_interest(Date::from_ymd(2023, 3, 31).unwrap())
+            .unwrap();
+        // 76 of the 255 days of the period on the US 30/360 basis
+        assert!((accrued - first_coupon * 76.0 / 255.0).abs() < 1e-12);
+        // the period fractions are cached once on the accrual schedule of the bond
+        let accruals = long_first.accruals().periods();
+        assert_eq!(accruals.len(), long_first.cash_flows().len());
+        assert!(accruals[0].period.is_stub);
+        assert!(!accruals[1].period.is_stub);
+        assert_eq!(
+            accruals[0].period.accrual_dates(false),
+            (
+                Date::from_ymd(2023, 1, 15).unwrap(),
+                Date::from_ymd(2023, 9, 30).unwrap()
+            )
+        );
+        assert!((accruals[0].unadjusted_fraction - 255.0 / 360.0).abs() < 1e-12);
+        assert!((accruals[1].unadjusted_fraction - 0.5).abs() < 1e-12);
+
+        // 29 February 2024 does not accrue on a JGB
+        let japanese = bond().with_accrued_interest_convention(AccruedInterestConvention::Japanese);
+        let accrued = japanese
+            .accrued_interest(Date::from_ymd(2024, 3, 1).unwrap())
+            .unwrap();
+        assert!((accrued - 0.547_945_2).abs() < 1e-12);
+
+        let settle_date = Date::from_ymd(2024, 7, 10).unwrap();
+        let simple_yield = japanese.japanese_simple_yield(settle_date, 101.0).unwrap();
+        assert!((simple_yield - (4.0 - 1.0 / 1.0) / 101.0).abs() < 1e-12);
+        assert!(japanese.japanese_simple_yield(settle_date, 0.0).is_err());
+        assert!(japanese
+            .japanese_simple_yield(Date::from_ymd(2025, 7, 10).unwrap(), 101.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_forward_price() {
+        use qlab_math::interpolation::linear::Linear;
+        use qlab_time::day_count::act_360::Act360;
+        use qlab_time::day_count::act_365::Act365;
+
+        let bond = Bond::new(
+            "UST",
+            Date::from_ymd(2023, 5, 15).unwrap(),
+            Date::from_ymd(2023, 11, 15).unwrap(),
+            Date::from_ymd(2027, 11, 15).unwrap(),
+            Date::from_ymd(2028, 5, 15).unwrap(),
+            Frequency::SA,
+            &WeekendsOnly,
+            DateRolling::Unadjusted,
+            0.04,
+            100.0,
+        )
+        .unwrap();
+        let settle_date = Date::from_ymd(2024, 2, 15).unwrap();
+        let forward_date = Date::from_ymd(2024, 6, 17).unwrap();
+        let pillars = [settle_date, Date::from_ymd(2029, 2, 15).unwrap()];
+        let yield_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &pillars, &[0.045, 0.045]).unwrap();
+        let repo_curve: RepoCurve<Act360, Linear<f64>> =
+            RepoCurve::new(settle_date, &[forward_date], &[0.05]).unwrap();
+
+        let dirty_price = bond.discounted_value(settle_date, &yield_curve).unwrap();
+        let forward_dirty = bond
+            .forward_dirty_price(settle_date, forward_date, &yield_curve, &repo_curve)
+            .unwrap();
+        // the coupon of 2024-05-15 is carried at repo
+        let carried = 2.0 / (1.0 + 0.05 * 90.0 / 360.0);
+        let expected = (dirty_price - carried) * (1.0 + 0.05 * 123.0 / 360.0);
+        assert!((forward_dirty - expected).abs() < 1e-12);
+        let forward_clean = bond
+            .forward_clean_price(settle_date, forward_date, &yield_curve, &repo_curve)
+            .unwrap();
+        assert!((forward_dirty - forward_clean - 2.0 * 33.0 / 184.0).abs() < 1e-12);
+
+        // delivering at the repo forward price earns the repo rate, up to the compounding of the coupon
+        let implied = bond
+            .implied_repo_rate::<Act360>(settle_date, forward_date, dirty_price, forward_clean)
+            .unwrap();
+        assert!((implied - 0.05).abs() < 1e-5);
+        assert!(bond
+            .forward_dirty_price(forward_date, settle_date, &yield_curve, &repo_curve)
+            .is_err());
+        assert!(bond
+            .implied_repo_rate::<Act360>(settle_date, settle_date, dirty_price, forward_clean)
+            .is_err());
+    }
+
+    #[test]
+    fn test_payment_dates_on_holidays() {
+        // Christmas Day 2024 is a Wednesday and a TARGET holiday, as is Boxing Day
+        let bond = Bond::new(
+            "BUND",
+            Date::from_ymd(2023, 12, 25).unwrap(),
+            Date::from_ymd(2024, 12, 25).unwrap(),
+            Date::from_ymd(2024, 12, 25).unwrap(),
+            Date::from_ymd(2025, 12, 25).unwrap(),
+            Frequency::A,
+            &calendar::target::Target,
+            DateRolling::Following,
+            0.025,
+            100.0,
+        )
+        .unwrap();
+        let payment_dates: Vec<_> = bond.cash_flows().iter().map(|&(date, _)| date).collect();
+        assert_eq!(
+            payment_dates,
+            [
+                Date::from_ymd(2024, 12, 27).unwrap(),
+                Date::from_ymd(2025, 12, 29).unwrap()
+            ]
+        );
+        // the next coupon accrues from the unadjusted due date
+        let accrued: f64 = bond
+            .accrued_interest(Date::from_ymd(2024, 12, 26).unwrap())
+            .unwrap();
+        assert!((accrued - 2.5 / 365.0).abs() < 1e-12);
+    }
 }
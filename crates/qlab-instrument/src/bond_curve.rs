@@ -0,0 +1,591 @@
+use crate::bond::Bond;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{Context, QLabResult};
+use qlab_math::interpolation::Interpolator;
+use qlab_math::optimization::least_squares_with_report;
+use qlab_math::value::Value;
+use qlab_termstructure::diagnostics::BootstrapDiagnostics;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::collections::BTreeMap;
+
+/// The parameterization of a curve fitted to bond prices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitModel<V> {
+    /// The spot yields at the pillars are free parameters joined by the interpolator of the curve.
+    PillarYields,
+    /// The spot yields at the pillars follow the Nelson-Siegel form
+    /// `b0 + b1 (1 - exp(-t / tau)) / (t / tau) + b2 ((1 - exp(-t / tau)) / (t / tau) - exp(-t / tau))`
+    /// with `b0`, `b1` and `b2` fitted for the given decay time `tau`.
+    NelsonSiegel { tau: V },
+}
+
+/// A market clean price of a bond and the weight of its squared pricing error in the fit.
+#[derive(Clone, Copy)]
+pub struct BondQuote<'a, V> {
+    pub bond: &'a Bond<V>,
+    pub clean_price: V,
+    pub weight: V,
+}
+
+/// The difference between the market and fitted clean prices of a bond.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondPricingError<V> {
+    pub bond_id: String,
+    pub market_clean_price: V,
+    pub model_clean_price: V,
+}
+
+impl<V: Value> BondPricingError<V> {
+    /// Returns the model clean price minus the market clean price.
+    #[must_use]
+    pub fn error(&self) -> V {
+        self.model_clean_price - self.market_clean_price
+    }
+}
+
+/// A yield curve fitted to bond prices together with the remaining pricing error of each bond.
+pub struct FittedBondCurve<D: DayCount, I: Interpolator> {
+    pub curve: YieldCurve<D, I>,
+    pub pricing_errors: Vec<BondPricingError<I::Value>>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> FittedBondCurve<D, I> {
+    /// Returns the root mean square of the pricing errors.
+    #[must_use]
+    pub fn rms_error(&self) -> V {
+        let sum = self
+            .pricing_errors
+            .iter()
+            .fold(V::zero(), |acc, error| acc + error.error() * error.error());
+        (sum / V::from_usize(self.pricing_errors.len().max(1)).unwrap_or_else(V::one)).sqrt()
+    }
+}
+
+/// Fits a [`YieldCurve`] to bond clean prices by weighted least squares.
+///
+/// The curve is defined by its spot yields at `pillars`, which must cover the payment dates of all quoted bonds,
+/// and the parameters of `model` are chosen to minimize the weighted sum of squared clean price errors.
+///
+/// The short end can be shaped apart from the pillars: [`BondCurveFitter::with_knots`] adds interpolation nodes
+/// whose yields are fitted like those of the pillars, and [`BondCurveFitter::with_anchor`] pins the yield of a node,
+/// e.g. the overnight point to the overnight rate. Under [`FitModel::PillarYields`] an anchored node takes no part
+/// in the fit; under [`FitModel::NelsonSiegel`] each of at most two anchors is a constraint on the fitted form,
+/// which passes through the anchored yields exactly.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_instrument::bond::Bond;
+/// use qlab_instrument::bond_curve::{BondCurveFitter, BondQuote, FitModel};
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::day_count::act_365::Act365;
+/// use qlab_time::frequency::Frequency;
+///
+/// let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+/// let pillars = [Date::from_ymd(2023, 10, 11).unwrap(), Date::from_ymd(2029, 10, 10).unwrap()];
+/// let bonds: Vec<_> = [(2025, 0.03), (2027, 0.04), (2029, 0.05)]
+///     .into_iter()
+///     .map(|(year, coupon)| {
+///         Bond::new(
+///             &format!("{year}"),
+///             Date::from_ymd(2023, 7, 10).unwrap(),
+///             Date::from_ymd(2024, 1, 10).unwrap(),
+///             Date::from_ymd(year, 1, 10).unwrap(),
+///             Date::from_ymd(year, 7, 10).unwrap(),
+///             Frequency::SA,
+///             &WeekendsOnly,
+///             DateRolling::Following,
+///             coupon,
+///             100.0,
+///         )
+///         .unwrap()
+///     })
+///     .collect();
+/// let quotes: Vec<_> = bonds
+///     .iter()
+///     .zip([99.5, 100.2, 103.0])
+///     .map(|(bond, clean_price)| BondQuote { bond, clean_price, weight: 1.0 })
+///     .collect();
+/// let fitted = BondCurveFitter::new(&pillars, FitModel::PillarYields)
+///     .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+///     .unwrap();
+/// assert_eq!(fitted.pricing_errors.len(), 3);
+/// assert!(fitted.rms_error() < 0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BondCurveFitter<V> {
+    pillars: Vec<Date>,
+    model: FitModel<V>,
+    knots: Vec<Date>,
+    anchors: BTreeMap<Date, V>,
+}
+
+impl<V: Value> BondCurveFitter<V> {
+    #[must_use]
+    pub fn new(pillars: &[Date], model: FitModel<V>) -> Self {
+        Self {
+            pillars: pillars.to_vec(),
+            model,
+            knots: Vec::new(),
+            anchors: BTreeMap::new(),
+        }
+    }
+
+    /// Adds interpolation nodes on `knots` besides the pillars, whose yields are fitted with those of the pillars.
+    #[must_use]
+    pub fn with_knots(mut self, knots: &[Date]) -> Self {
+        self.knots.extend_from_slice(knots);
+        self
+    }
+
+    /// Pins the spot yield of the node on `date` to `spot_yield`, adding the node if it is neither a pillar nor a knot.
+    #[must_use]
+    pub fn with_anchor(mut self, date: Date, spot_yield: V) -> Self {
+        self.anchors.insert(date, spot_yield);
+        self
+    }
+
+    #[must_use]
+    pub fn pillars(&self) -> &[Date] {
+        &self.pillars
+    }
+
+    #[must_use]
+    pub fn knots(&self) -> &[Date] {
+        &self.knots
+    }
+
+    /// Returns the pinned spot yields by date.
+    #[must_use]
+    pub fn anchors(&self) -> &BTreeMap<Date, V> {
+        &self.anchors
+    }
+
+    /// Returns the dates of all nodes of the curve in order, each with its pinned yield if it is anchored.
+    #[must_use]
+    pub fn nodes(&self) -> Vec<(Date, Option<V>)> {
+        let mut nodes: BTreeMap<_, _> = self
+            .pillars
+            .iter()
+            .chain(&self.knots)
+            .map(|&date| (date, None))
+            .collect();
+        nodes.extend(
+            self.anchors
+                .iter()
+                .map(|(&date, &spot_yield)| (date, Some(spot_yield))),
+        );
+        nodes.into_iter().collect()
+    }
+
+    #[must_use]
+    pub fn model(&self) -> FitModel<V> {
+        self.model
+    }
+
+    /// Fits the curve to `quotes` as of `settle_date`.
+    ///
+    /// # Errors
+    /// Returns an error if a weight is negative, if more than two anchors constrain a Nelson-Siegel fit
+    /// or two of them fall on the same year fraction, if there are fewer quotes than fitted parameters,
+    /// if a bond cannot be priced on the curve, or if the fit does not converge.
+    pub fn fit<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        quotes: &[BondQuote<V>],
+    ) -> QLabResult<FittedBondCurve<D, I>> {
+        self.fit_with_diagnostics(settle_date, quotes).0
+    }
+
+    /// Fits the curve as [`BondCurveFitter::fit`] does and returns the diagnostics of the fit together with
+    /// the outcome. The fit is a single solve recorded with an event per quote, on the maturity of its bond
+    /// with its weighted price error as the residual. The diagnostics are empty if the quotes are rejected
+    /// before solving.
+    pub fn fit_with_diagnostics<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        quotes: &[BondQuote<V>],
+    ) -> (QLabResult<FittedBondCurve<D, I>>, BootstrapDiagnostics<V>) {
+        let mut diagnostics = BootstrapDiagnostics::default();
+        let fitted = self.solve(settle_date, quotes, &mut diagnostics);
+        (fitted, diagnostics)
+    }
+
+    fn solve<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        quotes: &[BondQuote<V>],
+        diagnostics: &mut BootstrapDiagnostics<V>,
+    ) -> QLabResult<FittedBondCurve<D, I>> {
+        if let Some(quote) = quotes.iter().find(|quote| quote.weight < V::zero()) {
+            return Err(InvalidInput(
+                format!(
+                    "weight: {:?} of {} must not be negative",
+                    quote.weight,
+                    quote.bond.bond_id()
+                )
+                .into(),
+            )
+            .into());
+        }
+        let nodes = self.nodes();
+        let dates: Vec<_> = nodes.iter().map(|&(date, _)| date).collect();
+        let times = dates
+            .iter()
+            .map(|&date| D::calculate_day_count_fraction(settle_date, date))
+            .collect::<QLabResult<Vec<V>>>()?;
+        let anchored: Vec<_> = nodes
+            .iter()
+            .zip(&times)
+            .filter_map(|(&(_, anchor), &t)| anchor.map(|spot_yield| (t, spot_yield)))
+            .collect();
+        let initial = match self.model {
+            FitModel::PillarYields => vec![V::zero(); nodes.len() - anchored.len()],
+            FitModel::NelsonSiegel { tau } => {
+                // check the constraints once before fitting
+                anchored_nelson_siegel(&anchored, tau, &[V::zero(); 3][anchored.len().min(3)..])?;
+                vec![V::zero(); 3 - anchored.len()]
+            }
+        };
+        let curve = |parameters: &[V]| -> QLabResult<YieldCurve<D, I>> {
+            let mut free = parameters.iter();
+            let coefficients = match self.model {
+                FitModel::PillarYields => Vec::new(),
+                FitModel::NelsonSiegel { tau } => {
+                    anchored_nelson_siegel(&anchored, tau, parameters)?
+                }
+            };
+            let yields: Vec<_> = nodes
+                .iter()
+                .zip(&times)
+                .map(|(&(_, anchor), &t)| {
+                    anchor.unwrap_or_else(|| match self.model {
+                        FitModel::PillarYields => free.next().copied().unwrap_or_else(V::zero),
+                        FitModel::NelsonSiegel { tau } => nelson_siegel(&coefficients, tau, t),
+                    })
+                })
+                .collect();
+            YieldCurve::new(settle_date, &dates, &yields)
+        };
+        let residuals = |parameters: &[V]| {
+            let curve = curve(parameters)?;
+            quotes
+                .iter()
+                .map(|quote| {
+                    let clean_price = quote
+                        .bond
+                        .clean_price(settle_date, &curve)
+                        .with_context(|| format!("pricing {}", quote.bond.bond_id()))?;
+                    Ok(quote.weight.sqrt() * (clean_price - quote.clean_price))
+                })
+                .collect::<QLabResult<Vec<_>>>()
+        };
+        let maturities = quotes.iter().map(|quote| quote.bond.maturity_date());
+        let parameters = match least_squares_with_report(residuals, &initial)
+            .and_then(|report| Ok((residuals(&report.parameters)?, report)))
+        {
+            Ok((residuals, report)) => {
+                diagnostics.record_solved(maturities.zip(residuals), &report);
+                report.parameters
+            }
+            Err(err) => {
+                diagnostics.record_failed(maturities, &err.to_string());
+                return Err(err).context("fitting the curve to bond prices");
+            }
+        };
+        let curve = curve(&parameters)?;
+        let pricing_errors = quotes
+            .iter()
+            .map(|quote| {
+                Ok(BondPricingError {
+                    bond_id: quote.bond.bond_id().to_string(),
+                    market_clean_price: quote.clean_price,
+                    model_clean_price: quote.bond.clean_price(settle_date, &curve)?,
+                })
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        Ok(FittedBondCurve {
+            curve,
+            pricing_errors,
+        })
+    }
+}
+
+/// Solves the Nelson-Siegel coefficients whose form passes through the `anchored` pairs of year fraction and
+/// spot yield, given the coefficients left `free`: one anchor fixes `b0`, two anchors fix `b0` and `b1`.
+fn anchored_nelson_siegel<V: Value>(anchored: &[(V, V)], tau: V, free: &[V]) -> QLabResult<Vec<V>> {
+    let loading = |index: usize, t: V| {
+        let mut unit = [V::zero(); 3];
+        unit[index] = V::one();
+        nelson_siegel(&unit, tau, t)
+    };
+    // the yields left to the pinned coefficients once the free ones are taken off
+    let remaining = |(t, spot_yield): (V, V)| {
+        free.iter()
+            .enumerate()
+            .fold(spot_yield, |acc, (index, &coefficient)| {
+                acc - coefficient * loading(3 - free.len() + index, t)
+            })
+    };
+    let pinned = match *anchored {
+        [] => Vec::new(),
+        [first] => vec![remaining(first)],
+        [first, second] => {
+            let slope = loading(1, second.0) - loading(1, first.0);
+            if slope == V::zero() {
+                return Err(InvalidInput(
+                    "two anchors on the same year fraction cannot constrain a Nelson-Siegel fit"
+                        .into(),
+                )
+                .into());
+            }
+            let b1 = (remaining(second) - remaining(first)) / slope;
+            vec![remaining(first) - b1 * loading(1, first.0), b1]
+        }
+        _ => {
+            return Err(InvalidInput(
+                format!(
+                    "a Nelson-Siegel fit takes at most two anchors, got {}",
+                    anchored.len()
+                )
+                .into(),
+            )
+            .into())
+        }
+    };
+    Ok(pinned.into_iter().chain(free.iter().copied()).collect())
+}
+
+/// Calculates the Nelson-Siegel spot yield at the year fraction `t` from the level, slope and curvature
+/// in `parameters` and the decay time `tau`.
+///
+/// # Panics
+/// Will panic if `parameters` has fewer than three elements.
+#[must_use]
+pub fn nelson_siegel<V: Value>(parameters: &[V], tau: V, t: V) -> V {
+    let x = t / tau;
+    let decay = (-x).exp();
+    // the loading of the slope tends to one at the short end
+    let slope = if x > V::epsilon() {
+        (V::one() - decay) / x
+    } else {
+        V::one()
+    };
+    parameters[0] + parameters[1] * slope + parameters[2] * (slope - decay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::date_rolling::DateRolling;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::frequency::Frequency;
+
+    fn bond(maturity_year: i32, coupon_rate: f64) -> Bond<f64> {
+        Bond::new(
+            &format!("{maturity_year}"),
+            Date::from_ymd(2023, 7, 10).unwrap(),
+            Date::from_ymd(2024, 1, 10).unwrap(),
+            Date::from_ymd(maturity_year, 1, 10).unwrap(),
+            Date::from_ymd(maturity_year, 7, 10).unwrap(),
+            Frequency::SA,
+            &WeekendsOnly,
+            DateRolling::Following,
+            coupon_rate,
+            100.0,
+        )
+        .unwrap()
+    }
+
+    fn setup() -> (Date, Vec<Date>, Vec<Bond<f64>>) {
+        let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let pillars = [
+            (2023, 10, 11),
+            (2025, 10, 10),
+            (2028, 10, 10),
+            (2033, 10, 10),
+        ]
+        .iter()
+        .map(|&(y, m, d)| Date::from_ymd(y, m, d).unwrap())
+        .collect();
+        let bonds = [
+            (2024, 0.01),
+            (2025, 0.02),
+            (2026, 0.03),
+            (2028, 0.035),
+            (2030, 0.04),
+            (2033, 0.045),
+        ]
+        .iter()
+        .map(|&(year, coupon)| bond(year, coupon))
+        .collect();
+        (settle_date, pillars, bonds)
+    }
+
+    fn quotes<'a>(
+        bonds: &'a [Bond<f64>],
+        settle_date: Date,
+        curve: &YieldCurve<Act365, Linear<f64>>,
+    ) -> Vec<BondQuote<'a, f64>> {
+        bonds
+            .iter()
+            .map(|bond| BondQuote {
+                bond,
+                clean_price: bond.clean_price(settle_date, curve).unwrap(),
+                weight: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_accrued_interest() {
+        let bond = bond(2025, 0.04);
+        // 92 of 184 days of the first semi-annual coupon of 2 have accrued
+        let accrued = bond
+            .accrued_interest(Date::from_ymd(2023, 10, 10).unwrap())
+            .unwrap();
+        assert!((accrued - 2.0 * 92.0 / 184.0).abs() < 1e-12);
+        assert!(
+            bond.accrued_interest(Date::from_ymd(2023, 1, 1).unwrap())
+                .unwrap()
+                .abs()
+                < f64::EPSILON
+        );
+    }
+
+    #[test]
+    fn test_fit_pillar_yields() {
+        let (settle_date, pillars, bonds) = setup();
+        let yields = [0.03, 0.035, 0.04, 0.042];
+        let true_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &pillars, &yields).unwrap();
+        let quotes = quotes(&bonds, settle_date, &true_curve);
+        let fitted = BondCurveFitter::new(&pillars, FitModel::PillarYields)
+            .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+            .unwrap();
+        assert!(fitted.rms_error() < 1e-6);
+        for (&(_, actual), expected) in fitted.curve.pillars().iter().zip(yields) {
+            assert!((actual - expected).abs() < 1e-6);
+        }
+        let curve_fitter = BondCurveFitter::new(&pillars, FitModel::PillarYields);
+        let (fitted, diagnostics) =
+            curve_fitter.fit_with_diagnostics::<Act365, Linear<f64>>(settle_date, &quotes);
+        assert!(fitted.is_ok());
+        assert_eq!(diagnostics.solves(), 1);
+        assert_eq!(diagnostics.events().len(), bonds.len());
+        assert_eq!(diagnostics.events()[5].pillar(), bonds[5].maturity_date());
+        assert!(diagnostics.max_residual().unwrap() < 1e-6);
+        // three bonds cannot determine four yields
+        let (fitted, diagnostics) =
+            curve_fitter.fit_with_diagnostics::<Act365, Linear<f64>>(settle_date, &quotes[..3]);
+        assert!(fitted.is_err());
+        assert_eq!(diagnostics.failures().len(), 3);
+    }
+
+    #[test]
+    fn test_fit_nelson_siegel() {
+        let (settle_date, pillars, bonds) = setup();
+        let tau = 2.0;
+        let parameters = [0.045, -0.015, 0.01];
+        let yields: Vec<_> = pillars
+            .iter()
+            .map(|&pillar| {
+                let t = Act365::calculate_day_count_fraction(settle_date, pillar).unwrap();
+                nelson_siegel(&parameters, tau, t)
+            })
+            .collect();
+        let true_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &pillars, &yields).unwrap();
+        let mut quotes = quotes(&bonds, settle_date, &true_curve);
+        quotes[0].clean_price += 0.1;
+        let fitted = BondCurveFitter::new(&pillars, FitModel::NelsonSiegel { tau })
+            .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+            .unwrap();
+        // the mispriced bond carries most of the error
+        let errors: Vec<_> = fitted
+            .pricing_errors
+            .iter()
+            .map(BondPricingError::error)
+            .collect();
+        assert!(errors[0] < -0.05);
+        assert!(errors[1..].iter().all(|error| error.abs() < 0.05));
+
+        quotes[0].weight = -1.0;
+        assert!(
+            BondCurveFitter::new(&pillars, FitModel::NelsonSiegel { tau })
+                .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_knots_and_anchors() {
+        let (settle_date, pillars, bonds) = setup();
+        let true_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &pillars, &[0.03, 0.035, 0.04, 0.042]).unwrap();
+        let quotes = quotes(&bonds, settle_date, &true_curve);
+        let knot = Date::from_ymd(2024, 10, 10).unwrap();
+        let builder = BondCurveFitter::new(&pillars, FitModel::PillarYields)
+            .with_knots(&[knot])
+            .with_anchor(pillars[0], 0.03);
+        let nodes = builder.nodes();
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(nodes[0], (pillars[0], Some(0.03)));
+        assert_eq!(nodes[1], (knot, None));
+        let fitted = builder
+            .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+            .unwrap();
+        // the pinned overnight point holds and the knot on the true curve keeps the fit exact
+        let curve_pillars = fitted.curve.pillars();
+        assert_eq!(curve_pillars.len(), 5);
+        assert!((curve_pillars[0].1 - 0.03).abs() < f64::EPSILON);
+        assert!(fitted.rms_error() < 1e-6);
+
+        // an anchor on a new date adds a node, through which the Nelson-Siegel form passes
+        let tau = 2.0;
+        let parameters = [0.045, -0.015, 0.01];
+        let true_yield = |date| {
+            let t = Act365::calculate_day_count_fraction(settle_date, date).unwrap();
+            nelson_siegel(&parameters, tau, t)
+        };
+        let overnight = Date::from_ymd(2023, 10, 12).unwrap();
+        let builder = BondCurveFitter::new(&pillars, FitModel::NelsonSiegel { tau })
+            .with_anchor(overnight, true_yield(overnight))
+            .with_anchor(knot, true_yield(knot));
+        let nodes: Vec<_> = builder.nodes().into_iter().map(|(date, _)| date).collect();
+        assert_eq!(nodes.len(), 6);
+        let yields: Vec<_> = nodes.iter().map(|&date| true_yield(date)).collect();
+        let true_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &nodes, &yields).unwrap();
+        let quotes = self::quotes(&bonds, settle_date, &true_curve);
+        let fitted = builder
+            .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+            .unwrap();
+        assert!(fitted.rms_error() < 1e-6);
+        for (&(_, actual), expected) in fitted.curve.pillars().iter().zip(yields) {
+            assert!((actual - expected).abs() < 1e-6);
+        }
+
+        // an anchor off the form binds the fit
+        let fitted = BondCurveFitter::new(&pillars, FitModel::NelsonSiegel { tau })
+            .with_anchor(overnight, 0.06)
+            .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+            .unwrap();
+        assert!((fitted.curve.pillars()[1].1 - 0.06).abs() < f64::EPSILON);
+        assert!(fitted.rms_error() > 1e-3);
+
+        // three anchors leave nothing to fit
+        assert!(builder
+            .with_anchor(pillars[3], 0.04)
+            .fit::<Act365, Linear<f64>>(settle_date, &quotes)
+            .is_err());
+    }
+}
@@ -0,0 +1,606 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::discount_curves::DiscountCurves;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use qlab_time::frequency::Frequency;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+/// The curves and valuation date used to discount cash flows.
+pub struct MarketSnapshot<'a, D: DayCount, I: Interpolator> {
+    pub valuation_date: Date,
+    /// Each flow is discounted on the curve of its currency, or on the funding curve if the set has none.
+    pub discount_curves: &'a DiscountCurves<D, I>,
+}
+
+/// A single cash flow.
+pub struct CashFlow<'a, D: DayCount, I: Interpolator> {
+    pub payment_date: Date,
+    pub amount: I::Value,
+    pub currency: &'a str,
+    /// A curve whose discount factors are the survival probabilities of the payer, if the flow bears credit risk.
+    pub credit_curve: Option<&'a YieldCurve<D, I>>,
+}
+
+/// The valuation of a single cash flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowValue<V> {
+    pub payment_date: Date,
+    pub amount: V,
+    /// The currency in upper case.
+    pub currency: String,
+    pub discount_factor: V,
+    /// One for flows without a credit curve.
+    pub survival_probability: V,
+    pub present_value: V,
+}
+
+/// The per-flow breakdown of the valuation of a set of cash flows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashFlowValuation<V> {
+    pub flows: Vec<FlowValue<V>>,
+}
+
+impl<V: Value> CashFlowValuation<V> {
+    /// Returns the present value of the flows in each currency.
+    #[must_use]
+    pub fn present_value_by_currency(&self) -> BTreeMap<String, V> {
+        let mut present_values = BTreeMap::new();
+        for flow in &self.flows {
+            *present_values
+                .entry(flow.currency.clone())
+                .or_insert_with(V::zero) += flow.present_value;
+        }
+        present_values
+    }
+
+    /// Returns the present value of the flows in `currency`.
+    #[must_use]
+    pub fn present_value(&self, currency: &str) -> V {
+        let currency = currency.to_uppercase();
+        self.flows
+            .iter()
+            .filter(|flow| flow.currency == currency)
+            .fold(V::zero(), |acc, flow| acc + flow.present_value)
+    }
+}
+
+/// Discounts arbitrary cash flows, so that simple structures can be priced without a dedicated instrument type.
+///
+/// Flows paid on or before the valuation date are left out of the valuation.
+///
+/// # Errors
+/// Returns an error if a discount factor or survival probability calculation fails.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::cash_flow_engine::{value, CashFlow, MarketSnapshot};
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::discount_curves::DiscountCurves;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let maturities = [Date::from_ymd(2024, 1, 3).unwrap(), Date::from_ymd(2034, 1, 2).unwrap()];
+/// let curve: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(valuation_date, &maturities, &[0.04, 0.04]).unwrap();
+/// let curves = DiscountCurves::new(curve);
+/// let flows = [CashFlow {
+///     payment_date: Date::from_ymd(2025, 1, 1).unwrap(),
+///     amount: 100.0,
+///     currency: "usd",
+///     credit_curve: None,
+/// }];
+/// let valuation = value(flows, &MarketSnapshot { valuation_date, discount_curves: &curves }).unwrap();
+/// assert!((valuation.present_value("USD") - 100.0 * (-0.04_f64).exp()).abs() < 1e-12);
+/// ```
+pub fn value<'a, V: Value, D: DayCount + 'a, I: Interpolator<Value = V> + 'a>(
+    flows: impl IntoIterator<Item = CashFlow<'a, D, I>>,
+    market: &MarketSnapshot<D, I>,
+) -> QLabResult<CashFlowValuation<V>> {
+    let mut values = Vec::new();
+    for flow in flows {
+        if flow.payment_date <= market.valuation_date {
+            continue;
+        }
+        let discount_curve = market
+            .discount_curves
+            .collateral(flow.currency)
+            .unwrap_or_else(|| market.discount_curves.funding());
+        let discount_factor =
+            discount_curve.discount_factor(market.valuation_date, flow.payment_date)?;
+        let survival_probability = match flow.credit_curve {
+            Some(credit_curve) => {
+                credit_curve.discount_factor(market.valuation_date, flow.payment_date)?
+            }
+            None => V::one(),
+        };
+        values.push(FlowValue {
+            payment_date: flow.payment_date,
+            amount: flow.amount,
+            currency: flow.currency.to_uppercase(),
+            discount_factor,
+            survival_probability,
+            present_value: flow.amount * discount_factor * survival_probability,
+        });
+    }
+    Ok(CashFlowValuation { flows: values })
+}
+
+/// Merges the flows paid on the same date in the same currency and bearing the same credit curve,
+/// so that [`value`] evaluates each discount factor and survival probability once for a book of similar flows.
+///
+/// Currencies are compared ignoring case and credit curves by identity; the merged flows are sorted by
+/// payment date and keep the currency spelling of the first flow of each group.
+pub fn compress<'a, V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    flows: impl IntoIterator<Item = CashFlow<'a, D, I>>,
+) -> Vec<CashFlow<'a, D, I>> {
+    let mut merged = BTreeMap::new();
+    for flow in flows {
+        let key = (
+            flow.payment_date,
+            flow.currency.to_uppercase(),
+            flow.credit_curve.map(std::ptr::from_ref),
+        );
+        match merged.entry(key) {
+            Entry::Occupied(mut entry) => {
+                let compressed: &mut CashFlow<'a, D, I> = entry.get_mut();
+                compressed.amount += flow.amount;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(flow);
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+/// A rate applied period by period when projecting a pool, e.g. a prepayment or default vector.
+///
+/// The rate of each period is looked up by its index; periods beyond the end of the vector use its last rate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateVector<V> {
+    /// Rates per payment period, e.g. the single monthly mortality (SMM) or the monthly default rate (MDR).
+    Periodic(Vec<V>),
+    /// Annualized rates, e.g. the conditional prepayment rate (CPR) or the constant default rate (CDR),
+    /// de-annualized as `1 - (1 - rate)^(1 / f)` with `f` payments a year.
+    Annual(Vec<V>),
+}
+
+impl<V: Value> RateVector<V> {
+    fn rates(&self) -> &[V] {
+        match self {
+            Self::Periodic(rates) | Self::Annual(rates) => rates,
+        }
+    }
+
+    fn validate(&self, name: &str) -> QLabResult<()> {
+        let rates = self.rates();
+        if rates.is_empty() {
+            return Err(InvalidInput(format!("the {name} vector is empty").into()).into());
+        }
+        if let Some(rate) = rates
+            .iter()
+            .find(|&&rate| rate < V::zero() || rate > V::one())
+        {
+            return Err(InvalidInput(
+                format!("the {name} vector has a rate outside [0, 1]: {rate:?}").into(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Returns the rate of the period `period` paying `frequency`.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn periodic_rate(&self, period: usize, frequency: Frequency) -> V {
+        let rates = self.rates();
+        let Some(&rate) = rates.get(period).or(rates.last()) else {
+            return V::zero();
+        };
+        match self {
+            Self::Periodic(_) => rate,
+            Self::Annual(_) => {
+                let periods_per_year = V::from_u8(frequency as u8).unwrap();
+                V::one() - (V::one() - rate).powf(V::one() / periods_per_year)
+            }
+        }
+    }
+}
+
+/// How the performing balance of a pool repays on schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Amortization {
+    /// The whole balance is repaid in the last period.
+    Bullet,
+    /// Each period pays a level annuity of interest and principal over the remaining periods, as a mortgage does.
+    LevelPayment,
+}
+
+/// The prepayment, default and loss severity vectors with which a pool is projected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolAssumptions<V> {
+    prepayment: RateVector<V>,
+    default: RateVector<V>,
+    severity: Vec<V>,
+}
+
+impl<V: Value> PoolAssumptions<V> {
+    /// Creates the assumptions from vectors by period; `severity` is the share of a defaulted balance which is lost,
+    /// the rest being recovered in the period of default.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a vector is empty or has a rate outside [0, 1].
+    pub fn new(
+        prepayment: RateVector<V>,
+        default: RateVector<V>,
+        severity: Vec<V>,
+    ) -> QLabResult<Self> {
+        prepayment.validate("prepayment")?;
+        default.validate("default")?;
+        RateVector::Periodic(severity.clone()).validate("severity")?;
+        Ok(Self {
+            prepayment,
+            default,
+            severity,
+        })
+    }
+
+    #[must_use]
+    pub fn prepayment(&self) -> &RateVector<V> {
+        &self.prepayment
+    }
+
+    #[must_use]
+    pub fn default(&self) -> &RateVector<V> {
+        &self.default
+    }
+
+    #[must_use]
+    pub fn severity(&self) -> &[V] {
+        &self.severity
+    }
+}
+
+/// The projected flows of a pool over one payment period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolPeriod<V> {
+    pub payment_date: Date,
+    pub beginning_balance: V,
+    /// The balance defaulting at the start of the period.
+    pub default: V,
+    /// The interest paid on the performing balance.
+    pub interest: V,
+    pub scheduled_principal: V,
+    pub prepayment: V,
+    /// The part of the defaulted balance recovered in the period.
+    pub recovery: V,
+    /// The part of the defaulted balance written off.
+    pub loss: V,
+    pub ending_balance: V,
+}
+
+impl<V: Value> PoolPeriod<V> {
+    /// Returns the cash paid to the holders of the pool in the period.
+    #[must_use]
+    pub fn cash_flow(&self) -> V {
+        self.interest + self.scheduled_principal + self.prepayment + self.recovery
+    }
+}
+
+/// Projects a pool of `balance` paying `coupon_rate` at `frequency` on `payment_dates` under `assumptions`,
+/// so that structured cash flows can be valued from user-supplied vectors without a behavioral model.
+///
+/// Each period, the default rate applies to the beginning balance; the performing balance then pays interest and
+/// its scheduled principal, and the prepayment rate applies to the balance remaining after the scheduled principal.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `balance` is negative or no payment date is given.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::cash_flow_engine::{project_pool, Amortization, PoolAssumptions, RateVector};
+/// use qlab_time::date::Date;
+/// use qlab_time::frequency::Frequency;
+///
+/// let payment_dates: Vec<_> = (1..=12).map(|month| Date::from_ymd(2024, month, 25).unwrap()).collect();
+/// // 6% CPR and 2% CDR with 40% of the defaulted balance lost
+/// let assumptions = PoolAssumptions::new(
+///     RateVector::Annual(vec![0.06]),
+///     RateVector::Annual(vec![0.02]),
+///     vec![0.4],
+/// )
+/// .unwrap();
+/// let periods = project_pool(
+///     1_000_000.0_f64,
+///     0.05,
+///     &payment_dates,
+///     Frequency::M,
+///     Amortization::LevelPayment,
+///     &assumptions,
+/// )
+/// .unwrap();
+/// assert!(periods[11].ending_balance.abs() < 1e-6);
+/// let principal: f64 = periods
+///     .iter()
+///     .map(|period| period.scheduled_principal + period.prepayment + period.default)
+///     .sum();
+/// assert!((principal - 1_000_000.0).abs() < 1e-6);
+/// ```
+pub fn project_pool<V: Value>(
+    balance: V,
+    coupon_rate: V,
+    payment_dates: &[Date],
+    frequency: Frequency,
+    amortization: Amortization,
+    assumptions: &PoolAssumptions<V>,
+) -> QLabResult<Vec<PoolPeriod<V>>> {
+    if balance < V::zero() {
+        return Err(
+            InvalidInput(format!("balance: {balance:?} must not be negative").into()).into(),
+        );
+    }
+    if payment_dates.is_empty() {
+        return Err(InvalidInput("at least one payment date is required".into()).into());
+    }
+    let periodic_coupon = coupon_rate / V::from_u8(frequency as u8).unwrap();
+    let mut beginning_balance = balance;
+    let mut periods = Vec::with_capacity(payment_dates.len());
+    for (i, &payment_date) in payment_dates.iter().enumerate() {
+        let default = beginning_balance * assumptions.default.periodic_rate(i, frequency);
+        let severity = assumptions
+            .severity
+            .get(i)
+            .or(assumptions.severity.last())
+            .copied()
+            .unwrap_or_else(V::one);
+        let performing = beginning_balance - default;
+        let interest = performing * periodic_coupon;
+        let remaining = payment_dates.len() - i;
+        let scheduled_principal = if remaining == 1 {
+            performing
+        } else {
+            match amortization {
+                Amortization::Bullet => V::zero(),
+                Amortization::LevelPayment if periodic_coupon == V::zero() => {
+                    performing / V::from_usize(remaining).unwrap()
+                }
+                Amortization::LevelPayment => {
+                    let annuity = periodic_coupon
+                        / (V::one()
+                            - (V::one() + periodic_coupon)
+                                .powi(-i32::try_from(remaining).unwrap_or(i32::MAX)));
+                    performing * annuity - interest
+                }
+            }
+        };
+        let prepayment =
+            (performing - scheduled_principal) * assumptions.prepayment.periodic_rate(i, frequency);
+        let ending_balance = performing - scheduled_principal - prepayment;
+        periods.push(PoolPeriod {
+            payment_date,
+            beginning_balance,
+            default,
+            interest,
+            scheduled_principal,
+            prepayment,
+            recovery: default * (V::one() - severity),
+            loss: default * severity,
+            ending_balance,
+        });
+        beginning_balance = ending_balance;
+    }
+    Ok(periods)
+}
+
+/// Converts the projected periods of a pool into cash flows in `currency` for [`value`].
+#[must_use]
+pub fn pool_cash_flows<'a, V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    periods: &[PoolPeriod<V>],
+    currency: &'a str,
+) -> Vec<CashFlow<'a, D, I>> {
+    periods
+        .iter()
+        .map(|period| CashFlow {
+            payment_date: period.payment_date,
+            amount: period.cash_flow(),
+            currency,
+            credit_curve: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    fn flat(valuation_date: Date, rate: f64) -> YieldCurve<Act365, Linear<f64>> {
+        YieldCurve::new(
+            valuation_date,
+            &[
+                Date::from_ymd(2024, 1, 3).unwrap(),
+                Date::from_ymd(2034, 1, 2).unwrap(),
+            ],
+            &[rate, rate],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_value() {
+        let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let one_year = Date::from_ymd(2025, 1, 1).unwrap();
+        let curves = DiscountCurves::new(flat(valuation_date, 0.05))
+            .with_collateral_curve("EUR", flat(valuation_date, 0.03));
+        let hazard = flat(valuation_date, 0.02);
+        let flows = vec![
+            CashFlow {
+                payment_date: valuation_date,
+                amount: 1.0,
+                currency: "USD",
+                credit_curve: None,
+            },
+            CashFlow {
+                payment_date: one_year,
+                amount: 100.0,
+                currency: "USD",
+                credit_curve: Some(&hazard),
+            },
+            CashFlow {
+                payment_date: one_year,
+                amount: 50.0,
+                currency: "eur",
+                credit_curve: None,
+            },
+        ];
+        let valuation = value(
+            flows,
+            &MarketSnapshot {
+                valuation_date,
+                discount_curves: &curves,
+            },
+        )
+        .unwrap();
+        // the flow paid on the valuation date is left out
+        assert_eq!(valuation.flows.len(), 2);
+        assert!((valuation.flows[0].survival_probability - (-0.02_f64).exp()).abs() < 1e-12);
+        assert!((valuation.present_value("usd") - 100.0 * (-0.07_f64).exp()).abs() < 1e-10);
+        let by_currency = valuation.present_value_by_currency();
+        assert_eq!(by_currency.len(), 2);
+        assert!((by_currency["EUR"] - 50.0 * (-0.03_f64).exp()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_compress() {
+        let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let one_year = Date::from_ymd(2025, 1, 1).unwrap();
+        let two_years = Date::from_ymd(2026, 1, 1).unwrap();
+        let curves = DiscountCurves::new(flat(valuation_date, 0.05));
+        let hazard = flat(valuation_date, 0.02);
+        let flow = |payment_date, currency, credit_curve| CashFlow {
+            payment_date,
+            amount: 10.0,
+            currency,
+            credit_curve,
+        };
+        let flows = || {
+            vec![
+                flow(two_years, "usd", None),
+                flow(one_year, "usd", None),
+                flow(one_year, "USD", None),
+                flow(one_year, "usd", Some(&hazard)),
+                flow(one_year, "eur", None),
+                flow(one_year, "usd", Some(&hazard)),
+            ]
+        };
+        let compressed = compress(flows());
+        assert_eq!(compressed.len(), 4);
+        // sorted by payment date, currency and credit curve
+        assert_eq!(compressed[0].currency, "eur");
+        assert!((compressed[1].amount - 20.0).abs() < f64::EPSILON);
+        assert!((compressed[2].amount - 20.0).abs() < f64::EPSILON);
+        assert_eq!(compressed[3].payment_date, two_years);
+
+        let market = MarketSnapshot {
+            valuation_date,
+            discount_curves: &curves,
+        };
+        let expected = value(flows(), &market).unwrap().present_value_by_currency();
+        let actual = value(compressed, &market)
+            .unwrap()
+            .present_value_by_currency();
+        for (currency, present_value) in actual {
+            assert!((present_value - expected[&currency]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_project_pool() {
+        let payment_dates: Vec<_> = (1..=4)
+            .map(|quarter| Date::from_ymd(2024, 3 * quarter, 1).unwrap())
+            .collect();
+        let assumptions = PoolAssumptions::new(
+            RateVector::Periodic(vec![0.1, 0.2]),
+            RateVector::Periodic(vec![0.0, 0.05]),
+            vec![0.5],
+        )
+        .unwrap();
+        let periods = project_pool(
+            100.0_f64,
+            0.04,
+            &payment_dates,
+            Frequency::Q,
+            Amortization::Bullet,
+            &assumptions,
+        )
+        .unwrap();
+        assert!((periods[0].interest - 1.0).abs() < 1e-12);
+        assert!((periods[0].prepayment - 10.0).abs() < 1e-12);
+        // the last rates of the vectors carry over to later periods
+        assert!((periods[1].default - 4.5).abs() < 1e-12);
+        assert!((periods[1].recovery - 2.25).abs() < 1e-12);
+        assert!((periods[1].prepayment - 0.2 * 85.5).abs() < 1e-12);
+        assert!((periods[2].default - 0.05 * 68.4).abs() < 1e-12);
+        assert!(periods[3].ending_balance.abs() < 1e-12);
+        assert!(
+            (periods[3].scheduled_principal - periods[3].beginning_balance * 0.95).abs() < 1e-12
+        );
+
+        // a constant 10% CDR is a quarterly default rate of 1 - 0.9^(1/4)
+        let annual = RateVector::Annual(vec![0.1_f64]);
+        let rate = annual.periodic_rate(3, Frequency::Q);
+        assert!(((1.0 - rate).powi(4) - 0.9).abs() < 1e-12);
+
+        let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let curves = DiscountCurves::new(flat(valuation_date, 0.0));
+        let flows: Vec<CashFlow<Act365, Linear<f64>>> = pool_cash_flows(&periods, "USD");
+        let valuation = value(
+            flows,
+            &MarketSnapshot {
+                valuation_date,
+                discount_curves: &curves,
+            },
+        )
+        .unwrap();
+        let losses: f64 = periods.iter().map(|period| period.loss).sum();
+        let interest: f64 = periods.iter().map(|period| period.interest).sum();
+        assert!((valuation.present_value("USD") - (100.0 - losses + interest)).abs() < 1e-9);
+
+        assert!(PoolAssumptions::new(
+            RateVector::Periodic(vec![]),
+            RateVector::Periodic(vec![0.0]),
+            vec![0.5]
+        )
+        .is_err());
+        assert!(PoolAssumptions::new(
+            RateVector::Periodic(vec![0.1]),
+            RateVector::Annual(vec![1.5]),
+            vec![0.5]
+        )
+        .is_err());
+        assert!(project_pool(
+            100.0,
+            0.04,
+            &[],
+            Frequency::Q,
+            Amortization::Bullet,
+            &assumptions
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_termstructure::report::Table;
+use qlab_time::date_rolling::DateRolling;
+use qlab_time::registry::{DayCountFn, Registry, SharedCalendar};
+
+/// A day count, calendar and rolling convention, named as in the registries of [`Registry`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConventionSet {
+    pub day_count: String,
+    pub calendar: String,
+    pub rolling: String,
+}
+
+impl ConventionSet {
+    #[must_use]
+    pub fn new(day_count: &str, calendar: &str, rolling: &str) -> Self {
+        Self {
+            day_count: day_count.to_string(),
+            calendar: calendar.to_string(),
+            rolling: rolling.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_day_count(mut self, day_count: &str) -> Self {
+        self.day_count = day_count.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn with_calendar(mut self, calendar: &str) -> Self {
+        self.calendar = calendar.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn with_rolling(mut self, rolling: &str) -> Self {
+        self.rolling = rolling.to_string();
+        self
+    }
+
+    /// Returns the names joined by spaces, e.g. `ACT/360 TARGET MF`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!("{} {} {}", self.day_count, self.calendar, self.rolling)
+    }
+}
+
+/// The conventions of a [`ConventionSet`] resolved from the standard registries.
+#[derive(Clone)]
+pub struct Conventions<V> {
+    pub day_count: DayCountFn<V>,
+    pub calendar: SharedCalendar,
+    pub rolling: DateRolling,
+}
+
+impl<V: Value> Conventions<V> {
+    /// Resolves the names of `set` from [`Registry::day_counts`], [`Registry::calendars`]
+    /// and [`Registry::date_rollings`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a name is not registered.
+    pub fn resolve(set: &ConventionSet) -> QLabResult<Self> {
+        Ok(Self {
+            day_count: *Registry::<DayCountFn<V>>::day_counts().try_get(&set.day_count)?,
+            calendar: Registry::calendars().try_get(&set.calendar)?.clone(),
+            rolling: *Registry::date_rollings().try_get(&set.rolling)?,
+        })
+    }
+}
+
+/// The value under alternative conventions and its change from the value under the base conventions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConventionImpact<V> {
+    pub conventions: ConventionSet,
+    pub value: V,
+    pub impact: V,
+}
+
+/// The values of a curve, an instrument or a portfolio under alternative conventions,
+/// quantifying the risk of a counterparty assuming different conventions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConventionRisk<V> {
+    base: ConventionSet,
+    base_value: V,
+    impacts: Vec<ConventionImpact<V>>,
+}
+
+impl<V: Value> ConventionRisk<V> {
+    #[must_use]
+    pub fn base(&self) -> &ConventionSet {
+        &self.base
+    }
+
+    #[must_use]
+    pub fn base_value(&self) -> V {
+        self.base_value
+    }
+
+    #[must_use]
+    pub fn impacts(&self) -> &[ConventionImpact<V>] {
+        &self.impacts
+    }
+
+    /// Returns the alternative with the largest absolute impact, or `None` if no alternative was given.
+    #[must_use]
+    pub fn largest_impact(&self) -> Option<&ConventionImpact<V>> {
+        self.impacts.iter().max_by(|a, b| {
+            a.impact
+                .abs()
+                .partial_cmp(&b.impact.abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+
+    /// Builds a table with a row for the base conventions followed by a row per alternative;
+    /// values and impacts are printed with 4 decimals.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn report(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&["conventions", "value", "impact"]);
+        table.push_row(vec![
+            self.base.label(),
+            format!("{:.4?}", self.base_value),
+            String::new(),
+        ])?;
+        for impact in &self.impacts {
+            table.push_row(vec![
+                impact.conventions.label(),
+                format!("{:.4?}", impact.value),
+                format!("{:.4?}", impact.impact),
+            ])?;
+        }
+        Ok(table)
+    }
+}
+
+/// Reprices with `value` under the `base` conventions and under each of `alternatives`.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::convention_risk::{convention_risk, ConventionSet};
+/// use qlab_time::date::Date;
+///
+/// let start = Date::from_ymd(2024, 1, 1).unwrap();
+/// let end = Date::from_ymd(2024, 7, 1).unwrap();
+/// let base = ConventionSet::new("ACT/360", "TARGET", "F");
+/// let risk = convention_risk(&base, &[base.clone().with_day_count("30/360")], |conventions| {
+///     let fraction: f64 = (conventions.day_count)(start, end)?;
+///     Ok(1_000_000.0 * 0.05 * fraction)
+/// })
+/// .unwrap();
+/// // 182 days against 180 days
+/// assert!((risk.impacts()[0].impact + 1_000_000.0 * 0.05 * 2.0 / 360.0).abs() < 1e-9);
+/// ```
+///
+/// # Errors
+/// Returns an `InvalidInput` error if a name is not registered, or an error if `value` fails.
+pub fn convention_risk<V: Value>(
+    base: &ConventionSet,
+    alternatives: &[ConventionSet],
+    value: impl Fn(&Conventions<V>) -> QLabResult<V>,
+) -> QLabResult<ConventionRisk<V>> {
+    let base_value = value(&Conventions::resolve(base)?)?;
+    let impacts = alternatives
+        .iter()
+        .map(|conventions| {
+            let alternative = value(&Conventions::resolve(conventions)?)?;
+            Ok(ConventionImpact {
+                conventions: conventions.clone(),
+                value: alternative,
+                impact: alternative - base_value,
+            })
+        })
+        .collect::<QLabResult<_>>()?;
+    Ok(ConventionRisk {
+        base: base.clone(),
+        base_value,
+        impacts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_error::ComputeError::InvalidInput;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_termstructure::yield_curve::YieldCurve;
+    use qlab_time::date::Date;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::frequency::Frequency;
+    use qlab_time::schedule::{generate, StubPolicy};
+
+    #[test]
+    fn test_fixed_leg_convention_risk() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let curve: YieldCurve<Act365, Linear<f64>> = YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2024, 1, 3).unwrap(),
+                Date::from_ymd(2030, 1, 3).unwrap(),
+            ],
+            &[0.04, 0.045],
+        )
+        .unwrap();
+        // a five-year annual fixed leg whose dates fall on Christmas Day
+        let value = |conventions: &Conventions<f64>| {
+            let periods = generate(
+                Date::from_ymd(2024, 12, 25).unwrap(),
+                Date::from_ymd(2029, 12, 25).unwrap(),
+                Frequency::A,
+                &*conventions.calendar,
+                conventions.rolling,
+                StubPolicy::ShortFront,
+                false,
+            )?;
+            periods.iter().try_fold(0.0, |acc, period| {
+                let fraction = (conventions.day_count)(period.accrual_start, period.accrual_end)?;
+                Ok(acc
+                    + 1e6
+                        * 0.05
+                        * fraction
+                        * curve.discount_factor(settle_date, period.payment_date)?)
+            })
+        };
+        let base = ConventionSet::new("ACT/360", "TARGET", "MF");
+        let alternatives = [
+            base.clone().with_day_count("ACT/365"),
+            base.clone().with_calendar("WEEKENDS"),
+            base.clone().with_rolling("P"),
+        ];
+        let risk = convention_risk(&base, &alternatives, value).unwrap();
+        assert_eq!(risk.impacts().len(), 3);
+        assert!(risk.impacts()[0].impact < 0.0);
+        assert!(risk
+            .impacts()
+            .iter()
+            .all(|impact| impact.impact.abs() > 0.0));
+        assert_eq!(
+            risk.largest_impact().unwrap().conventions.day_count,
+            "ACT/365"
+        );
+        assert_eq!(risk.report().unwrap().rows().len(), 4);
+        assert!(convention_risk(&base, &[base.clone().with_calendar("MARS")], value).is_err());
+    }
+
+    #[test]
+    fn test_errors_and_edges() {
+        let start = Date::from_ymd(2024, 1, 1).unwrap();
+        let end = Date::from_ymd(2024, 7, 1).unwrap();
+        let accrual = |conventions: &Conventions<f64>| (conventions.day_count)(start, end);
+        let base = ConventionSet::new("ACT/360", "TARGET", "F");
+        assert_eq!(base.label(), "ACT/360 TARGET F");
+
+        // every name is resolved, for the base as well as the alternatives
+        assert!(Conventions::<f64>::resolve(&base.clone().with_day_count("ACT/999")).is_err());
+        assert!(Conventions::<f64>::resolve(&base.clone().with_rolling("SIDEWAYS")).is_err());
+        assert!(convention_risk(&base.clone().with_calendar("MARS"), &[], accrual).is_err());
+
+        // errors of the pricer are propagated
+        let failing = |conventions: &Conventions<f64>| {
+            if conventions.rolling == DateRolling::Preceding {
+                Err(InvalidInput("no price".into()).into())
+            } else {
+                accrual(conventions)
+            }
+        };
+        assert!(convention_risk(&base, &[base.clone().with_rolling("P")], failing).is_err());
+
+        // without alternatives, or with the base itself, there is no impact
+        let risk = convention_risk(&base, &[], accrual).unwrap();
+        assert!(risk.largest_impact().is_none());
+        assert_eq!(risk.base(), &base);
+        assert!((risk.base_value() - 182.0 / 360.0).abs() < 1e-15);
+        assert_eq!(risk.report().unwrap().rows().len(), 1);
+        let risk = convention_risk(&base, std::slice::from_ref(&base), accrual).unwrap();
+        assert!(risk.impacts()[0].impact.abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,119 @@
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_termstructure::report::Table;
+use qlab_time::date::Date;
+use std::fmt;
+
+/// Where the rate of a coupon comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateSource {
+    /// The fixed rate of the instrument.
+    Fixed,
+    /// Fixings published before the valuation date.
+    Historical,
+    /// Forward rates projected from the projection curve.
+    Projected,
+    /// Published fixings compounded with the rates projected for the rest of the period.
+    Mixed,
+}
+
+impl fmt::Display for RateSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fixed => "fixed",
+            Self::Historical => "historical",
+            Self::Projected => "projected",
+            Self::Mixed => "mixed",
+        })
+    }
+}
+
+/// A coupon of a [`CouponReport`], with the amount and present value signed from the side of the holder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CouponRow<V> {
+    pub accrual_start: Date,
+    pub accrual_end: Date,
+    pub payment_date: Date,
+    /// The first fixing date of a floating coupon, `None` for a fixed coupon.
+    pub fixing_date: Option<Date>,
+    pub source: RateSource,
+    /// The fixed rate, or the index rate of the period, simply compounded over the accrual fraction.
+    pub rate: V,
+    /// The margin paid over the index rate, zero for a fixed coupon.
+    pub spread: V,
+    pub accrual_fraction: V,
+    pub notional: V,
+    pub amount: V,
+    pub discount_factor: V,
+    pub present_value: V,
+}
+
+/// The coupons paid after a valuation date, one row per coupon, for reconciling a valuation coupon by coupon.
+///
+/// Notional and principal exchanges are not included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CouponReport<V> {
+    rows: Vec<CouponRow<V>>,
+}
+
+impl<V: Value> CouponReport<V> {
+    /// Creates a report of `rows`, sorted by payment date.
+    #[must_use]
+    pub fn new(mut rows: Vec<CouponRow<V>>) -> Self {
+        rows.sort_by_key(|row| row.payment_date);
+        Self { rows }
+    }
+
+    #[must_use]
+    pub fn rows(&self) -> &[CouponRow<V>] {
+        &self.rows
+    }
+
+    /// Returns the sum of the present values of the coupons.
+    #[must_use]
+    pub fn present_value(&self) -> V {
+        self.rows
+            .iter()
+            .fold(V::zero(), |acc, row| acc + row.present_value)
+    }
+
+    /// Builds a table of the coupons; rates and discount factors are printed with 8 decimals
+    /// and amounts with 2.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn table(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&[
+            "accrual start",
+            "accrual end",
+            "payment date",
+            "fixing date",
+            "source",
+            "rate",
+            "spread",
+            "accrual fraction",
+            "notional",
+            "amount",
+            "discount factor",
+            "present value",
+        ]);
+        for row in &self.rows {
+            table.push_row(vec![
+                row.accrual_start.to_string(),
+                row.accrual_end.to_string(),
+                row.payment_date.to_string(),
+                row.fixing_date
+                    .map_or_else(String::new, |date| date.to_string()),
+                row.source.to_string(),
+                format!("{:.8?}", row.rate),
+                format!("{:.8?}", row.spread),
+                format!("{:.8?}", row.accrual_fraction),
+                format!("{:.2?}", row.notional),
+                format!("{:.2?}", row.amount),
+                format!("{:.8?}", row.discount_factor),
+                format!("{:.2?}", row.present_value),
+            ])?;
+        }
+        Ok(table)
+    }
+}
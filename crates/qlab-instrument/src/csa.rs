@@ -0,0 +1,115 @@
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::discount_curves::DiscountCurves;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::day_count::DayCount;
+
+/// The terms of a credit support annex (CSA) governing the collateralization of a trade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Csa<V> {
+    collateral_currency: String,
+    threshold: V,
+}
+
+impl<V: Value> Csa<V> {
+    /// Creates a CSA under which the exposure is fully collateralized in cash of `collateral_currency`.
+    #[must_use]
+    pub fn new(collateral_currency: &str) -> Self {
+        Self {
+            collateral_currency: collateral_currency.to_uppercase(),
+            threshold: V::zero(),
+        }
+    }
+
+    /// Sets the exposure below which no collateral is posted.
+    #[must_use]
+    pub fn with_threshold(mut self, threshold: V) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    #[must_use]
+    pub fn collateral_currency(&self) -> &str {
+        &self.collateral_currency
+    }
+
+    #[must_use]
+    pub fn threshold(&self) -> V {
+        self.threshold
+    }
+
+    /// Returns whether the exposure is collateralized from the first unit, i.e. the threshold is zero.
+    #[must_use]
+    pub fn is_fully_collateralized(&self) -> bool {
+        self.threshold <= V::zero()
+    }
+}
+
+/// Selects the curve discounting the cash flows of a trade under `csa`.
+///
+/// Trades without a CSA, or whose CSA has a positive threshold, are discounted on the funding curve;
+/// fully collateralized trades are discounted on the curve of their collateral currency.
+///
+/// # Errors
+/// Returns an error if `curves` holds no curve for the collateral currency of a fully collateralized trade.
+pub fn select_discount_curve<'a, V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    csa: Option<&Csa<V>>,
+    curves: &'a DiscountCurves<D, I>,
+) -> QLabResult<&'a YieldCurve<D, I>> {
+    match csa {
+        Some(csa) if csa.is_fully_collateralized() => {
+            curves.try_collateral(csa.collateral_currency())
+        }
+        _ => Ok(curves.funding()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::semi_annual_bond;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::date::Date;
+    use qlab_time::day_count::act_365::Act365;
+
+    fn flat_curve(settle_date: Date, rate: f64) -> YieldCurve<Act365, Linear<f64>> {
+        YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2023, 10, 11).unwrap(),
+                Date::from_ymd(2033, 10, 10).unwrap(),
+            ],
+            &[rate, rate],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_discounted_value_with() {
+        let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let curves = DiscountCurves::new(flat_curve(settle_date, 0.05))
+            .with_collateral_curve("usd", flat_curve(settle_date, 0.04));
+        let bond =
+            semi_annual_bond("bond", Date::from_ymd(2028, 11, 7).unwrap(), 0.05, 100.0).unwrap();
+        let unsecured = bond.discounted_value_with(settle_date, &curves).unwrap();
+        let bond = bond.with_csa(Csa::new("USD"));
+        let collateralized = bond.discounted_value_with(settle_date, &curves).unwrap();
+        assert!(
+            (unsecured
+                - bond
+                    .discounted_value(settle_date, curves.funding())
+                    .unwrap())
+            .abs()
+                < f64::EPSILON
+        );
+        assert!(collateralized > unsecured);
+
+        let bond = bond.with_csa(Csa::new("USD").with_threshold(1_000_000.0));
+        let partially = bond.discounted_value_with(settle_date, &curves).unwrap();
+        assert!((partially - unsecured).abs() < f64::EPSILON);
+
+        let bond = bond.with_csa(Csa::new("EUR"));
+        assert!(bond.discounted_value_with(settle_date, &curves).is_err());
+    }
+}
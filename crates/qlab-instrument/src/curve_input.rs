@@ -0,0 +1,426 @@
+use crate::quote::{Quote, QuoteKind};
+use crate::sofr_future::SofrFuture;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_termstructure::report::Table;
+use qlab_time::date::Date;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A two-way market quote of a curve instrument as captured from a feed, with each side in its quote convention.
+#[derive(Debug, Clone)]
+pub struct MarketQuote<V> {
+    /// The name of the instrument, e.g. `USD-SOFR-5Y`.
+    pub label: String,
+    /// The maturity of the instrument, which becomes its pillar.
+    pub maturity: Date,
+    pub bid: Quote<V>,
+    pub ask: Quote<V>,
+    /// The date the quote was last updated.
+    pub as_of: Date,
+}
+
+impl<V: Value> MarketQuote<V> {
+    /// Returns `true` if the bid is above the ask, comparing two prices as quoted and other quotes
+    /// by their decimal rates.
+    #[must_use]
+    pub fn is_crossed(&self) -> bool {
+        match (self.bid.kind(), self.ask.kind()) {
+            (QuoteKind::Price, QuoteKind::Price) => self.bid.value() > self.ask.value(),
+            _ => self.bid.decimal_rate() > self.ask.decimal_rate(),
+        }
+    }
+}
+
+/// A problem found in the inputs of a curve before it is bootstrapped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputWarning<V> {
+    /// The bid is above the ask.
+    Crossed { label: String, bid: V, ask: V },
+    /// The quote was last updated `age` calendar days before the valuation date.
+    Stale {
+        label: String,
+        as_of: Date,
+        age: i64,
+    },
+    /// The quote is dated after the valuation date, so it cannot have been observed yet.
+    FutureDated {
+        label: String,
+        as_of: Date,
+        valuation_date: Date,
+    },
+    /// Several instruments mature on the same date and would share a pillar.
+    DuplicateMaturity { maturity: Date, labels: Vec<String> },
+    /// The rate implied by the future starting on `reference_start` is a peak or a trough of the strip,
+    /// deviating from both neighbours in the same direction by more than the tolerance.
+    NonMonotoneFutures {
+        reference_start: Date,
+        rate: V,
+        previous_rate: V,
+        next_rate: V,
+    },
+}
+
+impl<V: Value> InputWarning<V> {
+    /// Returns `true` if the bootstrap cannot use the inputs as they are: crossed quotes have no mid
+    /// to fit, quotes dated after the valuation date leak later data and duplicate maturities make the pillars
+    /// ambiguous. Stale quotes and kinks in a futures strip are priced but deserve a look.
+    #[must_use]
+    pub fn is_blocking(&self) -> bool {
+        matches!(
+            self,
+            Self::Crossed { .. } | Self::FutureDated { .. } | Self::DuplicateMaturity { .. }
+        )
+    }
+}
+
+impl<V: Value> fmt::Display for InputWarning<V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Crossed { label, bid, ask } => {
+                write!(f, "{label}: bid {bid:?} is above ask {ask:?}")
+            }
+            Self::Stale { label, as_of, age } => {
+                write!(f, "{label}: last updated on {as_of}, {age} days old")
+            }
+            Self::FutureDated {
+                label,
+                as_of,
+                valuation_date,
+            } => write!(f, "{label}: dated {as_of}, after the valuation on {valuation_date}"),
+            Self::DuplicateMaturity { maturity, labels } => {
+                write!(f, "{} all mature on {maturity}", labels.join(", "))
+            }
+            Self::NonMonotoneFutures {
+                reference_start,
+                rate,
+                previous_rate,
+                next_rate,
+            } => write!(
+                f,
+                "the future from {reference_start} implies {rate:?} between {previous_rate:?} and {next_rate:?}"
+            ),
+        }
+    }
+}
+
+/// Checks the market data of a curve before it is bootstrapped, so that bad quotes are reported
+/// instead of silently distorting the curve.
+///
+/// The checks collect every problem as an [`InputWarning`] rather than stopping at the first one;
+/// [`InputChecks::into_result`] turns the blocking ones into an error.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::curve_input::{CurveInputValidator, MarketQuote};
+/// use qlab_instrument::quote::{Quote, QuoteScale};
+/// use qlab_instrument::sofr_future::SofrFuture;
+/// use qlab_time::date::Date;
+///
+/// let valuation_date = Date::from_ymd(2024, 3, 20).unwrap();
+/// let quote = |label: &str, year, bid, ask, as_of| MarketQuote {
+///     label: label.to_string(),
+///     maturity: Date::from_ymd(year, 3, 20).unwrap(),
+///     bid: Quote::rate(bid, QuoteScale::Percent),
+///     ask: Quote::rate(ask, QuoteScale::Percent),
+///     as_of,
+/// };
+/// let quotes = [
+///     quote("2Y", 2026, 4.51_f64, 4.49, valuation_date),
+///     quote("5Y", 2029, 4.01, 4.03, Date::from_ymd(2024, 3, 1).unwrap()),
+/// ];
+/// let futures = [
+///     SofrFuture::three_month(2024, 6, 95.0).unwrap(),
+///     SofrFuture::three_month(2024, 9, 95.3).unwrap(),
+///     SofrFuture::three_month(2024, 12, 95.2).unwrap(),
+/// ];
+/// let validator = CurveInputValidator::new(valuation_date).with_futures_tolerance(0.0005);
+/// let checks = validator.check_quotes(&quotes).merge(validator.check_futures(&futures));
+/// // the 2Y is crossed, the 5Y is stale and the September future sticks out
+/// assert_eq!(checks.warnings().len(), 3);
+/// assert_eq!(checks.blocking().len(), 1);
+/// assert!(checks.into_result().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveInputValidator<V> {
+    valuation_date: Date,
+    max_age: i64,
+    futures_tolerance: V,
+}
+
+impl<V: Value> CurveInputValidator<V> {
+    /// Creates a validator accepting quotes updated on the valuation date or the day before,
+    /// and flagging any reversal of the rates of a futures strip.
+    #[must_use]
+    pub fn new(valuation_date: Date) -> Self {
+        Self {
+            valuation_date,
+            max_age: 1,
+            futures_tolerance: V::zero(),
+        }
+    }
+
+    /// Sets the number of calendar days after which a quote is stale, e.g. 3 to accept Friday quotes on a Monday.
+    #[must_use]
+    pub fn with_max_age(mut self, days: i64) -> Self {
+        self.max_age = days;
+        self
+    }
+
+    /// Sets by how much, as a decimal rate, a future may stick out of its neighbours before it is flagged.
+    #[must_use]
+    pub fn with_futures_tolerance(mut self, tolerance: V) -> Self {
+        self.futures_tolerance = tolerance;
+        self
+    }
+
+    #[must_use]
+    pub fn valuation_date(&self) -> Date {
+        self.valuation_date
+    }
+
+    #[must_use]
+    pub fn max_age(&self) -> i64 {
+        self.max_age
+    }
+
+    #[must_use]
+    pub fn futures_tolerance(&self) -> V {
+        self.futures_tolerance
+    }
+
+    /// Flags crossed, stale and future-dated quotes, and quotes maturing on the same date.
+    #[must_use]
+    pub fn check_quotes(&self, quotes: &[MarketQuote<V>]) -> InputChecks<V> {
+        let mut warnings = Vec::new();
+        let mut by_maturity: BTreeMap<Date, Vec<String>> = BTreeMap::new();
+        for quote in quotes {
+            if quote.is_crossed() {
+                warnings.push(InputWarning::Crossed {
+                    label: quote.label.clone(),
+                    bid: quote.bid.value(),
+                    ask: quote.ask.value(),
+                });
+            }
+            let age = self.valuation_date - quote.as_of;
+            if age < 0 {
+                warnings.push(InputWarning::FutureDated {
+                    label: quote.label.clone(),
+                    as_of: quote.as_of,
+                    valuation_date: self.valuation_date,
+                });
+            } else if age > self.max_age {
+                warnings.push(InputWarning::Stale {
+                    label: quote.label.clone(),
+                    as_of: quote.as_of,
+                    age,
+                });
+            }
+            by_maturity
+                .entry(quote.maturity)
+                .or_default()
+                .push(quote.label.clone());
+        }
+        warnings.extend(
+            by_maturity
+                .into_iter()
+                .filter(|(_, labels)| labels.len() > 1)
+                .map(|(maturity, labels)| InputWarning::DuplicateMaturity { maturity, labels }),
+        );
+        InputChecks { warnings }
+    }
+
+    /// Flags futures whose implied rates are a peak or a trough of the strip ordered by reference period,
+    /// and futures ending on the same date.
+    #[must_use]
+    pub fn check_futures(&self, futures: &[SofrFuture<V>]) -> InputChecks<V> {
+        let mut futures = futures.to_vec();
+        futures.sort_by_key(SofrFuture::reference_start);
+        let mut warnings: Vec<_> = futures
+            .windows(3)
+            .filter_map(|strip| {
+                let [previous, future, next] = strip else {
+                    return None;
+                };
+                let rate = future.implied_rate();
+                let (previous_rate, next_rate) = (previous.implied_rate(), next.implied_rate());
+                let tolerance = self.futures_tolerance;
+                let peak = rate - previous_rate > tolerance && rate - next_rate > tolerance;
+                let trough = previous_rate - rate > tolerance && next_rate - rate > tolerance;
+                (peak || trough).then(|| InputWarning::NonMonotoneFutures {
+                    reference_start: future.reference_start(),
+                    rate,
+                    previous_rate,
+                    next_rate,
+                })
+            })
+            .collect();
+        let mut by_end: BTreeMap<Date, Vec<String>> = BTreeMap::new();
+        for future in &futures {
+            by_end
+                .entry(future.reference_end())
+                .or_default()
+                .push(format!("{:?} {}", future.kind(), future.reference_start()));
+        }
+        warnings.extend(
+            by_end
+                .into_iter()
+                .filter(|(_, labels)| labels.len() > 1)
+                .map(|(maturity, labels)| InputWarning::DuplicateMaturity { maturity, labels }),
+        );
+        InputChecks { warnings }
+    }
+}
+
+/// The warnings found by a [`CurveInputValidator`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputChecks<V> {
+    warnings: Vec<InputWarning<V>>,
+}
+
+impl<V: Value> InputChecks<V> {
+    #[must_use]
+    pub fn warnings(&self) -> &[InputWarning<V>] {
+        &self.warnings
+    }
+
+    /// Returns `true` if no problem was found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Returns the warnings which prevent the bootstrap, see [`InputWarning::is_blocking`].
+    #[must_use]
+    pub fn blocking(&self) -> Vec<&InputWarning<V>> {
+        self.warnings
+            .iter()
+            .filter(|warning| warning.is_blocking())
+            .collect()
+    }
+
+    /// Appends the warnings of other checks, e.g. of the futures to those of the swap quotes.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.warnings.extend(other.warnings);
+        self
+    }
+
+    /// Builds a table with a row per warning.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn report(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&["blocking", "warning"]);
+        for warning in &self.warnings {
+            table.push_row(vec![warning.is_blocking().to_string(), warning.to_string()])?;
+        }
+        Ok(table)
+    }
+
+    /// Returns the checks if nothing prevents the bootstrap, so that non-blocking warnings can still be logged.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error listing the blocking warnings.
+    pub fn into_result(self) -> QLabResult<Self> {
+        let blocking: Vec<_> = self.blocking().iter().map(ToString::to_string).collect();
+        if blocking.is_empty() {
+            return Ok(self);
+        }
+        Err(InvalidInput(format!("bad curve inputs: {}", blocking.join("; ")).into()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::QuoteScale;
+
+    fn quote(label: &str, maturity: Date, bid: f64, ask: f64, as_of: Date) -> MarketQuote<f64> {
+        MarketQuote {
+            label: label.to_string(),
+            maturity,
+            bid: Quote::rate(bid, QuoteScale::Decimal),
+            ask: Quote::rate(ask, QuoteScale::Decimal),
+            as_of,
+        }
+    }
+
+    #[test]
+    fn test_curve_input_checks() {
+        let monday = Date::from_ymd(2024, 3, 18).unwrap();
+        let friday = Date::from_ymd(2024, 3, 15).unwrap();
+        let maturity = |year| Date::from_ymd(year, 3, 18).unwrap();
+        let quotes = [
+            quote("1Y", maturity(2025), 0.047, 0.0472, monday),
+            quote("2Y", maturity(2026), 0.045, 0.0452, friday),
+            quote("2Y-ALT", maturity(2026), 0.0451, 0.0453, monday),
+        ];
+        let validator = CurveInputValidator::new(monday);
+        let checks = validator.check_quotes(&quotes);
+        assert_eq!(
+            checks.warnings(),
+            &[
+                InputWarning::Stale {
+                    label: "2Y".to_string(),
+                    as_of: friday,
+                    age: 3
+                },
+                InputWarning::DuplicateMaturity {
+                    maturity: maturity(2026),
+                    labels: vec!["2Y".to_string(), "2Y-ALT".to_string()]
+                }
+            ]
+        );
+        assert!(checks.clone().into_result().is_err());
+        // Friday quotes are fine on a Monday once the age allows a weekend
+        let checks = validator.with_max_age(3).check_quotes(&quotes[..2]);
+        assert!(checks.is_clean());
+        assert!(checks.into_result().is_ok());
+
+        // a quote dated after the valuation date blocks, and futures prices are crossed as prices
+        let tuesday = Date::from_ymd(2024, 3, 19).unwrap();
+        let mut future_dated = quote("3Y", maturity(2027), 0.044, 0.0442, tuesday);
+        assert_eq!(
+            validator.check_quotes(&[future_dated.clone()]).warnings(),
+            &[InputWarning::FutureDated {
+                label: "3Y".to_string(),
+                as_of: tuesday,
+                valuation_date: monday
+            }]
+        );
+        future_dated.as_of = monday;
+        future_dated.bid = Quote::price(95.02);
+        future_dated.ask = Quote::price(95.01);
+        let checks = validator.check_quotes(&[future_dated.clone()]);
+        assert!(matches!(
+            checks.warnings(),
+            [InputWarning::Crossed { bid, .. }] if (bid - 95.02).abs() < 1e-12
+        ));
+        future_dated.ask = Quote::price(95.03);
+        assert!(validator.check_quotes(&[future_dated]).is_clean());
+
+        // rates of 5.00%, 4.80%, 4.90%, 4.60%, 4.50%
+        let futures: Vec<_> = [(3, 95.0), (6, 95.2), (9, 95.1), (12, 95.4), (3, 95.5)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(month, price))| {
+                SofrFuture::three_month(if i < 4 { 2024 } else { 2025 }, month, price).unwrap()
+            })
+            .collect();
+        let checks = validator.check_futures(&futures);
+        assert_eq!(checks.warnings().len(), 2);
+        assert!(checks.blocking().is_empty());
+        assert!(checks.report().unwrap().rows()[0][1].contains("2024-06-19"));
+        let tolerant = validator
+            .with_futures_tolerance(0.0015)
+            .check_futures(&futures);
+        assert!(tolerant.is_clean());
+        // two futures ending on the same date share a pillar
+        let same = [futures[0], futures[0]];
+        assert_eq!(validator.check_futures(&same).blocking().len(), 1);
+    }
+}
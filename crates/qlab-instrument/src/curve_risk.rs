@@ -0,0 +1,386 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::report::Table;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::day_count::DayCount;
+
+/// The sensitivity of a bootstrapped curve to the quotes of the instruments it was built from.
+///
+/// The entry in row `i` and column `j` is the change of the spot yield of pillar `j` in basis points
+/// per basis point of the quote of instrument `i`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveJacobian<V> {
+    instruments: Vec<String>,
+    entries: Vec<Vec<V>>,
+}
+
+/// The change in value per basis point of the quote of a curve instrument.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstrumentDelta<V> {
+    pub instrument: String,
+    pub delta: V,
+}
+
+impl<V: Value> CurveJacobian<V> {
+    /// Creates a Jacobian with a row of pillar sensitivities for each instrument.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the numbers of instruments and rows differ,
+    /// or if the rows have different lengths.
+    pub fn new(instruments: Vec<String>, entries: Vec<Vec<V>>) -> QLabResult<Self> {
+        if instruments.len() != entries.len() {
+            return Err(InvalidInput(
+                format!(
+                    "{} instruments are given for {} rows",
+                    instruments.len(),
+                    entries.len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        if let Some(row) = entries.iter().find(|row| row.len() != entries[0].len()) {
+            return Err(InvalidInput(
+                format!(
+                    "a row has {} pillars while the first has {}",
+                    row.len(),
+                    entries[0].len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            instruments,
+            entries,
+        })
+    }
+
+    #[must_use]
+    pub fn instruments(&self) -> &[String] {
+        &self.instruments
+    }
+
+    #[must_use]
+    pub fn entries(&self) -> &[Vec<V>] {
+        &self.entries
+    }
+
+    /// Maps sensitivities to the spot yields of the pillars, per basis point as returned by
+    /// [`node_sensitivities`], into sensitivities to the instrument quotes, per basis point.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the number of sensitivities differs from the number of pillars.
+    pub fn instrument_deltas(
+        &self,
+        node_sensitivities: &[V],
+    ) -> QLabResult<Vec<InstrumentDelta<V>>> {
+        if self
+            .entries
+            .first()
+            .is_some_and(|row| row.len() != node_sensitivities.len())
+        {
+            return Err(InvalidInput(
+                format!(
+                    "{} sensitivities are given for {} pillars",
+                    node_sensitivities.len(),
+                    self.entries[0].len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        Ok(self
+            .instruments
+            .iter()
+            .zip(&self.entries)
+            .map(|(instrument, row)| InstrumentDelta {
+                instrument: instrument.clone(),
+                delta: row
+                    .iter()
+                    .zip(node_sensitivities)
+                    .fold(V::zero(), |acc, (&entry, &sensitivity)| {
+                        acc + entry * sensitivity
+                    }),
+            })
+            .collect())
+    }
+}
+
+/// Calculates the Jacobian of the curve which `rebuild` bootstraps from the instrument `quotes`, labelled by
+/// `instruments`, by rebuilding it with each quote in turn one basis point higher,
+/// e.g. for a curve bootstrapped from swap par rates.
+///
+/// Each bumped rebuild starts from a copy of `quotes`, so no quote drifts from rounding a bump back out.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the numbers of instruments and quotes differ,
+/// or an error if `rebuild` fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+pub fn rebuilt_jacobian<V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    instruments: Vec<String>,
+    quotes: &[V],
+    rebuild: impl Fn(&[V]) -> QLabResult<YieldCurve<D, I>>,
+) -> QLabResult<CurveJacobian<V>> {
+    if instruments.len() != quotes.len() {
+        return Err(InvalidInput(
+            format!(
+                "{} instruments are given for {} quotes",
+                instruments.len(),
+                quotes.len()
+            )
+            .into(),
+        )
+        .into());
+    }
+    let basis_point = V::from_f64(1e-4).unwrap();
+    let yields = |curve: YieldCurve<D, I>| -> Vec<V> {
+        curve
+            .pillars()
+            .iter()
+            .map(|&(_, spot_yield)| spot_yield)
+            .collect()
+    };
+    let base = yields(rebuild(quotes)?);
+    let entries = (0..quotes.len())
+        .map(|i| {
+            let mut bumped = quotes.to_vec();
+            bumped[i] += basis_point;
+            Ok(yields(rebuild(&bumped)?)
+                .iter()
+                .zip(&base)
+                .map(|(&bumped, &base)| (bumped - base) / basis_point)
+                .collect())
+        })
+        .collect::<QLabResult<_>>()?;
+    CurveJacobian::new(instruments, entries)
+}
+
+/// Calculates the change of `value` per basis point of the spot yield of each pillar of `curve`,
+/// by central differences.
+///
+/// # Errors
+/// Returns an error if a bumped curve cannot be built or `value` fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+pub fn node_sensitivities<V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    curve: &YieldCurve<D, I>,
+    value: impl Fn(&YieldCurve<D, I>) -> QLabResult<V>,
+) -> QLabResult<Vec<V>> {
+    let basis_point = V::from_f64(1e-4).unwrap();
+    let two = V::one() + V::one();
+    curve
+        .pillars()
+        .iter()
+        .map(|&(pillar, _)| {
+            let bumped =
+                |shift: V| curve.try_shifted(|t, y| if t == pillar { y + shift } else { y });
+            let up = value(&bumped(basis_point)?)?;
+            let down = value(&bumped(-basis_point)?)?;
+            Ok((up - down) / two)
+        })
+        .collect()
+}
+
+/// Builds a table of instrument deltas; deltas are printed with 4 decimals.
+///
+/// # Errors
+/// Returns an error if the report cannot be assembled.
+pub fn delta_report<V: Value>(deltas: &[InstrumentDelta<V>]) -> QLabResult<Table> {
+    let mut table = Table::new(&["instrument", "delta per bp"]);
+    for delta in deltas {
+        table.push_row(vec![
+            delta.instrument.clone(),
+            format!("{:.4?}", delta.delta),
+        ])?;
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sofr_future::{SofrCurveBuilder, SofrFuture};
+    use crate::swap::{Swap, SwapDirection};
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_math::optimization::least_squares;
+    use qlab_time::date::Date;
+    use qlab_time::date_rolling::DateRolling;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::day_count::thirty_360::Thirty360;
+    use qlab_time::frequency::Frequency;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_instrument_deltas() {
+        let settlement_date = Date::from_ymd(2024, 3, 20).unwrap();
+        let futures = [
+            SofrFuture::three_month(2024, 3, 94.9).unwrap(),
+            SofrFuture::three_month(2024, 6, 95.1).unwrap(),
+            SofrFuture::three_month(2024, 9, 95.3).unwrap(),
+        ];
+        let fixings = HashMap::new();
+        let mut builder: SofrCurveBuilder<Act365, Linear<f64>> =
+            SofrCurveBuilder::new(settlement_date, 0.0531, &futures, &WeekendsOnly, &fixings)
+                .unwrap();
+        let jacobian = builder.jacobian(&WeekendsOnly, &fixings).unwrap();
+        assert_eq!(jacobian.instruments().len(), 4);
+        // a future does not move the overnight pillar
+        assert!(jacobian.entries()[1][0].abs() < 1e-12);
+        // the bumped prices are restored exactly
+        assert_eq!(builder.futures(), futures.as_slice());
+
+        let payment_date = Date::from_ymd(2024, 12, 18).unwrap();
+        let value = |curve: &YieldCurve<Act365, Linear<f64>>| {
+            Ok(1_000_000.0 * curve.discount_factor(settlement_date, payment_date)?)
+        };
+        let nodes = node_sensitivities(builder.curve(), value).unwrap();
+        let deltas = jacobian.instrument_deltas(&nodes).unwrap();
+
+        // rebuilding the curve with a future quoted 1bp higher in rate moves the value by its delta
+        let base = value(builder.curve()).unwrap();
+        builder
+            .update_price(1, 95.09, &WeekendsOnly, &fixings)
+            .unwrap();
+        let moved = value(builder.curve()).unwrap() - base;
+        assert!((moved - deltas[2].delta).abs() < 1e-2 * moved.abs());
+        assert!(deltas[2].delta < 0.0);
+        assert!(jacobian.instrument_deltas(&nodes[1..]).is_err());
+        assert_eq!(delta_report(&deltas).unwrap().rows().len(), 4);
+    }
+
+    #[test]
+    fn test_swap_quote_deltas() {
+        let ymd = |year, month, day| Date::from_ymd(year, month, day).unwrap();
+        let settle_date = ymd(2024, 1, 2);
+        let swap = |years, fixed_rate| {
+            Swap::new::<Thirty360, Act360>(
+                &format!("{years}Y"),
+                "USD",
+                "SOFR",
+                SwapDirection::Receiver,
+                ymd(2024, 1, 4),
+                ymd(2024 + years, 1, 4),
+                Frequency::A,
+                Frequency::Q,
+                &WeekendsOnly,
+                DateRolling::ModifiedFollowing,
+                fixed_rate,
+                1_000_000.0,
+            )
+            .unwrap()
+        };
+        let swaps = [swap(1, 0.0), swap(2, 0.0), swap(5, 0.0)];
+        let pillars = [
+            ymd(2024, 1, 3),
+            ymd(2025, 1, 10),
+            ymd(2026, 1, 10),
+            ymd(2029, 1, 10),
+        ];
+        // a single curve with the overnight yield pinned and the swap pillars solved to reprice the par rates
+        let rebuild = |quotes: &[f64]| {
+            let curve = |yields: &[f64]| {
+                let spot_yields: Vec<_> = quotes[..1].iter().chain(yields).copied().collect();
+                YieldCurve::<Act365, Linear<f64>>::new(settle_date, &pillars, &spot_yields)
+            };
+            let yields = least_squares(
+                |yields: &[f64]| {
+                    let curve = curve(yields)?;
+                    swaps
+                        .iter()
+                        .zip(&quotes[1..])
+                        .map(|(swap, &quote)| {
+                            Ok(swap.fair_rate(settle_date, &curve, &curve)? - quote)
+                        })
+                        .collect()
+                },
+                &quotes[1..],
+            )?;
+            curve(&yields)
+        };
+        let instruments: Vec<_> = ["ON", "1Y", "2Y", "5Y"].map(String::from).to_vec();
+        let quotes = [0.053, 0.048, 0.045, 0.041];
+        let jacobian = rebuilt_jacobian(instruments.clone(), &quotes, rebuild).unwrap();
+        assert_eq!(jacobian.instruments(), instruments.as_slice());
+        // the overnight quote moves its own pillar one for one, and no quote moves an earlier pillar
+        assert!((jacobian.entries()[0][0] - 1.0).abs() < 1e-9);
+        assert!(jacobian.entries()[3][..3]
+            .iter()
+            .all(|entry| entry.abs() < 1e-9));
+
+        // a 5Y receiver at par loses about its annuity per basis point of the 5Y quote
+        let curve = rebuild(&quotes).unwrap();
+        let receiver = swap(5, 0.041);
+        let value = |curve: &YieldCurve<Act365, Linear<f64>>| {
+            receiver.present_value(settle_date, curve, curve)
+        };
+        let nodes = node_sensitivities(&curve, value).unwrap();
+        let deltas = jacobian.instrument_deltas(&nodes).unwrap();
+        let mut bumped = quotes;
+        bumped[3] += 1e-4;
+        let moved = value(&rebuild(&bumped).unwrap()).unwrap() - value(&curve).unwrap();
+        assert!((moved - deltas[3].delta).abs() < 1e-2 * moved.abs());
+        assert!(deltas[3].delta < 0.0);
+        assert!(deltas[1].delta.abs() < 1e-2 * deltas[3].delta.abs());
+        assert!(rebuilt_jacobian(instruments, &quotes[1..], rebuild).is_err());
+    }
+
+    #[test]
+    fn test_jacobian_errors_and_edges() {
+        let names = |names: &[&str]| names.iter().map(|&name| name.to_string()).collect();
+        assert!(CurveJacobian::new(names(&["1Y"]), vec![vec![1.0], vec![0.0]]).is_err());
+        assert!(CurveJacobian::new(names(&["1Y", "2Y"]), vec![vec![1.0], vec![0.0, 1.0]]).is_err());
+
+        // each delta is the row of its instrument times the node sensitivities
+        let jacobian =
+            CurveJacobian::<f64>::new(names(&["1Y", "2Y"]), vec![vec![1.0, 0.5], vec![0.0, 2.0]])
+                .unwrap();
+        let deltas = jacobian.instrument_deltas(&[-3.0, 4.0]).unwrap();
+        assert_eq!(deltas[0].instrument, "1Y");
+        assert!((deltas[0].delta + 1.0).abs() < f64::EPSILON);
+        assert!((deltas[1].delta - 8.0).abs() < f64::EPSILON);
+        assert!(jacobian.instrument_deltas(&[1.0, 2.0, 3.0]).is_err());
+        let empty = CurveJacobian::<f64>::new(Vec::new(), Vec::new()).unwrap();
+        assert!(empty.instrument_deltas(&[1.0]).unwrap().is_empty());
+        assert!(delta_report::<f64>(&[]).unwrap().rows().is_empty());
+
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let pillars = [
+            Date::from_ymd(2025, 1, 2).unwrap(),
+            Date::from_ymd(2026, 1, 2).unwrap(),
+        ];
+        let rebuild =
+            |quotes: &[f64]| YieldCurve::<Act365, Linear<f64>>::new(settle_date, &pillars, quotes);
+        // a failing rebuild of a bumped curve is reported
+        let failing = |quotes: &[f64]| {
+            if quotes[1] > 0.05 {
+                Err(InvalidInput("no solution".into()).into())
+            } else {
+                rebuild(quotes)
+            }
+        };
+        assert!(rebuilt_jacobian(names(&["1Y", "2Y"]), &[0.04, 0.05], failing).is_err());
+        let jacobian = rebuilt_jacobian(names(&["1Y", "2Y"]), &[0.04, 0.045], rebuild).unwrap();
+        assert!((jacobian.entries()[0][0] - 1.0).abs() < 1e-9);
+        assert!(jacobian.entries()[0][1].abs() < 1e-9);
+
+        // a value independent of the curve has no sensitivity, and a failing value is reported
+        let curve = rebuild(&[0.04, 0.045]).unwrap();
+        let nodes = node_sensitivities(&curve, |_| Ok(1.0)).unwrap();
+        assert!(nodes.iter().all(|node| node.abs() < f64::EPSILON));
+        assert!(node_sensitivities(
+            &curve,
+            |_: &YieldCurve<Act365, Linear<f64>>| -> QLabResult<f64> {
+                Err(InvalidInput("no price".into()).into())
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+
+/// The fair value of an equity index future against its market price, in index points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BasisReport<V> {
+    fair_value: V,
+    market_price: V,
+    gross_basis: V,
+    carry: V,
+    implied_dividend_yield: V,
+}
+
+impl<V: Value> BasisReport<V> {
+    #[must_use]
+    pub fn fair_value(&self) -> V {
+        self.fair_value
+    }
+
+    #[must_use]
+    pub fn market_price(&self) -> V {
+        self.market_price
+    }
+
+    /// Returns the market price less the spot index level.
+    #[must_use]
+    pub fn gross_basis(&self) -> V {
+        self.gross_basis
+    }
+
+    /// Returns the fair value less the spot index level, the financing cost net of the dividends.
+    #[must_use]
+    pub fn carry(&self) -> V {
+        self.carry
+    }
+
+    /// Returns the market price less the fair value, positive when the future trades rich.
+    #[must_use]
+    pub fn net_basis(&self) -> V {
+        self.market_price - self.fair_value
+    }
+
+    /// Returns the continuously compounded dividend yield which reprices the future to its market price.
+    #[must_use]
+    pub fn implied_dividend_yield(&self) -> V {
+        self.implied_dividend_yield
+    }
+}
+
+/// A future on an equity index expiring on `expiry`, quoted in index points with a currency `multiplier` per point.
+///
+/// The fair value is the forward of the index, `S · Q(T) / P(T)`, where `P` discounts on the funding curve and `Q`
+/// is the discount factor of a dividend curve, a [`YieldCurve`] whose spot yields are the continuously compounded
+/// dividend yields of the index.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::equity_future::EquityIndexFuture;
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let pillars = [Date::from_ymd(2024, 1, 2).unwrap(), Date::from_ymd(2025, 1, 2).unwrap()];
+/// let discount: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(valuation_date, &pillars, &[0.05, 0.05]).unwrap();
+/// let dividend: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(valuation_date, &pillars, &[0.015, 0.015]).unwrap();
+///
+/// let future = EquityIndexFuture::new("ESH4", Date::from_ymd(2024, 3, 15).unwrap(), 50.0, 4_800.0).unwrap();
+/// let report = future
+///     .basis_report(valuation_date, 4_770.0, &discount, &dividend)
+///     .unwrap();
+/// assert!((report.carry() - 33.51).abs() < 0.01);
+/// // the future trades below its fair value, as if the index paid higher dividends
+/// assert!(report.net_basis() < 0.0);
+/// assert!(report.implied_dividend_yield() > 0.015);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityIndexFuture<V> {
+    contract_id: String,
+    expiry: Date,
+    multiplier: V,
+    price: V,
+}
+
+impl<V: Value> EquityIndexFuture<V> {
+    /// Creates a future expiring on `expiry` quoted at the market `price`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `multiplier` or `price` is not positive.
+    pub fn new(
+        contract_id: impl Into<String>,
+        expiry: Date,
+        multiplier: V,
+        price: V,
+    ) -> QLabResult<Self> {
+        if multiplier <= V::zero() {
+            return Err(InvalidInput(
+                format!("multiplier: {multiplier:?} must be positive").into(),
+            )
+            .into());
+        }
+        if price <= V::zero() {
+            return Err(InvalidInput(format!("price: {price:?} must be positive").into()).into());
+        }
+        Ok(Self {
+            contract_id: contract_id.into(),
+            expiry,
+            multiplier,
+            price,
+        })
+    }
+
+    #[must_use]
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    #[must_use]
+    pub fn expiry(&self) -> Date {
+        self.expiry
+    }
+
+    #[must_use]
+    pub fn multiplier(&self) -> V {
+        self.multiplier
+    }
+
+    #[must_use]
+    pub fn price(&self) -> V {
+        self.price
+    }
+
+    /// Calculates the fair value of the future from the `spot` level of the index on `valuation_date`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the future has expired before `valuation_date`, or an error if a discount
+    /// factor calculation fails.
+    pub fn fair_value<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        valuation_date: Date,
+        spot: V,
+        discount: &YieldCurve<D, I>,
+        dividend: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        if self.expiry < valuation_date {
+            return Err(InvalidInput(
+                format!(
+                    "{} expired on {} before {valuation_date}",
+                    self.contract_id, self.expiry
+                )
+                .into(),
+            )
+            .into());
+        }
+        Ok(
+            spot * dividend.discount_factor(valuation_date, self.expiry)?
+                / discount.discount_factor(valuation_date, self.expiry)?,
+        )
+    }
+
+    /// Compares the market price of the future with its fair value.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`EquityIndexFuture::fair_value`], or an `InvalidInput` error
+    /// if `spot` is not positive or the future expires on `valuation_date`, leaving no dividend yield to imply.
+    pub fn basis_report<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        valuation_date: Date,
+        spot: V,
+        discount: &YieldCurve<D, I>,
+        dividend: &YieldCurve<D, I>,
+    ) -> QLabResult<BasisReport<V>> {
+        if spot <= V::zero() {
+            return Err(InvalidInput(format!("spot: {spot:?} must be positive").into()).into());
+        }
+        let fair_value = self.fair_value(valuation_date, spot, discount, dividend)?;
+        let t = D::calculate_day_count_fraction::<V>(valuation_date, self.expiry)?;
+        if t <= V::zero() {
+            return Err(InvalidInput(
+                format!("{} expires on {valuation_date}", self.contract_id).into(),
+            )
+            .into());
+        }
+        let discount_factor = discount.discount_factor(valuation_date, self.expiry)?;
+        Ok(BasisReport {
+            fair_value,
+            market_price: self.price,
+            gross_basis: self.price - spot,
+            carry: fair_value - spot,
+            implied_dividend_yield: -(self.price * discount_factor / spot).ln() / t,
+        })
+    }
+
+    /// Calculates the value of `contracts` futures bought at the market price, in currency,
+    /// i.e. the fair value less the market price multiplied by the multiplier and the number of contracts.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`EquityIndexFuture::fair_value`].
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn position_value<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        valuation_date: Date,
+        spot: V,
+        discount: &YieldCurve<D, I>,
+        dividend: &YieldCurve<D, I>,
+        contracts: i32,
+    ) -> QLabResult<V> {
+        let fair_value = self.fair_value(valuation_date, spot, discount, dividend)?;
+        Ok((fair_value - self.price) * self.multiplier * V::from_i32(contracts).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_basis_report() {
+        let valuation_date = Date::from_ymd(2024, 1, 1).unwrap();
+        let expiry = Date::from_ymd(2025, 1, 1).unwrap();
+        let pillars = [valuation_date, Date::from_ymd(2026, 1, 1).unwrap()];
+        let discount: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(valuation_date, &pillars, &[0.04, 0.04]).unwrap();
+        let dividend: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(valuation_date, &pillars, &[0.02, 0.02]).unwrap();
+        let t = 366.0 / 365.0;
+        let fair_value = 1_000.0 * (0.02_f64 * t).exp();
+        let future = EquityIndexFuture::new("IDX", expiry, 10.0, fair_value).unwrap();
+        let report = future
+            .basis_report(valuation_date, 1_000.0, &discount, &dividend)
+            .unwrap();
+        assert!((report.fair_value() - fair_value).abs() < 1e-9);
+        assert!(report.net_basis().abs() < 1e-9);
+        assert!((report.gross_basis() - report.carry()).abs() < 1e-9);
+        assert!((report.implied_dividend_yield() - 0.02).abs() < 1e-12);
+
+        let rich = EquityIndexFuture::new("IDX", expiry, 10.0, fair_value + 2.0).unwrap();
+        let value = rich
+            .position_value(valuation_date, 1_000.0, &discount, &dividend, -3)
+            .unwrap();
+        assert!((value - 60.0).abs() < 1e-9);
+
+        assert!(EquityIndexFuture::new("IDX", expiry, 0.0, 1_000.0).is_err());
+        assert!(future
+            .fair_value(
+                Date::from_ymd(2025, 1, 2).unwrap(),
+                1_000.0,
+                &discount,
+                &dividend
+            )
+            .is_err());
+        assert!(future
+            .basis_report(expiry, 1_000.0, &discount, &dividend)
+            .is_err());
+    }
+}
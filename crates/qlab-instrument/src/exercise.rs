@@ -0,0 +1,256 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+
+/// When an option can be exercised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExerciseStyle {
+    /// On a single date.
+    European,
+    /// On each of a set of dates.
+    Bermudan,
+    /// On any date within a window.
+    American,
+}
+
+/// The strike of an exercise, quoted either as a clean price or as a yield.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strike<V> {
+    Price(V),
+    Yield(V),
+}
+
+impl<V: Value> Strike<V> {
+    /// Returns the strike as a price, converting a yield strike with `yield_to_price`.
+    ///
+    /// # Errors
+    /// Returns an error if the conversion fails.
+    pub fn price(self, yield_to_price: impl FnOnce(V) -> QLabResult<V>) -> QLabResult<V> {
+        match self {
+            Self::Price(price) => Ok(price),
+            Self::Yield(y) => yield_to_price(y),
+        }
+    }
+}
+
+/// The exercise rights of an option, shared by callable and puttable bonds, bond options and swaptions.
+///
+/// Each date of the schedule carries a strike. For an American exercise, a strike applies from its date
+/// until the next date of the schedule, and the last one until the end of the window.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::exercise::{Exercise, Strike};
+/// use qlab_time::date::Date;
+///
+/// let first_call = Date::from_ymd(2026, 6, 15).unwrap();
+/// let step_down = Date::from_ymd(2027, 6, 15).unwrap();
+/// let call = Exercise::american(
+///     &[(first_call, Strike::Price(102.0)), (step_down, Strike::Price(100.0))],
+///     Date::from_ymd(2030, 6, 15).unwrap(),
+/// )
+/// .unwrap();
+/// let date = Date::from_ymd(2026, 12, 15).unwrap();
+/// assert_eq!(call.strike_on(date), Some(Strike::Price(102.0)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exercise<V> {
+    style: ExerciseStyle,
+    schedule: Vec<(Date, Strike<V>)>,
+    /// The last date on which the option can be exercised.
+    end: Date,
+}
+
+impl<V: Value> Exercise<V> {
+    /// Creates an exercise on a single date.
+    #[must_use]
+    pub fn european(date: Date, strike: Strike<V>) -> Self {
+        Self {
+            style: ExerciseStyle::European,
+            schedule: vec![(date, strike)],
+            end: date,
+        }
+    }
+
+    /// Creates an exercise on each date of `schedule` at its strike.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `schedule` is empty or its dates are not increasing.
+    pub fn bermudan(schedule: &[(Date, Strike<V>)]) -> QLabResult<Self> {
+        Self::validate(schedule)?;
+        Ok(Self {
+            style: ExerciseStyle::Bermudan,
+            schedule: schedule.to_vec(),
+            end: schedule[schedule.len() - 1].0,
+        })
+    }
+
+    /// Creates an exercise on any date from the first date of `schedule` to `end`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `schedule` is empty, its dates are not increasing, or `end`
+    /// precedes its last date.
+    pub fn american(schedule: &[(Date, Strike<V>)], end: Date) -> QLabResult<Self> {
+        Self::validate(schedule)?;
+        let last = schedule[schedule.len() - 1].0;
+        if end < last {
+            return Err(InvalidInput(
+                format!("the end of exercise: {end} precedes the strike date: {last}").into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            style: ExerciseStyle::American,
+            schedule: schedule.to_vec(),
+            end,
+        })
+    }
+
+    fn validate(schedule: &[(Date, Strike<V>)]) -> QLabResult<()> {
+        if schedule.is_empty() {
+            return Err(InvalidInput("at least one exercise date is required".into()).into());
+        }
+        if let Some(pair) = schedule.windows(2).find(|pair| pair[1].0 <= pair[0].0) {
+            return Err(InvalidInput(
+                format!(
+                    "exercise dates must be increasing: {} is followed by {}",
+                    pair[0].0, pair[1].0
+                )
+                .into(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn style(&self) -> ExerciseStyle {
+        self.style
+    }
+
+    /// Returns the exercise dates with their strikes; for an American exercise, the dates from which
+    /// the strikes apply.
+    #[must_use]
+    pub fn schedule(&self) -> &[(Date, Strike<V>)] {
+        &self.schedule
+    }
+
+    #[must_use]
+    pub fn first_date(&self) -> Date {
+        self.schedule[0].0
+    }
+
+    #[must_use]
+    pub fn last_date(&self) -> Date {
+        self.end
+    }
+
+    /// Returns the strike at which the option can be exercised on `date`, or `None` if it cannot.
+    #[must_use]
+    pub fn strike_on(&self, date: Date) -> Option<Strike<V>> {
+        match self.style {
+            ExerciseStyle::European | ExerciseStyle::Bermudan => self
+                .schedule
+                .iter()
+                .find(|(exercise_date, _)| *exercise_date == date)
+                .map(|&(_, strike)| strike),
+            ExerciseStyle::American => {
+                if date > self.end {
+                    return None;
+                }
+                let pos = self
+                    .schedule
+                    .partition_point(|(exercise_date, _)| *exercise_date <= date);
+                pos.checked_sub(1).map(|i| self.schedule[i].1)
+            }
+        }
+    }
+
+    /// Returns the exercise rights remaining after `date`, or `None` if none remain.
+    ///
+    /// For an American exercise, the window is cut to start on the day after `date`.
+    #[must_use]
+    pub fn remaining_after(&self, date: Date) -> Option<Self> {
+        if self.end <= date {
+            return None;
+        }
+        let schedule = match self.style {
+            ExerciseStyle::American => {
+                let start = date.succ_opt()?.max(self.first_date());
+                let mut schedule = vec![(start, self.strike_on(start)?)];
+                schedule.extend(self.schedule.iter().filter(|(d, _)| *d > start));
+                schedule
+            }
+            ExerciseStyle::European | ExerciseStyle::Bermudan => self
+                .schedule
+                .iter()
+                .filter(|(d, _)| *d > date)
+                .copied()
+                .collect(),
+        };
+        Some(Self {
+            style: self.style,
+            schedule,
+            end: self.end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bermudan() {
+        let dates = [
+            Date::from_ymd(2025, 1, 15).unwrap(),
+            Date::from_ymd(2026, 1, 15).unwrap(),
+            Date::from_ymd(2027, 1, 15).unwrap(),
+        ];
+        let schedule = dates.map(|date| (date, Strike::Yield(0.04)));
+        let exercise = Exercise::bermudan(&schedule).unwrap();
+        assert_eq!(exercise.strike_on(dates[1]), Some(Strike::Yield(0.04)));
+        assert_eq!(
+            exercise.strike_on(Date::from_ymd(2025, 6, 1).unwrap()),
+            None
+        );
+        let remaining = exercise.remaining_after(dates[1]).unwrap();
+        assert_eq!(remaining.first_date(), dates[2]);
+        assert!(exercise.remaining_after(dates[2]).is_none());
+        assert!(Exercise::bermudan(&[schedule[1], schedule[0]]).is_err());
+        let price = Strike::Yield(0.04)
+            .price(|y| Ok(100.0 / (1.0 + y)))
+            .unwrap();
+        assert!((price - 100.0 / 1.04_f64).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_american() {
+        let first = Date::from_ymd(2025, 1, 15).unwrap();
+        let step = Date::from_ymd(2026, 1, 15).unwrap();
+        let end = Date::from_ymd(2027, 1, 15).unwrap();
+        let exercise = Exercise::american(
+            &[(first, Strike::Price(101.0)), (step, Strike::Price(100.0))],
+            end,
+        )
+        .unwrap();
+        assert_eq!(
+            exercise.strike_on(Date::from_ymd(2024, 12, 1).unwrap()),
+            None
+        );
+        assert_eq!(exercise.strike_on(end), Some(Strike::Price(100.0)));
+        assert_eq!(exercise.strike_on(end.succ_opt().unwrap()), None);
+        let date = Date::from_ymd(2025, 6, 1).unwrap();
+        let remaining = exercise.remaining_after(date).unwrap();
+        assert_eq!(
+            remaining.schedule(),
+            &[
+                (date.succ_opt().unwrap(), Strike::Price(101.0)),
+                (step, Strike::Price(100.0))
+            ]
+        );
+        assert!(Exercise::american(&[(end, Strike::Price(100.0))], step).is_err());
+    }
+}
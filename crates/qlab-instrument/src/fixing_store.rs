@@ -0,0 +1,160 @@
+use crate::swap::Swap;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Published fixings of floating rate indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixingStore<V> {
+    fixings: BTreeMap<String, BTreeMap<Date, V>>,
+}
+
+impl<V> Default for FixingStore<V> {
+    fn default() -> Self {
+        Self {
+            fixings: BTreeMap::default(),
+        }
+    }
+}
+
+impl<V: Value> FixingStore<V> {
+    /// Creates an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the fixing of `index` on `date`, replacing any previous one.
+    #[must_use]
+    pub fn with_fixing(mut self, index: &str, date: Date, value: V) -> Self {
+        self.insert(index, date, value);
+        self
+    }
+
+    /// Records the fixing of `index` on `date`, replacing any previous one.
+    pub fn insert(&mut self, index: &str, date: Date, value: V) {
+        self.fixings
+            .entry(index.to_string())
+            .or_default()
+            .insert(date, value);
+    }
+
+    /// Returns the fixing of `index` on `date`, if published.
+    #[must_use]
+    pub fn fixing(&self, index: &str, date: Date) -> Option<V> {
+        self.fixings.get(index)?.get(&date).copied()
+    }
+}
+
+/// A fixing needed to value an instrument but absent from a [`FixingStore`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MissingFixing {
+    pub index: String,
+    pub fixing_date: Date,
+    pub instrument_id: String,
+}
+
+/// Reports the fixings before `valuation_date` of the coupons paid after it that are absent from `store`,
+/// sorted by index, date and instrument, so that a batch can fail fast before pricing.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_instrument::fixing_store::{missing_fixings, FixingStore};
+/// use qlab_instrument::swap::{Swap, SwapDirection};
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::day_count::act_360::Act360;
+/// use qlab_time::frequency::Frequency;
+///
+/// let start = Date::from_ymd(2024, 1, 4).unwrap();
+/// let swap = Swap::new::<Act360, Act360>(
+///     "swap", "USD", "TERM", SwapDirection::Payer, start, Date::from_ymd(2025, 1, 6).unwrap(),
+///     Frequency::A, Frequency::Q, &WeekendsOnly, DateRolling::ModifiedFollowing, 0.04, 1e6,
+/// )
+/// .unwrap();
+/// let valuation_date = Date::from_ymd(2024, 2, 1).unwrap();
+/// let missing = missing_fixings(&[swap], &FixingStore::<f64>::new(), valuation_date);
+/// assert_eq!(missing.len(), 1);
+/// assert_eq!(missing[0].fixing_date, start);
+/// ```
+#[must_use]
+pub fn missing_fixings<V: Value>(
+    swaps: &[Swap<V>],
+    store: &FixingStore<V>,
+    valuation_date: Date,
+) -> Vec<MissingFixing> {
+    let mut missing = BTreeSet::new();
+    for swap in swaps {
+        for (payment_date, fixings) in swap.floating_fixings() {
+            if payment_date <= valuation_date {
+                continue;
+            }
+            for fixing in fixings {
+                if fixing.fixing_date < valuation_date
+                    && store.fixing(swap.index(), fixing.fixing_date).is_none()
+                {
+                    missing.insert(MissingFixing {
+                        index: swap.index().to_string(),
+                        fixing_date: fixing.fixing_date,
+                        instrument_id: swap.swap_id().to_string(),
+                    });
+                }
+            }
+        }
+    }
+    missing.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap::SwapDirection;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_time::date_rolling::DateRolling;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::fixing::FixingConvention;
+    use qlab_time::frequency::Frequency;
+
+    #[test]
+    fn test_missing_fixings() {
+        let swap = Swap::new::<Act360, Act360>(
+            "ois",
+            "USD",
+            "SOFR",
+            SwapDirection::Receiver,
+            Date::from_ymd(2023, 12, 4).unwrap(),
+            Date::from_ymd(2024, 12, 4).unwrap(),
+            Frequency::A,
+            Frequency::M,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.05,
+            1e6,
+        )
+        .unwrap()
+        .with_fixings(
+            FixingConvention::Overnight {
+                lookback: 0,
+                observation_shift: false,
+            },
+            &WeekendsOnly,
+        )
+        .unwrap();
+        // Friday 2024-01-12
+        let valuation_date = Date::from_ymd(2024, 1, 12).unwrap();
+        let store = FixingStore::new()
+            .with_fixing("SOFR", Date::from_ymd(2024, 1, 4).unwrap(), 0.0531)
+            .with_fixing("SOFR", Date::from_ymd(2024, 1, 5).unwrap(), 0.0531);
+        let missing = missing_fixings(&[swap], &store, valuation_date);
+        // the first coupon is paid on 2024-01-04, and the second one has fixed on six business days
+        assert_eq!(missing.len(), 4);
+        assert_eq!(missing[0].fixing_date, Date::from_ymd(2024, 1, 8).unwrap());
+        assert_eq!(missing[0].instrument_id, "ois");
+        assert_eq!(
+            store.fixing("SOFR", Date::from_ymd(2024, 1, 5).unwrap()),
+            Some(0.0531)
+        );
+    }
+}
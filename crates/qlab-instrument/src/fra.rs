@@ -0,0 +1,343 @@
+use crate::swap::SwapDirection;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+
+/// Calculates the simply compounded forward rate accruing on `DC` from `start` to `end`,
+/// projected from `projection_curve`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the period has no accrual, or an error if a discount factor
+/// calculation fails.
+pub fn forward_rate<V: Value, DC: DayCount, D: DayCount, I: Interpolator<Value = V>>(
+    projection_curve: &YieldCurve<D, I>,
+    start: Date,
+    end: Date,
+) -> QLabResult<V> {
+    let fraction = DC::calculate_day_count_fraction(start, end)?;
+    if fraction <= V::zero() {
+        return Err(InvalidInput(
+            format!("the period from {start} to {end} has no accrual").into(),
+        )
+        .into());
+    }
+    Ok((V::one() / projection_curve.discount_factor(start, end)? - V::one()) / fraction)
+}
+
+/// Calculates the convexity adjustment of a futures rate over the forward rate of its reference
+/// period under the Ho-Lee model, `σ²·t₁·t₂/2`, with `time_to_start` and `time_to_end` the year
+/// fractions to the start and end of the reference period.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+#[must_use]
+pub fn futures_convexity_adjustment<V: Value>(
+    volatility: V,
+    time_to_start: V,
+    time_to_end: V,
+) -> V {
+    V::from_f64(0.5).unwrap() * volatility * volatility * time_to_start * time_to_end
+}
+
+/// Converts a futures price quoted as `100` minus the rate in percent into the forward rate
+/// of its reference period, removing `convexity_adjustment` from the implied futures rate.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+#[must_use]
+pub fn forward_from_futures_price<V: Value>(price: V, convexity_adjustment: V) -> V {
+    let hundred = V::from_u8(100).unwrap();
+    (hundred - price) / hundred - convexity_adjustment
+}
+
+/// A forward rate agreement exchanging a fixed rate for the rate of a reference period from
+/// `start` to `end`, settled at `start` with the difference discounted at the fixing.
+///
+/// Under the multi-curve setup the fixing is projected from a projection curve, while the settlement
+/// is discounted from `start` to the valuation date on a separate discount curve.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::fra::Fra;
+/// use qlab_instrument::swap::SwapDirection;
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_360::Act360;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let pillars = [Date::from_ymd(2024, 7, 2).unwrap(), Date::from_ymd(2026, 1, 2).unwrap()];
+/// let discount: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(settle_date, &pillars, &[0.04, 0.042]).unwrap();
+/// let projection: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(settle_date, &pillars, &[0.045, 0.047]).unwrap();
+///
+/// let start = Date::from_ymd(2024, 10, 2).unwrap();
+/// let end = Date::from_ymd(2025, 1, 2).unwrap();
+/// let fra = Fra::new::<Act360>(SwapDirection::Payer, start, end, 0.04, 1_000_000.0).unwrap();
+/// let value = fra.present_value(settle_date, &discount, &projection).unwrap();
+/// assert!(value > 0.0);
+///
+/// let par_rate = fra.par_rate(&projection).unwrap();
+/// let fixed_rate = fra.fixed_rate_for_value(settle_date, &discount, &projection, value).unwrap();
+/// assert!((fixed_rate - 0.04).abs() < 1e-12);
+/// assert!(par_rate > 0.04);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fra<V> {
+    direction: SwapDirection,
+    start: Date,
+    end: Date,
+    fraction: V,
+    fixed_rate: V,
+    notional: V,
+}
+
+impl<V: Value> Fra<V> {
+    /// Creates an agreement on the reference period from `start` to `end`, accruing on `DC`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the period has no accrual, or an error if the day count
+    /// fraction cannot be calculated.
+    pub fn new<DC: DayCount>(
+        direction: SwapDirection,
+        start: Date,
+        end: Date,
+        fixed_rate: V,
+        notional: V,
+    ) -> QLabResult<Self> {
+        let fraction = DC::calculate_day_count_fraction(start, end)?;
+        if fraction <= V::zero() {
+            return Err(InvalidInput(
+                format!("the period from {start} to {end} has no accrual").into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            direction,
+            start,
+            end,
+            fraction,
+            fixed_rate,
+            notional,
+        })
+    }
+
+    #[must_use]
+    pub fn direction(&self) -> SwapDirection {
+        self.direction
+    }
+
+    #[must_use]
+    pub fn start(&self) -> Date {
+        self.start
+    }
+
+    #[must_use]
+    pub fn end(&self) -> Date {
+        self.end
+    }
+
+    #[must_use]
+    pub fn fraction(&self) -> V {
+        self.fraction
+    }
+
+    #[must_use]
+    pub fn fixed_rate(&self) -> V {
+        self.fixed_rate
+    }
+
+    #[must_use]
+    pub fn notional(&self) -> V {
+        self.notional
+    }
+
+    /// Returns `1` if the fixed rate is paid and `-1` if it is received.
+    fn sign(&self) -> V {
+        match self.direction {
+            SwapDirection::Payer => V::one(),
+            SwapDirection::Receiver => -V::one(),
+        }
+    }
+
+    /// Projects the rate of the reference period from `projection_curve`,
+    /// which is the fixed rate giving the agreement a value of zero.
+    ///
+    /// # Errors
+    /// Returns an error if a discount factor calculation fails.
+    pub fn par_rate<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        let forward_discount_factor = projection_curve.discount_factor(self.start, self.end)?;
+        Ok((V::one() / forward_discount_factor - V::one()) / self.fraction)
+    }
+
+    /// Calculates the value on `settle_date`, projecting the fixing from `projection_curve`
+    /// and discounting the settlement at `start` from `discount_curve`.
+    ///
+    /// # Errors
+    /// Returns an error if the agreement fixes before the settlement date of either curve,
+    /// or if a discount factor calculation fails.
+    pub fn present_value<
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        PD: DayCount,
+        PI: Interpolator<Value = V>,
+    >(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<PD, PI>,
+    ) -> QLabResult<V> {
+        let forward = self.par_rate(projection_curve)?;
+        Ok(
+            self.sign() * self.notional * self.fraction * (forward - self.fixed_rate)
+                / (V::one() + self.fraction * forward)
+                * discount_curve.discount_factor(settle_date, self.start)?,
+        )
+    }
+
+    /// Converts a market value on `settle_date` into the fixed rate at which the agreement has that value,
+    /// inverting [`Fra::present_value`] on the same curves.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the notional is zero, or an error if a discount factor
+    /// calculation fails.
+    pub fn fixed_rate_for_value<
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        PD: DayCount,
+        PI: Interpolator<Value = V>,
+    >(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<PD, PI>,
+        value: V,
+    ) -> QLabResult<V> {
+        if self.notional == V::zero() {
+            return Err(InvalidInput("the notional is zero".into()).into());
+        }
+        let forward = self.par_rate(projection_curve)?;
+        let discount_factor = discount_curve.discount_factor(settle_date, self.start)?;
+        Ok(forward
+            - self.sign() * value * (V::one() + self.fraction * forward)
+                / (self.notional * self.fraction * discount_factor))
+    }
+
+    /// Returns the agreement with the fixed rate replaced by `fixed_rate`.
+    #[must_use]
+    pub fn with_fixed_rate(mut self, fixed_rate: V) -> Self {
+        self.fixed_rate = fixed_rate;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_math::interpolation::spline::natural_cubic::NaturalCubic;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+
+    fn curve(settle_date: Date, short: f64, long: f64) -> YieldCurve<Act365, Linear<f64>> {
+        YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2024, 7, 2).unwrap(),
+                Date::from_ymd(2026, 1, 2).unwrap(),
+            ],
+            &[short, long],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_multi_curve_fra() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = curve(settle_date, 0.04, 0.042);
+        let projection = curve(settle_date, 0.045, 0.047);
+        let start = Date::from_ymd(2024, 10, 2).unwrap();
+        let end = Date::from_ymd(2025, 1, 2).unwrap();
+        let payer = Fra::new::<Act360>(SwapDirection::Payer, start, end, 0.05, 1e6).unwrap();
+
+        let forward = forward_rate::<f64, Act360, _, _>(&projection, start, end).unwrap();
+        assert!((payer.par_rate(&projection).unwrap() - forward).abs() < 1e-15);
+        assert!(forward > forward_rate::<f64, Act360, _, _>(&discount, start, end).unwrap());
+        let at_par = payer.clone().with_fixed_rate(forward);
+        assert!(
+            at_par
+                .present_value(settle_date, &discount, &projection)
+                .unwrap()
+                .abs()
+                < 1e-9
+        );
+
+        // settling at the start is equivalent to paying in arrears on a single curve
+        let value = payer
+            .present_value(settle_date, &projection, &projection)
+            .unwrap();
+        let in_arrears = 1e6
+            * payer.fraction()
+            * (forward - 0.05)
+            * projection.discount_factor(settle_date, end).unwrap();
+        assert!((value - in_arrears).abs() < 1e-9);
+
+        let value = payer
+            .present_value(settle_date, &discount, &projection)
+            .unwrap();
+        let receiver = Fra::new::<Act360>(SwapDirection::Receiver, start, end, 0.05, 1e6).unwrap();
+        assert!(
+            (receiver
+                .present_value(settle_date, &discount, &projection)
+                .unwrap()
+                + value)
+                .abs()
+                < 1e-9
+        );
+        let fixed_rate = receiver
+            .fixed_rate_for_value(settle_date, &discount, &projection, -value)
+            .unwrap();
+        assert!((fixed_rate - 0.05).abs() < 1e-12);
+
+        // the curves may differ in day count and interpolation
+        let cubic_projection: YieldCurve<Act360, NaturalCubic<f64>> = YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2024, 7, 2).unwrap(),
+                Date::from_ymd(2025, 1, 2).unwrap(),
+                Date::from_ymd(2026, 1, 2).unwrap(),
+            ],
+            &[0.045, 0.046, 0.047],
+        )
+        .unwrap();
+        let forward = payer.par_rate(&cubic_projection).unwrap();
+        assert!(
+            payer
+                .clone()
+                .with_fixed_rate(forward)
+                .present_value(settle_date, &discount, &cubic_projection)
+                .unwrap()
+                .abs()
+                < 1e-9
+        );
+        assert!(Fra::new::<Act360>(SwapDirection::Payer, end, start, 0.05, 1e6).is_err());
+    }
+
+    #[test]
+    fn test_futures_to_forward() {
+        let adjustment: f64 = futures_convexity_adjustment(0.01, 1.0, 1.25);
+        assert!((adjustment - 6.25e-5).abs() < 1e-15);
+        let forward: f64 = forward_from_futures_price(95.0, adjustment);
+        assert!((forward - (0.05 - 6.25e-5)).abs() < 1e-12);
+    }
+}
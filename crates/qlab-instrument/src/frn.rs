@@ -0,0 +1,634 @@
+use crate::coupon_report::{CouponReport, CouponRow, RateSource};
+use crate::fixing_store::FixingStore;
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::root_finding::{solve, SolverDomain};
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::calendar::Calendar;
+use qlab_time::date::Date;
+use qlab_time::date_rolling::DateRolling;
+use qlab_time::day_count::DayCount;
+use qlab_time::fixing::{fixings, FixingConvention};
+use qlab_time::frequency::Frequency;
+use qlab_time::schedule::{AccrualPeriod, AccrualSchedule, StubPolicy};
+
+/// A floating rate note paying the index rate of each period plus a quoted margin, and the face value at maturity.
+///
+/// Each period fixes on its accrual start until [`FloatingRateNote::with_fixing_lag`] sets the lag of the index.
+/// Fixings before the settlement date are looked up from a [`FixingStore`] under the index of the note, and the later
+/// ones are projected from a projection curve over the accrual period. Prices are dirty and in units of the face value.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_instrument::fixing_store::FixingStore;
+/// use qlab_instrument::frn::FloatingRateNote;
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::day_count::act_360::Act360;
+/// use qlab_time::day_count::act_365::Act365;
+/// use qlab_time::frequency::Frequency;
+///
+/// let issue_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let maturity_date = Date::from_ymd(2027, 1, 4).unwrap();
+/// let pillars = [Date::from_ymd(2024, 1, 4).unwrap(), maturity_date];
+/// let projection: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(issue_date, &pillars, &[0.04, 0.042]).unwrap();
+/// let note = FloatingRateNote::new::<Act360>(
+///     "FRN",
+///     "TERM",
+///     issue_date,
+///     maturity_date,
+///     Frequency::Q,
+///     &WeekendsOnly,
+///     DateRolling::ModifiedFollowing,
+///     0.005_f64,
+///     100.0,
+/// )
+/// .unwrap();
+///
+/// // a note bought at a discount yields more than its quoted margin
+/// let store = FixingStore::new();
+/// let margin = note.discount_margin(issue_date, &projection, &store, 99.0).unwrap();
+/// assert!(margin > 0.005);
+/// let price = note.dirty_price(issue_date, &projection, &store, margin).unwrap();
+/// assert!((price - 99.0).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatingRateNote<V> {
+    note_id: String,
+    index: String,
+    accruals: AccrualSchedule<V>,
+    /// The fixing date of each period of `accruals`.
+    fixing_dates: Vec<Date>,
+    quoted_margin: V,
+    face_value: V,
+}
+
+impl<V: Value> FloatingRateNote<V> {
+    /// Creates a note on `index` accruing on `DC` over periods of `frequency` from `issue_date` to `maturity_date`,
+    /// adjusted with `calendar` and `rolling`, with a short first period if the dates are irregular.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the face value is not positive, or an error if the schedule
+    /// cannot be generated.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<DC: DayCount>(
+        note_id: &str,
+        index: &str,
+        issue_date: Date,
+        maturity_date: Date,
+        frequency: Frequency,
+        calendar: &impl Calendar,
+        rolling: DateRolling,
+        quoted_margin: V,
+        face_value: V,
+    ) -> QLabResult<Self> {
+        if face_value <= V::zero() {
+            return Err(InvalidInput(
+                format!("face_value: {face_value:?} must be positive").into(),
+            )
+            .into());
+        }
+        let accruals = AccrualSchedule::generate::<DC>(
+            issue_date,
+            maturity_date,
+            frequency,
+            calendar,
+            rolling,
+            StubPolicy::ShortFront,
+        )?;
+        let fixing_dates = accruals
+            .periods()
+            .iter()
+            .map(|accrual| accrual.period.accrual_start)
+            .collect();
+        Ok(Self {
+            note_id: note_id.to_string(),
+            index: index.to_string(),
+            accruals,
+            fixing_dates,
+            quoted_margin,
+            face_value,
+        })
+    }
+
+    /// Fixes each period `lag` business days of the fixing `calendar` of the index before its accrual start,
+    /// as [`FixingConvention::Term`] does.
+    ///
+    /// # Errors
+    /// Returns an error if a fixing date overflows.
+    pub fn with_fixing_lag(mut self, lag: u32, calendar: &impl Calendar) -> QLabResult<Self> {
+        for (accrual, fixing_date) in self.accruals.periods().iter().zip(&mut self.fixing_dates) {
+            let (start, end) = accrual.period.accrual_dates(true);
+            *fixing_date =
+                fixings(start, end, FixingConvention::Term { lag }, calendar)?[0].fixing_date;
+        }
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn note_id(&self) -> &str {
+        &self.note_id
+    }
+
+    /// Returns the index whose fixings the coupons pay.
+    #[must_use]
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+
+    /// Returns the fixing date of each period.
+    #[must_use]
+    pub fn fixing_dates(&self) -> &[Date] {
+        &self.fixing_dates
+    }
+
+    #[must_use]
+    pub fn quoted_margin(&self) -> V {
+        self.quoted_margin
+    }
+
+    #[must_use]
+    pub fn face_value(&self) -> V {
+        self.face_value
+    }
+
+    #[must_use]
+    pub fn accruals(&self) -> &AccrualSchedule<V> {
+        &self.accruals
+    }
+
+    /// Returns the adjusted end of the last period.
+    ///
+    /// # Panics
+    /// Will panic if the schedule is empty, which [`FloatingRateNote::new`] never builds.
+    #[must_use]
+    pub fn maturity_date(&self) -> Date {
+        self.accruals.periods()[self.accruals.len() - 1]
+            .period
+            .accrual_end
+    }
+
+    /// Returns the published fixing of the period fixing on `fixing_date`, if `store` holds it.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the fixing is before `settle_date` but absent from `store`.
+    fn published_rate(
+        &self,
+        fixing_date: Date,
+        settle_date: Date,
+        store: &FixingStore<V>,
+    ) -> QLabResult<Option<V>> {
+        match store.fixing(&self.index, fixing_date) {
+            None if fixing_date < settle_date => Err(InvalidInput(
+                format!(
+                    "no fixing of {} on {fixing_date} for {}",
+                    self.index, self.note_id
+                )
+                .into(),
+            )
+            .into()),
+            rate => Ok(rate),
+        }
+    }
+
+    /// Returns the periods ending after `settle_date` with their fixing dates and index rates, published in `store`
+    /// or projected. A period fixing on or after `settle_date` is projected unless its fixing is published.
+    fn index_rates<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+    ) -> QLabResult<Vec<IndexRate<'_, V>>> {
+        self.accruals
+            .periods()
+            .iter()
+            .zip(&self.fixing_dates)
+            .filter(|(accrual, _)| settle_date < accrual.period.accrual_end)
+            .map(|(accrual, &fixing_date)| {
+                let (start, end) = accrual.period.accrual_dates(true);
+                let (rate, source) = match self.published_rate(fixing_date, settle_date, store)? {
+                    Some(rate) => (rate, RateSource::Historical),
+                    None => (
+                        (V::one() / projection_curve.discount_factor(start, end)? - V::one())
+                            / accrual.adjusted_fraction,
+                        RateSource::Projected,
+                    ),
+                };
+                Ok(IndexRate {
+                    accrual,
+                    fixing_date,
+                    rate,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the share of the period still to run after `settle_date`, in calendar days.
+    fn remaining_share(accrual: &AccrualPeriod<V>, settle_date: Date) -> QLabResult<V> {
+        let (start, end) = accrual.period.accrual_dates(true);
+        if settle_date <= start {
+            return Ok(V::one());
+        }
+        let remaining = V::from_i64(end - settle_date)
+            .ok_or_else(|| CastNumberError(format!("{settle_date}").into()))?;
+        let length =
+            V::from_i64(end - start).ok_or_else(|| CastNumberError(format!("{end}").into()))?;
+        Ok(remaining / length)
+    }
+
+    /// Returns the payment date and amount of each cash flow after `settle_date`, with the coupons of
+    /// periods which have not fixed projected from `projection_curve`. The face value is included
+    /// in the last cash flow.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a fixing before `settle_date` is absent from `store`,
+    /// or an error if a discount factor calculation fails.
+    pub fn projected_cash_flows<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+    ) -> QLabResult<Vec<(Date, V)>> {
+        let maturity_date = self.maturity_date();
+        Ok(self
+            .index_rates(settle_date, projection_curve, store)?
+            .into_iter()
+            .map(|IndexRate { accrual, rate, .. }| {
+                let mut amount =
+                    self.face_value * (rate + self.quoted_margin) * accrual.adjusted_fraction;
+                if accrual.period.accrual_end == maturity_date {
+                    amount += self.face_value;
+                }
+                (accrual.period.payment_date, amount)
+            })
+            .collect())
+    }
+
+    /// Reports each coupon paid after `settle_date`, with the index rates published in `store` or projected from
+    /// `projection_curve`, discounted on `discount_curve`. The face value repaid at maturity is not included.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a fixing before `settle_date` is absent from `store`,
+    /// or an error if a discount factor calculation fails.
+    pub fn coupon_report<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+    ) -> QLabResult<CouponReport<V>> {
+        let rows = self
+            .index_rates(settle_date, projection_curve, store)?
+            .into_iter()
+            .map(|index_rate| {
+                let IndexRate {
+                    accrual,
+                    fixing_date,
+                    rate,
+                    source,
+                } = index_rate;
+                let (start, end) = accrual.period.accrual_dates(true);
+                let amount =
+                    self.face_value * (rate + self.quoted_margin) * accrual.adjusted_fraction;
+                let discount_factor =
+                    discount_curve.discount_factor(settle_date, accrual.period.payment_date)?;
+                Ok(CouponRow {
+                    accrual_start: start,
+                    accrual_end: end,
+                    payment_date: accrual.period.payment_date,
+                    fixing_date: Some(fixing_date),
+                    source,
+                    rate,
+                    spread: self.quoted_margin,
+                    accrual_fraction: accrual.adjusted_fraction,
+                    notional: self.face_value,
+                    amount,
+                    discount_factor,
+                    present_value: amount * discount_factor,
+                })
+            })
+            .collect::<QLabResult<_>>()?;
+        Ok(CouponReport::new(rows))
+    }
+
+    /// Calculates the interest accrued in the current period up to `settle_date`, linearly in calendar days,
+    /// at the fixing of the period published in `store`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the fixing of the current period is absent from `store`,
+    /// or an error if the day counts cannot be cast into `V`.
+    pub fn accrued_interest(&self, settle_date: Date, store: &FixingStore<V>) -> QLabResult<V> {
+        let Some((accrual, &fixing_date)) = self
+            .accruals
+            .periods()
+            .iter()
+            .zip(&self.fixing_dates)
+            .find(|(accrual, _)| {
+                accrual.period.accrual_start < settle_date
+                    && settle_date < accrual.period.accrual_end
+            })
+        else {
+            return Ok(V::zero());
+        };
+        // the current period has started, so its fixing is due even if it falls on the settlement date
+        let rate = store.fixing(&self.index, fixing_date).ok_or_else(|| {
+            InvalidInput(
+                format!(
+                    "no fixing of {} on {fixing_date} for {}",
+                    self.index, self.note_id
+                )
+                .into(),
+            )
+        })?;
+        let elapsed = V::one() - Self::remaining_share(accrual, settle_date)?;
+        Ok(self.face_value * (rate + self.quoted_margin) * accrual.adjusted_fraction * elapsed)
+    }
+
+    /// Calculates the dirty price on `settle_date` discounting at the index rates plus `discount_margin`,
+    /// compounded over the periods: the cash flow of each period is discounted by
+    /// `1 / (1 + (index + discount_margin)·τ)` for itself and every earlier period, with the fraction `τ`
+    /// of the current period reduced to its part after `settle_date`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a fixing before `settle_date` is absent from `store`,
+    /// or an error if a discount factor calculation fails.
+    pub fn dirty_price<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+        discount_margin: V,
+    ) -> QLabResult<V> {
+        let maturity_date = self.maturity_date();
+        let mut discount_factor = V::one();
+        let mut price = V::zero();
+        for IndexRate { accrual, rate, .. } in
+            self.index_rates(settle_date, projection_curve, store)?
+        {
+            let remaining_fraction =
+                accrual.adjusted_fraction * Self::remaining_share(accrual, settle_date)?;
+            discount_factor /= V::one() + (rate + discount_margin) * remaining_fraction;
+            let mut amount =
+                self.face_value * (rate + self.quoted_margin) * accrual.adjusted_fraction;
+            if accrual.period.accrual_end == maturity_date {
+                amount += self.face_value;
+            }
+            price += amount * discount_factor;
+        }
+        Ok(price)
+    }
+
+    /// Solves for the discount margin, the spread over the projected index at which
+    /// [`FloatingRateNote::dirty_price`] reprices the note to `dirty_price`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if no margin within ±100% reprices the note,
+    /// or an error if the price cannot be calculated.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn discount_margin<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+        dirty_price: V,
+    ) -> QLabResult<V> {
+        solve(
+            |margin| {
+                Ok(self.dirty_price(settle_date, projection_curve, store, margin)? - dirty_price)
+            },
+            &SolverDomain::spread().with_guess(self.quoted_margin),
+            V::from_f64(1e-12).unwrap(),
+        )
+    }
+
+    /// Calculates the simple margin, the quoted margin plus the discount to the face value
+    /// amortized linearly to maturity, `QM + (F - P) / (F·T)`, with `P` the clean price and `T`
+    /// the years from `settle_date` to maturity on `DC`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the note has matured, or an error if the accrued interest
+    /// or the day count fraction cannot be calculated.
+    pub fn simple_margin<DC: DayCount>(
+        &self,
+        settle_date: Date,
+        store: &FixingStore<V>,
+        dirty_price: V,
+    ) -> QLabResult<V> {
+        let maturity_date = self.maturity_date();
+        let years = DC::calculate_day_count_fraction::<V>(settle_date, maturity_date)?;
+        if years <= V::zero() {
+            return Err(InvalidInput(
+                format!("the note matures on {maturity_date}, not after {settle_date}").into(),
+            )
+            .into());
+        }
+        let clean_price = dirty_price - self.accrued_interest(settle_date, store)?;
+        Ok(self.quoted_margin + (self.face_value - clean_price) / (self.face_value * years))
+    }
+
+    /// Calculates the spread duration, the relative change of the dirty price per unit change of the
+    /// discount margin, `-(1/P)·∂P/∂DM`, by central differences of one basis point around `discount_margin`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the price is zero, or an error if the price cannot be calculated.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn spread_duration<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+        discount_margin: V,
+    ) -> QLabResult<V> {
+        let bump = V::from_f64(1e-4).unwrap();
+        let price = self.dirty_price(settle_date, projection_curve, store, discount_margin)?;
+        if price == V::zero() {
+            return Err(InvalidInput("the price of the note is zero".into()).into());
+        }
+        let up = self.dirty_price(settle_date, projection_curve, store, discount_margin + bump)?;
+        let down =
+            self.dirty_price(settle_date, projection_curve, store, discount_margin - bump)?;
+        Ok((down - up) / (V::from_u8(2).unwrap() * bump * price))
+    }
+}
+
+/// The index rate of a period of a [`FloatingRateNote`] with its fixing date and where the rate comes from.
+struct IndexRate<'a, V> {
+    accrual: &'a AccrualPeriod<V>,
+    fixing_date: Date,
+    rate: V,
+    source: RateSource,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+
+    fn ymd(year: i32, month: u32, day: u32) -> Date {
+        Date::from_ymd(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_discount_margin() {
+        let issue_date = ymd(2024, 1, 2);
+        let projection: YieldCurve<Act365, Linear<f64>> = YieldCurve::new(
+            issue_date,
+            &[ymd(2024, 4, 2), ymd(2029, 1, 2)],
+            &[0.04, 0.045],
+        )
+        .unwrap();
+        let note = FloatingRateNote::new::<Act360>(
+            "FRN",
+            "TERM",
+            issue_date,
+            ymd(2029, 1, 2),
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.01,
+            100.0,
+        )
+        .unwrap();
+        assert_eq!(note.accruals().len(), 20);
+        let store = FixingStore::new();
+
+        // discounting at the quoted margin reprices the note to par on a reset date
+        let par = note
+            .dirty_price(issue_date, &projection, &store, 0.01)
+            .unwrap();
+        assert!((par - 100.0).abs() < 1e-9);
+        let margin = note
+            .discount_margin(issue_date, &projection, &store, 100.0)
+            .unwrap();
+        assert!((margin - 0.01).abs() < 1e-9);
+        assert!(
+            (note
+                .simple_margin::<Act365>(issue_date, &store, 100.0)
+                .unwrap()
+                - 0.01)
+                .abs()
+                < 1e-15
+        );
+        // a floater is insensitive to rates but as sensitive to its spread as a fixed coupon bond
+        let duration = note
+            .spread_duration(issue_date, &projection, &store, 0.01)
+            .unwrap();
+        assert!(3.0 < duration && duration < 5.0);
+
+        // between resets the current coupon is fixed
+        let settle_date = ymd(2024, 2, 15);
+        assert!(note
+            .dirty_price(settle_date, &projection, &store, 0.01)
+            .is_err());
+        let store = store.with_fixing("TERM", issue_date, 0.038);
+        let accrued = note.accrued_interest(settle_date, &store).unwrap();
+        assert!((accrued - 100.0 * 0.048 * 44.0 / 360.0).abs() < 1e-12);
+        let flows = note
+            .projected_cash_flows(settle_date, &projection, &store)
+            .unwrap();
+        assert_eq!(flows.len(), 20);
+        assert!((flows[0].1 - 100.0 * 0.048 * 91.0 / 360.0).abs() < 1e-12);
+        assert!(flows[19].1 > 100.0);
+
+        let discount = note
+            .dirty_price(settle_date, &projection, &store, 0.012)
+            .unwrap();
+        let margin = note
+            .discount_margin(settle_date, &projection, &store, discount)
+            .unwrap();
+        assert!((margin - 0.012).abs() < 1e-9);
+        assert!(
+            note.simple_margin::<Act365>(settle_date, &store, discount)
+                .unwrap()
+                > 0.01
+        );
+        assert!(note
+            .simple_margin::<Act365>(ymd(2029, 1, 2), &store, 100.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_coupon_report() {
+        let issue_date = ymd(2024, 1, 2);
+        let settle_date = ymd(2024, 2, 15);
+        let projection: YieldCurve<Act365, Linear<f64>> = YieldCurve::new(
+            settle_date,
+            &[ymd(2024, 4, 2), ymd(2026, 1, 2)],
+            &[0.04, 0.045],
+        )
+        .unwrap();
+        let note = FloatingRateNote::new::<Act360>(
+            "FRN",
+            "TERM",
+            issue_date,
+            ymd(2026, 1, 2),
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.01,
+            100.0,
+        )
+        .unwrap();
+        assert!(note
+            .coupon_report(settle_date, &projection, &projection, &FixingStore::new())
+            .is_err());
+        let store = FixingStore::new().with_fixing("TERM", issue_date, 0.05);
+        let report = note
+            .coupon_report(settle_date, &projection, &projection, &store)
+            .unwrap();
+        assert_eq!(report.rows().len(), 8);
+        let current = report.rows()[0];
+        assert_eq!(current.source, RateSource::Historical);
+        assert_eq!(current.fixing_date, Some(issue_date));
+        assert!((current.amount - 100.0 * 0.06 * 91.0 / 360.0).abs() < 1e-12);
+        assert!(report.rows()[1..]
+            .iter()
+            .all(|row| row.source == RateSource::Projected));
+        let flows = note
+            .projected_cash_flows(settle_date, &projection, &store)
+            .unwrap();
+        for (row, (date, amount)) in report.rows().iter().zip(&flows[..7]) {
+            assert_eq!(row.payment_date, *date);
+            assert!((row.amount - amount).abs() < 1e-12);
+        }
+
+        // with a lag of two business days, the period starting on Tuesday 2024-04-02 fixes on Friday 2024-03-29,
+        // which needs a published fixing once the note settles after it, even before the period starts
+        let lagged = note.with_fixing_lag(2, &WeekendsOnly).unwrap();
+        assert_eq!(lagged.fixing_dates()[0], ymd(2023, 12, 29));
+        assert_eq!(lagged.fixing_dates()[1], ymd(2024, 3, 29));
+        let store = FixingStore::new().with_fixing("TERM", ymd(2023, 12, 29), 0.05);
+        let report = lagged
+            .coupon_report(settle_date, &projection, &projection, &store)
+            .unwrap();
+        assert_eq!(report.rows()[0].fixing_date, Some(ymd(2023, 12, 29)));
+        assert_eq!(report.rows()[1].fixing_date, Some(ymd(2024, 3, 29)));
+        assert_eq!(report.rows()[1].source, RateSource::Projected);
+        let settle_date = ymd(2024, 4, 1);
+        assert!(lagged
+            .dirty_price(settle_date, &projection, &store, 0.01)
+            .is_err());
+        let store = store.with_fixing("TERM", ymd(2024, 3, 29), 0.045);
+        let flows = lagged
+            .projected_cash_flows(settle_date, &projection, &store)
+            .unwrap();
+        let fraction = lagged.accruals().periods()[1].adjusted_fraction;
+        assert!((flows[1].1 - 100.0 * 0.055 * fraction).abs() < 1e-12);
+        assert!(lagged.accrued_interest(settle_date, &store).unwrap() > 0.0);
+    }
+}
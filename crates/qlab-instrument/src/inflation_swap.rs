@@ -0,0 +1,638 @@
+use crate::swap::SwapDirection;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{Context, QLabResult};
+use qlab_math::interpolation::Interpolator;
+use qlab_math::optimization::least_squares_with_report;
+use qlab_math::value::Value;
+use qlab_termstructure::diagnostics::BootstrapDiagnostics;
+use qlab_termstructure::inflation_curve::InflationCurve;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::calendar::Calendar;
+use qlab_time::date::Date;
+use qlab_time::date_rolling::DateRolling;
+use qlab_time::day_count::DayCount;
+use qlab_time::frequency::Frequency;
+use qlab_time::schedule::{AccrualSchedule, StubPolicy};
+
+fn inflation_sign<V: Value>(direction: SwapDirection) -> V {
+    match direction {
+        SwapDirection::Payer => V::one(),
+        SwapDirection::Receiver => -V::one(),
+    }
+}
+
+/// A zero-coupon inflation swap exchanging at maturity the growth of a price index over the life of the swap
+/// against a fixed rate compounded annually, both on the notional.
+///
+/// A payer pays the fixed leg and receives the inflation leg. The index is observed on the start and maturity dates,
+/// and the compounding period is measured by the day count of the inflation curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZeroCouponInflationSwap<V> {
+    direction: SwapDirection,
+    start: Date,
+    maturity: Date,
+    fixed_rate: V,
+    notional: V,
+}
+
+impl<V: Value> ZeroCouponInflationSwap<V> {
+    /// # Errors
+    /// Returns an `InvalidInput` error if `maturity` does not follow `start`.
+    pub fn new(
+        direction: SwapDirection,
+        start: Date,
+        maturity: Date,
+        fixed_rate: V,
+        notional: V,
+    ) -> QLabResult<Self> {
+        if maturity <= start {
+            return Err(InvalidInput(
+                format!("maturity: {maturity} must follow start: {start}").into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            direction,
+            start,
+            maturity,
+            fixed_rate,
+            notional,
+        })
+    }
+
+    #[must_use]
+    pub fn direction(&self) -> SwapDirection {
+        self.direction
+    }
+
+    #[must_use]
+    pub fn start(&self) -> Date {
+        self.start
+    }
+
+    #[must_use]
+    pub fn maturity(&self) -> Date {
+        self.maturity
+    }
+
+    #[must_use]
+    pub fn fixed_rate(&self) -> V {
+        self.fixed_rate
+    }
+
+    #[must_use]
+    pub fn notional(&self) -> V {
+        self.notional
+    }
+
+    fn index_growth<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        inflation_curve: &InflationCurve<D, I>,
+    ) -> QLabResult<(V, V)> {
+        let growth = inflation_curve.index_ratio(self.maturity)?
+            / inflation_curve.index_ratio(self.start)?;
+        Ok((
+            growth,
+            D::calculate_day_count_fraction(self.start, self.maturity)?,
+        ))
+    }
+
+    /// Calculates the fixed rate at which the swap is worth nothing.
+    ///
+    /// # Errors
+    /// Returns an error if the start precedes the base date of `inflation_curve` or the projection fails.
+    pub fn fair_rate<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        inflation_curve: &InflationCurve<D, I>,
+    ) -> QLabResult<V> {
+        let (growth, t) = self.index_growth(inflation_curve)?;
+        Ok(growth.powf(V::one() / t) - V::one())
+    }
+
+    /// Calculates the present value of the swap from the side of its direction.
+    ///
+    /// # Errors
+    /// Returns an error if the projection or a discount factor calculation fails.
+    pub fn present_value<D: DayCount, I: Interpolator<Value = V>, E: DayCount, J>(
+        &self,
+        settle_date: Date,
+        inflation_curve: &InflationCurve<D, I>,
+        discount_curve: &YieldCurve<E, J>,
+    ) -> QLabResult<V>
+    where
+        J: Interpolator<Value = V>,
+    {
+        if self.maturity <= settle_date {
+            return Ok(V::zero());
+        }
+        let (growth, t) = self.index_growth(inflation_curve)?;
+        let fixed = (V::one() + self.fixed_rate).powf(t);
+        Ok(inflation_sign::<V>(self.direction)
+            * self.notional
+            * (growth - fixed)
+            * discount_curve.discount_factor(settle_date, self.maturity)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct YearOnYearPeriod<V> {
+    /// The unadjusted dates on which the index is observed.
+    observation_start: Date,
+    observation_end: Date,
+    payment_date: Date,
+    fraction: V,
+}
+
+/// A year-on-year inflation swap exchanging each year the annual growth rate of a price index
+/// against a fixed rate, both on the notional.
+///
+/// A payer pays the fixed leg and receives the inflation leg. The index is observed on the unadjusted dates of an
+/// annual schedule. Projecting each growth rate as a ratio of forward indices ignores the convexity of the ratio,
+/// which [`YearOnYearInflationSwap::present_value_with_adjustment`] corrects with a model supplied adjustment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YearOnYearInflationSwap<V> {
+    direction: SwapDirection,
+    fixed_rate: V,
+    notional: V,
+    periods: Vec<YearOnYearPeriod<V>>,
+    maturity: Date,
+    last_observation: Date,
+}
+
+impl<V: Value> YearOnYearInflationSwap<V> {
+    /// Creates a swap paying on an annual schedule from `start` to `end` adjusted by `calendar` and `rolling`,
+    /// with fixed accruals measured by `DC`.
+    ///
+    /// # Errors
+    /// Returns an error if the schedule cannot be generated or a day count fraction cannot be calculated.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<DC: DayCount>(
+        direction: SwapDirection,
+        start: Date,
+        end: Date,
+        calendar: &impl Calendar,
+        rolling: DateRolling,
+        fixed_rate: V,
+        notional: V,
+    ) -> QLabResult<Self> {
+        let periods: Vec<_> = AccrualSchedule::generate::<DC>(
+            start,
+            end,
+            Frequency::A,
+            calendar,
+            rolling,
+            StubPolicy::ShortBack,
+        )?
+        .into_iter()
+        .map(|accrual| YearOnYearPeriod {
+            observation_start: accrual.period.unadjusted_start,
+            observation_end: accrual.period.unadjusted_end,
+            payment_date: accrual.period.payment_date,
+            fraction: accrual.adjusted_fraction,
+        })
+        .collect();
+        let last = *periods.last().ok_or_else(|| {
+            InvalidInput(format!("no period lies between {start} and {end}").into())
+        })?;
+        Ok(Self {
+            direction,
+            fixed_rate,
+            notional,
+            periods,
+            maturity: last.payment_date,
+            last_observation: last.observation_end,
+        })
+    }
+
+    #[must_use]
+    pub fn direction(&self) -> SwapDirection {
+        self.direction
+    }
+
+    #[must_use]
+    pub fn fixed_rate(&self) -> V {
+        self.fixed_rate
+    }
+
+    #[must_use]
+    pub fn notional(&self) -> V {
+        self.notional
+    }
+
+    /// Returns the last payment date.
+    #[must_use]
+    pub fn maturity(&self) -> Date {
+        self.maturity
+    }
+
+    /// Returns the last observation date of the index.
+    #[must_use]
+    pub fn last_observation(&self) -> Date {
+        self.last_observation
+    }
+
+    /// Calculates the notional times the sum of the discounted fixed accrual fractions paid after `settle_date`.
+    ///
+    /// # Errors
+    /// Returns an error if a discount factor calculation fails.
+    pub fn annuity<E: DayCount, J: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<E, J>,
+    ) -> QLabResult<V> {
+        self.periods
+            .iter()
+            .filter(|period| period.payment_date > settle_date)
+            .try_fold(V::zero(), |acc, period| {
+                Ok(acc
+                    + self.notional
+                        * period.fraction
+                        * discount_curve.discount_factor(settle_date, period.payment_date)?)
+            })
+    }
+
+    /// Calculates the value of receiving the inflation leg paid after `settle_date`, adding `adjustment`,
+    /// called with the observation dates of each period, to the growth rate projected from `inflation_curve`.
+    ///
+    /// # Errors
+    /// Returns an error if an observation precedes the base date of `inflation_curve`,
+    /// or if `adjustment`, the projection or a discount factor calculation fails.
+    pub fn inflation_leg_value<D, I, E, J>(
+        &self,
+        settle_date: Date,
+        inflation_curve: &InflationCurve<D, I>,
+        discount_curve: &YieldCurve<E, J>,
+        adjustment: impl Fn(Date, Date) -> QLabResult<V>,
+    ) -> QLabResult<V>
+    where
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        E: DayCount,
+        J: Interpolator<Value = V>,
+    {
+        self.periods
+            .iter()
+            .filter(|period| period.payment_date > settle_date)
+            .try_fold(V::zero(), |acc, period| {
+                let growth = inflation_curve.index_ratio(period.observation_end)?
+                    / inflation_curve.index_ratio(period.observation_start)?
+                    - V::one()
+                    + adjustment(period.observation_start, period.observation_end)?;
+                Ok(acc
+                    + self.notional
+                        * growth
+                        * discount_curve.discount_factor(settle_date, period.payment_date)?)
+            })
+    }
+
+    /// Calculates the present value of the swap from the side of its direction with convexity adjusted
+    /// growth rates, see [`YearOnYearInflationSwap::inflation_leg_value`].
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`YearOnYearInflationSwap::inflation_leg_value`].
+    pub fn present_value_with_adjustment<D, I, E, J>(
+        &self,
+        settle_date: Date,
+        inflation_curve: &InflationCurve<D, I>,
+        discount_curve: &YieldCurve<E, J>,
+        adjustment: impl Fn(Date, Date) -> QLabResult<V>,
+    ) -> QLabResult<V>
+    where
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        E: DayCount,
+        J: Interpolator<Value = V>,
+    {
+        let inflation =
+            self.inflation_leg_value(settle_date, inflation_curve, discount_curve, adjustment)?;
+        let fixed = self.fixed_rate * self.annuity(settle_date, discount_curve)?;
+        Ok(inflation_sign::<V>(self.direction) * (inflation - fixed))
+    }
+
+    /// Calculates the present value of the swap without convexity adjustment.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`YearOnYearInflationSwap::inflation_leg_value`].
+    pub fn present_value<D, I, E, J>(
+        &self,
+        settle_date: Date,
+        inflation_curve: &InflationCurve<D, I>,
+        discount_curve: &YieldCurve<E, J>,
+    ) -> QLabResult<V>
+    where
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        E: DayCount,
+        J: Interpolator<Value = V>,
+    {
+        self.present_value_with_adjustment(settle_date, inflation_curve, discount_curve, |_, _| {
+            Ok(V::zero())
+        })
+    }
+
+    /// Calculates the fixed rate for which the present value is zero, with convexity adjusted growth rates.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`YearOnYearInflationSwap::inflation_leg_value`].
+    pub fn fair_rate_with_adjustment<D, I, E, J>(
+        &self,
+        settle_date: Date,
+        inflation_curve: &InflationCurve<D, I>,
+        discount_curve: &YieldCurve<E, J>,
+        adjustment: impl Fn(Date, Date) -> QLabResult<V>,
+    ) -> QLabResult<V>
+    where
+        D: DayCount,
+        I: Interpolator<Value = V>,
+        E: DayCount,
+        J: Interpolator<Value = V>,
+    {
+        Ok(
+            self.inflation_leg_value(settle_date, inflation_curve, discount_curve, adjustment)?
+                / self.annuity(settle_date, discount_curve)?,
+        )
+    }
+}
+
+/// Bootstraps a zero-coupon inflation curve from the index on `base_date` and quoted zero-coupon
+/// and year-on-year inflation swaps.
+///
+/// The curve has a pillar on the maturity of each zero-coupon swap and on the last observation of each
+/// year-on-year swap, whose rates are solved so that the fair rates match the quoted fixed rates.
+/// Year-on-year swaps are discounted on `discount_curve` as of `base_date`, with growth rates corrected by `adjustment`.
+///
+/// # Errors
+/// Returns an error if no swap is given, if two swaps share a pillar, or if the solver fails.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::inflation_swap::{bootstrap, ZeroCouponInflationSwap};
+/// use qlab_instrument::swap::SwapDirection;
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::inflation_curve::InflationCurve;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let base_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let swaps = [
+///     ZeroCouponInflationSwap::new(SwapDirection::Payer, base_date, Date::from_ymd(2026, 1, 2).unwrap(), 0.025, 1.0)
+///         .unwrap(),
+///     ZeroCouponInflationSwap::new(SwapDirection::Payer, base_date, Date::from_ymd(2029, 1, 2).unwrap(), 0.023, 1.0)
+///         .unwrap(),
+/// ];
+/// let discount_curve: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(base_date, &[Date::from_ymd(2034, 1, 2).unwrap()], &[0.04]).unwrap();
+/// let curve: InflationCurve<Act365, Linear<f64>> =
+///     bootstrap(base_date, 310.0, &swaps, &[], &discount_curve, |_, _| Ok(0.0)).unwrap();
+/// assert!((swaps[1].fair_rate(&curve).unwrap() - 0.023).abs() < 1e-9);
+/// ```
+pub fn bootstrap<V: Value, D, I, E, J>(
+    base_date: Date,
+    base_index: V,
+    zero_coupon: &[ZeroCouponInflationSwap<V>],
+    year_on_year: &[YearOnYearInflationSwap<V>],
+    discount_curve: &YieldCurve<E, J>,
+    adjustment: impl Fn(Date, Date) -> QLabResult<V>,
+) -> QLabResult<InflationCurve<D, I>>
+where
+    D: DayCount,
+    I: Interpolator<Value = V>,
+    E: DayCount,
+    J: Interpolator<Value = V>,
+{
+    bootstrap_with_diagnostics(
+        base_date,
+        base_index,
+        zero_coupon,
+        year_on_year,
+        discount_curve,
+        adjustment,
+    )
+    .0
+}
+
+/// Bootstraps the curve as [`bootstrap`] does and returns the diagnostics of the solve together with the
+/// outcome, with an event per pillar. The diagnostics are empty if the swaps are rejected before solving.
+pub fn bootstrap_with_diagnostics<V: Value, D, I, E, J>(
+    base_date: Date,
+    base_index: V,
+    zero_coupon: &[ZeroCouponInflationSwap<V>],
+    year_on_year: &[YearOnYearInflationSwap<V>],
+    discount_curve: &YieldCurve<E, J>,
+    adjustment: impl Fn(Date, Date) -> QLabResult<V>,
+) -> (QLabResult<InflationCurve<D, I>>, BootstrapDiagnostics<V>)
+where
+    D: DayCount,
+    I: Interpolator<Value = V>,
+    E: DayCount,
+    J: Interpolator<Value = V>,
+{
+    let mut diagnostics = BootstrapDiagnostics::default();
+    // the pillars in the order of the residuals, zero-coupon swaps first
+    let instrument_pillars: Vec<_> = zero_coupon
+        .iter()
+        .map(|swap| swap.maturity)
+        .chain(
+            year_on_year
+                .iter()
+                .map(YearOnYearInflationSwap::last_observation),
+        )
+        .collect();
+    let mut pillars: Vec<(Date, V)> = instrument_pillars
+        .iter()
+        .copied()
+        .zip(
+            zero_coupon
+                .iter()
+                .map(|swap| swap.fixed_rate)
+                .chain(year_on_year.iter().map(|swap| swap.fixed_rate)),
+        )
+        .collect();
+    if pillars.is_empty() {
+        return (
+            Err(InvalidInput("at least one inflation swap is required".into()).into()),
+            diagnostics,
+        );
+    }
+    pillars.sort_by_key(|&(maturity, _)| maturity);
+    if let Some(pair) = pillars.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return (
+            Err(
+                InvalidInput(format!("two inflation swaps share the pillar {}", pair[0].0).into())
+                    .into(),
+            ),
+            diagnostics,
+        );
+    }
+    let maturities: Vec<_> = pillars.iter().map(|&(maturity, _)| maturity).collect();
+    let initial: Vec<_> = pillars.iter().map(|&(_, rate)| rate).collect();
+    let curve =
+        |rates: &[V]| InflationCurve::<D, I>::new(base_date, base_index, &maturities, rates);
+    let residuals = |rates: &[V]| {
+        let curve = curve(rates)?;
+        zero_coupon
+            .iter()
+            .map(|swap| {
+                Ok(swap.fair_rate(&curve).with_context(|| {
+                    format!("pricing the zero-coupon swap to {}", swap.maturity)
+                })? - swap.fixed_rate)
+            })
+            .chain(year_on_year.iter().map(|swap| {
+                Ok(swap
+                    .fair_rate_with_adjustment(base_date, &curve, discount_curve, &adjustment)
+                    .with_context(|| {
+                        format!("pricing the year-on-year swap to {}", swap.maturity())
+                    })?
+                    - swap.fixed_rate)
+            }))
+            .collect::<QLabResult<Vec<_>>>()
+    };
+    let solved = least_squares_with_report(residuals, &initial)
+        .and_then(|report| Ok((residuals(&report.parameters)?, report)));
+    let curve = match solved {
+        Ok((residuals, report)) => {
+            diagnostics.record_solved(instrument_pillars.iter().copied().zip(residuals), &report);
+            curve(&report.parameters)
+        }
+        Err(err) => {
+            diagnostics.record_failed(maturities.iter().copied(), &err.to_string());
+            Err(err)
+        }
+    };
+    (
+        curve.context("bootstrapping the inflation curve"),
+        diagnostics,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    fn discount_curve(settle_date: Date) -> YieldCurve<Act365, Linear<f64>> {
+        YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2025, 1, 2).unwrap(),
+                Date::from_ymd(2034, 1, 2).unwrap(),
+            ],
+            &[0.045, 0.04],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_zero_coupon() {
+        let base_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let maturity = Date::from_ymd(2029, 1, 2).unwrap();
+        let curve: InflationCurve<Act365, Linear<f64>> =
+            InflationCurve::new(base_date, 300.0, &[maturity], &[0.025]).unwrap();
+        let swap =
+            ZeroCouponInflationSwap::new(SwapDirection::Payer, base_date, maturity, 0.02, 1e6)
+                .unwrap();
+        assert!((swap.fair_rate(&curve).unwrap() - 0.025).abs() < 1e-12);
+        let discount = discount_curve(base_date);
+        let pv = swap.present_value(base_date, &curve, &discount).unwrap();
+        let receiver =
+            ZeroCouponInflationSwap::new(SwapDirection::Receiver, base_date, maturity, 0.02, 1e6)
+                .unwrap();
+        assert!(pv > 0.0);
+        assert!(
+            (pv + receiver
+                .present_value(base_date, &curve, &discount)
+                .unwrap())
+            .abs()
+                < 1e-9
+        );
+        assert!(
+            ZeroCouponInflationSwap::new(SwapDirection::Payer, maturity, base_date, 0.02, 1e6)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let base_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = discount_curve(base_date);
+        let zero_coupon = [ZeroCouponInflationSwap::new(
+            SwapDirection::Payer,
+            base_date,
+            Date::from_ymd(2026, 1, 2).unwrap(),
+            0.03,
+            1.0,
+        )
+        .unwrap()];
+        let year_on_year = [YearOnYearInflationSwap::new::<Act365>(
+            SwapDirection::Payer,
+            base_date,
+            Date::from_ymd(2031, 1, 2).unwrap(),
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.025,
+            1.0,
+        )
+        .unwrap()];
+        let adjustment = |_: Date, _: Date| Ok(0.0005);
+        let curve: InflationCurve<Act365, Linear<f64>> = bootstrap(
+            base_date,
+            300.0,
+            &zero_coupon,
+            &year_on_year,
+            &discount,
+            adjustment,
+        )
+        .unwrap();
+        assert_eq!(curve.pillars().len(), 2);
+        assert!((zero_coupon[0].fair_rate(&curve).unwrap() - 0.03).abs() < 1e-9);
+        let pv = year_on_year[0]
+            .present_value_with_adjustment(base_date, &curve, &discount, adjustment)
+            .unwrap();
+        assert!(pv.abs() < 1e-9);
+        // without the adjustment the inflation leg is worth less
+        assert!(
+            year_on_year[0]
+                .present_value(base_date, &curve, &discount)
+                .unwrap()
+                < 0.0
+        );
+        assert!(bootstrap::<_, Act365, Linear<f64>, _, _>(
+            base_date,
+            300.0,
+            &[zero_coupon[0], zero_coupon[0]],
+            &[],
+            &discount,
+            adjustment,
+        )
+        .is_err());
+
+        let (solved, diagnostics) = bootstrap_with_diagnostics::<_, Act365, Linear<f64>, _, _>(
+            base_date,
+            300.0,
+            &zero_coupon,
+            &year_on_year,
+            &discount,
+            adjustment,
+        );
+        assert!(solved.is_ok());
+        let events = diagnostics.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].pillar(), year_on_year[0].last_observation());
+        assert!(diagnostics.max_residual().unwrap() < 1e-9);
+        // an adjustment which cannot be evaluated fails both pillars of the joint solve
+        let (failed, diagnostics) = bootstrap_with_diagnostics::<_, Act365, Linear<f64>, _, _>(
+            base_date,
+            300.0,
+            &zero_coupon,
+            &year_on_year,
+            &discount,
+            |_, _| Err(InvalidInput("no adjustment".into()).into()),
+        );
+        assert!(failed.is_err());
+        assert_eq!(diagnostics.failures().len(), 2);
+    }
+}
@@ -1 +1,26 @@
+pub mod alm;
+pub mod asian_option;
 pub mod bond;
+pub mod bond_curve;
+pub mod cash_flow_engine;
+pub mod convention_risk;
+pub mod coupon_report;
+pub mod csa;
+pub mod curve_input;
+pub mod curve_risk;
+pub mod equity_future;
+pub mod exercise;
+pub mod fixing_store;
+pub mod fra;
+pub mod frn;
+pub mod inflation_swap;
+pub mod lifecycle;
+pub mod pnl_explain;
+pub mod portfolio;
+pub mod pricing_cache;
+pub mod quote;
+pub mod sofr_future;
+pub mod spot_vol_ladder;
+pub mod stress;
+pub mod swap;
+pub mod testing;
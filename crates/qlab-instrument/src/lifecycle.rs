@@ -0,0 +1,255 @@
+use crate::exercise::Strike;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+
+/// The record of an option exercised on a date, settled by a single amount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExerciseRecord<V> {
+    pub date: Date,
+    pub strike: Strike<V>,
+    /// The amount received on the exercise date, negative if paid.
+    pub settlement_amount: V,
+}
+
+/// An event in the life of a trade after its inception.
+///
+/// An event affects the flows paid after its date, and its fee is a flow paid on its date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LifecycleEvent<V> {
+    /// Scales the outstanding notional by `factor`, as in an increase of the trade or a notional reset.
+    NotionalChange { date: Date, factor: V },
+    /// Terminates `fraction` of the outstanding notional against `fee`, received if positive.
+    PartialTermination { date: Date, fraction: V, fee: V },
+    /// Terminates the trade against `fee`, received if positive.
+    Termination { date: Date, fee: V },
+    /// Exercises the option, ending the trade.
+    Exercise(ExerciseRecord<V>),
+}
+
+impl<V: Value> LifecycleEvent<V> {
+    #[must_use]
+    pub fn date(&self) -> Date {
+        match *self {
+            Self::NotionalChange { date, .. }
+            | Self::PartialTermination { date, .. }
+            | Self::Termination { date, .. }
+            | Self::Exercise(ExerciseRecord { date, .. }) => date,
+        }
+    }
+
+    /// Returns the factor by which the event scales the outstanding notional.
+    fn factor(&self) -> V {
+        match *self {
+            Self::NotionalChange { factor, .. } => factor,
+            Self::PartialTermination { fraction, .. } => V::one() - fraction,
+            Self::Termination { .. } | Self::Exercise(_) => V::zero(),
+        }
+    }
+
+    /// Returns the amount settled on the date of the event.
+    fn settlement(&self) -> V {
+        match *self {
+            Self::NotionalChange { .. } => V::zero(),
+            Self::PartialTermination { fee, .. } | Self::Termination { fee, .. } => fee,
+            Self::Exercise(record) => record.settlement_amount,
+        }
+    }
+}
+
+/// The cash flows of a trade split at an evaluation date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleFlows<V> {
+    /// The flows paid on or before the evaluation date, which are settled and no longer valued.
+    pub past: Vec<(Date, V)>,
+    /// The flows paid after the evaluation date.
+    pub future: Vec<(Date, V)>,
+}
+
+/// The history of a trade, applied to the flows of the instrument as originally issued so that
+/// a trade with history is valued on what remains of it.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::lifecycle::{Lifecycle, LifecycleEvent};
+/// use qlab_time::date::Date;
+///
+/// let date = |year| Date::from_ymd(year, 6, 30).unwrap();
+/// let lifecycle = Lifecycle::new()
+///     .with_event(LifecycleEvent::PartialTermination { date: date(2024), fraction: 0.25, fee: 1.5 })
+///     .unwrap();
+/// let flows = lifecycle.apply(&[(date(2024), 5.0), (date(2025), 105.0)], date(2024));
+/// assert_eq!(flows.past, [(date(2024), 5.0), (date(2024), 1.5)]);
+/// assert_eq!(flows.future, [(date(2025), 78.75)]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lifecycle<V> {
+    events: Vec<LifecycleEvent<V>>,
+}
+
+impl<V: Value> Lifecycle<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    /// Records an event, which must not precede the recorded ones.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the event precedes the last recorded event, if the trade has ended,
+    /// or if a terminated fraction is not within `(0, 1]` or a notional factor is negative.
+    pub fn with_event(mut self, event: LifecycleEvent<V>) -> QLabResult<Self> {
+        if let Some(last) = self.events.last() {
+            if event.date() < last.date() {
+                return Err(InvalidInput(
+                    format!(
+                        "{} precedes the last event on {}",
+                        event.date(),
+                        last.date()
+                    )
+                    .into(),
+                )
+                .into());
+            }
+            if last.factor().is_zero() {
+                return Err(
+                    InvalidInput(format!("the trade has ended on {}", last.date()).into()).into(),
+                );
+            }
+        }
+        let valid = match event {
+            LifecycleEvent::NotionalChange { factor, .. } => factor >= V::zero(),
+            LifecycleEvent::PartialTermination { fraction, .. } => {
+                fraction > V::zero() && fraction <= V::one()
+            }
+            LifecycleEvent::Termination { .. } | LifecycleEvent::Exercise(_) => true,
+        };
+        if !valid {
+            return Err(InvalidInput(format!("invalid event: {event:?}").into()).into());
+        }
+        self.events.push(event);
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn events(&self) -> &[LifecycleEvent<V>] {
+        &self.events
+    }
+
+    /// Returns the fraction of the original notional outstanding for flows paid on `date`,
+    /// i.e. after the events dated before it.
+    #[must_use]
+    pub fn outstanding_factor(&self, date: Date) -> V {
+        self.events
+            .iter()
+            .take_while(|event| event.date() < date)
+            .fold(V::one(), |acc, event| acc * event.factor())
+    }
+
+    /// Returns `true` if the trade has been terminated or exercised on or before `date`.
+    #[must_use]
+    pub fn has_ended(&self, date: Date) -> bool {
+        self.events
+            .iter()
+            .any(|event| event.date() <= date && event.factor().is_zero())
+    }
+
+    /// Returns the exercise of the trade, if any.
+    #[must_use]
+    pub fn exercise(&self) -> Option<&ExerciseRecord<V>> {
+        self.events.iter().find_map(|event| match event {
+            LifecycleEvent::Exercise(record) => Some(record),
+            _ => None,
+        })
+    }
+
+    /// Scales each of `flows`, given as originally issued, by the outstanding factor on its date,
+    /// adds the settlements of the events, and splits the flows at `evaluation_date`.
+    /// Flows of a trade that has ended are dropped.
+    #[must_use]
+    pub fn apply(&self, flows: &[(Date, V)], evaluation_date: Date) -> LifecycleFlows<V> {
+        let mut all: Vec<_> = flows
+            .iter()
+            .map(|&(date, amount)| (date, amount * self.outstanding_factor(date)))
+            .filter(|(_, amount)| !amount.is_zero())
+            .chain(
+                self.events
+                    .iter()
+                    .map(|event| (event.date(), event.settlement()))
+                    .filter(|(_, amount)| !amount.is_zero()),
+            )
+            .collect();
+        all.sort_by_key(|&(date, _)| date);
+        let (past, future) = all
+            .into_iter()
+            .partition(|&(date, _)| date <= evaluation_date);
+        LifecycleFlows { past, future }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let date = |year, month| Date::from_ymd(year, month, 1).unwrap();
+        let flows: Vec<(Date, f64)> = (2024..2028).map(|year| (date(year, 1), 10.0)).collect();
+        let lifecycle = Lifecycle::new()
+            .with_event(LifecycleEvent::NotionalChange {
+                date: date(2024, 6),
+                factor: 2.0_f64,
+            })
+            .unwrap()
+            .with_event(LifecycleEvent::PartialTermination {
+                date: date(2025, 6),
+                fraction: 0.5,
+                fee: -1.0,
+            })
+            .unwrap();
+        assert!((lifecycle.outstanding_factor(date(2025, 1)) - 2.0).abs() < 1e-15);
+        assert!((lifecycle.outstanding_factor(date(2026, 1)) - 1.0).abs() < 1e-15);
+        let split = lifecycle.apply(&flows, date(2025, 6));
+        assert_eq!(
+            split.past,
+            [
+                (date(2024, 1), 10.0),
+                (date(2025, 1), 20.0),
+                (date(2025, 6), -1.0)
+            ]
+        );
+        assert_eq!(split.future, [(date(2026, 1), 10.0), (date(2027, 1), 10.0)]);
+
+        let exercised = lifecycle
+            .with_event(LifecycleEvent::Exercise(ExerciseRecord {
+                date: date(2026, 6),
+                strike: Strike::Price(100.0),
+                settlement_amount: 3.0,
+            }))
+            .unwrap();
+        assert!(exercised.has_ended(date(2026, 6)));
+        assert!(!exercised.has_ended(date(2026, 5)));
+        assert_eq!(
+            exercised.exercise().map(|record| record.date),
+            Some(date(2026, 6))
+        );
+        let split = exercised.apply(&flows, date(2025, 6));
+        assert_eq!(split.future, [(date(2026, 1), 10.0), (date(2026, 6), 3.0)]);
+        assert!(exercised
+            .clone()
+            .with_event(LifecycleEvent::Termination {
+                date: date(2027, 1),
+                fee: 0.0
+            })
+            .is_err());
+        assert!(Lifecycle::new()
+            .with_event(LifecycleEvent::PartialTermination {
+                date: date(2024, 1),
+                fraction: 1.5,
+                fee: 0.0
+            })
+            .is_err());
+    }
+}
@@ -0,0 +1,372 @@
+use crate::bond::Bond;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::collections::{BTreeMap, HashSet};
+
+/// The market state used to value a portfolio on a given date.
+///
+/// `curves` holds the rate curves and `vol_surfaces` the volatility market, which is `()` for portfolios
+/// without options. `fx_rates` maps each currency to the value of one unit of it in the reporting currency.
+pub struct Snapshot<'a, C, S, V> {
+    pub valuation_date: Date,
+    pub curves: &'a C,
+    pub vol_surfaces: &'a S,
+    pub fx_rates: &'a BTreeMap<String, V>,
+}
+
+/// Revalues a trade in the reporting currency on a snapshot.
+pub type Revaluation<'a, C, S, V> = dyn Fn(&Snapshot<C, S, V>) -> QLabResult<V> + 'a;
+
+/// The decomposition of the value change of a portfolio between two snapshots.
+///
+/// The components are obtained by sequential revaluation and add up to the total P&L.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PnlExplain<V> {
+    /// The value of the start portfolio in the start snapshot.
+    pub start_value: V,
+    /// The value of the end portfolio in the end snapshot.
+    pub end_value: V,
+    /// The change from rolling the valuation date forward with the start market unchanged.
+    pub carry: V,
+    /// The change from moving from the start curves to the end curves.
+    pub curve: V,
+    /// The change from moving from the start volatility surfaces to the end ones.
+    pub vol: V,
+    /// The change from moving from the start FX rates to the end ones.
+    pub fx: V,
+    /// The value of the trades which only belong to the end portfolio.
+    pub new_trades: V,
+    /// The negated start value of the trades which only belong to the start portfolio.
+    pub removed_trades: V,
+}
+
+impl<V: Value> PnlExplain<V> {
+    /// Returns the total P&L, i.e. the end value minus the start value.
+    #[must_use]
+    pub fn total_pnl(&self) -> V {
+        self.end_value - self.start_value
+    }
+
+    /// Returns the part of the total P&L not attributed to any component, which is zero up to rounding.
+    #[must_use]
+    pub fn unexplained(&self) -> V {
+        self.total_pnl()
+            - self.carry
+            - self.curve
+            - self.vol
+            - self.fx
+            - self.new_trades
+            - self.removed_trades
+    }
+}
+
+fn value<V: Value, C, S>(
+    trades: &[&(&str, &Revaluation<C, S, V>)],
+    snapshot: &Snapshot<C, S, V>,
+) -> QLabResult<V> {
+    trades
+        .iter()
+        .try_fold(V::zero(), |acc, (_, revalue)| Ok(acc + revalue(snapshot)?))
+}
+
+/// Explains the value change of a portfolio of `(trade_id, revaluation)` pairs from `start` to `end`.
+///
+/// Trades are matched by their ID. Trades held over the whole period are revalued in turn with
+/// the valuation date moved to the end date (carry), the end curves (curve), the end volatility surfaces (vol)
+/// and the end FX rates (fx), each step keeping the changes of the previous ones;
+/// trades entered or removed in between are reported separately. A component is zero when no held trade
+/// depends on the corresponding part of the market.
+///
+/// # Errors
+/// Returns an error if a revaluation fails.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::pnl_explain::{explain, Revaluation, Snapshot};
+/// use qlab_time::date::Date;
+/// use std::collections::BTreeMap;
+///
+/// // a 1,000 USD cash balance reported in JPY
+/// let cash = |market: &Snapshot<(), (), f64>| Ok(1000.0 * market.fx_rates["USD"]);
+/// let trades: [(&str, &Revaluation<_, _, _>); 1] = [("cash", &cash)];
+/// let start_fx = BTreeMap::from([("USD".to_string(), 150.0)]);
+/// let end_fx = BTreeMap::from([("USD".to_string(), 151.0)]);
+/// let start = Snapshot {
+///     valuation_date: Date::from_ymd(2023, 10, 10).unwrap(),
+///     curves: &(),
+///     vol_surfaces: &(),
+///     fx_rates: &start_fx,
+/// };
+/// let end = Snapshot {
+///     valuation_date: Date::from_ymd(2023, 10, 11).unwrap(),
+///     fx_rates: &end_fx,
+///     ..start
+/// };
+/// let pnl = explain(&trades, &trades, &start, &end).unwrap();
+/// assert!((pnl.fx - 1000.0).abs() < 1e-9);
+/// assert!(pnl.carry.abs() < 1e-9 && pnl.curve.abs() < 1e-9 && pnl.vol.abs() < 1e-9);
+/// ```
+pub fn explain<V: Value, C, S>(
+    start_trades: &[(&str, &Revaluation<C, S, V>)],
+    end_trades: &[(&str, &Revaluation<C, S, V>)],
+    start: &Snapshot<C, S, V>,
+    end: &Snapshot<C, S, V>,
+) -> QLabResult<PnlExplain<V>> {
+    let start_ids: HashSet<&str> = start_trades.iter().map(|(trade_id, _)| *trade_id).collect();
+    let end_ids: HashSet<&str> = end_trades.iter().map(|(trade_id, _)| *trade_id).collect();
+    let (held, removed): (Vec<_>, Vec<_>) = start_trades
+        .iter()
+        .partition(|(trade_id, _)| end_ids.contains(trade_id));
+    let added: Vec<_> = end_trades
+        .iter()
+        .filter(|(trade_id, _)| !start_ids.contains(trade_id))
+        .collect();
+    let all_start: Vec<_> = start_trades.iter().collect();
+    let all_end: Vec<_> = end_trades.iter().collect();
+
+    let carried = Snapshot {
+        valuation_date: end.valuation_date,
+        ..*start
+    };
+    let curve_moved = Snapshot {
+        curves: end.curves,
+        ..carried
+    };
+    let vol_moved = Snapshot {
+        vol_surfaces: end.vol_surfaces,
+        ..curve_moved
+    };
+    let held_start = value(&held, start)?;
+    let held_carried = value(&held, &carried)?;
+    let held_curve_moved = value(&held, &curve_moved)?;
+    let held_vol_moved = value(&held, &vol_moved)?;
+    let held_end = value(&held, end)?;
+    Ok(PnlExplain {
+        start_value: value(&all_start, start)?,
+        end_value: value(&all_end, end)?,
+        carry: held_carried - held_start,
+        curve: held_curve_moved - held_carried,
+        vol: held_vol_moved - held_curve_moved,
+        fx: held_end - held_vol_moved,
+        new_trades: value(&added, end)?,
+        removed_trades: -value(&removed, start)?,
+    })
+}
+
+type BondRevaluation<'a, V, D, I, S> = (&'a str, Box<Revaluation<'a, YieldCurve<D, I>, S, V>>);
+
+fn bond_revaluations<'a, V: Value, D: DayCount, I: Interpolator<Value = V>, S>(
+    bonds: &'a [Bond<V>],
+) -> Vec<BondRevaluation<'a, V, D, I, S>> {
+    bonds
+        .iter()
+        .map(|bond| {
+            let revalue: Box<Revaluation<'a, YieldCurve<D, I>, S, V>> =
+                Box::new(move |market| bond.discounted_value(market.valuation_date, market.curves));
+            (bond.bond_id(), revalue)
+        })
+        .collect()
+}
+
+/// Explains the value change of a bond portfolio from `start` to `end`, matching trades by their bond ID.
+///
+/// Bonds are valued on the yield curve of the snapshot in its own currency, so their vol and FX
+/// components are zero.
+///
+/// # Errors
+/// Returns an error if a valuation fails, e.g. if the end date precedes the settlement date of the start curve.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::pnl_explain::{explain_bonds, Snapshot};
+/// use qlab_instrument::testing::semi_annual_bond;
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+/// use std::collections::BTreeMap;
+///
+/// let start_date = Date::from_ymd(2023, 10, 10).unwrap();
+/// let end_date = Date::from_ymd(2023, 10, 11).unwrap();
+/// let maturities = [Date::from_ymd(2023, 10, 11).unwrap(), Date::from_ymd(2033, 10, 10).unwrap()];
+/// let start_curve: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(start_date, &maturities, &[0.03, 0.04]).unwrap();
+/// let end_curve: YieldCurve<Act365, Linear<f64>> =
+///     YieldCurve::new(end_date, &maturities, &[0.031, 0.041]).unwrap();
+/// let bond = semi_annual_bond("bond", Date::from_ymd(2028, 11, 7).unwrap(), 0.05, 100.0).unwrap();
+/// let bonds = [bond];
+/// let fx_rates = BTreeMap::new();
+/// let pnl = explain_bonds(
+///     &bonds,
+///     &bonds,
+///     &Snapshot { valuation_date: start_date, curves: &start_curve, vol_surfaces: &(), fx_rates: &fx_rates },
+///     &Snapshot { valuation_date: end_date, curves: &end_curve, vol_surfaces: &(), fx_rates: &fx_rates },
+/// )
+/// .unwrap();
+/// assert!(pnl.carry > 0.0);
+/// assert!(pnl.curve < 0.0);
+/// assert!(pnl.vol.abs() < f64::EPSILON && pnl.fx.abs() < f64::EPSILON);
+/// assert!(pnl.unexplained().abs() < 1e-10);
+/// ```
+pub fn explain_bonds<V: Value, D: DayCount, I: Interpolator<Value = V>, S>(
+    start_bonds: &[Bond<V>],
+    end_bonds: &[Bond<V>],
+    start: &Snapshot<YieldCurve<D, I>, S, V>,
+    end: &Snapshot<YieldCurve<D, I>, S, V>,
+) -> QLabResult<PnlExplain<V>> {
+    let start_revaluations = bond_revaluations(start_bonds);
+    let end_revaluations = bond_revaluations(end_bonds);
+    let start_trades: Vec<_> = start_revaluations
+        .iter()
+        .map(|(bond_id, revalue)| (*bond_id, revalue.as_ref()))
+        .collect();
+    let end_trades: Vec<_> = end_revaluations
+        .iter()
+        .map(|(bond_id, revalue)| (*bond_id, revalue.as_ref()))
+        .collect();
+    explain(&start_trades, &end_trades, start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::semi_annual_bond;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_model::black;
+    use qlab_model::option_type::OptionType;
+    use qlab_time::day_count::act_365::Act365;
+
+    type Curve = YieldCurve<Act365, Linear<f64>>;
+
+    fn bond(bond_id: &str, coupon_rate: f64) -> Bond<f64> {
+        semi_annual_bond(
+            bond_id,
+            Date::from_ymd(2028, 11, 7).unwrap(),
+            coupon_rate,
+            100.0,
+        )
+        .unwrap()
+    }
+
+    fn curves(start_date: Date, end_date: Date) -> (Curve, Curve) {
+        let long_end = Date::from_ymd(2033, 10, 10).unwrap();
+        let start_curve = YieldCurve::new(
+            start_date,
+            &[Date::from_ymd(2023, 10, 11).unwrap(), long_end],
+            &[0.03, 0.04],
+        )
+        .unwrap();
+        let end_curve = YieldCurve::new(
+            end_date,
+            &[Date::from_ymd(2023, 10, 18).unwrap(), long_end],
+            &[0.032, 0.041],
+        )
+        .unwrap();
+        (start_curve, end_curve)
+    }
+
+    #[test]
+    fn test_explain_bonds() {
+        let start_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let end_date = Date::from_ymd(2023, 10, 17).unwrap();
+        let (start_curve, end_curve) = curves(start_date, end_date);
+        let start_bonds = [bond("held", 0.05), bond("sold", 0.04)];
+        let end_bonds = [bond("held", 0.05), bond("bought", 0.03)];
+        let fx_rates = BTreeMap::new();
+        let start = Snapshot {
+            valuation_date: start_date,
+            curves: &start_curve,
+            vol_surfaces: &(),
+            fx_rates: &fx_rates,
+        };
+        let end = Snapshot {
+            valuation_date: end_date,
+            curves: &end_curve,
+            vol_surfaces: &(),
+            fx_rates: &fx_rates,
+        };
+        let pnl = explain_bonds(&start_bonds, &end_bonds, &start, &end).unwrap();
+        assert!(pnl.carry > 0.0);
+        assert!(pnl.curve < 0.0);
+        assert!(pnl.vol.abs() < f64::EPSILON);
+        assert!(pnl.fx.abs() < f64::EPSILON);
+        assert!(pnl.new_trades > 0.0);
+        assert!(pnl.removed_trades < 0.0);
+        assert!(
+            (pnl.removed_trades
+                + start_bonds[1]
+                    .discounted_value(start_date, &start_curve)
+                    .unwrap())
+            .abs()
+                < 1e-12
+        );
+        assert!(pnl.unexplained().abs() < 1e-10);
+
+        // nothing changes between identical snapshots
+        let same = explain_bonds(&start_bonds, &start_bonds, &start, &start).unwrap();
+        assert!(same.total_pnl().abs() < f64::EPSILON);
+        assert!(same.carry.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_explain_vol_and_fx() {
+        let start_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let end_date = Date::from_ymd(2023, 10, 17).unwrap();
+        let expiry = Date::from_ymd(2024, 10, 10).unwrap();
+        let (start_curve, end_curve) = curves(start_date, end_date);
+        // a USD call on a flat volatility, reported in JPY
+        let call = |market: &Snapshot<Curve, f64, f64>| {
+            let df = market
+                .curves
+                .discount_factor(market.valuation_date, expiry)?;
+            let t = Act365::calculate_day_count_fraction(market.valuation_date, expiry)?;
+            let price = black::price(OptionType::Call, 100.0, 100.0, *market.vol_surfaces, t);
+            Ok(market.fx_rates["USD"] * df * price)
+        };
+        let trades: [(&str, &Revaluation<_, _, _>); 1] = [("call", &call)];
+        let (start_fx, end_fx) = (
+            BTreeMap::from([("USD".to_string(), 150.0)]),
+            BTreeMap::from([("USD".to_string(), 151.0)]),
+        );
+        let start = Snapshot {
+            valuation_date: start_date,
+            curves: &start_curve,
+            vol_surfaces: &0.2,
+            fx_rates: &start_fx,
+        };
+        let end = Snapshot {
+            valuation_date: end_date,
+            curves: &end_curve,
+            vol_surfaces: &0.25,
+            fx_rates: &end_fx,
+        };
+        let pnl = explain(&trades, &trades, &start, &end).unwrap();
+        assert!(pnl.carry < 0.0);
+        assert!(pnl.vol > 0.0);
+        assert!(pnl.fx > 0.0);
+        // the FX move is applied last, so it scales the end value in USD
+        let end_usd = call(&end).unwrap() / 151.0;
+        assert!((pnl.fx - end_usd).abs() < 1e-9);
+        assert!(pnl.new_trades.abs() < f64::EPSILON);
+        assert!(pnl.removed_trades.abs() < f64::EPSILON);
+        assert!(pnl.unexplained().abs() < 1e-9);
+
+        // the vol bucket stays empty for a trade which does not depend on it
+        let forward = |market: &Snapshot<Curve, f64, f64>| {
+            let df = market
+                .curves
+                .discount_factor(market.valuation_date, expiry)?;
+            Ok(market.fx_rates["USD"] * df * 5.0)
+        };
+        let trades: [(&str, &Revaluation<_, _, _>); 1] = [("forward", &forward)];
+        let pnl = explain(&trades, &trades, &start, &end).unwrap();
+        assert!(pnl.vol.abs() < f64::EPSILON);
+        assert!(pnl.fx > 0.0);
+    }
+}
@@ -0,0 +1,484 @@
+use crate::bond::Bond;
+use crate::lifecycle::Lifecycle;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The attribute by which position results are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AggregationKey {
+    Counterparty,
+    Book,
+    Currency,
+    /// The netting set, where a position outside any netting set forms a [`Group::Standalone`] of its own.
+    NettingSet,
+}
+
+/// A group of positions formed by an [`AggregationKey`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Group {
+    /// The positions sharing the value of the key.
+    Named(String),
+    /// A position outside any netting set, by its index in [`Portfolio::positions`], which nets with nothing.
+    Standalone(usize),
+}
+
+impl Group {
+    /// Creates the group of the positions whose key has the value `name`.
+    #[must_use]
+    pub fn named(name: &str) -> Self {
+        Self::Named(name.to_string())
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(name) => f.write_str(name),
+            Self::Standalone(index) => write!(f, "position #{index}"),
+        }
+    }
+}
+
+/// A holding of a bond together with the metadata used to slice results.
+pub struct Position<V> {
+    bond: Bond<V>,
+    quantity: V,
+    counterparty: String,
+    book: String,
+    currency: String,
+    netting_set: Option<String>,
+    lifecycle: Option<Lifecycle<V>>,
+}
+
+impl<V: Value> Position<V> {
+    /// Creates a position outside any netting set. The currency is stored in upper case.
+    #[must_use]
+    pub fn new(bond: Bond<V>, quantity: V, counterparty: &str, book: &str, currency: &str) -> Self {
+        Self {
+            bond,
+            quantity,
+            counterparty: counterparty.to_string(),
+            book: book.to_string(),
+            currency: currency.to_uppercase(),
+            netting_set: None,
+            lifecycle: None,
+        }
+    }
+
+    /// Assigns the position to a netting set, within which exposures offset each other.
+    #[must_use]
+    pub fn with_netting_set(mut self, netting_set: &str) -> Self {
+        self.netting_set = Some(netting_set.to_string());
+        self
+    }
+
+    /// Attaches the history of the trade, so that the position is valued on the remaining flows of the bond.
+    #[must_use]
+    pub fn with_lifecycle(mut self, lifecycle: Lifecycle<V>) -> Self {
+        self.lifecycle = Some(lifecycle);
+        self
+    }
+
+    #[must_use]
+    pub fn lifecycle(&self) -> Option<&Lifecycle<V>> {
+        self.lifecycle.as_ref()
+    }
+
+    #[must_use]
+    pub fn bond(&self) -> &Bond<V> {
+        &self.bond
+    }
+
+    #[must_use]
+    pub fn quantity(&self) -> V {
+        self.quantity
+    }
+
+    #[must_use]
+    pub fn counterparty(&self) -> &str {
+        &self.counterparty
+    }
+
+    #[must_use]
+    pub fn book(&self) -> &str {
+        &self.book
+    }
+
+    #[must_use]
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    #[must_use]
+    pub fn netting_set(&self) -> Option<&str> {
+        self.netting_set.as_deref()
+    }
+
+    /// Returns the value of the position's attribute selected by `key`,
+    /// which is `None` for the netting set of a position outside any netting set.
+    #[must_use]
+    pub fn key(&self, key: AggregationKey) -> Option<&str> {
+        match key {
+            AggregationKey::Counterparty => Some(&self.counterparty),
+            AggregationKey::Book => Some(&self.book),
+            AggregationKey::Currency => Some(&self.currency),
+            AggregationKey::NettingSet => self.netting_set.as_deref(),
+        }
+    }
+
+    /// Returns the flows valued by [`Position::npv`] on `settle_date`, scaled by the quantity.
+    #[must_use]
+    pub fn remaining_cash_flows(&self, settle_date: Date) -> Vec<(Date, V)> {
+        let flows = match &self.lifecycle {
+            Some(lifecycle) => lifecycle.apply(&self.bond.cash_flows(), settle_date).future,
+            None => self.bond.remaining_cash_flows(settle_date),
+        };
+        flows
+            .into_iter()
+            .map(|(payment_date, amount)| (payment_date, self.quantity * amount))
+            .collect()
+    }
+
+    /// Calculates the present value of the position, i.e. the quantity times the discounted value of the bond,
+    /// or of the flows remaining after the events of its lifecycle.
+    ///
+    /// # Errors
+    /// Returns an error if the valuation of the bond fails.
+    pub fn npv<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        let Some(lifecycle) = &self.lifecycle else {
+            return Ok(self.quantity * self.bond.discounted_value(settle_date, yield_curve)?);
+        };
+        let flows = lifecycle.apply(&self.bond.cash_flows(), settle_date);
+        let value = flows
+            .future
+            .iter()
+            .try_fold(V::zero(), |acc, &(payment_date, amount)| {
+                Ok::<_, qlab_error::QLabError>(
+                    acc + amount * yield_curve.discount_factor(settle_date, payment_date)?,
+                )
+            })?;
+        Ok(self.quantity * value)
+    }
+}
+
+/// A collection of positions whose results can be grouped by an [`AggregationKey`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::portfolio::{AggregationKey, Group, Portfolio, Position};
+/// use qlab_instrument::testing::semi_annual_bond;
+/// use qlab_time::date::Date;
+///
+/// let bond = |bond_id| {
+///     semi_annual_bond(bond_id, Date::from_ymd(2028, 11, 7).unwrap(), 0.05, 100.0).unwrap()
+/// };
+/// let portfolio = Portfolio::new()
+///     .with_position(Position::new(bond("a"), 1.0, "bank", "rates", "usd"))
+///     .with_position(Position::new(bond("b"), 2.0, "bank", "credit", "USD"));
+/// let by_currency = portfolio.aggregate(AggregationKey::Currency, &[10.0, 5.0]).unwrap();
+/// assert_eq!(by_currency.get(&Group::named("USD")), Some(&15.0));
+/// ```
+#[derive(Default)]
+pub struct Portfolio<V> {
+    positions: Vec<Position<V>>,
+}
+
+impl<V: Value> Portfolio<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            positions: Vec::new(),
+        }
+    }
+
+    /// Adds a position.
+    #[must_use]
+    pub fn with_position(mut self, position: Position<V>) -> Self {
+        self.positions.push(position);
+        self
+    }
+
+    #[must_use]
+    pub fn positions(&self) -> &[Position<V>] {
+        &self.positions
+    }
+
+    /// Calculates the present value of each position, in the order of [`Portfolio::positions`].
+    ///
+    /// # Errors
+    /// Returns an error if the valuation of a position fails.
+    pub fn npvs<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<Vec<V>> {
+        self.positions
+            .iter()
+            .map(|position| position.npv(settle_date, yield_curve))
+            .collect()
+    }
+
+    /// Sums per-position results, such as present values or risk figures, by the attribute selected by `key`.
+    ///
+    /// `results` must hold one value per position, in the order of [`Portfolio::positions`].
+    /// Each position outside any netting set forms a group of its own when grouped by netting set.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the number of results differs from the number of positions.
+    pub fn aggregate(&self, key: AggregationKey, results: &[V]) -> QLabResult<BTreeMap<Group, V>> {
+        if results.len() != self.positions.len() {
+            return Err(InvalidInput(
+                format!(
+                    "{} results were given for {} positions",
+                    results.len(),
+                    self.positions.len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        let mut groups = BTreeMap::new();
+        for (index, (position, &result)) in self.positions.iter().zip(results).enumerate() {
+            let group = position
+                .key(key)
+                .map_or(Group::Standalone(index), Group::named);
+            *groups.entry(group).or_insert_with(V::zero) += result;
+        }
+        Ok(groups)
+    }
+
+    /// Calculates the present values grouped by the attribute selected by `key`.
+    ///
+    /// # Errors
+    /// Returns an error if the valuation of a position fails.
+    pub fn npv_by<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        key: AggregationKey,
+        settle_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<BTreeMap<Group, V>> {
+        self.aggregate(key, &self.npvs(settle_date, yield_curve)?)
+    }
+
+    /// Merges the flows of all positions remaining on `settle_date` into one flow per currency and payment date.
+    #[must_use]
+    pub fn compressed_cash_flows(&self, settle_date: Date) -> BTreeMap<(String, Date), V> {
+        let mut compressed = BTreeMap::new();
+        for position in &self.positions {
+            for (payment_date, amount) in position.remaining_cash_flows(settle_date) {
+                *compressed
+                    .entry((position.currency.clone(), payment_date))
+                    .or_insert_with(V::zero) += amount;
+            }
+        }
+        compressed
+    }
+
+    /// Calculates the present values grouped by currency like [`Portfolio::npv_by`], but discounts the
+    /// [compressed](Portfolio::compressed_cash_flows) flows, evaluating each discount factor once
+    /// however many positions pay on its date.
+    ///
+    /// # Errors
+    /// Returns an error if a discount factor calculation fails.
+    pub fn compressed_npv_by_currency<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<BTreeMap<String, V>> {
+        let mut discount_factors = BTreeMap::new();
+        let mut npvs = BTreeMap::new();
+        for ((currency, payment_date), amount) in self.compressed_cash_flows(settle_date) {
+            let discount_factor =
+                if let Some(&discount_factor) = discount_factors.get(&payment_date) {
+                    discount_factor
+                } else {
+                    let discount_factor = yield_curve.discount_factor(settle_date, payment_date)?;
+                    discount_factors.insert(payment_date, discount_factor);
+                    discount_factor
+                };
+            *npvs.entry(currency).or_insert_with(V::zero) += amount * discount_factor;
+        }
+        Ok(npvs)
+    }
+
+    /// Calculates the current exposure of each netting set, i.e. the positive part of its netted present value.
+    ///
+    /// # Errors
+    /// Returns an error if the valuation of a position fails.
+    pub fn exposures<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<BTreeMap<Group, V>> {
+        let mut netted = self.npv_by(AggregationKey::NettingSet, settle_date, yield_curve)?;
+        for value in netted.values_mut() {
+            *value = value.max(V::zero());
+        }
+        Ok(netted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::LifecycleEvent;
+    use crate::testing::semi_annual_bond;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    fn bond(bond_id: &str) -> Bond<f64> {
+        semi_annual_bond(bond_id, Date::from_ymd(2028, 11, 7).unwrap(), 0.05, 100.0).unwrap()
+    }
+
+    #[test]
+    fn test_aggregation_and_netting() {
+        let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let yield_curve: YieldCurve<Act365, Linear<f64>> = YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2023, 10, 11).unwrap(),
+                Date::from_ymd(2033, 10, 10).unwrap(),
+            ],
+            &[0.03, 0.04],
+        )
+        .unwrap();
+        let portfolio = Portfolio::new()
+            .with_position(
+                Position::new(bond("long"), 2.0, "bank", "rates", "usd").with_netting_set("isda"),
+            )
+            .with_position(
+                Position::new(bond("short"), -3.0, "bank", "credit", "usd")
+                    .with_netting_set("isda"),
+            )
+            .with_position(Position::new(bond("alone"), 1.0, "fund", "rates", "eur"));
+        let unit = bond("unit")
+            .discounted_value(settle_date, &yield_curve)
+            .unwrap();
+
+        let by_book = portfolio
+            .npv_by(AggregationKey::Book, settle_date, &yield_curve)
+            .unwrap();
+        assert_eq!(by_book.len(), 2);
+        assert!((by_book[&Group::named("rates")] - 3.0 * unit).abs() < 1e-10);
+        assert!((by_book[&Group::named("credit")] + 3.0 * unit).abs() < 1e-10);
+
+        let by_counterparty = portfolio
+            .npv_by(AggregationKey::Counterparty, settle_date, &yield_curve)
+            .unwrap();
+        assert!((by_counterparty[&Group::named("bank")] + unit).abs() < 1e-10);
+
+        // the netting set offsets to a negative value, leaving only the stand-alone position exposed
+        let exposures = portfolio.exposures(settle_date, &yield_curve).unwrap();
+        assert_eq!(
+            exposures.keys().collect::<Vec<_>>(),
+            vec![&Group::named("isda"), &Group::Standalone(2)]
+        );
+        assert!(exposures[&Group::named("isda")].abs() < f64::EPSILON);
+        assert!((exposures[&Group::Standalone(2)] - unit).abs() < 1e-10);
+        assert_eq!(Group::Standalone(2).to_string(), "position #2");
+
+        assert!(portfolio
+            .aggregate(AggregationKey::Currency, &[1.0])
+            .is_err());
+
+        // half of the holding was sold back before the settlement date
+        let lifecycle = Lifecycle::new()
+            .with_event(LifecycleEvent::PartialTermination {
+                date: Date::from_ymd(2023, 9, 1).unwrap(),
+                fraction: 0.5,
+                fee: 49.0,
+            })
+            .unwrap();
+        let halved = Position::new(bond("halved"), 1.0, "bank", "rates", "usd")
+            .with_lifecycle(lifecycle)
+            .npv(settle_date, &yield_curve)
+            .unwrap();
+        assert!((halved - 0.5 * unit).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_standalone_positions() {
+        // two stand-alone positions in the same bond, and a netting set named like the bond
+        let portfolio = Portfolio::new()
+            .with_position(Position::new(bond("a"), 1.0, "bank", "rates", "usd"))
+            .with_position(Position::new(bond("a"), -1.0, "fund", "rates", "usd"))
+            .with_position(
+                Position::new(bond("b"), 1.0, "bank", "rates", "usd").with_netting_set("a"),
+            );
+        assert_eq!(
+            portfolio.positions()[0].key(AggregationKey::NettingSet),
+            None
+        );
+        let netted = portfolio
+            .aggregate(AggregationKey::NettingSet, &[5.0, -2.0, 3.0])
+            .unwrap();
+        assert_eq!(netted.len(), 3);
+        assert!((netted[&Group::Standalone(0)] - 5.0).abs() < f64::EPSILON);
+        assert!((netted[&Group::Standalone(1)] + 2.0).abs() < f64::EPSILON);
+        assert!((netted[&Group::named("a")] - 3.0).abs() < f64::EPSILON);
+        let by_book = portfolio
+            .aggregate(AggregationKey::Book, &[5.0, -2.0, 3.0])
+            .unwrap();
+        assert!((by_book[&Group::named("rates")] - 6.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_compression() {
+        let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let yield_curve: YieldCurve<Act365, Linear<f64>> = YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2023, 10, 11).unwrap(),
+                Date::from_ymd(2033, 10, 10).unwrap(),
+            ],
+            &[0.03, 0.04],
+        )
+        .unwrap();
+        let lifecycle = Lifecycle::new()
+            .with_event(LifecycleEvent::PartialTermination {
+                date: Date::from_ymd(2023, 9, 1).unwrap(),
+                fraction: 0.5,
+                fee: 49.0,
+            })
+            .unwrap();
+        let mut portfolio = Portfolio::new();
+        for i in 0..50 {
+            portfolio = portfolio.with_position(Position::new(
+                bond(&i.to_string()),
+                f64::from(i),
+                "bank",
+                "rates",
+                if i % 2 == 0 { "usd" } else { "eur" },
+            ));
+        }
+        let portfolio = portfolio.with_position(
+            Position::new(bond("halved"), 4.0, "bank", "rates", "usd").with_lifecycle(lifecycle),
+        );
+
+        let compressed = portfolio.compressed_cash_flows(settle_date);
+        let flows_per_bond = bond("unit").remaining_cash_flows(settle_date).len();
+        assert_eq!(compressed.len(), 2 * flows_per_bond);
+        let expected = portfolio
+            .npv_by(AggregationKey::Currency, settle_date, &yield_curve)
+            .unwrap();
+        let npvs = portfolio
+            .compressed_npv_by_currency(settle_date, &yield_curve)
+            .unwrap();
+        assert_eq!(npvs.len(), 2);
+        for (currency, npv) in npvs {
+            assert!((npv - expected[&Group::Named(currency)]).abs() < 1e-8 * npv.abs());
+        }
+    }
+}
@@ -0,0 +1,337 @@
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_SNAPSHOT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Market quotes keyed by identifier, with a snapshot version bumped whenever a quote changes
+/// and the version at which each quote last changed.
+///
+/// Versions count the changes of one snapshot only, so each snapshot also has an identity, fresh for every
+/// new or cloned snapshot, which tells apart snapshots that reach the same version through different changes.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::pricing_cache::VersionedQuotes;
+///
+/// let mut quotes = VersionedQuotes::new();
+/// assert!(quotes.set("USD.SOFR.1Y", 0.0512));
+/// assert!(!quotes.set("USD.SOFR.1Y", 0.0512));
+/// assert!(quotes.set("USD.SOFR.2Y", 0.0468));
+/// assert_eq!(quotes.version(), 2);
+/// assert_eq!(quotes.quote_version("USD.SOFR.1Y"), Some(1));
+/// ```
+#[derive(Debug)]
+pub struct VersionedQuotes<V> {
+    snapshot_id: u64,
+    quotes: HashMap<String, (V, u64)>,
+    version: u64,
+}
+
+impl<V> Default for VersionedQuotes<V> {
+    fn default() -> Self {
+        Self {
+            snapshot_id: NEXT_SNAPSHOT_ID.fetch_add(1, Ordering::Relaxed),
+            quotes: HashMap::new(),
+            version: 0,
+        }
+    }
+}
+
+/// A clone is a new snapshot with its own identity, so that its later changes are not mistaken for those of
+/// the original.
+impl<V: Clone> Clone for VersionedQuotes<V> {
+    fn clone(&self) -> Self {
+        Self {
+            quotes: self.quotes.clone(),
+            version: self.version,
+            ..Self::default()
+        }
+    }
+}
+
+/// Snapshots are equal if they hold the same quotes at the same versions, whatever their identities.
+impl<V: PartialEq> PartialEq for VersionedQuotes<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.quotes == other.quotes && self.version == other.version
+    }
+}
+
+impl<V: Value> VersionedQuotes<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the identity of the snapshot, unique among the snapshots created in the process.
+    #[must_use]
+    pub fn snapshot_id(&self) -> u64 {
+        self.snapshot_id
+    }
+
+    /// Sets the quote `quote_id` and returns `true` if its value changed, in which case a new
+    /// snapshot version is created.
+    pub fn set(&mut self, quote_id: &str, value: V) -> bool {
+        if self
+            .quotes
+            .get(quote_id)
+            .is_some_and(|&(current, _)| current == value)
+        {
+            return false;
+        }
+        self.version += 1;
+        self.quotes
+            .insert(quote_id.to_string(), (value, self.version));
+        true
+    }
+
+    #[must_use]
+    pub fn quote(&self, quote_id: &str) -> Option<V> {
+        self.quotes.get(quote_id).map(|&(value, _)| value)
+    }
+
+    /// Returns the version of the snapshot, which is the number of changes applied to the quotes.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the snapshot version at which `quote_id` last changed.
+    #[must_use]
+    pub fn quote_version(&self, quote_id: &str) -> Option<u64> {
+        self.quotes.get(quote_id).map(|&(_, version)| version)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct CacheEntry<V> {
+    npv: V,
+    /// The latest version of the snapshot at which the NPV is known to be valid.
+    version: u64,
+    /// The quotes the NPV depends on with their versions when it was calculated.
+    dependencies: Vec<(String, Option<u64>)>,
+}
+
+/// A cache of instrument NPVs keyed by instrument identifier and market snapshot identity.
+///
+/// Each NPV is stored with the versions of the quotes it depends on, so that a change of a quote
+/// invalidates only the instruments which depend on it, and a risk run on a market in which few quotes
+/// moved reprices few instruments. NPVs priced on different snapshots, e.g. a base market and its bumped
+/// clones, are cached side by side.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::pricing_cache::{PricingCache, VersionedQuotes};
+///
+/// let mut quotes = VersionedQuotes::new();
+/// quotes.set("1Y", 0.05);
+/// quotes.set("2Y", 0.045);
+/// let mut cache = PricingCache::new();
+/// let price = |market: &VersionedQuotes<f64>| Ok(100.0 / (1.0 + market.quote("1Y").unwrap()));
+///
+/// let npv = cache.get_or_price("bill", &["1Y"], &quotes, price).unwrap();
+/// quotes.set("2Y", 0.046);
+/// // the bill does not depend on the 2Y quote, so its NPV is reused
+/// assert_eq!(cache.get_or_price("bill", &["1Y"], &quotes, price).unwrap(), npv);
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+/// assert_eq!(cache.npv("bill", &quotes), Some(npv));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PricingCache<V> {
+    entries: HashMap<(String, u64), CacheEntry<V>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<V: Value> PricingCache<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the NPV of `instrument_id` if it is cached as valid at the current version of `market`.
+    #[must_use]
+    pub fn npv(&self, instrument_id: &str, market: &VersionedQuotes<V>) -> Option<V> {
+        self.entries
+            .get(&(instrument_id.to_string(), market.snapshot_id()))
+            .filter(|entry| entry.version == market.version())
+            .map(|entry| entry.npv)
+    }
+
+    /// Returns the NPV of `instrument_id` cached on the snapshot `market` if none of the quotes in `dependencies`
+    /// changed since it was calculated, and otherwise prices it with `price` on `market` and caches the result.
+    ///
+    /// # Errors
+    /// Returns an error if `price` fails, in which case nothing is cached for the instrument.
+    pub fn get_or_price(
+        &mut self,
+        instrument_id: &str,
+        dependencies: &[&str],
+        market: &VersionedQuotes<V>,
+        price: impl FnOnce(&VersionedQuotes<V>) -> QLabResult<V>,
+    ) -> QLabResult<V> {
+        let key = (instrument_id.to_string(), market.snapshot_id());
+        if let Some(entry) = self.entries.get_mut(&key) {
+            let unchanged = entry.version == market.version()
+                || (entry.dependencies.len() == dependencies.len()
+                    && entry.dependencies.iter().zip(dependencies).all(
+                        |((quote_id, version), dependency)| {
+                            quote_id == dependency && *version == market.quote_version(dependency)
+                        },
+                    ));
+            if unchanged {
+                entry.version = market.version();
+                self.hits += 1;
+                return Ok(entry.npv);
+            }
+        }
+        self.misses += 1;
+        self.entries.remove(&key);
+        let npv = price(market)?;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                npv,
+                version: market.version(),
+                dependencies: dependencies
+                    .iter()
+                    .map(|&quote_id| (quote_id.to_string(), market.quote_version(quote_id)))
+                    .collect(),
+            },
+        );
+        Ok(npv)
+    }
+
+    /// Removes the NPVs depending on `quote_id` on every snapshot and returns how many were removed.
+    pub fn invalidate_quote(&mut self, quote_id: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, entry| {
+            entry
+                .dependencies
+                .iter()
+                .all(|(dependency, _)| dependency != quote_id)
+        });
+        before - self.entries.len()
+    }
+
+    /// Removes the NPVs of `instrument_id` on every snapshot, e.g. after the trade was amended.
+    pub fn invalidate(&mut self, instrument_id: &str) {
+        self.entries.retain(|(id, _), _| id != instrument_id);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of NPVs served from the cache.
+    #[must_use]
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of NPVs which had to be calculated.
+    #[must_use]
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_selective_invalidation() {
+        let mut quotes = VersionedQuotes::new();
+        for (quote_id, rate) in [("1Y", 0.05), ("5Y", 0.045), ("10Y", 0.043)] {
+            quotes.set(quote_id, rate);
+        }
+        let calls = Cell::new(0);
+        let zero = |quote_id: &'static str, years: i32| {
+            let calls = &calls;
+            move |market: &VersionedQuotes<f64>| {
+                calls.set(calls.get() + 1);
+                Ok(100.0 / (1.0 + market.quote(quote_id).unwrap()).powi(years))
+            }
+        };
+        let mut cache = PricingCache::new();
+        let run = |cache: &mut PricingCache<f64>, market: &VersionedQuotes<f64>| {
+            cache
+                .get_or_price("a", &["1Y"], market, zero("1Y", 1))
+                .unwrap();
+            cache
+                .get_or_price("b", &["5Y"], market, zero("5Y", 5))
+                .unwrap();
+            cache
+                .get_or_price("c", &["10Y"], market, zero("10Y", 10))
+                .unwrap()
+        };
+        run(&mut cache, &quotes);
+        assert_eq!(calls.get(), 3);
+        // an unchanged market is served entirely from the cache
+        run(&mut cache, &quotes);
+        assert_eq!(calls.get(), 3);
+
+        quotes.set("5Y", 0.046);
+        run(&mut cache, &quotes);
+        assert_eq!(calls.get(), 4);
+        assert!(cache.npv("b", &quotes).unwrap() < 100.0 / 1.045_f64.powi(5));
+        assert_eq!((cache.hits(), cache.misses()), (5, 4));
+
+        assert_eq!(cache.invalidate_quote("10Y"), 1);
+        assert_eq!(cache.len(), 2);
+        let c = run(&mut cache, &quotes);
+        assert_eq!(calls.get(), 5);
+        assert!((c - 100.0 / 1.043_f64.powi(10)).abs() < 1e-12);
+
+        // a failed pricing leaves nothing cached
+        cache.invalidate("a");
+        assert!(cache
+            .get_or_price("a", &["1Y"], &quotes, |_| Err(
+                qlab_error::ComputeError::InvalidInput("stale".into()).into()
+            ))
+            .is_err());
+        assert_eq!(cache.npv("a", &quotes), None);
+
+        // clones reaching the same version through different changes do not share NPVs
+        let mut up = quotes.clone();
+        let mut down = quotes.clone();
+        assert_ne!(up.snapshot_id(), down.snapshot_id());
+        assert_eq!(up, down);
+        up.set("1Y", 0.051);
+        down.set("1Y", 0.049);
+        assert_eq!(up.version(), down.version());
+        let a_up = cache
+            .get_or_price("a", &["1Y"], &up, zero("1Y", 1))
+            .unwrap();
+        let a_down = cache
+            .get_or_price("a", &["1Y"], &down, zero("1Y", 1))
+            .unwrap();
+        assert!((a_up - 100.0 / 1.051).abs() < 1e-12);
+        assert!((a_down - 100.0 / 1.049).abs() < 1e-12);
+        // the base market keeps its own entries
+        assert_eq!(cache.npv("b", &up), None);
+        assert!(cache.npv("b", &quotes).is_some());
+        cache.invalidate("a");
+        assert_eq!(cache.npv("a", &up), None);
+        assert_eq!(cache.npv("a", &down), None);
+    }
+}
@@ -0,0 +1,193 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::registry::DayCountFn;
+
+/// The unit a rate or spread is quoted in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuoteScale {
+    /// `0.05` for five percent.
+    Decimal,
+    /// `5.0` for five percent.
+    Percent,
+    /// `500.0` for five percent.
+    BasisPoints,
+}
+
+impl QuoteScale {
+    fn to_decimal<V: Value>(self, value: V) -> V {
+        match self {
+            Self::Decimal => value,
+            Self::Percent => value / V::from_u8(100).unwrap(),
+            Self::BasisPoints => value / V::from_u16(10_000).unwrap(),
+        }
+    }
+}
+
+/// What a quoted number represents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteKind<V> {
+    /// A simply compounded rate, such as a deposit or par swap rate.
+    Rate,
+    /// A futures price, quoted as `100` minus the rate in percent.
+    Price,
+    /// A spread over `reference_rate`, such as an OIS basis quote.
+    Spread { reference_rate: V },
+}
+
+/// A market quote together with its convention, so that mixed inputs of a curve bootstrap
+/// convert into the rate its solver targets.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::quote::{Quote, QuoteScale};
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_360::Act360;
+/// use qlab_time::day_count::act_365::Act365;
+/// use qlab_time::day_count::DayCount;
+///
+/// let start = Date::from_ymd(2024, 1, 2).unwrap();
+/// let end = Date::from_ymd(2024, 4, 2).unwrap();
+/// // 5% on an Act/365 basis is worth 91/365 * 5% over the period, i.e. 360/365 * 5% on an Act/360 basis
+/// let quote = Quote::rate(5.0_f64, QuoteScale::Percent)
+///     .with_basis(Act365::calculate_day_count_fraction);
+/// let rate = quote.to_rate(start, end, Act360::calculate_day_count_fraction).unwrap();
+/// assert!((rate - 0.05 * 360.0 / 365.0).abs() < 1e-12);
+///
+/// let future = Quote::price(94.75_f64);
+/// let rate = future.to_rate(start, end, Act360::calculate_day_count_fraction).unwrap();
+/// assert!((rate - 0.0525).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Quote<V> {
+    value: V,
+    kind: QuoteKind<V>,
+    scale: QuoteScale,
+    basis: Option<DayCountFn<V>>,
+}
+
+impl<V: Value> Quote<V> {
+    /// Creates a rate quote.
+    #[must_use]
+    pub fn rate(value: V, scale: QuoteScale) -> Self {
+        Self {
+            value,
+            kind: QuoteKind::Rate,
+            scale,
+            basis: None,
+        }
+    }
+
+    /// Creates a futures price quote.
+    #[must_use]
+    pub fn price(value: V) -> Self {
+        Self {
+            value,
+            kind: QuoteKind::Price,
+            scale: QuoteScale::Percent,
+            basis: None,
+        }
+    }
+
+    /// Creates a quote of a spread over `reference_rate`, which is a decimal rate on the same basis.
+    #[must_use]
+    pub fn spread(value: V, scale: QuoteScale, reference_rate: V) -> Self {
+        Self {
+            value,
+            kind: QuoteKind::Spread { reference_rate },
+            scale,
+            basis: None,
+        }
+    }
+
+    /// Sets the day count the quoted rate accrues on.
+    /// Without a basis the quote is assumed to accrue on the basis of the target.
+    #[must_use]
+    pub fn with_basis(mut self, basis: DayCountFn<V>) -> Self {
+        self.basis = Some(basis);
+        self
+    }
+
+    #[must_use]
+    pub fn value(&self) -> V {
+        self.value
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> QuoteKind<V> {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn scale(&self) -> QuoteScale {
+        self.scale
+    }
+
+    /// Returns the quoted rate as a decimal, on the quoted basis.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn decimal_rate(&self) -> V {
+        match self.kind {
+            QuoteKind::Rate => self.scale.to_decimal(self.value),
+            QuoteKind::Price => {
+                let hundred = V::from_u8(100).unwrap();
+                (hundred - self.value) / hundred
+            }
+            QuoteKind::Spread { reference_rate } => {
+                reference_rate + self.scale.to_decimal(self.value)
+            }
+        }
+    }
+
+    /// Converts the quote into the simply compounded decimal rate accruing on `target` from `start` to `end`,
+    /// which earns the same interest as the quoted rate accruing on its basis.
+    ///
+    /// # Errors
+    /// Returns an error if a day count fraction cannot be calculated, or an `InvalidInput` error
+    /// if the target fraction is zero.
+    pub fn to_rate(&self, start: Date, end: Date, target: DayCountFn<V>) -> QLabResult<V> {
+        let rate = self.decimal_rate();
+        let Some(basis) = self.basis else {
+            return Ok(rate);
+        };
+        let target_fraction = target(start, end)?;
+        if target_fraction == V::zero() {
+            return Err(InvalidInput(
+                format!("the period from {start} to {end} has no accrual on the target basis")
+                    .into(),
+            )
+            .into());
+        }
+        Ok(rate * basis(start, end)? / target_fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::DayCount;
+
+    #[test]
+    fn test_to_rate() {
+        let start = Date::from_ymd(2024, 1, 2).unwrap();
+        let end = Date::from_ymd(2024, 7, 2).unwrap();
+        let target: DayCountFn<f64> = Act360::calculate_day_count_fraction;
+        let rates = [
+            Quote::rate(0.0525, QuoteScale::Decimal),
+            Quote::rate(5.25, QuoteScale::Percent),
+            Quote::rate(525.0, QuoteScale::BasisPoints),
+            Quote::price(94.75),
+            Quote::spread(25.0, QuoteScale::BasisPoints, 0.05),
+        ];
+        for quote in rates {
+            assert!((quote.to_rate(start, end, target).unwrap() - 0.0525).abs() < 1e-12);
+        }
+        let quote = Quote::rate(0.05, QuoteScale::Decimal).with_basis(target);
+        assert!(quote.to_rate(start, start, target).is_err());
+    }
+}
@@ -0,0 +1,1039 @@
+use crate::curve_input::{CurveInputValidator, InputChecks};
+use crate::curve_risk::CurveJacobian;
+use crate::quote::Quote;
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::{Context, QLabResult};
+use qlab_math::interpolation::Interpolator;
+use qlab_math::optimization::least_squares_with_report;
+use qlab_math::value::Value;
+use qlab_termstructure::diagnostics::BootstrapDiagnostics;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::calendar::Calendar;
+use qlab_time::date::Date;
+use qlab_time::day_count::act_360::Act360;
+use qlab_time::day_count::DayCount;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+/// The contract type of a SOFR future, which determines its reference period and settlement convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SofrFutureKind {
+    /// Settles on the arithmetic average of SOFR over the calendar days of a contract month.
+    OneMonth,
+    /// Settles on SOFR compounded daily over the IMM quarter starting on the third Wednesday of the contract month.
+    ThreeMonth,
+}
+
+/// A SOFR future quoted as `100` minus the settlement rate in percent.
+///
+/// On a calendar day which is not a SOFR business day the rate of the preceding business day applies,
+/// so a reference period starting on a holiday or weekend picks up the last rate of the previous month.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SofrFuture<V> {
+    kind: SofrFutureKind,
+    reference_start: Date,
+    reference_end: Date,
+    price: V,
+}
+
+fn invalid_contract(year: i32, month: u32) -> qlab_error::ComputeError {
+    InvalidInput(format!("no contract month exists for {year}-{month}").into())
+}
+
+fn next_month(year: i32, month: u32, months: u32) -> (i32, u32) {
+    let index = month - 1 + months;
+    (
+        year + i32::try_from(index / 12).unwrap_or(0),
+        index % 12 + 1,
+    )
+}
+
+impl<V: Value> SofrFuture<V> {
+    /// Creates a one-month future referencing the calendar month `month` of `year`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the contract month is invalid.
+    pub fn one_month(year: i32, month: u32, price: V) -> QLabResult<Self> {
+        let reference_start =
+            Date::from_ymd(year, month, 1).ok_or_else(|| invalid_contract(year, month))?;
+        let (end_year, end_month) = next_month(year, month, 1);
+        let reference_end =
+            Date::from_ymd(end_year, end_month, 1).ok_or_else(|| invalid_contract(year, month))?;
+        Ok(Self {
+            kind: SofrFutureKind::OneMonth,
+            reference_start,
+            reference_end,
+            price,
+        })
+    }
+
+    /// Creates a three-month future referencing the period from the third Wednesday of `month`
+    /// to the third Wednesday three months later.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the contract month is invalid.
+    pub fn three_month(year: i32, month: u32, price: V) -> QLabResult<Self> {
+        let reference_start =
+            Date::third_wednesday(year, month).ok_or_else(|| invalid_contract(year, month))?;
+        let (end_year, end_month) = next_month(year, month, 3);
+        let reference_end = Date::third_wednesday(end_year, end_month)
+            .ok_or_else(|| invalid_contract(year, month))?;
+        Ok(Self {
+            kind: SofrFutureKind::ThreeMonth,
+            reference_start,
+            reference_end,
+            price,
+        })
+    }
+
+    /// Creates a future of `kind` on the contract month `month` of `year` from a quote of any convention,
+    /// converted into a rate accruing on the Act/360 basis of SOFR over the reference period.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the contract month is invalid, or an error if the quote cannot be converted.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn from_quote(
+        kind: SofrFutureKind,
+        year: i32,
+        month: u32,
+        quote: &Quote<V>,
+    ) -> QLabResult<Self> {
+        let mut future = match kind {
+            SofrFutureKind::OneMonth => Self::one_month(year, month, V::zero())?,
+            SofrFutureKind::ThreeMonth => Self::three_month(year, month, V::zero())?,
+        };
+        let rate = quote.to_rate(
+            future.reference_start,
+            future.reference_end,
+            Act360::calculate_day_count_fraction,
+        )?;
+        let hundred = V::from_u8(100).unwrap();
+        future.price = hundred * (V::one() - rate);
+        Ok(future)
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> SofrFutureKind {
+        self.kind
+    }
+
+    /// Returns the first calendar day of the reference period.
+    #[must_use]
+    pub fn reference_start(&self) -> Date {
+        self.reference_start
+    }
+
+    /// Returns the day after the last calendar day of the reference period.
+    #[must_use]
+    pub fn reference_end(&self) -> Date {
+        self.reference_end
+    }
+
+    #[must_use]
+    pub fn price(&self) -> V {
+        self.price
+    }
+
+    /// Returns the settlement rate implied by the price, as a decimal.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn implied_rate(&self) -> V {
+        let hundred = V::from_u8(100).unwrap();
+        (hundred - self.price) / hundred
+    }
+
+    /// Calculates the settlement rate from the SOFR published for each business day.
+    ///
+    /// `rate_on` returns the rate of a business day of `calendar`; it is also called for the last business day
+    /// before the reference period when the period starts on a non-business day.
+    ///
+    /// # Errors
+    /// Returns an error if `rate_on` fails or if day counts cannot be cast into `V`.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn settlement_rate(
+        &self,
+        calendar: &impl Calendar,
+        rate_on: impl Fn(Date) -> QLabResult<V>,
+    ) -> QLabResult<V> {
+        let days_in_year = V::from_u16(360).unwrap();
+        let mut average = V::zero();
+        let mut compounded = V::one();
+        let mut date = self.reference_start;
+        while date < self.reference_end {
+            let mut fixing_date = date;
+            while !calendar.is_business_day(fixing_date) {
+                fixing_date = fixing_date.pred_opt().ok_or_else(|| {
+                    InvalidInput(format!("no business day precedes {date}").into())
+                })?;
+            }
+            // the rate applies until the next business day or the end of the reference period
+            let mut next = date
+                .succ_opt()
+                .ok_or_else(|| InvalidInput(format!("no date follows {date}").into()))?;
+            while next < self.reference_end && !calendar.is_business_day(next) {
+                next = next
+                    .succ_opt()
+                    .ok_or_else(|| InvalidInput(format!("no date follows {next}").into()))?;
+            }
+            let days = V::from_i64(next - date)
+                .ok_or_else(|| CastNumberError(format!("{}", next - date).into()))?;
+            let rate = rate_on(fixing_date)?;
+            average += rate * days;
+            compounded *= V::one() + rate * days / days_in_year;
+            date = next;
+        }
+        let period = V::from_i64(self.reference_end - self.reference_start).ok_or_else(|| {
+            CastNumberError(format!("{}", self.reference_end - self.reference_start).into())
+        })?;
+        Ok(match self.kind {
+            SofrFutureKind::OneMonth => average / period,
+            SofrFutureKind::ThreeMonth => (compounded - V::one()) * days_in_year / period,
+        })
+    }
+
+    /// Projects the settlement rate with the overnight forwards of `yield_curve`,
+    /// taking the rates of business days before its settlement date from `fixings`.
+    ///
+    /// # Errors
+    /// Returns an error if a past fixing is missing or if a discount factor calculation fails.
+    pub fn projected_rate<D: DayCount, I: Interpolator<Value = V>, S: BuildHasher>(
+        &self,
+        yield_curve: &YieldCurve<D, I>,
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<V> {
+        self.settlement_rate(calendar, |date| {
+            if date < yield_curve.settlement_date() {
+                fixings.get(&date).copied().ok_or_else(|| {
+                    InvalidInput(format!("the SOFR fixing on {date} is missing").into()).into()
+                })
+            } else {
+                overnight_forward(yield_curve, calendar, date)
+            }
+        })
+    }
+}
+
+fn next_business_day(calendar: &impl Calendar, date: Date) -> QLabResult<Date> {
+    let mut next = date;
+    loop {
+        next = next
+            .succ_opt()
+            .ok_or_else(|| InvalidInput(format!("no date follows {next}").into()))?;
+        if calendar.is_business_day(next) {
+            return Ok(next);
+        }
+    }
+}
+
+/// Calculates the simply compounded Act/360 forward rate from `date` to the next business day.
+fn overnight_forward<V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    yield_curve: &YieldCurve<D, I>,
+    calendar: &impl Calendar,
+    date: Date,
+) -> QLabResult<V> {
+    let next = next_business_day(calendar, date)?;
+    let days = V::from_i64(next - date)
+        .ok_or_else(|| CastNumberError(format!("{}", next - date).into()))?;
+    let discount_factor = yield_curve.discount_factor(date, next)?;
+    Ok((V::one() / discount_factor - V::one()) * V::from_u16(360).unwrap() / days)
+}
+
+/// Converts the overnight rate from `settlement_date` to `first_pillar` into a spot yield.
+fn overnight_yield<V: Value, D: DayCount>(
+    settlement_date: Date,
+    first_pillar: Date,
+    overnight_rate: V,
+) -> QLabResult<V> {
+    let days = V::from_i64(first_pillar - settlement_date)
+        .ok_or_else(|| CastNumberError(format!("{}", first_pillar - settlement_date).into()))?;
+    Ok(
+        (V::one() + overnight_rate * days / V::from_u16(360).unwrap()).ln()
+            / D::calculate_day_count_fraction::<V>(settlement_date, first_pillar)?,
+    )
+}
+
+/// Builds the curve on the pillars, the anchored nodes and the knots, whose yields follow linearly in time
+/// from the neighbouring pillars and anchors and flat beyond them.
+fn node_curve<V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    settlement_date: Date,
+    pillars: &[Date],
+    yields: &[V],
+    anchors: &BTreeMap<Date, V>,
+    knots: &[Date],
+) -> QLabResult<YieldCurve<D, I>> {
+    let mut nodes: BTreeMap<_, _> = pillars
+        .iter()
+        .copied()
+        .zip(yields.iter().copied())
+        .collect();
+    nodes.extend(anchors);
+    let time = |date| D::calculate_day_count_fraction::<V>(settlement_date, date);
+    let knot_yields = knots
+        .iter()
+        .map(
+            |&knot| match (nodes.range(..knot).next_back(), nodes.range(knot..).next()) {
+                (Some((&before, &y0)), Some((&after, &y1))) => {
+                    let (t0, t1) = (time(before)?, time(after)?);
+                    Ok(y0 + (y1 - y0) * (time(knot)? - t0) / (t1 - t0))
+                }
+                (Some((_, &y)), None) | (None, Some((_, &y))) => Ok(y),
+                (None, None) => Err(InvalidInput("the curve has no pillar".into()).into()),
+            },
+        )
+        .collect::<QLabResult<Vec<_>>>()?;
+    nodes.extend(knots.iter().copied().zip(knot_yields));
+    let (dates, spot_yields): (Vec<_>, Vec<_>) = nodes.into_iter().unzip();
+    YieldCurve::new(settlement_date, &dates, &spot_yields)
+}
+
+/// Bootstraps an overnight curve from the current overnight rate and SOFR futures.
+///
+/// The curve has a pillar on the business day after `settlement_date`, fixed by `overnight_rate`, and a pillar
+/// on the first business day on or after the end of each future's reference period, whose yields are solved
+/// so that the projected settlement rates match the rates implied by the prices. Futures convexity is ignored.
+/// Rates of business days before `settlement_date` are taken from `fixings`.
+///
+/// # Errors
+/// Returns an error if the futures fail the checks of [`SofrCurveBuilder`], if two futures share a pillar,
+/// if a past fixing is missing, or if the solver fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::sofr_future::{bootstrap, SofrFuture};
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use std::collections::HashMap;
+///
+/// let futures = [
+///     SofrFuture::three_month(2024, 3, 94.9).unwrap(),
+///     SofrFuture::three_month(2024, 6, 95.1).unwrap(),
+/// ];
+/// let curve: YieldCurve<Act365, Linear<f64>> = bootstrap(
+///     Date::from_ymd(2024, 3, 20).unwrap(),
+///     0.0531,
+///     &futures,
+///     &WeekendsOnly,
+///     &HashMap::new(),
+/// )
+/// .unwrap();
+/// let rate = futures[1].projected_rate(&curve, &WeekendsOnly, &HashMap::new()).unwrap();
+/// assert!((rate - 0.049).abs() < 1e-9);
+/// ```
+pub fn bootstrap<V: Value, D: DayCount, I: Interpolator<Value = V>, S: BuildHasher>(
+    settlement_date: Date,
+    overnight_rate: V,
+    futures: &[SofrFuture<V>],
+    calendar: &impl Calendar,
+    fixings: &HashMap<Date, V, S>,
+) -> QLabResult<YieldCurve<D, I>> {
+    Ok(
+        SofrCurveBuilder::new(settlement_date, overnight_rate, futures, calendar, fixings)?
+            .into_curve(),
+    )
+}
+
+/// Bootstraps the curve as [`bootstrap`] does and returns the diagnostics of the solve together with the
+/// outcome, see [`SofrCurveBuilder::new_with_diagnostics`].
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+pub fn bootstrap_with_diagnostics<
+    V: Value,
+    D: DayCount,
+    I: Interpolator<Value = V>,
+    S: BuildHasher,
+>(
+    settlement_date: Date,
+    overnight_rate: V,
+    futures: &[SofrFuture<V>],
+    calendar: &impl Calendar,
+    fixings: &HashMap<Date, V, S>,
+) -> (QLabResult<YieldCurve<D, I>>, BootstrapDiagnostics<V>) {
+    let (builder, diagnostics) = SofrCurveBuilder::new_with_diagnostics(
+        settlement_date,
+        overnight_rate,
+        futures,
+        calendar,
+        fixings,
+    );
+    (builder.map(SofrCurveBuilder::into_curve), diagnostics)
+}
+
+/// An overnight curve bootstrapped from SOFR futures as by [`bootstrap`], which keeps its inputs so that
+/// a changed price re-solves only the pillar of its future and the later pillars.
+///
+/// Each future depends on the curve up to the end of its reference period, so with an interpolator whose
+/// value between two pillars depends on those pillars only, such as a linear one, the earlier pillars keep
+/// their solved yields exactly. With a global interpolator such as a spline they are held fixed as an approximation.
+///
+/// The short end can be shaped apart from the pillars by [`SofrCurveBuilder::with_anchor`], which pins the yield
+/// of a node, and [`SofrCurveBuilder::with_knots`], which adds nodes interpolated from their neighbours.
+///
+/// The futures are checked by a [`CurveInputValidator`] as of the settlement date before solving:
+/// blocking problems reject them, and the other warnings are kept in [`SofrCurveBuilder::input_checks`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::sofr_future::{SofrCurveBuilder, SofrFuture};
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use std::collections::HashMap;
+///
+/// let futures = [
+///     SofrFuture::three_month(2024, 3, 94.9).unwrap(),
+///     SofrFuture::three_month(2024, 6, 95.1).unwrap(),
+/// ];
+/// let settlement_date = Date::from_ymd(2024, 3, 20).unwrap();
+/// let mut builder: SofrCurveBuilder<Act365, Linear<f64>> =
+///     SofrCurveBuilder::new(settlement_date, 0.0531, &futures, &WeekendsOnly, &HashMap::new()).unwrap();
+/// builder.update_price(1, 95.2, &WeekendsOnly, &HashMap::new()).unwrap();
+/// let rate = builder.futures()[1].projected_rate(builder.curve(), &WeekendsOnly, &HashMap::new()).unwrap();
+/// assert!((rate - 0.048).abs() < 1e-9);
+/// ```
+pub struct SofrCurveBuilder<D: DayCount, I: Interpolator> {
+    settlement_date: Date,
+    overnight_rate: I::Value,
+    /// The futures sorted by the end of their reference periods.
+    futures: Vec<SofrFuture<I::Value>>,
+    pillars: Vec<Date>,
+    /// The spot yields of the pillars, starting with the overnight pillar.
+    yields: Vec<I::Value>,
+    /// The pinned spot yields of nodes besides the pillars.
+    anchors: BTreeMap<Date, I::Value>,
+    /// Interpolation nodes besides the pillars and anchors.
+    knots: Vec<Date>,
+    curve: YieldCurve<D, I>,
+    diagnostics: BootstrapDiagnostics<I::Value>,
+    input_checks: InputChecks<I::Value>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> SofrCurveBuilder<D, I> {
+    /// Bootstraps the curve as [`bootstrap`] does.
+    ///
+    /// # Errors
+    /// Returns an error if the validator blocks the futures, if two futures share a pillar, if a past fixing
+    /// is missing, or if the solver fails.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn new<S: BuildHasher>(
+        settlement_date: Date,
+        overnight_rate: V,
+        futures: &[SofrFuture<V>],
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<Self> {
+        Self::new_with_diagnostics(settlement_date, overnight_rate, futures, calendar, fixings).0
+    }
+
+    /// Bootstraps the curve as [`SofrCurveBuilder::new`] does and returns the diagnostics of the solve
+    /// together with the outcome, so that a failed bootstrap still reports the pillars it failed at.
+    /// The diagnostics are empty if the futures are rejected before solving.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn new_with_diagnostics<S: BuildHasher>(
+        settlement_date: Date,
+        overnight_rate: V,
+        futures: &[SofrFuture<V>],
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> (QLabResult<Self>, BootstrapDiagnostics<V>) {
+        let mut builder = match Self::unsolved(settlement_date, overnight_rate, futures, calendar) {
+            Ok(builder) => builder,
+            Err(err) => return (Err(err), BootstrapDiagnostics::default()),
+        };
+        let solved = builder
+            .solve_from(0, calendar, fixings)
+            .context("bootstrapping the overnight curve from SOFR futures");
+        let diagnostics = builder.diagnostics.clone();
+        (solved.map(|()| builder), diagnostics)
+    }
+
+    /// Validates the futures and sets up the pillars with the yields implied by them as the initial guess.
+    fn unsolved(
+        settlement_date: Date,
+        overnight_rate: V,
+        futures: &[SofrFuture<V>],
+        calendar: &impl Calendar,
+    ) -> QLabResult<Self> {
+        let input_checks = CurveInputValidator::new(settlement_date)
+            .check_futures(futures)
+            .into_result()?;
+        let first_pillar = next_business_day(calendar, settlement_date)?;
+        let first_yield = overnight_yield::<V, D>(settlement_date, first_pillar, overnight_rate)?;
+
+        let mut futures = futures.to_vec();
+        futures.sort_by_key(|future| future.reference_end);
+        let mut pillars = vec![first_pillar];
+        for future in &futures {
+            let mut pillar = future.reference_end;
+            while !calendar.is_business_day(pillar) {
+                pillar = next_business_day(calendar, pillar)?;
+            }
+            if pillars.last().is_some_and(|&last| last >= pillar) {
+                return Err(InvalidInput(
+                    format!("the futures ending on {pillar} share a pillar with an earlier one")
+                        .into(),
+                )
+                .into());
+            }
+            pillars.push(pillar);
+        }
+        let mut yields = vec![first_yield];
+        yields.extend(futures.iter().map(SofrFuture::implied_rate));
+        let curve = YieldCurve::new(settlement_date, &pillars, &yields)?;
+        Ok(Self {
+            settlement_date,
+            overnight_rate,
+            futures,
+            pillars,
+            yields,
+            anchors: BTreeMap::new(),
+            knots: Vec::new(),
+            curve,
+            diagnostics: BootstrapDiagnostics::default(),
+            input_checks,
+        })
+    }
+
+    /// Pins the spot yield of a node on `date` to `spot_yield`, e.g. to a one-week term rate,
+    /// and re-solves the curve.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `date` does not follow the settlement date or is already a node,
+    /// or an error if a past fixing is missing or the solver fails.
+    pub fn with_anchor<S: BuildHasher>(
+        mut self,
+        date: Date,
+        spot_yield: V,
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<Self> {
+        self.check_new_node(date)?;
+        self.anchors.insert(date, spot_yield);
+        self.solve_from(0, calendar, fixings)
+            .context("bootstrapping the overnight curve with an anchored node")?;
+        Ok(self)
+    }
+
+    /// Adds interpolation nodes on `knots` and re-solves the curve. The yield of a knot follows linearly in time
+    /// from the neighbouring pillars and anchors, so that a global interpolator such as a spline bends through it
+    /// rather than overshooting between distant pillars.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a knot does not follow the settlement date or is already a node,
+    /// or an error if a past fixing is missing or the solver fails.
+    pub fn with_knots<S: BuildHasher>(
+        mut self,
+        knots: &[Date],
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<Self> {
+        for &knot in knots {
+            self.check_new_node(knot)?;
+            self.knots.push(knot);
+        }
+        self.knots.sort_unstable();
+        self.solve_from(0, calendar, fixings)
+            .context("bootstrapping the overnight curve with extra knots")?;
+        Ok(self)
+    }
+
+    fn check_new_node(&self, date: Date) -> QLabResult<()> {
+        if date <= self.settlement_date {
+            return Err(InvalidInput(
+                format!(
+                    "the node on {date} must follow the settlement date {}",
+                    self.settlement_date
+                )
+                .into(),
+            )
+            .into());
+        }
+        if self.pillars.contains(&date)
+            || self.anchors.contains_key(&date)
+            || self.knots.contains(&date)
+        {
+            return Err(
+                InvalidInput(format!("{date} is already a node of the curve").into()).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the pinned spot yields by date.
+    #[must_use]
+    pub fn anchors(&self) -> &BTreeMap<Date, V> {
+        &self.anchors
+    }
+
+    #[must_use]
+    pub fn knots(&self) -> &[Date] {
+        &self.knots
+    }
+
+    /// Returns the futures in the order of their pillars.
+    #[must_use]
+    pub fn futures(&self) -> &[SofrFuture<V>] {
+        &self.futures
+    }
+
+    #[must_use]
+    pub fn curve(&self) -> &YieldCurve<D, I> {
+        &self.curve
+    }
+
+    /// Returns the solver diagnostics of the initial bootstrap and of every later re-solve, with an event
+    /// per re-solved pillar. A failed re-solve is recorded even though it leaves the curve unchanged;
+    /// the diagnostics of a failed initial bootstrap are returned by [`SofrCurveBuilder::new_with_diagnostics`].
+    #[must_use]
+    pub fn diagnostics(&self) -> &BootstrapDiagnostics<V> {
+        &self.diagnostics
+    }
+
+    /// Returns the non-blocking warnings about the futures, such as kinks in the strip, as of the latest prices.
+    #[must_use]
+    pub fn input_checks(&self) -> &InputChecks<V> {
+        &self.input_checks
+    }
+
+    #[must_use]
+    pub fn into_curve(self) -> YieldCurve<D, I> {
+        self.curve
+    }
+
+    /// Sets the price of the future at `index` in [`SofrCurveBuilder::futures`] and re-solves its pillar
+    /// and the later ones. The builder is left unchanged if the update fails.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `index` is out of range, or an error if a past fixing is missing
+    /// or the solver fails.
+    pub fn update_price<S: BuildHasher>(
+        &mut self,
+        index: usize,
+        price: V,
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<()> {
+        if index >= self.futures.len() {
+            return Err(InvalidInput(
+                format!("no future at {index} among {}", self.futures.len()).into(),
+            )
+            .into());
+        }
+        let previous = self.futures[index].price;
+        self.futures[index].price = price;
+        let solved = self.solve_from(index, calendar, fixings).with_context(|| {
+            format!(
+                "re-bootstrapping the overnight curve from the SOFR future referencing {}",
+                self.futures[index].reference_start()
+            )
+        });
+        if solved.is_err() {
+            self.futures[index].price = previous;
+        } else {
+            self.input_checks =
+                CurveInputValidator::new(self.settlement_date).check_futures(&self.futures);
+        }
+        solved
+    }
+
+    /// Calculates the Jacobian of the curve with respect to the overnight rate, labelled `"ON"`, and the rates
+    /// implied by the futures in the order of [`SofrCurveBuilder::futures`], labelled by their contract codes
+    /// and reference starts, by re-solving the curve with each rate bumped by one basis point.
+    /// The builder is left unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if a past fixing is missing or the solver fails.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn jacobian<S: BuildHasher>(
+        &mut self,
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<CurveJacobian<V>> {
+        let base = self.yields.clone();
+        let diagnostics = self.diagnostics.clone();
+        let entries = self.bumped_rows(&base, calendar, fixings);
+        self.yields.clone_from(&base);
+        self.diagnostics = diagnostics;
+        self.curve = node_curve(
+            self.settlement_date,
+            &self.pillars,
+            &self.yields,
+            &self.anchors,
+            &self.knots,
+        )?;
+        let mut instruments = vec!["ON".to_string()];
+        instruments.extend(self.futures.iter().map(|future| {
+            let code = match future.kind {
+                SofrFutureKind::OneMonth => "SR1",
+                SofrFutureKind::ThreeMonth => "SR3",
+            };
+            format!("{code} {}", future.reference_start)
+        }));
+        CurveJacobian::new(instruments, entries?)
+    }
+
+    fn bumped_rows<S: BuildHasher>(
+        &mut self,
+        base: &[V],
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<Vec<Vec<V>>> {
+        let basis_point = V::from_f64(1e-4).unwrap();
+        let row = |yields: &[V]| {
+            base.iter()
+                .zip(yields)
+                .map(|(&base, &bumped)| (bumped - base) / basis_point)
+                .collect::<Vec<_>>()
+        };
+        self.yields[0] = overnight_yield::<V, D>(
+            self.settlement_date,
+            self.pillars[0],
+            self.overnight_rate + basis_point,
+        )?;
+        self.solve_from(0, calendar, fixings)?;
+        let mut rows = vec![row(&self.yields)];
+        // a rate one basis point higher lowers the price by one hundredth
+        let price_bump = V::from_u8(100).unwrap() * basis_point;
+        for index in 0..self.futures.len() {
+            self.yields.clear();
+            self.yields.extend_from_slice(base);
+            let price = self.futures[index].price;
+            self.futures[index].price = price - price_bump;
+            let solved = self.solve_from(index, calendar, fixings);
+            self.futures[index].price = price;
+            solved?;
+            rows.push(row(&self.yields));
+        }
+        Ok(rows)
+    }
+
+    /// Solves the yields of the pillars of the futures from `from` onwards, holding the earlier yields.
+    fn solve_from<S: BuildHasher>(
+        &mut self,
+        from: usize,
+        calendar: &impl Calendar,
+        fixings: &HashMap<Date, V, S>,
+    ) -> QLabResult<()> {
+        let fixed = &self.yields[..=from];
+        let curve = |unknowns: &[V]| {
+            let spot_yields: Vec<_> = fixed.iter().chain(unknowns).copied().collect();
+            node_curve::<V, D, I>(
+                self.settlement_date,
+                &self.pillars,
+                &spot_yields,
+                &self.anchors,
+                &self.knots,
+            )
+        };
+        let futures = &self.futures[from..];
+        let residuals = |unknowns: &[V]| {
+            let curve = curve(unknowns)?;
+            futures
+                .iter()
+                .map(|future| {
+                    let projected_rate = future
+                        .projected_rate(&curve, calendar, fixings)
+                        .with_context(|| {
+                            format!(
+                                "projecting the SOFR future referencing {}",
+                                future.reference_start()
+                            )
+                        })?;
+                    Ok(projected_rate - future.implied_rate())
+                })
+                .collect::<QLabResult<Vec<_>>>()
+        };
+        let initial: Vec<_> = futures.iter().map(SofrFuture::implied_rate).collect();
+        let pillars = &self.pillars[from + 1..];
+        let report = match least_squares_with_report(residuals, &initial)
+            .and_then(|report| Ok((residuals(&report.parameters)?, report)))
+        {
+            Ok((residuals, report)) => {
+                self.diagnostics
+                    .record_solved(pillars.iter().copied().zip(residuals), &report);
+                report
+            }
+            Err(err) => {
+                self.diagnostics
+                    .record_failed(pillars.iter().copied(), &err.to_string());
+                return Err(err);
+            }
+        };
+        let curve = curve(&report.parameters)?;
+        self.yields.truncate(from + 1);
+        self.yields.extend(report.parameters);
+        self.curve = curve;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quote::QuoteScale;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_termstructure::diagnostics::BootstrapEvent;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_reference_periods() {
+        let future = SofrFuture::three_month(2024, 3, 95.0).unwrap();
+        assert_eq!(
+            future.reference_start(),
+            Date::from_ymd(2024, 3, 20).unwrap()
+        );
+        assert_eq!(future.reference_end(), Date::from_ymd(2024, 6, 19).unwrap());
+        let future = SofrFuture::three_month(2024, 12, 95.0).unwrap();
+        assert_eq!(future.reference_end(), Date::from_ymd(2025, 3, 19).unwrap());
+        let future = SofrFuture::one_month(2024, 12, 95.0_f64).unwrap();
+        assert_eq!(future.reference_end(), Date::from_ymd(2025, 1, 1).unwrap());
+        assert!((future.implied_rate() - 0.05).abs() < 1e-12);
+        assert!(SofrFuture::one_month(2024, 13, 95.0).is_err());
+    }
+
+    #[test]
+    fn test_from_quote() {
+        let price = Quote::price(94.75_f64);
+        let rate = Quote::rate(525.0, QuoteScale::BasisPoints);
+        for quote in [price, rate] {
+            let future =
+                SofrFuture::from_quote(SofrFutureKind::ThreeMonth, 2024, 3, &quote).unwrap();
+            assert!((future.price() - 94.75).abs() < 1e-12);
+        }
+        // a rate on an Act/365 basis earns less per Act/360 day
+        let act_365 = Quote::rate(5.25_f64, QuoteScale::Percent)
+            .with_basis(Act365::calculate_day_count_fraction);
+        let future = SofrFuture::from_quote(SofrFutureKind::OneMonth, 2024, 3, &act_365).unwrap();
+        assert!((future.implied_rate() - 0.0525 * 360.0 / 365.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_settlement_conventions() {
+        // June 2024 starts on a Saturday, which carries the rate of Friday May 31
+        let future = SofrFuture::one_month(2024, 6, 95.0).unwrap();
+        let may_31 = Date::from_ymd(2024, 5, 31).unwrap();
+        let rate = future
+            .settlement_rate(&WeekendsOnly, |date| {
+                Ok(if date == may_31 { 0.06 } else { 0.05 })
+            })
+            .unwrap();
+        assert!((rate - (2.0 * 0.06 + 28.0 * 0.05_f64) / 30.0).abs() < 1e-15);
+
+        // a constant rate compounds over each business day and the following non-business days
+        let future = SofrFuture::three_month(2024, 3, 95.0).unwrap();
+        let rate = future.settlement_rate(&WeekendsOnly, |_| Ok(0.05)).unwrap();
+        // 91 days in 65 weekday periods, of which 13 span a weekend
+        let compounded =
+            (1.0 + 0.05 / 360.0_f64).powi(52) * (1.0 + 0.05 * 3.0 / 360.0_f64).powi(13);
+        assert!(
+            (rate - (compounded - 1.0) * 360.0 / 91.0).abs() < 1e-12,
+            "{rate}"
+        );
+        assert!(rate > 0.05);
+    }
+
+    #[test]
+    fn test_update_price() {
+        let settlement_date = Date::from_ymd(2024, 3, 20).unwrap();
+        let futures = [
+            SofrFuture::three_month(2024, 3, 94.75).unwrap(),
+            SofrFuture::three_month(2024, 6, 94.9).unwrap(),
+            SofrFuture::three_month(2024, 9, 95.2).unwrap(),
+        ];
+        let fixings = HashMap::new();
+        let mut builder: SofrCurveBuilder<Act365, Linear<f64>> =
+            SofrCurveBuilder::new(settlement_date, 0.0531, &futures, &WeekendsOnly, &fixings)
+                .unwrap();
+        let before = builder.curve().pillars().to_vec();
+        builder
+            .update_price(1, 95.0, &WeekendsOnly, &fixings)
+            .unwrap();
+        let after = builder.curve().pillars();
+        // the pillars before the updated future keep their yields
+        assert_eq!(before[..2], after[..2]);
+        assert!((before[2].1 - after[2].1).abs() > 1e-6);
+        let updated = [futures[0], builder.futures()[1], futures[2]];
+        let full: YieldCurve<Act365, Linear<f64>> =
+            bootstrap(settlement_date, 0.0531, &updated, &WeekendsOnly, &fixings).unwrap();
+        for (incremental, full) in after.iter().zip(full.pillars()) {
+            assert!((incremental.1 - full.1).abs() < 1e-9);
+        }
+        assert!(builder
+            .update_price(3, 95.0, &WeekendsOnly, &fixings)
+            .is_err());
+
+        // the initial bootstrap solves all three pillars and the update the last two
+        let diagnostics = builder.diagnostics();
+        assert_eq!(diagnostics.solves(), 2);
+        let events = diagnostics.events();
+        assert_eq!(events.len(), 5);
+        assert!(events[3..].iter().all(|event| event.solve() == 1));
+        assert_eq!(events[3].pillar(), futures[1].reference_end());
+        assert!(matches!(
+            events[4],
+            BootstrapEvent::Solved { pillar, .. } if pillar == futures[2].reference_end()
+        ));
+        assert!(diagnostics.max_residual().unwrap() < 1e-9);
+        assert!(diagnostics.failures().is_empty());
+        assert_eq!(diagnostics.report().unwrap().rows().len(), 5);
+
+        // a kink in the strip is kept as a warning, and futures ending on the same date are rejected
+        assert!(builder.input_checks().is_clean());
+        builder
+            .update_price(1, 94.7, &WeekendsOnly, &fixings)
+            .unwrap();
+        let warnings = builder.input_checks().warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(!warnings[0].is_blocking());
+        let duplicated = SofrCurveBuilder::<Act365, Linear<f64>>::new(
+            settlement_date,
+            0.0531,
+            &[futures[0], futures[0]],
+            &WeekendsOnly,
+            &fixings,
+        );
+        assert!(duplicated.is_err_and(|err| err.to_string().contains("bad curve inputs")));
+    }
+
+    #[test]
+    fn test_anchors_and_knots() {
+        let settlement_date = Date::from_ymd(2024, 3, 20).unwrap();
+        let futures = [
+            SofrFuture::three_month(2024, 3, 94.9).unwrap(),
+            SofrFuture::three_month(2024, 6, 95.1).unwrap(),
+        ];
+        let fixings = HashMap::new();
+        let one_week = Date::from_ymd(2024, 3, 27).unwrap();
+        let knot = Date::from_ymd(2024, 8, 1).unwrap();
+        let build = || {
+            SofrCurveBuilder::<Act365, Linear<f64>>::new(
+                settlement_date,
+                0.0531,
+                &futures,
+                &WeekendsOnly,
+                &fixings,
+            )
+            .unwrap()
+            .with_anchor(one_week, 0.0525, &WeekendsOnly, &fixings)
+            .unwrap()
+            .with_knots(&[knot], &WeekendsOnly, &fixings)
+            .unwrap()
+        };
+        let builder = build();
+        assert_eq!(builder.anchors().len(), 1);
+        assert_eq!(builder.knots(), [knot]);
+        let pillars = builder.curve().pillars();
+        assert_eq!(pillars.len(), 5);
+        // the pinned one-week yield holds and the futures still reprice
+        assert!((pillars[1].1 - 0.0525).abs() < f64::EPSILON);
+        for future in builder.futures() {
+            let rate = future
+                .projected_rate(builder.curve(), &WeekendsOnly, &fixings)
+                .unwrap();
+            assert!((rate - future.implied_rate()).abs() < 1e-9);
+        }
+        // the knot lies on the line between its neighbouring pillars
+        let (t0, y0) = pillars[2];
+        let (t, y) = pillars[3];
+        let (t1, y1) = pillars[4];
+        assert!((y - (y0 + (y1 - y0) * (t - t0) / (t1 - t0))).abs() < 1e-12);
+
+        // a node must follow the settlement date and not repeat a pillar, an anchor or a knot
+        for date in [settlement_date, one_week, knot, futures[0].reference_end()] {
+            assert!(build()
+                .with_anchor(date, 0.05, &WeekendsOnly, &fixings)
+                .is_err());
+        }
+        assert!(build()
+            .with_knots(
+                &[Date::from_ymd(2024, 3, 21).unwrap()],
+                &WeekendsOnly,
+                &fixings
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_bootstrap() {
+        let settlement_date = Date::from_ymd(2024, 4, 10).unwrap();
+        let fixings: HashMap<_, _> = {
+            let mut fixings = HashMap::new();
+            let mut date = Date::from_ymd(2024, 3, 20).unwrap();
+            while date < settlement_date {
+                fixings.insert(date, 0.0532);
+                date = date.succ_opt().unwrap();
+            }
+            fixings
+        };
+        let futures = [
+            SofrFuture::one_month(2024, 5, 94.72).unwrap(),
+            SofrFuture::three_month(2024, 3, 94.75).unwrap(),
+            SofrFuture::three_month(2024, 6, 94.9).unwrap(),
+            SofrFuture::three_month(2024, 9, 95.2).unwrap(),
+        ];
+        let curve: YieldCurve<Act365, Linear<f64>> =
+            bootstrap(settlement_date, 0.0531, &futures, &WeekendsOnly, &fixings).unwrap();
+        assert_eq!(curve.pillars().len(), 5);
+        for future in &futures {
+            let rate = future
+                .projected_rate(&curve, &WeekendsOnly, &fixings)
+                .unwrap();
+            assert!((rate - future.implied_rate()).abs() < 1e-9);
+        }
+        // the partly fixed contract needs its past fixings
+        assert!(futures[1]
+            .projected_rate(&curve, &WeekendsOnly, &HashMap::new())
+            .is_err());
+        // without them the bootstrap fails at the pillars of the joint solve
+        let (curve, diagnostics) = bootstrap_with_diagnostics::<_, Act365, Linear<f64>, _>(
+            settlement_date,
+            0.0531,
+            &futures,
+            &WeekendsOnly,
+            &HashMap::new(),
+        );
+        assert!(curve.is_err());
+        assert_eq!(diagnostics.solves(), 1);
+        let failures = diagnostics.failures();
+        assert_eq!(failures.len(), futures.len());
+        assert!(failures
+            .windows(2)
+            .all(|pair| pair[0].pillar() < pair[1].pillar()));
+        assert!(matches!(
+            failures[0],
+            BootstrapEvent::Failed { message, .. } if message.contains("2024-03-20")
+        ));
+        let duplicated = [futures[2], futures[2]];
+        assert!(bootstrap::<_, Act365, Linear<f64>, _>(
+            settlement_date,
+            0.0531,
+            &duplicated,
+            &WeekendsOnly,
+            &fixings
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::parallel::for_each_index;
+use qlab_math::value::Value;
+use qlab_termstructure::report::Table;
+use std::ops::ControlFlow;
+use std::sync::{Mutex, PoisonError};
+
+/// A shift of the market data an option portfolio is revalued on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotVolShift<V> {
+    /// The relative change of the underlying spot, e.g. `-0.05` for a 5% fall.
+    pub spot: V,
+    /// The absolute change of the volatilities, e.g. `0.01` for one vol point up.
+    pub vol: V,
+}
+
+/// The values of a portfolio over a grid of spot shifts by vol shifts, the trader's spot/vol risk matrix.
+///
+/// Rows follow the spot shifts and columns the vol shifts, in the order they were given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotVolLadder<V> {
+    spot_shifts: Vec<V>,
+    vol_shifts: Vec<V>,
+    base_value: V,
+    values: Vec<Vec<V>>,
+}
+
+impl<V: Value> SpotVolLadder<V> {
+    #[must_use]
+    pub fn spot_shifts(&self) -> &[V] {
+        &self.spot_shifts
+    }
+
+    #[must_use]
+    pub fn vol_shifts(&self) -> &[V] {
+        &self.vol_shifts
+    }
+
+    /// Returns the value without any shift.
+    #[must_use]
+    pub fn base_value(&self) -> V {
+        self.base_value
+    }
+
+    /// Returns the revalued portfolio values, a row per spot shift.
+    #[must_use]
+    pub fn values(&self) -> &[Vec<V>] {
+        &self.values
+    }
+
+    /// Returns the change from the base value under the `spot_index`-th spot shift
+    /// and the `vol_index`-th vol shift, or `None` if an index is out of range.
+    #[must_use]
+    pub fn pnl(&self, spot_index: usize, vol_index: usize) -> Option<V> {
+        self.values
+            .get(spot_index)
+            .and_then(|row| row.get(vol_index))
+            .map(|&value| value - self.base_value)
+    }
+
+    /// Builds a table of the changes from the base value with a row per spot shift and a column per vol shift;
+    /// shifts are printed as percentages and changes with 4 decimals.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn report(&self) -> QLabResult<Table> {
+        let hundred = V::from_u8(100).unwrap();
+        let mut headers = vec!["spot \\ vol".to_string()];
+        headers.extend(
+            self.vol_shifts
+                .iter()
+                .map(|&shift| format!("{:+.2?}%", shift * hundred)),
+        );
+        let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+        let mut table = Table::new(&headers);
+        for (&spot_shift, row) in self.spot_shifts.iter().zip(&self.values) {
+            let mut cells = vec![format!("{:+.2?}%", spot_shift * hundred)];
+            cells.extend(
+                row.iter()
+                    .map(|&value| format!("{:.4?}", value - self.base_value)),
+            );
+            table.push_row(cells)?;
+        }
+        Ok(table)
+    }
+}
+
+/// Revalues a portfolio with `value` under every combination of `spot_shifts` and `vol_shifts`,
+/// spreading the revaluations over `threads` threads.
+///
+/// The revaluations are independent, so the ladder does not depend on the number of threads.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::spot_vol_ladder::spot_vol_ladder;
+///
+/// // a long forward with a vega of -50 per unit of vol
+/// let ladder = spot_vol_ladder(&[-0.1_f64, 0.0, 0.1], &[-0.01, 0.0, 0.01], 2, |shift| {
+///     Ok(100.0 * (1.0 + shift.spot) - 100.0 - 50.0 * shift.vol)
+/// })
+/// .unwrap();
+/// assert!((ladder.pnl(2, 0).unwrap() - 10.5).abs() < 1e-12);
+/// assert_eq!(ladder.report().unwrap().rows().len(), 3);
+/// ```
+///
+/// # Errors
+/// Returns an `InvalidInput` error if there is no shift along an axis or `threads` is zero,
+/// or the error of the first failed revaluation in row order.
+///
+/// # Panics
+/// Will propagate the panic of `value` raised on a worker thread.
+pub fn spot_vol_ladder<V, F>(
+    spot_shifts: &[V],
+    vol_shifts: &[V],
+    threads: usize,
+    value: F,
+) -> QLabResult<SpotVolLadder<V>>
+where
+    V: Value + Send + Sync,
+    F: Fn(SpotVolShift<V>) -> QLabResult<V> + Sync,
+{
+    if spot_shifts.is_empty() || vol_shifts.is_empty() || threads == 0 {
+        return Err(InvalidInput(
+            format!(
+                "{} spot shifts, {} vol shifts and {threads} threads must be positive",
+                spot_shifts.len(),
+                vol_shifts.len()
+            )
+            .into(),
+        )
+        .into());
+    }
+    let base_value = value(SpotVolShift {
+        spot: V::zero(),
+        vol: V::zero(),
+    })?;
+    let num_cells = spot_shifts.len() * vol_shifts.len();
+    let cells = Mutex::new((0..num_cells).map(|_| None).collect::<Vec<_>>());
+    for_each_index(num_cells, threads, |cell| {
+        let shift = SpotVolShift {
+            spot: spot_shifts[cell / vol_shifts.len()],
+            vol: vol_shifts[cell % vol_shifts.len()],
+        };
+        let result = value(shift);
+        cells.lock().unwrap_or_else(PoisonError::into_inner)[cell] = Some(result);
+        ControlFlow::Continue(())
+    });
+    let cells = cells.into_inner().unwrap_or_else(PoisonError::into_inner);
+    let values = cells
+        .into_iter()
+        .map(|cell| {
+            cell.unwrap_or_else(|| Err(InvalidInput("a cell was not revalued".into()).into()))
+        })
+        .collect::<QLabResult<Vec<_>>>()?;
+    Ok(SpotVolLadder {
+        spot_shifts: spot_shifts.to_vec(),
+        vol_shifts: vol_shifts.to_vec(),
+        base_value,
+        values: values.chunks(vol_shifts.len()).map(<[V]>::to_vec).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_model::black;
+    use qlab_model::option_type::OptionType;
+
+    #[test]
+    fn test_spot_vol_ladder() {
+        // long 10 calls and short 5 puts on a spot of 100 with 20% vol, one year to expiry
+        let portfolio = |shift: SpotVolShift<f64>| {
+            let spot = 100.0 * (1.0 + shift.spot);
+            let vol = 0.2 + shift.vol;
+            Ok(10.0 * black::price(OptionType::Call, spot, 105.0, vol, 1.0)
+                - 5.0 * black::price(OptionType::Put, spot, 95.0, vol, 1.0))
+        };
+        let spot_shifts = [-0.2, -0.1, -0.05, 0.0, 0.05, 0.1, 0.2];
+        let vol_shifts = [-0.05, 0.0, 0.05];
+        let ladder = spot_vol_ladder(&spot_shifts, &vol_shifts, 1, portfolio).unwrap();
+        assert!(ladder.pnl(3, 1).unwrap().abs() < 1e-12);
+        // long delta and long calls dominate: the value rises with the spot and with the vol at the money
+        for row in ladder.values().windows(2) {
+            assert!(row[1][1] > row[0][1]);
+        }
+        assert!(ladder.pnl(3, 2).unwrap() > 0.0);
+        assert_eq!(ladder.pnl(7, 0), None);
+        let report = ladder.report().unwrap();
+        assert_eq!(report.headers()[1], "-5.00%");
+        assert_eq!(report.rows()[0][0], "-20.00%");
+
+        for threads in [2, 5, 64] {
+            let parallel = spot_vol_ladder(&spot_shifts, &vol_shifts, threads, portfolio).unwrap();
+            assert_eq!(parallel, ladder);
+        }
+
+        assert!(spot_vol_ladder(&[], &vol_shifts, 1, portfolio).is_err());
+        assert!(spot_vol_ladder(&spot_shifts, &vol_shifts, 4, |shift| {
+            if shift.vol < 0.0 && shift.spot > 0.0 {
+                Err(InvalidInput("negative vol".into()).into())
+            } else {
+                portfolio(shift)
+            }
+        })
+        .is_err());
+    }
+}
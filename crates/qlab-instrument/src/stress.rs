@@ -0,0 +1,432 @@
+use crate::bond::Bond;
+use qlab_error::ComputeError::CastNumberError;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+
+/// A shock applied to the spot yields of a curve.
+///
+/// All shifts are expressed as absolute changes in yield (e.g. `0.01` for +100bp).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CurveShock<V> {
+    /// Shifts every pillar by the same amount.
+    Parallel(V),
+    /// Shifts each pillar by a value linearly interpolated from a table of `(year fraction, shift)` pairs.
+    ///
+    /// The table must be sorted by year fraction. Pillars outside the table are shifted by the nearest entry.
+    Table(Vec<(V, V)>),
+}
+
+impl<V: Value> CurveShock<V> {
+    fn shift_at(&self, t: V) -> V {
+        match self {
+            Self::Parallel(shift) => *shift,
+            Self::Table(table) => {
+                let Some(&(first_t, first_shift)) = table.first() else {
+                    return V::zero();
+                };
+                if t <= first_t {
+                    return first_shift;
+                }
+                for window in table.windows(2) {
+                    let (t0, s0) = window[0];
+                    let (t1, s1) = window[1];
+                    if t <= t1 {
+                        return s0 + (s1 - s0) * (t - t0) / (t1 - t0);
+                    }
+                }
+                table[table.len() - 1].1
+            }
+        }
+    }
+}
+
+/// A shock applied to volatilities.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VolShock<V> {
+    /// Multiplies every volatility by a factor (e.g. `2` to double them).
+    Scale(V),
+    /// Shifts every volatility by an absolute amount (e.g. `0.05` for +5 vol points).
+    Shift(V),
+}
+
+/// A named stress scenario composed of curve and vol shocks.
+///
+/// Curve shocks of a scenario are additive: the spot yield of each pillar is shifted by the sum of all
+/// curve shocks. Vol shocks are applied to a volatility one after the other, in the order they were added.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::stress::Scenario;
+///
+/// let scenario = Scenario::<f64>::parallel_up_100bp()
+///     .unwrap()
+///     .compose(&Scenario::crisis_2008().unwrap());
+/// assert_eq!(scenario.name(), "+100bp parallel + 2008 crisis");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario<V> {
+    name: String,
+    shocks: Vec<CurveShock<V>>,
+    vol_shocks: Vec<VolShock<V>>,
+}
+
+impl<V: Value> Scenario<V> {
+    /// Creates a scenario without any shocks.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            shocks: Vec::new(),
+            vol_shocks: Vec::new(),
+        }
+    }
+
+    /// Adds a shock to the scenario.
+    #[must_use]
+    pub fn with_shock(mut self, shock: CurveShock<V>) -> Self {
+        self.shocks.push(shock);
+        self
+    }
+
+    /// Adds a vol shock to the scenario.
+    #[must_use]
+    pub fn with_vol_shock(mut self, shock: VolShock<V>) -> Self {
+        self.vol_shocks.push(shock);
+        self
+    }
+
+    /// Combines two scenarios into one applying the shocks of both.
+    #[must_use]
+    pub fn compose(&self, other: &Self) -> Self {
+        Self {
+            name: format!("{} + {}", self.name, other.name),
+            shocks: self
+                .shocks
+                .iter()
+                .chain(other.shocks.iter())
+                .cloned()
+                .collect(),
+            vol_shocks: self
+                .vol_shocks
+                .iter()
+                .chain(other.vol_shocks.iter())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Creates a scenario shifting all yields by `bp` basis points.
+    ///
+    /// # Errors
+    /// Returns an error if `bp` cannot be cast to `V`.
+    pub fn parallel(name: &str, bp: f64) -> QLabResult<Self> {
+        Ok(Self::new(name).with_shock(CurveShock::Parallel(from_bp(bp)?)))
+    }
+
+    /// Creates a scenario shifting yields according to a table of `(year fraction, bp)` pairs.
+    ///
+    /// # Errors
+    /// Returns an error if an entry of `table` cannot be cast to `V`.
+    pub fn table(name: &str, table: &[(f64, f64)]) -> QLabResult<Self> {
+        let table = table
+            .iter()
+            .map(|&(t, bp)| {
+                let t = V::from_f64(t).ok_or_else(|| CastNumberError(format!("{t}").into()))?;
+                Ok((t, from_bp(bp)?))
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        Ok(Self::new(name).with_shock(CurveShock::Table(table)))
+    }
+
+    /// A +100bp parallel shift.
+    ///
+    /// # Errors
+    /// Returns an error if the shift cannot be cast to `V`.
+    pub fn parallel_up_100bp() -> QLabResult<Self> {
+        Self::parallel("+100bp parallel", 100.0)
+    }
+
+    /// A -100bp parallel shift.
+    ///
+    /// # Errors
+    /// Returns an error if the shift cannot be cast to `V`.
+    pub fn parallel_down_100bp() -> QLabResult<Self> {
+        Self::parallel("-100bp parallel", -100.0)
+    }
+
+    /// A stylized reproduction of the move of the USD curve in the fourth quarter of 2008:
+    /// a strong bull steepening driven by the front end.
+    ///
+    /// # Errors
+    /// Returns an error if the shifts cannot be cast to `V`.
+    pub fn crisis_2008() -> QLabResult<Self> {
+        Self::table(
+            "2008 crisis",
+            &[
+                (0.25, -250.0),
+                (2.0, -200.0),
+                (5.0, -175.0),
+                (10.0, -150.0),
+                (30.0, -100.0),
+            ],
+        )
+    }
+
+    /// Doubles every volatility.
+    ///
+    /// # Errors
+    /// Returns an error if the factor cannot be cast to `V`.
+    pub fn vol_doubling() -> QLabResult<Self> {
+        let two = V::from_u8(2).ok_or_else(|| CastNumberError("2".into()))?;
+        Ok(Self::new("vol doubling").with_vol_shock(VolShock::Scale(two)))
+    }
+
+    /// Returns the name of the scenario.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the shocks of the scenario.
+    #[must_use]
+    pub fn shocks(&self) -> &[CurveShock<V>] {
+        &self.shocks
+    }
+
+    /// Returns the vol shocks of the scenario.
+    #[must_use]
+    pub fn vol_shocks(&self) -> &[VolShock<V>] {
+        &self.vol_shocks
+    }
+
+    /// Applies the vol shocks of the scenario to `vol`, returning the stressed volatility.
+    #[must_use]
+    pub fn apply_vol(&self, vol: V) -> V {
+        self.vol_shocks.iter().fold(vol, |vol, shock| match shock {
+            VolShock::Scale(factor) => vol * *factor,
+            VolShock::Shift(shift) => vol + *shift,
+        })
+    }
+
+    /// Applies the scenario to `yield_curve`, returning the stressed curve.
+    ///
+    /// # Errors
+    /// Returns an error if the stressed curve cannot be constructed.
+    pub fn apply<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        yield_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<YieldCurve<D, I>> {
+        yield_curve.try_shifted(|t, y| {
+            self.shocks
+                .iter()
+                .fold(y, |acc, shock| acc + shock.shift_at(t))
+        })
+    }
+}
+
+fn from_bp<V: Value>(bp: f64) -> QLabResult<V> {
+    V::from_f64(bp / 10_000.0).ok_or_else(|| CastNumberError(format!("{bp}").into()).into())
+}
+
+/// The valuation of a single instrument under a stress scenario.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressedValue<V> {
+    pub instrument_id: String,
+    pub base_value: V,
+    pub stressed_value: V,
+}
+
+impl<V: Value> StressedValue<V> {
+    /// Returns the stressed P&L, i.e. the stressed value minus the base value.
+    #[must_use]
+    pub fn pnl(&self) -> V {
+        self.stressed_value - self.base_value
+    }
+}
+
+/// The result of running a stress scenario over a set of instruments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StressResult<V> {
+    pub scenario: String,
+    pub values: Vec<StressedValue<V>>,
+}
+
+impl<V: Value> StressResult<V> {
+    /// Returns the aggregate stressed P&L over all instruments.
+    #[must_use]
+    pub fn total_pnl(&self) -> V {
+        self.values
+            .iter()
+            .fold(V::zero(), |acc, value| acc + value.pnl())
+    }
+}
+
+/// Revalues `bonds` under `scenario` and reports the stressed P&L per bond and in aggregate.
+///
+/// # Errors
+/// Returns an error if the stressed curve cannot be constructed or if a valuation fails.
+pub fn run<V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    scenario: &Scenario<V>,
+    bonds: &[Bond<V>],
+    settle_date: Date,
+    yield_curve: &YieldCurve<D, I>,
+) -> QLabResult<StressResult<V>> {
+    let stressed_curve = scenario.apply(yield_curve)?;
+    let values = bonds
+        .iter()
+        .map(|bond| {
+            Ok(StressedValue {
+                instrument_id: bond.bond_id().to_string(),
+                base_value: bond.discounted_value(settle_date, yield_curve)?,
+                stressed_value: bond.discounted_value(settle_date, &stressed_curve)?,
+            })
+        })
+        .collect::<QLabResult<Vec<_>>>()?;
+    Ok(StressResult {
+        scenario: scenario.name().to_string(),
+        values,
+    })
+}
+
+/// Prices an instrument from a yield curve and a volatility.
+pub type Pricer<'a, V, D, I> = dyn Fn(&YieldCurve<D, I>, V) -> QLabResult<V> + 'a;
+
+/// Revalues instruments given as `(instrument_id, pricer)` pairs under `scenario`, with both the curve and
+/// the volatility stressed, and reports the stressed P&L per instrument and in aggregate.
+///
+/// # Errors
+/// Returns an error if the stressed curve cannot be constructed or if a pricer fails.
+pub fn run_with_vol<V: Value, D: DayCount, I: Interpolator<Value = V>>(
+    scenario: &Scenario<V>,
+    instruments: &[(&str, &Pricer<V, D, I>)],
+    yield_curve: &YieldCurve<D, I>,
+    vol: V,
+) -> QLabResult<StressResult<V>> {
+    let stressed_curve = scenario.apply(yield_curve)?;
+    let stressed_vol = scenario.apply_vol(vol);
+    let values = instruments
+        .iter()
+        .map(|&(instrument_id, pricer)| {
+            Ok(StressedValue {
+                instrument_id: instrument_id.to_string(),
+                base_value: pricer(yield_curve, vol)?,
+                stressed_value: pricer(&stressed_curve, stressed_vol)?,
+            })
+        })
+        .collect::<QLabResult<Vec<_>>>()?;
+    Ok(StressResult {
+        scenario: scenario.name().to_string(),
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::semi_annual_bond;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_model::black;
+    use qlab_model::option_type::OptionType;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_table_shift() {
+        let shock = CurveShock::Table(vec![(1.0, -0.02), (3.0, 0.0)]);
+        assert!((shock.shift_at(0.5) + 0.02_f64).abs() < f64::EPSILON);
+        assert!((shock.shift_at(2.0) + 0.01_f64).abs() < f64::EPSILON);
+        assert!(shock.shift_at(5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_run() {
+        let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let maturities = [
+            Date::from_ymd(2023, 10, 11).unwrap(),
+            Date::from_ymd(2028, 10, 10).unwrap(),
+            Date::from_ymd(2043, 10, 12).unwrap(),
+        ];
+        let yield_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &maturities, &[0.02, 0.03, 0.04]).unwrap();
+        let bond = semi_annual_bond(
+            "5 yr bond",
+            Date::from_ymd(2028, 11, 7).unwrap(),
+            0.05,
+            100.0,
+        )
+        .unwrap();
+
+        let up = run(
+            &Scenario::parallel_up_100bp().unwrap(),
+            std::slice::from_ref(&bond),
+            settle_date,
+            &yield_curve,
+        )
+        .unwrap();
+        let down = run(
+            &Scenario::parallel_down_100bp().unwrap(),
+            std::slice::from_ref(&bond),
+            settle_date,
+            &yield_curve,
+        )
+        .unwrap();
+        assert_eq!(up.values[0].instrument_id, "5 yr bond");
+        assert!(up.total_pnl() < 0.0);
+        assert!(down.total_pnl() > 0.0);
+        assert!(down.total_pnl() > -up.total_pnl());
+
+        let combined = Scenario::parallel_up_100bp()
+            .unwrap()
+            .compose(&Scenario::parallel_down_100bp().unwrap());
+        let flat = run(&combined, &[bond], settle_date, &yield_curve).unwrap();
+        assert!(flat.total_pnl().abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_vol_doubling() {
+        let settle_date = Date::from_ymd(2023, 10, 10).unwrap();
+        let maturities = [
+            Date::from_ymd(2023, 10, 11).unwrap(),
+            Date::from_ymd(2028, 10, 10).unwrap(),
+        ];
+        let yield_curve: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(settle_date, &maturities, &[0.02, 0.03]).unwrap();
+        let expiry = Date::from_ymd(2024, 10, 10).unwrap();
+        let call = |curve: &YieldCurve<Act365, Linear<f64>>, vol: f64| {
+            let df = curve.discount_factor(settle_date, expiry)?;
+            Ok(df * black::price(OptionType::Call, 100.0, 100.0, vol, 1.0))
+        };
+        let forward = |curve: &YieldCurve<Act365, Linear<f64>>, _: f64| {
+            Ok(curve.discount_factor(settle_date, expiry)? * (100.0 - 95.0))
+        };
+        let instruments: [(&str, &Pricer<_, _, _>); 2] = [("call", &call), ("forward", &forward)];
+
+        let doubled = run_with_vol(
+            &Scenario::vol_doubling().unwrap(),
+            &instruments,
+            &yield_curve,
+            0.2,
+        )
+        .unwrap();
+        assert_eq!(doubled.scenario, "vol doubling");
+        let expected = call(&yield_curve, 0.4).unwrap() - call(&yield_curve, 0.2).unwrap();
+        assert!((doubled.values[0].pnl() - expected).abs() < 1e-12);
+        assert!(doubled.values[0].pnl() > 0.0);
+        assert!(doubled.values[1].pnl().abs() < 1e-12);
+
+        // curve and vol shocks compose, vol shocks in order
+        let scenario = Scenario::<f64>::parallel_up_100bp()
+            .unwrap()
+            .compose(&Scenario::vol_doubling().unwrap())
+            .with_vol_shock(VolShock::Shift(-0.1));
+        assert!((scenario.apply_vol(0.2) - 0.3).abs() < 1e-12);
+        let combined = run_with_vol(&scenario, &instruments, &yield_curve, 0.2).unwrap();
+        assert!(combined.values[1].pnl() < 0.0);
+        let rates_only = Scenario::<f64>::parallel_up_100bp().unwrap();
+        assert!((rates_only.apply_vol(0.2) - 0.2).abs() < f64::EPSILON);
+    }
+}
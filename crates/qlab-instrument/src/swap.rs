@@ -0,0 +1,1343 @@
+use crate::alm::RepricingPosition;
+use crate::coupon_report::{CouponReport, CouponRow, RateSource};
+use crate::fixing_store::FixingStore;
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::calendar::Calendar;
+use qlab_time::date::Date;
+use qlab_time::date_rolling::DateRolling;
+use qlab_time::day_count::DayCount;
+use qlab_time::fixing::{fixings, Fixing, FixingConvention};
+use qlab_time::frequency::Frequency;
+use qlab_time::schedule::{AccrualSchedule, StubPolicy};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The side of the fixed leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwapDirection {
+    /// Pays the fixed leg and receives the floating leg.
+    Payer,
+    /// Receives the fixed leg and pays the floating leg.
+    Receiver,
+}
+
+/// A leg of a swap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SwapLeg {
+    Fixed,
+    Floating,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Accrual<V> {
+    unadjusted_start: Date,
+    unadjusted_end: Date,
+    /// The start of accrual, adjusted to a business day.
+    start: Date,
+    /// The end of accrual, adjusted to a business day.
+    end: Date,
+    payment_date: Date,
+    /// The day count fraction of the accrual in use, see [`Swap::with_adjusted_accrual`].
+    fraction: V,
+    adjusted_fraction: V,
+    unadjusted_fraction: V,
+    /// The notional outstanding over the accrual period.
+    notional: V,
+    /// The fixings of a floating coupon, empty for a fixed coupon.
+    fixings: Vec<Fixing>,
+}
+
+impl<V: Value> Accrual<V> {
+    /// Returns the rate of a floating coupon whose fixings compound to `growth` over the adjusted period,
+    /// which is the period the index is observed over.
+    fn floating_rate(&self, growth: V) -> V {
+        (growth - V::one()) / self.adjusted_fraction
+    }
+
+    /// Returns the interest per unit notional of a floating coupon whose fixings compound to `growth`,
+    /// accruing its rate over the fraction in use, see [`Swap::with_adjusted_accrual`].
+    fn floating_interest(&self, growth: V) -> V {
+        if self.fraction == self.adjusted_fraction {
+            growth - V::one()
+        } else {
+            self.floating_rate(growth) * self.fraction
+        }
+    }
+}
+
+/// An interest rate swap exchanging a fixed rate against a floating index projected from a curve.
+///
+/// The swap may start after the valuation date, its notional may amortize or accrete through
+/// [`Swap::with_notional_schedule`], and the notionals may be exchanged as set by [`Swap::with_exchanges`].
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::struct_field_names)]
+pub struct Swap<V> {
+    swap_id: String,
+    currency: String,
+    index: String,
+    direction: SwapDirection,
+    start: Date,
+    end: Date,
+    notional: V,
+    notional_schedule: Vec<(Date, V)>,
+    initial_exchange: bool,
+    final_exchange: bool,
+    fixed_rate: V,
+    fixed_accruals: Vec<Accrual<V>>,
+    floating_accruals: Vec<Accrual<V>>,
+    adjusted_accrual: bool,
+}
+
+fn accruals<V: Value, DC: DayCount>(
+    start: Date,
+    end: Date,
+    frequency: Frequency,
+    calendar: &impl Calendar,
+    rolling: DateRolling,
+    notional: V,
+) -> QLabResult<Vec<Accrual<V>>> {
+    AccrualSchedule::generate::<DC>(
+        start,
+        end,
+        frequency,
+        calendar,
+        rolling,
+        StubPolicy::ShortBack,
+    )?
+    .into_iter()
+    .map(|accrual| {
+        let period = accrual.period;
+        Ok(Accrual {
+            unadjusted_start: period.unadjusted_start,
+            unadjusted_end: period.unadjusted_end,
+            start: period.accrual_start,
+            end: period.accrual_end,
+            payment_date: period.payment_date,
+            fraction: accrual.adjusted_fraction,
+            adjusted_fraction: accrual.adjusted_fraction,
+            unadjusted_fraction: accrual.unadjusted_fraction,
+            notional,
+            fixings: Vec::new(),
+        })
+    })
+    .collect()
+}
+
+impl<V: Value> Swap<V> {
+    /// Creates a swap whose legs accrue from `start` to `end` on schedules adjusted by `calendar` and `rolling`,
+    /// with fixed accruals measured by `FD` and floating accruals by `LD`.
+    /// A `start` after the valuation date gives a forward-starting swap.
+    ///
+    /// The notional is constant and is not exchanged until set otherwise.
+    ///
+    /// Each floating coupon fixes on its accrual start until [`Swap::with_fixings`] sets the index conventions.
+    ///
+    /// # Errors
+    /// Returns an error if a schedule cannot be generated.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<FD: DayCount, LD: DayCount>(
+        swap_id: &str,
+        currency: &str,
+        index: &str,
+        direction: SwapDirection,
+        start: Date,
+        end: Date,
+        fixed_frequency: Frequency,
+        floating_frequency: Frequency,
+        calendar: &impl Calendar,
+        rolling: DateRolling,
+        fixed_rate: V,
+        notional: V,
+    ) -> QLabResult<Self> {
+        let mut floating_accruals =
+            accruals::<V, LD>(start, end, floating_frequency, calendar, rolling, notional)?;
+        for accrual in &mut floating_accruals {
+            accrual.fixings = fixings(
+                accrual.start,
+                accrual.end,
+                FixingConvention::Term { lag: 0 },
+                calendar,
+            )?;
+        }
+        Ok(Self {
+            swap_id: swap_id.to_string(),
+            currency: currency.to_uppercase(),
+            index: index.to_string(),
+            direction,
+            start,
+            end,
+            notional,
+            notional_schedule: Vec::new(),
+            initial_exchange: false,
+            final_exchange: false,
+            fixed_rate,
+            fixed_accruals: accruals::<V, FD>(
+                start,
+                end,
+                fixed_frequency,
+                calendar,
+                rolling,
+                notional,
+            )?,
+            floating_accruals,
+            adjusted_accrual: true,
+        })
+    }
+
+    /// Sets whether coupons of both legs accrue between the adjusted dates of their periods, as by default,
+    /// or between the unadjusted ones. Payments are made on the adjusted dates either way, and floating rates
+    /// are still observed over the adjusted periods.
+    #[must_use]
+    pub fn with_adjusted_accrual(mut self, adjusted: bool) -> Self {
+        for accrual in self
+            .fixed_accruals
+            .iter_mut()
+            .chain(&mut self.floating_accruals)
+        {
+            accrual.fraction = if adjusted {
+                accrual.adjusted_fraction
+            } else {
+                accrual.unadjusted_fraction
+            };
+        }
+        self.adjusted_accrual = adjusted;
+        self
+    }
+
+    /// Returns `true` if coupons accrue between adjusted dates, see [`Swap::with_adjusted_accrual`].
+    #[must_use]
+    pub fn adjusted_accrual(&self) -> bool {
+        self.adjusted_accrual
+    }
+
+    /// Sets an amortizing or accreting notional: from each date of `schedule` onwards the notional becomes the
+    /// paired amount, and a coupon accrues on the notional outstanding at the unadjusted start of its period.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the dates of `schedule` are not increasing.
+    pub fn with_notional_schedule(mut self, schedule: &[(Date, V)]) -> QLabResult<Self> {
+        if schedule.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(InvalidInput(
+                format!(
+                    "the dates of the notional schedule of {} must be increasing",
+                    self.swap_id
+                )
+                .into(),
+            )
+            .into());
+        }
+        self.notional_schedule = schedule.to_vec();
+        for accrual in self
+            .fixed_accruals
+            .iter_mut()
+            .chain(&mut self.floating_accruals)
+        {
+            accrual.notional = Self::outstanding(self.notional, schedule, accrual.unadjusted_start);
+        }
+        Ok(self)
+    }
+
+    /// Sets whether each leg pays out its notional on the start date and whether it repays the notional,
+    /// including any amortization, on the payment dates.
+    #[must_use]
+    pub fn with_exchanges(mut self, initial_exchange: bool, final_exchange: bool) -> Self {
+        self.initial_exchange = initial_exchange;
+        self.final_exchange = final_exchange;
+        self
+    }
+
+    /// Regenerates the fixings of the floating coupons under `convention` on the fixing `calendar` of the index.
+    ///
+    /// # Errors
+    /// Returns an error if a fixing date overflows.
+    pub fn with_fixings(
+        mut self,
+        convention: FixingConvention,
+        calendar: &impl Calendar,
+    ) -> QLabResult<Self> {
+        for accrual in &mut self.floating_accruals {
+            accrual.fixings = fixings(accrual.start, accrual.end, convention, calendar)?;
+        }
+        Ok(self)
+    }
+
+    /// Returns the fixings of each floating coupon together with its payment date.
+    #[must_use]
+    pub fn floating_fixings(&self) -> Vec<(Date, &[Fixing])> {
+        self.floating_accruals
+            .iter()
+            .map(|accrual| (accrual.payment_date, accrual.fixings.as_slice()))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn swap_id(&self) -> &str {
+        &self.swap_id
+    }
+
+    #[must_use]
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    #[must_use]
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+
+    #[must_use]
+    pub fn direction(&self) -> SwapDirection {
+        self.direction
+    }
+
+    /// Returns the date from which the swap accrues.
+    #[must_use]
+    pub fn start_date(&self) -> Date {
+        self.start
+    }
+
+    #[must_use]
+    pub fn end_date(&self) -> Date {
+        self.end
+    }
+
+    /// Returns the notional before the first step of the notional schedule.
+    #[must_use]
+    pub fn initial_notional(&self) -> V {
+        self.notional
+    }
+
+    /// Returns the notional outstanding on `date`, which is the amount of the last step of the notional schedule
+    /// on or before `date`, or the initial notional before the first step.
+    #[must_use]
+    pub fn notional_on(&self, date: Date) -> V {
+        Self::outstanding(self.notional, &self.notional_schedule, date)
+    }
+
+    fn outstanding(initial: V, schedule: &[(Date, V)], date: Date) -> V {
+        schedule
+            .iter()
+            .take_while(|(step, _)| *step <= date)
+            .last()
+            .map_or(initial, |&(_, notional)| notional)
+    }
+
+    #[must_use]
+    pub fn notional_schedule(&self) -> &[(Date, V)] {
+        &self.notional_schedule
+    }
+
+    #[must_use]
+    pub fn initial_exchange(&self) -> bool {
+        self.initial_exchange
+    }
+
+    #[must_use]
+    pub fn final_exchange(&self) -> bool {
+        self.final_exchange
+    }
+
+    #[must_use]
+    pub fn fixed_rate(&self) -> V {
+        self.fixed_rate
+    }
+
+    fn fixed_sign(&self) -> V {
+        match self.direction {
+            SwapDirection::Payer => -V::one(),
+            SwapDirection::Receiver => V::one(),
+        }
+    }
+
+    /// Calculates the value of receiving the fixed leg at a rate of one, i.e. the sum of the discounted
+    /// accrual fractions times the outstanding notionals of the coupons paid after `settle_date`.
+    ///
+    /// # Errors
+    /// Returns an error if a discount factor calculation fails.
+    pub fn annuity<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        self.fixed_accruals
+            .iter()
+            .filter(|accrual| accrual.payment_date > settle_date)
+            .try_fold(V::zero(), |acc, accrual| {
+                Ok(acc
+                    + accrual.notional
+                        * accrual.fraction
+                        * discount_curve.discount_factor(settle_date, accrual.payment_date)?)
+            })
+    }
+
+    /// Calculates the value of receiving the floating leg, projecting each coupon from `projection_curve`
+    /// over the periods weighting its fixings.
+    ///
+    /// # Errors
+    /// Returns an error if a coupon paid after `settle_date` has a fixing before the settlement date of
+    /// `projection_curve`, as past fixings are not supported, or if a discount factor calculation fails.
+    pub fn floating_leg_value<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        self.floating_accruals
+            .iter()
+            .filter(|accrual| accrual.payment_date > settle_date)
+            .try_fold(V::zero(), |acc, accrual| {
+                Ok(acc
+                    + self.floating_coupon(accrual, projection_curve)?
+                        * discount_curve.discount_factor(settle_date, accrual.payment_date)?)
+            })
+    }
+
+    /// Projects the floating coupon of `accrual` from `projection_curve` over the periods weighting its fixings.
+    fn floating_coupon<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        accrual: &Accrual<V>,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        let (Some(first), Some(last)) = (accrual.fixings.first(), accrual.fixings.last()) else {
+            return Ok(V::zero());
+        };
+        if first.fixing_date < projection_curve.settlement_date() {
+            return Err(InvalidInput(
+                format!(
+                    "the coupon accruing from {} of {} needs a past fixing on {}",
+                    accrual.start, self.swap_id, first.fixing_date
+                )
+                .into(),
+            )
+            .into());
+        }
+        let forward_discount_factor = projection_curve.discount_factor(first.start, last.end)?;
+        Ok(accrual.notional * accrual.floating_interest(V::one() / forward_discount_factor))
+    }
+
+    /// Returns the notional exchanges of `leg` paid after `settle_date` for its receiver,
+    /// see [`Swap::notional_exchange_value`].
+    fn exchange_flows(&self, leg: SwapLeg, settle_date: Date) -> Vec<(Date, V)> {
+        let accruals = match leg {
+            SwapLeg::Fixed => &self.fixed_accruals,
+            SwapLeg::Floating => &self.floating_accruals,
+        };
+        let mut flows = Vec::new();
+        if let Some(first) = accruals.first() {
+            if self.initial_exchange && first.start > settle_date {
+                flows.push((first.start, -first.notional));
+            }
+        }
+        if self.final_exchange {
+            for (i, accrual) in accruals.iter().enumerate() {
+                let next_notional = accruals.get(i + 1).map_or(V::zero(), |next| next.notional);
+                if accrual.payment_date > settle_date && accrual.notional != next_notional {
+                    flows.push((accrual.payment_date, accrual.notional - next_notional));
+                }
+            }
+        }
+        flows
+    }
+
+    /// Projects the flows of both legs paid after `settle_date` from the side of the direction of the swap,
+    /// including the notional exchanges, sorted by payment date.
+    ///
+    /// # Errors
+    /// Returns an error if a floating coupon cannot be projected, see [`Swap::floating_leg_value`].
+    pub fn cash_flows<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<Vec<(Date, V)>> {
+        let sign = self.fixed_sign();
+        let mut flows = Vec::new();
+        for accrual in &self.fixed_accruals {
+            if accrual.payment_date > settle_date {
+                let coupon = self.fixed_rate * accrual.notional * accrual.fraction;
+                flows.push((accrual.payment_date, sign * coupon));
+            }
+        }
+        for accrual in &self.floating_accruals {
+            if accrual.payment_date > settle_date {
+                let coupon = self.floating_coupon(accrual, projection_curve)?;
+                flows.push((accrual.payment_date, -sign * coupon));
+            }
+        }
+        for (date, amount) in self.exchange_flows(SwapLeg::Fixed, settle_date) {
+            flows.push((date, sign * amount));
+        }
+        for (date, amount) in self.exchange_flows(SwapLeg::Floating, settle_date) {
+            flows.push((date, -sign * amount));
+        }
+        flows.sort_by_key(|&(date, _)| date);
+        Ok(flows)
+    }
+
+    /// Reports each coupon of both legs paid after `settle_date` from the side of the direction of the swap.
+    ///
+    /// Fixings before `settle_date` are looked up from `store` under the index of the swap, and compounded with
+    /// the rate projected from `projection_curve` for the rest of the period, each weighted by its share of
+    /// the accrual fraction in calendar days. Unlike [`Swap::present_value`], coupons which have started fixing
+    /// are valued.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a fixing before `settle_date` is absent from `store`,
+    /// or an error if a discount factor calculation fails.
+    pub fn coupon_report<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+    ) -> QLabResult<CouponReport<V>> {
+        let sign = self.fixed_sign();
+        let mut rows = Vec::new();
+        for accrual in &self.fixed_accruals {
+            if accrual.payment_date > settle_date {
+                let amount = sign * self.fixed_rate * accrual.notional * accrual.fraction;
+                rows.push(Self::coupon_row(
+                    accrual,
+                    None,
+                    RateSource::Fixed,
+                    self.fixed_rate,
+                    amount,
+                    discount_curve.discount_factor(settle_date, accrual.payment_date)?,
+                ));
+            }
+        }
+        for accrual in &self.floating_accruals {
+            if accrual.payment_date > settle_date {
+                let (source, growth) =
+                    self.floating_growth(accrual, settle_date, projection_curve, store)?;
+                rows.push(Self::coupon_row(
+                    accrual,
+                    accrual.fixings.first().map(|fixing| fixing.fixing_date),
+                    source,
+                    accrual.floating_rate(growth),
+                    -sign * accrual.notional * accrual.floating_interest(growth),
+                    discount_curve.discount_factor(settle_date, accrual.payment_date)?,
+                ));
+            }
+        }
+        Ok(CouponReport::new(rows))
+    }
+
+    fn coupon_row(
+        accrual: &Accrual<V>,
+        fixing_date: Option<Date>,
+        source: RateSource,
+        rate: V,
+        amount: V,
+        discount_factor: V,
+    ) -> CouponRow<V> {
+        CouponRow {
+            accrual_start: accrual.start,
+            accrual_end: accrual.end,
+            payment_date: accrual.payment_date,
+            fixing_date,
+            source,
+            rate,
+            spread: V::zero(),
+            accrual_fraction: accrual.fraction,
+            notional: accrual.notional,
+            amount,
+            discount_factor,
+            present_value: amount * discount_factor,
+        }
+    }
+
+    /// Returns the growth factor of a unit notional over the floating accrual, compounding the published fixings
+    /// before `settle_date` with the rate projected from the first fixing on or after it.
+    fn floating_growth<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        accrual: &Accrual<V>,
+        settle_date: Date,
+        projection_curve: &YieldCurve<D, I>,
+        store: &FixingStore<V>,
+    ) -> QLabResult<(RateSource, V)> {
+        let (Some(first), Some(last)) = (accrual.fixings.first(), accrual.fixings.last()) else {
+            return Ok((RateSource::Projected, V::one()));
+        };
+        let days = |start: Date, end: Date| {
+            V::from_i64(end - start).ok_or_else(|| CastNumberError(format!("{end}").into()))
+        };
+        let total_days = days(first.start, last.end)?;
+        let mut growth = V::one();
+        let mut projected_from = None;
+        for fixing in &accrual.fixings {
+            if fixing.fixing_date >= settle_date {
+                projected_from = Some(fixing.start);
+                break;
+            }
+            let rate = store
+                .fixing(&self.index, fixing.fixing_date)
+                .ok_or_else(|| {
+                    InvalidInput(
+                        format!(
+                            "no fixing of {} on {} for {}",
+                            self.index, fixing.fixing_date, self.swap_id
+                        )
+                        .into(),
+                    )
+                })?;
+            let fraction = accrual.adjusted_fraction * days(fixing.start, fixing.end)? / total_days;
+            growth *= V::one() + rate * fraction;
+        }
+        let source = match projected_from {
+            None => RateSource::Historical,
+            Some(start) => {
+                growth /= projection_curve.discount_factor(start, last.end)?;
+                if start == first.start {
+                    RateSource::Projected
+                } else {
+                    RateSource::Mixed
+                }
+            }
+        };
+        Ok((source, growth))
+    }
+
+    /// Returns the notional of each leg outstanding after `settle_date` with the date on which its rate is next reset,
+    /// signed as an asset for the receiver of the leg: the fixed leg reprices at the end of the swap and the
+    /// floating leg at the start of its next unfixed accrual.
+    #[must_use]
+    pub fn repricing_positions(&self, settle_date: Date) -> Vec<RepricingPosition<V>> {
+        let sign = self.fixed_sign();
+        let mut positions = Vec::new();
+        if let Some(accrual) = self
+            .fixed_accruals
+            .iter()
+            .find(|accrual| accrual.payment_date > settle_date)
+        {
+            positions.push(RepricingPosition {
+                repricing_date: self.end,
+                notional: sign * accrual.notional,
+            });
+        }
+        if let Some(accrual) = self
+            .floating_accruals
+            .iter()
+            .find(|accrual| accrual.payment_date > settle_date)
+        {
+            positions.push(RepricingPosition {
+                repricing_date: if accrual.start > settle_date {
+                    accrual.start
+                } else {
+                    accrual.end
+                },
+                notional: -sign * accrual.notional,
+            });
+        }
+        positions
+    }
+
+    /// Calculates the value of the notional exchanges of `leg` for its receiver, who pays the initial notional
+    /// on the start date and is repaid each reduction of the notional and the final notional on the payment dates,
+    /// counting the flows after `settle_date` only. An accreting notional is paid rather than repaid.
+    ///
+    /// # Errors
+    /// Returns an error if a discount factor calculation fails.
+    pub fn notional_exchange_value<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        leg: SwapLeg,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        self.exchange_flows(leg, settle_date).into_iter().try_fold(
+            V::zero(),
+            |acc, (date, amount)| {
+                Ok(acc + amount * discount_curve.discount_factor(settle_date, date)?)
+            },
+        )
+    }
+
+    /// Calculates the present value of the swap from the side of its direction, including the notional exchanges.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`Swap::floating_leg_value`].
+    pub fn present_value<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        let fixed = self.fixed_rate * self.annuity(settle_date, discount_curve)?;
+        let floating = self.floating_leg_value(settle_date, discount_curve, projection_curve)?;
+        Ok(self.fixed_sign()
+            * (fixed - floating + self.net_exchange_value(settle_date, discount_curve)?))
+    }
+
+    /// Calculates the value of the fixed leg exchanges less the floating leg exchanges,
+    /// which cancel out unless the legs amortize on different dates.
+    fn net_exchange_value<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        Ok(
+            self.notional_exchange_value(SwapLeg::Fixed, settle_date, discount_curve)?
+                - self.notional_exchange_value(SwapLeg::Floating, settle_date, discount_curve)?,
+        )
+    }
+
+    /// Calculates the fixed rate for which the present value is zero.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`Swap::floating_leg_value`].
+    pub fn fair_rate<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        Ok(
+            (self.floating_leg_value(settle_date, discount_curve, projection_curve)?
+                - self.net_exchange_value(settle_date, discount_curve)?)
+                / self.annuity(settle_date, discount_curve)?,
+        )
+    }
+
+    /// Calculates the basis point sensitivity (BPS) of the fixed leg, i.e. the change in present value
+    /// for a one basis point increase of the fixed rate.
+    ///
+    /// # Errors
+    /// Returns an error if a discount factor calculation fails.
+    pub fn bps<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<V> {
+        Ok(self.fixed_sign() * self.annuity(settle_date, discount_curve)? * basis_point())
+    }
+
+    /// Calculates the present value, the BPS, the DV01 and the gamma ladder with respect to each curve.
+    ///
+    /// The DV01 of a curve is the change in present value for a one basis point parallel increase of its spot yields.
+    /// The gamma of a pillar is the second difference of the present value for one basis point moves of the
+    /// spot yield of that pillar alone.
+    ///
+    /// # Errors
+    /// Returns an error if a bumped curve cannot be built or a valuation fails.
+    pub fn risk<D: DayCount, I: Interpolator<Value = V>>(
+        &self,
+        settle_date: Date,
+        discount_curve: &YieldCurve<D, I>,
+        projection_curve: &YieldCurve<D, I>,
+    ) -> QLabResult<SwapRisk<V>> {
+        let bp = basis_point::<V>();
+        let present_value = self.present_value(settle_date, discount_curve, projection_curve)?;
+        let parallel = |curve: &YieldCurve<D, I>| curve.try_shifted(|_, y| y + bp);
+        let gamma_ladder = |risk_curve: RiskCurve,
+                            curve: &YieldCurve<D, I>,
+                            bumped: &dyn Fn(&YieldCurve<D, I>) -> _| {
+            curve
+                .maturities()
+                .iter()
+                .zip(curve.pillars())
+                .map(|(&maturity, &(pillar, _))| {
+                    let up = curve.try_shifted(|t, y| if t == pillar { y + bp } else { y })?;
+                    let down = curve.try_shifted(|t, y| if t == pillar { y - bp } else { y })?;
+                    Ok((
+                        (risk_curve.clone(), maturity),
+                        bumped(&up)? - present_value - present_value + bumped(&down)?,
+                    ))
+                })
+                .collect::<QLabResult<Vec<_>>>()
+        };
+        let mut gamma: BTreeMap<_, _> = gamma_ladder(
+            RiskCurve::Discount(self.currency.clone()),
+            discount_curve,
+            &|curve| self.present_value(settle_date, curve, projection_curve),
+        )?
+        .into_iter()
+        .collect();
+        gamma.extend(gamma_ladder(
+            RiskCurve::Projection(self.index.clone()),
+            projection_curve,
+            &|curve| self.present_value(settle_date, discount_curve, curve),
+        )?);
+        let measures = RiskMeasures {
+            present_value,
+            bps: self.bps(settle_date, discount_curve)?,
+            discount_dv01: self.present_value(
+                settle_date,
+                &parallel(discount_curve)?,
+                projection_curve,
+            )? - present_value,
+            projection_dv01: self.present_value(
+                settle_date,
+                discount_curve,
+                &parallel(projection_curve)?,
+            )? - present_value,
+            gamma,
+        };
+        Ok(SwapRisk {
+            swap_id: self.swap_id.clone(),
+            currency: self.currency.clone(),
+            index: self.index.clone(),
+            measures,
+        })
+    }
+}
+
+fn basis_point<V: Value>() -> V {
+    V::from_f64(1e-4).unwrap()
+}
+
+/// The curve a gamma bucket is measured on.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RiskCurve {
+    /// The discount curve of a currency.
+    Discount(String),
+    /// The projection curve of a floating rate index.
+    Projection(String),
+}
+
+/// Risk measures which add up across instruments sharing the same curves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskMeasures<V> {
+    pub present_value: V,
+    pub bps: V,
+    pub discount_dv01: V,
+    pub projection_dv01: V,
+    /// The gamma per curve and pillar maturity.
+    pub gamma: BTreeMap<(RiskCurve, Date), V>,
+}
+
+impl<V: Value> RiskMeasures<V> {
+    /// Returns the gamma ladder of `curve` in order of pillar maturity.
+    #[must_use]
+    pub fn gamma_ladder(&self, curve: &RiskCurve) -> Vec<(Date, V)> {
+        self.gamma
+            .iter()
+            .filter(|((risk_curve, _), _)| risk_curve == curve)
+            .map(|(&(_, pillar), &gamma)| (pillar, gamma))
+            .collect()
+    }
+
+    /// Adds `other` to the measures, merging the gamma buckets by curve and pillar maturity.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a curve present in both measures has different pillars.
+    pub fn try_add(&mut self, other: &Self) -> QLabResult<()> {
+        let curves = |measures: &Self| {
+            measures
+                .gamma
+                .keys()
+                .map(|(curve, _)| curve.clone())
+                .collect::<BTreeSet<_>>()
+        };
+        for curve in curves(self).intersection(&curves(other)) {
+            let pillars = |measures: &Self| {
+                measures
+                    .gamma_ladder(curve)
+                    .into_iter()
+                    .map(|(pillar, _)| pillar)
+                    .collect::<Vec<_>>()
+            };
+            if pillars(self) != pillars(other) {
+                return Err(InvalidInput(
+                    format!("gamma ladders of {curve:?} are on different pillars").into(),
+                )
+                .into());
+            }
+        }
+        self.present_value += other.present_value;
+        self.bps += other.bps;
+        self.discount_dv01 += other.discount_dv01;
+        self.projection_dv01 += other.projection_dv01;
+        for (key, &gamma) in &other.gamma {
+            *self.gamma.entry(key.clone()).or_insert_with(V::zero) += gamma;
+        }
+        Ok(())
+    }
+}
+
+/// The risk measures of a single swap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapRisk<V> {
+    pub swap_id: String,
+    pub currency: String,
+    pub index: String,
+    pub measures: RiskMeasures<V>,
+}
+
+/// The attribute by which swap risk is rolled up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RollupKey {
+    Currency,
+    Index,
+}
+
+/// Sums the risk measures of swaps sharing the currency or index selected by `key`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if swaps in the same group have gamma ladders of a curve on different pillars.
+pub fn roll_up<V: Value>(
+    risks: &[SwapRisk<V>],
+    key: RollupKey,
+) -> QLabResult<BTreeMap<String, RiskMeasures<V>>> {
+    let mut totals: BTreeMap<String, RiskMeasures<V>> = BTreeMap::new();
+    for risk in risks {
+        let group = match key {
+            RollupKey::Currency => &risk.currency,
+            RollupKey::Index => &risk.index,
+        };
+        match totals.get_mut(group) {
+            Some(total) => total.try_add(&risk.measures)?,
+            None => {
+                totals.insert(group.clone(), risk.measures.clone());
+            }
+        }
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::day_count::thirty_360::Thirty360;
+
+    fn curve(settle_date: Date, short: f64, long: f64) -> YieldCurve<Act365, Linear<f64>> {
+        YieldCurve::new(
+            settle_date,
+            &[
+                Date::from_ymd(2024, 1, 3).unwrap(),
+                Date::from_ymd(2027, 1, 4).unwrap(),
+                Date::from_ymd(2035, 1, 3).unwrap(),
+            ],
+            &[short, f64::midpoint(short, long), long],
+        )
+        .unwrap()
+    }
+
+    fn swap(swap_id: &str, direction: SwapDirection, fixed_rate: f64, years: i32) -> Swap<f64> {
+        Swap::new::<Thirty360, Act360>(
+            swap_id,
+            "usd",
+            "SOFR",
+            direction,
+            Date::from_ymd(2024, 1, 4).unwrap(),
+            Date::from_ymd(2024 + years, 1, 4).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            fixed_rate,
+            1_000_000.0,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fair_rate_and_bps() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = curve(settle_date, 0.04, 0.045);
+        let projection = curve(settle_date, 0.042, 0.047);
+        let payer = swap("payer", SwapDirection::Payer, 0.04, 5);
+        let fair_rate = payer
+            .fair_rate(settle_date, &discount, &projection)
+            .unwrap();
+        let at_par = swap("par", SwapDirection::Payer, fair_rate, 5);
+        assert!(
+            at_par
+                .present_value(settle_date, &discount, &projection)
+                .unwrap()
+                .abs()
+                < 1e-6
+        );
+
+        // paying the fixed leg below par is worth the rate difference times the annuity
+        let pv = payer
+            .present_value(settle_date, &discount, &projection)
+            .unwrap();
+        let bps = payer.bps(settle_date, &discount).unwrap();
+        assert!(bps < 0.0);
+        assert!((pv - (fair_rate - 0.04) / 1e-4 * -bps).abs() < 1e-6);
+        let receiver = swap("receiver", SwapDirection::Receiver, 0.04, 5);
+        assert!(
+            (receiver
+                .present_value(settle_date, &discount, &projection)
+                .unwrap()
+                + pv)
+                .abs()
+                < 1e-9
+        );
+        // forward starting coupons need no fixing, but a settlement after the start does
+        let later = Date::from_ymd(2024, 2, 1).unwrap();
+        assert!(payer
+            .present_value(
+                later,
+                &curve(later, 0.04, 0.045),
+                &curve(later, 0.04, 0.045)
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_fixings() {
+        let settle_date = Date::from_ymd(2024, 1, 4).unwrap();
+        let discount = curve(settle_date, 0.04, 0.045);
+        let term = swap("term", SwapDirection::Payer, 0.04, 1);
+        assert_eq!(term.floating_fixings()[0].1[0].fixing_date, settle_date);
+        let overnight = term
+            .clone()
+            .with_fixings(
+                FixingConvention::Overnight {
+                    lookback: 0,
+                    observation_shift: false,
+                },
+                &WeekendsOnly,
+            )
+            .unwrap();
+        // compounding daily fixings in arrears projects the same coupons as a term rate
+        let term_value = term
+            .floating_leg_value(settle_date, &discount, &discount)
+            .unwrap();
+        let overnight_value = overnight
+            .floating_leg_value(settle_date, &discount, &discount)
+            .unwrap();
+        assert!((term_value - overnight_value).abs() < 1e-9);
+        let (payment_date, fixings) = overnight.floating_fixings()[0];
+        assert_eq!(payment_date, Date::from_ymd(2024, 4, 4).unwrap());
+        assert_eq!(fixings.len(), 65);
+
+        // a lookback moves the first fixing before the settlement of the curve
+        let lookback = term
+            .with_fixings(
+                FixingConvention::Overnight {
+                    lookback: 2,
+                    observation_shift: true,
+                },
+                &WeekendsOnly,
+            )
+            .unwrap();
+        assert!(lookback
+            .floating_leg_value(settle_date, &discount, &discount)
+            .is_err());
+    }
+
+    #[test]
+    fn test_unadjusted_accrual() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = curve(settle_date, 0.04, 0.045);
+        // from a Saturday to a Sunday, paid on the following Mondays
+        let adjusted = Swap::new::<Act360, Act360>(
+            "weekend",
+            "usd",
+            "SOFR",
+            SwapDirection::Payer,
+            Date::from_ymd(2024, 3, 16).unwrap(),
+            Date::from_ymd(2025, 3, 16).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::Following,
+            0.04,
+            1_000_000.0,
+        )
+        .unwrap();
+        assert!(adjusted.adjusted_accrual());
+        let unadjusted = adjusted.clone().with_adjusted_accrual(false);
+        let ratio = unadjusted.annuity(settle_date, &discount).unwrap()
+            / adjusted.annuity(settle_date, &discount).unwrap();
+        assert!((ratio - 365.0 / 364.0).abs() < 1e-12);
+        // the floating leg accrues the same projected rates over the unadjusted periods too, the first one
+        // from Saturday 2024-03-16 rather than Monday 2024-03-18
+        let floating = |swap: &Swap<f64>| {
+            swap.floating_leg_value(settle_date, &discount, &discount)
+                .unwrap()
+        };
+        assert!(floating(&unadjusted) > floating(&adjusted));
+        let coupons = |swap: &Swap<f64>| {
+            swap.coupon_report(settle_date, &discount, &discount, &FixingStore::new())
+                .unwrap()
+                .rows()
+                .iter()
+                .filter(|row| row.source != RateSource::Fixed)
+                .map(|row| (row.rate, row.amount))
+                .collect::<Vec<_>>()
+        };
+        let fractions: Vec<_> = adjusted
+            .floating_accruals
+            .iter()
+            .zip(&unadjusted.floating_accruals)
+            .map(|(adjusted, unadjusted)| {
+                (adjusted.adjusted_fraction, unadjusted.unadjusted_fraction)
+            })
+            .collect();
+        assert!(fractions
+            .iter()
+            .any(|(adjusted, unadjusted)| (adjusted - unadjusted).abs() > 1e-6));
+        for ((adjusted, unadjusted), (adjusted_fraction, unadjusted_fraction)) in coupons(&adjusted)
+            .into_iter()
+            .zip(coupons(&unadjusted))
+            .zip(fractions)
+        {
+            assert!((adjusted.0 - unadjusted.0).abs() < 1e-15);
+            assert!(
+                (adjusted.1 / adjusted_fraction - unadjusted.1 / unadjusted_fraction).abs() < 1e-6
+            );
+        }
+        let payment_dates = |swap: &Swap<f64>| -> Vec<Date> {
+            swap.cash_flows(settle_date, &discount)
+                .unwrap()
+                .into_iter()
+                .map(|(date, _)| date)
+                .collect()
+        };
+        assert_eq!(payment_dates(&adjusted), payment_dates(&unadjusted));
+        assert_eq!(unadjusted.with_adjusted_accrual(true), adjusted);
+    }
+
+    #[test]
+    fn test_forward_starting_and_amortizing() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = curve(settle_date, 0.04, 0.045);
+        let forward = Swap::new::<Thirty360, Act360>(
+            "forward",
+            "usd",
+            "SOFR",
+            SwapDirection::Payer,
+            Date::from_ymd(2025, 1, 6).unwrap(),
+            Date::from_ymd(2028, 1, 6).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.04,
+            1_000_000.0,
+        )
+        .unwrap();
+        assert!(forward.start_date() > settle_date);
+        // a forward-starting swap at its fair rate is worth nothing today
+        let fair_rate = forward
+            .fair_rate(settle_date, &discount, &discount)
+            .unwrap();
+        let at_fair_rate = Swap::new::<Thirty360, Act360>(
+            "forward",
+            "usd",
+            "SOFR",
+            SwapDirection::Payer,
+            forward.start_date(),
+            forward.end_date(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            fair_rate,
+            1_000_000.0,
+        )
+        .unwrap();
+        assert!(
+            at_fair_rate
+                .present_value(settle_date, &discount, &discount)
+                .unwrap()
+                .abs()
+                < 1e-6
+        );
+        let spot = swap("spot", SwapDirection::Payer, 0.04, 4);
+        assert!(
+            (fair_rate - spot.fair_rate(settle_date, &discount, &discount).unwrap()).abs() > 1e-5
+        );
+
+        // amortizing by a third each year
+        let amortizing = forward
+            .clone()
+            .with_notional_schedule(&[
+                (Date::from_ymd(2026, 1, 6).unwrap(), 666_666.0),
+                (Date::from_ymd(2027, 1, 6).unwrap(), 333_333.0),
+            ])
+            .unwrap();
+        let ratio = amortizing.annuity(settle_date, &discount).unwrap()
+            / forward.annuity(settle_date, &discount).unwrap();
+        assert!(ratio > 0.6 && ratio < 0.7);
+        assert!((amortizing.initial_notional() - 1_000_000.0).abs() < 1e-9);
+        assert!((amortizing.notional_on(settle_date) - 1_000_000.0).abs() < 1e-9);
+        let step = Date::from_ymd(2026, 1, 6).unwrap();
+        assert!((amortizing.notional_on(step) - 666_666.0).abs() < 1e-9);
+        assert!((amortizing.notional_on(forward.end_date()) - 333_333.0).abs() < 1e-9);
+        assert!(forward
+            .clone()
+            .with_notional_schedule(&[
+                (Date::from_ymd(2027, 1, 6).unwrap(), 1.0),
+                (Date::from_ymd(2026, 1, 6).unwrap(), 2.0),
+            ])
+            .is_err());
+
+        // a floating leg exchanging its notional on a single curve is worth par at the start
+        let exchanged = amortizing.with_exchanges(true, true);
+        let floating = exchanged
+            .floating_leg_value(settle_date, &discount, &discount)
+            .unwrap()
+            + exchanged
+                .notional_exchange_value(SwapLeg::Floating, settle_date, &discount)
+                .unwrap();
+        assert!(floating.abs() < 1e-6);
+        // the exchanges of both legs offset each other
+        let pv = exchanged
+            .present_value(settle_date, &discount, &discount)
+            .unwrap();
+        let without = exchanged
+            .clone()
+            .with_exchanges(false, false)
+            .present_value(settle_date, &discount, &discount)
+            .unwrap();
+        assert!((pv - without).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_risk_and_roll_up() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = curve(settle_date, 0.04, 0.045);
+        let projection = curve(settle_date, 0.042, 0.047);
+        let payer = swap("payer", SwapDirection::Payer, 0.04, 5)
+            .risk(settle_date, &discount, &projection)
+            .unwrap();
+        let receiver = swap("receiver", SwapDirection::Receiver, 0.045, 10)
+            .risk(settle_date, &discount, &projection)
+            .unwrap();
+        // a payer gains when the projected rates rise
+        assert!(payer.measures.projection_dv01 > 0.0);
+        assert!(receiver.measures.projection_dv01 < 0.0);
+        let discount_ladder = payer
+            .measures
+            .gamma_ladder(&RiskCurve::Discount("USD".into()));
+        assert_eq!(discount_ladder.len(), 3);
+        assert_eq!(discount_ladder[2].0, Date::from_ymd(2035, 1, 3).unwrap());
+
+        let by_index = roll_up(&[payer.clone(), receiver.clone()], RollupKey::Index).unwrap();
+        let total = &by_index["SOFR"];
+        assert!(
+            (total.present_value - payer.measures.present_value - receiver.measures.present_value)
+                .abs()
+                < 1e-9
+        );
+        let bucket = (
+            RiskCurve::Projection("SOFR".into()),
+            Date::from_ymd(2027, 1, 4).unwrap(),
+        );
+        assert!(
+            (total.gamma[&bucket]
+                - payer.measures.gamma[&bucket]
+                - receiver.measures.gamma[&bucket])
+                .abs()
+                < 1e-9
+        );
+        let by_currency = roll_up(std::slice::from_ref(&payer), RollupKey::Currency).unwrap();
+        assert_eq!(by_currency.keys().collect::<Vec<_>>(), vec!["USD"]);
+
+        // ladders on different curves are kept apart
+        let mut euro = payer.clone();
+        euro.currency = "EUR".into();
+        euro.measures.gamma = euro
+            .measures
+            .gamma
+            .into_iter()
+            .map(|((curve, pillar), gamma)| match curve {
+                RiskCurve::Discount(_) => ((RiskCurve::Discount("EUR".into()), pillar), gamma),
+                projection @ RiskCurve::Projection(_) => ((projection, pillar), gamma),
+            })
+            .collect();
+        let total = &roll_up(&[payer.clone(), euro], RollupKey::Index).unwrap()["SOFR"];
+        assert_eq!(total.gamma.len(), 9);
+
+        // a curve with other pillars cannot be merged
+        let other = swap("other", SwapDirection::Payer, 0.04, 5)
+            .risk(
+                settle_date,
+                &discount,
+                &YieldCurve::new(
+                    settle_date,
+                    &[
+                        Date::from_ymd(2024, 1, 3).unwrap(),
+                        Date::from_ymd(2030, 1, 3).unwrap(),
+                        Date::from_ymd(2035, 1, 3).unwrap(),
+                    ],
+                    &[0.042, 0.045, 0.047],
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert!(roll_up(&[payer, other], RollupKey::Index).is_err());
+    }
+
+    #[test]
+    fn test_coupon_report() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount = curve(settle_date, 0.04, 0.045);
+        let projection = curve(settle_date, 0.042, 0.047);
+        let payer = swap("payer", SwapDirection::Payer, 0.04, 2);
+        let store = FixingStore::new();
+        let report = payer
+            .coupon_report(settle_date, &discount, &projection, &store)
+            .unwrap();
+        assert_eq!(report.rows().len(), 10);
+        assert!(report
+            .rows()
+            .windows(2)
+            .all(|pair| pair[0].payment_date <= pair[1].payment_date));
+        let pv = payer
+            .present_value(settle_date, &discount, &projection)
+            .unwrap();
+        assert!((report.present_value() - pv).abs() < 1e-9);
+        let fixed = report
+            .rows()
+            .iter()
+            .find(|row| row.source == RateSource::Fixed)
+            .unwrap();
+        assert!(fixed.amount < 0.0 && fixed.fixing_date.is_none());
+        assert_eq!(report.table().unwrap().rows().len(), 10);
+
+        // after the start, the current coupon uses the published fixing
+        let later = Date::from_ymd(2024, 2, 1).unwrap();
+        let (discount, projection) = (curve(later, 0.04, 0.045), curve(later, 0.042, 0.047));
+        assert!(payer
+            .coupon_report(later, &discount, &projection, &store)
+            .is_err());
+        let store = store.with_fixing("SOFR", Date::from_ymd(2024, 1, 4).unwrap(), 0.053);
+        let report = payer
+            .coupon_report(later, &discount, &projection, &store)
+            .unwrap();
+        let current = report
+            .rows()
+            .iter()
+            .find(|row| row.source == RateSource::Historical)
+            .unwrap();
+        assert!((current.rate - 0.053).abs() < 1e-15);
+        assert!((current.amount - 1e6 * 0.053 * 91.0 / 360.0).abs() < 1e-9);
+
+        // an overnight coupon compounds the published fixings with the projected rest of the period
+        let overnight = payer
+            .with_fixings(
+                FixingConvention::Overnight {
+                    lookback: 0,
+                    observation_shift: false,
+                },
+                &WeekendsOnly,
+            )
+            .unwrap();
+        let mut store = FixingStore::new();
+        for (_, fixings) in overnight.floating_fixings() {
+            for fixing in fixings.iter().filter(|fixing| fixing.fixing_date < later) {
+                store.insert("SOFR", fixing.fixing_date, 0.053);
+            }
+        }
+        let report = overnight
+            .coupon_report(later, &discount, &projection, &store)
+            .unwrap();
+        let current = report
+            .rows()
+            .iter()
+            .find(|row| row.source == RateSource::Mixed)
+            .unwrap();
+        assert!(current.rate > 0.042 && current.rate < 0.053);
+    }
+}
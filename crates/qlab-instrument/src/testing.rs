@@ -0,0 +1,61 @@
+//! Deterministic instruments for tests and examples, re-exported by `qlab::testing`.
+
+use crate::bond::Bond;
+use calendar::weekendsonly::WeekendsOnly;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::date_rolling::DateRolling;
+use qlab_time::frequency::Frequency;
+use qlab_time::period::months::Months;
+
+/// Creates a semi-annual bond issued on 2023-05-08 with its first coupon on 2023-11-07,
+/// maturing on `maturity_date` and paid on the following weekday.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `maturity_date` is less than six months after the first coupon date
+/// or the bond cannot be built.
+///
+/// # Panics
+/// Will not panic, since the issue and first coupon dates are valid.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_instrument::testing::semi_annual_bond;
+/// use qlab_time::date::Date;
+///
+/// let bond = semi_annual_bond("5 yr bond", Date::from_ymd(2028, 11, 7).unwrap(), 0.05, 100.0).unwrap();
+/// assert_eq!(bond.cash_flows().len(), 11);
+/// assert!(semi_annual_bond("short", Date::from_ymd(2024, 1, 7).unwrap(), 0.05, 100.0).is_err());
+/// ```
+pub fn semi_annual_bond<V: Value>(
+    bond_id: &str,
+    maturity_date: Date,
+    coupon_rate: V,
+    face_value: V,
+) -> QLabResult<Bond<V>> {
+    let first_coupon_date = Date::from_ymd(2023, 11, 7).unwrap();
+    let penultimate_coupon_date = maturity_date
+        .checked_sub_months(Months::new(6))
+        .filter(|&date| first_coupon_date <= date)
+        .ok_or_else(|| {
+            InvalidInput(
+                format!("a bond maturing on {maturity_date} has no regular coupon period").into(),
+            )
+        })?;
+    Bond::new(
+        bond_id,
+        Date::from_ymd(2023, 5, 8).unwrap(),
+        first_coupon_date,
+        penultimate_coupon_date,
+        maturity_date,
+        Frequency::SA,
+        &WeekendsOnly,
+        DateRolling::Following,
+        coupon_rate,
+        face_value,
+    )
+    .ok_or_else(|| InvalidInput(format!("{bond_id} cannot be built").into()).into())
+}
@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use qlab_math::interpolation::linear::Linear;
+use qlab_math::interpolation::spline::natural_cubic::NaturalCubic;
+use qlab_math::interpolation::Interpolator;
+
+fn curve_points() -> Vec<(f64, f64)> {
+    (0..40)
+        .map(|i| {
+            let t = f64::from(i) * 0.75 + 0.1;
+            (t, 0.02 + 0.01 * (1.0 - (-t / 5.0).exp()))
+        })
+        .collect()
+}
+
+fn evaluation_grid() -> Vec<f64> {
+    (0..1000).map(|i| 0.1 + f64::from(i) * 0.029).collect()
+}
+
+fn bench_interpolator<I: Interpolator<Value = f64>>(c: &mut Criterion, name: &str) {
+    let points = curve_points();
+    let xs = evaluation_grid();
+    c.bench_function(&format!("{name}/fit"), |b| {
+        b.iter(|| I::default().try_fit(black_box(&points)).unwrap());
+    });
+    let interpolator = I::default().try_fit(&points).unwrap();
+    c.bench_function(&format!("{name}/value"), |b| {
+        b.iter(|| {
+            xs.iter()
+                .map(|&x| interpolator.try_value(black_box(x)).unwrap())
+                .sum::<f64>()
+        });
+    });
+}
+
+fn interpolation(c: &mut Criterion) {
+    bench_interpolator::<Linear<f64>>(c, "linear");
+    bench_interpolator::<NaturalCubic<f64>>(c, "natural_cubic");
+}
+
+criterion_group!(benches, interpolation);
+criterion_main!(benches);
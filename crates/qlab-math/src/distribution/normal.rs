@@ -0,0 +1,173 @@
+use crate::value::Value;
+
+const SQRT_2PI: f64 = 2.506_628_274_631_000_5;
+
+fn constant<V: Value>(x: f64) -> V {
+    V::from_f64(x).unwrap()
+}
+
+fn polynomial<V: Value>(coefficients: &[f64], x: V) -> V {
+    coefficients
+        .iter()
+        .fold(V::zero(), |acc, &c| acc * x + constant(c))
+}
+
+/// Calculates the probability density function of the standard normal distribution.
+///
+/// # Panics
+/// Will panic if `V` fails to cast constants.
+#[must_use]
+pub fn pdf<V: Value>(x: V) -> V {
+    (-x * x / constant(2.0)).exp() / constant(SQRT_2PI)
+}
+
+/// Calculates the cumulative distribution function of the standard normal distribution.
+///
+/// Uses the algorithm of Hart (1968) as presented by West (2005), which is accurate to double precision.
+///
+/// # Panics
+/// Will panic if `V` fails to cast constants.
+#[must_use]
+pub fn cdf<V: Value>(x: V) -> V {
+    let x_abs = x.abs();
+    let tail = if x_abs > constant(37.0) {
+        V::zero()
+    } else {
+        let exponential = (-x_abs * x_abs / constant(2.0)).exp();
+        if x_abs < constant(7.071_067_811_865_47) {
+            exponential
+                * polynomial(
+                    &[
+                        3.526_249_659_989_11E-2,
+                        0.700_383_064_443_688,
+                        6.373_962_203_531_65,
+                        33.912_866_078_383,
+                        112.079_291_497_871,
+                        221.213_596_169_931,
+                        220.206_867_912_376,
+                    ],
+                    x_abs,
+                )
+                / polynomial(
+                    &[
+                        8.838_834_764_831_84E-2,
+                        1.755_667_163_182_64,
+                        16.064_177_579_207,
+                        86.780_732_202_946_1,
+                        296.564_248_779_674,
+                        637.333_633_378_831,
+                        793.826_512_519_948,
+                        440.413_735_824_752,
+                    ],
+                    x_abs,
+                )
+        } else {
+            let mut build = x_abs + constant(0.65);
+            for k in [4.0, 3.0, 2.0, 1.0] {
+                build = x_abs + constant::<V>(k) / build;
+            }
+            exponential / build / constant(SQRT_2PI)
+        }
+    };
+    if x > V::zero() {
+        V::one() - tail
+    } else {
+        tail
+    }
+}
+
+/// Calculates the inverse of the cumulative distribution function of the standard normal distribution.
+///
+/// Uses the rational approximation of Acklam refined by a single Halley step.
+///
+/// # Returns
+///
+/// The quantile of `p`, which is negative infinity for `p <= 0` and positive infinity for `p >= 1`.
+///
+/// # Panics
+/// Will panic if `V` fails to cast constants.
+#[must_use]
+pub fn inverse_cdf<V: Value>(p: V) -> V {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 6] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+        1.0,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 5] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+        1.0,
+    ];
+    const P_LOW: f64 = 0.024_25;
+
+    if p <= V::zero() {
+        return constant(f64::NEG_INFINITY);
+    }
+    if p >= V::one() {
+        return constant(f64::INFINITY);
+    }
+    let x = if p < constant(P_LOW) {
+        let q = (constant::<V>(-2.0) * p.ln()).sqrt();
+        polynomial(&C, q) / polynomial(&D, q)
+    } else if p <= constant(1.0 - P_LOW) {
+        let q = p - constant(0.5);
+        let r = q * q;
+        polynomial(&A, r) * q / polynomial(&B, r)
+    } else {
+        let q = (constant::<V>(-2.0) * (V::one() - p).ln()).sqrt();
+        -polynomial(&C, q) / polynomial(&D, q)
+    };
+    let e = cdf(x) - p;
+    let u = e * constant(SQRT_2PI) * (x * x / constant(2.0)).exp();
+    x - u / (V::one() + x * u / constant(2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cdf() {
+        assert!((cdf(0.0_f64) - 0.5).abs() < f64::EPSILON);
+        assert!((cdf(1.0_f64) - 0.841_344_746_068_542_9).abs() < 1e-14);
+        assert!((cdf(-1.959_963_984_540_054_f64) - 0.025).abs() < 1e-14);
+        assert!((cdf(-8.0_f64) - 6.220_960_574_271_785e-16).abs() < 1e-22);
+        assert!(cdf(-40.0_f64).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_pdf() {
+        assert!((pdf(0.0_f64) - 0.398_942_280_401_432_7).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_inverse_cdf() {
+        for p in [1e-10_f64, 0.001, 0.024, 0.3, 0.5, 0.7, 0.99, 1.0 - 1e-10] {
+            let x = inverse_cdf(p);
+            assert!((cdf(x) - p).abs() / p < 1e-12, "p: {p}");
+        }
+        assert!((inverse_cdf(0.975_f64) - 1.959_963_984_540_054).abs() < 1e-12);
+        assert!(inverse_cdf(0.0_f64).is_infinite());
+    }
+}
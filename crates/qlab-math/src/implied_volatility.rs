@@ -0,0 +1,259 @@
+use crate::distribution::normal;
+use crate::value::Value;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+const MAX_ITERATIONS: usize = 200;
+
+fn two<V: Value>() -> V {
+    V::one() + V::one()
+}
+
+fn pi<V: Value>() -> V {
+    V::from_f64(std::f64::consts::PI).unwrap()
+}
+
+fn sqrt_tau<V: Value>() -> V {
+    V::from_f64(std::f64::consts::TAU).unwrap().sqrt()
+}
+
+/// Calculates the undiscounted Black price of the out-of-the-money option, i.e. the call if `strike >= forward`
+/// and the put otherwise, for the total standard deviation `std_dev` (the volatility times the square root of time).
+///
+/// By put-call parity this is also the time value of the in-the-money option.
+#[must_use]
+pub fn black_otm_price<V: Value>(forward: V, strike: V, std_dev: V) -> V {
+    if std_dev <= V::zero() {
+        return V::zero();
+    }
+    let d1 = (forward / strike).ln() / std_dev + std_dev / two();
+    let d2 = d1 - std_dev;
+    let price = if strike >= forward {
+        forward * normal::cdf(d1) - strike * normal::cdf(d2)
+    } else {
+        strike * normal::cdf(-d2) - forward * normal::cdf(-d1)
+    };
+    price.max(V::zero())
+}
+
+/// Calculates the total standard deviation for which [`black_otm_price`] reproduces `otm_price`.
+///
+/// The search starts from the Corrado-Miller rational approximation, falling back to the inflection point
+/// `sqrt(2 |ln(forward / strike)|)` of the price where the approximation breaks down far from the money.
+/// Newton steps are then taken on the logarithm of the price, which keeps them well scaled for the tiny prices
+/// of extreme strikes, and any step leaving the bracket of the root is replaced by bisection so that the
+/// iteration converges for every admissible price.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the forward or strike are not positive, or if `otm_price` lies outside
+/// `[0, min(forward, strike))`.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::implied_volatility::{black_otm_price, black_std_dev};
+///
+/// let price = black_otm_price(100.0, 400.0, 0.3);
+/// let std_dev = black_std_dev(100.0, 400.0, price).unwrap();
+/// assert!((std_dev - 0.3_f64).abs() < 1e-10);
+/// ```
+pub fn black_std_dev<V: Value>(forward: V, strike: V, otm_price: V) -> QLabResult<V> {
+    if forward <= V::zero() || strike <= V::zero() {
+        return Err(InvalidInput(
+            format!("forward: {forward:?} and strike: {strike:?} must be positive").into(),
+        )
+        .into());
+    }
+    let upper_bound = forward.min(strike);
+    if otm_price < V::zero() || otm_price >= upper_bound {
+        return Err(InvalidInput(
+            format!("price: {otm_price:?} must lie in [0, {upper_bound:?})").into(),
+        )
+        .into());
+    }
+    if otm_price == V::zero() {
+        return Ok(V::zero());
+    }
+    // Corrado-Miller on the call price
+    let call = otm_price + (forward - strike).max(V::zero());
+    let half_intrinsic = (forward - strike) / two();
+    let excess = call - half_intrinsic;
+    let discriminant =
+        (excess * excess - (forward - strike) * (forward - strike) / pi::<V>()).max(V::zero());
+    let corrado_miller = sqrt_tau::<V>() / (forward + strike) * (excess + discriminant.sqrt());
+    let inflection = (two::<V>() * (forward / strike).ln().abs()).sqrt();
+    let initial_guess = if corrado_miller > V::zero() && corrado_miller < V::max_value() {
+        corrado_miller
+    } else {
+        inflection
+    };
+    solve(
+        |std_dev| {
+            let d1 = (forward / strike).ln() / std_dev + std_dev / two();
+            (
+                black_otm_price(forward, strike, std_dev),
+                forward * normal::pdf(d1),
+            )
+        },
+        otm_price,
+        initial_guess,
+    )
+}
+
+/// Calculates the undiscounted Bachelier price of the out-of-the-money option, i.e. the call if `strike >= forward`
+/// and the put otherwise, for the total normal standard deviation `std_dev`.
+///
+/// By put-call parity this is also the time value of the in-the-money option.
+#[must_use]
+pub fn bachelier_otm_price<V: Value>(forward: V, strike: V, std_dev: V) -> V {
+    if std_dev <= V::zero() {
+        return V::zero();
+    }
+    let moneyness = -(forward - strike).abs();
+    let d = moneyness / std_dev;
+    (moneyness * normal::cdf(d) + std_dev * normal::pdf(d)).max(V::zero())
+}
+
+/// Calculates the total normal standard deviation for which [`bachelier_otm_price`] reproduces `otm_price`.
+///
+/// The search starts from the larger of the at-the-money solution and the leading term of the asymptotic expansion
+/// far from the money, and proceeds as in [`black_std_dev`].
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `otm_price` is negative.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::implied_volatility::{bachelier_otm_price, bachelier_std_dev};
+///
+/// let price = bachelier_otm_price(0.01, 0.05, 0.008);
+/// let std_dev = bachelier_std_dev(0.01, 0.05, price).unwrap();
+/// assert!((std_dev - 0.008_f64).abs() < 1e-12);
+/// ```
+pub fn bachelier_std_dev<V: Value>(forward: V, strike: V, otm_price: V) -> QLabResult<V> {
+    if otm_price < V::zero() {
+        return Err(
+            InvalidInput(format!("price: {otm_price:?} must not be negative").into()).into(),
+        );
+    }
+    if otm_price == V::zero() {
+        return Ok(V::zero());
+    }
+    let distance = (forward - strike).abs();
+    let at_the_money = otm_price * sqrt_tau::<V>();
+    // far from the money the price behaves like std_dev * pdf(distance / std_dev) up to a power of std_dev
+    let asymptotic = if distance > at_the_money {
+        distance / (two::<V>() * (distance / at_the_money).ln()).sqrt()
+    } else {
+        V::zero()
+    };
+    solve(
+        |std_dev| {
+            (
+                bachelier_otm_price(forward, strike, std_dev),
+                normal::pdf(distance / std_dev),
+            )
+        },
+        otm_price,
+        at_the_money.max(asymptotic),
+    )
+}
+
+/// Solves `price(std_dev) = target` for a positive `target` and a price which is increasing in `std_dev`.
+///
+/// `price` returns the price and its derivative with respect to `std_dev`.
+/// Newton steps on `ln(price)` are taken from `initial_guess` and fall back to bisection whenever they leave the bracket.
+fn solve<V: Value>(price: impl Fn(V) -> (V, V), target: V, initial_guess: V) -> QLabResult<V> {
+    let tolerance = V::epsilon().sqrt() * V::epsilon().sqrt().sqrt();
+    let mut lower = V::zero();
+    let mut upper = initial_guess.max(V::epsilon());
+    let mut expansions = 0;
+    while price(upper).0 < target {
+        lower = upper;
+        upper *= two();
+        expansions += 1;
+        if expansions > MAX_ITERATIONS {
+            return Err(InvalidInput(format!("no volatility reprices {target:?}").into()).into());
+        }
+    }
+    let mut std_dev = initial_guess.max(lower).min(upper);
+    for _ in 0..MAX_ITERATIONS {
+        let (value, derivative) = price(std_dev);
+        if (value - target).abs() <= tolerance * target {
+            return Ok(std_dev);
+        }
+        if value > target {
+            upper = std_dev;
+        } else {
+            lower = std_dev;
+        }
+        let newton = std_dev - (value / target).ln() * value / derivative;
+        std_dev = if value > V::zero() && derivative > V::zero() && newton > lower && newton < upper
+        {
+            newton
+        } else {
+            (lower + upper) / two()
+        };
+        if upper - lower <= V::epsilon() * upper {
+            return Ok(std_dev);
+        }
+    }
+    Err(InvalidInput(format!("implied volatility of {target:?} did not converge").into()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_black_extreme_moneyness() {
+        for ratio in [1e-4, 0.01, 0.5, 1.0, 2.0, 100.0, 1e4] {
+            for std_dev in [0.001, 0.05, 0.3, 1.0, 4.0, 10.0] {
+                let strike = 100.0 * ratio;
+                let price = black_otm_price(100.0, strike, std_dev);
+                // prices indistinguishable from zero or from the upper bound carry no information
+                if price < f64::MIN_POSITIVE || price >= 100.0_f64.min(strike) * (1.0 - 1e-12) {
+                    continue;
+                }
+                let implied = black_std_dev(100.0, strike, price).unwrap();
+                let repriced = black_otm_price(100.0, strike, implied);
+                assert!(
+                    (repriced - price).abs() <= 1e-10 * price,
+                    "{ratio} {std_dev}: {implied}"
+                );
+                if price > 1e-200 {
+                    assert!(
+                        (implied - std_dev).abs() <= 1e-6 * std_dev,
+                        "{ratio} {std_dev}: {implied}"
+                    );
+                }
+            }
+        }
+        assert!(black_std_dev(100.0, 120.0, 0.0_f64).unwrap().abs() < f64::EPSILON);
+        assert!(black_std_dev(100.0, 120.0, 100.0).is_err());
+        assert!(black_std_dev(100.0, 120.0, -1.0).is_err());
+        assert!(black_std_dev(-100.0, 120.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_bachelier_extreme_moneyness() {
+        for distance in [0.0, 0.001, 0.01, 0.1, 1.0] {
+            for std_dev in [0.0001, 0.001, 0.01, 0.1] {
+                let price = bachelier_otm_price(0.0, distance, std_dev);
+                if price < f64::MIN_POSITIVE {
+                    continue;
+                }
+                let implied = bachelier_std_dev(0.0, distance, price).unwrap();
+                assert!(
+                    (implied - std_dev).abs() <= 1e-8 * std_dev,
+                    "{distance} {std_dev}: {implied}"
+                );
+                // symmetric in the side of the strike
+                let put = bachelier_std_dev(distance, 0.0, price).unwrap();
+                assert!((put - implied).abs() <= 1e-12 * std_dev);
+            }
+        }
+        assert!(bachelier_std_dev(0.0, 0.01, -1e-4).is_err());
+    }
+}
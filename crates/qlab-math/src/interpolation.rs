@@ -3,17 +3,21 @@ use num_traits::Zero;
 use qlab_error::InterpolationError;
 
 pub mod linear;
+pub mod piecewise_constant;
 pub mod spline;
+pub mod validation;
 
 trait X<V> {
     fn x(&self) -> &V;
 }
 
+#[derive(Clone)]
 struct Point2D<V> {
     x: V,
     y: V,
 }
 
+#[derive(Clone)]
 struct Point2DWithSlope<V> {
     coordinate: Point2D<V>,
     dydx: V,
@@ -74,6 +78,13 @@ pub trait Interpolator: Default {
     ///
     /// An Error returns if interpolation fails.
     fn try_value(&self, t: Self::Value) -> Result<Self::Value, InterpolationError<Self::Value>>;
+
+    /// Returns an interpolator configured as `self` but without fitted points, so that refitting on new points
+    /// keeps any settings chosen at construction.
+    #[must_use]
+    fn unfitted(&self) -> Self {
+        Self::default()
+    }
 }
 
 fn find_index_at_left_boundary<V: PartialOrd>(
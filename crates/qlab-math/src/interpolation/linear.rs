@@ -20,9 +20,10 @@ use qlab_error::InterpolationError;
 /// assert_eq!(linear.try_value(2.0).unwrap(), 3.0);
 ///
 /// ```
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Linear<V> {
     points: Vec<Point2D<V>>,
+    slopes: Vec<V>,
 }
 
 impl<V: Real> Linear<V> {
@@ -41,6 +42,7 @@ impl<V: Real> Linear<V> {
     pub fn new() -> Self {
         Self {
             points: Vec::default(),
+            slopes: Vec::default(),
         }
     }
 }
@@ -52,6 +54,11 @@ impl<V: Value> Interpolator for Linear<V> {
         for &(x, y) in raw_points {
             points.push(Point2D { x, y });
         }
+        // slopes are precomputed so that evaluation costs one search and one multiply-add
+        self.slopes = points
+            .windows(2)
+            .map(|pair| (pair[1].y - pair[0].y) / (pair[1].x - pair[0].x))
+            .collect();
         self.points = points;
         Ok(self)
     }
@@ -75,9 +82,6 @@ impl<V: Value> Interpolator for Linear<V> {
     fn try_value(&self, x: V) -> Result<V, InterpolationError<V>> {
         let pos = find_index_at_left_boundary(&self.points, x)?;
 
-        Ok(self.points[pos].y
-            + (self.points[pos + 1].y - self.points[pos].y)
-                / (self.points[pos + 1].x - self.points[pos].x)
-                * (x - self.points[pos].x))
+        Ok(self.points[pos].y + self.slopes[pos] * (x - self.points[pos].x))
     }
 }
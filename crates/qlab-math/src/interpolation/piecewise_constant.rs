@@ -0,0 +1,114 @@
+use crate::interpolation::{find_index_at_left_boundary, Interpolator, Point2D};
+use crate::value::Value;
+use qlab_error::InterpolationError;
+
+/// A step function taking, between two consecutive points, the value of the right point.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::piecewise_constant::PiecewiseConstant;
+/// use qlab_math::interpolation::Interpolator;
+///
+/// let step = PiecewiseConstant::default().try_fit(&[(1.0, 2.0), (3.0, 4.0)]).unwrap();
+/// assert_eq!(step.try_value(1.0).unwrap(), 2.0);
+/// assert_eq!(step.try_value(2.0).unwrap(), 4.0);
+/// assert_eq!(step.try_value(3.0).unwrap(), 4.0);
+/// ```
+#[derive(Default, Clone)]
+pub struct PiecewiseConstant<V> {
+    points: Vec<Point2D<V>>,
+}
+
+impl<V: Value> Interpolator for PiecewiseConstant<V> {
+    type Value = V;
+
+    /// Fits the step function to `raw_points`, which must be sorted by their x-coordinates.
+    ///
+    /// # Errors
+    ///
+    /// * `InterpolationError::InsufficientPointsError(0)` - If `raw_points` is empty.
+    /// * `InterpolationError::PointOrderError` - If the x-coordinates are not in ascending order.
+    fn try_fit(mut self, raw_points: &[(V, V)]) -> Result<Self, InterpolationError<V>> {
+        if raw_points.is_empty() {
+            return Err(InterpolationError::InsufficientPointsError(0));
+        }
+        if raw_points.windows(2).any(|pair| pair[1].0 < pair[0].0) {
+            return Err(InterpolationError::PointOrderError);
+        }
+        self.points = raw_points.iter().map(|&(x, y)| Point2D { x, y }).collect();
+        Ok(self)
+    }
+
+    /// Returns the value of the first point whose x-coordinate is not smaller than `x`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OutOfLowerBound(x)` or `OutOfUpperBound(x)` if `x` lies outside the x-coordinates of the points.
+    fn try_value(&self, x: V) -> Result<V, InterpolationError<V>> {
+        if self.points.len() == 1 && self.points[0].x == x {
+            return Ok(self.points[0].y);
+        }
+        let pos = find_index_at_left_boundary(&self.points, x)?;
+        if x == self.points[pos].x {
+            Ok(self.points[pos].y)
+        } else {
+            Ok(self.points[pos + 1].y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let step = PiecewiseConstant::default()
+            .try_fit(&[(0.0_f64, 1.0), (1.0, 2.0)])
+            .unwrap();
+        assert!(step.try_value(-0.1).is_err());
+        assert!(step.try_value(1.1).is_err());
+        assert!((step.try_value(0.5).unwrap() - 2.0).abs() < f64::EPSILON);
+        assert!(PiecewiseConstant::<f64>::default().try_fit(&[]).is_err());
+        assert!(PiecewiseConstant::default()
+            .try_fit(&[(1.0, 1.0), (0.0, 2.0)])
+            .is_err());
+    }
+
+    #[test]
+    fn test_knots_and_single_point() {
+        let step = PiecewiseConstant::default()
+            .try_fit(&[(0.0_f64, 1.0), (1.0, 2.0), (2.0, 3.0)])
+            .unwrap();
+        // each knot takes its own value, and the step to the right starts just after it
+        for (x, y) in [
+            (0.0, 1.0),
+            (1.0, 2.0),
+            (2.0, 3.0),
+            (1e-12, 2.0),
+            (1.0 + 1e-12, 3.0),
+        ] {
+            assert!((step.try_value(x).unwrap() - y).abs() < f64::EPSILON, "{x}");
+        }
+
+        // a single point is defined at its x-coordinate only
+        let single = PiecewiseConstant::default()
+            .try_fit(&[(1.0_f64, 5.0)])
+            .unwrap();
+        assert!((single.try_value(1.0).unwrap() - 5.0).abs() < f64::EPSILON);
+        assert!(matches!(
+            single.try_value(0.5),
+            Err(InterpolationError::OutOfLowerBound(_))
+        ));
+        assert!(matches!(
+            single.try_value(1.5),
+            Err(InterpolationError::OutOfUpperBound(_))
+        ));
+
+        // refitting replaces the points
+        let refitted = step.try_fit(&[(5.0, 0.5), (6.0, 0.25)]).unwrap();
+        assert!(refitted.try_value(1.0).is_err());
+        assert!((refitted.try_value(5.5).unwrap() - 0.25).abs() < f64::EPSILON);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::value::Value;
 
+pub mod akima;
 pub mod catmull_rom;
 pub mod hermite;
+pub mod hyman;
+pub mod monotone_cubic;
 pub mod natural_cubic;
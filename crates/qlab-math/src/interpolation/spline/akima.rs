@@ -0,0 +1,107 @@
+use crate::interpolation::spline::hermite::{Hermite, SlopeProvider};
+use crate::interpolation::spline::Value;
+use qlab_error::InterpolationError;
+
+/// Decides the knot slopes by the method of Akima, weighting the secants on both sides of each knot by
+/// the change of the secants on the other side, so that an outlier only bends the spline next to it.
+///
+/// Two secants are extrapolated linearly beyond each end, and the average of the neighbouring secants is
+/// used where both weights vanish.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AkimaSlopes;
+
+impl<V: Value> SlopeProvider<V> for AkimaSlopes {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        let n = xs_and_ys.len();
+        let secants = xs_and_ys
+            .windows(2)
+            .map(|pair| {
+                if pair[1].0 == pair[0].0 {
+                    return Err(InterpolationError::PointOrderError);
+                }
+                Ok((pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+            })
+            .collect::<Result<Vec<V>, _>>()?;
+        if n == 2 {
+            return Ok(vec![secants[0]; 2]);
+        }
+        let two = V::one() + V::one();
+        // the secants from two before the first point to two after the last one
+        let mut extended = Vec::with_capacity(n + 3);
+        let before = two * secants[0] - secants[1];
+        extended.push(two * before - secants[0]);
+        extended.push(before);
+        extended.extend_from_slice(&secants);
+        let after = two * secants[n - 2] - secants[n - 3];
+        extended.push(after);
+        extended.push(two * after - secants[n - 2]);
+        Ok(extended
+            .windows(4)
+            .map(|window| {
+                let left_weight = (window[3] - window[2]).abs();
+                let right_weight = (window[1] - window[0]).abs();
+                if left_weight + right_weight == V::zero() {
+                    (window[1] + window[2]) / two
+                } else {
+                    (left_weight * window[1] + right_weight * window[2])
+                        / (left_weight + right_weight)
+                }
+            })
+            .collect())
+    }
+}
+
+/// The Akima spline, a cubic Hermite spline with slopes by [`AkimaSlopes`], which is continuously differentiable
+/// and does not propagate the wiggles of an outlier across the curve.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::spline::akima::Akima;
+/// use qlab_math::interpolation::Interpolator;
+///
+/// let points = [(0.0, 0.2), (1.0, 0.2), (2.0, 0.2), (3.0, 0.35), (4.0, 0.2), (5.0, 0.2), (6.0, 0.2)];
+/// let spline: Akima<f64> = Akima::default().try_fit(&points).unwrap();
+/// // the outlier at 3 leaves the flat ends untouched
+/// assert!((spline.try_value(0.5).unwrap() - 0.2).abs() < 1e-15);
+/// assert!((spline.try_value(5.5).unwrap() - 0.2).abs() < 1e-15);
+/// ```
+pub type Akima<V> = Hermite<V, AkimaSlopes>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::Interpolator;
+
+    #[test]
+    fn test_akima() {
+        let line = [(0.0_f64, 1.0), (1.0, 3.0), (2.5, 6.0), (4.0, 9.0)];
+        let spline = Akima::default().try_fit(&line).unwrap();
+        for x in [0.0, 0.7, 1.9, 3.3, 4.0] {
+            assert!((spline.try_value(x).unwrap() - (1.0 + 2.0 * x)).abs() < 1e-14);
+        }
+
+        let step = [
+            (0.0_f64, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (3.0, 1.0),
+            (4.0, 1.0),
+            (5.0, 1.0),
+        ];
+        let spline = Akima::default().try_fit(&step).unwrap();
+        for (x, y) in [(0.5, 0.0), (1.5, 0.0), (3.5, 1.0), (4.5, 1.0)] {
+            assert!((spline.try_value(x).unwrap() - y).abs() < 1e-15);
+        }
+        let slopes = AkimaSlopes.slopes(&step).unwrap();
+        assert!(slopes.iter().all(|slope| slope.abs() < 1e-15));
+        assert!((spline.try_value(2.5).unwrap() - 0.5).abs() < 1e-15);
+
+        let pair = Akima::default().try_fit(&line[..2]).unwrap();
+        assert!((pair.try_value(0.5).unwrap() - 2.0).abs() < 1e-15);
+        assert!(Akima::default()
+            .try_fit(&[(0.0_f64, 0.0), (0.0, 1.0), (1.0, 2.0)])
+            .is_err());
+        assert!(spline.try_value(5.5).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use nalgebra::{Matrix4, Vector4};
 use qlab_error::InterpolationError;
 use std::ops::Mul;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct CatmullRom<V: Value> {
     points: Vec<Point2D<V>>,
 }
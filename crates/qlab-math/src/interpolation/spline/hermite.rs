@@ -1,12 +1,113 @@
 use crate::interpolation::spline::Value;
-use crate::interpolation::{find_index_at_left_boundary, Point2DWithSlope};
+use crate::interpolation::{find_index_at_left_boundary, Interpolator, Point2DWithSlope};
 use nalgebra::Matrix4;
 use nalgebra::Vector4;
 use qlab_error::InterpolationError;
 
-pub struct Hermite<V: Value> {
+/// A strategy deciding the slopes of a Hermite spline at its knots.
+pub trait SlopeProvider<V: Value>: Default {
+    /// Returns the slope at each of the sorted `xs_and_ys`.
+    ///
+    /// # Errors
+    /// Returns an error if the slopes cannot be determined for the given points.
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>>;
+}
+
+fn secant<V: Value>(left: (V, V), right: (V, V)) -> V {
+    (right.1 - left.1) / (right.0 - left.0)
+}
+
+/// Uses the average of the secants on both sides of each knot, and the one-sided secant at the ends.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FiniteDifference;
+
+impl<V: Value> SlopeProvider<V> for FiniteDifference {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        let n = xs_and_ys.len();
+        Ok((0..n)
+            .map(|i| {
+                if i == 0 {
+                    secant(xs_and_ys[0], xs_and_ys[1])
+                } else if i + 1 == n {
+                    secant(xs_and_ys[n - 2], xs_and_ys[n - 1])
+                } else {
+                    (secant(xs_and_ys[i - 1], xs_and_ys[i])
+                        + secant(xs_and_ys[i], xs_and_ys[i + 1]))
+                        / V::from_i8(2).unwrap()
+                }
+            })
+            .collect())
+    }
+}
+
+/// Uses the secant between the neighbours of each knot as in Catmull-Rom splines,
+/// and the one-sided secant at the ends.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CatmullRomSlopes;
+
+impl<V: Value> SlopeProvider<V> for CatmullRomSlopes {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        let n = xs_and_ys.len();
+        Ok((0..n)
+            .map(|i| {
+                secant(
+                    xs_and_ys[i.saturating_sub(1)],
+                    xs_and_ys[(i + 1).min(n - 1)],
+                )
+            })
+            .collect())
+    }
+}
+
+/// Uses slopes given by the user, one per knot.
+#[derive(Debug, Default, Clone)]
+pub struct UserSlopes<V>(pub Vec<V>);
+
+impl<V: Value> SlopeProvider<V> for UserSlopes<V> {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        if self.0.len() != xs_and_ys.len() {
+            return Err(InterpolationError::InsufficientPointsError(self.0.len()));
+        }
+        Ok(self.0.clone())
+    }
+}
+
+/// A cubic Hermite spline whose knot slopes are decided by `S`.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::spline::hermite::{CatmullRomSlopes, Hermite};
+/// use qlab_math::interpolation::Interpolator;
+///
+/// let points = [(0.0, 0.0), (1.0, 1.0), (3.0, 9.0)];
+/// let spline: Hermite<f64, CatmullRomSlopes> = Hermite::default().try_fit(&points).unwrap();
+/// assert!((spline.try_value(1.0).unwrap() - 1.0).abs() < f64::EPSILON);
+/// ```
+#[derive(Clone)]
+pub struct Hermite<V: Value, S = FiniteDifference> {
     points: Vec<Point2DWithSlope<V>>,
     m: Matrix4<V>,
+    slope_provider: S,
+}
+
+impl<V: Value, S: SlopeProvider<V>> Default for Hermite<V, S> {
+    fn default() -> Self {
+        Self::with_slope_provider(S::default())
+    }
+}
+
+fn try_sorted_points<V: Value>(
+    raw_points: impl Iterator<Item = (V, V, V)>,
+) -> Result<Vec<Point2DWithSlope<V>>, InterpolationError<V>> {
+    let mut points: Vec<Point2DWithSlope<V>> = Vec::new();
+    for (x, y, dydx) in raw_points {
+        if points.last().is_some_and(|last| x < last.coordinate.x) {
+            return Err(InterpolationError::PointOrderError);
+        }
+        points.push(Point2DWithSlope::new(x, y, dydx));
+    }
+    Ok(points)
 }
 
 impl<V: Value> Hermite<V> {
@@ -15,8 +116,8 @@ impl<V: Value> Hermite<V> {
     /// # Arguments
     ///
     /// * `raw_points` - A slice of tuples representing the raw points of the spline.
-    ///                 Each tuple should contain three elements: the x-coordinate, the y-coordinate,
-    ///                 and the derivative of y with respect to x (dy/dx).
+    ///   Each tuple should contain three elements: the x-coordinate, the y-coordinate,
+    ///   and the derivative of y with respect to x (dy/dx).
     ///
     /// # Returns
     ///
@@ -25,23 +126,28 @@ impl<V: Value> Hermite<V> {
     ///
     /// # Errors
     ///
-    /// * `InterpolationError::InsufficientPointsError(n)` - If the number of `raw_points` is less than 3, where `n` is the number of `raw_points`.
+    /// * `InterpolationError::InsufficientPointsError(n)` - If the number of `raw_points` is less than 2, where `n` is the number of `raw_points`.
     /// * `InterpolationError::PointOrderError` - If the x-coordinates of the `raw_points` are not in ascending order.
+    pub fn try_new(raw_points: &[(V, V, V)]) -> Result<Self, InterpolationError<V>> {
+        if raw_points.len() < 2 {
+            return Err(InterpolationError::InsufficientPointsError(
+                raw_points.len(),
+            ));
+        }
+        Ok(Self {
+            points: try_sorted_points(raw_points.iter().copied())?,
+            ..Self::default()
+        })
+    }
+}
+
+impl<V: Value, S: SlopeProvider<V>> Hermite<V, S> {
+    /// Creates an unfitted spline deciding its slopes with `slope_provider`.
     ///
     /// # Panics
     /// Will panic if `V` fail to cast constants.
-    pub fn try_new(raw_points: &[(V, V, V)]) -> Result<Self, InterpolationError<V>> {
-        let mut temp = raw_points[0].0;
-
-        let mut points = Vec::new();
-        for &(x, y, dydx) in raw_points {
-            let point = Point2DWithSlope::new(x, y, dydx);
-            if point.coordinate.x < temp {
-                return Err(InterpolationError::PointOrderError);
-            }
-            temp = point.coordinate.x;
-            points.push(point);
-        }
+    #[must_use]
+    pub fn with_slope_provider(slope_provider: S) -> Self {
         let m = Matrix4::new(
             V::from_i8(2).unwrap(),
             V::from_i8(-2).unwrap(),
@@ -60,7 +166,45 @@ impl<V: Value> Hermite<V> {
             V::zero(),
             V::zero(),
         );
-        Ok(Self { points, m })
+        Self {
+            points: Vec::new(),
+            m,
+            slope_provider,
+        }
+    }
+}
+
+impl<V: Value, S: SlopeProvider<V>> Interpolator for Hermite<V, S> {
+    type Value = V;
+
+    /// Fits the spline to `raw_points` with the slopes decided by the slope provider.
+    ///
+    /// # Errors
+    ///
+    /// * `InterpolationError::InsufficientPointsError(n)` - If the number of `raw_points` is less than 2,
+    ///   or if the slope provider does not return one slope per point.
+    /// * `InterpolationError::PointOrderError` - If the x-coordinates of the `raw_points` are not in ascending order.
+    fn try_fit(mut self, raw_points: &[(V, V)]) -> Result<Self, InterpolationError<V>> {
+        if raw_points.len() < 2 {
+            return Err(InterpolationError::InsufficientPointsError(
+                raw_points.len(),
+            ));
+        }
+        // slope providers rely on sorted points
+        if raw_points.windows(2).any(|pair| pair[1].0 < pair[0].0) {
+            return Err(InterpolationError::PointOrderError);
+        }
+        let slopes = self.slope_provider.slopes(raw_points)?;
+        if slopes.len() != raw_points.len() {
+            return Err(InterpolationError::InsufficientPointsError(slopes.len()));
+        }
+        self.points = try_sorted_points(
+            raw_points
+                .iter()
+                .zip(slopes)
+                .map(|(&(x, y), dydx)| (x, y, dydx)),
+        )?;
+        Ok(self)
     }
 
     /// Tries to evaluate the interpolated value of Hermite spline at a given point x.
@@ -78,10 +222,7 @@ impl<V: Value> Hermite<V> {
     ///
     /// Returns `OutOfLowerBound(x)` if `x` is less than the minimum x-coordinate value of any point in the Hermite spline.
     /// Returns `OutOfUpperBound(x)` if `x` is greater than the maximum x-coordinate value of any point in the Hermite spline.
-    ///
-    /// # Panics
-    /// Will panic if partial comparison of points fail.
-    pub fn try_value(&self, x: V) -> Result<V, InterpolationError<V>> {
+    fn try_value(&self, x: V) -> Result<V, InterpolationError<V>> {
         let pos = find_index_at_left_boundary(&self.points, x)?;
 
         let point = &self.points[pos];
@@ -100,3 +241,41 @@ impl<V: Value> Hermite<V> {
         Ok((d.transpose() * self.m * f).x)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slope_providers() {
+        let points = [(0.0_f64, 0.0), (1.0, 1.0), (3.0, 9.0)];
+        let finite_difference = FiniteDifference.slopes(&points).unwrap();
+        assert_eq!(finite_difference, vec![1.0, 2.5, 4.0]);
+        let catmull_rom = CatmullRomSlopes.slopes(&points).unwrap();
+        assert_eq!(catmull_rom, vec![1.0, 3.0, 4.0]);
+        assert!(UserSlopes(vec![0.0]).slopes(&points).is_err());
+    }
+
+    #[test]
+    fn test_user_slopes_match_try_new() {
+        // with exact slopes the cubic y = x^3 is reproduced
+        let points = [(0.0_f64, 0.0), (1.0, 1.0), (2.0, 8.0)];
+        let fitted = Hermite::with_slope_provider(UserSlopes(vec![0.0, 3.0, 12.0]))
+            .try_fit(&points)
+            .unwrap();
+        let constructed =
+            Hermite::try_new(&[(0.0, 0.0, 0.0), (1.0, 1.0, 3.0), (2.0, 8.0, 12.0)]).unwrap();
+        for x in [0.25, 0.5, 1.5, 1.9] {
+            assert!((fitted.try_value(x).unwrap() - x * x * x).abs() < 1e-12);
+            assert!((constructed.try_value(x).unwrap() - x * x * x).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_invalid_points() {
+        assert!(Hermite::<f64>::default().try_fit(&[(0.0, 1.0)]).is_err());
+        assert!(Hermite::<f64>::default()
+            .try_fit(&[(1.0, 1.0), (0.0, 1.0)])
+            .is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::interpolation::spline::hermite::{Hermite, SlopeProvider};
+use crate::interpolation::spline::natural_cubic::NaturalCubicSlopes;
+use crate::interpolation::spline::Value;
+use qlab_error::InterpolationError;
+
+/// Filters the slopes decided by `S` as proposed by Hyman, so that the Hermite spline has no extrema
+/// between knots other than those of the data.
+///
+/// Where the data are monotone around a knot, its slope is set to zero if it has the wrong sign
+/// and is capped at three times the smaller neighbouring secant; at a local extremum of the data it is
+/// set to zero. Slopes which already satisfy the conditions are kept, so that the filtered spline
+/// stays as close as possible to the one of `S`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HymanFilter<S = NaturalCubicSlopes>(pub S);
+
+impl<V: Value, S: SlopeProvider<V>> SlopeProvider<V> for HymanFilter<S> {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        let mut slopes = self.0.slopes(xs_and_ys)?;
+        if slopes.len() != xs_and_ys.len() {
+            return Err(InterpolationError::InsufficientPointsError(slopes.len()));
+        }
+        let secants: Vec<V> = xs_and_ys
+            .windows(2)
+            .map(|pair| (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+            .collect();
+        let three = V::from_i8(3).unwrap();
+        let n = slopes.len();
+        for (i, slope) in slopes.iter_mut().enumerate() {
+            let (left, right) = (
+                secants[i.saturating_sub(1)],
+                secants[i.min(n.saturating_sub(2))],
+            );
+            if left * right <= V::zero() {
+                *slope = V::zero();
+                continue;
+            }
+            let bound = three * left.abs().min(right.abs());
+            // the slope must share the sign of the secants
+            *slope = if right > V::zero() {
+                slope.max(V::zero()).min(bound)
+            } else {
+                slope.min(V::zero()).max(-bound)
+            };
+        }
+        Ok(slopes)
+    }
+}
+
+/// A natural cubic spline whose slopes pass through [`HymanFilter`], trading the continuity of the second
+/// derivative for the absence of spurious extrema.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::spline::hyman::HymanNaturalCubic;
+/// use qlab_math::interpolation::spline::natural_cubic::NaturalCubic;
+/// use qlab_math::interpolation::Interpolator;
+///
+/// let points = [(0.0, 0.0), (1.0, 0.0), (2.0, 1.0), (3.0, 1.0)];
+/// let natural = NaturalCubic::default().try_fit(&points).unwrap();
+/// let filtered: HymanNaturalCubic<f64> = HymanNaturalCubic::default().try_fit(&points).unwrap();
+/// // the natural spline dips below the flat start of the data, the filtered one does not
+/// assert!(natural.try_value(0.5).unwrap() < 0.0);
+/// assert!(filtered.try_value(0.5).unwrap() >= 0.0);
+/// ```
+pub type HymanNaturalCubic<V> = Hermite<V, HymanFilter<NaturalCubicSlopes>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::spline::natural_cubic::NaturalCubic;
+    use crate::interpolation::Interpolator;
+
+    #[test]
+    fn test_hyman_filter() {
+        // a smooth monotone curve keeps the natural spline
+        let smooth: Vec<(f64, f64)> = (0..6)
+            .map(|i| (f64::from(i), (0.3 * f64::from(i)).exp()))
+            .collect();
+        let natural = NaturalCubic::default().try_fit(&smooth).unwrap();
+        let unfiltered = Hermite::with_slope_provider(NaturalCubicSlopes)
+            .try_fit(&smooth)
+            .unwrap();
+        let filtered = HymanNaturalCubic::default().try_fit(&smooth).unwrap();
+        for x in [0.3, 1.7, 2.5, 4.9] {
+            let expected = natural.try_value(x).unwrap();
+            assert!((unfiltered.try_value(x).unwrap() - expected).abs() < 1e-12);
+            assert!((filtered.try_value(x).unwrap() - expected).abs() < 1e-12);
+        }
+
+        // a steep curve has no spurious extrema once filtered
+        let steep = [
+            (0.0_f64, 0.01),
+            (1.0, 0.011),
+            (2.0, 0.04),
+            (3.0, 0.041),
+            (5.0, 0.042),
+        ];
+        let filtered = HymanNaturalCubic::default().try_fit(&steep).unwrap();
+        let values: Vec<f64> = (0..=500)
+            .map(|i| filtered.try_value(f64::from(i) / 100.0).unwrap())
+            .collect();
+        assert!(values.windows(2).all(|pair| pair[1] >= pair[0]));
+        for &(x, y) in &steep {
+            assert!((filtered.try_value(x).unwrap() - y).abs() < 1e-15);
+        }
+        // an extremum of the data stays at its knot
+        let peak = Hermite::with_slope_provider(HymanFilter::<NaturalCubicSlopes>::default())
+            .try_fit(&[(0.0_f64, 0.0), (1.0, 1.0), (2.0, 0.5), (3.0, 0.4)])
+            .unwrap();
+        assert!(peak.try_value(1.1).unwrap() < 1.0);
+    }
+}
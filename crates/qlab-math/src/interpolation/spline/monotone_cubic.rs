@@ -0,0 +1,118 @@
+use crate::interpolation::spline::hermite::{Hermite, SlopeProvider};
+use crate::interpolation::spline::Value;
+use qlab_error::InterpolationError;
+
+/// Decides the knot slopes by the method of Fritsch and Carlson, so that the Hermite spline is monotone
+/// between knots wherever the data are.
+///
+/// The slopes start from the average of the neighbouring secants, are set to zero at local extrema
+/// and on flat intervals, and are scaled down on each interval until `α² + β² ≤ 9`,
+/// where `α` and `β` are the slopes at its ends over its secant.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FritschCarlsonSlopes;
+
+impl<V: Value> SlopeProvider<V> for FritschCarlsonSlopes {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        let n = xs_and_ys.len();
+        let secants: Vec<V> = xs_and_ys
+            .windows(2)
+            .map(|pair| {
+                if pair[1].0 == pair[0].0 {
+                    return Err(InterpolationError::PointOrderError);
+                }
+                Ok((pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+            })
+            .collect::<Result<_, _>>()?;
+        let two = V::one() + V::one();
+        let nine = V::from_i8(9).unwrap();
+        let mut slopes: Vec<V> = (0..n)
+            .map(|i| {
+                if i == 0 {
+                    secants[0]
+                } else if i + 1 == n {
+                    secants[n - 2]
+                } else if secants[i - 1] * secants[i] <= V::zero() {
+                    V::zero()
+                } else {
+                    (secants[i - 1] + secants[i]) / two
+                }
+            })
+            .collect();
+        for (i, &secant) in secants.iter().enumerate() {
+            if secant == V::zero() {
+                slopes[i] = V::zero();
+                slopes[i + 1] = V::zero();
+                continue;
+            }
+            let alpha = slopes[i] / secant;
+            let beta = slopes[i + 1] / secant;
+            let radius = alpha * alpha + beta * beta;
+            if radius > nine {
+                let tau = (V::one() + two) / radius.sqrt();
+                slopes[i] = tau * alpha * secant;
+                slopes[i + 1] = tau * beta * secant;
+            }
+        }
+        Ok(slopes)
+    }
+}
+
+/// A monotone cubic Hermite spline, whose values between two knots stay within the values at the knots,
+/// so that a steep curve is interpolated without the overshoots of natural cubic or Catmull-Rom splines.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::spline::monotone_cubic::MonotoneCubic;
+/// use qlab_math::interpolation::Interpolator;
+///
+/// let points = [(0.0, 0.0), (1.0, 0.0), (2.0, 1.0), (3.0, 1.0)];
+/// let spline: MonotoneCubic<f64> = MonotoneCubic::default().try_fit(&points).unwrap();
+/// for i in 0..=30 {
+///     let y = spline.try_value(f64::from(i) / 10.0).unwrap();
+///     assert!((0.0..=1.0).contains(&y));
+/// }
+/// ```
+pub type MonotoneCubic<V> = Hermite<V, FritschCarlsonSlopes>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::spline::natural_cubic::NaturalCubic;
+    use crate::interpolation::Interpolator;
+
+    #[test]
+    fn test_monotone_cubic() {
+        let points = [
+            (0.25_f64, 0.01),
+            (1.0, 0.012),
+            (2.0, 0.045),
+            (5.0, 0.05),
+            (10.0, 0.051),
+        ];
+        let spline = MonotoneCubic::default().try_fit(&points).unwrap();
+        for &(x, y) in &points {
+            assert!((spline.try_value(x).unwrap() - y).abs() < 1e-15);
+        }
+        let natural = NaturalCubic::default().try_fit(&points).unwrap();
+        let grid: Vec<f64> = (25..=1000).map(|i| f64::from(i) / 100.0).collect();
+        let is_increasing = |values: &[f64]| values.windows(2).all(|pair| pair[1] >= pair[0]);
+        let monotone: Vec<f64> = grid.iter().map(|&x| spline.try_value(x).unwrap()).collect();
+        assert!(is_increasing(&monotone));
+        let oscillating: Vec<f64> = grid
+            .iter()
+            .map(|&x| natural.try_value(x).unwrap())
+            .collect();
+        assert!(!is_increasing(&oscillating));
+
+        // a local maximum stays at the knot
+        let peak = MonotoneCubic::default()
+            .try_fit(&[(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)])
+            .unwrap();
+        assert!(peak.try_value(0.9).unwrap() < 1.0);
+        assert!(peak.try_value(1.1).unwrap() < 1.0);
+        assert!(MonotoneCubic::default()
+            .try_fit(&[(0.0, 0.0), (0.0, 1.0)])
+            .is_err());
+    }
+}
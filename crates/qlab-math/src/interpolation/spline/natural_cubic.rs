@@ -1,12 +1,16 @@
+use crate::interpolation::spline::hermite::SlopeProvider;
 use crate::interpolation::spline::Value;
 use crate::interpolation::{find_index_at_left_boundary, Interpolator, Point2DWithSlope};
 use crate::linear_algebra::tridiagonal_matrix::TridiagonalMatrix;
 use nalgebra::DVector;
 use qlab_error::InterpolationError;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct NaturalCubic<V: Value> {
     points: Vec<Point2DWithSlope<V>>,
+    /// The coefficients `[a, b, c, d]` of `a + b * dx + c * dx^2 + d * dx^3` on each interval,
+    /// where `dx` is the distance from the left end of the interval.
+    coefficients: Vec<[V; 4]>,
 }
 
 impl<V: Value> Interpolator for NaturalCubic<V> {
@@ -16,8 +20,8 @@ impl<V: Value> Interpolator for NaturalCubic<V> {
     /// # Arguments
     ///
     /// * `raw_points` - A slice of tuples representing the raw points. Each tuple
-    ///                  should contain a value of type V for the x-coordinate and
-    ///                  a value of type V for the y-coordinate.
+    ///   should contain a value of type V for the x-coordinate and
+    ///   a value of type V for the y-coordinate.
     ///
     /// # Returns
     ///
@@ -55,13 +59,14 @@ impl<V: Value> Interpolator for NaturalCubic<V> {
                 b_upper_diagonals.push(h_next / V::from_i8(6).unwrap());
                 c_upper_diagonals.push(h_next.recip());
             }
+            let mut boundary = V::zero();
             if i == 1 {
-                m.push(raw_points[i - 1].1 / h);
-            } else if i + 2 == raw_points.len() {
-                m.push(raw_points[i + 1].1 / h_next);
-            } else {
-                m.push(V::zero());
+                boundary += raw_points[i - 1].1 / h;
             }
+            if i + 2 == raw_points.len() {
+                boundary += raw_points[i + 1].1 / h_next;
+            }
+            m.push(boundary);
             b_diagonals.push((h + h_next) / V::from_i8(3).unwrap());
             c_diagonals.push(-(h.recip() + h_next.recip()));
         }
@@ -93,11 +98,27 @@ impl<V: Value> Interpolator for NaturalCubic<V> {
             points.push(point);
         }
 
+        let six = V::from_i8(6).unwrap();
+        let two = V::from_i8(2).unwrap();
+        self.coefficients = points
+            .windows(2)
+            .map(|pair| {
+                let (point, next_point) = (&pair[0], &pair[1]);
+                let width = next_point.coordinate.x - point.coordinate.x;
+                [
+                    point.coordinate.y,
+                    (next_point.coordinate.y - point.coordinate.y) / width
+                        - width * (two * point.dydx + next_point.dydx) / six,
+                    point.dydx / two,
+                    (next_point.dydx - point.dydx) / (six * width),
+                ]
+            })
+            .collect();
         self.points = points;
         Ok(self)
     }
 
-    /// Evaluates the Hermite spline at the given value `x`.
+    /// Evaluates the spline at the given value `x` from the coefficients precomputed by `try_fit`.
     ///
     /// # Arguments
     ///
@@ -111,28 +132,42 @@ impl<V: Value> Interpolator for NaturalCubic<V> {
     /// If `x` is below the lower bound of the spline's points, returns `Err(OutOfLowerBound(x))`.
     /// If `x` is above the upper bound of the spline's points, returns `Err(OutOfUpperBound(x))`.
     ///
-    /// # Panics
-    /// * Will panic if points contains a point which cannot be compared partially.
-    /// * Will panic if `V` cannot cast constants.
-    ///
     fn try_value(&self, x: V) -> Result<V, InterpolationError<V>> {
         let pos = find_index_at_left_boundary(&self.points, x)?;
-        let point = &self.points[pos];
-        let next_point = &self.points[pos + 1];
-        let h = next_point.coordinate.x - point.coordinate.x;
-        let six = V::from_i8(6).unwrap();
-        Ok((next_point.coordinate.x - x)
-            * (next_point.coordinate.x - x)
-            * (next_point.coordinate.x - x)
-            / six
-            / h
-            * point.dydx
-            + (x - point.coordinate.x) * (x - point.coordinate.x) * (x - point.coordinate.x)
-                / six
-                / h
-                * next_point.dydx
-            + (next_point.coordinate.x - x) * (point.coordinate.y / h - h / six * point.dydx)
-            + (x - point.coordinate.x) * (next_point.coordinate.y / h - h / six * next_point.dydx))
+        let [constant, linear, quadratic, cubic] = self.coefficients[pos];
+        let dx = x - self.points[pos].coordinate.x;
+        Ok(constant + dx * (linear + dx * (quadratic + dx * cubic)))
+    }
+}
+
+impl<V: Value> NaturalCubic<V> {
+    /// Returns the first derivative of the fitted spline at each knot.
+    fn knot_slopes(&self) -> Vec<V> {
+        let mut slopes: Vec<V> = self
+            .coefficients
+            .iter()
+            .map(|&[_, linear, _, _]| linear)
+            .collect();
+        if let (Some(&[_, linear, quadratic, cubic]), [.., left, right]) =
+            (self.coefficients.last(), self.points.as_slice())
+        {
+            let width = right.coordinate.x - left.coordinate.x;
+            let (two, three) = (V::one() + V::one(), V::one() + V::one() + V::one());
+            slopes.push(linear + width * (two * quadratic + three * width * cubic));
+        }
+        slopes
+    }
+}
+
+/// Uses the first derivatives of the natural cubic spline through the points, so that the Hermite spline
+/// coincides with [`NaturalCubic`] unless the slopes are filtered, e.g. by
+/// [`HymanFilter`](crate::interpolation::spline::hyman::HymanFilter).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NaturalCubicSlopes;
+
+impl<V: Value> SlopeProvider<V> for NaturalCubicSlopes {
+    fn slopes(&self, xs_and_ys: &[(V, V)]) -> Result<Vec<V>, InterpolationError<V>> {
+        Ok(NaturalCubic::default().try_fit(xs_and_ys)?.knot_slopes())
     }
 }
 
@@ -148,4 +183,49 @@ mod tests {
         let val = interpolator.try_value(0.75).unwrap();
         assert!((0.25_f64 - val) / 0.25_f64 < f64::EPSILON);
     }
+
+    #[test]
+    fn test_matches_closed_form() {
+        let points = [
+            (0.0_f64, 1.0),
+            (0.5, 0.7),
+            (1.5, 0.9),
+            (2.0, 0.2),
+            (4.0, 0.5),
+        ];
+        let interpolator = NaturalCubic::default().try_fit(&points).unwrap();
+        for pair in interpolator.points.windows(2) {
+            let (p0, p1) = (&pair[0], &pair[1]);
+            let (x0, x1) = (p0.coordinate.x, p1.coordinate.x);
+            let h = x1 - x0;
+            for x in [x0, x0 + 0.3 * h, x0 + 0.8 * h, x1] {
+                let expected = (x1 - x).powi(3) / 6.0 / h * p0.dydx
+                    + (x - x0).powi(3) / 6.0 / h * p1.dydx
+                    + (x1 - x) * (p0.coordinate.y / h - h / 6.0 * p0.dydx)
+                    + (x - x0) * (p1.coordinate.y / h - h / 6.0 * p1.dydx);
+                let val = interpolator.try_value(x).unwrap();
+                assert!((val - expected).abs() < 1e-14, "{x}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_continuous_slopes() {
+        for points in [
+            vec![(0.0_f64, 1.0), (1.0, 2.0), (3.0, 0.5)],
+            (0..6)
+                .map(|i| (f64::from(i), (0.3 * f64::from(i)).exp()))
+                .collect(),
+        ] {
+            let interpolator = NaturalCubic::default().try_fit(&points).unwrap();
+            for (i, pair) in interpolator.points.windows(2).enumerate() {
+                let [_, linear, quadratic, cubic] = interpolator.coefficients[i];
+                let width = pair[1].coordinate.x - pair[0].coordinate.x;
+                let end_slope = linear + width * (2.0 * quadratic + 3.0 * width * cubic);
+                if let Some(next) = interpolator.coefficients.get(i + 1) {
+                    assert!((end_slope - next[1]).abs() < 1e-12);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,169 @@
+use crate::interpolation::Interpolator;
+use crate::value::Value;
+use qlab_error::InterpolationError;
+
+/// The errors of an interpolation scheme in predicting each of its interior points from the others.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossValidation<V> {
+    residuals: Vec<(V, V)>,
+}
+
+impl<V: Value> CrossValidation<V> {
+    /// Returns each interior point's `x` with the prediction less the actual `y`.
+    #[must_use]
+    pub fn residuals(&self) -> &[(V, V)] {
+        &self.residuals
+    }
+
+    /// Returns the root mean square of the residuals.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast the number of residuals.
+    #[must_use]
+    pub fn rmse(&self) -> V {
+        let sum = self
+            .residuals
+            .iter()
+            .fold(V::zero(), |acc, &(_, error)| acc + error * error);
+        (sum / V::from_usize(self.residuals.len()).unwrap()).sqrt()
+    }
+
+    /// Returns the largest absolute residual.
+    #[must_use]
+    pub fn max_abs_error(&self) -> V {
+        self.residuals
+            .iter()
+            .fold(V::zero(), |acc, &(_, error)| acc.max(error.abs()))
+    }
+}
+
+/// Runs leave-one-out cross-validation of the scheme of `interpolator` on the sorted `points`:
+/// each interior point is left out in turn, the others are fitted by [`Interpolator::unfitted`],
+/// and the fit is compared with the point left out.
+/// The end points are kept since interpolators do not extrapolate.
+///
+/// # Errors
+/// Returns `InterpolationError::InsufficientPointsError` if there are fewer than three points,
+/// or an error if a fit or a prediction fails, e.g. if the scheme needs more points than remain.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_math::interpolation::spline::natural_cubic::NaturalCubic;
+/// use qlab_math::interpolation::validation::leave_one_out;
+///
+/// let points: Vec<(f64, f64)> = (0..8).map(|i| (f64::from(i), f64::from(i).sin())).collect();
+/// let linear = leave_one_out(&Linear::default(), &points).unwrap();
+/// let cubic = leave_one_out(&NaturalCubic::default(), &points).unwrap();
+/// // the smooth function is predicted better by the spline
+/// assert!(cubic.rmse() < linear.rmse());
+/// ```
+pub fn leave_one_out<V: Value, I: Interpolator<Value = V>>(
+    interpolator: &I,
+    points: &[(V, V)],
+) -> Result<CrossValidation<V>, InterpolationError<V>> {
+    if points.len() < 3 {
+        return Err(InterpolationError::InsufficientPointsError(points.len()));
+    }
+    let residuals = (1..points.len() - 1)
+        .map(|left_out| {
+            let others: Vec<(V, V)> = points
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != left_out)
+                .map(|(_, &point)| point)
+                .collect();
+            let (x, y) = points[left_out];
+            let fitted = interpolator.unfitted().try_fit(&others)?;
+            Ok((x, fitted.try_value(x)? - y))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(CrossValidation { residuals })
+}
+
+/// Returns the largest curvature `|y''| / (1 + y'^2)^(3/2)` of the fitted `interpolator` over `samples`
+/// evenly spaced points from `from` to `to`, with the derivatives taken by central differences
+/// at the spacing of the samples.
+///
+/// # Errors
+/// Returns `InterpolationError::InsufficientPointsError` if `samples` is less than three,
+/// `InterpolationError::PointOrderError` if `from` is not below `to`, or an error if an evaluation fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+pub fn max_curvature<V: Value, I: Interpolator<Value = V>>(
+    interpolator: &I,
+    from: V,
+    to: V,
+    samples: usize,
+) -> Result<V, InterpolationError<V>> {
+    if samples < 3 {
+        return Err(InterpolationError::InsufficientPointsError(samples));
+    }
+    if from >= to {
+        return Err(InterpolationError::PointOrderError);
+    }
+    let h = (to - from) / V::from_usize(samples - 1).unwrap();
+    let values = (0..samples)
+        .map(|i| {
+            let x = if i + 1 == samples {
+                to
+            } else {
+                from + h * V::from_usize(i).unwrap()
+            };
+            interpolator.try_value(x)
+        })
+        .collect::<Result<Vec<V>, _>>()?;
+    let two = V::one() + V::one();
+    let three_halves = V::from_f64(1.5).unwrap();
+    Ok(values.windows(3).fold(V::zero(), |acc, window| {
+        let first = (window[2] - window[0]) / (two * h);
+        let second = (window[2] - two * window[1] + window[0]) / (h * h);
+        acc.max(second.abs() / (V::one() + first * first).powf(three_halves))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpolation::linear::Linear;
+    use crate::interpolation::spline::catmull_rom::CatmullRom;
+    use crate::interpolation::spline::hermite::{Hermite, UserSlopes};
+
+    #[test]
+    fn test_validation() {
+        let line = [(0.0_f64, 1.0), (1.0, 3.0), (2.0, 5.0), (4.0, 9.0)];
+        let linear = leave_one_out(&Linear::default(), &line).unwrap();
+        assert_eq!(linear.residuals().len(), 2);
+        assert!(linear.rmse() < 1e-15 && linear.max_abs_error() < 1e-15);
+
+        let kinked = [
+            (0.0_f64, 0.0),
+            (1.0, 0.0),
+            (2.0, 0.0),
+            (3.0, 1.0),
+            (4.0, 2.0),
+        ];
+        let linear = leave_one_out(&Linear::default(), &kinked).unwrap();
+        assert!((linear.max_abs_error() - 0.5).abs() < 1e-15);
+        assert!((linear.rmse() - (0.25_f64 / 3.0).sqrt()).abs() < 1e-15);
+        assert!(leave_one_out(&CatmullRom::default(), &kinked).is_ok());
+        assert!(leave_one_out(&Linear::default(), &kinked[..2]).is_err());
+
+        // a parabola has curvature 2 at its vertex
+        let parabola: Vec<(f64, f64)> = (-4..=4)
+            .map(|i| (f64::from(i) / 4.0, (f64::from(i) / 4.0).powi(2)))
+            .collect();
+        let slopes = parabola.iter().map(|&(x, _)| 2.0 * x).collect();
+        let spline = Hermite::with_slope_provider(UserSlopes(slopes))
+            .try_fit(&parabola)
+            .unwrap();
+        let curvature = max_curvature(&spline, -1.0, 1.0, 201).unwrap();
+        assert!((curvature - 2.0).abs() < 1e-6);
+        let linear = Linear::default().try_fit(&line).unwrap();
+        assert!(max_curvature(&linear, 0.0, 4.0, 9).unwrap() < 1e-12);
+        assert!(max_curvature(&linear, 4.0, 0.0, 9).is_err());
+        assert!(max_curvature(&linear, 0.0, 5.0, 9).is_err());
+    }
+}
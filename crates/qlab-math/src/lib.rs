@@ -1,3 +1,9 @@
+pub mod distribution;
+pub mod implied_volatility;
 pub mod interpolation;
-pub(crate) mod linear_algebra;
+pub mod linear_algebra;
+pub mod optimization;
+pub mod parallel;
+pub mod random;
+pub mod root_finding;
 pub mod value;
@@ -1 +1,3 @@
+pub mod cholesky;
+pub(crate) mod dense;
 pub(crate) mod tridiagonal_matrix;
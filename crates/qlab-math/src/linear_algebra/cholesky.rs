@@ -0,0 +1,90 @@
+use crate::value::Value;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+/// Factorizes a symmetric positive semidefinite matrix given by rows into `L·Lᵀ`, returning the lower
+/// triangular `L` by rows, e.g. to turn independent normal draws `z` into draws `L·z` with covariance `matrix`.
+///
+/// A pivot within a relative tolerance of zero is taken as zero, so that singular matrices such as a
+/// correlation of one still factorize; the corresponding column of `L` is then zero below the diagonal.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::linear_algebra::cholesky::cholesky;
+///
+/// let lower = cholesky(&[vec![1.0_f64, 0.6], vec![0.6, 1.0]]).unwrap();
+/// assert_eq!(lower[0], vec![1.0, 0.0]);
+/// assert!((lower[1][0] - 0.6).abs() < 1e-15 && (lower[1][1] - 0.8).abs() < 1e-15);
+/// ```
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the matrix is empty, not square, not symmetric or not positive semidefinite.
+pub fn cholesky<V: Value>(matrix: &[Vec<V>]) -> QLabResult<Vec<Vec<V>>> {
+    let n = matrix.len();
+    if n == 0 || matrix.iter().any(|row| row.len() != n) {
+        return Err(InvalidInput("a Cholesky factorization needs a square matrix".into()).into());
+    }
+    let scale = matrix
+        .iter()
+        .enumerate()
+        .fold(V::zero(), |acc, (i, row)| acc.max(row[i].abs()));
+    let tolerance = V::epsilon().sqrt() * scale;
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, column) in matrix.iter().enumerate().take(i) {
+            if (row[j] - column[i]).abs() > tolerance {
+                return Err(InvalidInput(
+                    format!("the matrix is not symmetric at ({i}, {j})").into(),
+                )
+                .into());
+            }
+        }
+    }
+    let mut lower = vec![vec![V::zero(); n]; n];
+    for j in 0..n {
+        let pivot = matrix[j][j] - (0..j).fold(V::zero(), |acc, k| acc + lower[j][k] * lower[j][k]);
+        if pivot < -tolerance {
+            return Err(InvalidInput(
+                format!("the matrix is not positive semidefinite at row {j}").into(),
+            )
+            .into());
+        }
+        if pivot <= tolerance {
+            continue;
+        }
+        let diagonal = pivot.sqrt();
+        lower[j][j] = diagonal;
+        for i in j + 1..n {
+            let dot = (0..j).fold(V::zero(), |acc, k| acc + lower[i][k] * lower[j][k]);
+            lower[i][j] = (matrix[i][j] - dot) / diagonal;
+        }
+    }
+    Ok(lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cholesky() {
+        let matrix: Vec<Vec<f64>> = vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ];
+        let lower = cholesky(&matrix).unwrap();
+        let expected = [[2.0, 0.0, 0.0], [6.0, 1.0, 0.0], [-8.0, 5.0, 3.0]];
+        for (row, expected_row) in lower.iter().zip(expected) {
+            for (&actual, value) in row.iter().zip(expected_row) {
+                assert!((actual - value).abs() < 1e-12);
+            }
+        }
+        // perfectly correlated assets
+        let singular = cholesky(&[vec![1.0, 1.0], vec![1.0, 1.0]]).unwrap();
+        assert_eq!(singular, vec![vec![1.0, 0.0], vec![1.0, 0.0]]);
+        assert!(cholesky(&[vec![1.0, 2.0], vec![2.0, 1.0]]).is_err());
+        assert!(cholesky(&[vec![1.0, 0.5], vec![0.4, 1.0]]).is_err());
+        assert!(cholesky::<f64>(&[vec![1.0, 0.5]]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::value::Value;
+
+/// Solves `ax = b` for a square matrix given by rows with Gaussian elimination and partial pivoting.
+///
+/// Returns `None` if the shapes do not match or the matrix is singular.
+pub(crate) fn solve<V: Value>(mut a: Vec<Vec<V>>, mut b: Vec<V>) -> Option<Vec<V>> {
+    let n = b.len();
+    if a.len() != n || a.iter().any(|row| row.len() != n) {
+        return None;
+    }
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&i, &j| {
+            a[i][column]
+                .abs()
+                .partial_cmp(&a[j][column].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+        if a[pivot][column] == V::zero() {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        for row in column + 1..n {
+            let factor = a[row][column] / a[column][column];
+            let (above, below) = a.split_at_mut(row);
+            for (target, &source) in below[0][column..].iter_mut().zip(&above[column][column..]) {
+                *target -= factor * source;
+            }
+            let reduction = factor * b[column];
+            b[row] -= reduction;
+        }
+    }
+    let mut x = vec![V::zero(); n];
+    for row in (0..n).rev() {
+        let known = (row + 1..n).fold(V::zero(), |acc, k| acc + a[row][k] * x[k]);
+        x[row] = (b[row] - known) / a[row][row];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let a = vec![
+            vec![0.0, 2.0, 1.0],
+            vec![1.0, 1.0, 0.0],
+            vec![2.0, 0.0, 3.0],
+        ];
+        let x = solve(a, vec![7.0, 3.0, 11.0]).unwrap();
+        for (actual, expected) in x.iter().zip([1.0_f64, 2.0, 3.0]) {
+            assert!((actual - expected).abs() < 1e-12);
+        }
+        assert!(solve(vec![vec![1.0, 2.0], vec![2.0, 4.0]], vec![1.0, 2.0]).is_none());
+    }
+
+    #[test]
+    fn test_shapes_and_pivoting() {
+        // mismatched shapes
+        assert!(solve(vec![vec![1.0, 0.0]], vec![1.0, 2.0]).is_none());
+        assert!(solve(vec![vec![1.0], vec![0.0, 1.0]], vec![1.0, 2.0]).is_none());
+        assert!(solve(
+            vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]],
+            vec![1.0, 2.0]
+        )
+        .is_none());
+        // an empty system has the empty solution
+        assert_eq!(solve::<f64>(Vec::new(), Vec::new()), Some(Vec::new()));
+        // a zero column is singular
+        assert!(solve(vec![vec![0.0, 1.0], vec![0.0, 2.0]], vec![1.0, 2.0]).is_none());
+
+        let x = solve(vec![vec![4.0]], vec![2.0]).unwrap();
+        assert!((x[0] - 0.5_f64).abs() < f64::EPSILON);
+        // a tiny pivot is swapped for the larger one, keeping the solution accurate
+        let x = solve(vec![vec![1e-20, 1.0], vec![1.0, 1.0]], vec![1.0, 2.0]).unwrap();
+        assert!((x[0] - 1.0_f64).abs() < 1e-12);
+        assert!((x[1] - 1.0_f64).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::linear_algebra::dense;
+use crate::value::Value;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use std::cell::Cell;
+
+const MAX_ITERATIONS: usize = 200;
+
+/// Why [`least_squares_with_report`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StopReason {
+    /// A step reduced the cost or moved the parameters by less than the tolerances.
+    Converged,
+    /// No step reduced the cost any further even with the largest damping, which happens
+    /// when the minimum is reached to machine precision or the problem is degenerate.
+    Stalled,
+}
+
+/// The solution of [`least_squares_with_report`] with the diagnostics of the iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeastSquaresReport<V> {
+    pub parameters: Vec<V>,
+    /// The number of Levenberg-Marquardt iterations, i.e. of Jacobian evaluations.
+    pub iterations: usize,
+    /// The number of calls of the residuals.
+    pub evaluations: usize,
+    /// The sum of squared residuals at the solution.
+    pub cost: V,
+    pub stop_reason: StopReason,
+}
+
+/// Minimizes the sum of squared `residuals` over the parameters with the Levenberg-Marquardt algorithm,
+/// starting from `initial`.
+///
+/// The Jacobian is approximated by forward differences. The iteration stops once a step no longer reduces
+/// the cost by more than the relative machine precision or moves the parameters by more than the square root of it.
+///
+/// # Errors
+/// Returns an error if `residuals` fails, if there are fewer residuals than parameters,
+/// or if the iteration does not converge.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::optimization::least_squares;
+///
+/// // fit y = a * exp(b * x) to exact data
+/// let data: Vec<(f64, f64)> = (0..5).map(|i| (f64::from(i), 2.0 * (0.3 * f64::from(i)).exp())).collect();
+/// let fitted = least_squares(
+///     |p: &[f64]| Ok(data.iter().map(|&(x, y)| p[0] * (p[1] * x).exp() - y).collect()),
+///     &[1.0, 0.0],
+/// )
+/// .unwrap();
+/// assert!((fitted[0] - 2.0).abs() < 1e-6);
+/// assert!((fitted[1] - 0.3).abs() < 1e-6);
+/// ```
+pub fn least_squares<V: Value>(
+    residuals: impl Fn(&[V]) -> QLabResult<Vec<V>>,
+    initial: &[V],
+) -> QLabResult<Vec<V>> {
+    Ok(least_squares_with_report(residuals, initial)?.parameters)
+}
+
+/// Minimizes the sum of squared `residuals` as [`least_squares`] does and reports how the iteration went.
+///
+/// # Errors
+/// Returns an error if `residuals` fails, if there are fewer residuals than parameters,
+/// or if the iteration does not converge.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+pub fn least_squares_with_report<V: Value>(
+    residuals: impl Fn(&[V]) -> QLabResult<Vec<V>>,
+    initial: &[V],
+) -> QLabResult<LeastSquaresReport<V>> {
+    let evaluations = Cell::new(0);
+    let residuals = |parameters: &[V]| {
+        evaluations.set(evaluations.get() + 1);
+        residuals(parameters)
+    };
+    let cost = |r: &[V]| r.iter().fold(V::zero(), |acc, &x| acc + x * x);
+    let ten = V::from_u8(10).unwrap();
+    let mut parameters = initial.to_vec();
+    let mut current = residuals(&parameters)?;
+    if current.len() < parameters.len() {
+        return Err(InvalidInput(
+            format!(
+                "{} residuals cannot determine {} parameters",
+                current.len(),
+                parameters.len()
+            )
+            .into(),
+        )
+        .into());
+    }
+    let mut current_cost = cost(&current);
+    let mut damping = V::from_f64(1e-3).unwrap();
+    for iteration in 1..=MAX_ITERATIONS {
+        let report = |parameters, cost, stop_reason| LeastSquaresReport {
+            parameters,
+            iterations: iteration,
+            evaluations: evaluations.get(),
+            cost,
+            stop_reason,
+        };
+        let mut jacobian = Vec::with_capacity(parameters.len());
+        for j in 0..parameters.len() {
+            let step = V::epsilon().sqrt() * parameters[j].abs().max(V::one());
+            let mut bumped = parameters.clone();
+            bumped[j] += step;
+            let column: Vec<_> = residuals(&bumped)?
+                .iter()
+                .zip(&current)
+                .map(|(&up, &base)| (up - base) / step)
+                .collect();
+            jacobian.push(column);
+        }
+        let gradient: Vec<_> = jacobian
+            .iter()
+            .map(|column| dot(column, &current))
+            .collect();
+        let normal: Vec<Vec<V>> = jacobian
+            .iter()
+            .map(|a| jacobian.iter().map(|b| dot(a, b)).collect())
+            .collect();
+        loop {
+            let mut damped = normal.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += damping * normal[i][i].max(V::epsilon());
+            }
+            let step = dense::solve(damped, gradient.iter().map(|&g| -g).collect());
+            let Some(step) = step else {
+                damping *= ten;
+                continue;
+            };
+            let candidate: Vec<_> = parameters.iter().zip(&step).map(|(&p, &s)| p + s).collect();
+            let trial = residuals(&candidate)?;
+            let trial_cost = cost(&trial);
+            if trial_cost < current_cost {
+                let converged = current_cost - trial_cost <= V::epsilon() * current_cost
+                    || step
+                        .iter()
+                        .zip(&parameters)
+                        .all(|(&s, &p)| s.abs() <= V::epsilon().sqrt() * p.abs().max(V::one()));
+                parameters = candidate;
+                current = trial;
+                current_cost = trial_cost;
+                damping /= ten;
+                if converged {
+                    return Ok(report(parameters, current_cost, StopReason::Converged));
+                }
+                break;
+            }
+            damping *= ten;
+            if damping > V::one() / V::epsilon() {
+                // no step reduces the cost any further
+                return Ok(report(parameters, current_cost, StopReason::Stalled));
+            }
+        }
+    }
+    Err(InvalidInput("least squares did not converge".into()).into())
+}
+
+fn dot<V: Value>(a: &[V], b: &[V]) -> V {
+    a.iter().zip(b).fold(V::zero(), |acc, (&x, &y)| acc + x * y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rosenbrock() {
+        // the Rosenbrock function as the squared residuals (1 - x, 10 (y - x^2))
+        let fitted = least_squares(
+            |p: &[f64]| Ok(vec![1.0 - p[0], 10.0 * (p[1] - p[0] * p[0])]),
+            &[-1.2, 1.0],
+        )
+        .unwrap();
+        assert!((fitted[0] - 1.0).abs() < 1e-6);
+        assert!((fitted[1] - 1.0).abs() < 1e-6);
+        assert!(least_squares(|p: &[f64]| Ok(vec![p[0] + p[1]]), &[0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        let report =
+            least_squares_with_report(|p: &[f64]| Ok(vec![p[0] - 2.0, p[0] - 2.0]), &[0.0])
+                .unwrap();
+        assert!((report.parameters[0] - 2.0).abs() < 1e-9);
+        assert!(report.iterations >= 1);
+        // the initial residuals, a forward difference and a trial per iteration at least
+        assert!(report.evaluations > 2 * report.iterations);
+        assert!(report.cost < 1e-18);
+    }
+}
@@ -0,0 +1,91 @@
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+/// Calls `task` with every index below `len` on up to `threads` scoped threads.
+///
+/// Each idle thread claims the next unclaimed index, so the indices are handed out in increasing order
+/// but may complete in any order. Once a call returns [`ControlFlow::Break`], no further index is claimed.
+/// With a single thread the indices are processed in order on the calling thread.
+///
+/// # Panics
+/// Will propagate the panic of `task` raised on a worker thread.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::parallel::for_each_index;
+/// use std::ops::ControlFlow;
+/// use std::sync::Mutex;
+///
+/// let squares = Mutex::new(vec![0; 10]);
+/// for_each_index(10, 4, |i| {
+///     squares.lock().unwrap()[i] = i * i;
+///     ControlFlow::Continue(())
+/// });
+/// assert_eq!(squares.into_inner().unwrap()[9], 81);
+/// ```
+pub fn for_each_index<F>(len: usize, threads: usize, task: F)
+where
+    F: Fn(usize) -> ControlFlow<()> + Sync,
+{
+    let next_index = AtomicUsize::new(0);
+    let stopped = AtomicBool::new(false);
+    let (task, next_index, stopped) = (&task, &next_index, &stopped);
+    let worker = move || {
+        while !stopped.load(Ordering::Relaxed) {
+            let index = next_index.fetch_add(1, Ordering::Relaxed);
+            if index >= len {
+                return;
+            }
+            if task(index).is_break() {
+                stopped.store(true, Ordering::Relaxed);
+            }
+        }
+    };
+    if threads <= 1 {
+        worker();
+    } else {
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads.min(len)).map(|_| scope.spawn(worker)).collect();
+            for handle in handles {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_for_each_index() {
+        for threads in [1, 3, 16] {
+            let visited = Mutex::new(vec![0; 20]);
+            for_each_index(20, threads, |i| {
+                visited.lock().unwrap()[i] += 1;
+                ControlFlow::Continue(())
+            });
+            assert!(visited
+                .into_inner()
+                .unwrap()
+                .iter()
+                .all(|&count| count == 1));
+        }
+        let visited = Mutex::new(Vec::new());
+        for_each_index(20, 1, |i| {
+            visited.lock().unwrap().push(i);
+            if i == 4 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(visited.into_inner().unwrap(), [0, 1, 2, 3, 4]);
+        for_each_index(0, 4, |_| unreachable!());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod halton;
+pub mod xoshiro;
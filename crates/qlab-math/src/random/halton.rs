@@ -0,0 +1,77 @@
+/// A low-discrepancy Halton sequence.
+///
+/// The `i`-th coordinate of each point is the radical inverse of the point index in the base of the `i`-th prime.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::random::halton::Halton;
+///
+/// let halton = Halton::new(2);
+/// let mut point = [0.0; 2];
+/// halton.fill_point(1, &mut point);
+/// assert_eq!(point, [0.5, 1.0 / 3.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Halton {
+    bases: Vec<u64>,
+}
+
+impl Halton {
+    /// Creates a Halton sequence with points of the given dimension.
+    #[must_use]
+    pub fn new(dimension: usize) -> Self {
+        let mut bases = Vec::with_capacity(dimension);
+        let mut candidate = 2;
+        while bases.len() < dimension {
+            if bases.iter().all(|base| candidate % base != 0) {
+                bases.push(candidate);
+            }
+            candidate += 1;
+        }
+        Self { bases }
+    }
+
+    /// Returns the dimension of the points of the sequence.
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// Writes the point with the given index into `point`.
+    ///
+    /// The index `0` corresponds to the origin and is usually skipped.
+    /// Only the first `min(point.len(), dimension)` coordinates are written.
+    #[allow(clippy::cast_precision_loss)] // bases are small primes
+    pub fn fill_point(&self, index: u64, point: &mut [f64]) {
+        for (coordinate, &base) in point.iter_mut().zip(&self.bases) {
+            let inverse_base = 1.0 / base as f64;
+            let mut factor = inverse_base;
+            let mut value = 0.0;
+            let mut n = index;
+            while n > 0 {
+                value += (n % base) as f64 * factor;
+                n /= base;
+                factor *= inverse_base;
+            }
+            *coordinate = value;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_point() {
+        let halton = Halton::new(3);
+        assert_eq!(halton.dimension(), 3);
+        let mut point = [0.0; 3];
+        halton.fill_point(5, &mut point);
+        // 5 = 101 (base 2), 12 (base 3), 10 (base 5)
+        assert!((point[0] - 0.625).abs() < f64::EPSILON);
+        assert!((point[1] - 7.0 / 9.0).abs() < f64::EPSILON);
+        assert!((point[2] - 0.04).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,108 @@
+/// A pseudo-random number generator based on the xoshiro256++ algorithm of Blackman and Vigna.
+///
+/// The generator is deterministic: two generators created from the same seed and stream
+/// produce the same sequence on every platform.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::random::xoshiro::Xoshiro256PlusPlus;
+///
+/// let mut rng1 = Xoshiro256PlusPlus::from_seed_and_stream(42, 7);
+/// let mut rng2 = Xoshiro256PlusPlus::from_seed_and_stream(42, 7);
+/// assert_eq!(rng1.next_u64(), rng2.next_u64());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256PlusPlus {
+    state: [u64; 4],
+}
+
+fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Xoshiro256PlusPlus {
+    /// Creates a generator whose state is expanded from `seed` with `SplitMix64`.
+    #[must_use]
+    pub fn from_seed(seed: u64) -> Self {
+        let mut split_mix_state = seed;
+        let state = [
+            split_mix_64(&mut split_mix_state),
+            split_mix_64(&mut split_mix_state),
+            split_mix_64(&mut split_mix_state),
+            split_mix_64(&mut split_mix_state),
+        ];
+        Self { state }
+    }
+
+    /// Creates a generator for the sub-stream `stream` of `seed`.
+    ///
+    /// Sub-streams of the same seed are decorrelated by hashing the stream index into the seed,
+    /// which allows e.g. one stream per Monte Carlo path independently of how paths are scheduled.
+    #[must_use]
+    pub fn from_seed_and_stream(seed: u64, stream: u64) -> Self {
+        let mut hash_state = stream;
+        Self::from_seed(seed ^ split_mix_64(&mut hash_state))
+    }
+
+    /// Returns the next pseudo-random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[0]
+            .wrapping_add(self.state[3])
+            .rotate_left(23)
+            .wrapping_add(self.state[0]);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// Returns the next pseudo-random number uniformly distributed on the open interval (0, 1).
+    #[allow(clippy::cast_precision_loss)] // 53 bits fit in the mantissa
+    pub fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1_u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_sequence() {
+        // Reference values of the C implementation seeded with the state [1, 2, 3, 4].
+        let mut rng = Xoshiro256PlusPlus {
+            state: [1, 2, 3, 4],
+        };
+        assert_eq!(rng.next_u64(), 41_943_041);
+        assert_eq!(rng.next_u64(), 58_720_359);
+        assert_eq!(rng.next_u64(), 3_588_806_011_781_223);
+    }
+
+    #[test]
+    fn test_streams_differ() {
+        let mut rng1 = Xoshiro256PlusPlus::from_seed_and_stream(1, 0);
+        let mut rng2 = Xoshiro256PlusPlus::from_seed_and_stream(1, 1);
+        assert_ne!(rng1.next_u64(), rng2.next_u64());
+    }
+
+    #[test]
+    fn test_next_f64_in_open_interval() {
+        let mut rng = Xoshiro256PlusPlus::from_seed(0);
+        let mut sum = 0.0;
+        for _ in 0..10_000 {
+            let u = rng.next_f64();
+            assert!(u > 0.0 && u < 1.0);
+            sum += u;
+        }
+        assert!((sum / 10_000.0 - 0.5).abs() < 0.01);
+    }
+}
@@ -0,0 +1,341 @@
+use crate::value::Value;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+const MAX_ITERATIONS: usize = 200;
+
+/// Where to look for the root of a pricing function: an initial guess, the initial width of the bracket
+/// around it, and hard bounds of the domain which the bracket never crosses.
+///
+/// The presets hold the usual ranges of the quantities solved for by instruments,
+/// so that each solver does not repeat its own bracketing heuristics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolverDomain<V> {
+    guess: V,
+    step: V,
+    lower_bound: V,
+    upper_bound: V,
+}
+
+impl<V: Value> SolverDomain<V> {
+    /// Creates a domain bracketing from `guess - step` to `guess + step`, without bounds.
+    #[must_use]
+    pub fn new(guess: V, step: V) -> Self {
+        Self {
+            guess,
+            step: step.abs(),
+            lower_bound: V::min_value(),
+            upper_bound: V::max_value(),
+        }
+    }
+
+    /// A yield to maturity as a decimal, guessed at 5% and bounded between -99% and 1000%.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn yield_to_maturity() -> Self {
+        Self::new(V::from_f64(0.05).unwrap(), V::from_f64(0.01).unwrap())
+            .with_bounds(V::from_f64(-0.99).unwrap(), V::from_u8(10).unwrap())
+    }
+
+    /// A lognormal volatility, guessed at 20% and bounded between 0.01% and 1000%.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn volatility() -> Self {
+        Self::new(V::from_f64(0.2).unwrap(), V::from_f64(0.1).unwrap())
+            .with_bounds(V::from_f64(1e-4).unwrap(), V::from_u8(10).unwrap())
+    }
+
+    /// A spread over a curve or an index as a decimal, guessed at zero and bounded by ±100%.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn spread() -> Self {
+        Self::new(V::zero(), V::from_f64(0.001).unwrap()).with_bounds(-V::one(), V::one())
+    }
+
+    /// Replaces the initial guess. A guess outside the bounds is clamped into them, also when the bounds
+    /// are replaced afterwards.
+    #[must_use]
+    pub fn with_guess(mut self, guess: V) -> Self {
+        self.guess = guess;
+        self
+    }
+
+    /// Replaces the bounds of the domain.
+    #[must_use]
+    pub fn with_bounds(mut self, lower_bound: V, upper_bound: V) -> Self {
+        self.lower_bound = lower_bound;
+        self.upper_bound = upper_bound;
+        self
+    }
+
+    /// Returns the initial guess clamped into the bounds.
+    #[must_use]
+    pub fn guess(&self) -> V {
+        self.guess.max(self.lower_bound).min(self.upper_bound)
+    }
+
+    #[must_use]
+    pub fn bounds(&self) -> (V, V) {
+        (self.lower_bound, self.upper_bound)
+    }
+}
+
+/// Finds an interval on which `f` changes sign, expanding from the initial bracket of `domain`.
+///
+/// While `f` has the same sign at both ends, the end at which `|f|` is smaller, i.e. the one closer to
+/// the root of a monotone function, is moved outwards by 1.6 times the width of the bracket,
+/// stopping at the bounds of the domain.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the bounds are not ordered or no sign change is found within them,
+/// or an error if `f` fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+pub fn bracket<V: Value>(
+    f: impl Fn(V) -> QLabResult<V>,
+    domain: &SolverDomain<V>,
+) -> QLabResult<(V, V)> {
+    let (lower_bound, upper_bound) = domain.bounds();
+    if lower_bound >= upper_bound {
+        return Err(InvalidInput(
+            format!("the bounds {lower_bound:?} and {upper_bound:?} are not ordered").into(),
+        )
+        .into());
+    }
+    let guess = domain.guess();
+    let mut a = (guess - domain.step).max(lower_bound);
+    let mut b = (guess + domain.step).min(upper_bound);
+    if a >= b {
+        a = lower_bound;
+        b = upper_bound;
+    }
+    let (mut fa, mut fb) = (f(a)?, f(b)?);
+    let growth = V::from_f64(1.6).unwrap();
+    for _ in 0..MAX_ITERATIONS {
+        if fa * fb <= V::zero() {
+            return Ok((a, b));
+        }
+        let width = b - a;
+        let expand_lower = (fa.abs() < fb.abs() && a > lower_bound) || b >= upper_bound;
+        if expand_lower {
+            if a <= lower_bound {
+                break;
+            }
+            a = (a - growth * width).max(lower_bound);
+            fa = f(a)?;
+        } else {
+            b = (b + growth * width).min(upper_bound);
+            fb = f(b)?;
+        }
+    }
+    Err(InvalidInput(
+        format!("no root is bracketed between {lower_bound:?} and {upper_bound:?}").into(),
+    )
+    .into())
+}
+
+/// Finds a root of `f` in `[lower, upper]` with Brent's method, combining bisection with secant
+/// and inverse quadratic interpolation steps, to an absolute accuracy of `tolerance`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `f` has the same sign at both ends or the iteration does not converge,
+/// or an error if `f` fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+#[allow(clippy::many_single_char_names)] // named as in the algorithm
+pub fn brent<V: Value>(
+    f: impl Fn(V) -> QLabResult<V>,
+    lower: V,
+    upper: V,
+    tolerance: V,
+) -> QLabResult<V> {
+    let two = V::one() + V::one();
+    let three = two + V::one();
+    let half = V::from_f64(0.5).unwrap();
+    let (mut a, mut b) = (lower, upper);
+    let (mut fa, mut fb) = (f(a)?, f(b)?);
+    if fa * fb > V::zero() {
+        return Err(
+            InvalidInput(format!("f has the same sign at {lower:?} and {upper:?}").into()).into(),
+        );
+    }
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    for _ in 0..MAX_ITERATIONS {
+        if fb * fc > V::zero() {
+            (c, fc) = (a, fa);
+            d = b - a;
+            e = d;
+        }
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+        let tolerance = two * V::epsilon() * b.abs() + half * tolerance;
+        let m = half * (c - b);
+        if m.abs() <= tolerance || fb == V::zero() {
+            return Ok(b);
+        }
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            let s = fb / fa;
+            let (mut p, mut q) = if a == c {
+                (two * m * s, V::one() - s)
+            } else {
+                let q = fa / fc;
+                let r = fb / fc;
+                (
+                    s * (two * m * q * (q - r) - (b - a) * (r - V::one())),
+                    (q - V::one()) * (r - V::one()) * (s - V::one()),
+                )
+            };
+            if p > V::zero() {
+                q = -q;
+            } else {
+                p = -p;
+            }
+            if two * p < (three * m * q - (tolerance * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = m;
+                e = m;
+            }
+        } else {
+            d = m;
+            e = m;
+        }
+        (a, fa) = (b, fb);
+        b += if d.abs() > tolerance {
+            d
+        } else if m > V::zero() {
+            tolerance
+        } else {
+            -tolerance
+        };
+        fb = f(b)?;
+    }
+    Err(InvalidInput(
+        format!("Brent's method did not converge between {lower:?} and {upper:?}").into(),
+    )
+    .into())
+}
+
+/// Solves `f(x) = 0` for a monotone pricing function `f`, bracketing the root from `domain`
+/// with [`bracket`] and refining it with [`brent`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::root_finding::{solve, SolverDomain};
+///
+/// // the yield of a 5-year annual 4% bond priced at 95
+/// let price = |y: f64| (1..=5).map(|t| (if t == 5 { 104.0 } else { 4.0 }) / (1.0 + y).powi(t)).sum::<f64>();
+/// let ytm = solve(|y| Ok(price(y) - 95.0), &SolverDomain::yield_to_maturity(), 1e-12).unwrap();
+/// assert!((price(ytm) - 95.0).abs() < 1e-9);
+/// assert!(ytm > 0.04);
+/// ```
+///
+/// # Errors
+/// Returns an `InvalidInput` error if no root is found within the bounds of `domain`,
+/// or an error if `f` fails.
+pub fn solve<V: Value>(
+    f: impl Fn(V) -> QLabResult<V>,
+    domain: &SolverDomain<V>,
+    tolerance: V,
+) -> QLabResult<V> {
+    let (lower, upper) = bracket(&f, domain)?;
+    brent(f, lower, upper, tolerance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::implied_volatility::black_otm_price;
+
+    #[test]
+    fn test_solve() {
+        // a premium far from the initial bracket of the volatility
+        let premium = black_otm_price(100.0, 100.0, 1.5);
+        let vol: f64 = solve(
+            |vol| Ok(black_otm_price(100.0, 100.0, vol) - premium),
+            &SolverDomain::volatility(),
+            1e-12,
+        )
+        .unwrap();
+        assert!((vol - 1.5).abs() < 1e-9);
+
+        // a decreasing function of the spread
+        let spread = solve(
+            |s: f64| Ok((-5.0 * (0.03 + s)).exp() - 0.8),
+            &SolverDomain::spread(),
+            1e-14,
+        )
+        .unwrap();
+        assert!((spread - (-0.8_f64.ln() / 5.0 - 0.03)).abs() < 1e-12);
+
+        // no root within the bounds
+        assert!(solve(|x: f64| Ok(x * x + 1.0), &SolverDomain::spread(), 1e-12).is_err());
+        assert!(bracket(
+            |x: f64| Ok(x),
+            &SolverDomain::new(0.0, 1.0).with_bounds(1.0, -1.0)
+        )
+        .is_err());
+        assert!(brent(|x: f64| Ok(x - 2.0), 0.0, 1.0, 1e-12).is_err());
+    }
+
+    #[test]
+    fn test_guess_outside_bounds() {
+        let domain = SolverDomain::<f64>::spread().with_guess(5.0);
+        assert!((domain.guess() - 1.0).abs() < f64::EPSILON);
+        let domain = domain.with_bounds(-0.5, 0.5);
+        assert!((domain.guess() - 0.5).abs() < f64::EPSILON);
+        let domain = domain.with_bounds(-10.0, 10.0);
+        assert!((domain.guess() - 5.0).abs() < f64::EPSILON);
+
+        // the bracket starts from the clamped guess and still finds a root near the other bound
+        let domain = SolverDomain::new(0.0, 0.01)
+            .with_bounds(-1.0, 1.0)
+            .with_guess(-50.0);
+        let root = solve(|x: f64| Ok(x - 0.9), &domain, 1e-12).unwrap();
+        assert!((root - 0.9).abs() < 1e-10);
+        let (a, b) = bracket(|x: f64| Ok(x - 0.9), &domain).unwrap();
+        assert!((-1.0..=0.9).contains(&a) && (0.9..=1.0).contains(&b));
+    }
+
+    #[test]
+    fn test_root_at_bounds_and_failures() {
+        // a root on a bound of the domain
+        let domain = SolverDomain::new(0.5, 0.1).with_bounds(0.0, 1.0);
+        let root = solve(|x: f64| Ok(x - 1.0), &domain, 1e-12).unwrap();
+        assert!((root - 1.0).abs() < 1e-12);
+        let root = brent(|x: f64| Ok(x), 0.0, 1.0, 1e-12).unwrap();
+        assert!(root.abs() < 1e-12);
+
+        // a step wider than the domain brackets the whole domain
+        let domain = SolverDomain::new(0.0, 100.0).with_bounds(-1.0, 1.0);
+        assert_eq!(bracket(|x: f64| Ok(x), &domain).unwrap(), (-1.0, 1.0));
+
+        // errors of the function are propagated
+        let failing = |x: f64| {
+            if x > 0.5 {
+                Err(InvalidInput("out of range".into()).into())
+            } else {
+                Ok(x - 0.75)
+            }
+        };
+        assert!(solve(failing, &domain, 1e-12).is_err());
+        assert!(brent(failing, 0.0, 1.0, 1e-12).is_err());
+
+        // equal bounds are not ordered
+        assert!(bracket(|x: f64| Ok(x), &domain.with_bounds(0.0, 0.0)).is_err());
+    }
+}
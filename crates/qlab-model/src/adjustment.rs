@@ -0,0 +1,159 @@
+use qlab_math::value::Value;
+
+/// Calculates the drift correction of an asset under the measure of a foreign currency (quanto drift).
+///
+/// Under the quanto measure the asset drift is reduced by `correlation * asset_vol * fx_vol`.
+///
+/// # Arguments
+///
+/// * `asset_vol` - The lognormal volatility of the asset.
+/// * `fx_vol` - The lognormal volatility of the exchange rate, quoted as units of payment currency per unit of asset currency.
+/// * `correlation` - The correlation between the asset and the exchange rate.
+///
+/// # Returns
+///
+/// The drift correction to be added to the asset drift.
+#[must_use]
+pub fn quanto_drift_adjustment<V: Value>(asset_vol: V, fx_vol: V, correlation: V) -> V {
+    -correlation * asset_vol * fx_vol
+}
+
+/// Calculates the forward of an asset paid in a foreign currency at a fixed exchange rate.
+///
+/// # Arguments
+///
+/// * `forward` - The forward of the asset in its own currency.
+/// * `asset_vol` - The lognormal volatility of the asset.
+/// * `fx_vol` - The lognormal volatility of the exchange rate.
+/// * `correlation` - The correlation between the asset and the exchange rate.
+/// * `t` - The time to expiry in years.
+///
+/// # Returns
+///
+/// The quanto-adjusted forward `forward * exp(-correlation * asset_vol * fx_vol * t)`.
+#[must_use]
+pub fn quanto_forward<V: Value>(forward: V, asset_vol: V, fx_vol: V, correlation: V, t: V) -> V {
+    forward * (quanto_drift_adjustment(asset_vol, fx_vol, correlation) * t).exp()
+}
+
+/// Calculates the timing adjustment of a variable observed at `t` but paid at a later date.
+///
+/// The approximation assumes lognormal dynamics for both the variable and the forward rate
+/// between the observation and the payment dates.
+///
+/// # Arguments
+///
+/// * `forward` - The forward value of the variable for payment at its natural date.
+/// * `forward_vol` - The volatility of the variable.
+/// * `rate` - The forward rate from the observation date to the payment date.
+/// * `rate_vol` - The volatility of `rate`.
+/// * `correlation` - The correlation between the variable and `rate`.
+/// * `accrual` - The accrual fraction between the observation date and the payment date.
+/// * `t` - The time to the observation date in years.
+///
+/// # Returns
+///
+/// The adjustment to be added to `forward`, which is negative for a positive correlation.
+#[must_use]
+pub fn timing_adjustment<V: Value>(
+    forward: V,
+    forward_vol: V,
+    rate: V,
+    rate_vol: V,
+    correlation: V,
+    accrual: V,
+    t: V,
+) -> V {
+    -forward * correlation * forward_vol * rate_vol * rate * accrual * t
+        / (V::one() + rate * accrual)
+}
+
+/// Calculates the futures-to-forward convexity adjustment under the Ho-Lee model.
+///
+/// # Arguments
+///
+/// * `vol` - The normal volatility of the short rate.
+/// * `t1` - The time to the start of the underlying period of the futures.
+/// * `t2` - The time to the end of the underlying period of the futures.
+///
+/// # Returns
+///
+/// The adjustment `vol^2 * t1 * t2 / 2` to be subtracted from the continuously compounded futures rate.
+#[must_use]
+pub fn futures_convexity_ho_lee<V: Value>(vol: V, t1: V, t2: V) -> V {
+    vol * vol * t1 * t2 / (V::one() + V::one())
+}
+
+/// Calculates the futures-to-forward convexity adjustment under the Hull-White model.
+///
+/// Reduces to [`futures_convexity_ho_lee`] when `mean_reversion` is zero.
+///
+/// # Arguments
+///
+/// * `vol` - The normal volatility of the short rate.
+/// * `mean_reversion` - The mean reversion speed of the short rate.
+/// * `t1` - The time to the start of the underlying period of the futures.
+/// * `t2` - The time to the end of the underlying period of the futures.
+///
+/// # Returns
+///
+/// The adjustment to be subtracted from the continuously compounded futures rate.
+#[must_use]
+pub fn futures_convexity_hull_white<V: Value>(vol: V, mean_reversion: V, t1: V, t2: V) -> V {
+    if mean_reversion.is_zero() {
+        return futures_convexity_ho_lee(vol, t1, t2);
+    }
+    let two = V::one() + V::one();
+    let b = |t: V, s: V| (V::one() - (-mean_reversion * (s - t)).exp()) / mean_reversion;
+    let b12 = b(t1, t2);
+    let b01 = b(V::zero(), t1);
+    b12 / (t2 - t1)
+        * (b12 * (V::one() - (-two * mean_reversion * t1).exp()) + two * mean_reversion * b01 * b01)
+        * vol
+        * vol
+        / (two * two * mean_reversion)
+}
+
+/// Converts a continuously compounded futures rate into the corresponding forward rate.
+///
+/// # Arguments
+///
+/// * `futures_rate` - The continuously compounded rate implied by the futures price.
+/// * `convexity_adjustment` - The adjustment from one of the convexity models of this module.
+#[must_use]
+pub fn forward_rate_from_futures<V: Value>(futures_rate: V, convexity_adjustment: V) -> V {
+    futures_rate - convexity_adjustment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quanto_forward() {
+        let forward = quanto_forward(100.0, 0.2, 0.1, 0.5, 2.0);
+        assert!((forward - 100.0 * (-0.02_f64).exp()).abs() < 1e-12);
+        assert!((quanto_forward(100.0, 0.2, 0.1, 0.0, 2.0) - 100.0_f64).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_timing_adjustment() {
+        let adjustment = timing_adjustment(0.05, 0.2, 0.05, 0.2, 1.0, 0.5, 1.0);
+        assert!((adjustment + 0.05 * 0.04 * 0.05 * 0.5 / 1.025_f64).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_futures_convexity() {
+        // Hull, Options, Futures, and Other Derivatives: sigma = 1.2%, t1 = 8, t2 = 8.25
+        let ho_lee = futures_convexity_ho_lee(0.012, 8.0, 8.25);
+        assert!((ho_lee - 0.004_752_f64).abs() < 1e-12);
+        let hull_white = futures_convexity_hull_white(0.012, 0.0, 8.0, 8.25);
+        assert!((hull_white - ho_lee).abs() < f64::EPSILON);
+        let nearly_ho_lee = futures_convexity_hull_white(0.012, 1e-8, 8.0, 8.25);
+        assert!((nearly_ho_lee - ho_lee).abs() < 1e-9);
+        let mean_reverting = futures_convexity_hull_white(0.012, 0.1, 8.0, 8.25);
+        assert!(mean_reverting < ho_lee);
+        let forward = forward_rate_from_futures(0.05, ho_lee);
+        assert!((forward - 0.045_248_f64).abs() < 1e-12);
+    }
+}
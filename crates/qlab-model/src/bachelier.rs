@@ -0,0 +1,104 @@
+use crate::option_type::OptionType;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::distribution::normal;
+use qlab_math::implied_volatility;
+use qlab_math::value::Value;
+
+/// Calculates the undiscounted price of a vanilla option under the Bachelier (normal) model.
+///
+/// Unlike the Black model, forwards and strikes may be zero or negative.
+///
+/// # Arguments
+///
+/// * `option_type` - Call or put.
+/// * `forward` - The forward of the underlying.
+/// * `strike` - The strike of the option.
+/// * `vol` - The normal volatility, in the units of the forward per square root of a year.
+/// * `t` - The time to expiry in years.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::bachelier;
+/// use qlab_model::option_type::OptionType;
+///
+/// // an at-the-money option is worth vol * sqrt(t) / sqrt(2 * pi)
+/// let price = bachelier::price(OptionType::Call, -0.001, -0.001, 0.01, 1.0);
+/// assert!((price - 0.003_989_422_804_014_327_f64).abs() < 1e-15);
+/// ```
+#[must_use]
+pub fn price<V: Value>(option_type: OptionType, forward: V, strike: V, vol: V, t: V) -> V {
+    let sign = option_type.sign::<V>();
+    let moneyness = sign * (forward - strike);
+    let std_dev = vol * t.sqrt();
+    if std_dev <= V::zero() {
+        return moneyness.max(V::zero());
+    }
+    let d = moneyness / std_dev;
+    moneyness * normal::cdf(d) + std_dev * normal::pdf(d)
+}
+
+/// Calculates the sensitivity of the undiscounted Bachelier price to the normal volatility.
+#[must_use]
+pub fn vega<V: Value>(forward: V, strike: V, vol: V, t: V) -> V {
+    let sqrt_t = t.sqrt();
+    let std_dev = vol * sqrt_t;
+    if std_dev <= V::zero() {
+        return V::zero();
+    }
+    sqrt_t * normal::pdf((forward - strike) / std_dev)
+}
+
+/// Calculates the normal volatility that reproduces the undiscounted price `premium`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `t` is not positive or `premium` is below the intrinsic value.
+pub fn implied_vol<V: Value>(
+    option_type: OptionType,
+    forward: V,
+    strike: V,
+    t: V,
+    premium: V,
+) -> QLabResult<V> {
+    if t <= V::zero() {
+        return Err(InvalidInput(format!("t: {t:?} must be positive").into()).into());
+    }
+    let intrinsic = (option_type.sign::<V>() * (forward - strike)).max(V::zero());
+    if premium < intrinsic {
+        return Err(InvalidInput(
+            format!("premium: {premium:?} is below the intrinsic value: {intrinsic:?}").into(),
+        )
+        .into());
+    }
+    if premium == intrinsic {
+        return Ok(V::zero());
+    }
+    // by put-call parity the time value is the price of the out-of-the-money option,
+    // which avoids the cancellation of inverting a deep in-the-money price
+    Ok(implied_volatility::bachelier_std_dev(forward, strike, premium - intrinsic)? / t.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_call_parity() {
+        let call = price(OptionType::Call, 0.01, 0.015, 0.008, 3.0);
+        let put = price(OptionType::Put, 0.01, 0.015, 0.008, 3.0);
+        assert!((call - put + 0.005_f64).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_implied_vol() {
+        for strike in [-0.02, -0.005, 0.0, 0.01, 0.05] {
+            for option_type in [OptionType::Call, OptionType::Put] {
+                let premium = price(option_type, -0.003, strike, 0.0075, 2.0);
+                let vol = implied_vol(option_type, -0.003, strike, 2.0, premium).unwrap();
+                assert!((vol - 0.0075_f64).abs() < 1e-10, "{option_type:?} {strike}");
+            }
+        }
+        assert!(implied_vol(OptionType::Call, 0.02, 0.01, 1.0, 0.005).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::option_type::OptionType;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::distribution::normal;
+use qlab_math::implied_volatility;
+use qlab_math::value::Value;
+
+fn intrinsic<V: Value>(option_type: OptionType, forward: V, strike: V) -> V {
+    (option_type.sign::<V>() * (forward - strike)).max(V::zero())
+}
+
+/// Calculates the undiscounted price of a vanilla option under the Black (lognormal) model.
+///
+/// # Arguments
+///
+/// * `option_type` - Call or put.
+/// * `forward` - The forward of the underlying, which must be positive.
+/// * `strike` - The strike of the option, which must be positive.
+/// * `vol` - The lognormal volatility.
+/// * `t` - The time to expiry in years.
+///
+/// # Returns
+///
+/// The forward premium of the option; multiply by the discount factor to obtain the present value.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::black;
+/// use qlab_model::option_type::OptionType;
+///
+/// let price = black::price(OptionType::Call, 100.0, 100.0, 0.2, 1.0);
+/// assert!((price - 7.965_567_455_405_804_f64).abs() < 1e-12);
+/// ```
+#[must_use]
+pub fn price<V: Value>(option_type: OptionType, forward: V, strike: V, vol: V, t: V) -> V {
+    let std_dev = vol * t.sqrt();
+    if std_dev <= V::zero() {
+        return intrinsic(option_type, forward, strike);
+    }
+    let sign = option_type.sign::<V>();
+    let d1 = (forward / strike).ln() / std_dev + std_dev / (V::one() + V::one());
+    let d2 = d1 - std_dev;
+    sign * (forward * normal::cdf(sign * d1) - strike * normal::cdf(sign * d2))
+}
+
+/// Calculates the undiscounted price of a vanilla option under the shifted Black model,
+/// in which `forward + shift` follows a lognormal process.
+#[must_use]
+pub fn shifted_price<V: Value>(
+    option_type: OptionType,
+    forward: V,
+    strike: V,
+    vol: V,
+    t: V,
+    shift: V,
+) -> V {
+    price(option_type, forward + shift, strike + shift, vol, t)
+}
+
+/// Calculates the sensitivity of the undiscounted Black price to the volatility.
+#[must_use]
+pub fn vega<V: Value>(forward: V, strike: V, vol: V, t: V) -> V {
+    let sqrt_t = t.sqrt();
+    let std_dev = vol * sqrt_t;
+    if std_dev <= V::zero() {
+        return V::zero();
+    }
+    let d1 = (forward / strike).ln() / std_dev + std_dev / (V::one() + V::one());
+    forward * normal::pdf(d1) * sqrt_t
+}
+
+/// Calculates the lognormal volatility that reproduces the undiscounted price `premium`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if the forward, strike or time are not positive, or if
+/// `premium` lies outside the no-arbitrage bounds of the Black model.
+pub fn implied_vol<V: Value>(
+    option_type: OptionType,
+    forward: V,
+    strike: V,
+    t: V,
+    premium: V,
+) -> QLabResult<V> {
+    if forward <= V::zero() || strike <= V::zero() || t <= V::zero() {
+        return Err(InvalidInput(
+            format!("forward: {forward:?}, strike: {strike:?} and t: {t:?} must be positive")
+                .into(),
+        )
+        .into());
+    }
+    let lower_bound = intrinsic(option_type, forward, strike);
+    let upper_bound = match option_type {
+        OptionType::Call => forward,
+        OptionType::Put => strike,
+    };
+    if premium < lower_bound || premium >= upper_bound {
+        return Err(InvalidInput(
+            format!("premium: {premium:?} must lie in [{lower_bound:?}, {upper_bound:?})").into(),
+        )
+        .into());
+    }
+    if premium == lower_bound {
+        return Ok(V::zero());
+    }
+    // by put-call parity the time value is the price of the out-of-the-money option,
+    // which avoids the cancellation of inverting a deep in-the-money price
+    Ok(implied_volatility::black_std_dev(forward, strike, premium - lower_bound)? / t.sqrt())
+}
+
+/// Calculates the shifted lognormal volatility that reproduces the undiscounted price `premium`.
+///
+/// # Errors
+/// Returns an error under the same conditions as [`implied_vol`] applied to the shifted forward and strike.
+pub fn shifted_implied_vol<V: Value>(
+    option_type: OptionType,
+    forward: V,
+    strike: V,
+    t: V,
+    shift: V,
+    premium: V,
+) -> QLabResult<V> {
+    implied_vol(option_type, forward + shift, strike + shift, t, premium)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_call_parity() {
+        let call = price(OptionType::Call, 105.0, 100.0, 0.25, 2.0);
+        let put = price(OptionType::Put, 105.0, 100.0, 0.25, 2.0);
+        assert!((call - put - 5.0_f64).abs() < 1e-12);
+        let expired = price(OptionType::Put, 95.0, 100.0, 0.25, 0.0);
+        assert!((expired - 5.0_f64).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_implied_vol() {
+        for (option_type, strike) in [
+            (OptionType::Call, 50.0),
+            (OptionType::Call, 100.0),
+            (OptionType::Put, 100.0),
+            (OptionType::Put, 180.0),
+        ] {
+            let premium = price(option_type, 100.0, strike, 0.3, 1.5);
+            let vol = implied_vol(option_type, 100.0, strike, 1.5, premium).unwrap();
+            assert!((vol - 0.3_f64).abs() < 1e-9, "{option_type:?} {strike}");
+        }
+        assert!(implied_vol(OptionType::Call, 100.0, 100.0, 1.0, 100.0).is_err());
+        assert!(implied_vol(OptionType::Call, 100.0, 90.0, 1.0, 5.0).is_err());
+    }
+
+    #[test]
+    fn test_shifted() {
+        let premium = shifted_price(OptionType::Call, -0.002, 0.0, 0.3, 1.0, 0.03);
+        let vol = shifted_implied_vol(OptionType::Call, -0.002, 0.0, 1.0, 0.03, premium).unwrap();
+        assert!((vol - 0.3_f64).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::adjustment::timing_adjustment;
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+
+/// The market inputs of a constant maturity swap (CMS) rate observed at a single fixing date.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CmsRateInputs<V> {
+    /// The forward rate of the underlying swap.
+    pub forward_swap_rate: V,
+    /// The lognormal volatility of the underlying swap rate, e.g. taken from the swaption at-the-money vol.
+    pub swap_rate_vol: V,
+    /// The time to the fixing date in years.
+    pub time_to_fixing: V,
+    /// The tenor of the underlying swap in years.
+    pub swap_tenor: u32,
+    /// The number of fixed-leg payments per year of the underlying swap.
+    pub payments_per_year: u32,
+}
+
+impl<V: Value> CmsRateInputs<V> {
+    /// Calculates the first and second derivatives with respect to the yield of the price of a bond
+    /// whose coupon is the forward swap rate, evaluated at the forward swap rate.
+    fn bond_price_derivatives(&self) -> QLabResult<(V, V)> {
+        if self.swap_tenor == 0 || self.payments_per_year == 0 {
+            return Err(InvalidInput(
+                format!(
+                    "swap_tenor: {} and payments_per_year: {} must be positive",
+                    self.swap_tenor, self.payments_per_year
+                )
+                .into(),
+            )
+            .into());
+        }
+        let cast = |n: u32| V::from_u32(n).ok_or_else(|| CastNumberError(format!("{n}").into()));
+        let frequency = cast(self.payments_per_year)?;
+        let num_payments = self.swap_tenor * self.payments_per_year;
+        let coupon = self.forward_swap_rate / frequency;
+        let discount = (V::one() + self.forward_swap_rate / frequency).recip();
+        let mut first = V::zero();
+        let mut second = V::zero();
+        for i in 1..=num_payments {
+            let index = cast(i)?;
+            let cash_flow = if i == num_payments {
+                coupon + V::one()
+            } else {
+                coupon
+            };
+            let discounted = cash_flow * discount.powi(i32::try_from(i).unwrap_or(i32::MAX));
+            first -= index / frequency * discounted * discount;
+            second += index * (index + V::one()) / (frequency * frequency)
+                * discounted
+                * discount
+                * discount;
+        }
+        Ok((first, second))
+    }
+
+    /// Calculates the convexity adjustment of the CMS rate over the forward swap rate.
+    ///
+    /// Uses the yield-based approximation `-0.5 * y^2 * vol^2 * t * G''(y) / G'(y)`, where `G` is the
+    /// price of a bond paying the forward swap rate `y` as its coupon.
+    ///
+    /// # Errors
+    /// Returns an error if the swap tenor or the payment frequency is zero.
+    pub fn convexity_adjustment(&self) -> QLabResult<V> {
+        let (first, second) = self.bond_price_derivatives()?;
+        let y = self.forward_swap_rate;
+        Ok(
+            -y * y * self.swap_rate_vol * self.swap_rate_vol * self.time_to_fixing * second
+                / first
+                / (V::one() + V::one()),
+        )
+    }
+
+    /// Calculates the expected CMS rate under the measure of its natural payment date.
+    ///
+    /// # Errors
+    /// Returns an error if the swap tenor or the payment frequency is zero.
+    pub fn cms_rate(&self) -> QLabResult<V> {
+        Ok(self.forward_swap_rate + self.convexity_adjustment()?)
+    }
+}
+
+/// A coupon paying `gearing * CMS rate + spread` on a notional over an accrual period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CmsCoupon<V> {
+    pub notional: V,
+    pub accrual: V,
+    pub gearing: V,
+    pub spread: V,
+}
+
+/// The timing adjustment inputs of a CMS coupon paid after the natural payment date of the rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaymentDelay<V> {
+    /// The forward rate from the fixing date to the payment date.
+    pub rate: V,
+    /// The volatility of `rate`.
+    pub rate_vol: V,
+    /// The correlation between the swap rate and `rate`.
+    pub correlation: V,
+    /// The accrual fraction of `rate`.
+    pub accrual: V,
+}
+
+impl<V: Value> CmsCoupon<V> {
+    /// Calculates the expected coupon rate including the convexity adjustment and, if the coupon is paid
+    /// later than at its fixing, the timing adjustment.
+    ///
+    /// # Errors
+    /// Returns an error if the swap tenor or the payment frequency of `inputs` is zero.
+    pub fn adjusted_rate(
+        &self,
+        inputs: &CmsRateInputs<V>,
+        payment_delay: Option<&PaymentDelay<V>>,
+    ) -> QLabResult<V> {
+        let mut rate = inputs.cms_rate()?;
+        if let Some(delay) = payment_delay {
+            rate += timing_adjustment(
+                rate,
+                inputs.swap_rate_vol,
+                delay.rate,
+                delay.rate_vol,
+                delay.correlation,
+                delay.accrual,
+                inputs.time_to_fixing,
+            );
+        }
+        Ok(self.gearing * rate + self.spread)
+    }
+
+    /// Calculates the present value of the coupon.
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - The market inputs of the CMS rate.
+    /// * `payment_delay` - The timing adjustment inputs, if the coupon is paid after its natural date.
+    /// * `discount_factor` - The discount factor to the payment date.
+    ///
+    /// # Errors
+    /// Returns an error if the swap tenor or the payment frequency of `inputs` is zero.
+    pub fn present_value(
+        &self,
+        inputs: &CmsRateInputs<V>,
+        payment_delay: Option<&PaymentDelay<V>>,
+        discount_factor: V,
+    ) -> QLabResult<V> {
+        Ok(self.notional
+            * self.accrual
+            * self.adjusted_rate(inputs, payment_delay)?
+            * discount_factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs() -> CmsRateInputs<f64> {
+        CmsRateInputs {
+            forward_swap_rate: 0.05,
+            swap_rate_vol: 0.2,
+            time_to_fixing: 5.0,
+            swap_tenor: 10,
+            payments_per_year: 1,
+        }
+    }
+
+    #[test]
+    fn test_bond_price_derivatives() {
+        // compare with central finite differences of the bond price
+        let inputs = inputs();
+        let price = |y: f64| {
+            (1..=10)
+                .map(|i| {
+                    let cash_flow = if i == 10 { 1.05 } else { 0.05 };
+                    cash_flow / (1.0 + y).powi(i)
+                })
+                .sum::<f64>()
+        };
+        let h = 1e-4;
+        let (first, second) = inputs.bond_price_derivatives().unwrap();
+        let first_difference = (price(0.05 + h) - price(0.05 - h)) / (2.0 * h);
+        let second_difference = (price(0.05 + h) - 2.0 * price(0.05) + price(0.05 - h)) / (h * h);
+        assert!((first / first_difference - 1.0).abs() < 1e-6);
+        assert!((second / second_difference - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_cms_rate() {
+        let inputs = inputs();
+        let adjustment = inputs.convexity_adjustment().unwrap();
+        assert!(adjustment > 0.0 && adjustment < 0.01);
+        let zero_vol = CmsRateInputs {
+            swap_rate_vol: 0.0,
+            ..inputs
+        };
+        assert!((zero_vol.cms_rate().unwrap() - 0.05).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_coupon() {
+        let inputs = inputs();
+        let coupon = CmsCoupon {
+            notional: 1_000_000.0,
+            accrual: 1.0,
+            gearing: 1.0,
+            spread: 0.001,
+        };
+        let undelayed = coupon.adjusted_rate(&inputs, None).unwrap();
+        assert!((undelayed - inputs.cms_rate().unwrap() - 0.001).abs() < f64::EPSILON);
+        let delay = PaymentDelay {
+            rate: 0.05,
+            rate_vol: 0.2,
+            correlation: 0.8,
+            accrual: 1.0,
+        };
+        let delayed = coupon.adjusted_rate(&inputs, Some(&delay)).unwrap();
+        assert!(delayed < undelayed);
+        let pv = coupon.present_value(&inputs, None, 0.8).unwrap();
+        assert!((pv - 800_000.0 * undelayed).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,11 @@
+pub mod adjustment;
+pub mod bachelier;
+pub mod black;
+pub mod cms;
+pub mod monte_carlo;
+pub mod multi_asset;
+pub mod option_type;
+pub mod pricing_results;
+pub mod short_rate;
+pub mod vol_conversion;
+pub mod vol_surface;
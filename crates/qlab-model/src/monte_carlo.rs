@@ -0,0 +1,470 @@
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::{QLabError, QLabResult};
+use qlab_math::distribution::normal;
+use qlab_math::parallel::for_each_index;
+use qlab_math::random::halton::Halton;
+use qlab_math::random::xoshiro::Xoshiro256PlusPlus;
+use qlab_math::value::Value;
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Configuration of a Monte Carlo simulation.
+///
+/// Every path draws its random numbers from its own sub-stream of `seed` (or its own point of the
+/// quasi-random sequence), and paths are grouped into chunks of `chunk_size`. Idle threads take the
+/// next unsimulated chunk, and the statistics of the chunks are merged in chunk order.
+/// The result therefore depends on `seed`, `num_paths`, `antithetic`, `quasi_random`, `chunk_size`
+/// and `target_std_error`, but is bit-for-bit identical for any number of `threads`.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::monte_carlo::{simulate, SimulationConfig};
+///
+/// let config = SimulationConfig::new(42, 10_000).with_antithetic(true);
+/// let statistics = simulate::<f64, _>(&config, 1, |z| z[0] * z[0]).unwrap();
+/// assert!((statistics.mean() - 1.0).abs() < 5.0 * statistics.std_error());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    pub num_paths: usize,
+    pub antithetic: bool,
+    pub quasi_random: bool,
+    pub chunk_size: usize,
+    pub threads: usize,
+    pub target_std_error: Option<f64>,
+}
+
+impl SimulationConfig {
+    /// Creates a sequential pseudo-random configuration without variance reduction.
+    #[must_use]
+    pub fn new(seed: u64, num_paths: usize) -> Self {
+        Self {
+            seed,
+            num_paths,
+            antithetic: false,
+            quasi_random: false,
+            chunk_size: 4096,
+            threads: 1,
+            target_std_error: None,
+        }
+    }
+
+    /// Enables or disables antithetic variates.
+    ///
+    /// With antithetic variates, paths `2k` and `2k + 1` use opposite normal draws and their average counts as one sample.
+    #[must_use]
+    pub fn with_antithetic(mut self, antithetic: bool) -> Self {
+        self.antithetic = antithetic;
+        self
+    }
+
+    /// Switches between pseudo-random numbers and the Halton sequence.
+    ///
+    /// The quasi-random sequence is deterministic, so `seed` is not used when it is enabled.
+    #[must_use]
+    pub fn with_quasi_random(mut self, quasi_random: bool) -> Self {
+        self.quasi_random = quasi_random;
+        self
+    }
+
+    /// Sets the number of paths per chunk and the number of threads the chunks are distributed over.
+    #[must_use]
+    pub fn with_chunking(mut self, chunk_size: usize, threads: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self.threads = threads;
+        self
+    }
+
+    /// Stops the simulation after the first chunk at which the standard error of the merged chunks falls to
+    /// `target_std_error`, so that `num_paths` becomes an upper bound on the number of paths.
+    #[must_use]
+    pub fn with_target_std_error(mut self, target_std_error: f64) -> Self {
+        self.target_std_error = Some(target_std_error);
+        self
+    }
+
+    fn validate(&self) -> QLabResult<()> {
+        if self.num_paths == 0 || self.chunk_size == 0 || self.threads == 0 {
+            return Err(InvalidInput(
+                format!(
+                    "num_paths: {}, chunk_size: {} and threads: {} must be positive",
+                    self.num_paths, self.chunk_size, self.threads
+                )
+                .into(),
+            )
+            .into());
+        }
+        if self.antithetic
+            && (!self.num_paths.is_multiple_of(2) || !self.chunk_size.is_multiple_of(2))
+        {
+            return Err(InvalidInput(
+                format!(
+                    "num_paths: {} and chunk_size: {} must be even for antithetic variates",
+                    self.num_paths, self.chunk_size
+                )
+                .into(),
+            )
+            .into());
+        }
+        if let Some(target) = self.target_std_error {
+            if target.is_nan() || target <= 0.0 {
+                return Err(InvalidInput(
+                    format!("target_std_error: {target} must be positive").into(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Summary statistics of the samples of a simulation, accumulated with Welford's algorithm
+/// so that no sample has to be stored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics<V> {
+    count: usize,
+    mean: V,
+    sum_of_squared_deviations: V,
+}
+
+impl<V: Value> Statistics<V> {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            mean: V::zero(),
+            sum_of_squared_deviations: V::zero(),
+        }
+    }
+
+    fn add(&mut self, sample: V) {
+        self.count += 1;
+        let delta = sample - self.mean;
+        self.mean += delta / self.count_as_value();
+        self.sum_of_squared_deviations += delta * (sample - self.mean);
+    }
+
+    /// Merges the statistics of disjoint samples by the pairwise update of Chan et al.
+    fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+        let count = self.count_as_value();
+        let other_count = other.count_as_value();
+        self.count += other.count;
+        let total = self.count_as_value();
+        let delta = other.mean - self.mean;
+        self.mean += delta * other_count / total;
+        self.sum_of_squared_deviations +=
+            other.sum_of_squared_deviations + delta * delta * count * other_count / total;
+    }
+
+    fn count_as_value(&self) -> V {
+        V::from_usize(self.count).unwrap()
+    }
+
+    /// Returns the number of samples.
+    ///
+    /// With antithetic variates a sample is the average of a pair of paths.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the sample mean, which is zero without samples.
+    #[must_use]
+    pub fn mean(&self) -> V {
+        self.mean
+    }
+
+    /// Returns the unbiased sample variance, which is zero for less than two samples.
+    ///
+    /// # Panics
+    /// Will panic if the sample count cannot be cast to `V`.
+    #[must_use]
+    pub fn variance(&self) -> V {
+        if self.count < 2 {
+            return V::zero();
+        }
+        (self.sum_of_squared_deviations / (self.count_as_value() - V::one())).max(V::zero())
+    }
+
+    /// Returns the standard error of the sample mean, which is zero without samples.
+    ///
+    /// # Panics
+    /// Will panic if the sample count cannot be cast to `V`.
+    #[must_use]
+    pub fn std_error(&self) -> V {
+        if self.count == 0 {
+            return V::zero();
+        }
+        (self.variance() / self.count_as_value()).sqrt()
+    }
+}
+
+struct PathGenerator {
+    seed: u64,
+    halton: Option<Halton>,
+    uniforms: Vec<f64>,
+}
+
+impl PathGenerator {
+    fn new(config: &SimulationConfig, dimension: usize) -> Self {
+        Self {
+            seed: config.seed,
+            halton: config.quasi_random.then(|| Halton::new(dimension)),
+            uniforms: vec![0.0; dimension],
+        }
+    }
+
+    fn fill_normals<V: Value>(&mut self, stream: u64, normals: &mut [V]) -> QLabResult<()> {
+        if let Some(halton) = &self.halton {
+            // the origin of the Halton sequence maps to -inf and is skipped
+            halton.fill_point(stream + 1, &mut self.uniforms);
+        } else {
+            let mut rng = Xoshiro256PlusPlus::from_seed_and_stream(self.seed, stream);
+            for uniform in &mut self.uniforms {
+                *uniform = rng.next_f64();
+            }
+        }
+        for (normal, &uniform) in normals.iter_mut().zip(&self.uniforms) {
+            *normal = V::from_f64(normal::inverse_cdf(uniform))
+                .ok_or_else(|| CastNumberError(format!("{uniform}").into()))?;
+        }
+        Ok(())
+    }
+}
+
+fn simulate_chunk<V: Value, F: Fn(&[V]) -> V>(
+    config: &SimulationConfig,
+    dimension: usize,
+    payoff: &F,
+    first_path: usize,
+    last_path: usize,
+) -> QLabResult<Statistics<V>> {
+    let mut generator = PathGenerator::new(config, dimension);
+    let mut normals = vec![V::zero(); dimension];
+    let mut statistics = Statistics::new();
+    let mut path = first_path;
+    while path < last_path {
+        generator.fill_normals(path as u64, &mut normals)?;
+        if config.antithetic {
+            let value = payoff(&normals);
+            for normal in &mut normals {
+                *normal = -*normal;
+            }
+            let antithetic_value = payoff(&normals);
+            statistics.add((value + antithetic_value) / (V::one() + V::one()));
+            path += 2;
+        } else {
+            statistics.add(payoff(&normals));
+            path += 1;
+        }
+    }
+    Ok(statistics)
+}
+
+/// Runs a Monte Carlo simulation of `payoff` under `config`.
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the simulation.
+/// * `dimension` - The number of independent standard normal draws per path.
+/// * `payoff` - A function mapping the normal draws of a path to its (discounted) payoff.
+///
+/// When `config` has a target standard error, the simulation stops after the first chunk at which the
+/// statistics of all chunks up to it reach the target; chunks simulated beyond it by other threads are discarded.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `config` is inconsistent, e.g. an odd number of paths with antithetic variates.
+///
+/// # Panics
+/// Will propagate the panic of `payoff` raised on a worker thread.
+pub fn simulate<V, F>(
+    config: &SimulationConfig,
+    dimension: usize,
+    payoff: F,
+) -> QLabResult<Statistics<V>>
+where
+    V: Value + Send,
+    F: Fn(&[V]) -> V + Sync,
+{
+    config.validate()?;
+    let num_chunks = config.num_paths.div_ceil(config.chunk_size);
+    let target = config
+        .target_std_error
+        .map(|target| {
+            V::from_f64(target).ok_or_else(|| CastNumberError(format!("{target}").into()))
+        })
+        .transpose()?;
+    let progress = Mutex::new(Progress {
+        statistics: Statistics::new(),
+        merged_chunks: 0,
+        pending: BTreeMap::new(),
+        finished: false,
+        target,
+        error: None,
+    });
+    let (payoff, shared) = (&payoff, &progress);
+    for_each_index(num_chunks, config.threads, |chunk| {
+        if lock(shared).finished {
+            return ControlFlow::Break(());
+        }
+        let first_path = chunk * config.chunk_size;
+        let last_path = (first_path + config.chunk_size).min(config.num_paths);
+        let statistics = simulate_chunk(config, dimension, payoff, first_path, last_path);
+        let mut progress = lock(shared);
+        match statistics {
+            Ok(statistics) => {
+                progress.pending.insert(chunk, statistics);
+                progress.merge_in_order();
+            }
+            Err(err) => {
+                progress.finished = true;
+                progress.error.get_or_insert(err);
+            }
+        }
+        if progress.finished {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    let progress = progress
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner);
+    match progress.error {
+        Some(err) => Err(err),
+        None => Ok(progress.statistics),
+    }
+}
+
+/// The statistics merged so far by the workers of a simulation.
+struct Progress<V> {
+    statistics: Statistics<V>,
+    merged_chunks: usize,
+    /// The statistics of chunks finished before an earlier chunk, by chunk index.
+    pending: BTreeMap<usize, Statistics<V>>,
+    finished: bool,
+    /// The standard error at which the simulation stops.
+    target: Option<V>,
+    /// The error of the first chunk that failed to simulate.
+    error: Option<QLabError>,
+}
+
+impl<V: Value> Progress<V> {
+    /// Merges the pending chunks that follow the merged ones, stopping once the standard error reaches the target.
+    fn merge_in_order(&mut self) {
+        while !self.finished {
+            let Some(statistics) = self.pending.remove(&self.merged_chunks) else {
+                return;
+            };
+            self.statistics.merge(&statistics);
+            self.merged_chunks += 1;
+            if self.target.is_some_and(|target| {
+                self.statistics.count() > 1 && self.statistics.std_error() <= target
+            }) {
+                self.finished = true;
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // a worker panicking while holding the lock is propagated by the scope
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call_payoff(z: &[f64]) -> f64 {
+        let spot = 100.0 * (-0.5 * 0.04 + 0.2 * z[0]).exp();
+        (spot - 100.0).max(0.0)
+    }
+
+    #[test]
+    fn test_reproducible_across_threads() {
+        let config = SimulationConfig::new(7, 20_001).with_chunking(1000, 1);
+        let sequential = simulate(&config, 1, call_payoff).unwrap();
+        for threads in [2, 3, 8, 64] {
+            let parallel = simulate(&config.with_chunking(1000, threads), 1, call_payoff).unwrap();
+            assert_eq!(sequential, parallel);
+        }
+        let rerun = simulate(&config, 1, call_payoff).unwrap();
+        assert_eq!(sequential, rerun);
+        let other_seed = simulate(&SimulationConfig { seed: 8, ..config }, 1, call_payoff).unwrap();
+        assert_ne!(sequential, other_seed);
+    }
+
+    #[test]
+    fn test_black_scholes_call() {
+        // Black-Scholes price with zero rates, 20% vol and 1y expiry
+        let expected = 7.965_567_455_405_804;
+        let pseudo = SimulationConfig::new(1, 100_000).with_antithetic(true);
+        let statistics = simulate(&pseudo, 1, call_payoff).unwrap();
+        assert_eq!(statistics.count(), 50_000);
+        assert!((statistics.mean() - expected).abs() < 3.0 * statistics.std_error());
+
+        let quasi = SimulationConfig::new(1, 100_000)
+            .with_quasi_random(true)
+            .with_chunking(1024, 4);
+        let statistics = simulate(&quasi, 1, call_payoff).unwrap();
+        assert!((statistics.mean() - expected).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let config = SimulationConfig::new(1, 11).with_antithetic(true);
+        assert!(simulate(&config, 1, call_payoff).is_err());
+        let config = SimulationConfig::new(1, 10).with_chunking(10, 0);
+        assert!(simulate(&config, 1, call_payoff).is_err());
+        let config = SimulationConfig::new(1, 10).with_target_std_error(0.0);
+        assert!(simulate(&config, 1, call_payoff).is_err());
+    }
+
+    #[test]
+    fn test_target_std_error() {
+        let config = SimulationConfig::new(3, 1_000_000)
+            .with_chunking(1000, 1)
+            .with_target_std_error(0.1);
+        let statistics = simulate(&config, 1, call_payoff).unwrap();
+        assert!(statistics.std_error() <= 0.1);
+        assert!(statistics.count() < 1_000_000 && statistics.count().is_multiple_of(1000));
+        // one chunk less would not have reached the target
+        let shorter = SimulationConfig::new(3, statistics.count() - 1000).with_chunking(1000, 1);
+        assert!(simulate(&shorter, 1, call_payoff).unwrap().std_error() > 0.1);
+        for threads in [2, 7] {
+            let parallel = simulate(&config.with_chunking(1000, threads), 1, call_payoff).unwrap();
+            assert_eq!(statistics, parallel);
+        }
+    }
+
+    #[test]
+    fn test_welford_merge() {
+        let samples: [f64; 4] = [1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0];
+        let mut whole = Statistics::new();
+        let (mut left, mut right) = (Statistics::new(), Statistics::new());
+        for (i, &sample) in samples.iter().enumerate() {
+            whole.add(sample);
+            if i < 1 {
+                left.add(sample);
+            } else {
+                right.add(sample);
+            }
+        }
+        left.merge(&right);
+        // the naive sum of squares loses the variance of 30 to cancellation at this magnitude
+        for statistics in [whole, left] {
+            assert!((statistics.mean() - (1e9 + 10.0)).abs() < 1e-6);
+            assert!((statistics.variance() - 30.0).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::monte_carlo::{simulate, SimulationConfig, Statistics};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::linear_algebra::cholesky::cholesky;
+use qlab_math::value::Value;
+use qlab_time::observation::TimeGrid;
+
+/// The dynamics of a single asset of a [`CorrelatedProcess`], driven by its own Brownian motion `W`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Marginal<V> {
+    /// Geometric Brownian motion `dS = μ S dt + σ S dW`, e.g. an equity or an FX rate.
+    Lognormal { spot: V, drift: V, volatility: V },
+    /// Arithmetic Brownian motion `dS = μ dt + σ dW`, e.g. a spread or a rate which may turn negative.
+    Normal { spot: V, drift: V, volatility: V },
+}
+
+impl<V: Value> Marginal<V> {
+    fn volatility(&self) -> V {
+        match *self {
+            Self::Lognormal { volatility, .. } | Self::Normal { volatility, .. } => volatility,
+        }
+    }
+
+    fn spot(&self) -> V {
+        match *self {
+            Self::Lognormal { spot, .. } | Self::Normal { spot, .. } => spot,
+        }
+    }
+
+    /// Evolves `value` over `dt` given the correlated normal draw `z`, exactly for both dynamics.
+    fn step(&self, value: V, dt: V, z: V) -> V {
+        match *self {
+            Self::Lognormal {
+                drift, volatility, ..
+            } => {
+                let half = V::one() / (V::one() + V::one());
+                value
+                    * ((drift - half * volatility * volatility) * dt + volatility * dt.sqrt() * z)
+                        .exp()
+            }
+            Self::Normal {
+                drift, volatility, ..
+            } => value + drift * dt + volatility * dt.sqrt() * z,
+        }
+    }
+}
+
+/// Returns the drift of an asset paid out in another currency at a fixed exchange rate, its drift less
+/// `ρ·σ_S·σ_X` with `ρ` the correlation of the asset with the FX rate quoted as domestic per foreign.
+#[must_use]
+pub fn quanto_drift<V: Value>(
+    drift: V,
+    correlation: V,
+    asset_volatility: V,
+    fx_volatility: V,
+) -> V {
+    drift - correlation * asset_volatility * fx_volatility
+}
+
+/// Several assets with their own [`Marginal`] dynamics whose Brownian motions are correlated,
+/// simulated at a grid of times for basket, spread and quanto payoffs.
+///
+/// The independent normal draws of a path are correlated through the Cholesky factor of the correlation
+/// matrix, step by step, so that a path uses `assets × steps` draws with those of the first step first.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::monte_carlo::SimulationConfig;
+/// use qlab_model::multi_asset::{CorrelatedProcess, Marginal};
+///
+/// let equity = |spot| Marginal::Lognormal { spot, drift: 0.0_f64, volatility: 0.25 };
+/// let correlation = [vec![1.0, 0.6], vec![0.6, 1.0]];
+/// let process = CorrelatedProcess::new(&[equity(100.0), equity(50.0)], &correlation, &[0.5, 1.0]).unwrap();
+/// assert_eq!(process.dimension(), 4);
+///
+/// // an at-the-money call on a basket of both equities
+/// let config = SimulationConfig::new(7, 20_000).with_antithetic(true);
+/// let statistics = process
+///     .simulate(&config, |paths| (paths[0][1] + 2.0 * paths[1][1] - 200.0).max(0.0))
+///     .unwrap();
+/// assert!(statistics.mean() > 0.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelatedProcess<V> {
+    marginals: Vec<Marginal<V>>,
+    /// The lower Cholesky factor of the correlation matrix, by rows.
+    lower: Vec<Vec<V>>,
+    times: Vec<V>,
+}
+
+impl<V: Value> CorrelatedProcess<V> {
+    /// Creates a process of `marginals` correlated by `correlation`, observed at `times` in years from today.
+    ///
+    /// The paths start from the spots at zero implicitly, so `times` must not include zero; pass
+    /// [`TimeGrid::step_times`] rather than [`TimeGrid::times`], or use [`CorrelatedProcess::on_time_grid`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if there is no asset or time, if the correlation matrix does not match
+    /// the assets, has a diagonal other than one, an entry outside [-1, 1] or is not positive semidefinite,
+    /// if a volatility is negative, or if the times are not positive and increasing.
+    pub fn new(marginals: &[Marginal<V>], correlation: &[Vec<V>], times: &[V]) -> QLabResult<Self> {
+        if marginals.is_empty() || correlation.len() != marginals.len() {
+            return Err(InvalidInput(
+                format!(
+                    "{} assets need a correlation matrix of the same size, not {}",
+                    marginals.len(),
+                    correlation.len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        for (i, row) in correlation.iter().enumerate() {
+            if row.get(i) != Some(&V::one()) || row.iter().any(|rho| rho.abs() > V::one()) {
+                return Err(InvalidInput(
+                    format!("row {i} of the correlation matrix must have a unit diagonal and entries within [-1, 1]").into(),
+                )
+                .into());
+            }
+        }
+        if let Some(marginal) = marginals
+            .iter()
+            .find(|marginal| marginal.volatility() < V::zero())
+        {
+            return Err(
+                InvalidInput(format!("{marginal:?} has a negative volatility").into()).into(),
+            );
+        }
+        if times.is_empty()
+            || times[0] <= V::zero()
+            || times.windows(2).any(|pair| pair[1] <= pair[0])
+        {
+            return Err(
+                InvalidInput("times must be non-empty, positive and increasing".into()).into(),
+            );
+        }
+        Ok(Self {
+            marginals: marginals.to_vec(),
+            lower: cholesky(correlation)?,
+            times: times.to_vec(),
+        })
+    }
+
+    /// Creates a process simulated at the steps of `grid`, so that the observation dates the grid was built from
+    /// are columns of the paths at [`TimeGrid::observation_steps`].
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`CorrelatedProcess::new`], in particular if the grid has
+    /// no remaining observation.
+    pub fn on_time_grid(
+        marginals: &[Marginal<V>],
+        correlation: &[Vec<V>],
+        grid: &TimeGrid<V>,
+    ) -> QLabResult<Self> {
+        Self::new(marginals, correlation, grid.step_times())
+    }
+
+    #[must_use]
+    pub fn marginals(&self) -> &[Marginal<V>] {
+        &self.marginals
+    }
+
+    #[must_use]
+    pub fn times(&self) -> &[V] {
+        &self.times
+    }
+
+    #[must_use]
+    pub fn num_assets(&self) -> usize {
+        self.marginals.len()
+    }
+
+    /// Returns the number of independent normal draws per path.
+    #[must_use]
+    pub fn dimension(&self) -> usize {
+        self.marginals.len() * self.times.len()
+    }
+
+    /// Maps the independent normal draws of a path to the values of each asset at each time,
+    /// a row per asset with a column per time.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the number of draws differs from [`CorrelatedProcess::dimension`].
+    pub fn paths(&self, normals: &[V]) -> QLabResult<Vec<Vec<V>>> {
+        if normals.len() != self.dimension() {
+            return Err(InvalidInput(
+                format!(
+                    "a path needs {} normal draws, not {}",
+                    self.dimension(),
+                    normals.len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        let mut paths: Vec<Vec<V>> = self
+            .marginals
+            .iter()
+            .map(|_| Vec::with_capacity(self.times.len()))
+            .collect();
+        let mut previous_time = V::zero();
+        for (step, (&time, draws)) in self
+            .times
+            .iter()
+            .zip(normals.chunks(self.marginals.len()))
+            .enumerate()
+        {
+            let dt = time - previous_time;
+            for (asset, (marginal, path)) in self.marginals.iter().zip(&mut paths).enumerate() {
+                let z = self.lower[asset]
+                    .iter()
+                    .zip(draws)
+                    .fold(V::zero(), |acc, (&l, &draw)| acc + l * draw);
+                let value = if step == 0 {
+                    marginal.spot()
+                } else {
+                    path[step - 1]
+                };
+                path.push(marginal.step(value, dt, z));
+            }
+            previous_time = time;
+        }
+        Ok(paths)
+    }
+
+    /// Runs a Monte Carlo simulation of `payoff`, which maps the paths returned by [`CorrelatedProcess::paths`]
+    /// to the (discounted) payoff of a path.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `config` is inconsistent.
+    ///
+    /// # Panics
+    /// Will propagate the panic of `payoff` raised on a worker thread.
+    pub fn simulate<F>(&self, config: &SimulationConfig, payoff: F) -> QLabResult<Statistics<V>>
+    where
+        V: Send + Sync,
+        F: Fn(&[Vec<V>]) -> V + Sync,
+    {
+        simulate(config, self.dimension(), |normals: &[V]| {
+            // the draws always match the dimension
+            self.paths(normals)
+                .map_or_else(|_| V::zero(), |paths| payoff(&paths))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::distribution::normal;
+    use qlab_time::date::Date;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::day_count::DayCount;
+    use qlab_time::observation::ObservationSchedule;
+
+    #[test]
+    fn test_exchange_option() {
+        let (vol1, vol2, rho) = (0.2_f64, 0.3, 0.5);
+        let marginals = [
+            Marginal::Lognormal {
+                spot: 100.0,
+                drift: 0.0,
+                volatility: vol1,
+            },
+            Marginal::Lognormal {
+                spot: 100.0,
+                drift: 0.0,
+                volatility: vol2,
+            },
+        ];
+        let correlation = [vec![1.0, rho], vec![rho, 1.0]];
+        let process = CorrelatedProcess::new(&marginals, &correlation, &[1.0]).unwrap();
+        // Margrabe's formula for the option to exchange the second asset for the first
+        let vol = (vol1 * vol1 + vol2 * vol2 - 2.0 * rho * vol1 * vol2).sqrt();
+        let expected = 100.0 * (normal::cdf(0.5 * vol) - normal::cdf(-0.5 * vol));
+        let config = SimulationConfig::new(11, 200_000).with_antithetic(true);
+        let statistics = process
+            .simulate(&config, |paths| (paths[0][0] - paths[1][0]).max(0.0))
+            .unwrap();
+        assert!((statistics.mean() - expected).abs() < 4.0 * statistics.std_error());
+
+        // a normal spread and a quanto equity over several steps
+        let fx_vol = 0.1_f64;
+        let marginals = [
+            Marginal::Normal {
+                spot: 0.01,
+                drift: 0.002,
+                volatility: 0.005,
+            },
+            Marginal::Lognormal {
+                spot: 100.0,
+                drift: quanto_drift(0.0, -0.3, vol1, fx_vol),
+                volatility: vol1,
+            },
+        ];
+        let process = CorrelatedProcess::new(&marginals, &correlation, &[0.5, 1.0, 2.0]).unwrap();
+        let config = SimulationConfig::new(3, 100_000).with_chunking(4096, 4);
+        let spread = process.simulate(&config, |paths| paths[0][2]).unwrap();
+        assert!((spread.mean() - 0.014).abs() < 4.0 * spread.std_error());
+        let quanto = process.simulate(&config, |paths| paths[1][2]).unwrap();
+        let forward: f64 = 100.0 * (2.0 * 0.3 * vol1 * fx_vol).exp();
+        assert!((quanto.mean() - forward).abs() < 4.0 * quanto.std_error());
+        assert!(process.paths(&[0.0; 5]).is_err());
+
+        assert!(CorrelatedProcess::new(&marginals, &[vec![1.0]], &[1.0]).is_err());
+        let invalid = [vec![1.0, 1.5], vec![1.5, 1.0]];
+        assert!(CorrelatedProcess::new(&marginals, &invalid, &[1.0]).is_err());
+        assert!(CorrelatedProcess::new(&marginals, &correlation, &[1.0, 1.0]).is_err());
+    }
+
+    #[test]
+    fn test_time_grid() {
+        let valuation_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let schedule = ObservationSchedule::new(&[
+            Date::from_ymd(2024, 4, 2).unwrap(),
+            Date::from_ymd(2025, 1, 2).unwrap(),
+        ])
+        .unwrap();
+        let grid = schedule
+            .time_grid::<f64, Act365>(valuation_date, 0.25)
+            .unwrap();
+        let marginals = [Marginal::Normal {
+            spot: 1.0,
+            drift: 1.0,
+            volatility: 0.0,
+        }];
+        // the leading zero of the grid is implicit
+        assert!(CorrelatedProcess::new(&marginals, &[vec![1.0]], grid.times()).is_err());
+        let process = CorrelatedProcess::on_time_grid(&marginals, &[vec![1.0]], &grid).unwrap();
+        assert_eq!(process.times(), grid.step_times());
+        let paths = process.paths(&vec![0.0; process.dimension()]).unwrap();
+        // without volatility the path is the spot plus the drift accrued up to each observation
+        for (&step, date) in grid.observation_steps().iter().zip(schedule.dates()) {
+            let t: f64 = Act365::calculate_day_count_fraction(valuation_date, *date).unwrap();
+            assert!((paths[0][step] - (1.0 + t)).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use qlab_math::value::Value;
+
+/// The right of a vanilla option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+impl OptionType {
+    /// Returns `1` for a call and `-1` for a put.
+    #[must_use]
+    pub fn sign<V: Value>(self) -> V {
+        match self {
+            Self::Call => V::one(),
+            Self::Put => -V::one(),
+        }
+    }
+
+    /// Returns the opposite right, i.e. a put for a call and vice versa.
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Call => Self::Put,
+            Self::Put => Self::Call,
+        }
+    }
+}
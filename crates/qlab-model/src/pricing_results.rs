@@ -0,0 +1,45 @@
+/// The price of an instrument with the sensitivities an engine calculated alongside it.
+///
+/// Lattice engines read the greeks off the grid built for the price, so a sensitivity is `None`
+/// when the engine does not provide it rather than being recalculated by revaluation.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PricingResults<V> {
+    pub npv: V,
+    /// The first derivative of the price with respect to the underlying state variable.
+    pub delta: Option<V>,
+    /// The second derivative of the price with respect to the underlying state variable.
+    pub gamma: Option<V>,
+    /// The derivative of the price with respect to time, per year.
+    pub theta: Option<V>,
+    /// The early-exercise boundary as pairs of exercise time and the critical value of the state variable
+    /// beyond which exercising is optimal, for an option exercisable before its expiry.
+    pub exercise_boundary: Option<Vec<(V, V)>>,
+}
+
+impl<V> PricingResults<V> {
+    /// Creates results holding a price only.
+    #[must_use]
+    pub fn new(npv: V) -> Self {
+        Self {
+            npv,
+            delta: None,
+            gamma: None,
+            theta: None,
+            exercise_boundary: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_greeks(mut self, delta: V, gamma: V, theta: V) -> Self {
+        self.delta = Some(delta);
+        self.gamma = Some(gamma);
+        self.theta = Some(theta);
+        self
+    }
+
+    #[must_use]
+    pub fn with_exercise_boundary(mut self, boundary: Vec<(V, V)>) -> Self {
+        self.exercise_boundary = Some(boundary);
+        self
+    }
+}
@@ -0,0 +1,208 @@
+pub mod black_karasinski;
+pub mod g2pp;
+
+use crate::option_type::OptionType;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+
+/// The discount factor from today to a time in years, i.e. the initial term structure a short rate model is fitted to.
+pub type DiscountFunction<'a, V> = &'a dyn Fn(V) -> QLabResult<V>;
+
+/// A swaption per unit notional, with times measured in years from today.
+///
+/// On each exercise time the holder may enter the swap exchanging a floating leg for the fixed coupons paid
+/// after that time, so a single exercise time gives a European swaption and several give a co-terminal
+/// Bermudan swaption. A call is a payer swaption and a put a receiver swaption.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swaption<V> {
+    option_type: OptionType,
+    exercise_times: Vec<V>,
+    payment_times: Vec<V>,
+    accruals: Vec<V>,
+    fixed_rate: V,
+}
+
+impl<V: Value> Swaption<V> {
+    /// Creates a swaption on the fixed coupons at `payment_times`, each accruing over the corresponding
+    /// year fraction of `accruals`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a list of times is empty or not increasing, if the lengths of
+    /// `payment_times` and `accruals` do not match, if an exercise time is negative, or if the last
+    /// exercise time is not before the last payment time.
+    pub fn new(
+        option_type: OptionType,
+        exercise_times: &[V],
+        payment_times: &[V],
+        accruals: &[V],
+        fixed_rate: V,
+    ) -> QLabResult<Self> {
+        if payment_times.len() != accruals.len() {
+            return Err(
+                InvalidInput("payment times and accruals are different lengths".into()).into(),
+            );
+        }
+        for times in [exercise_times, payment_times] {
+            if times.is_empty() || times.windows(2).any(|pair| pair[1] <= pair[0]) {
+                return Err(InvalidInput(
+                    "exercise and payment times must be non-empty and increasing".into(),
+                )
+                .into());
+            }
+        }
+        if exercise_times[0] < V::zero()
+            || exercise_times[exercise_times.len() - 1] >= payment_times[payment_times.len() - 1]
+        {
+            return Err(InvalidInput(
+                "exercise times must lie between today and the last payment time".into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            option_type,
+            exercise_times: exercise_times.to_vec(),
+            payment_times: payment_times.to_vec(),
+            accruals: accruals.to_vec(),
+            fixed_rate,
+        })
+    }
+
+    #[must_use]
+    pub fn option_type(&self) -> OptionType {
+        self.option_type
+    }
+
+    #[must_use]
+    pub fn exercise_times(&self) -> &[V] {
+        &self.exercise_times
+    }
+
+    #[must_use]
+    pub fn payment_times(&self) -> &[V] {
+        &self.payment_times
+    }
+
+    #[must_use]
+    pub fn accruals(&self) -> &[V] {
+        &self.accruals
+    }
+
+    #[must_use]
+    pub fn fixed_rate(&self) -> V {
+        self.fixed_rate
+    }
+
+    /// Returns the annuity and the forward swap rate today of the swap starting at the first exercise time.
+    ///
+    /// # Errors
+    /// Returns an error if `discount` fails.
+    pub fn forward_swap_rate(&self, discount: DiscountFunction<V>) -> QLabResult<(V, V)> {
+        let start = self.exercise_times[0];
+        let mut annuity = V::zero();
+        let mut last = V::one();
+        for (&t, &accrual) in self.payment_times.iter().zip(&self.accruals) {
+            if t > start {
+                last = discount(t)?;
+                annuity += accrual * last;
+            }
+        }
+        Ok((annuity, (discount(start)? - last) / annuity))
+    }
+}
+
+/// A fixed coupon bond per unit face value which the issuer may redeem early, with times measured in years from today.
+///
+/// On each call time the issuer may redeem the bond at the corresponding call price, after paying the coupon
+/// due at that time. The face value is repaid with the last coupon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallableBond<V> {
+    payment_times: Vec<V>,
+    coupons: Vec<V>,
+    call_times: Vec<V>,
+    call_prices: Vec<V>,
+}
+
+impl<V: Value> CallableBond<V> {
+    /// Creates a bond paying `coupons` at `payment_times`, callable at `call_prices` on `call_times`.
+    /// Without call times the bond is a straight bond.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the lengths of the times and the amounts do not match,
+    /// if there is no payment, if a list of times is not increasing, or if a call time is negative or
+    /// not before the last payment time.
+    pub fn new(
+        payment_times: &[V],
+        coupons: &[V],
+        call_times: &[V],
+        call_prices: &[V],
+    ) -> QLabResult<Self> {
+        if payment_times.len() != coupons.len() || call_times.len() != call_prices.len() {
+            return Err(InvalidInput(
+                "payment times and coupons, or call times and call prices are different lengths"
+                    .into(),
+            )
+            .into());
+        }
+        if payment_times.is_empty()
+            || [payment_times, call_times]
+                .iter()
+                .any(|times| times.windows(2).any(|pair| pair[1] <= pair[0]))
+        {
+            return Err(InvalidInput(
+                "payment times must be non-empty and increasing, and call times increasing".into(),
+            )
+            .into());
+        }
+        let maturity = payment_times[payment_times.len() - 1];
+        if call_times.iter().any(|&t| t < V::zero() || t >= maturity) {
+            return Err(InvalidInput(
+                "call times must lie between today and the last payment time".into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            payment_times: payment_times.to_vec(),
+            coupons: coupons.to_vec(),
+            call_times: call_times.to_vec(),
+            call_prices: call_prices.to_vec(),
+        })
+    }
+
+    #[must_use]
+    pub fn payment_times(&self) -> &[V] {
+        &self.payment_times
+    }
+
+    #[must_use]
+    pub fn coupons(&self) -> &[V] {
+        &self.coupons
+    }
+
+    #[must_use]
+    pub fn call_times(&self) -> &[V] {
+        &self.call_times
+    }
+
+    #[must_use]
+    pub fn call_prices(&self) -> &[V] {
+        &self.call_prices
+    }
+
+    /// Returns the last payment time.
+    #[must_use]
+    pub fn maturity(&self) -> V {
+        self.payment_times[self.payment_times.len() - 1]
+    }
+}
+
+/// The option-adjusted spread of an instrument with the sensitivities of its price at that spread.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OasResults<V> {
+    /// The constant spread over the short rates of the model which reprices the instrument.
+    pub oas: V,
+    /// The relative fall of the price per unit rise of the spread, `-(1/P)·∂P/∂s`.
+    pub spread_duration: V,
+    /// The change of the price per unit change of the volatility of the model, at a constant spread.
+    pub vega: V,
+}
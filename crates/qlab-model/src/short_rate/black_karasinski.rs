@@ -0,0 +1,723 @@
+use crate::pricing_results::PricingResults;
+use crate::short_rate::{CallableBond, DiscountFunction, OasResults, Swaption};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::optimization::least_squares;
+use qlab_math::root_finding::{solve, SolverDomain};
+use qlab_math::value::Value;
+
+const MAX_ITERATIONS: usize = 100;
+
+/// The option values at the steps up to the second one and the exercise boundary of a swaption rolled back
+/// through a tree.
+type SwaptionRollBack<V> = (Vec<Vec<V>>, Vec<(V, V)>);
+
+/// The Black-Karasinski model, in which the logarithm of the short rate follows
+/// `d ln r = (theta(t) - a ln r) dt + sigma dW`, with `theta` fitted to the initial term structure.
+///
+/// The short rate stays positive, which suits currencies with high rates; prices are calculated on a
+/// Hull-White trinomial tree.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::option_type::OptionType;
+/// use qlab_model::short_rate::black_karasinski::BlackKarasinski;
+/// use qlab_model::short_rate::Swaption;
+///
+/// let discount = |t: f64| Ok((-0.05 * t).exp());
+/// let model = BlackKarasinski::new(0.1, 0.2).unwrap();
+/// let payment_times = [2.0, 3.0, 4.0, 5.0, 6.0];
+/// let european = Swaption::new(OptionType::Call, &[1.0], &payment_times, &[1.0; 5], 0.05).unwrap();
+/// let bermudan = Swaption::new(OptionType::Call, &[1.0, 2.0, 3.0], &payment_times, &[1.0; 5], 0.05).unwrap();
+/// let european = model.swaption_price(&discount, &european, 120).unwrap();
+/// let bermudan = model.swaption_price(&discount, &bermudan, 120).unwrap();
+/// assert!(european > 0.0 && bermudan > european);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlackKarasinski<V> {
+    mean_reversion: V,
+    volatility: V,
+}
+
+impl<V: Value> BlackKarasinski<V> {
+    /// Creates a model with the mean reversion `a` and the volatility `sigma` of the logarithm of the short rate.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if either parameter is not positive.
+    pub fn new(mean_reversion: V, volatility: V) -> QLabResult<Self> {
+        if mean_reversion <= V::zero() || volatility <= V::zero() {
+            return Err(InvalidInput(
+                format!(
+                    "mean reversion: {mean_reversion:?} and volatility: {volatility:?} must be positive"
+                )
+                .into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            mean_reversion,
+            volatility,
+        })
+    }
+
+    #[must_use]
+    pub fn mean_reversion(&self) -> V {
+        self.mean_reversion
+    }
+
+    #[must_use]
+    pub fn volatility(&self) -> V {
+        self.volatility
+    }
+
+    /// Builds a trinomial tree of `steps` equal steps up to `maturity` fitted to `discount`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `steps` is zero or `maturity` is not positive, or if the tree
+    /// cannot reproduce a discount factor, e.g. for a non-positive forward rate.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn tree(
+        &self,
+        discount: DiscountFunction<V>,
+        maturity: V,
+        steps: usize,
+    ) -> QLabResult<TrinomialTree<V>> {
+        if steps == 0 || maturity <= V::zero() {
+            return Err(InvalidInput(
+                format!("a tree needs steps: {steps} and a positive maturity: {maturity:?}").into(),
+            )
+            .into());
+        }
+        let dt = maturity / V::from_usize(steps).unwrap();
+        let dx = self.volatility * (V::from_u8(3).unwrap() * dt).sqrt();
+        let jmax = (V::from_f64(0.184).unwrap() / (self.mean_reversion * dt))
+            .ceil()
+            .to_i64()
+            .unwrap_or(i64::MAX)
+            .max(1);
+        let mut tree = TrinomialTree {
+            dt,
+            dx,
+            jmax,
+            drift: -self.mean_reversion * dt,
+            alphas: Vec::with_capacity(steps),
+        };
+        // Arrow-Debreu prices of the nodes of the current step
+        let mut state_prices = vec![V::one()];
+        for i in 0..steps {
+            let target = discount(V::from_usize(i + 1).unwrap() * dt)?;
+            let alpha = tree.fit_alpha(i, &state_prices, target)?;
+            tree.alphas.push(alpha);
+            let width = tree.width(i);
+            let mut next = vec![V::zero(); tree.index(i + 1, tree.width(i + 1)) + 1];
+            for (j, &state_price) in (-width..=width).zip(&state_prices) {
+                let discounted = state_price * (-tree.short_rate(i, j) * dt).exp();
+                for (k, p) in tree.branches(j) {
+                    next[tree.index(i + 1, k)] += p * discounted;
+                }
+            }
+            state_prices = next;
+        }
+        Ok(tree)
+    }
+
+    /// Prices `swaption` on a tree of `steps` steps up to its last payment time, with exercise and payment
+    /// times moved to the nearest step.
+    ///
+    /// # Errors
+    /// Returns an error if the tree cannot be built.
+    pub fn swaption_price(
+        &self,
+        discount: DiscountFunction<V>,
+        swaption: &Swaption<V>,
+        steps: usize,
+    ) -> QLabResult<V> {
+        let maturity = swaption.payment_times()[swaption.payment_times().len() - 1];
+        self.tree(discount, maturity, steps)?
+            .swaption_price(swaption)
+    }
+
+    /// Prices `bond` on a tree of `steps` steps up to its maturity at `spread` over the short rates.
+    ///
+    /// # Errors
+    /// Returns an error if the tree cannot be built.
+    pub fn callable_bond_price(
+        &self,
+        discount: DiscountFunction<V>,
+        bond: &CallableBond<V>,
+        steps: usize,
+        spread: V,
+    ) -> QLabResult<V> {
+        self.tree(discount, bond.maturity(), steps)?
+            .callable_bond_price(bond, spread)
+    }
+
+    /// Solves for the option-adjusted spread at which `bond` is worth `price` on a tree of `steps` steps,
+    /// with the spread duration by central differences of one basis point of the spread and the vega
+    /// by central differences of one vol point, on trees rebuilt with the shifted volatility.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `price` is not positive, or an error if a tree cannot be built
+    /// or no spread reprices the bond.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn option_adjusted_spread(
+        &self,
+        discount: DiscountFunction<V>,
+        bond: &CallableBond<V>,
+        steps: usize,
+        price: V,
+    ) -> QLabResult<OasResults<V>> {
+        if price <= V::zero() {
+            return Err(InvalidInput(format!("price: {price:?} must be positive").into()).into());
+        }
+        let tree = self.tree(discount, bond.maturity(), steps)?;
+        let oas = tree.option_adjusted_spread(bond, price)?;
+        let two = V::from_u8(2).unwrap();
+        let spread_bump = V::from_f64(1e-4).unwrap();
+        let spread_duration = (tree.callable_bond_price(bond, oas - spread_bump)?
+            - tree.callable_bond_price(bond, oas + spread_bump)?)
+            / (two * spread_bump * price);
+        let vol_bump = V::from_f64(0.01).unwrap().min(self.volatility / two);
+        let bumped = |shift: V| {
+            Self::new(self.mean_reversion, self.volatility + shift)?
+                .callable_bond_price(discount, bond, steps, oas)
+        };
+        let vega = (bumped(vol_bump)? - bumped(-vol_bump)?) / (two * vol_bump);
+        Ok(OasResults {
+            oas,
+            spread_duration,
+            vega,
+        })
+    }
+
+    /// Calibrates the mean reversion and volatility to the market prices of swaptions by least squares,
+    /// starting from `initial`.
+    ///
+    /// # Errors
+    /// Returns an error if a price cannot be calculated or the optimization does not converge.
+    pub fn calibrate(
+        discount: DiscountFunction<V>,
+        quotes: &[(Swaption<V>, V)],
+        steps: usize,
+        initial: Self,
+    ) -> QLabResult<Self> {
+        let model = |params: &[V]| Self::new(params[0].exp(), params[1].exp());
+        let params = least_squares(
+            |params: &[V]| {
+                let model = model(params)?;
+                quotes
+                    .iter()
+                    .map(|(swaption, price)| {
+                        Ok(model.swaption_price(discount, swaption, steps)? - *price)
+                    })
+                    .collect()
+            },
+            &[initial.mean_reversion.ln(), initial.volatility.ln()],
+        )?;
+        model(&params)
+    }
+}
+
+/// A recombining trinomial tree of the logarithm of the short rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrinomialTree<V> {
+    dt: V,
+    dx: V,
+    jmax: i64,
+    /// The mean reversion times the time step, with a negative sign.
+    drift: V,
+    /// The shift of the logarithm of the short rate at each step.
+    alphas: Vec<V>,
+}
+
+impl<V: Value> TrinomialTree<V> {
+    #[must_use]
+    pub fn steps(&self) -> usize {
+        self.alphas.len()
+    }
+
+    #[must_use]
+    pub fn dt(&self) -> V {
+        self.dt
+    }
+
+    /// Returns the largest node index at step `i`.
+    fn width(&self, i: usize) -> i64 {
+        i64::try_from(i).map_or(self.jmax, |i| i.min(self.jmax))
+    }
+
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)] // nodes lie within the width of their step
+    fn index(&self, i: usize, j: i64) -> usize {
+        (j + self.width(i)) as usize
+    }
+
+    /// Returns the short rate at node `j` of step `i`.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants or if `i` is not a step of the tree.
+    #[must_use]
+    pub fn short_rate(&self, i: usize, j: i64) -> V {
+        (self.alphas[i] + V::from_i64(j).unwrap() * self.dx).exp()
+    }
+
+    /// Returns the nodes of the next step reached from node `j` with their probabilities.
+    fn branches(&self, j: i64) -> [(i64, V); 3] {
+        let sixth = V::one() / V::from_u8(6).unwrap();
+        let half = V::from_f64(0.5).unwrap();
+        let jm = V::from_i64(j).unwrap() * self.drift;
+        let jm2 = jm * jm;
+        // only the outermost nodes after the tree stops widening branch inward
+        let edge = j.abs() == self.jmax;
+        if edge && j > 0 {
+            let two = V::from_u8(2).unwrap();
+            [
+                (
+                    j,
+                    V::from_u8(7).unwrap() * sixth + (jm2 + V::from_u8(3).unwrap() * jm) * half,
+                ),
+                (j - 1, -two * sixth - jm2 - two * jm),
+                (j - 2, sixth + (jm2 + jm) * half),
+            ]
+        } else if edge && j < 0 {
+            let two = V::from_u8(2).unwrap();
+            [
+                (
+                    j,
+                    V::from_u8(7).unwrap() * sixth + (jm2 - V::from_u8(3).unwrap() * jm) * half,
+                ),
+                (j + 1, -two * sixth - jm2 + two * jm),
+                (j + 2, sixth + (jm2 - jm) * half),
+            ]
+        } else {
+            [
+                (j + 1, sixth + (jm2 + jm) * half),
+                (j, V::from_u8(4).unwrap() * sixth - jm2),
+                (j - 1, sixth + (jm2 - jm) * half),
+            ]
+        }
+    }
+
+    /// Solves for the shift at step `i` with which the nodes reproduce the discount factor `target`
+    /// to the end of the step.
+    fn fit_alpha(&self, i: usize, state_prices: &[V], target: V) -> QLabResult<V> {
+        let width = self.width(i);
+        let total = state_prices
+            .iter()
+            .fold(V::zero(), |acc, &price| acc + price);
+        if target <= V::zero() || target >= total {
+            return Err(InvalidInput(
+                format!(
+                    "the discount factor: {target:?} at step {} needs a positive forward rate",
+                    i + 1
+                )
+                .into(),
+            )
+            .into());
+        }
+        let mut alpha = ((total / target).ln() / self.dt).ln();
+        for _ in 0..MAX_ITERATIONS {
+            let mut value = -target;
+            let mut derivative = V::zero();
+            for (j, &state_price) in (-width..=width).zip(state_prices) {
+                let rate = (alpha + V::from_i64(j).unwrap() * self.dx).exp();
+                let discounted = state_price * (-rate * self.dt).exp();
+                value += discounted;
+                derivative -= discounted * rate * self.dt;
+            }
+            let step = value / derivative;
+            alpha -= step;
+            if step.abs()
+                <= V::epsilon().sqrt() * V::epsilon().sqrt().sqrt() * (V::one() + alpha.abs())
+            {
+                return Ok(alpha);
+            }
+        }
+        Err(InvalidInput(
+            format!(
+                "the tree does not fit the discount factor at step {}",
+                i + 1
+            )
+            .into(),
+        )
+        .into())
+    }
+
+    /// Returns the values at step `i` of the values `next` at step `i + 1`.
+    fn roll_back(&self, i: usize, next: &[V]) -> Vec<V> {
+        self.roll_back_with_spread(i, next, V::zero())
+    }
+
+    /// Returns the values at step `i` of the values `next` at step `i + 1`, discounting at the short rates plus `spread`.
+    fn roll_back_with_spread(&self, i: usize, next: &[V], spread: V) -> Vec<V> {
+        let width = self.width(i);
+        (-width..=width)
+            .map(|j| {
+                let expectation = self.branches(j).iter().fold(V::zero(), |acc, &(k, p)| {
+                    acc + p * next[self.index(i + 1, k)]
+                });
+                expectation * (-(self.short_rate(i, j) + spread) * self.dt).exp()
+            })
+            .collect()
+    }
+
+    fn step(&self, t: V) -> QLabResult<usize> {
+        let step = (t / self.dt).round().to_usize().unwrap_or(usize::MAX);
+        if step > self.steps() {
+            return Err(InvalidInput(format!("time: {t:?} is beyond the tree").into()).into());
+        }
+        Ok(step)
+    }
+
+    /// Prices `swaption` by backward induction, with exercise and payment times moved to the nearest step.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a payment time is beyond the tree.
+    pub fn swaption_price(&self, swaption: &Swaption<V>) -> QLabResult<V> {
+        Ok(self.roll_back_swaption(swaption)?.0[0][0])
+    }
+
+    /// Prices `swaption` as [`TrinomialTree::swaption_price`] does and reads the greeks with respect to
+    /// the short rate off the first steps of the tree.
+    ///
+    /// Delta and gamma are the finite differences of the values at the three nodes of the first step against
+    /// their short rates; theta is the change from the root to the central node of the second step over two steps.
+    ///
+    /// The exercise boundary holds, for each exercise step at which some node exercises, the time of the step
+    /// and the critical short rate: the lowest rate of an exercising node for a payer swaption, which is exercised
+    /// at rates at or above it, and the highest for a receiver swaption.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a payment time is beyond the tree or the last payment is
+    /// less than two steps away.
+    pub fn swaption_results(&self, swaption: &Swaption<V>) -> QLabResult<PricingResults<V>> {
+        let (values, boundary) = self.roll_back_swaption(swaption)?;
+        let [root, first, second] = values.as_slice() else {
+            return Err(InvalidInput(
+                "the greeks need the last payment at least two steps away".into(),
+            )
+            .into());
+        };
+        let [down, middle, up] =
+            [-1, 0, 1].map(|j| (self.short_rate(1, j), first[self.index(1, j)]));
+        let two = V::one() + V::one();
+        let delta = (up.1 - down.1) / (up.0 - down.0);
+        let gamma = two
+            * ((up.1 - middle.1) / (up.0 - middle.0) - (middle.1 - down.1) / (middle.0 - down.0))
+            / (up.0 - down.0);
+        let theta = (second[self.index(2, 0)] - root[0]) / (two * self.dt);
+        Ok(PricingResults::new(root[0])
+            .with_greeks(delta, gamma, theta)
+            .with_exercise_boundary(boundary))
+    }
+
+    /// Prices `bond` by backward induction discounting at the short rates plus `spread`, with the issuer
+    /// calling whenever the call price is below the value of the bond; payment and call times are moved
+    /// to the nearest step.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a payment time is beyond the tree.
+    pub fn callable_bond_price(&self, bond: &CallableBond<V>, spread: V) -> QLabResult<V> {
+        let call_steps = bond
+            .call_times()
+            .iter()
+            .map(|&t| self.step(t))
+            .collect::<QLabResult<Vec<_>>>()?;
+        let payment_steps = bond
+            .payment_times()
+            .iter()
+            .map(|&t| self.step(t))
+            .collect::<QLabResult<Vec<_>>>()?;
+        let last = payment_steps[payment_steps.len() - 1];
+        // the value after the coupons paid at the current step
+        let mut values = vec![V::zero(); self.index(last, self.width(last)) + 1];
+        for i in (0..=last).rev() {
+            if i < last {
+                values = self.roll_back_with_spread(i, &values, spread);
+            }
+            for (&step, &call_price) in call_steps.iter().zip(bond.call_prices()) {
+                if step == i {
+                    for value in &mut values {
+                        *value = value.min(call_price);
+                    }
+                }
+            }
+            for (&step, &coupon) in payment_steps.iter().zip(bond.coupons()) {
+                if step == i {
+                    let amount = coupon + if step == last { V::one() } else { V::zero() };
+                    for value in &mut values {
+                        *value += amount;
+                    }
+                }
+            }
+        }
+        Ok(values[0])
+    }
+
+    /// Solves for the option-adjusted spread, the constant spread over the short rates at which
+    /// [`TrinomialTree::callable_bond_price`] reprices `bond` to `price`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if no spread within ±100% reprices the bond or a payment time is
+    /// beyond the tree.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn option_adjusted_spread(&self, bond: &CallableBond<V>, price: V) -> QLabResult<V> {
+        solve(
+            |spread| Ok(self.callable_bond_price(bond, spread)? - price),
+            &SolverDomain::spread(),
+            V::from_f64(1e-12).unwrap(),
+        )
+    }
+
+    /// Rolls `swaption` back through the tree and returns the option values at the steps up to the second one,
+    /// with the exercise boundary described in [`TrinomialTree::swaption_results`].
+    fn roll_back_swaption(&self, swaption: &Swaption<V>) -> QLabResult<SwaptionRollBack<V>> {
+        let exercise_steps = swaption
+            .exercise_times()
+            .iter()
+            .map(|&t| self.step(t))
+            .collect::<QLabResult<Vec<_>>>()?;
+        let payment_steps = swaption
+            .payment_times()
+            .iter()
+            .map(|&t| self.step(t))
+            .collect::<QLabResult<Vec<_>>>()?;
+        let last = payment_steps[payment_steps.len() - 1];
+        let sign = swaption.option_type().sign::<V>();
+        // the fixed leg including the final notional, and the option
+        let mut bond = vec![V::zero(); self.index(last, self.width(last)) + 1];
+        let mut option = bond.clone();
+        let mut early = Vec::with_capacity(3);
+        let mut boundary = Vec::new();
+        for i in (0..=last).rev() {
+            if i < last {
+                bond = self.roll_back(i, &bond);
+                option = self.roll_back(i, &option);
+            }
+            if exercise_steps.contains(&i) {
+                let mut critical: Option<V> = None;
+                for ((value, &fixed_leg), j) in option
+                    .iter_mut()
+                    .zip(&bond)
+                    .zip(-self.width(i)..=self.width(i))
+                {
+                    let exercise = sign * (V::one() - fixed_leg);
+                    if exercise > V::zero() && exercise >= *value {
+                        let rate = self.short_rate(i, j);
+                        critical = Some(critical.map_or(rate, |critical| {
+                            if sign > V::zero() {
+                                critical.min(rate)
+                            } else {
+                                critical.max(rate)
+                            }
+                        }));
+                    }
+                    *value = value.max(exercise);
+                }
+                if let Some(rate) = critical {
+                    boundary.push((V::from_usize(i).unwrap() * self.dt, rate));
+                }
+            }
+            if i <= 2 {
+                early.push(option.clone());
+            }
+            for (&step, &accrual) in payment_steps.iter().zip(swaption.accruals()) {
+                if step == i {
+                    let coupon = swaption.fixed_rate() * accrual
+                        + if step == last { V::one() } else { V::zero() };
+                    for value in &mut bond {
+                        *value += coupon;
+                    }
+                }
+            }
+        }
+        early.reverse();
+        boundary.reverse();
+        Ok((early, boundary))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::option_type::OptionType;
+
+    #[allow(clippy::unnecessary_wraps)] // the signature of a discount function
+    fn discount(t: f64) -> QLabResult<f64> {
+        Ok((-0.03 * t - 0.002 * t * t).exp())
+    }
+
+    #[test]
+    fn test_tree_fits_discount_factors() {
+        let model = BlackKarasinski::new(0.1, 0.25).unwrap();
+        let tree = model.tree(&discount, 10.0, 200).unwrap();
+        // a bond paying one at each step reproduces the initial curve
+        for maturity in [1.0, 4.5, 10.0] {
+            let swaption =
+                Swaption::new(OptionType::Call, &[0.0], &[maturity], &[0.0], 0.0).unwrap();
+            let step = tree.step(maturity).unwrap();
+            let mut values = vec![1.0; tree.index(step, tree.width(step)) + 1];
+            for i in (0..step).rev() {
+                values = tree.roll_back(i, &values);
+            }
+            assert!((values[0] - discount(maturity).unwrap()).abs() < 1e-12);
+            // exercising today into a swap with no coupons receives one and pays the final notional
+            let price = tree.swaption_price(&swaption).unwrap();
+            assert!((price - (1.0 - discount(maturity).unwrap())).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_swaption_results() {
+        let model = BlackKarasinski::new(0.1, 0.2).unwrap();
+        let tree = model.tree(&discount, 6.0, 120).unwrap();
+        let payment_times = [2.0, 3.0, 4.0, 5.0, 6.0];
+        for (option_type, sign) in [(OptionType::Call, 1.0), (OptionType::Put, -1.0)] {
+            let swaption =
+                Swaption::new(option_type, &[1.0], &payment_times, &[1.0; 5], 0.04).unwrap();
+            let results = tree.swaption_results(&swaption).unwrap();
+            assert!((results.npv - tree.swaption_price(&swaption).unwrap()).abs() < 1e-15);
+            // a payer swaption gains and a receiver swaption loses value as rates rise
+            assert!(sign * results.delta.unwrap() > 0.0);
+            assert!(results.gamma.unwrap() > 0.0);
+            assert!(results.theta.unwrap().is_finite());
+        }
+        let short = Swaption::new(OptionType::Call, &[0.0], &[0.05], &[0.05], 0.04).unwrap();
+        assert!(tree.swaption_results(&short).is_err());
+
+        // a Bermudan payer swaption is exercised above a positive critical rate at each exercise time
+        let bermudan = Swaption::new(
+            OptionType::Call,
+            &[1.0, 2.0, 3.0],
+            &payment_times,
+            &[1.0; 5],
+            0.04,
+        )
+        .unwrap();
+        let boundary = tree
+            .swaption_results(&bermudan)
+            .unwrap()
+            .exercise_boundary
+            .unwrap();
+        assert_eq!(boundary.len(), 3);
+        for (&(time, rate), expected) in boundary.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((time - expected).abs() < 1e-12);
+            assert!(rate > 0.0 && rate < 0.2);
+        }
+        // nothing is left to exercise after the last exercise time, so the swaption is exercised exactly at the
+        // nodes where the fixed leg paid after it is worth less than the notional, all beyond the critical rate
+        let exercise_step = tree.step(3.0).unwrap();
+        let last = tree.step(6.0).unwrap();
+        let coupon_steps = [tree.step(4.0).unwrap(), tree.step(5.0).unwrap()];
+        let mut fixed_leg = vec![1.04; tree.index(last, tree.width(last)) + 1];
+        for i in (exercise_step..last).rev() {
+            fixed_leg = tree.roll_back(i, &fixed_leg);
+            if coupon_steps.contains(&i) {
+                for value in &mut fixed_leg {
+                    *value += 0.04;
+                }
+            }
+        }
+        let width = tree.width(exercise_step);
+        let exercised = fixed_leg.iter().filter(|&&value| value < 1.0).count();
+        assert!(0 < exercised && exercised < fixed_leg.len());
+        for (j, &value) in (-width..=width).zip(&fixed_leg) {
+            assert_eq!(
+                tree.short_rate(exercise_step, j) >= boundary[2].1,
+                value < 1.0,
+                "node {j}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let payment_times: Vec<f64> = (2..=6).map(f64::from).collect();
+        let quotes: Vec<(Swaption<f64>, f64)> = [1.0, 2.0, 3.0]
+            .iter()
+            .map(|&expiry| {
+                let payments: Vec<f64> = payment_times
+                    .iter()
+                    .copied()
+                    .filter(|&t| t > expiry)
+                    .collect();
+                let swaption = Swaption::new(
+                    OptionType::Put,
+                    &[expiry],
+                    &payments,
+                    &vec![1.0; payments.len()],
+                    0.04,
+                )
+                .unwrap();
+                let price = BlackKarasinski::new(0.05, 0.3)
+                    .unwrap()
+                    .swaption_price(&discount, &swaption, 60)
+                    .unwrap();
+                (swaption, price)
+            })
+            .collect();
+        let calibrated = BlackKarasinski::calibrate(
+            &discount,
+            &quotes,
+            60,
+            BlackKarasinski::new(0.1, 0.2).unwrap(),
+        )
+        .unwrap();
+        assert!((calibrated.mean_reversion() - 0.05).abs() < 1e-4);
+        assert!((calibrated.volatility() - 0.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_option_adjusted_spread() {
+        let model = BlackKarasinski::new(0.1, 0.2).unwrap();
+        let payment_times: Vec<f64> = (1..=10).map(f64::from).collect();
+        let coupons = [0.05; 10];
+        let straight = CallableBond::new(&payment_times, &coupons, &[], &[]).unwrap();
+        let callable =
+            CallableBond::new(&payment_times, &coupons, &[3.0, 5.0, 7.0], &[1.0; 3]).unwrap();
+        let value = payment_times
+            .iter()
+            .map(|&t| 0.05 * discount(t).unwrap())
+            .sum::<f64>()
+            + discount(10.0).unwrap();
+        let straight_price = model
+            .callable_bond_price(&discount, &straight, 200, 0.0)
+            .unwrap();
+        assert!((straight_price - value).abs() < 1e-12);
+        let callable_price = model
+            .callable_bond_price(&discount, &callable, 200, 0.0)
+            .unwrap();
+        assert!(callable_price < straight_price);
+
+        // a price below the model value is explained by a positive spread
+        let results = model
+            .option_adjusted_spread(&discount, &callable, 200, callable_price - 0.02)
+            .unwrap();
+        assert!(results.oas > 0.0);
+        let repriced = model
+            .callable_bond_price(&discount, &callable, 200, results.oas)
+            .unwrap();
+        assert!((repriced - (callable_price - 0.02)).abs() < 1e-10);
+        // the call shortens the bond and the short call loses value as volatility rises
+        let straight_results = model
+            .option_adjusted_spread(&discount, &straight, 200, straight_price)
+            .unwrap();
+        assert!(straight_results.oas.abs() < 1e-10);
+        assert!(0.0 < results.spread_duration);
+        assert!(results.spread_duration < straight_results.spread_duration);
+        assert!(results.vega < 0.0);
+        assert!(straight_results.vega.abs() < 1e-10);
+
+        assert!(model
+            .option_adjusted_spread(&discount, &callable, 200, 0.0)
+            .is_err());
+        assert!(CallableBond::new(&payment_times, &coupons, &[10.0], &[1.0]).is_err());
+        assert!(CallableBond::new(&payment_times, &coupons[1..], &[], &[]).is_err());
+    }
+}
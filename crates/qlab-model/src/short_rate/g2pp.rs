@@ -0,0 +1,271 @@
+use crate::bachelier;
+use crate::short_rate::{DiscountFunction, Swaption};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::optimization::least_squares;
+use qlab_math::value::Value;
+
+/// The two-factor Gaussian (G2++) model, in which the short rate is `r = x + y + phi(t)` with
+/// `dx = -a x dt + sigma dW1`, `dy = -b y dt + eta dW2`, `dW1 dW2 = rho dt`
+/// and `phi` fitted to the initial term structure.
+///
+/// The second factor decorrelates rates of different tenors, which a one-factor model cannot do.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::option_type::OptionType;
+/// use qlab_model::short_rate::g2pp::G2pp;
+/// use qlab_model::short_rate::Swaption;
+///
+/// let discount = |t: f64| Ok((-0.03 * t).exp());
+/// let model = G2pp::new(0.5, 0.01, 0.05, 0.008, -0.7).unwrap();
+/// let swaption = Swaption::new(OptionType::Call, &[1.0], &[2.0, 3.0, 4.0], &[1.0; 3], 0.03).unwrap();
+/// let price = model.swaption_price(&discount, &swaption).unwrap();
+/// assert!(price > 0.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct G2pp<V> {
+    a: V,
+    sigma: V,
+    b: V,
+    eta: V,
+    rho: V,
+}
+
+impl<V: Value> G2pp<V> {
+    /// Creates a model with the mean reversions `a`, `b`, the volatilities `sigma`, `eta` and the correlation `rho`
+    /// of the two factors.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a mean reversion or volatility is not positive, or if `rho` is
+    /// not within `[-1, 1]`.
+    pub fn new(a: V, sigma: V, b: V, eta: V, rho: V) -> QLabResult<Self> {
+        if a <= V::zero() || sigma <= V::zero() || b <= V::zero() || eta <= V::zero() {
+            return Err(InvalidInput(
+                format!(
+                    "mean reversions: {a:?}, {b:?} and volatilities: {sigma:?}, {eta:?} must be positive"
+                )
+                .into(),
+            )
+            .into());
+        }
+        if rho.abs() > V::one() {
+            return Err(InvalidInput(
+                format!("correlation: {rho:?} must be within [-1, 1]").into(),
+            )
+            .into());
+        }
+        Ok(Self {
+            a,
+            sigma,
+            b,
+            eta,
+            rho,
+        })
+    }
+
+    #[must_use]
+    pub fn a(&self) -> V {
+        self.a
+    }
+
+    #[must_use]
+    pub fn sigma(&self) -> V {
+        self.sigma
+    }
+
+    #[must_use]
+    pub fn b(&self) -> V {
+        self.b
+    }
+
+    #[must_use]
+    pub fn eta(&self) -> V {
+        self.eta
+    }
+
+    #[must_use]
+    pub fn rho(&self) -> V {
+        self.rho
+    }
+
+    /// Prices a European `swaption` analytically.
+    ///
+    /// The weights of the forward swap rate on the logarithms of the discount bonds are frozen at their
+    /// values today, which makes the swap rate Gaussian with a variance integrating the bond volatilities
+    /// in closed form; the price follows from the Bachelier formula.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `swaption` has more than one exercise time, or an error if `discount` fails.
+    pub fn swaption_price(
+        &self,
+        discount: DiscountFunction<V>,
+        swaption: &Swaption<V>,
+    ) -> QLabResult<V> {
+        if swaption.exercise_times().len() != 1 {
+            return Err(InvalidInput(
+                "the analytic G2++ price is available for European swaptions only".into(),
+            )
+            .into());
+        }
+        let expiry = swaption.exercise_times()[0];
+        let (annuity, swap_rate) = swaption.forward_swap_rate(discount)?;
+        // the sensitivities of the swap rate to the logarithm of each discount bond
+        let mut weights = vec![(expiry, discount(expiry)? / annuity)];
+        for (&t, &accrual) in swaption.payment_times().iter().zip(swaption.accruals()) {
+            if t > expiry {
+                weights.push((t, -swap_rate * accrual * discount(t)? / annuity));
+            }
+        }
+        let (last, _) = weights[weights.len() - 1];
+        let last_weight = discount(last)? / annuity;
+        let n = weights.len();
+        weights[n - 1].1 -= last_weight;
+
+        let mut variance = V::zero();
+        for &(ti, wi) in &weights {
+            for &(tj, wj) in &weights {
+                let integral = self.sigma * self.sigma * integral(self.a, self.a, ti, tj, expiry)
+                    + self.eta * self.eta * integral(self.b, self.b, ti, tj, expiry)
+                    + (V::one() + V::one())
+                        * self.rho
+                        * self.sigma
+                        * self.eta
+                        * integral(self.a, self.b, ti, tj, expiry);
+                variance += wi * wj * integral;
+            }
+        }
+        let vol = (variance.max(V::zero()) / expiry).sqrt();
+        Ok(annuity
+            * bachelier::price(
+                swaption.option_type(),
+                swap_rate,
+                swaption.fixed_rate(),
+                vol,
+                expiry,
+            ))
+    }
+
+    /// Calibrates the model to the market prices of European swaptions by least squares, starting from `initial`.
+    ///
+    /// # Errors
+    /// Returns an error if a price cannot be calculated or the optimization does not converge.
+    pub fn calibrate(
+        discount: DiscountFunction<V>,
+        quotes: &[(Swaption<V>, V)],
+        initial: Self,
+    ) -> QLabResult<Self> {
+        let model = |params: &[V]| {
+            Self::new(
+                params[0].exp(),
+                params[1].exp(),
+                params[2].exp(),
+                params[3].exp(),
+                params[4].tanh(),
+            )
+        };
+        let params = least_squares(
+            |params: &[V]| {
+                let model = model(params)?;
+                quotes
+                    .iter()
+                    .map(|(swaption, price)| Ok(model.swaption_price(discount, swaption)? - *price))
+                    .collect()
+            },
+            &[
+                initial.a.ln(),
+                initial.sigma.ln(),
+                initial.b.ln(),
+                initial.eta.ln(),
+                initial.rho.atanh(),
+            ],
+        )?;
+        model(&params)
+    }
+}
+
+/// Integrates `B(k1, t1 - s) B(k2, t2 - s)` over `s` from zero to `expiry`, where `B(k, u) = (1 - exp(-k u)) / k`.
+fn integral<V: Value>(k1: V, k2: V, t1: V, t2: V, expiry: V) -> V {
+    let growth = |k: V| ((k * expiry).exp() - V::one()) / k;
+    (expiry - (-k1 * t1).exp() * growth(k1) - (-k2 * t2).exp() * growth(k2)
+        + (-k1 * t1 - k2 * t2).exp() * growth(k1 + k2))
+        / (k1 * k2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::option_type::OptionType;
+
+    #[allow(clippy::unnecessary_wraps)] // the signature of a discount function
+    fn discount(t: f64) -> QLabResult<f64> {
+        Ok((-0.02 * t - 0.001 * t * t).exp())
+    }
+
+    fn swaption(expiry: f64, tenor: i32, option_type: OptionType) -> Swaption<f64> {
+        let payments: Vec<f64> = (1..=tenor).map(|i| expiry + f64::from(i)).collect();
+        let fixed_rate = Swaption::new(
+            option_type,
+            &[expiry],
+            &payments,
+            &vec![1.0; payments.len()],
+            0.0,
+        )
+        .unwrap()
+        .forward_swap_rate(&discount)
+        .unwrap()
+        .1;
+        Swaption::new(
+            option_type,
+            &[expiry],
+            &payments,
+            &vec![1.0; payments.len()],
+            fixed_rate,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_one_factor_limit() {
+        // without the second factor an at-the-money option on a one-period swap is an option on a bond,
+        // whose log-price has the Hull-White variance
+        let model = G2pp::new(0.1, 0.01, 0.1, 1e-12, 0.0).unwrap();
+        let (expiry, maturity) = (2.0, 3.0);
+        let b = (1.0 - (-0.1_f64 * (maturity - expiry)).exp()) / 0.1;
+        let variance = 0.01 * 0.01 * b * b * (1.0 - (-0.2_f64 * expiry).exp()) / 0.2;
+        let payer = swaption(expiry, 1, OptionType::Call);
+        let receiver = swaption(expiry, 1, OptionType::Put);
+        let payer_price = model.swaption_price(&discount, &payer).unwrap();
+        let receiver_price = model.swaption_price(&discount, &receiver).unwrap();
+        assert!((payer_price - receiver_price).abs() < 1e-15);
+        // the swap rate moves by (1 + S) times the log-price of the bond
+        let (annuity, swap_rate) = payer.forward_swap_rate(&discount).unwrap();
+        let expected =
+            annuity * (1.0 + swap_rate) * (variance / (2.0 * std::f64::consts::PI)).sqrt();
+        assert!((payer_price - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let market = G2pp::new(0.6, 0.012, 0.04, 0.009, -0.6).unwrap();
+        let quotes: Vec<(Swaption<f64>, f64)> = [1.0, 2.0, 5.0]
+            .iter()
+            .flat_map(|&expiry| [1, 5, 10].map(|tenor| swaption(expiry, tenor, OptionType::Call)))
+            .map(|swaption| {
+                let price = market.swaption_price(&discount, &swaption).unwrap();
+                (swaption, price)
+            })
+            .collect();
+        let calibrated = G2pp::calibrate(
+            &discount,
+            &quotes,
+            G2pp::new(0.3, 0.01, 0.05, 0.01, -0.3).unwrap(),
+        )
+        .unwrap();
+        for (swaption, price) in &quotes {
+            let model_price = calibrated.swaption_price(&discount, swaption).unwrap();
+            assert!((model_price - price).abs() < 1e-7);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::option_type::OptionType;
+use crate::{bachelier, black};
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+
+fn out_of_the_money<V: Value>(forward: V, strike: V) -> OptionType {
+    if strike >= forward {
+        OptionType::Call
+    } else {
+        OptionType::Put
+    }
+}
+
+/// Converts a shifted lognormal volatility into the normal volatility giving the same option price.
+///
+/// The conversion is exact in price: the out-of-the-money option is priced under the shifted Black
+/// model and the normal volatility is implied from that price. A zero `shift` converts a plain lognormal volatility.
+///
+/// # Errors
+/// Returns an error if the shifted forward or strike are not positive, or if the inversion fails.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::vol_conversion::{lognormal_to_normal, normal_to_lognormal};
+///
+/// let normal_vol = lognormal_to_normal(0.03, 0.035, 2.0, 0.2, 0.0).unwrap();
+/// let lognormal_vol = normal_to_lognormal(0.03, 0.035, 2.0, normal_vol, 0.0).unwrap();
+/// assert!((lognormal_vol - 0.2_f64).abs() < 1e-9);
+/// ```
+pub fn lognormal_to_normal<V: Value>(
+    forward: V,
+    strike: V,
+    t: V,
+    lognormal_vol: V,
+    shift: V,
+) -> QLabResult<V> {
+    let option_type = out_of_the_money(forward, strike);
+    let premium = black::shifted_price(option_type, forward, strike, lognormal_vol, t, shift);
+    bachelier::implied_vol(option_type, forward, strike, t, premium)
+}
+
+/// Converts a normal volatility into the shifted lognormal volatility giving the same option price.
+///
+/// # Errors
+/// Returns an error if the shifted forward or strike are not positive, or if the price is not attainable under the shifted Black model.
+pub fn normal_to_lognormal<V: Value>(
+    forward: V,
+    strike: V,
+    t: V,
+    normal_vol: V,
+    shift: V,
+) -> QLabResult<V> {
+    let option_type = out_of_the_money(forward, strike);
+    let premium = bachelier::price(option_type, forward, strike, normal_vol, t);
+    black::shifted_implied_vol(option_type, forward, strike, t, shift, premium)
+}
+
+/// Converts a lognormal volatility quoted with `from_shift` into the one quoted with `to_shift`.
+///
+/// # Errors
+/// Returns an error if one of the shifted forwards or strikes is not positive, or if the inversion fails.
+pub fn change_shift<V: Value>(
+    forward: V,
+    strike: V,
+    t: V,
+    lognormal_vol: V,
+    from_shift: V,
+    to_shift: V,
+) -> QLabResult<V> {
+    let option_type = out_of_the_money(forward, strike);
+    let premium = black::shifted_price(option_type, forward, strike, lognormal_vol, t, from_shift);
+    black::shifted_implied_vol(option_type, forward, strike, t, to_shift, premium)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at_the_money_approximation() {
+        // at the money the normal vol is close to forward * lognormal vol
+        let normal_vol = lognormal_to_normal(0.05, 0.05, 1.0, 0.2, 0.0).unwrap();
+        assert!((normal_vol - 0.01_f64).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_negative_rates() {
+        let normal_vol = 0.006_f64;
+        let shifted = normal_to_lognormal(-0.004, 0.001, 5.0, normal_vol, 0.02).unwrap();
+        let round_trip = lognormal_to_normal(-0.004, 0.001, 5.0, shifted, 0.02).unwrap();
+        assert!((round_trip - normal_vol).abs() < 1e-12);
+        assert!(normal_to_lognormal(-0.004, 0.001, 5.0, normal_vol, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_change_shift() {
+        let vol = change_shift(0.01, 0.012, 3.0, 0.25, 0.01, 0.03).unwrap();
+        let back = change_shift(0.01, 0.012, 3.0, vol, 0.03, 0.01).unwrap();
+        assert!(vol < 0.25);
+        assert!((back - 0.25_f64).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,388 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::marker::PhantomData;
+
+pub mod builder;
+pub mod fx;
+
+/// How the surface interpolates between quoted expiries at constant log-moneyness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeInterpolation {
+    /// Linear in total variance `vol^2 * t`, which keeps forward variances constant between expiries.
+    #[default]
+    TotalVariance,
+    /// Linear in volatility.
+    Volatility,
+}
+
+/// The quotes of a smile as `(expiry, forward, strikes_and_vols)`.
+pub type SmileQuotes<V> = (V, V, Vec<(V, V)>);
+
+/// The smile of a single expiry, stored as total variance against log-moneyness.
+struct Smile<I: Interpolator> {
+    expiry: I::Value,
+    forward: I::Value,
+    lower: I::Value,
+    upper: I::Value,
+    interpolator: Option<I>,
+    flat_variance: I::Value,
+}
+
+impl<V: Value, I: Interpolator<Value = V>> Smile<I> {
+    /// Returns the total variance at log-moneyness `k`, extrapolated flat outside the quoted range.
+    fn total_variance(&self, k: V) -> QLabResult<V> {
+        match &self.interpolator {
+            Some(interpolator) => Ok(interpolator.try_value(k.max(self.lower).min(self.upper))?),
+            None => Ok(self.flat_variance),
+        }
+    }
+}
+
+/// A Black volatility surface built from one smile per expiry.
+///
+/// Within an expiry the total variance is interpolated in log-moneyness `ln(strike / forward)` by `I`
+/// and extrapolated flat beyond the quoted strikes.
+/// Between expiries the total variance (or the volatility, see [`TimeInterpolation`]) is interpolated
+/// linearly in time at constant log-moneyness, with forwards interpolated linearly;
+/// outside the quoted expiries the volatility is extrapolated flat.
+pub struct VolSurface<I: Interpolator> {
+    smiles: Vec<Smile<I>>,
+    time_interpolation: TimeInterpolation,
+}
+
+impl<V: Value, I: Interpolator<Value = V>> VolSurface<I> {
+    /// Creates a surface from the quoted smiles.
+    ///
+    /// # Arguments
+    ///
+    /// * `smiles` - One `(expiry, forward, strikes_and_vols)` entry per expiry, where expiries are year fractions.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if there is no smile, if expiries are not positive and strictly increasing,
+    /// if a smile has no quote, if a forward, strike or vol is not positive, or if fitting a smile fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_math::interpolation::linear::Linear;
+    /// use qlab_model::vol_surface::VolSurface;
+    ///
+    /// let surface: VolSurface<Linear<f64>> = VolSurface::try_new(&[
+    ///     (0.5, 100.0, vec![(90.0, 0.25), (100.0, 0.2), (110.0, 0.22)]),
+    ///     (1.0, 101.0, vec![(90.0, 0.24), (100.0, 0.21), (110.0, 0.22)]),
+    /// ])
+    /// .unwrap();
+    /// assert!((surface.black_vol(0.5, 100.0).unwrap() - 0.2).abs() < 1e-12);
+    /// ```
+    pub fn try_new(smiles: &[SmileQuotes<V>]) -> QLabResult<Self> {
+        if smiles.is_empty() {
+            return Err(InvalidInput("at least one smile is required".into()).into());
+        }
+        let mut fitted: Vec<Smile<I>> = Vec::with_capacity(smiles.len());
+        for (expiry, forward, strikes_and_vols) in smiles {
+            let (expiry, forward) = (*expiry, *forward);
+            if expiry <= V::zero() || forward <= V::zero() {
+                return Err(InvalidInput(
+                    format!("expiry: {expiry:?} and forward: {forward:?} must be positive").into(),
+                )
+                .into());
+            }
+            if let Some(previous) = fitted.last() {
+                if expiry <= previous.expiry {
+                    return Err(InvalidInput(
+                        format!("expiries must be strictly increasing: {expiry:?}").into(),
+                    )
+                    .into());
+                }
+            }
+            fitted.push(Self::fit_smile(expiry, forward, strikes_and_vols)?);
+        }
+        Ok(Self {
+            smiles: fitted,
+            time_interpolation: TimeInterpolation::default(),
+        })
+    }
+
+    fn fit_smile(expiry: V, forward: V, strikes_and_vols: &[(V, V)]) -> QLabResult<Smile<I>> {
+        let mut points = Vec::with_capacity(strikes_and_vols.len());
+        for &(strike, vol) in strikes_and_vols {
+            if strike <= V::zero() || vol <= V::zero() {
+                return Err(InvalidInput(
+                    format!("strike: {strike:?} and vol: {vol:?} must be positive").into(),
+                )
+                .into());
+            }
+            points.push(((strike / forward).ln(), vol * vol * expiry));
+        }
+        points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+        let (Some(&(lower, flat_variance)), Some(&(upper, _))) = (points.first(), points.last())
+        else {
+            return Err(InvalidInput(format!("no quote for the expiry: {expiry:?}").into()).into());
+        };
+        let interpolator = if points.len() > 1 {
+            Some(I::default().try_fit(&points)?)
+        } else {
+            None
+        };
+        Ok(Smile {
+            expiry,
+            forward,
+            lower,
+            upper,
+            interpolator,
+            flat_variance,
+        })
+    }
+
+    /// Sets how the surface interpolates between expiries.
+    #[must_use]
+    pub fn with_time_interpolation(mut self, time_interpolation: TimeInterpolation) -> Self {
+        self.time_interpolation = time_interpolation;
+        self
+    }
+
+    #[must_use]
+    pub fn time_interpolation(&self) -> TimeInterpolation {
+        self.time_interpolation
+    }
+
+    /// Returns the quoted expiries.
+    #[must_use]
+    pub fn expiries(&self) -> Vec<V> {
+        self.smiles.iter().map(|smile| smile.expiry).collect()
+    }
+
+    /// Returns the forward at `t`, interpolated linearly between expiries and flat outside them.
+    #[must_use]
+    pub fn forward(&self, t: V) -> V {
+        let (left, right, weight) = self.bracket(t);
+        left.forward + (right.forward - left.forward) * weight
+    }
+
+    /// Finds the smiles around `t` and the linear weight of the right one.
+    fn bracket(&self, t: V) -> (&Smile<I>, &Smile<I>, V) {
+        let pos = self.smiles.partition_point(|smile| smile.expiry < t);
+        if pos == 0 {
+            return (&self.smiles[0], &self.smiles[0], V::zero());
+        }
+        if pos == self.smiles.len() {
+            let last = &self.smiles[pos - 1];
+            return (last, last, V::zero());
+        }
+        let (left, right) = (&self.smiles[pos - 1], &self.smiles[pos]);
+        (
+            left,
+            right,
+            (t - left.expiry) / (right.expiry - left.expiry),
+        )
+    }
+
+    /// Calculates the total variance `vol^2 * t` at time `t` and `strike`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `t` or `strike` is not positive, or an error if the smile interpolation fails.
+    pub fn total_variance(&self, t: V, strike: V) -> QLabResult<V> {
+        if t <= V::zero() || strike <= V::zero() {
+            return Err(InvalidInput(
+                format!("t: {t:?} and strike: {strike:?} must be positive").into(),
+            )
+            .into());
+        }
+        let k = (strike / self.forward(t)).ln();
+        let (left, right, weight) = self.bracket(t);
+        if std::ptr::eq(left, right) {
+            // flat vol extrapolation scales the total variance with time
+            return Ok(left.total_variance(k)? * t / left.expiry);
+        }
+        let left_variance = left.total_variance(k)?;
+        let right_variance = right.total_variance(k)?;
+        match self.time_interpolation {
+            TimeInterpolation::TotalVariance => {
+                Ok(left_variance + (right_variance - left_variance) * weight)
+            }
+            TimeInterpolation::Volatility => {
+                let left_vol = (left_variance / left.expiry).sqrt();
+                let right_vol = (right_variance / right.expiry).sqrt();
+                let vol = left_vol + (right_vol - left_vol) * weight;
+                Ok(vol * vol * t)
+            }
+        }
+    }
+
+    /// Calculates the Black volatility at time `t` and `strike`.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`Self::total_variance`].
+    pub fn black_vol(&self, t: V, strike: V) -> QLabResult<V> {
+        Ok((self.total_variance(t, strike)? / t).sqrt())
+    }
+
+    /// Calculates the forward volatility between `t1` and `t2` at `strike`,
+    /// i.e. `sqrt((w(t2) - w(t1)) / (t2 - t1))` for the total variance `w`, as used by forward-starting options.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `t1` is negative, if `t2` is not after `t1`,
+    /// or if the total variance decreases between `t1` and `t2`.
+    pub fn forward_vol(&self, t1: V, t2: V, strike: V) -> QLabResult<V> {
+        if t1 < V::zero() || t2 <= t1 {
+            return Err(InvalidInput(
+                format!("t1: {t1:?} must be non-negative and before t2: {t2:?}").into(),
+            )
+            .into());
+        }
+        let first = if t1 == V::zero() {
+            V::zero()
+        } else {
+            self.total_variance(t1, strike)?
+        };
+        let forward_variance = self.total_variance(t2, strike)? - first;
+        if forward_variance < V::zero() {
+            return Err(InvalidInput(
+                format!("total variance decreases between t1: {t1:?} and t2: {t2:?}").into(),
+            )
+            .into());
+        }
+        Ok((forward_variance / (t2 - t1)).sqrt())
+    }
+}
+
+/// A [`VolSurface`] anchored at a reference date, measuring time to dates with the day count `D`.
+///
+/// The expiries of the surface must have been computed with the same day count from the same reference date.
+pub struct DatedVolSurface<D: DayCount, I: Interpolator> {
+    reference_date: Date,
+    surface: VolSurface<I>,
+    _day_count: PhantomData<D>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> DatedVolSurface<D, I> {
+    #[must_use]
+    pub fn new(reference_date: Date, surface: VolSurface<I>) -> Self {
+        Self {
+            reference_date,
+            surface,
+            _day_count: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn reference_date(&self) -> Date {
+        self.reference_date
+    }
+
+    #[must_use]
+    pub fn surface(&self) -> &VolSurface<I> {
+        &self.surface
+    }
+
+    /// Returns the year fraction from the reference date to `date`.
+    ///
+    /// # Errors
+    /// Returns an error if the day count fraction cannot be calculated.
+    pub fn time_to(&self, date: Date) -> QLabResult<V> {
+        D::calculate_day_count_fraction(self.reference_date, date)
+    }
+
+    /// Calculates the Black volatility of an option expiring on `expiry_date`.
+    ///
+    /// # Errors
+    /// Returns an error if `expiry_date` is not after the reference date or if the surface lookup fails.
+    pub fn black_vol(&self, expiry_date: Date, strike: V) -> QLabResult<V> {
+        self.surface.black_vol(self.time_to(expiry_date)?, strike)
+    }
+
+    /// Calculates the forward volatility between `start_date` and `end_date`.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`VolSurface::forward_vol`].
+    pub fn forward_vol(&self, start_date: Date, end_date: Date, strike: V) -> QLabResult<V> {
+        self.surface
+            .forward_vol(self.time_to(start_date)?, self.time_to(end_date)?, strike)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+
+    fn surface() -> VolSurface<Linear<f64>> {
+        VolSurface::try_new(&[
+            (0.5, 100.0, vec![(110.0, 0.22), (90.0, 0.25), (100.0, 0.2)]),
+            (1.0, 100.0, vec![(90.0, 0.24), (100.0, 0.22), (110.0, 0.23)]),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_black_vol() {
+        let surface = surface();
+        assert!((surface.black_vol(1.0, 110.0).unwrap() - 0.23).abs() < 1e-12);
+        // flat extrapolation in strike and in time
+        assert!((surface.black_vol(0.5, 200.0).unwrap() - 0.22).abs() < 1e-12);
+        assert!((surface.black_vol(0.1, 100.0).unwrap() - 0.2).abs() < 1e-12);
+        assert!((surface.black_vol(5.0, 100.0).unwrap() - 0.22).abs() < 1e-12);
+        // linear in total variance between expiries
+        let variance = surface.total_variance(0.75, 100.0).unwrap();
+        assert!((variance - f64::midpoint(0.5 * 0.04, 1.0 * 0.0484)).abs() < 1e-12);
+        assert!(surface.black_vol(0.5, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_time_interpolation() {
+        let surface = surface().with_time_interpolation(TimeInterpolation::Volatility);
+        assert!((surface.black_vol(0.75, 100.0).unwrap() - 0.21).abs() < 1e-12);
+        assert_eq!(surface.time_interpolation(), TimeInterpolation::Volatility);
+    }
+
+    #[test]
+    fn test_forward_vol() {
+        let surface = surface();
+        // the forward variance between expiries is 1.0 * 0.22^2 - 0.5 * 0.2^2
+        let forward_vol = surface.forward_vol(0.5, 1.0, 100.0).unwrap();
+        assert!((forward_vol - ((0.0484_f64 - 0.02) / 0.5).sqrt()).abs() < 1e-12);
+        let spot_vol = surface.forward_vol(0.0, 0.5, 100.0).unwrap();
+        assert!((spot_vol - 0.2).abs() < 1e-12);
+        assert!(surface.forward_vol(1.0, 0.5, 100.0).is_err());
+        let inverted = VolSurface::<Linear<f64>>::try_new(&[
+            (0.5, 100.0, vec![(100.0, 0.4)]),
+            (1.0, 100.0, vec![(100.0, 0.2)]),
+        ])
+        .unwrap();
+        assert!(inverted.forward_vol(0.5, 1.0, 100.0).is_err());
+    }
+
+    #[test]
+    fn test_dated() {
+        use qlab_time::day_count::act_365::Act365;
+
+        let reference_date = Date::from_ymd(2023, 1, 1).unwrap();
+        let dated: DatedVolSurface<Act365, _> = DatedVolSurface::new(
+            reference_date,
+            VolSurface::<Linear<f64>>::try_new(&[(1.0, 100.0, vec![(100.0, 0.2)])]).unwrap(),
+        );
+        let expiry = Date::from_ymd(2024, 1, 1).unwrap();
+        assert!((dated.time_to(expiry).unwrap() - 1.0).abs() < f64::EPSILON);
+        assert!((dated.black_vol(expiry, 100.0).unwrap() - 0.2).abs() < 1e-12);
+        let forward_vol = dated.forward_vol(Date::from_ymd(2023, 7, 2).unwrap(), expiry, 100.0);
+        assert!((forward_vol.unwrap() - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_invalid_smiles() {
+        assert!(VolSurface::<Linear<f64>>::try_new(&[]).is_err());
+        assert!(VolSurface::<Linear<f64>>::try_new(&[
+            (1.0, 100.0, vec![(100.0, 0.2)]),
+            (0.5, 100.0, vec![(100.0, 0.2)]),
+        ])
+        .is_err());
+        assert!(VolSurface::<Linear<f64>>::try_new(&[(1.0, 100.0, vec![])]).is_err());
+        let single = VolSurface::<Linear<f64>>::try_new(&[(1.0, 100.0, vec![(100.0, 0.2)])]);
+        assert!((single.unwrap().black_vol(2.0, 50.0).unwrap() - 0.2).abs() < 1e-12);
+    }
+}
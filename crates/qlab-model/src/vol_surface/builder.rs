@@ -0,0 +1,318 @@
+use crate::black;
+use crate::option_type::OptionType;
+use crate::vol_surface::{SmileQuotes, VolSurface};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::distribution::normal;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+
+/// The strike of an option quote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteStrike<V> {
+    /// An absolute strike.
+    Absolute(V),
+    /// A forward Black delta: positive for calls (e.g. `0.25`) and negative for puts (e.g. `-0.25`).
+    ///
+    /// Only valid together with a [`QuoteValue::Vol`], as the strike depends on the vol.
+    Delta(V),
+}
+
+/// The quoted value of an option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteValue<V> {
+    /// A Black volatility.
+    Vol(V),
+    /// An undiscounted option premium.
+    Premium(OptionType, V),
+}
+
+/// A raw option quote.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolQuote<V> {
+    /// The time to expiry in years.
+    pub expiry: V,
+    /// The forward of the underlying to the expiry.
+    pub forward: V,
+    pub strike: QuoteStrike<V>,
+    pub value: QuoteValue<V>,
+}
+
+impl<V: Value> VolQuote<V> {
+    /// Converts the quote into an absolute strike and a Black volatility.
+    fn strike_and_vol(&self) -> QLabResult<(V, V)> {
+        match (self.strike, self.value) {
+            (QuoteStrike::Absolute(strike), QuoteValue::Vol(vol)) => Ok((strike, vol)),
+            (QuoteStrike::Absolute(strike), QuoteValue::Premium(option_type, premium)) => {
+                let vol =
+                    black::implied_vol(option_type, self.forward, strike, self.expiry, premium)?;
+                Ok((strike, vol))
+            }
+            (QuoteStrike::Delta(delta), QuoteValue::Vol(vol)) => {
+                if delta == V::zero() || delta.abs() >= V::one() {
+                    return Err(InvalidInput(
+                        format!("delta: {delta:?} must lie in (-1, 0) or (0, 1)").into(),
+                    )
+                    .into());
+                }
+                let d1 = normal::inverse_cdf(if delta > V::zero() {
+                    delta
+                } else {
+                    delta + V::one()
+                });
+                let std_dev = vol * self.expiry.sqrt();
+                let strike = self.forward
+                    * (-d1 * std_dev + std_dev * std_dev / (V::one() + V::one())).exp();
+                Ok((strike, vol))
+            }
+            (QuoteStrike::Delta(delta), QuoteValue::Premium(..)) => Err(InvalidInput(
+                format!("delta: {delta:?} cannot be combined with a premium quote").into(),
+            )
+            .into()),
+        }
+    }
+}
+
+/// A fitted surface together with the quotes rejected as arbitrageable.
+pub struct BuiltSurface<I: Interpolator> {
+    pub surface: VolSurface<I>,
+    pub rejected: Vec<VolQuote<I::Value>>,
+}
+
+/// Builds a [`VolSurface`] from raw option quotes.
+///
+/// Quotes sharing an expiry form a smile and must share the forward.
+/// Before fitting, quotes violating static arbitrage are removed, expiry by expiry in increasing order:
+/// the total variance must not decrease from the previous expiry at constant log-moneyness (calendar),
+/// then the call prices must be non-increasing with slopes no steeper than -1 and convex in strike (butterfly).
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_model::vol_surface::builder::{QuoteStrike, QuoteValue, VolQuote, VolSurfaceBuilder};
+///
+/// let quote = |expiry, strike, vol| VolQuote {
+///     expiry,
+///     forward: 100.0,
+///     strike: QuoteStrike::Absolute(strike),
+///     value: QuoteValue::Vol(vol),
+/// };
+/// let built = VolSurfaceBuilder::new()
+///     .with_quote(quote(1.0, 90.0, 0.24))
+///     .with_quote(quote(1.0, 100.0, 0.2))
+///     .with_quote(quote(1.0, 110.0, 0.22))
+///     .build::<Linear<f64>>()
+///     .unwrap();
+/// assert!(built.rejected.is_empty());
+/// assert!((built.surface.black_vol(1.0, 100.0).unwrap() - 0.2).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VolSurfaceBuilder<V> {
+    quotes: Vec<VolQuote<V>>,
+}
+
+struct NormalizedQuote<V> {
+    index: usize,
+    strike: V,
+    vol: V,
+}
+
+impl<V: Value> VolSurfaceBuilder<V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { quotes: Vec::new() }
+    }
+
+    /// Adds a quote.
+    #[must_use]
+    pub fn with_quote(mut self, quote: VolQuote<V>) -> Self {
+        self.quotes.push(quote);
+        self
+    }
+
+    /// Returns the quotes added so far.
+    #[must_use]
+    pub fn quotes(&self) -> &[VolQuote<V>] {
+        &self.quotes
+    }
+
+    /// Cleans the quotes and fits the surface.
+    ///
+    /// # Errors
+    /// Returns an error if a quote cannot be converted into a strike and a vol,
+    /// if quotes of the same expiry have different forwards, or if no quote survives the cleaning.
+    pub fn build<I: Interpolator<Value = V>>(&self) -> QLabResult<BuiltSurface<I>> {
+        let mut order: Vec<usize> = (0..self.quotes.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.quotes[a]
+                .expiry
+                .partial_cmp(&self.quotes[b].expiry)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut rejected = Vec::new();
+        let mut smiles: Vec<SmileQuotes<V>> = Vec::new();
+        let mut previous: Option<VolSurface<I>> = None;
+        for group in order.chunk_by(|&a, &b| self.quotes[a].expiry == self.quotes[b].expiry) {
+            let (expiry, forward) = (self.quotes[group[0]].expiry, self.quotes[group[0]].forward);
+            let mut smile = Vec::with_capacity(group.len());
+            for &index in group {
+                let quote = &self.quotes[index];
+                if quote.forward != forward {
+                    return Err(InvalidInput(
+                        format!("quotes of the expiry: {expiry:?} have different forwards").into(),
+                    )
+                    .into());
+                }
+                let (strike, vol) = quote.strike_and_vol()?;
+                smile.push(NormalizedQuote { index, strike, vol });
+            }
+            smile.sort_by(|a, b| {
+                a.strike
+                    .partial_cmp(&b.strike)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            if let Some(previous) = &previous {
+                let mut accepted = Vec::with_capacity(smile.len());
+                for quote in smile {
+                    let k = (quote.strike / forward).ln();
+                    let floor = previous.smiles[0].total_variance(k)?;
+                    if quote.vol * quote.vol * expiry < floor {
+                        rejected.push(self.quotes[quote.index]);
+                    } else {
+                        accepted.push(quote);
+                    }
+                }
+                smile = accepted;
+            }
+            for index in remove_butterfly_arbitrage(&mut smile, expiry, forward) {
+                rejected.push(self.quotes[index]);
+            }
+            if smile.is_empty() {
+                continue;
+            }
+            let points: Vec<_> = smile
+                .iter()
+                .map(|quote| (quote.strike, quote.vol))
+                .collect();
+            previous = Some(VolSurface::try_new(&[(expiry, forward, points.clone())])?);
+            smiles.push((expiry, forward, points));
+        }
+        Ok(BuiltSurface {
+            surface: VolSurface::try_new(&smiles)?,
+            rejected,
+        })
+    }
+}
+
+/// Removes quotes until the call prices of the smile are non-increasing, no steeper than -1 and convex in strike,
+/// returning the indices of the removed quotes.
+fn remove_butterfly_arbitrage<V: Value>(
+    smile: &mut Vec<NormalizedQuote<V>>,
+    expiry: V,
+    forward: V,
+) -> Vec<usize> {
+    let tolerance = V::epsilon().sqrt();
+    let mut removed = Vec::new();
+    loop {
+        let prices: Vec<_> = smile
+            .iter()
+            .map(|quote| black::price(OptionType::Call, forward, quote.strike, quote.vol, expiry))
+            .collect();
+        let slopes: Vec<_> = (1..smile.len())
+            .map(|i| (prices[i] - prices[i - 1]) / (smile[i].strike - smile[i - 1].strike))
+            .collect();
+        let violation = slopes
+            .iter()
+            .position(|&slope| slope > tolerance || slope < -V::one() - tolerance)
+            .map(|i| i + 1)
+            .or_else(|| {
+                slopes
+                    .windows(2)
+                    .position(|pair| pair[1] < pair[0] - tolerance)
+                    .map(|i| i + 1)
+            });
+        match violation {
+            Some(i) => removed.push(smile.remove(i).index),
+            None => return removed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+
+    fn quote(expiry: f64, strike: f64, vol: f64) -> VolQuote<f64> {
+        VolQuote {
+            expiry,
+            forward: 100.0,
+            strike: QuoteStrike::Absolute(strike),
+            value: QuoteValue::Vol(vol),
+        }
+    }
+
+    #[test]
+    fn test_quote_conversion() {
+        let premium = black::price(OptionType::Put, 100.0, 95.0, 0.3, 2.0);
+        let quote = VolQuote {
+            expiry: 2.0,
+            forward: 100.0,
+            strike: QuoteStrike::Absolute(95.0),
+            value: QuoteValue::Premium(OptionType::Put, premium),
+        };
+        let (strike, vol) = quote.strike_and_vol().unwrap();
+        assert!((strike - 95.0_f64).abs() < f64::EPSILON);
+        assert!((vol - 0.3_f64).abs() < 1e-9);
+
+        // a 50 delta call strike sits slightly above the forward
+        let quote = VolQuote {
+            strike: QuoteStrike::Delta(0.5),
+            value: QuoteValue::Vol(0.2),
+            ..quote
+        };
+        let (strike, _) = quote.strike_and_vol().unwrap();
+        assert!((strike - 100.0 * (0.02_f64 * 2.0).exp()).abs() < 1e-9);
+        let put = VolQuote {
+            strike: QuoteStrike::Delta(-0.25),
+            ..quote
+        };
+        assert!(put.strike_and_vol().unwrap().0 < 100.0);
+        let invalid = VolQuote {
+            strike: QuoteStrike::Delta(0.25),
+            value: QuoteValue::Premium(OptionType::Call, 5.0),
+            ..quote
+        };
+        assert!(invalid.strike_and_vol().is_err());
+    }
+
+    #[test]
+    fn test_butterfly_cleaning() {
+        // the 100 strike vol spike makes the call prices concave
+        let built = VolSurfaceBuilder::new()
+            .with_quote(quote(1.0, 90.0, 0.2))
+            .with_quote(quote(1.0, 100.0, 0.6))
+            .with_quote(quote(1.0, 110.0, 0.2))
+            .build::<Linear<f64>>()
+            .unwrap();
+        assert_eq!(built.rejected, vec![quote(1.0, 100.0, 0.6)]);
+        assert!((built.surface.black_vol(1.0, 100.0).unwrap() - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_calendar_cleaning() {
+        let built = VolSurfaceBuilder::new()
+            .with_quote(quote(2.0, 100.0, 0.1))
+            .with_quote(quote(1.0, 100.0, 0.2))
+            .with_quote(quote(2.0, 110.0, 0.2))
+            .with_quote(quote(1.0, 110.0, 0.2))
+            .build::<Linear<f64>>()
+            .unwrap();
+        assert_eq!(built.rejected, vec![quote(2.0, 100.0, 0.1)]);
+        assert_eq!(built.surface.expiries(), vec![1.0, 2.0]);
+        assert!(VolSurfaceBuilder::<f64>::new()
+            .build::<Linear<f64>>()
+            .is_err());
+    }
+}
@@ -0,0 +1,465 @@
+use crate::option_type::OptionType;
+use crate::vol_surface::{SmileQuotes, VolSurface};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::distribution::normal;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::root_finding;
+use qlab_math::value::Value;
+
+/// How the delta of an FX option is measured, per unit of foreign notional.
+///
+/// Spot deltas are discounted with the foreign rate to the delivery date. Premium-adjusted deltas,
+/// the convention of pairs whose premium is paid in the foreign currency such as USD/JPY,
+/// subtract the premium from the hedge and are `K / F * N(d2)` instead of `N(d1)` for a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeltaConvention {
+    Spot,
+    Forward,
+    PremiumAdjustedSpot,
+    PremiumAdjustedForward,
+}
+
+impl DeltaConvention {
+    #[must_use]
+    pub fn is_premium_adjusted(self) -> bool {
+        matches!(
+            self,
+            Self::PremiumAdjustedSpot | Self::PremiumAdjustedForward
+        )
+    }
+
+    #[must_use]
+    pub fn is_spot(self) -> bool {
+        matches!(self, Self::Spot | Self::PremiumAdjustedSpot)
+    }
+}
+
+/// How the at-the-money strike of an FX smile is defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AtmConvention {
+    /// The strike at which a straddle has zero delta, the market standard for most pairs.
+    #[default]
+    DeltaNeutral,
+    /// The forward.
+    Forward,
+}
+
+/// Returns the strike at which `option_type` has `delta` by `convention`,
+/// where `delta` is positive for calls and negative for puts.
+///
+/// A premium-adjusted call delta is not monotone in the strike and peaks below one;
+/// the strike above its peak is returned, as the market does.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `forward`, `vol`, `t` or `foreign_discount_factor` is not positive,
+/// if `delta` has the wrong sign or is not attainable by `convention`, or if the root search fails.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_model::option_type::OptionType;
+/// use qlab_model::vol_surface::fx::{delta_to_strike, strike_to_delta, DeltaConvention};
+///
+/// let convention = DeltaConvention::PremiumAdjustedSpot;
+/// let strike: f64 = delta_to_strike(OptionType::Call, 0.25, 150.0, 0.1, 1.0, 0.95, convention).unwrap();
+/// let delta = strike_to_delta(OptionType::Call, strike, 150.0, 0.1, 1.0, 0.95, convention);
+/// assert!((delta - 0.25).abs() < 1e-12);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn delta_to_strike<V: Value>(
+    option_type: OptionType,
+    delta: V,
+    forward: V,
+    vol: V,
+    t: V,
+    foreign_discount_factor: V,
+    convention: DeltaConvention,
+) -> QLabResult<V> {
+    if forward <= V::zero() || vol <= V::zero() || t <= V::zero() {
+        return Err(InvalidInput(
+            format!("forward: {forward:?}, vol: {vol:?} and t: {t:?} must be positive").into(),
+        )
+        .into());
+    }
+    if foreign_discount_factor <= V::zero() {
+        return Err(InvalidInput(
+            format!("foreign_discount_factor: {foreign_discount_factor:?} must be positive").into(),
+        )
+        .into());
+    }
+    let sign = option_type.sign::<V>();
+    let scale = if convention.is_spot() {
+        foreign_discount_factor
+    } else {
+        V::one()
+    };
+    // the delta per unit of the discount factor, positive for both rights
+    let target = sign * delta / scale;
+    if target <= V::zero() || target >= V::one() {
+        return Err(InvalidInput(
+            format!("delta: {delta:?} is not attainable by a {option_type:?} in {convention:?}")
+                .into(),
+        )
+        .into());
+    }
+    let two = V::one() + V::one();
+    let std_dev = vol * t.sqrt();
+    let unadjusted = -sign * normal::inverse_cdf(target) * std_dev + std_dev * std_dev / two;
+    if !convention.is_premium_adjusted() {
+        return Ok(forward * unadjusted.exp());
+    }
+    // the premium lowers the call delta and raises the size of the put delta at every strike,
+    // so the adjusted strike is below the unadjusted one for both rights
+    let excess = |log_moneyness: V| {
+        Ok(log_moneyness.exp() * normal::cdf(sign * d2(log_moneyness, std_dev)) - target)
+    };
+    let lower = match option_type {
+        OptionType::Call => {
+            // the peak of the call delta, where `std_dev * N(d2) = n(d2)`
+            let peak = root_finding::brent(
+                |d2| Ok(std_dev * normal::cdf(d2) - normal::pdf(d2)),
+                -std_dev,
+                std_dev + V::from_i8(10).unwrap(),
+                V::epsilon(),
+            )?;
+            let lower = -peak * std_dev - std_dev * std_dev / two;
+            if excess(lower)? < V::zero() {
+                return Err(InvalidInput(
+                    format!("delta: {delta:?} exceeds the largest premium-adjusted call delta")
+                        .into(),
+                )
+                .into());
+            }
+            lower
+        }
+        OptionType::Put => {
+            let mut lower = unadjusted - std_dev;
+            while excess(lower)? > V::zero() {
+                lower = lower - two * (unadjusted - lower);
+            }
+            lower
+        }
+    };
+    let log_moneyness = root_finding::brent(excess, lower, unadjusted, V::epsilon())?;
+    Ok(forward * log_moneyness.exp())
+}
+
+/// Returns the delta of `option_type` at `strike` by `convention`, positive for calls and negative for puts.
+///
+/// The inputs are expected to be positive, as validated by [`delta_to_strike`].
+#[allow(clippy::too_many_arguments)]
+#[must_use]
+pub fn strike_to_delta<V: Value>(
+    option_type: OptionType,
+    strike: V,
+    forward: V,
+    vol: V,
+    t: V,
+    foreign_discount_factor: V,
+    convention: DeltaConvention,
+) -> V {
+    let sign = option_type.sign::<V>();
+    let std_dev = vol * t.sqrt();
+    let log_moneyness = (strike / forward).ln();
+    let delta = if convention.is_premium_adjusted() {
+        sign * log_moneyness.exp() * normal::cdf(sign * d2(log_moneyness, std_dev))
+    } else {
+        sign * normal::cdf(sign * (d2(log_moneyness, std_dev) + std_dev))
+    };
+    if convention.is_spot() {
+        delta * foreign_discount_factor
+    } else {
+        delta
+    }
+}
+
+fn d2<V: Value>(log_moneyness: V, std_dev: V) -> V {
+    -log_moneyness / std_dev - std_dev / (V::one() + V::one())
+}
+
+/// Returns the at-the-money strike of a smile with the at-the-money `vol`.
+///
+/// The delta-neutral strike is `F * exp(vol^2 * t / 2)`, or `F * exp(-vol^2 * t / 2)`
+/// with premium-adjusted deltas.
+#[must_use]
+pub fn atm_strike<V: Value>(
+    forward: V,
+    vol: V,
+    t: V,
+    atm_convention: AtmConvention,
+    delta_convention: DeltaConvention,
+) -> V {
+    match atm_convention {
+        AtmConvention::Forward => forward,
+        AtmConvention::DeltaNeutral => {
+            let half_variance = vol * vol * t / (V::one() + V::one());
+            if delta_convention.is_premium_adjusted() {
+                forward * (-half_variance).exp()
+            } else {
+                forward * half_variance.exp()
+            }
+        }
+    }
+}
+
+/// The risk reversal and butterfly quoted at a delta of an FX smile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaPillar<V> {
+    /// The delta of the call, e.g. `0.25`; the put is quoted at its negative.
+    pub delta: V,
+    /// The call vol less the put vol.
+    pub risk_reversal: V,
+    /// The average of the call and put vols less the at-the-money vol.
+    pub butterfly: V,
+}
+
+/// The broker quotes of an FX smile at one expiry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FxSmileQuote<V> {
+    /// The time to expiry in years.
+    pub expiry: V,
+    pub forward: V,
+    /// The discount factor of the foreign currency to the delivery date, used by spot deltas.
+    pub foreign_discount_factor: V,
+    pub atm_vol: V,
+    pub pillars: Vec<DeltaPillar<V>>,
+}
+
+impl<V: Value> FxSmileQuote<V> {
+    /// Converts the quotes into strikes and vols: the at-the-money point followed by a put and a call per pillar.
+    ///
+    /// Butterflies are read as smile strangles, so that the call and put vols of a pillar are
+    /// `atm + butterfly ± risk_reversal / 2`.
+    ///
+    /// # Errors
+    /// Returns an error if the strike of a pillar cannot be found, see [`delta_to_strike`].
+    pub fn strikes_and_vols(
+        &self,
+        delta_convention: DeltaConvention,
+        atm_convention: AtmConvention,
+    ) -> QLabResult<Vec<(V, V)>> {
+        let mut points = Vec::with_capacity(2 * self.pillars.len() + 1);
+        points.push((
+            atm_strike(
+                self.forward,
+                self.atm_vol,
+                self.expiry,
+                atm_convention,
+                delta_convention,
+            ),
+            self.atm_vol,
+        ));
+        let two = V::one() + V::one();
+        for pillar in &self.pillars {
+            let wing = self.atm_vol + pillar.butterfly;
+            for (option_type, vol) in [
+                (OptionType::Put, wing - pillar.risk_reversal / two),
+                (OptionType::Call, wing + pillar.risk_reversal / two),
+            ] {
+                let strike = delta_to_strike(
+                    option_type,
+                    option_type.sign::<V>() * pillar.delta,
+                    self.forward,
+                    vol,
+                    self.expiry,
+                    self.foreign_discount_factor,
+                    delta_convention,
+                )?;
+                points.push((strike, vol));
+            }
+        }
+        Ok(points)
+    }
+}
+
+/// A [`VolSurface`] built from the at-the-money, risk reversal and butterfly quotes of FX options,
+/// with the quoted deltas converted to strikes by the conventions of the currency pair.
+pub struct FxVolSurface<I: Interpolator> {
+    surface: VolSurface<I>,
+    delta_convention: DeltaConvention,
+    atm_convention: AtmConvention,
+}
+
+impl<V: Value, I: Interpolator<Value = V>> FxVolSurface<I> {
+    /// Creates a surface from one smile quote per expiry.
+    ///
+    /// # Errors
+    /// Returns an error if the quotes cannot be converted to strikes,
+    /// or under the same conditions as [`VolSurface::try_new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_math::interpolation::linear::Linear;
+    /// use qlab_model::vol_surface::fx::{AtmConvention, DeltaConvention, DeltaPillar, FxSmileQuote, FxVolSurface};
+    ///
+    /// let quote = FxSmileQuote {
+    ///     expiry: 1.0,
+    ///     forward: 1.1,
+    ///     foreign_discount_factor: 0.97,
+    ///     atm_vol: 0.08,
+    ///     pillars: vec![DeltaPillar { delta: 0.25, risk_reversal: -0.005, butterfly: 0.002 }],
+    /// };
+    /// let surface: FxVolSurface<Linear<f64>> =
+    ///     FxVolSurface::try_new(&[quote], DeltaConvention::Spot, AtmConvention::DeltaNeutral).unwrap();
+    /// let atm = 1.1 * (0.5_f64 * 0.08 * 0.08).exp();
+    /// assert!((surface.black_vol(1.0, atm).unwrap() - 0.08).abs() < 1e-12);
+    /// ```
+    pub fn try_new(
+        quotes: &[FxSmileQuote<V>],
+        delta_convention: DeltaConvention,
+        atm_convention: AtmConvention,
+    ) -> QLabResult<Self> {
+        let smiles = quotes
+            .iter()
+            .map(|quote| {
+                Ok((
+                    quote.expiry,
+                    quote.forward,
+                    quote.strikes_and_vols(delta_convention, atm_convention)?,
+                ))
+            })
+            .collect::<QLabResult<Vec<SmileQuotes<V>>>>()?;
+        Ok(Self {
+            surface: VolSurface::try_new(&smiles)?,
+            delta_convention,
+            atm_convention,
+        })
+    }
+
+    #[must_use]
+    pub fn surface(&self) -> &VolSurface<I> {
+        &self.surface
+    }
+
+    #[must_use]
+    pub fn delta_convention(&self) -> DeltaConvention {
+        self.delta_convention
+    }
+
+    #[must_use]
+    pub fn atm_convention(&self) -> AtmConvention {
+        self.atm_convention
+    }
+
+    /// Calculates the Black volatility at time `t` and `strike`.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`VolSurface::black_vol`].
+    pub fn black_vol(&self, t: V, strike: V) -> QLabResult<V> {
+        self.surface.black_vol(t, strike)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+
+    const CONVENTIONS: [DeltaConvention; 4] = [
+        DeltaConvention::Spot,
+        DeltaConvention::Forward,
+        DeltaConvention::PremiumAdjustedSpot,
+        DeltaConvention::PremiumAdjustedForward,
+    ];
+
+    #[test]
+    fn test_fx_vol_surface() {
+        let (forward, vol, t, discount) = (1.1_f64, 0.12, 2.0, 0.95);
+        for convention in CONVENTIONS {
+            for (option_type, delta) in [
+                (OptionType::Call, 0.1),
+                (OptionType::Call, 0.25),
+                (OptionType::Put, -0.25),
+                (OptionType::Put, -0.1),
+            ] {
+                let strike =
+                    delta_to_strike(option_type, delta, forward, vol, t, discount, convention)
+                        .unwrap();
+                let round_trip =
+                    strike_to_delta(option_type, strike, forward, vol, t, discount, convention);
+                assert!((round_trip - delta).abs() < 1e-12, "{convention:?}");
+            }
+            // the delta-neutral straddle
+            let atm = atm_strike(forward, vol, t, AtmConvention::DeltaNeutral, convention);
+            let call =
+                strike_to_delta(OptionType::Call, atm, forward, vol, t, discount, convention);
+            let put = strike_to_delta(OptionType::Put, atm, forward, vol, t, discount, convention);
+            assert!((call + put).abs() < 1e-12, "{convention:?}");
+            assert!(delta_to_strike(
+                OptionType::Call,
+                -0.25,
+                forward,
+                vol,
+                t,
+                discount,
+                convention
+            )
+            .is_err());
+        }
+        // a premium-adjusted call delta peaks well below one at a high vol
+        assert!(delta_to_strike(
+            OptionType::Call,
+            0.8,
+            forward,
+            1.0,
+            t,
+            discount,
+            DeltaConvention::PremiumAdjustedForward
+        )
+        .is_err());
+        assert!(delta_to_strike(
+            OptionType::Call,
+            0.8,
+            forward,
+            1.0,
+            t,
+            discount,
+            DeltaConvention::Forward
+        )
+        .is_ok());
+
+        let quote = |expiry: f64, atm_vol| FxSmileQuote {
+            expiry,
+            forward,
+            foreign_discount_factor: discount,
+            atm_vol,
+            pillars: vec![
+                DeltaPillar {
+                    delta: 0.25,
+                    risk_reversal: 0.01,
+                    butterfly: 0.003,
+                },
+                DeltaPillar {
+                    delta: 0.1,
+                    risk_reversal: 0.02,
+                    butterfly: 0.01,
+                },
+            ],
+        };
+        let convention = DeltaConvention::PremiumAdjustedSpot;
+        let surface: FxVolSurface<Linear<f64>> = FxVolSurface::try_new(
+            &[quote(0.5, 0.1), quote(1.0, 0.11)],
+            convention,
+            AtmConvention::Forward,
+        )
+        .unwrap();
+        assert_eq!(surface.delta_convention(), convention);
+        assert_eq!(surface.atm_convention(), AtmConvention::Forward);
+        assert!((surface.black_vol(1.0, forward).unwrap() - 0.11).abs() < 1e-12);
+        let points = quote(1.0, 0.11)
+            .strikes_and_vols(convention, AtmConvention::Forward)
+            .unwrap();
+        assert_eq!(points.len(), 5);
+        for &(strike, vol) in &points {
+            assert!((surface.black_vol(1.0, strike).unwrap() - vol).abs() < 1e-12);
+        }
+        // the put wing lies below the forward and the call wing above it
+        assert!(points[1].0 < forward && points[2].0 > forward);
+        assert!((points[2].1 - points[1].1 - 0.01).abs() < 1e-15);
+    }
+}
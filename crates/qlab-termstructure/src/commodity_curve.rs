@@ -0,0 +1,445 @@
+use crate::diagnostics::BootstrapDiagnostics;
+use crate::pillar_curve::PillarCurve;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::marker::PhantomData;
+
+/// Multiplicative factors by calendar month which shape the forwards of a seasonal commodity,
+/// e.g. natural gas trading higher for winter delivery.
+///
+/// A factor of one leaves the forward of a month on the carry curve; the factors are rescaled so that
+/// their geometric mean is one, so that the seasonality shapes the curve without shifting its level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Seasonality<V> {
+    factors: [V; 12],
+}
+
+impl<V: Value> Seasonality<V> {
+    /// Creates a seasonality from the factors of January to December.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a factor is not positive.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn new(factors: [V; 12]) -> QLabResult<Self> {
+        if let Some(factor) = factors.iter().find(|&&factor| factor <= V::zero()) {
+            return Err(InvalidInput(
+                format!("seasonal factor: {factor:?} must be positive").into(),
+            )
+            .into());
+        }
+        let mean_log = factors
+            .iter()
+            .fold(V::zero(), |acc, &factor| acc + factor.ln())
+            / V::from_u8(12).unwrap();
+        let scale = (-mean_log).exp();
+        Ok(Self {
+            factors: factors.map(|factor| factor * scale),
+        })
+    }
+
+    /// Returns a seasonality with every factor equal to one.
+    #[must_use]
+    pub fn flat() -> Self {
+        Self {
+            factors: [V::one(); 12],
+        }
+    }
+
+    /// Returns the normalized factors of January to December.
+    #[must_use]
+    pub fn factors(&self) -> &[V; 12] {
+        &self.factors
+    }
+
+    /// Returns the factor of the month of `date`.
+    #[must_use]
+    pub fn factor(&self, date: Date) -> V {
+        self.factors[date.month() as usize - 1]
+    }
+
+    /// Returns the factor of the month of `date` relative to that of the month of `base_date`.
+    #[must_use]
+    pub fn ratio(&self, base_date: Date, date: Date) -> V {
+        self.factor(date) / self.factor(base_date)
+    }
+}
+
+/// A commodity forward curve under the cost-of-carry model, `F(T) = S · exp(c(t)·t) · s(T) / s(T0)`, with `S` the spot
+/// price on the spot date `T0`, `t` the year fraction from the spot date to the delivery date `T`, `c` the net carry
+/// and `s` the seasonal factor of a month.
+///
+/// The spot price carries the seasonality of the spot month, so the forwards are shaped by the factor of the
+/// delivery month relative to it, and the forward for delivery on the spot date is the spot price.
+///
+/// The net carry is the financing rate plus the storage cost less the convenience yield, interpolated with `I`
+/// between the pillars and extrapolated flat beyond them. Curves are built either from those parameters with
+/// [`CommodityCurve::from_carry`] or from monthly futures with [`CommodityCurve::bootstrap`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::commodity_curve::{CommodityCurve, Seasonality};
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let spot_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let ymd = |year, month| Date::from_ymd(year, month, 2).unwrap();
+/// // gas for winter delivery trades at a premium
+/// let mut factors = [1.0_f64; 12];
+/// for month in [0, 1, 10, 11] {
+///     factors[month] = 1.2;
+/// }
+/// let seasonality = Seasonality::new(factors).unwrap();
+/// let futures = [(ymd(2024, 6), 2.4), (ymd(2024, 12), 3.1), (ymd(2025, 6), 2.5)];
+/// let curve: CommodityCurve<Act365, Linear<f64>> =
+///     CommodityCurve::bootstrap(spot_date, 2.5, &futures, seasonality).unwrap();
+/// for (delivery, price) in futures {
+///     assert!((curve.forward(delivery).unwrap() - price).abs() < 1e-12);
+/// }
+/// // a winter month between the pillars keeps its premium
+/// assert!(curve.forward(ymd(2025, 1)).unwrap() > curve.forward(ymd(2025, 3)).unwrap());
+/// ```
+pub struct CommodityCurve<D: DayCount, I: Interpolator> {
+    spot_date: Date,
+    spot: I::Value,
+    seasonality: Seasonality<I::Value>,
+    curve: PillarCurve<I>,
+    _day_count: PhantomData<D>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> CommodityCurve<D, I> {
+    /// Creates a curve from the net carry of each maturity, the financing rate plus the storage cost less
+    /// the convenience yield, all continuously compounded.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the spot price is not positive, if the lengths of the inputs do not match,
+    /// if no maturity is given or the maturities are not increasing after the spot date, or an error if the
+    /// interpolator fails to fit.
+    pub fn from_carry(
+        spot_date: Date,
+        spot: V,
+        maturities: &[Date],
+        rates: &[V],
+        storage_costs: &[V],
+        convenience_yields: &[V],
+        seasonality: Seasonality<V>,
+    ) -> QLabResult<Self> {
+        if rates.len() != maturities.len()
+            || storage_costs.len() != maturities.len()
+            || convenience_yields.len() != maturities.len()
+        {
+            return Err(InvalidInput(
+                "maturities, rates, storage costs and convenience yields are different lengths"
+                    .into(),
+            )
+            .into());
+        }
+        let carries: Vec<_> = rates
+            .iter()
+            .zip(storage_costs)
+            .zip(convenience_yields)
+            .map(|((&rate, &storage_cost), &convenience_yield)| {
+                rate + storage_cost - convenience_yield
+            })
+            .collect();
+        Self::with_carries(spot_date, spot, maturities, &carries, seasonality)
+    }
+
+    /// Bootstraps the curve from futures prices by delivery date, solving the net carry of each delivery date
+    /// so that the curve reprices its future after `seasonality` is applied.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the spot price or a futures price is not positive, if no future is given
+    /// or the delivery dates are not increasing after the spot date, or an error if the interpolator fails to fit.
+    pub fn bootstrap(
+        spot_date: Date,
+        spot: V,
+        futures: &[(Date, V)],
+        seasonality: Seasonality<V>,
+    ) -> QLabResult<Self> {
+        Self::bootstrap_with_diagnostics(spot_date, spot, futures, seasonality).0
+    }
+
+    /// Bootstraps the curve as [`CommodityCurve::bootstrap`] does and returns the diagnostics together with
+    /// the outcome. Each carry is solved in closed form, so every delivery date is recorded as a separate solve
+    /// with the repricing error of its future, or as a failure if its carry cannot be solved.
+    pub fn bootstrap_with_diagnostics(
+        spot_date: Date,
+        spot: V,
+        futures: &[(Date, V)],
+        seasonality: Seasonality<V>,
+    ) -> (QLabResult<Self>, BootstrapDiagnostics<V>) {
+        let mut diagnostics = BootstrapDiagnostics::default();
+        let mut carries = Vec::with_capacity(futures.len());
+        let mut failure = None;
+        for &(delivery, price) in futures {
+            let carry = if price <= V::zero() {
+                Err(InvalidInput(
+                    format!(
+                        "the future delivering on {delivery} has a non-positive price: {price:?}"
+                    )
+                    .into(),
+                )
+                .into())
+            } else {
+                D::calculate_day_count_fraction::<V>(spot_date, delivery)
+                    .map(|t| (price / (spot * seasonality.ratio(spot_date, delivery))).ln() / t)
+            };
+            match carry {
+                Ok(carry) => carries.push(carry),
+                Err(err) => {
+                    diagnostics.record_failed([delivery], &err.to_string());
+                    failure.get_or_insert(err);
+                }
+            }
+        }
+        if let Some(err) = failure {
+            return (Err(err), diagnostics);
+        }
+        let maturities: Vec<_> = futures.iter().map(|&(delivery, _)| delivery).collect();
+        let curve = Self::with_carries(spot_date, spot, &maturities, &carries, seasonality);
+        if let Ok(curve) = &curve {
+            for &(delivery, price) in futures {
+                match curve.forward(delivery) {
+                    Ok(forward) => diagnostics.record_exact(delivery, forward - price),
+                    Err(err) => diagnostics.record_failed([delivery], &err.to_string()),
+                }
+            }
+        }
+        (curve, diagnostics)
+    }
+
+    fn with_carries(
+        spot_date: Date,
+        spot: V,
+        maturities: &[Date],
+        carries: &[V],
+        seasonality: Seasonality<V>,
+    ) -> QLabResult<Self> {
+        if spot <= V::zero() {
+            return Err(InvalidInput(format!("spot: {spot:?} must be positive").into()).into());
+        }
+        if maturities.is_empty() {
+            return Err(InvalidInput("at least one maturity is required".into()).into());
+        }
+        if maturities[0] <= spot_date || maturities.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(InvalidInput(
+                format!("maturities must increase after the spot date: {spot_date}").into(),
+            )
+            .into());
+        }
+        let pillars = maturities
+            .iter()
+            .zip(carries)
+            .map(|(&maturity, &carry)| {
+                Ok((D::calculate_day_count_fraction(spot_date, maturity)?, carry))
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        Ok(Self {
+            spot_date,
+            spot,
+            seasonality,
+            curve: PillarCurve::new(pillars)?,
+            _day_count: PhantomData,
+        })
+    }
+
+    #[must_use]
+    pub fn spot_date(&self) -> Date {
+        self.spot_date
+    }
+
+    #[must_use]
+    pub fn spot(&self) -> V {
+        self.spot
+    }
+
+    #[must_use]
+    pub fn seasonality(&self) -> &Seasonality<V> {
+        &self.seasonality
+    }
+
+    /// Returns the pillars of the curve as pairs of year fraction from the spot date and net carry.
+    #[must_use]
+    pub fn pillars(&self) -> &[(V, V)] {
+        self.curve.pillars()
+    }
+
+    /// Returns the net carry at the year fraction `t` from the spot date.
+    ///
+    /// # Errors
+    /// Returns an error if the interpolation fails.
+    pub fn carry(&self, t: V) -> QLabResult<V> {
+        self.curve.value(t)
+    }
+
+    /// Calculates the forward price for delivery on `date`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `date` precedes the spot date, or an error if the interpolation fails.
+    pub fn forward(&self, date: Date) -> QLabResult<V> {
+        if date < self.spot_date {
+            return Err(InvalidInput(
+                format!("{date} precedes the spot date: {}", self.spot_date).into(),
+            )
+            .into());
+        }
+        let t = D::calculate_day_count_fraction(self.spot_date, date)?;
+        Ok(self.spot * (self.carry(t)? * t).exp() * self.seasonality.ratio(self.spot_date, date))
+    }
+
+    /// Returns the convenience yield implied at `date` by a financing rate and a storage cost,
+    /// i.e. the rate plus the storage cost less the net carry.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`CommodityCurve::forward`].
+    pub fn implied_convenience_yield(&self, date: Date, rate: V, storage_cost: V) -> QLabResult<V> {
+        if date < self.spot_date {
+            return Err(InvalidInput(
+                format!("{date} precedes the spot date: {}", self.spot_date).into(),
+            )
+            .into());
+        }
+        let t = D::calculate_day_count_fraction(self.spot_date, date)?;
+        Ok(rate + storage_cost - self.carry(t)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_commodity_curve() {
+        let spot_date = Date::from_ymd(2024, 1, 1).unwrap();
+        let maturities = [
+            Date::from_ymd(2025, 1, 1).unwrap(),
+            Date::from_ymd(2026, 1, 1).unwrap(),
+        ];
+        // a crude oil curve in backwardation: the convenience yield exceeds financing and storage
+        let curve: CommodityCurve<Act365, Linear<f64>> = CommodityCurve::from_carry(
+            spot_date,
+            80.0,
+            &maturities,
+            &[0.05, 0.045],
+            &[0.02, 0.02],
+            &[0.1, 0.1],
+            Seasonality::flat(),
+        )
+        .unwrap();
+        let t = 366.0 / 365.0;
+        let expected = 80.0 * (-0.03_f64 * t).exp();
+        assert!((curve.forward(maturities[0]).unwrap() - expected).abs() < 1e-12);
+        assert!(curve.forward(maturities[1]).unwrap() < curve.forward(maturities[0]).unwrap());
+        let implied = curve
+            .implied_convenience_yield(maturities[1], 0.045, 0.02)
+            .unwrap();
+        assert!((implied - 0.1).abs() < 1e-15);
+        assert!((curve.forward(spot_date).unwrap() - 80.0).abs() < 1e-12);
+        assert!(curve.forward(Date::from_ymd(2023, 12, 1).unwrap()).is_err());
+
+        let seasonality = Seasonality::new([2.0; 12]).unwrap();
+        assert!(seasonality
+            .factors()
+            .iter()
+            .all(|&factor| (factor - 1.0_f64).abs() < 1e-15));
+        assert!(Seasonality::new([0.0; 12]).is_err());
+        let futures = [(maturities[1], 80.0), (maturities[0], 82.0)];
+        assert!(CommodityCurve::<Act365, Linear<f64>>::bootstrap(
+            spot_date,
+            80.0,
+            &futures,
+            Seasonality::flat()
+        )
+        .is_err());
+        assert!(CommodityCurve::<Act365, Linear<f64>>::bootstrap(
+            spot_date,
+            80.0,
+            &[(maturities[0], 0.0)],
+            Seasonality::flat()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_seasonal_spot() {
+        // the spot is quoted in January, a winter month with a premium
+        let spot_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let mut factors = [1.0_f64; 12];
+        for month in [0, 1, 10, 11] {
+            factors[month] = 1.2;
+        }
+        let seasonality = Seasonality::new(factors).unwrap();
+        let july = Date::from_ymd(2024, 7, 2).unwrap();
+        let curve: CommodityCurve<Act365, Linear<f64>> = CommodityCurve::from_carry(
+            spot_date,
+            3.0,
+            &[july],
+            &[0.0],
+            &[0.0],
+            &[0.0],
+            seasonality,
+        )
+        .unwrap();
+        // without carry the forward is the spot price on the spot date, scaled by the seasonal ratio after it
+        assert!((curve.forward(spot_date).unwrap() - 3.0).abs() < 1e-12);
+        assert!((curve.forward(july).unwrap() - 3.0 / 1.2).abs() < 1e-12);
+        assert!((curve.forward(Date::from_ymd(2024, 12, 2).unwrap()).unwrap() - 3.0).abs() < 1e-12);
+        assert!((seasonality.ratio(july, spot_date) - 1.2).abs() < 1e-12);
+
+        // a bootstrap reprices its futures and recovers the carry apart from the seasonality
+        let futures = [
+            (july, 2.5 * (0.03_f64 * 182.0 / 365.0).exp()),
+            (Date::from_ymd(2025, 1, 2).unwrap(), 3.2),
+        ];
+        let curve: CommodityCurve<Act365, Linear<f64>> =
+            CommodityCurve::bootstrap(spot_date, 3.0, &futures, seasonality).unwrap();
+        for (delivery, price) in futures {
+            assert!((curve.forward(delivery).unwrap() - price).abs() < 1e-12);
+        }
+        assert!((curve.carry(curve.pillars()[0].0).unwrap() - 0.03).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bootstrap_diagnostics() {
+        let spot_date = Date::from_ymd(2024, 1, 1).unwrap();
+        let deliveries = [
+            Date::from_ymd(2024, 7, 1).unwrap(),
+            Date::from_ymd(2025, 1, 1).unwrap(),
+        ];
+        let (curve, diagnostics) =
+            CommodityCurve::<Act365, Linear<f64>>::bootstrap_with_diagnostics(
+                spot_date,
+                80.0,
+                &[(deliveries[0], 81.0), (deliveries[1], 79.5)],
+                Seasonality::flat(),
+            );
+        assert!(curve.is_ok());
+        assert_eq!(diagnostics.solves(), 2);
+        assert_eq!(diagnostics.events()[1].pillar(), deliveries[1]);
+        assert!(diagnostics.max_residual().unwrap() < 1e-12);
+        assert_eq!(diagnostics.total_evaluations(), 2);
+
+        // only the pillar with the bad price fails
+        let (curve, diagnostics) =
+            CommodityCurve::<Act365, Linear<f64>>::bootstrap_with_diagnostics(
+                spot_date,
+                80.0,
+                &[(deliveries[0], 81.0), (deliveries[1], -1.0)],
+                Seasonality::flat(),
+            );
+        assert!(curve.is_err());
+        let failures = diagnostics.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].pillar(), deliveries[1]);
+    }
+}
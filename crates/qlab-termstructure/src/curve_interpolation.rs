@@ -0,0 +1,315 @@
+use qlab_error::InterpolationError;
+use qlab_math::interpolation::linear::Linear;
+use qlab_math::interpolation::spline::monotone_cubic::MonotoneCubic;
+use qlab_math::interpolation::spline::natural_cubic::NaturalCubic;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+
+/// The market conventions for interpolating a yield curve between its pillars.
+///
+/// Each convention names the quantity that is interpolated and how, and is applied by [`CurveInterpolator`]
+/// to the pillars of a [`YieldCurve`](crate::yield_curve::YieldCurve) given as spot yields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CurveInterpolation {
+    /// Linear in the continuously compounded zero rate.
+    #[default]
+    LinearZero,
+    /// Linear in the logarithm of the discount factor, i.e. flat instantaneous forwards between pillars.
+    LogLinearDiscount,
+    /// A natural cubic spline of the zero rate, which needs at least three pillars.
+    NaturalCubicZero,
+    /// A monotone cubic spline of the zero rate by Fritsch and Carlson, which does not overshoot between pillars.
+    MonotoneCubicZero,
+    /// The monotone convex method of Hagan and West on the instantaneous forwards, which reprices the pillars,
+    /// keeps the forwards continuous and avoids the oscillations of splines.
+    MonotoneConvexForward,
+}
+
+/// Interpolates spot yields by a [`CurveInterpolation`] chosen at runtime.
+///
+/// The log-linear and monotone convex conventions work on `t * y(t)`, are anchored at zero time
+/// and return the limit of the zero rate, the instantaneous forward, at `t = 0`.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_termstructure::curve_interpolation::{CurveInterpolation, CurveInterpolator};
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let settlement_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let maturities = [
+///     Date::from_ymd(2025, 1, 1).unwrap(),
+///     Date::from_ymd(2026, 1, 1).unwrap(),
+/// ];
+/// let curve: YieldCurve<Act365, CurveInterpolator<f64>> = YieldCurve::with_interpolation(
+///     settlement_date,
+///     &maturities,
+///     &[0.02, 0.03],
+///     CurveInterpolation::LogLinearDiscount,
+/// )
+/// .unwrap();
+/// // the forward between the pillars is flat, so half way the discount factor is the geometric mean
+/// let halfway = Date::from_ymd(2025, 7, 2).unwrap();
+/// let df = curve.discount_factor(settlement_date, halfway).unwrap();
+/// let expected = ((-0.02_f64).exp() * (-0.06_f64).exp()).sqrt();
+/// assert!((df - expected).abs() < 1e-3);
+/// ```
+#[derive(Default, Clone)]
+pub struct CurveInterpolator<V: Value> {
+    interpolation: CurveInterpolation,
+    fitted: Fitted<V>,
+}
+
+#[derive(Default, Clone)]
+enum Fitted<V: Value> {
+    #[default]
+    Unfitted,
+    LinearZero(Linear<V>),
+    NaturalCubicZero(NaturalCubic<V>),
+    MonotoneCubicZero(MonotoneCubic<V>),
+    /// The nodes `(t, t * y(t))` starting at zero time, and the fitted forwards for the monotone convex method.
+    Integrated {
+        nodes: Vec<(V, V)>,
+        forwards: Option<Vec<V>>,
+    },
+}
+
+impl<V: Value> CurveInterpolator<V> {
+    #[must_use]
+    pub fn new(interpolation: CurveInterpolation) -> Self {
+        Self {
+            interpolation,
+            fitted: Fitted::Unfitted,
+        }
+    }
+
+    #[must_use]
+    pub fn interpolation(&self) -> CurveInterpolation {
+        self.interpolation
+    }
+}
+
+/// Returns the discrete forwards between consecutive nodes of `t * y(t)`.
+fn discrete_forwards<V: Value>(nodes: &[(V, V)]) -> Vec<V> {
+    nodes
+        .windows(2)
+        .map(|pair| (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+        .collect()
+}
+
+/// Returns the instantaneous forwards at the nodes, averaging the neighbouring discrete forwards
+/// by the lengths of the periods and extrapolating at both ends as in Hagan and West.
+fn node_forwards<V: Value>(nodes: &[(V, V)], discrete: &[V]) -> Vec<V> {
+    let n = discrete.len();
+    if n == 1 {
+        return vec![discrete[0]; 2];
+    }
+    let half = V::one() / (V::one() + V::one());
+    let mut forwards = vec![V::zero(); n + 1];
+    for i in 1..n {
+        let (t_prev, t, t_next) = (nodes[i - 1].0, nodes[i].0, nodes[i + 1].0);
+        forwards[i] = (t - t_prev) / (t_next - t_prev) * discrete[i]
+            + (t_next - t) / (t_next - t_prev) * discrete[i - 1];
+    }
+    forwards[0] = discrete[0] - half * (forwards[1] - discrete[0]);
+    forwards[n] = discrete[n - 1] - half * (forwards[n - 1] - discrete[n - 1]);
+    forwards
+}
+
+/// Integrates from zero to `x` the deviation `g` of the instantaneous forward from the discrete forward
+/// over a period scaled to `[0, 1]`, where `g0` and `g1` are the deviations at its ends.
+fn monotone_convex_integral<V: Value>(g0: V, g1: V, x: V) -> V {
+    let zero = V::zero();
+    let one = V::one();
+    let two = one + one;
+    let three = two + one;
+    if g0 == zero && g1 == zero {
+        return zero;
+    }
+    let cube = |u: V| u * u * u;
+    if (g0 < zero && -g0 / two <= g1 && g1 <= -two * g0)
+        || (g0 > zero && -g0 / two >= g1 && g1 >= -two * g0)
+    {
+        // the quadratic through the deviations, which integrates to zero over the period
+        g0 * (x - two * x * x + cube(x)) + g1 * (cube(x) - x * x)
+    } else if (g0 < zero && g1 > -two * g0) || (g0 > zero && g1 < -two * g0) {
+        let eta = (g1 + two * g0) / (g1 - g0);
+        if x <= eta {
+            g0 * x
+        } else {
+            g0 * x + (g1 - g0) * cube(x - eta) / (three * (one - eta) * (one - eta))
+        }
+    } else if (g0 > zero && zero > g1 && g1 > -g0 / two)
+        || (g0 < zero && zero < g1 && g1 < -g0 / two)
+    {
+        let eta = three * g1 / (g1 - g0);
+        if x < eta {
+            g1 * x + (g0 - g1) * (eta - cube(eta - x) / (eta * eta)) / three
+        } else {
+            g1 * x + (g0 - g1) * eta / three
+        }
+    } else {
+        let eta = g1 / (g1 + g0);
+        let a = -g0 * g1 / (g0 + g1);
+        if x <= eta {
+            a * x + (g0 - a) * (eta - cube(eta - x) / (eta * eta)) / three
+        } else {
+            a * x
+                + (g0 - a) * eta / three
+                + (g1 - a) * cube(x - eta) / (three * (one - eta) * (one - eta))
+        }
+    }
+}
+
+impl<V: Value> Interpolator for CurveInterpolator<V> {
+    type Value = V;
+
+    fn try_fit(mut self, raw_points: &[(V, V)]) -> Result<Self, InterpolationError<V>> {
+        if raw_points.windows(2).any(|pair| pair[1].0 <= pair[0].0) {
+            return Err(InterpolationError::PointOrderError);
+        }
+        self.fitted = match self.interpolation {
+            CurveInterpolation::LinearZero => {
+                Fitted::LinearZero(Linear::default().try_fit(raw_points)?)
+            }
+            CurveInterpolation::NaturalCubicZero => {
+                Fitted::NaturalCubicZero(NaturalCubic::default().try_fit(raw_points)?)
+            }
+            CurveInterpolation::MonotoneCubicZero => {
+                Fitted::MonotoneCubicZero(MonotoneCubic::default().try_fit(raw_points)?)
+            }
+            CurveInterpolation::LogLinearDiscount | CurveInterpolation::MonotoneConvexForward => {
+                let Some(&(first, _)) = raw_points.first() else {
+                    return Err(InterpolationError::InsufficientPointsError(0));
+                };
+                if first < V::zero() {
+                    return Err(InterpolationError::OutOfLowerBound(first));
+                }
+                let mut nodes = Vec::with_capacity(raw_points.len() + 1);
+                if first > V::zero() {
+                    nodes.push((V::zero(), V::zero()));
+                }
+                nodes.extend(raw_points.iter().map(|&(t, y)| (t, t * y)));
+                if nodes.len() < 2 {
+                    return Err(InterpolationError::InsufficientPointsError(
+                        raw_points.len(),
+                    ));
+                }
+                let forwards = (self.interpolation == CurveInterpolation::MonotoneConvexForward)
+                    .then(|| node_forwards(&nodes, &discrete_forwards(&nodes)));
+                Fitted::Integrated { nodes, forwards }
+            }
+        };
+        Ok(self)
+    }
+
+    fn try_value(&self, t: V) -> Result<V, InterpolationError<V>> {
+        match &self.fitted {
+            Fitted::Unfitted => Err(InterpolationError::InsufficientPointsError(0)),
+            Fitted::LinearZero(linear) => linear.try_value(t),
+            Fitted::NaturalCubicZero(spline) => spline.try_value(t),
+            Fitted::MonotoneCubicZero(spline) => spline.try_value(t),
+            Fitted::Integrated { nodes, forwards } => {
+                if t < V::zero() {
+                    return Err(InterpolationError::OutOfLowerBound(t));
+                }
+                let last = nodes[nodes.len() - 1].0;
+                if t > last {
+                    return Err(InterpolationError::OutOfUpperBound(t));
+                }
+                let index = nodes
+                    .partition_point(|node| node.0 < t)
+                    .clamp(1, nodes.len() - 1);
+                let ((t0, r0), (t1, r1)) = (nodes[index - 1], nodes[index]);
+                let length = t1 - t0;
+                let discrete = (r1 - r0) / length;
+                if t == V::zero() {
+                    // the zero rate tends to the instantaneous forward at zero time
+                    return Ok(forwards.as_ref().map_or(discrete, |forwards| forwards[0]));
+                }
+                let x = (t - t0) / length;
+                let mut integral = r0 + length * discrete * x;
+                if let Some(forwards) = forwards {
+                    let g0 = forwards[index - 1] - discrete;
+                    let g1 = forwards[index] - discrete;
+                    integral += length * monotone_convex_integral(g0, g1, x);
+                }
+                Ok(integral / t)
+            }
+        }
+    }
+
+    fn unfitted(&self) -> Self {
+        Self::new(self.interpolation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PILLARS: [(f64, f64); 5] = [
+        (0.5, 0.03),
+        (1.0, 0.032),
+        (2.0, 0.035),
+        (5.0, 0.034),
+        (10.0, 0.038),
+    ];
+
+    #[test]
+    fn test_reprices_pillars() {
+        for interpolation in [
+            CurveInterpolation::LinearZero,
+            CurveInterpolation::LogLinearDiscount,
+            CurveInterpolation::NaturalCubicZero,
+            CurveInterpolation::MonotoneCubicZero,
+            CurveInterpolation::MonotoneConvexForward,
+        ] {
+            let interpolator = CurveInterpolator::new(interpolation)
+                .try_fit(&PILLARS)
+                .unwrap();
+            for &(t, y) in &PILLARS {
+                assert!((interpolator.try_value(t).unwrap() - y).abs() < 1e-14);
+            }
+            assert!(interpolator.try_value(10.5).is_err());
+            assert_eq!(interpolator.unfitted().interpolation(), interpolation);
+        }
+    }
+
+    #[test]
+    fn test_log_linear_discount() {
+        let interpolator = CurveInterpolator::new(CurveInterpolation::LogLinearDiscount)
+            .try_fit(&PILLARS)
+            .unwrap();
+        // flat forward between 2 and 5 years
+        let forward = (5.0 * 0.034 - 2.0 * 0.035) / 3.0;
+        let y = interpolator.try_value(3.0).unwrap();
+        assert!((3.0 * y - (2.0 * 0.035 + forward)).abs() < 1e-15);
+        assert!((interpolator.try_value(0.0).unwrap() - 0.03).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_monotone_convex_forward() {
+        let interpolator = CurveInterpolator::new(CurveInterpolation::MonotoneConvexForward)
+            .try_fit(&PILLARS)
+            .unwrap();
+        let integral = |t: f64| t * interpolator.try_value(t).unwrap();
+        // the instantaneous forwards are continuous across pillars
+        let h = 1e-6;
+        for &(t, _) in &PILLARS[..4] {
+            let left = (integral(t) - integral(t - h)) / h;
+            let right = (integral(t + h) - integral(t)) / h;
+            assert!((left - right).abs() < 1e-5, "{t}: {left} {right}");
+        }
+        // a flat curve stays flat
+        let flat = CurveInterpolator::new(CurveInterpolation::MonotoneConvexForward)
+            .try_fit(&[(1.0, 0.02), (3.0, 0.02)])
+            .unwrap();
+        for t in [0.0, 0.3, 2.2] {
+            assert!((flat.try_value(t).unwrap() - 0.02_f64).abs() < 1e-15);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::report::Table;
+use qlab_error::QLabResult;
+use qlab_math::optimization::{LeastSquaresReport, StopReason};
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+
+/// The outcome of a curve bootstrap at a single pillar.
+///
+/// Pillars solved jointly share the index of their `solve` together with its iteration counts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BootstrapEvent<V> {
+    Solved {
+        pillar: Date,
+        solve: usize,
+        iterations: usize,
+        evaluations: usize,
+        /// The residual of the instrument of the pillar at the solution.
+        residual: V,
+        stop_reason: StopReason,
+    },
+    Failed {
+        pillar: Date,
+        solve: usize,
+        message: String,
+    },
+}
+
+impl<V> BootstrapEvent<V> {
+    #[must_use]
+    pub fn pillar(&self) -> Date {
+        match self {
+            BootstrapEvent::Solved { pillar, .. } | BootstrapEvent::Failed { pillar, .. } => {
+                *pillar
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn solve(&self) -> usize {
+        match self {
+            BootstrapEvent::Solved { solve, .. } | BootstrapEvent::Failed { solve, .. } => *solve,
+        }
+    }
+}
+
+/// The events recorded while a curve was bootstrapped and rebuilt, in the order they occurred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootstrapDiagnostics<V> {
+    events: Vec<BootstrapEvent<V>>,
+    solves: usize,
+}
+
+impl<V> Default for BootstrapDiagnostics<V> {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            solves: 0,
+        }
+    }
+}
+
+impl<V: Value> BootstrapDiagnostics<V> {
+    /// Records a successful solve with the residual of the instrument of each of its pillars.
+    pub fn record_solved(
+        &mut self,
+        pillars: impl IntoIterator<Item = (Date, V)>,
+        report: &LeastSquaresReport<V>,
+    ) {
+        let solve = self.next_solve();
+        self.events.extend(
+            pillars
+                .into_iter()
+                .map(|(pillar, residual)| BootstrapEvent::Solved {
+                    pillar,
+                    solve,
+                    iterations: report.iterations,
+                    evaluations: report.evaluations,
+                    residual,
+                    stop_reason: report.stop_reason,
+                }),
+        );
+    }
+
+    /// Records a pillar solved in closed form, which takes a single evaluation and no iteration.
+    pub fn record_exact(&mut self, pillar: Date, residual: V) {
+        let solve = self.next_solve();
+        self.events.push(BootstrapEvent::Solved {
+            pillar,
+            solve,
+            iterations: 0,
+            evaluations: 1,
+            residual,
+            stop_reason: StopReason::Converged,
+        });
+    }
+
+    /// Records a failed solve of `pillars`.
+    pub fn record_failed(&mut self, pillars: impl IntoIterator<Item = Date>, message: &str) {
+        let solve = self.next_solve();
+        self.events
+            .extend(pillars.into_iter().map(|pillar| BootstrapEvent::Failed {
+                pillar,
+                solve,
+                message: message.to_string(),
+            }));
+    }
+
+    fn next_solve(&mut self) -> usize {
+        self.solves += 1;
+        self.solves - 1
+    }
+
+    #[must_use]
+    pub fn events(&self) -> &[BootstrapEvent<V>] {
+        &self.events
+    }
+
+    /// Returns the number of solves, each of which records an event per pillar.
+    #[must_use]
+    pub fn solves(&self) -> usize {
+        self.solves
+    }
+
+    /// Returns the pillars whose solve failed.
+    #[must_use]
+    pub fn failures(&self) -> Vec<&BootstrapEvent<V>> {
+        self.events
+            .iter()
+            .filter(|event| matches!(event, BootstrapEvent::Failed { .. }))
+            .collect()
+    }
+
+    /// Returns the pillars whose solve stalled rather than converged, see [`StopReason::Stalled`].
+    #[must_use]
+    pub fn stalled(&self) -> Vec<&BootstrapEvent<V>> {
+        self.events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    BootstrapEvent::Solved {
+                        stop_reason: StopReason::Stalled,
+                        ..
+                    }
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the largest absolute residual over the solved pillars, or `None` if none was solved.
+    #[must_use]
+    pub fn max_residual(&self) -> Option<V> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                BootstrapEvent::Solved { residual, .. } => Some(residual.abs()),
+                BootstrapEvent::Failed { .. } => None,
+            })
+            .reduce(|max, residual| if residual > max { residual } else { max })
+    }
+
+    /// Returns the number of residual evaluations over all solves, counting each solve once.
+    #[must_use]
+    pub fn total_evaluations(&self) -> usize {
+        let mut last_solve = None;
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                BootstrapEvent::Solved {
+                    solve, evaluations, ..
+                } if last_solve != Some(*solve) => {
+                    last_solve = Some(*solve);
+                    Some(*evaluations)
+                }
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Builds a table with a row per pillar of every solve.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn report(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&[
+            "solve",
+            "pillar",
+            "outcome",
+            "iterations",
+            "evaluations",
+            "residual",
+        ]);
+        for event in &self.events {
+            let row = match event {
+                BootstrapEvent::Solved {
+                    pillar,
+                    solve,
+                    iterations,
+                    evaluations,
+                    residual,
+                    stop_reason,
+                } => vec![
+                    solve.to_string(),
+                    pillar.to_string(),
+                    format!("{stop_reason:?}"),
+                    iterations.to_string(),
+                    evaluations.to_string(),
+                    format!("{residual:?}"),
+                ],
+                BootstrapEvent::Failed {
+                    pillar,
+                    solve,
+                    message,
+                } => vec![
+                    solve.to_string(),
+                    pillar.to_string(),
+                    format!("Failed: {message}"),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ],
+            };
+            table.push_row(row)?;
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(evaluations: usize, stop_reason: StopReason) -> LeastSquaresReport<f64> {
+        LeastSquaresReport {
+            parameters: vec![0.0; 2],
+            iterations: 3,
+            evaluations,
+            cost: 0.0,
+            stop_reason,
+        }
+    }
+
+    #[test]
+    fn test_per_pillar_events() {
+        let pillars = [
+            Date::from_ymd(2024, 6, 19).unwrap(),
+            Date::from_ymd(2024, 9, 18).unwrap(),
+            Date::from_ymd(2024, 12, 18).unwrap(),
+        ];
+        let mut diagnostics = BootstrapDiagnostics::default();
+        assert!(diagnostics.max_residual().is_none());
+        diagnostics.record_solved(
+            [(pillars[0], 1e-12), (pillars[1], -3e-12)],
+            &report(7, StopReason::Converged),
+        );
+        diagnostics.record_solved([(pillars[1], 2e-12)], &report(5, StopReason::Stalled));
+        diagnostics.record_exact(pillars[2], 0.0);
+        diagnostics.record_failed(pillars[1..].iter().copied(), "no fixing");
+
+        assert_eq!(diagnostics.solves(), 4);
+        assert_eq!(diagnostics.events().len(), 6);
+        assert_eq!(diagnostics.events()[1].solve(), 0);
+        assert_eq!(diagnostics.events()[1].pillar(), pillars[1]);
+        // the joint solve is counted once
+        assert_eq!(diagnostics.total_evaluations(), 7 + 5 + 1);
+        assert!((diagnostics.max_residual().unwrap() - 3e-12).abs() < f64::EPSILON);
+
+        let stalled = diagnostics.stalled();
+        assert_eq!(stalled.len(), 1);
+        assert_eq!(stalled[0].pillar(), pillars[1]);
+        let failures = diagnostics.failures();
+        assert_eq!(failures.len(), 2);
+        assert!(failures.iter().all(|failure| failure.solve() == 3));
+        assert_eq!(failures[1].pillar(), pillars[2]);
+
+        let table = diagnostics.report().unwrap();
+        assert_eq!(table.rows().len(), 6);
+        assert_eq!(table.rows()[5][2], "Failed: no fixing");
+        assert_eq!(table.rows()[3][3], "0");
+    }
+
+    #[test]
+    fn test_empty() {
+        let diagnostics = BootstrapDiagnostics::<f64>::default();
+        assert_eq!(diagnostics.solves(), 0);
+        assert_eq!(diagnostics.total_evaluations(), 0);
+        assert!(diagnostics.failures().is_empty());
+        assert!(diagnostics.report().unwrap().rows().is_empty());
+    }
+}
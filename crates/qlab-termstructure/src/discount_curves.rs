@@ -0,0 +1,55 @@
+use crate::yield_curve::YieldCurve;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_time::day_count::DayCount;
+use std::collections::HashMap;
+
+/// A set of discount curves: one per collateral currency, plus a funding curve for uncollateralized cash flows.
+///
+/// Currency codes are matched case-insensitively.
+pub struct DiscountCurves<D: DayCount, I: Interpolator> {
+    funding: YieldCurve<D, I>,
+    collateral: HashMap<String, YieldCurve<D, I>>,
+}
+
+impl<D: DayCount, I: Interpolator> DiscountCurves<D, I> {
+    /// Creates a curve set discounting every cash flow on `funding` until collateral curves are added.
+    #[must_use]
+    pub fn new(funding: YieldCurve<D, I>) -> Self {
+        Self {
+            funding,
+            collateral: HashMap::new(),
+        }
+    }
+
+    /// Adds the curve used to discount cash flows collateralized in `currency`, replacing any previous one.
+    #[must_use]
+    pub fn with_collateral_curve(mut self, currency: &str, curve: YieldCurve<D, I>) -> Self {
+        self.collateral.insert(currency.to_uppercase(), curve);
+        self
+    }
+
+    /// Returns the curve for uncollateralized cash flows.
+    #[must_use]
+    pub fn funding(&self) -> &YieldCurve<D, I> {
+        &self.funding
+    }
+
+    /// Returns the curve for cash flows collateralized in `currency`, if any.
+    #[must_use]
+    pub fn collateral(&self, currency: &str) -> Option<&YieldCurve<D, I>> {
+        self.collateral.get(&currency.to_uppercase())
+    }
+
+    /// Returns the curve for cash flows collateralized in `currency`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the set holds no curve for `currency`.
+    pub fn try_collateral(&self, currency: &str) -> QLabResult<&YieldCurve<D, I>> {
+        self.collateral(currency).ok_or_else(|| {
+            InvalidInput(format!("no discount curve for collateral currency: {currency}").into())
+                .into()
+        })
+    }
+}
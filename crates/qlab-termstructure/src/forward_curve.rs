@@ -0,0 +1,222 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::marker::PhantomData;
+
+/// A discount curve interpolating instantaneous forward rates rather than spot yields.
+///
+/// Interpolating in yield space makes the forwards depend on the slope of the yields and hence noisy;
+/// here the forwards themselves are interpolated by `I`, e.g. piecewise constant with
+/// [`PiecewiseConstant`](qlab_math::interpolation::piecewise_constant::PiecewiseConstant)
+/// or smooth with a spline, and integrated into discount factors.
+/// The forward is extrapolated flat before the first and after the last pillar.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::piecewise_constant::PiecewiseConstant;
+/// use qlab_termstructure::forward_curve::ForwardCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let settlement_date = Date::from_ymd(2023, 1, 1).unwrap();
+/// let maturities = [Date::from_ymd(2024, 1, 1).unwrap(), Date::from_ymd(2025, 1, 1).unwrap()];
+/// let curve: ForwardCurve<Act365, PiecewiseConstant<f64>> =
+///     ForwardCurve::new(settlement_date, &maturities, &[0.02, 0.04]).unwrap();
+/// let discount_factor = curve.discount_factor(settlement_date, maturities[1]).unwrap();
+/// assert!((discount_factor - (-0.02 - 0.04 * 366.0 / 365.0_f64).exp()).abs() < 1e-15);
+/// ```
+pub struct ForwardCurve<D: DayCount, I: Interpolator> {
+    settlement_date: Date,
+    pillars: Vec<(I::Value, I::Value)>,
+    /// The integral of the forward from the settlement date to each pillar.
+    integrals: Vec<I::Value>,
+    interpolator: I,
+    _day_count: PhantomData<D>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> ForwardCurve<D, I> {
+    /// Creates a curve from the instantaneous forward rates at the maturities.
+    ///
+    /// # Errors
+    /// Returns an `Err` variant if the lengths of `maturities` and `forwards` do not match, if no maturity is given,
+    /// if the maturities are not increasing or precede the settlement date, or if the interpolator fails to fit.
+    pub fn new(settlement_date: Date, maturities: &[Date], forwards: &[V]) -> QLabResult<Self> {
+        if maturities.len() != forwards.len() {
+            return Err(
+                InvalidInput("maturities and forwards are different lengths".into()).into(),
+            );
+        }
+        if maturities.is_empty() {
+            return Err(InvalidInput("at least one maturity is required".into()).into());
+        }
+        if maturities[0] < settlement_date || maturities.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(InvalidInput(
+                format!("maturities must increase from the settlement date: {settlement_date}")
+                    .into(),
+            )
+            .into());
+        }
+        let pillars = maturities
+            .iter()
+            .zip(forwards)
+            .map(|(&maturity, &forward)| {
+                Ok((
+                    D::calculate_day_count_fraction(settlement_date, maturity)?,
+                    forward,
+                ))
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        let interpolator = I::default().try_fit(&pillars)?;
+        let mut curve = Self {
+            settlement_date,
+            pillars,
+            integrals: Vec::new(),
+            interpolator,
+            _day_count: PhantomData,
+        };
+        let mut integral = curve.pillars[0].0 * curve.pillars[0].1;
+        curve.integrals.push(integral);
+        for i in 1..curve.pillars.len() {
+            integral += curve.segment_integral(curve.pillars[i - 1].0, curve.pillars[i].0)?;
+            curve.integrals.push(integral);
+        }
+        Ok(curve)
+    }
+
+    /// Returns the settlement date of the curve.
+    #[must_use]
+    pub fn settlement_date(&self) -> Date {
+        self.settlement_date
+    }
+
+    /// Returns the pillars of the curve as pairs of year fraction from the settlement date and instantaneous forward.
+    #[must_use]
+    pub fn pillars(&self) -> &[(V, V)] {
+        &self.pillars
+    }
+
+    /// Returns the instantaneous forward rate at the year fraction `t` from the settlement date.
+    ///
+    /// # Errors
+    /// Returns an error if the interpolation fails.
+    pub fn instantaneous_forward(&self, t: V) -> QLabResult<V> {
+        let (first, last) = (self.pillars[0], self.pillars[self.pillars.len() - 1]);
+        if t <= first.0 {
+            return Ok(first.1);
+        }
+        if t >= last.0 {
+            return Ok(last.1);
+        }
+        Ok(self.interpolator.try_value(t)?)
+    }
+
+    /// Integrates the interpolated forward over `[t1, t2]` within a single segment between pillars
+    /// with the 3 point Gauss-Legendre rule, which is exact for polynomials up to degree 5.
+    fn segment_integral(&self, t1: V, t2: V) -> QLabResult<V> {
+        let half_width = (t2 - t1) / (V::one() + V::one());
+        if half_width.is_zero() {
+            return Ok(V::zero());
+        }
+        let midpoint = t1 + half_width;
+        let node = V::from_f64(0.6).unwrap().sqrt() * half_width;
+        let (outer, inner) = (V::from_u8(5).unwrap(), V::from_u8(8).unwrap());
+        let sum = outer * self.interpolator.try_value(midpoint - node)?
+            + inner * self.interpolator.try_value(midpoint)?
+            + outer * self.interpolator.try_value(midpoint + node)?;
+        Ok(sum * half_width / V::from_u8(9).unwrap())
+    }
+
+    /// Returns the integral of the forward from the settlement date to `t`.
+    fn integral(&self, t: V) -> QLabResult<V> {
+        let (first, last) = (self.pillars[0], self.pillars[self.pillars.len() - 1]);
+        if t <= first.0 {
+            return Ok(t * first.1);
+        }
+        if t >= last.0 {
+            return Ok(self.integrals[self.integrals.len() - 1] + (t - last.0) * last.1);
+        }
+        let pos = self.pillars.partition_point(|pillar| pillar.0 < t);
+        Ok(self.integrals[pos - 1] + self.segment_integral(self.pillars[pos - 1].0, t)?)
+    }
+
+    /// Calculates the discount factor between two dates.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `d2` precedes `d1` or either date precedes the settlement date.
+    pub fn discount_factor(&self, d1: Date, d2: Date) -> QLabResult<V> {
+        if d2 < d1 {
+            return Err(
+                InvalidInput(format!("d1: {d1} must be smaller than d2: {d2}").into()).into(),
+            );
+        }
+        if d1 < self.settlement_date {
+            return Err(InvalidInput(
+                format!(
+                    "{d1} precedes the settlement date: {}",
+                    self.settlement_date
+                )
+                .into(),
+            )
+            .into());
+        }
+        let t1 = D::calculate_day_count_fraction(self.settlement_date, d1)?;
+        let t2 = D::calculate_day_count_fraction(self.settlement_date, d2)?;
+        Ok((self.integral(t1)? - self.integral(t2)?).exp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_math::interpolation::piecewise_constant::PiecewiseConstant;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_piecewise_constant() {
+        let settlement_date = Date::from_ymd(2023, 1, 1).unwrap();
+        let maturities = [
+            Date::from_ymd(2023, 7, 2).unwrap(),
+            Date::from_ymd(2024, 1, 1).unwrap(),
+        ];
+        let curve: ForwardCurve<Act365, PiecewiseConstant<f64>> =
+            ForwardCurve::new(settlement_date, &maturities, &[0.01, 0.03]).unwrap();
+        // the forward over a period inside a step is the step value
+        let d1 = Date::from_ymd(2023, 8, 1).unwrap();
+        let d2 = Date::from_ymd(2023, 9, 1).unwrap();
+        let discount_factor = curve.discount_factor(d1, d2).unwrap();
+        assert!((discount_factor - (-0.03 * 31.0 / 365.0_f64).exp()).abs() < 1e-15);
+        // and flat beyond the last pillar
+        let d3 = Date::from_ymd(2025, 1, 1).unwrap();
+        let discount_factor = curve.discount_factor(maturities[1], d3).unwrap();
+        assert!((discount_factor - (-0.03 * 366.0 / 365.0_f64).exp()).abs() < 1e-15);
+        assert!(curve.discount_factor(d2, d1).is_err());
+    }
+
+    #[test]
+    fn test_linear_forwards() {
+        let settlement_date = Date::from_ymd(2023, 1, 1).unwrap();
+        let maturities = [
+            Date::from_ymd(2023, 1, 1).unwrap(),
+            Date::from_ymd(2024, 1, 1).unwrap(),
+        ];
+        let curve: ForwardCurve<Act365, Linear<f64>> =
+            ForwardCurve::new(settlement_date, &maturities, &[0.02, 0.04]).unwrap();
+        assert!((curve.instantaneous_forward(0.5).unwrap() - 0.03).abs() < 1e-15);
+        // the trapezoid under a linear forward
+        let discount_factor = curve
+            .discount_factor(settlement_date, maturities[1])
+            .unwrap();
+        assert!((discount_factor - (-0.03_f64).exp()).abs() < 1e-15);
+        assert!(ForwardCurve::<Act365, Linear<f64>>::new(
+            settlement_date,
+            &[maturities[1], maturities[0]],
+            &[0.02, 0.04]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::marker::PhantomData;
+
+/// A zero-coupon inflation curve projecting a price index from its value on a base date.
+///
+/// The curve interpolates annually compounded zero-coupon inflation rates with `I`, so that the index
+/// projected at a year fraction `t` from the base date is `base_index * (1 + z(t))^t`.
+/// The rate is extrapolated flat before the first and after the last pillar.
+/// Dates are those on which the index is observed, so any observation lag is applied by the caller.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::inflation_curve::InflationCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let base_date = Date::from_ymd(2023, 1, 1).unwrap();
+/// let maturities = [Date::from_ymd(2024, 1, 1).unwrap(), Date::from_ymd(2028, 1, 1).unwrap()];
+/// let curve: InflationCurve<Act365, Linear<f64>> =
+///     InflationCurve::new(base_date, 300.0, &maturities, &[0.03, 0.025]).unwrap();
+/// let index = curve.projected_index(maturities[0]).unwrap();
+/// assert!((index - 300.0 * 1.03_f64).abs() < 1e-12);
+/// ```
+pub struct InflationCurve<D: DayCount, I: Interpolator> {
+    base_date: Date,
+    base_index: I::Value,
+    pillars: Vec<(I::Value, I::Value)>,
+    interpolator: I,
+    _day_count: PhantomData<D>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> InflationCurve<D, I> {
+    /// Creates a curve from the zero-coupon inflation rates from `base_date` to the maturities.
+    ///
+    /// # Errors
+    /// Returns an `Err` variant if the lengths of `maturities` and `rates` do not match, if no maturity is given,
+    /// if the maturities are not increasing after the base date, or if the interpolator fails to fit.
+    pub fn new(
+        base_date: Date,
+        base_index: V,
+        maturities: &[Date],
+        rates: &[V],
+    ) -> QLabResult<Self> {
+        if maturities.len() != rates.len() {
+            return Err(InvalidInput("maturities and rates are different lengths".into()).into());
+        }
+        if maturities.is_empty() {
+            return Err(InvalidInput("at least one maturity is required".into()).into());
+        }
+        if maturities[0] <= base_date || maturities.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(InvalidInput(
+                format!("maturities must increase after the base date: {base_date}").into(),
+            )
+            .into());
+        }
+        let pillars = maturities
+            .iter()
+            .zip(rates)
+            .map(|(&maturity, &rate)| {
+                Ok((D::calculate_day_count_fraction(base_date, maturity)?, rate))
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        let interpolator = I::default().try_fit(&pillars)?;
+        Ok(Self {
+            base_date,
+            base_index,
+            pillars,
+            interpolator,
+            _day_count: PhantomData,
+        })
+    }
+
+    #[must_use]
+    pub fn base_date(&self) -> Date {
+        self.base_date
+    }
+
+    #[must_use]
+    pub fn base_index(&self) -> V {
+        self.base_index
+    }
+
+    /// Returns the pillars of the curve as pairs of year fraction from the base date and zero-coupon inflation rate.
+    #[must_use]
+    pub fn pillars(&self) -> &[(V, V)] {
+        &self.pillars
+    }
+
+    /// Returns the zero-coupon inflation rate at the year fraction `t` from the base date.
+    ///
+    /// # Errors
+    /// Returns an error if the interpolation fails.
+    pub fn zero_rate(&self, t: V) -> QLabResult<V> {
+        let (first, last) = (self.pillars[0], self.pillars[self.pillars.len() - 1]);
+        if t <= first.0 {
+            return Ok(first.1);
+        }
+        if t >= last.0 {
+            return Ok(last.1);
+        }
+        Ok(self.interpolator.try_value(t)?)
+    }
+
+    /// Calculates the ratio of the index projected at `date` to the base index.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `date` precedes the base date, or an error if the interpolation fails.
+    pub fn index_ratio(&self, date: Date) -> QLabResult<V> {
+        if date < self.base_date {
+            return Err(InvalidInput(
+                format!("{date} precedes the base date: {}", self.base_date).into(),
+            )
+            .into());
+        }
+        let t = D::calculate_day_count_fraction(self.base_date, date)?;
+        Ok((V::one() + self.zero_rate(t)?).powf(t))
+    }
+
+    /// Projects the index at `date`.
+    ///
+    /// # Errors
+    /// Returns an error under the same conditions as [`InflationCurve::index_ratio`].
+    pub fn projected_index(&self, date: Date) -> QLabResult<V> {
+        Ok(self.base_index * self.index_ratio(date)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_projected_index() {
+        let base_date = Date::from_ymd(2023, 1, 1).unwrap();
+        let maturities = [
+            Date::from_ymd(2024, 1, 1).unwrap(),
+            Date::from_ymd(2026, 1, 1).unwrap(),
+        ];
+        let curve: InflationCurve<Act365, Linear<f64>> =
+            InflationCurve::new(base_date, 100.0, &maturities, &[0.04, 0.02]).unwrap();
+        assert!((curve.index_ratio(base_date).unwrap() - 1.0).abs() < 1e-15);
+        let t = 1096.0 / 365.0;
+        let expected = 100.0 * 1.02_f64.powf(t);
+        assert!((curve.projected_index(maturities[1]).unwrap() - expected).abs() < 1e-12);
+        // flat extrapolation beyond the last pillar
+        let later = Date::from_ymd(2030, 1, 1).unwrap();
+        let t = Act365::calculate_day_count_fraction::<f64>(base_date, later).unwrap();
+        assert!((curve.index_ratio(later).unwrap() - 1.02_f64.powf(t)).abs() < 1e-12);
+        assert!(curve
+            .index_ratio(Date::from_ymd(2022, 1, 1).unwrap())
+            .is_err());
+        assert!(InflationCurve::<Act365, Linear<f64>>::new(
+            base_date,
+            100.0,
+            &[base_date],
+            &[0.02]
+        )
+        .is_err());
+    }
+}
@@ -1 +1,10 @@
+pub mod commodity_curve;
+pub mod curve_interpolation;
+pub mod diagnostics;
+pub mod discount_curves;
+pub mod forward_curve;
+pub mod inflation_curve;
+mod pillar_curve;
+pub mod repo_curve;
+pub mod report;
 pub mod yield_curve;
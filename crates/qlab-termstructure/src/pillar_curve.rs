@@ -0,0 +1,35 @@
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+
+/// Pillars of year fraction and value interpolated by `I` between them and extrapolated flat beyond them.
+pub(crate) struct PillarCurve<I: Interpolator> {
+    pillars: Vec<(I::Value, I::Value)>,
+    interpolator: I,
+}
+
+impl<I: Interpolator> PillarCurve<I> {
+    /// Fits `I` to `pillars`, which must be non-empty and increasing in year fraction.
+    pub(crate) fn new(pillars: Vec<(I::Value, I::Value)>) -> QLabResult<Self> {
+        let interpolator = I::default().try_fit(&pillars)?;
+        Ok(Self {
+            pillars,
+            interpolator,
+        })
+    }
+
+    pub(crate) fn pillars(&self) -> &[(I::Value, I::Value)] {
+        &self.pillars
+    }
+
+    /// Returns the value at the year fraction `t`, flat before the first and after the last pillar.
+    pub(crate) fn value(&self, t: I::Value) -> QLabResult<I::Value> {
+        let (first, last) = (self.pillars[0], self.pillars[self.pillars.len() - 1]);
+        if t <= first.0 {
+            return Ok(first.1);
+        }
+        if t >= last.0 {
+            return Ok(last.1);
+        }
+        Ok(self.interpolator.try_value(t)?)
+    }
+}
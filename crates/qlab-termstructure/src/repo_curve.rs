@@ -0,0 +1,189 @@
+use crate::pillar_curve::PillarCurve;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use std::marker::PhantomData;
+
+/// A term structure of repo rates, the simply compounded rates accruing on `D` at which a security is financed
+/// from the settlement date to each term.
+///
+/// The rates are interpolated by `I` in the year fraction of their terms and extrapolated flat.
+/// A security trading special, i.e. financed below the general collateral rate, is modelled by
+/// [`RepoCurve::with_spread`] with a negative spread.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::repo_curve::RepoCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_360::Act360;
+///
+/// let settlement_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let terms = [Date::from_ymd(2024, 2, 2).unwrap(), Date::from_ymd(2024, 4, 2).unwrap()];
+/// let general_collateral: RepoCurve<Act360, Linear<f64>> =
+///     RepoCurve::new(settlement_date, &terms, &[0.053, 0.052]).unwrap();
+/// let special = general_collateral.with_spread(-0.01).unwrap();
+/// let growth = 1.0 / special.discount_factor(settlement_date, terms[1]).unwrap();
+/// assert!((growth - (1.0 + 0.042 * 91.0 / 360.0)).abs() < 1e-15);
+/// ```
+pub struct RepoCurve<D: DayCount, I: Interpolator> {
+    settlement_date: Date,
+    curve: PillarCurve<I>,
+    _day_count: PhantomData<D>,
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V>> RepoCurve<D, I> {
+    /// Creates a curve from the repo rates of terms ending on `terms`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the lengths of `terms` and `repo_rates` do not match, if no term is given
+    /// or the terms are not increasing after the settlement date, or an error if the interpolator fails to fit.
+    pub fn new(settlement_date: Date, terms: &[Date], repo_rates: &[V]) -> QLabResult<Self> {
+        if terms.len() != repo_rates.len() {
+            return Err(InvalidInput("terms and repo_rates are different lengths".into()).into());
+        }
+        if terms.is_empty() {
+            return Err(InvalidInput("at least one term is required".into()).into());
+        }
+        if terms[0] <= settlement_date || terms.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(InvalidInput(
+                format!("terms must increase after the settlement date: {settlement_date}").into(),
+            )
+            .into());
+        }
+        let pillars = terms
+            .iter()
+            .zip(repo_rates)
+            .map(|(&term, &rate)| {
+                Ok((
+                    D::calculate_day_count_fraction(settlement_date, term)?,
+                    rate,
+                ))
+            })
+            .collect::<QLabResult<Vec<_>>>()?;
+        Self::from_pillars(settlement_date, pillars)
+    }
+
+    fn from_pillars(settlement_date: Date, pillars: Vec<(V, V)>) -> QLabResult<Self> {
+        Ok(Self {
+            settlement_date,
+            curve: PillarCurve::new(pillars)?,
+            _day_count: PhantomData,
+        })
+    }
+
+    /// Creates the curve of a security financed `spread` over the rates of this curve,
+    /// e.g. a negative spread for a security trading special against general collateral.
+    ///
+    /// # Errors
+    /// Returns an error if the interpolator fails to fit the shifted rates.
+    pub fn with_spread(&self, spread: V) -> QLabResult<Self> {
+        let pillars = self
+            .curve
+            .pillars()
+            .iter()
+            .map(|&(t, rate)| (t, rate + spread))
+            .collect();
+        Self::from_pillars(self.settlement_date, pillars)
+    }
+
+    #[must_use]
+    pub fn settlement_date(&self) -> Date {
+        self.settlement_date
+    }
+
+    /// Returns the pillars of the curve as pairs of year fraction from the settlement date and repo rate.
+    #[must_use]
+    pub fn pillars(&self) -> &[(V, V)] {
+        self.curve.pillars()
+    }
+
+    /// Returns the repo rate of the term from the settlement date to `end`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `end` precedes the settlement date, or an error if the interpolation fails.
+    pub fn repo_rate(&self, end: Date) -> QLabResult<V> {
+        let t = self.year_fraction(end)?;
+        self.curve.value(t)
+    }
+
+    fn year_fraction(&self, date: Date) -> QLabResult<V> {
+        if date < self.settlement_date {
+            return Err(InvalidInput(
+                format!(
+                    "{date} precedes the settlement date: {}",
+                    self.settlement_date
+                )
+                .into(),
+            )
+            .into());
+        }
+        D::calculate_day_count_fraction(self.settlement_date, date)
+    }
+
+    /// Calculates the repo discount factor from `d2` back to `d1`, the ratio of the repo growth factors
+    /// `1 + r·t` of the terms from the settlement date to `d2` and to `d1`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `d2` precedes `d1` or `d1` precedes the settlement date,
+    /// or an error if the interpolation fails.
+    pub fn discount_factor(&self, d1: Date, d2: Date) -> QLabResult<V> {
+        if d2 < d1 {
+            return Err(
+                InvalidInput(format!("d1: {d1} must be smaller than d2: {d2}").into()).into(),
+            );
+        }
+        let growth = |date: Date| -> QLabResult<V> {
+            let t = self.year_fraction(date)?;
+            Ok(V::one() + self.curve.value(t)? * t)
+        };
+        Ok(growth(d1)? / growth(d2)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_360::Act360;
+
+    #[test]
+    fn test_repo_curve() {
+        let settlement_date = Date::from_ymd(2024, 1, 1).unwrap();
+        let terms = [
+            Date::from_ymd(2024, 1, 31).unwrap(),
+            Date::from_ymd(2024, 3, 31).unwrap(),
+        ];
+        let curve: RepoCurve<Act360, Linear<f64>> =
+            RepoCurve::new(settlement_date, &terms, &[0.05, 0.04]).unwrap();
+        assert!((curve.repo_rate(settlement_date).unwrap() - 0.05).abs() < 1e-15);
+        let middle = Date::from_ymd(2024, 3, 1).unwrap();
+        assert!((curve.repo_rate(middle).unwrap() - 0.045).abs() < 1e-15);
+        assert!(
+            (curve
+                .repo_rate(Date::from_ymd(2025, 1, 1).unwrap())
+                .unwrap()
+                - 0.04)
+                .abs()
+                < 1e-15
+        );
+        let forward = curve.discount_factor(terms[0], terms[1]).unwrap();
+        let expected = (1.0 + 0.05 * 30.0 / 360.0) / (1.0 + 0.04 * 90.0 / 360.0);
+        assert!((forward - expected).abs() < 1e-15);
+        assert!(curve.discount_factor(terms[1], terms[0]).is_err());
+        assert!(curve
+            .repo_rate(Date::from_ymd(2023, 12, 31).unwrap())
+            .is_err());
+        assert!(RepoCurve::<Act360, Linear<f64>>::new(settlement_date, &terms, &[0.05]).is_err());
+        assert!(RepoCurve::<Act360, Linear<f64>>::new(
+            settlement_date,
+            &[terms[1], terms[0]],
+            &[0.05, 0.04]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A table of formatted values rendered as aligned text via `Display` or as CSV via [`Table::to_csv`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_termstructure::report::Table;
+///
+/// let mut table = Table::new(&["tenor", "rate"]);
+/// table.push_row(vec!["1Y".to_string(), "0.02".to_string()]).unwrap();
+/// assert_eq!(table.to_csv(), "tenor,rate\n1Y,0.02\n");
+/// assert_eq!(table.to_string(), "tenor  rate\n-----  ----\n   1Y  0.02\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Creates an empty table with the given column headers.
+    #[must_use]
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(ToString::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Appends a row.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the number of cells differs from the number of headers.
+    pub fn push_row(&mut self, row: Vec<String>) -> QLabResult<()> {
+        if row.len() != self.headers.len() {
+            return Err(InvalidInput(
+                format!(
+                    "row has {} cells but the table has {} columns",
+                    row.len(),
+                    self.headers.len()
+                )
+                .into(),
+            )
+            .into());
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    #[must_use]
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// Renders the table as CSV, quoting cells which contain commas, quotes or line breaks.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in std::iter::once(&self.headers).chain(self.rows.iter()) {
+            let cells: Vec<_> = row.iter().map(|cell| csv_cell(cell)).collect();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+impl fmt::Display for Table {
+    /// Renders the table as right-aligned columns separated by two spaces, with a dashed rule under the headers.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let widths: Vec<_> = (0..self.headers.len())
+            .map(|i| {
+                std::iter::once(&self.headers)
+                    .chain(self.rows.iter())
+                    .map(|row| row[i].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let rule: Vec<_> = widths.iter().map(|&width| "-".repeat(width)).collect();
+        for row in std::iter::once(&self.headers)
+            .chain(std::iter::once(&rule))
+            .chain(self.rows.iter())
+        {
+            let cells: Vec<_> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:>width$}"))
+                .collect();
+            writeln!(f, "{}", cells.join("  "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(&["name", "value"]);
+        table
+            .push_row(vec!["a, b".to_string(), "say \"hi\"".to_string()])
+            .unwrap();
+        assert_eq!(table.to_csv(), "name,value\n\"a, b\",\"say \"\"hi\"\"\"\n");
+        assert_eq!(
+            table.to_string(),
+            "name     value\n----  --------\na, b  say \"hi\"\n"
+        );
+        assert!(table.push_row(vec!["too short".to_string()]).is_err());
+    }
+}
@@ -1,9 +1,13 @@
+use crate::curve_interpolation::{CurveInterpolation, CurveInterpolator};
+use crate::report::Table;
 use num_traits::real::Real;
+use num_traits::Zero;
 use qlab_error::ComputeError::InvalidInput;
 use qlab_error::QLabResult;
 use qlab_math::interpolation::Interpolator;
 use qlab_math::value::Value;
 use qlab_time::date::Date;
+use qlab_time::day_count::kind::{DayCountKind, DayCountVisitor};
 use qlab_time::day_count::DayCount;
 use std::marker::PhantomData;
 
@@ -12,10 +16,24 @@ use std::marker::PhantomData;
 /// The trait is generic over the type of Realing point values (`V`) and the day count convention (`D`).
 pub struct YieldCurve<D: DayCount, I: Interpolator> {
     settlement_date: Date,
+    maturities: Vec<Date>,
+    pillars: Vec<(I::Value, I::Value)>,
     interpolator: I,
     _day_count: PhantomData<D>,
 }
 
+impl<D: DayCount, I: Interpolator + Clone> Clone for YieldCurve<D, I> {
+    fn clone(&self) -> Self {
+        Self {
+            settlement_date: self.settlement_date,
+            maturities: self.maturities.clone(),
+            pillars: self.pillars.clone(),
+            interpolator: self.interpolator.clone(),
+            _day_count: PhantomData,
+        }
+    }
+}
+
 impl<D: DayCount, I: Interpolator<Value: Value>> YieldCurve<D, I> {
     /// Creates a new instance of the `QLab` struct.
     ///
@@ -37,24 +55,81 @@ impl<D: DayCount, I: Interpolator<Value: Value>> YieldCurve<D, I> {
         settlement_date: Date,
         maturities: &[Date],
         spot_yields: &[I::Value],
+    ) -> QLabResult<Self> {
+        Self::with_interpolator(settlement_date, maturities, spot_yields, I::default())
+    }
+
+    /// Creates a curve like [`YieldCurve::new`], fitting the spot yields with a configured `interpolator`.
+    ///
+    /// # Errors
+    /// Returns an `Err` variant if the lengths of `maturities` and `spot_yields` do not match,
+    /// or if `interpolator` fails to fit the pillars.
+    pub fn with_interpolator(
+        settlement_date: Date,
+        maturities: &[Date],
+        spot_yields: &[I::Value],
+        interpolator: I,
     ) -> QLabResult<Self> {
         if maturities.len() != spot_yields.len() {
             return Err(
                 InvalidInput("maturities and spot_yields are different lengths".into()).into(),
             );
         }
-        let maturities: Vec<_> = maturities
+        let times: Vec<_> = maturities
             .iter()
             .map(|maturity| D::calculate_day_count_fraction(settlement_date, *maturity))
             .collect::<Result<Vec<I::Value>, _>>()?;
-        let val: Vec<_> = maturities
+        let val: Vec<_> = times
             .iter()
             .copied()
             .zip(spot_yields.iter().copied())
             .collect();
-        let interpolator = I::default().try_fit(&val)?;
+        let interpolator = interpolator.try_fit(&val)?;
         Ok(Self {
             settlement_date,
+            maturities: maturities.to_vec(),
+            pillars: val,
+            _day_count: PhantomData,
+            interpolator,
+        })
+    }
+
+    /// Returns the settlement date of the curve.
+    #[must_use]
+    pub fn settlement_date(&self) -> Date {
+        self.settlement_date
+    }
+
+    /// Returns the maturity dates of the pillars.
+    #[must_use]
+    pub fn maturities(&self) -> &[Date] {
+        &self.maturities
+    }
+
+    /// Returns the pillars of the curve as pairs of year fraction from the settlement date and spot yield.
+    #[must_use]
+    pub fn pillars(&self) -> &[(I::Value, I::Value)] {
+        &self.pillars
+    }
+
+    /// Creates a new curve whose spot yields are transformed by `shift`.
+    ///
+    /// `shift` receives the year fraction and the spot yield of each pillar and returns the new spot yield.
+    /// The interpolator is refitted on the transformed pillars.
+    ///
+    /// # Errors
+    /// Returns an error if the interpolator fails to fit the transformed pillars.
+    pub fn try_shifted(&self, shift: impl Fn(I::Value, I::Value) -> I::Value) -> QLabResult<Self> {
+        let pillars: Vec<_> = self
+            .pillars
+            .iter()
+            .map(|&(t, y)| (t, shift(t, y)))
+            .collect();
+        let interpolator = self.interpolator.unfitted().try_fit(&pillars)?;
+        Ok(Self {
+            settlement_date: self.settlement_date,
+            maturities: self.maturities.clone(),
+            pillars,
             _day_count: PhantomData,
             interpolator,
         })
@@ -107,12 +182,149 @@ impl<D: DayCount, I: Interpolator<Value: Value>> YieldCurve<D, I> {
         Ok((t1 * y1 - t2 * y2).exp())
     }
 
+    /// Builds a report listing, for each pillar, its year fraction, zero rate, discount factor
+    /// and the continuously compounded forward rate from the previous pillar.
+    ///
+    /// Rates and discount factors are printed with 8 decimals; render the table with `Display` or as CSV.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn report(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&["t", "zero rate", "discount factor", "forward rate"]);
+        let mut previous = (I::Value::zero(), I::Value::zero());
+        for &(t, y) in &self.pillars {
+            let forward = if t > previous.0 {
+                (t * y - previous.0 * previous.1) / (t - previous.0)
+            } else {
+                y
+            };
+            table.push_row(vec![
+                format!("{t:.8?}"),
+                format!("{y:.8?}"),
+                format!("{:.8?}", (-t * y).exp()),
+                format!("{forward:.8?}"),
+            ])?;
+            previous = (t, y);
+        }
+        Ok(table)
+    }
+
     // Calculates continuous yield at the specified time.
     fn yield_curve(&self, t: I::Value) -> QLabResult<I::Value> {
         Ok(self.interpolator.try_value(t)?)
     }
 }
 
+impl<D: DayCount, V: Value> YieldCurve<D, CurveInterpolator<V>> {
+    /// Creates a curve interpolated by the market convention `interpolation`.
+    ///
+    /// # Errors
+    /// Returns an `Err` variant if the lengths of `maturities` and `spot_yields` do not match,
+    /// or if the pillars cannot be interpolated by `interpolation`.
+    pub fn with_interpolation(
+        settlement_date: Date,
+        maturities: &[Date],
+        spot_yields: &[V],
+        interpolation: CurveInterpolation,
+    ) -> QLabResult<Self> {
+        Self::with_interpolator(
+            settlement_date,
+            maturities,
+            spot_yields,
+            CurveInterpolator::new(interpolation),
+        )
+    }
+
+    /// Returns the market convention the curve is interpolated by.
+    #[must_use]
+    pub fn interpolation(&self) -> CurveInterpolation {
+        self.interpolator.interpolation()
+    }
+}
+
+/// A discount curve whose day count convention and interpolation are hidden behind a trait object,
+/// for curves set up from configuration at runtime.
+pub trait DiscountCurve<V> {
+    fn settlement_date(&self) -> Date;
+
+    /// Calculates the discount factor from `d2` back to `d1`.
+    ///
+    /// # Errors
+    /// Returns an error if the dates are invalid for the curve or a calculation fails.
+    fn discount_factor(&self, d1: Date, d2: Date) -> QLabResult<V>;
+}
+
+impl<D: DayCount, I: Interpolator<Value: Value>> DiscountCurve<I::Value> for YieldCurve<D, I> {
+    fn settlement_date(&self) -> Date {
+        self.settlement_date
+    }
+
+    fn discount_factor(&self, d1: Date, d2: Date) -> QLabResult<I::Value> {
+        Self::discount_factor(self, d1, d2)
+    }
+}
+
+/// Creates a [`YieldCurve`] of the day count convention and interpolation chosen at runtime.
+///
+/// # Errors
+/// Returns an error under the same conditions as [`YieldCurve::with_interpolation`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_termstructure::curve_interpolation::CurveInterpolation;
+/// use qlab_termstructure::yield_curve::boxed_yield_curve;
+/// use qlab_time::date::Date;
+///
+/// let settlement_date = Date::from_ymd(2024, 1, 1).unwrap();
+/// let maturities = [Date::from_ymd(2025, 1, 1).unwrap(), Date::from_ymd(2026, 1, 1).unwrap()];
+/// let curve = boxed_yield_curve(
+///     "ACT/360".parse().unwrap(),
+///     settlement_date,
+///     &maturities,
+///     &[0.03, 0.035],
+///     CurveInterpolation::LinearZero,
+/// )
+/// .unwrap();
+/// let discount_factor = curve.discount_factor(settlement_date, maturities[0]).unwrap();
+/// assert!((discount_factor - (-0.03_f64 * 366.0 / 360.0).exp()).abs() < 1e-15);
+/// ```
+pub fn boxed_yield_curve<V: Value>(
+    day_count: DayCountKind,
+    settlement_date: Date,
+    maturities: &[Date],
+    spot_yields: &[V],
+    interpolation: CurveInterpolation,
+) -> QLabResult<Box<dyn DiscountCurve<V>>> {
+    struct Build<'a, V> {
+        settlement_date: Date,
+        maturities: &'a [Date],
+        spot_yields: &'a [V],
+        interpolation: CurveInterpolation,
+    }
+
+    impl<V: Value> DayCountVisitor for Build<'_, V> {
+        type Output = QLabResult<Box<dyn DiscountCurve<V>>>;
+
+        fn visit<D: DayCount + 'static>(self) -> Self::Output {
+            let curve = YieldCurve::<D, CurveInterpolator<V>>::with_interpolation(
+                self.settlement_date,
+                self.maturities,
+                self.spot_yields,
+                self.interpolation,
+            )?;
+            Ok(Box::new(curve))
+        }
+    }
+
+    day_count.visit(Build {
+        settlement_date,
+        maturities,
+        spot_yields,
+        interpolation,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,4 +359,106 @@ mod tests {
         let discount_factor = yield_curve.discount_factor(d1, d2).unwrap();
         assert!((discount_factor - 1.0_f64).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn test_try_shifted() {
+        let settlement_date = Date::from_ymd(2022, 12, 31).unwrap();
+        let maturities = vec![Date::from_ymd(2023, 12, 31).unwrap()];
+        let spot_yields = vec![0.02];
+
+        let yield_curve =
+            YieldCurve::<Act365, Flat>::new(settlement_date, &maturities, &spot_yields).unwrap();
+        let shifted = yield_curve.try_shifted(|_t, y| y + 0.01).unwrap();
+        assert_eq!(shifted.settlement_date(), settlement_date);
+        assert!((shifted.pillars()[0].1 - 0.03).abs() < f64::EPSILON);
+        assert!((yield_curve.pillars()[0].1 - 0.02).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_hermite_curve() {
+        use qlab_math::interpolation::spline::hermite::Hermite;
+
+        let settlement_date = Date::from_ymd(2023, 1, 1).unwrap();
+        let maturities = vec![
+            Date::from_ymd(2023, 1, 2).unwrap(),
+            Date::from_ymd(2024, 1, 1).unwrap(),
+            Date::from_ymd(2028, 1, 1).unwrap(),
+        ];
+        let yield_curve = YieldCurve::<Act365, Hermite<f64>>::new(
+            settlement_date,
+            &maturities,
+            &[0.02, 0.03, 0.035],
+        )
+        .unwrap();
+        let discount_factor = yield_curve
+            .discount_factor(settlement_date, Date::from_ymd(2024, 1, 1).unwrap())
+            .unwrap();
+        assert!((discount_factor - (-0.03_f64).exp()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_report() {
+        let settlement_date = Date::from_ymd(2023, 1, 1).unwrap();
+        let maturities = vec![
+            Date::from_ymd(2024, 1, 1).unwrap(),
+            Date::from_ymd(2025, 1, 1).unwrap(),
+        ];
+        let yield_curve =
+            YieldCurve::<Act365, Flat>::new(settlement_date, &maturities, &[0.02, 0.03]).unwrap();
+        let report = yield_curve.report().unwrap();
+        assert_eq!(
+            report.to_csv(),
+            "t,zero rate,discount factor,forward rate\n\
+             1.00000000,0.02000000,0.98019867,0.02000000\n\
+             2.00273973,0.03000000,0.94168713,0.03997268\n"
+        );
+    }
+
+    #[test]
+    fn test_boxed_yield_curve() {
+        let settlement_date = Date::from_ymd(2024, 1, 1).unwrap();
+        let maturities = [
+            Date::from_ymd(2025, 1, 1).unwrap(),
+            Date::from_ymd(2026, 1, 1).unwrap(),
+        ];
+        let spot_yields = [0.03, 0.04];
+        let curves: Vec<Box<dyn DiscountCurve<f64>>> = ["ACT/365", "30/360"]
+            .into_iter()
+            .map(|name| {
+                boxed_yield_curve(
+                    name.parse().unwrap(),
+                    settlement_date,
+                    &maturities,
+                    &spot_yields,
+                    CurveInterpolation::LinearZero,
+                )
+                .unwrap()
+            })
+            .collect();
+        let typed = YieldCurve::<Act365, CurveInterpolator<f64>>::with_interpolation(
+            settlement_date,
+            &maturities,
+            &spot_yields,
+            CurveInterpolation::LinearZero,
+        )
+        .unwrap();
+        let end = Date::from_ymd(2025, 7, 1).unwrap();
+        assert_eq!(curves[0].settlement_date(), settlement_date);
+        assert!(
+            (curves[0].discount_factor(settlement_date, end).unwrap()
+                - typed.discount_factor(settlement_date, end).unwrap())
+            .abs()
+                < f64::EPSILON
+        );
+        let thirty_360 = curves[1].discount_factor(settlement_date, end).unwrap();
+        assert!((thirty_360 - (-1.5_f64 * (0.03 + 0.5 * 0.01)).exp()).abs() < 1e-15);
+        assert!(boxed_yield_curve(
+            DayCountKind::Act360,
+            settlement_date,
+            &maturities,
+            &[0.03],
+            CurveInterpolation::LinearZero
+        )
+        .is_err());
+    }
 }
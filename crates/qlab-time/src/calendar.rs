@@ -1,4 +1,14 @@
 use crate::date::Date;
+use crate::date_rolling::DateRolling;
+use crate::period::days::Days;
+use crate::period::Period;
+use crate::weekday::Weekday;
+use std::cell::Cell;
+
+pub mod bespoke;
+pub mod cached;
+pub mod rules;
+pub mod version;
 
 pub trait Calendar {
     fn is_business_day(&self, date: Date) -> bool;
@@ -6,6 +16,275 @@ pub trait Calendar {
     fn is_holiday(&self, date: Date) -> bool {
         !self.is_business_day(date)
     }
+
+    /// Returns `true` if `date` falls on a weekend day, which is a holiday in every week.
+    ///
+    /// The provided implementation takes Saturday and Sunday as the weekend;
+    /// calendars with other weekend days override it.
+    fn is_weekend(&self, date: Date) -> bool {
+        matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday)
+    }
+
+    /// Counts the business days from `start` to `end`, including `start` but not `end`.
+    /// The count is negative if `end` precedes `start`.
+    ///
+    /// The provided implementation counts the days off the weekend by whole weeks, assuming that
+    /// [`Calendar::is_weekend`] depends on the weekday only, and then subtracts the other holidays listed by
+    /// [`Calendar::holidays_between`]. Rule-based calendars expose no list of their holidays, so the provided
+    /// listing evaluates [`Calendar::is_holiday`] on every day of the range and the count costs O(days).
+    /// Wrap a calendar counting over long or repeated ranges in a [`CachedCalendar`](cached::CachedCalendar),
+    /// whose counts take constant time; calendars that know their holidays in advance may override either method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_time::calendar::Calendar;
+    /// use qlab_time::date::Date;
+    ///
+    /// let start = Date::from_ymd(2024, 3, 29).unwrap();
+    /// let end = Date::from_ymd(2024, 4, 8).unwrap();
+    /// assert_eq!(WeekendsOnly.business_days_between(start, end), 6);
+    /// assert_eq!(WeekendsOnly.business_days_between(end, start), -6);
+    /// ```
+    #[allow(
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_truncation
+    )] // counts of at most a week
+    fn business_days_between(&self, start: Date, end: Date) -> i64 {
+        if end < start {
+            return -self.business_days_between(end, start);
+        }
+        let days = end - start;
+        // the weekend falls on the same weekdays every week, so only the first week is checked day by day
+        let week: Vec<bool> = (0..7)
+            .map(|offset| {
+                start
+                    .checked_add_days(Days::new(offset))
+                    .is_some_and(|date| !self.is_weekend(date))
+            })
+            .collect();
+        let weekdays = |days: usize| week[..days].iter().filter(|&&weekday| weekday).count() as i64;
+        let holidays = self
+            .holidays_between(start, end)
+            .into_iter()
+            .filter(|&date| !self.is_weekend(date))
+            .count() as i64;
+        days / 7 * weekdays(7) + weekdays((days % 7) as usize) - holidays
+    }
+
+    /// Adjusts `date` to a business day by `rolling`; the modified conventions stay within the month of `date`,
+    /// and the half-month one also within its half of the month.
+    ///
+    /// Returns `None` if the adjustment runs out of the range of dates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_time::calendar::Calendar;
+    /// use qlab_time::date::Date;
+    /// use qlab_time::date_rolling::DateRolling;
+    ///
+    /// let sunday = Date::from_ymd(2024, 3, 31).unwrap();
+    /// assert_eq!(WeekendsOnly.adjust(sunday, DateRolling::Following), Date::from_ymd(2024, 4, 1));
+    /// assert_eq!(WeekendsOnly.adjust(sunday, DateRolling::ModifiedFollowing), Date::from_ymd(2024, 3, 29));
+    /// ```
+    fn adjust(&self, date: Date, rolling: DateRolling) -> Option<Date> {
+        let following = |mut date: Date| {
+            while !self.is_business_day(date) {
+                date = date.succ_opt()?;
+            }
+            Some(date)
+        };
+        let preceding = |mut date: Date| {
+            while !self.is_business_day(date) {
+                date = date.pred_opt()?;
+            }
+            Some(date)
+        };
+        match rolling {
+            DateRolling::Unadjusted => Some(date),
+            DateRolling::Following => following(date),
+            DateRolling::Preceding => preceding(date),
+            DateRolling::ModifiedFollowing => following(date)
+                .filter(|adjusted| adjusted.month() == date.month())
+                .or_else(|| preceding(date)),
+            DateRolling::ModifiedPreceding => preceding(date)
+                .filter(|adjusted| adjusted.month() == date.month())
+                .or_else(|| following(date)),
+            DateRolling::Nearest => {
+                if self.is_business_day(date) {
+                    return Some(date);
+                }
+                let (mut later, mut earlier) = (date, date);
+                loop {
+                    later = later.succ_opt()?;
+                    if self.is_business_day(later) {
+                        return Some(later);
+                    }
+                    earlier = earlier.pred_opt()?;
+                    if self.is_business_day(earlier) {
+                        return Some(earlier);
+                    }
+                }
+            }
+            DateRolling::HalfMonthModifiedFollowing => following(date)
+                .filter(|adjusted| {
+                    adjusted.month() == date.month() && (date.day() > 15 || adjusted.day() <= 15)
+                })
+                .or_else(|| preceding(date)),
+        }
+    }
+
+    /// Adjusts each of `dates` in place by `rolling` as [`Calendar::adjust`] does.
+    ///
+    /// The provided implementation evaluates each day of the span of `dates` at most once, however many dates
+    /// fall on or roll over it, so that adjusting the dates of many schedules at once does not repeat
+    /// the holiday rules of the same days. Dates spanning more than [`MAX_ADJUST_SPAN_DAYS`] days are adjusted
+    /// one by one instead, so that a few far apart dates do not allocate a lookup for every day in between.
+    ///
+    /// Returns `None` if the adjustment of a date runs out of the range of dates,
+    /// in which case none of `dates` is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::target::Target;
+    /// use qlab_time::calendar::Calendar;
+    /// use qlab_time::date::Date;
+    /// use qlab_time::date_rolling::DateRolling;
+    ///
+    /// let mut dates = [
+    ///     Date::from_ymd(2024, 3, 31).unwrap(),
+    ///     Date::from_ymd(2024, 12, 25).unwrap(),
+    ///     Date::from_ymd(2024, 3, 31).unwrap(),
+    /// ];
+    /// Target.adjust_many(&mut dates, DateRolling::Following).unwrap();
+    /// assert_eq!(dates[0], Date::from_ymd(2024, 4, 2).unwrap());
+    /// assert_eq!(dates[1], Date::from_ymd(2024, 12, 27).unwrap());
+    /// assert_eq!(dates[2], dates[0]);
+    /// ```
+    fn adjust_many(&self, dates: &mut [Date], rolling: DateRolling) -> Option<()> {
+        let (Some(&first), Some(&last)) = (dates.iter().min(), dates.iter().max()) else {
+            return Some(());
+        };
+        if rolling == DateRolling::Unadjusted {
+            return Some(());
+        }
+        let adjusted = if last - first > MAX_ADJUST_SPAN_DAYS {
+            dates
+                .iter()
+                .map(|&date| self.adjust(date, rolling))
+                .collect::<Option<Vec<_>>>()?
+        } else {
+            // a margin for the dates rolling over the ends of the span
+            let margin = Days::new(7);
+            let start = first.checked_sub_days(margin).unwrap_or(first);
+            let end = last.checked_add_days(margin).unwrap_or(last);
+            let lookup = SpanLookup {
+                calendar: self,
+                start,
+                end,
+                #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                // `end` does not precede `start` and the span is capped
+                business_days: vec![Cell::new(None); (end - start) as usize + 1],
+            };
+            dates
+                .iter()
+                .map(|&date| lookup.adjust(date, rolling))
+                .collect::<Option<Vec<_>>>()?
+        };
+        dates.copy_from_slice(&adjusted);
+        Some(())
+    }
+
+    /// Adds `period` to `date` and adjusts the result by `rolling`.
+    ///
+    /// With `end_of_month`, a date on the last day of its month advanced by a whole number of months
+    /// lands on the last day of the resulting month before the adjustment, e.g. 2024-04-30 + 1M = 2024-05-31.
+    ///
+    /// Returns `None` if the result runs out of the range of dates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_time::calendar::Calendar;
+    /// use qlab_time::date::Date;
+    /// use qlab_time::date_rolling::DateRolling;
+    /// use qlab_time::period::months::Months;
+    ///
+    /// let end_of_april = Date::from_ymd(2024, 4, 30).unwrap();
+    /// let advanced = WeekendsOnly.advance(end_of_april, Months::new(1), DateRolling::ModifiedFollowing, true);
+    /// assert_eq!(advanced, Date::from_ymd(2024, 5, 31));
+    /// ```
+    fn advance(
+        &self,
+        date: Date,
+        period: impl Period,
+        rolling: DateRolling,
+        end_of_month: bool,
+    ) -> Option<Date>
+    where
+        Self: Sized,
+    {
+        let mut unadjusted = period.checked_add(date)?;
+        if end_of_month && date.is_end_of_month() && period.is_whole_months() {
+            unadjusted = unadjusted.end_of_month();
+        }
+        self.adjust(unadjusted, rolling)
+    }
+
+    /// Lists the holidays from `start` to `end`, including `start` but not `end`, in ascending order.
+    ///
+    /// Weekend days are holidays too; filter them out with [`Calendar::is_weekend`] to list public holidays only.
+    /// The provided implementation evaluates [`Calendar::is_holiday`] on every day of the range.
+    /// The list is empty if `end` does not follow `start`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_time::calendar::Calendar;
+    /// use qlab_time::date::Date;
+    ///
+    /// let start = Date::from_ymd(2024, 3, 29).unwrap();
+    /// let end = Date::from_ymd(2024, 4, 8).unwrap();
+    /// let holidays = WeekendsOnly.holidays_between(start, end);
+    /// assert_eq!(holidays.len(), 4);
+    /// assert_eq!(holidays[0], Date::from_ymd(2024, 3, 30).unwrap());
+    /// ```
+    fn holidays_between(&self, start: Date, end: Date) -> Vec<Date> {
+        Date::range(start, end)
+            .filter(|&date| self.is_holiday(date))
+            .collect()
+    }
+
+    /// Lists the business days of `month` of `year` in ascending order,
+    /// which is empty if the month is not valid.
+    fn business_days_in_month(&self, year: i32, month: u32) -> Vec<Date> {
+        let Some(first) = Date::from_ymd(year, month, 1) else {
+            return Vec::new();
+        };
+        let end = first.end_of_month();
+        Date::range(first, end)
+            .chain(std::iter::once(end))
+            .filter(|&date| self.is_business_day(date))
+            .collect()
+    }
+
+    /// Returns an iterator over the business days from `start` to `end`, including `start` but not `end`.
+    ///
+    /// The iterator is boxed so that shared calendars behind a trait object can list their business days too.
+    fn business_days_in(&self, start: Date, end: Date) -> Box<dyn Iterator<Item = Date> + '_> {
+        Box::new(BusinessDayIter {
+            calendar: self,
+            next: start,
+            end,
+        })
+    }
 }
 
 impl<C: calendar::Calendar> Calendar for C {
@@ -17,3 +296,305 @@ impl<C: calendar::Calendar> Calendar for C {
         self.is_holiday(date.0)
     }
 }
+
+/// The longest span of dates, in days, for which [`Calendar::adjust_many`] looks up each day at most once.
+pub const MAX_ADJUST_SPAN_DAYS: i64 = 100 * 366;
+
+/// The business days of `calendar` from `start` to `end` inclusive, each evaluated on its first lookup,
+/// see [`Calendar::adjust_many`].
+struct SpanLookup<'a, C: ?Sized> {
+    calendar: &'a C,
+    start: Date,
+    end: Date,
+    business_days: Vec<Cell<Option<bool>>>,
+}
+
+impl<C: Calendar + ?Sized> Calendar for SpanLookup<'_, C> {
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)] // the offset is checked to be within the span
+    fn is_business_day(&self, date: Date) -> bool {
+        if date < self.start || self.end < date {
+            return self.calendar.is_business_day(date);
+        }
+        let cell = &self.business_days[(date - self.start) as usize];
+        cell.get().unwrap_or_else(|| {
+            let is_business_day = self.calendar.is_business_day(date);
+            cell.set(Some(is_business_day));
+            is_business_day
+        })
+    }
+}
+
+/// An iterator over the business days of a calendar in a range of dates, see [`Calendar::business_days_in`].
+#[derive(Debug)]
+struct BusinessDayIter<'a, C: ?Sized> {
+    calendar: &'a C,
+    next: Date,
+    end: Date,
+}
+
+impl<C: Calendar + ?Sized> Iterator for BusinessDayIter<'_, C> {
+    type Item = Date;
+
+    fn next(&mut self) -> Option<Date> {
+        while self.next < self.end {
+            let date = self.next;
+            // `end` bounds the range, so a successor exists
+            self.next = date.succ_opt().unwrap_or(self.end);
+            if self.calendar.is_business_day(date) {
+                return Some(date);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::weekendsonly::WeekendsOnly;
+
+    #[test]
+    fn test_business_days_in() {
+        let start = Date::from_ymd(2024, 3, 29).unwrap();
+        let end = Date::from_ymd(2024, 4, 3).unwrap();
+        let days: Vec<_> = WeekendsOnly.business_days_in(start, end).collect();
+        assert_eq!(
+            days,
+            [
+                start,
+                Date::from_ymd(2024, 4, 1).unwrap(),
+                Date::from_ymd(2024, 4, 2).unwrap(),
+            ]
+        );
+        assert_eq!(WeekendsOnly.business_days_in(end, start).count(), 0);
+        assert_eq!(WeekendsOnly.business_days_between(start, start), 0);
+        // 2024 has 262 weekdays
+        assert_eq!(
+            WeekendsOnly.business_days_between(
+                Date::from_ymd(2024, 1, 1).unwrap(),
+                Date::from_ymd(2025, 1, 1).unwrap()
+            ),
+            262
+        );
+
+        // the count by whole weeks agrees with a walk over every day, whatever the weekday of the start
+        let target = calendar::target::Target;
+        let first = Date::from_ymd(2024, 12, 20).unwrap();
+        for (start, end) in
+            Date::range(first, Date::from_ymd(2024, 12, 28).unwrap()).flat_map(|start| {
+                Date::range(start, Date::from_ymd(2025, 1, 10).unwrap())
+                    .map(move |end| (start, end))
+            })
+        {
+            let walked = Date::range(start, end)
+                .filter(|&date| target.is_business_day(date))
+                .count();
+            assert_eq!(
+                target.business_days_between(start, end),
+                i64::try_from(walked).unwrap(),
+                "{start} {end}"
+            );
+            assert_eq!(target.business_days_in(start, end).count(), walked);
+        }
+
+        // a shared calendar lists its business days behind the trait object
+        let shared: &dyn Calendar = &WeekendsOnly;
+        assert_eq!(shared.business_days_in(start, end).count(), 3);
+    }
+
+    #[test]
+    fn test_holiday_listing() {
+        // Easter Monday 2024 is a TARGET holiday
+        let target = calendar::target::Target;
+        let holidays = target.holidays_between(
+            Date::from_ymd(2024, 3, 29).unwrap(),
+            Date::from_ymd(2024, 4, 6).unwrap(),
+        );
+        assert_eq!(holidays.len(), 4);
+        assert!(holidays.contains(&Date::from_ymd(2024, 4, 1).unwrap()));
+        assert_eq!(
+            holidays
+                .iter()
+                .filter(|&&date| !target.is_weekend(date))
+                .count(),
+            2
+        );
+        assert!(target
+            .holidays_between(
+                Date::from_ymd(2024, 4, 6).unwrap(),
+                Date::from_ymd(2024, 3, 29).unwrap()
+            )
+            .is_empty());
+
+        let april = target.business_days_in_month(2024, 4);
+        assert_eq!(april.len(), 21);
+        assert_eq!(april[0], Date::from_ymd(2024, 4, 2).unwrap());
+        assert_eq!(april[20], Date::from_ymd(2024, 4, 30).unwrap());
+        assert_eq!(WeekendsOnly.business_days_in_month(2024, 3).len(), 21);
+        assert!(WeekendsOnly.business_days_in_month(2024, 13).is_empty());
+    }
+
+    /// A calendar without business days after its last year, so that adjusting late dates fails.
+    struct Closing;
+
+    impl Calendar for Closing {
+        fn is_business_day(&self, date: Date) -> bool {
+            date.year() < 262_000 && !self.is_weekend(date)
+        }
+    }
+
+    #[test]
+    fn test_adjust_many() {
+        let target = calendar::target::Target;
+        let rollings = [
+            DateRolling::Unadjusted,
+            DateRolling::Following,
+            DateRolling::ModifiedFollowing,
+            DateRolling::Preceding,
+            DateRolling::ModifiedPreceding,
+            DateRolling::Nearest,
+            DateRolling::HalfMonthModifiedFollowing,
+        ];
+        // unsorted dates, some of them repeated
+        let mut dates: Vec<Date> = Date::range(
+            Date::from_ymd(2023, 12, 20).unwrap(),
+            Date::from_ymd(2025, 1, 10).unwrap(),
+        )
+        .step_by(3)
+        .collect();
+        dates.reverse();
+        dates.extend_from_within(..20);
+        for rolling in rollings {
+            let mut adjusted = dates.clone();
+            target.adjust_many(&mut adjusted, rolling).unwrap();
+            for (&date, &adjusted) in dates.iter().zip(&adjusted) {
+                assert_eq!(target.adjust(date, rolling), Some(adjusted), "{date}");
+            }
+        }
+        // far apart dates are adjusted one by one, and a failing date leaves all of them unchanged
+        let mut far_apart = [
+            Date::from_ymd(2000, 12, 25).unwrap(),
+            Date::from_ymd(2102, 12, 25).unwrap(),
+        ];
+        target
+            .adjust_many(&mut far_apart, DateRolling::Following)
+            .unwrap();
+        assert_eq!(far_apart[0], Date::from_ymd(2000, 12, 27).unwrap());
+        assert_eq!(far_apart[1], Date::from_ymd(2102, 12, 27).unwrap());
+        let sunday = Date::from_ymd(2024, 3, 31).unwrap();
+        for mut failing in [
+            [sunday, Date::from_ymd(262_100, 1, 1).unwrap()],
+            [sunday, Date::from_ymd(262_000, 1, 1).unwrap()],
+        ] {
+            assert_eq!(
+                Closing.adjust_many(&mut failing, DateRolling::Following),
+                None
+            );
+            assert_eq!(failing[0], sunday);
+        }
+        let mut empty: [Date; 0] = [];
+        assert_eq!(
+            WeekendsOnly.adjust_many(&mut empty, DateRolling::Following),
+            Some(())
+        );
+    }
+
+    #[test]
+    fn test_advance() {
+        use crate::period::days::Days;
+        use crate::period::months::Months;
+
+        let friday = Date::from_ymd(2024, 5, 31).unwrap();
+        assert_eq!(
+            WeekendsOnly.advance(friday, Days::new(1), DateRolling::Following, false),
+            Date::from_ymd(2024, 6, 3)
+        );
+        // the modified conventions compare against the month of the unadjusted date
+        assert_eq!(
+            WeekendsOnly.advance(friday, Days::new(1), DateRolling::ModifiedPreceding, false),
+            Date::from_ymd(2024, 6, 3)
+        );
+        let end_of_february = Date::from_ymd(2023, 2, 28).unwrap();
+        assert_eq!(
+            WeekendsOnly.advance(
+                end_of_february,
+                Months::new(1),
+                DateRolling::Unadjusted,
+                true
+            ),
+            Date::from_ymd(2023, 3, 31)
+        );
+        assert_eq!(
+            WeekendsOnly.advance(
+                end_of_february,
+                Months::new(1),
+                DateRolling::Unadjusted,
+                false
+            ),
+            Date::from_ymd(2023, 3, 28)
+        );
+        let saturday = Date::from_ymd(2024, 6, 1).unwrap();
+        assert_eq!(
+            WeekendsOnly.adjust(saturday, DateRolling::ModifiedPreceding),
+            Date::from_ymd(2024, 6, 3)
+        );
+        assert_eq!(
+            WeekendsOnly.adjust(saturday, DateRolling::Unadjusted),
+            Some(saturday)
+        );
+    }
+
+    #[test]
+    fn test_business_days_with_holidays() {
+        use crate::calendar::cached::CachedCalendar;
+        use calendar::japan::Japan;
+        use calendar::unitedstates::UsSettlement;
+
+        let new_year = |year| Date::from_ymd(year, 1, 1).unwrap();
+        // 262 weekdays in 2024 less 17 Japanese and 11 US holidays falling on weekdays,
+        // the substitute holidays and the bank holidays of 2 and 3 January and 31 December included
+        assert_eq!(
+            Japan.business_days_between(new_year(2024), new_year(2025)),
+            245
+        );
+        assert_eq!(
+            UsSettlement.business_days_between(new_year(2024), new_year(2025)),
+            251
+        );
+        assert_eq!(
+            Japan.business_days_between(new_year(2020), new_year(2026)),
+            1466
+        );
+        assert_eq!(
+            UsSettlement.business_days_between(new_year(2020), new_year(2026)),
+            1502
+        );
+        // ranges starting and ending mid-week and mid-year agree with a walk over every day and with the cache
+        let japan = CachedCalendar::new(Japan, 2015, 2030).unwrap();
+        let us = CachedCalendar::new(UsSettlement, 2015, 2030).unwrap();
+        let start = Date::from_ymd(2019, 4, 26).unwrap();
+        let end = Date::from_ymd(2027, 9, 23).unwrap();
+        let walked = |calendar: &dyn Calendar| {
+            i64::try_from(
+                Date::range(start, end)
+                    .filter(|&date| calendar.is_business_day(date))
+                    .count(),
+            )
+            .unwrap()
+        };
+        assert_eq!(Japan.business_days_between(start, end), walked(&Japan));
+        assert_eq!(
+            Japan.business_days_between(start, end),
+            japan.business_days_between(start, end)
+        );
+        assert_eq!(Japan.business_days_between(end, start), -walked(&Japan));
+        assert_eq!(
+            UsSettlement.business_days_between(start, end),
+            walked(&UsSettlement)
+        );
+        assert_eq!(
+            UsSettlement.business_days_between(start, end),
+            us.business_days_between(start, end)
+        );
+    }
+}
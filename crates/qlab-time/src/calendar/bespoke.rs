@@ -0,0 +1,237 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::holiday_data::parse_holidays;
+use crate::period::days::Days;
+use chrono::{Datelike, Weekday};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+
+/// A calendar defined by an explicit list of holidays and a set of weekend days,
+/// for proprietary or exchange-specific holiday files.
+///
+/// The weekend consists of Saturday and Sunday unless set by [`BespokeCalendar::with_weekend`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::calendar::bespoke::BespokeCalendar;
+/// use qlab_time::calendar::Calendar;
+/// use qlab_time::date::Date;
+/// use std::collections::HashSet;
+///
+/// let founders_day = Date::from_ymd(2024, 6, 14).unwrap();
+/// let calendar = BespokeCalendar::new("desk", HashSet::from([founders_day]));
+/// assert!(calendar.is_holiday(founders_day));
+///
+/// let reloaded = BespokeCalendar::parse("desk", &calendar.to_text()).unwrap();
+/// assert_eq!(reloaded, calendar);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BespokeCalendar {
+    name: String,
+    weekend: Vec<Weekday>,
+    holidays: HashSet<Date>,
+}
+
+impl BespokeCalendar {
+    #[must_use]
+    pub fn new(name: &str, holidays: HashSet<Date>) -> Self {
+        Self {
+            name: name.to_string(),
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            holidays,
+        }
+    }
+
+    /// Creates a calendar from the text of a holiday file, see [`parse_holidays`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a line is not a valid date.
+    pub fn parse(name: &str, text: &str) -> QLabResult<Self> {
+        Ok(Self::new(name, parse_holidays(text)?.into_iter().collect()))
+    }
+
+    /// Loads a calendar from a holiday file with one `YYYY-MM-DD` date per line.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the file cannot be read or a line is not a valid date.
+    pub fn load(name: &str, path: impl AsRef<Path>) -> QLabResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| InvalidInput(format!("{}: {err}", path.display()).into()))?;
+        Self::parse(name, &text)
+    }
+
+    /// Replaces the weekend days, which are holidays in every week.
+    #[must_use]
+    pub fn with_weekend(mut self, weekend: &[Weekday]) -> Self {
+        self.weekend = weekend.to_vec();
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn weekend(&self) -> &[Weekday] {
+        &self.weekend
+    }
+
+    #[must_use]
+    pub fn holidays(&self) -> &HashSet<Date> {
+        &self.holidays
+    }
+
+    pub fn add_holiday(&mut self, date: Date) {
+        self.holidays.insert(date);
+    }
+
+    pub fn remove_holiday(&mut self, date: Date) {
+        self.holidays.remove(&date);
+    }
+
+    /// Writes the holidays in ascending order, one per line after a comment line with the name,
+    /// in the format read by [`BespokeCalendar::parse`]. The weekend is not written.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut holidays: Vec<_> = self.holidays.iter().collect();
+        holidays.sort_unstable();
+        let mut text = format!("# {}\n", self.name);
+        for holiday in holidays {
+            // writing into a `String` cannot fail
+            let _ = writeln!(text, "{holiday}");
+        }
+        text
+    }
+
+    /// Saves the holidays to `path`, see [`BespokeCalendar::to_text`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> QLabResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_text())
+            .map_err(|err| InvalidInput(format!("{}: {err}", path.display()).into()).into())
+    }
+}
+
+impl Calendar for BespokeCalendar {
+    fn is_weekend(&self, date: Date) -> bool {
+        self.weekend.contains(&date.0.weekday())
+    }
+
+    fn is_business_day(&self, date: Date) -> bool {
+        !self.is_weekend(date) && !self.holidays.contains(&date)
+    }
+
+    fn holidays_between(&self, start: Date, end: Date) -> Vec<Date> {
+        let mut holidays: Vec<_> = self
+            .holidays
+            .iter()
+            .copied()
+            .filter(|&date| start <= date && date < end)
+            .collect();
+        // each weekend day recurs weekly from its first occurrence
+        for weekday in &self.weekend {
+            let offset =
+                (7 + weekday.num_days_from_monday() - start.0.weekday().num_days_from_monday()) % 7;
+            if let Some(first) = start.checked_add_days(Days::new(u64::from(offset))) {
+                holidays.extend(Date::range(first, end).step_by_period(Days::new(7)));
+            }
+        }
+        holidays.sort_unstable();
+        holidays.dedup();
+        holidays
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bespoke_calendar() {
+        let holiday = Date::from_ymd(2024, 4, 10).unwrap();
+        let mut calendar = BespokeCalendar::new("gulf", HashSet::from([holiday]))
+            .with_weekend(&[Weekday::Fri, Weekday::Sat]);
+        assert!(calendar.is_holiday(holiday));
+        assert!(calendar.is_holiday(Date::from_ymd(2024, 4, 12).unwrap()));
+        assert!(calendar.is_business_day(Date::from_ymd(2024, 4, 14).unwrap()));
+        calendar.remove_holiday(holiday);
+        assert!(calendar.is_business_day(holiday));
+        calendar.add_holiday(Date::from_ymd(2024, 4, 11).unwrap());
+        // a holiday on the weekend is listed once
+        calendar.add_holiday(Date::from_ymd(2024, 4, 19).unwrap());
+
+        let start = Date::from_ymd(2024, 4, 9).unwrap();
+        let end = Date::from_ymd(2024, 4, 21).unwrap();
+        let walked: Vec<_> = Date::range(start, end)
+            .filter(|&date| calendar.is_holiday(date))
+            .collect();
+        assert_eq!(calendar.holidays_between(start, end), walked);
+        assert_eq!(walked.len(), 5);
+        assert_eq!(calendar.business_days_between(start, end), 7);
+        assert!(calendar.holidays_between(end, start).is_empty());
+
+        // unique per process so that concurrent test runs do not share the file
+        let path = std::env::temp_dir().join(format!(
+            "qlab_test_bespoke_calendar_{}.txt",
+            std::process::id()
+        ));
+        calendar.save(&path).unwrap();
+        let loaded = BespokeCalendar::load("gulf", &path)
+            .unwrap()
+            .with_weekend(&[Weekday::Fri, Weekday::Sat]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, calendar);
+        assert!(BespokeCalendar::load("gulf", &path).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_and_edges() {
+        assert!(BespokeCalendar::parse("desk", "2024-01-02\n2024-13-01\n").is_err());
+        assert!(BespokeCalendar::parse("desk", "not a date").is_err());
+        let calendar = BespokeCalendar::parse(
+            "desk",
+            "# desk\n\n  2024-01-02  \n# 2024-01-03\n2024-01-02\n",
+        )
+        .unwrap();
+        assert_eq!(calendar.name(), "desk");
+        assert_eq!(calendar.weekend(), &[Weekday::Sat, Weekday::Sun]);
+        assert_eq!(calendar.holidays().len(), 1);
+        assert_eq!(
+            BespokeCalendar::new("empty", HashSet::new()).to_text(),
+            "# empty\n"
+        );
+
+        // a holiday on a weekend day is listed once
+        let saturday = Date::from_ymd(2024, 1, 6).unwrap();
+        let mut calendar = calendar;
+        calendar.add_holiday(saturday);
+        let start = Date::from_ymd(2024, 1, 1).unwrap();
+        let end = Date::from_ymd(2024, 1, 8).unwrap();
+        assert_eq!(
+            calendar.holidays_between(start, end),
+            vec![
+                Date::from_ymd(2024, 1, 2).unwrap(),
+                saturday,
+                Date::from_ymd(2024, 1, 7).unwrap()
+            ]
+        );
+
+        // without weekend days, only the listed holidays are closed
+        let calendar = calendar.with_weekend(&[]);
+        assert_eq!(calendar.business_days_between(start, end), 5);
+        assert_eq!(calendar.holidays_between(start, end).len(), 2);
+
+        let missing_dir = std::env::temp_dir()
+            .join(format!("qlab_test_missing_dir_{}", std::process::id()))
+            .join("calendar.txt");
+        assert!(calendar.save(&missing_dir).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::date_rolling::DateRolling;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+/// A calendar whose business days over a range of years are precomputed into a bitset,
+/// so that [`Calendar::is_business_day`] is a bit lookup and [`Calendar::business_days_between`]
+/// takes constant time instead of evaluating the holiday rules of every day.
+///
+/// Dates outside the cached years are delegated to the wrapped calendar.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::target::Target;
+/// use qlab_time::calendar::cached::CachedCalendar;
+/// use qlab_time::calendar::Calendar;
+/// use qlab_time::date::Date;
+///
+/// let calendar = CachedCalendar::new(Target, 2000, 2060).unwrap();
+/// let christmas = Date::from_ymd(2024, 12, 25).unwrap();
+/// assert!(calendar.is_holiday(christmas));
+///
+/// let start = Date::from_ymd(2024, 1, 1).unwrap();
+/// let end = Date::from_ymd(2054, 1, 1).unwrap();
+/// assert_eq!(
+///     calendar.business_days_between(start, end),
+///     Target.business_days_between(start, end)
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedCalendar<C> {
+    calendar: C,
+    /// The first day of the first cached year.
+    start: Date,
+    /// The first day after the last cached year.
+    end: Date,
+    /// A bit per day from `start`, set on business days.
+    words: Vec<u64>,
+    /// The number of business days from `start` to the first day of each word.
+    counts: Vec<i64>,
+}
+
+impl<C: Calendar> CachedCalendar<C> {
+    /// Precomputes the business days of `calendar` from the start of `first_year` to the end of `last_year`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `last_year` precedes `first_year` or a year is out of the range of dates.
+    pub fn new(calendar: C, first_year: i32, last_year: i32) -> QLabResult<Self> {
+        let (Some(start), Some(end)) = (
+            Date::from_ymd(first_year, 1, 1),
+            last_year
+                .checked_add(1)
+                .and_then(|year| Date::from_ymd(year, 1, 1)),
+        ) else {
+            return Err(InvalidInput(
+                format!("years from {first_year} to {last_year} are out of range").into(),
+            )
+            .into());
+        };
+        if last_year < first_year {
+            return Err(InvalidInput(
+                format!("last_year: {last_year} must not precede first_year: {first_year}").into(),
+            )
+            .into());
+        }
+        let mut words = Vec::new();
+        let mut counts = Vec::new();
+        let mut count = 0;
+        for (i, date) in Date::range(start, end).enumerate() {
+            if i % 64 == 0 {
+                words.push(0);
+                counts.push(count);
+            }
+            if calendar.is_business_day(date) {
+                words[i / 64] |= 1 << (i % 64);
+                count += 1;
+            }
+        }
+        Ok(Self {
+            calendar,
+            start,
+            end,
+            words,
+            counts,
+        })
+    }
+
+    /// Returns the wrapped calendar.
+    #[must_use]
+    pub fn calendar(&self) -> &C {
+        &self.calendar
+    }
+
+    /// Returns the first cached date and the first date after the cached range.
+    #[must_use]
+    pub fn range(&self) -> (Date, Date) {
+        (self.start, self.end)
+    }
+
+    /// Returns the offset of `date` from the start of the cache if the date lies in the cached range.
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)] // the offset is checked to be within the cache
+    fn offset(&self, date: Date) -> Option<usize> {
+        (self.start <= date && date < self.end).then(|| (date - self.start) as usize)
+    }
+
+    /// Returns the offset of the end of the cache if `date` is that end.
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)] // the cache holds the days up to its end
+    fn end_offset(&self, date: Date) -> Option<usize> {
+        (date == self.end).then(|| (self.end - self.start) as usize)
+    }
+
+    /// Returns the number of business days from the start of the cache to `offset`, excluding `offset`,
+    /// which may be the end of the cache.
+    fn count_before(&self, offset: usize) -> i64 {
+        let (word, bit) = (offset / 64, offset % 64);
+        if word == self.words.len() {
+            let last = self.words.len() - 1;
+            return self.counts[last] + i64::from(self.words[last].count_ones());
+        }
+        let mask = (1_u64 << bit) - 1;
+        self.counts[word] + i64::from((self.words[word] & mask).count_ones())
+    }
+}
+
+impl<C: Calendar> Calendar for CachedCalendar<C> {
+    fn is_business_day(&self, date: Date) -> bool {
+        match self.offset(date) {
+            Some(offset) => self.words[offset / 64] & (1 << (offset % 64)) != 0,
+            None => self.calendar.is_business_day(date),
+        }
+    }
+
+    fn is_weekend(&self, date: Date) -> bool {
+        self.calendar.is_weekend(date)
+    }
+
+    fn business_days_between(&self, start: Date, end: Date) -> i64 {
+        if end < start {
+            return -self.business_days_between(end, start);
+        }
+        match (
+            self.offset(start),
+            self.offset(end).or_else(|| self.end_offset(end)),
+        ) {
+            (Some(start), Some(end)) => self.count_before(end) - self.count_before(start),
+            _ => self.calendar.business_days_between(start, end),
+        }
+    }
+
+    /// Adjusts each date by [`Calendar::adjust`], whose lookups are already cached,
+    /// leaving all of `dates` unchanged if one of them fails.
+    fn adjust_many(&self, dates: &mut [Date], rolling: DateRolling) -> Option<()> {
+        let adjusted = dates
+            .iter()
+            .map(|&date| self.adjust(date, rolling))
+            .collect::<Option<Vec<_>>>()?;
+        dates.copy_from_slice(&adjusted);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::target::Target;
+
+    #[test]
+    fn test_cached_calendar() {
+        let cached = CachedCalendar::new(Target, 2020, 2030).unwrap();
+        let (start, end) = cached.range();
+        assert_eq!(start, Date::from_ymd(2020, 1, 1).unwrap());
+        assert_eq!(end, Date::from_ymd(2031, 1, 1).unwrap());
+        let outside = Date::from_ymd(2019, 12, 1).unwrap();
+        let beyond = Date::from_ymd(2031, 2, 1).unwrap();
+        for date in Date::range(outside, beyond) {
+            assert_eq!(cached.is_business_day(date), Target.is_business_day(date));
+        }
+        let dates: Vec<_> = Date::range(outside, beyond)
+            .step_by(37)
+            .chain([end])
+            .collect();
+        for &d1 in &dates {
+            for &d2 in &dates {
+                assert_eq!(
+                    cached.business_days_between(d1, d2),
+                    Target.business_days_between(d1, d2),
+                    "{d1} to {d2}"
+                );
+            }
+        }
+        assert!(CachedCalendar::new(Target, 2030, 2020).is_err());
+        assert!(CachedCalendar::new(Target, 2020, i32::MAX).is_err());
+    }
+}
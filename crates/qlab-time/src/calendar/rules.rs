@@ -0,0 +1,564 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::RwLock;
+
+/// How a holiday falling on a weekend day is observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Observance {
+    /// The holiday is observed on its date only.
+    #[default]
+    Actual,
+    /// A holiday on a weekend day is observed on the nearest weekday, the following one if both are equally near,
+    /// so that with a Saturday-Sunday weekend a Saturday holiday is observed on Friday and a Sunday one on Monday.
+    Nearest,
+    /// A holiday on the last day of the weekend, Sunday for a Saturday-Sunday weekend, is observed on the
+    /// following weekday; a holiday on an earlier weekend day is not moved.
+    SundayToMonday,
+    /// A holiday on a weekend day is observed on the next weekday which is not already a holiday,
+    /// so that e.g. Christmas and Boxing Day on a weekend are observed on Monday and Tuesday.
+    NextFreeWeekday,
+}
+
+/// The date on which a holiday falls in a given year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HolidayDate {
+    /// A fixed month and day, skipped in years in which it does not exist.
+    Fixed { month: u32, day: u32 },
+    /// The `n`th `weekday` of `month`, counted from the end of the month if `n` is negative.
+    NthWeekday { month: u32, weekday: Weekday, n: i8 },
+    /// The given number of days after Easter Sunday, e.g. `-2` for Good Friday.
+    EasterOffset { days: i64 },
+    /// A single date, such as a royal wedding.
+    Once(Date),
+}
+
+/// A named holiday with its date, observance and the years in which it applies.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::calendar::rules::{HolidayRule, Observance};
+///
+/// let independence_day = HolidayRule::fixed("Independence Day", 7, 4)
+///     .unwrap()
+///     .with_observance(Observance::Nearest)
+///     .with_years(Some(1776), None);
+/// assert_eq!(independence_day.name(), "Independence Day");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HolidayRule {
+    name: String,
+    date: HolidayDate,
+    observance: Observance,
+    first_year: Option<i32>,
+    last_year: Option<i32>,
+}
+
+fn check_month(month: u32) -> QLabResult<()> {
+    if (1..=12).contains(&month) {
+        Ok(())
+    } else {
+        Err(InvalidInput(format!("{month} is not a month").into()).into())
+    }
+}
+
+impl HolidayRule {
+    fn new(name: &str, date: HolidayDate) -> Self {
+        Self {
+            name: name.to_string(),
+            date,
+            observance: Observance::Actual,
+            first_year: None,
+            last_year: None,
+        }
+    }
+
+    /// Creates a holiday on `day` of `month` every year.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the month and day exist in no year.
+    pub fn fixed(name: &str, month: u32, day: u32) -> QLabResult<Self> {
+        if NaiveDate::from_ymd_opt(2000, month, day).is_none() {
+            return Err(InvalidInput(format!("{month}-{day} is not a date").into()).into());
+        }
+        Ok(Self::new(name, HolidayDate::Fixed { month, day }))
+    }
+
+    /// Creates a holiday on the `n`th `weekday` of `month`, or the `-n`th counted from the end if `n` is negative.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `month` is invalid or `n` is not within `1..=5` or `-5..=-1`.
+    pub fn nth_weekday(name: &str, month: u32, weekday: Weekday, n: i8) -> QLabResult<Self> {
+        check_month(month)?;
+        if n == 0 || n.unsigned_abs() > 5 {
+            return Err(
+                InvalidInput(format!("{n} is not an occurrence of a weekday").into()).into(),
+            );
+        }
+        Ok(Self::new(
+            name,
+            HolidayDate::NthWeekday { month, weekday, n },
+        ))
+    }
+
+    /// Creates a holiday `days` days after Easter Sunday in the Gregorian calendar.
+    #[must_use]
+    pub fn easter_offset(name: &str, days: i64) -> Self {
+        Self::new(name, HolidayDate::EasterOffset { days })
+    }
+
+    /// Creates a holiday on a single date.
+    #[must_use]
+    pub fn once(name: &str, date: Date) -> Self {
+        Self::new(name, HolidayDate::Once(date))
+    }
+
+    #[must_use]
+    pub fn with_observance(mut self, observance: Observance) -> Self {
+        self.observance = observance;
+        self
+    }
+
+    /// Restricts the rule to the years from `first_year` to `last_year`, both inclusive.
+    #[must_use]
+    pub fn with_years(mut self, first_year: Option<i32>, last_year: Option<i32>) -> Self {
+        self.first_year = first_year;
+        self.last_year = last_year;
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn date(&self) -> HolidayDate {
+        self.date
+    }
+
+    #[must_use]
+    pub fn observance(&self) -> Observance {
+        self.observance
+    }
+
+    fn applies_in(&self, year: i32) -> bool {
+        self.first_year.is_none_or(|first| first <= year)
+            && self.last_year.is_none_or(|last| year <= last)
+    }
+
+    /// Returns the date of the holiday in `year` before the observance shift, if it falls in that year.
+    #[must_use]
+    pub fn unadjusted_date(&self, year: i32) -> Option<Date> {
+        if !self.applies_in(year) {
+            return None;
+        }
+        match self.date {
+            HolidayDate::Fixed { month, day } => Date::from_ymd(year, month, day),
+            HolidayDate::NthWeekday { month, weekday, n } => nth_weekday(year, month, weekday, n),
+            HolidayDate::EasterOffset { days } => {
+                let easter = easter_sunday(year)?;
+                let date = if days < 0 {
+                    easter.checked_sub_days(Days::new(days.unsigned_abs()))?
+                } else {
+                    easter.checked_add_days(Days::new(days.unsigned_abs()))?
+                };
+                Some(Date(date))
+            }
+            HolidayDate::Once(date) => (date.year() == year).then_some(date),
+        }
+    }
+}
+
+pub(crate) fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i8) -> Option<Date> {
+    if n > 0 {
+        return NaiveDate::from_weekday_of_month_opt(year, month, weekday, n.unsigned_abs())
+            .map(Date);
+    }
+    let last = Date::from_ymd(year, month, 1)?.end_of_month().0;
+    let back = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7
+        + 7 * (u32::from(n.unsigned_abs()) - 1);
+    let date = last.checked_sub_days(Days::new(u64::from(back)))?;
+    (date.month() == month).then_some(Date(date))
+}
+
+/// Calculates Easter Sunday in the Gregorian calendar by the anonymous Gregorian algorithm.
+#[allow(clippy::many_single_char_names)] // named as in the algorithm
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year.rem_euclid(19);
+    let b = year.div_euclid(100);
+    let c = year.rem_euclid(100);
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, u32::try_from(month).ok()?, u32::try_from(day).ok()?)
+}
+
+/// A calendar generated from weekend days and holiday rules.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Weekday;
+/// use qlab_time::calendar::rules::{HolidayRule, Observance, RuleCalendar};
+/// use qlab_time::calendar::Calendar;
+/// use qlab_time::date::Date;
+///
+/// let calendar = RuleCalendar::new("example")
+///     .with_rule(HolidayRule::fixed("New Year's Day", 1, 1).unwrap().with_observance(Observance::NextFreeWeekday))
+///     .with_rule(HolidayRule::easter_offset("Good Friday", -2))
+///     .with_rule(HolidayRule::nth_weekday("Spring Holiday", 5, Weekday::Mon, -1).unwrap());
+/// assert!(calendar.is_holiday(Date::from_ymd(2023, 1, 2).unwrap()));
+/// assert!(calendar.is_holiday(Date::from_ymd(2024, 3, 29).unwrap()));
+/// assert!(calendar.is_holiday(Date::from_ymd(2024, 5, 27).unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleCalendar {
+    name: String,
+    weekend: Vec<Weekday>,
+    rules: Vec<HolidayRule>,
+    observed_cache: ObservedCache,
+}
+
+/// The observed holiday dates of the rules of each year, evaluated on first lookup.
+///
+/// A clone starts empty and all caches compare equal, so the cache never affects the identity of a calendar.
+#[derive(Debug, Default)]
+struct ObservedCache(RwLock<HashMap<i32, BTreeSet<Date>>>);
+
+impl Clone for ObservedCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for ObservedCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ObservedCache {}
+
+impl RuleCalendar {
+    /// Creates a calendar with Saturday and Sunday as weekend days and no holiday rules.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            rules: Vec::new(),
+            observed_cache: ObservedCache::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_weekend(mut self, weekend: &[Weekday]) -> Self {
+        self.weekend = weekend.to_vec();
+        self.observed_cache = ObservedCache::default();
+        self
+    }
+
+    /// Adds a rule. Rules observed on the next free weekday are shifted in the order they are added.
+    #[must_use]
+    pub fn with_rule(mut self, rule: HolidayRule) -> Self {
+        self.rules.push(rule);
+        self.observed_cache = ObservedCache::default();
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &[HolidayRule] {
+        &self.rules
+    }
+
+    fn falls_on_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend.contains(&date.weekday())
+    }
+
+    /// Returns the weekday nearest to `date`, the following one if both are equally near.
+    fn nearest_weekday(&self, date: NaiveDate) -> Option<NaiveDate> {
+        (1..7).find_map(|days| {
+            let after = date
+                .checked_add_days(Days::new(days))
+                .filter(|&after| !self.falls_on_weekend(after));
+            let before = date
+                .checked_sub_days(Days::new(days))
+                .filter(|&before| !self.falls_on_weekend(before));
+            after.or(before)
+        })
+    }
+
+    /// Evaluates the rules of `year` into observed holidays keyed by date, with the names of their rules.
+    ///
+    /// An observed date may fall in the adjacent year, e.g. New Year's Day on a Saturday observed on Friday.
+    fn observed(&self, year: i32) -> BTreeMap<Date, &str> {
+        let mut holidays = BTreeMap::new();
+        let mut shifted = Vec::new();
+        for rule in &self.rules {
+            let Some(date) = rule.unadjusted_date(year) else {
+                continue;
+            };
+            let observed = match rule.observance {
+                _ if !self.falls_on_weekend(date.0) => Some(date.0),
+                Observance::Actual => Some(date.0),
+                Observance::Nearest => self.nearest_weekday(date.0),
+                Observance::SundayToMonday => match date.0.succ_opt() {
+                    Some(next) if !self.falls_on_weekend(next) => Some(next),
+                    _ => Some(date.0),
+                },
+                Observance::NextFreeWeekday => {
+                    shifted.push((date.0, rule.name()));
+                    None
+                }
+            };
+            if let Some(observed) = observed {
+                holidays.insert(Date(observed), rule.name());
+            }
+        }
+        for (mut date, name) in shifted {
+            while self.falls_on_weekend(date) || holidays.contains_key(&Date(date)) {
+                let Some(next) = date.succ_opt() else {
+                    break;
+                };
+                date = next;
+            }
+            holidays.insert(Date(date), name);
+        }
+        holidays
+    }
+
+    /// Returns the observed holidays from `first_year` to `last_year`, both inclusive, with the names of their rules,
+    /// in ascending order. Weekend days are not listed unless a holiday is observed on them.
+    #[must_use]
+    pub fn holidays(&self, first_year: i32, last_year: i32) -> Vec<(Date, String)> {
+        (first_year..=last_year)
+            .flat_map(|year| {
+                self.observed(year)
+                    .into_iter()
+                    .map(|(date, name)| (date, name.to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns whether `date` is observed as a holiday by the rules of `year`, evaluating them once per year.
+    fn is_observed_in(&self, year: i32, date: Date) -> bool {
+        if let Some(dates) = self
+            .observed_cache
+            .0
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(&year).map(|dates| dates.contains(&date)))
+        {
+            return dates;
+        }
+        let dates: BTreeSet<Date> = self.observed(year).into_keys().collect();
+        let observed = dates.contains(&date);
+        if let Ok(mut cache) = self.observed_cache.0.write() {
+            cache.insert(year, dates);
+        }
+        observed
+    }
+}
+
+impl Calendar for RuleCalendar {
+    fn is_weekend(&self, date: Date) -> bool {
+        self.falls_on_weekend(date.0)
+    }
+
+    fn is_business_day(&self, date: Date) -> bool {
+        if self.falls_on_weekend(date.0) {
+            return false;
+        }
+        let year = date.year();
+        !(year - 1..=year + 1).any(|year| self.is_observed_in(year, date))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calendar::unitedkingdom::UnitedKingdom;
+
+    #[test]
+    fn test_rule_calendar() {
+        let next_free = Observance::NextFreeWeekday;
+        let london = RuleCalendar::new("london")
+            .with_rule(
+                HolidayRule::fixed("New Year's Day", 1, 1)
+                    .unwrap()
+                    .with_observance(next_free),
+            )
+            .with_rule(HolidayRule::easter_offset("Good Friday", -2))
+            .with_rule(HolidayRule::easter_offset("Easter Monday", 1))
+            .with_rule(HolidayRule::nth_weekday("Early May", 5, Weekday::Mon, 1).unwrap())
+            .with_rule(HolidayRule::nth_weekday("Spring", 5, Weekday::Mon, -1).unwrap())
+            .with_rule(HolidayRule::nth_weekday("Summer", 8, Weekday::Mon, -1).unwrap())
+            .with_rule(
+                HolidayRule::fixed("Christmas Day", 12, 25)
+                    .unwrap()
+                    .with_observance(next_free),
+            )
+            .with_rule(
+                HolidayRule::fixed("Boxing Day", 12, 26)
+                    .unwrap()
+                    .with_observance(next_free),
+            );
+        let mut date = Date::from_ymd(2013, 1, 1).unwrap();
+        while date.year() < 2020 {
+            assert_eq!(
+                london.is_business_day(date),
+                UnitedKingdom::default().is_business_day(date),
+                "{date}"
+            );
+            date = date.succ_opt().unwrap();
+        }
+        let holidays = london.holidays(2021, 2021);
+        assert_eq!(holidays.len(), 8);
+        assert_eq!(
+            holidays[7],
+            (
+                Date::from_ymd(2021, 12, 28).unwrap(),
+                "Boxing Day".to_string()
+            )
+        );
+
+        // New Year's Day on a Saturday observed on the Friday before
+        let new_york = RuleCalendar::new("new york").with_rule(
+            HolidayRule::fixed("New Year's Day", 1, 1)
+                .unwrap()
+                .with_observance(Observance::Nearest)
+                .with_years(Some(2023), None),
+        );
+        assert!(new_york.is_holiday(Date::from_ymd(2027, 12, 31).unwrap()));
+        assert!(new_york.is_business_day(Date::from_ymd(2021, 12, 31).unwrap()));
+        assert_eq!(new_york.clone(), new_york);
+        assert!(HolidayRule::fixed("", 2, 30).is_err());
+        assert!(HolidayRule::nth_weekday("", 5, Weekday::Mon, 6).is_err());
+    }
+
+    #[test]
+    fn test_observance_follows_weekend() {
+        let date = |m, d| Date::from_ymd(2024, m, d).unwrap();
+        // with a Friday-Saturday weekend, 2024-03-01 is a Friday and 2024-03-02 a Saturday
+        let rule = |name, day, observance| {
+            HolidayRule::fixed(name, 3, day)
+                .unwrap()
+                .with_observance(observance)
+        };
+        let nearest = RuleCalendar::new("nearest")
+            .with_weekend(&[Weekday::Fri, Weekday::Sat])
+            .with_rule(rule("Friday", 1, Observance::Nearest))
+            .with_rule(rule("Saturday", 2, Observance::Nearest));
+        assert_eq!(
+            nearest.holidays(2024, 2024),
+            vec![
+                (date(2, 29), "Friday".to_string()),
+                (date(3, 3), "Saturday".to_string())
+            ]
+        );
+        assert!(!nearest.is_business_day(date(2, 29)));
+        assert!(!nearest.is_business_day(date(3, 3)));
+        assert!(nearest.is_business_day(date(3, 4)));
+
+        let sunday_to_monday = RuleCalendar::new("last weekend day")
+            .with_weekend(&[Weekday::Fri, Weekday::Sat])
+            .with_rule(rule("Friday", 1, Observance::SundayToMonday))
+            .with_rule(rule("Saturday", 2, Observance::SundayToMonday));
+        assert_eq!(
+            sunday_to_monday.holidays(2024, 2024),
+            vec![
+                (date(3, 1), "Friday".to_string()),
+                (date(3, 3), "Saturday".to_string())
+            ]
+        );
+
+        // a single weekend day moves a holiday to the following day
+        let sunday_only = RuleCalendar::new("sunday only")
+            .with_weekend(&[Weekday::Sun])
+            .with_rule(rule("Sunday", 3, Observance::Nearest));
+        assert!(sunday_only.is_business_day(date(3, 2)));
+        assert!(!sunday_only.is_business_day(date(3, 4)));
+
+        // lookups hit the cache, which is reset when a rule is added
+        assert!(sunday_only.is_business_day(date(3, 5)));
+        let sunday_only = sunday_only.with_rule(rule("Tuesday", 5, Observance::Actual));
+        assert!(!sunday_only.is_business_day(date(3, 5)));
+        assert!(!sunday_only.is_business_day(date(3, 4)));
+    }
+
+    #[test]
+    fn test_rule_errors_and_edges() {
+        assert!(HolidayRule::fixed("", 13, 1).is_err());
+        assert!(HolidayRule::fixed("", 4, 31).is_err());
+        assert!(HolidayRule::nth_weekday("", 0, Weekday::Mon, 1).is_err());
+        assert!(HolidayRule::nth_weekday("", 13, Weekday::Mon, 1).is_err());
+        assert!(HolidayRule::nth_weekday("", 5, Weekday::Mon, 0).is_err());
+        assert!(HolidayRule::nth_weekday("", 5, Weekday::Mon, -6).is_err());
+
+        // the 29th of February exists in leap years only
+        let leap_day = HolidayRule::fixed("Leap Day", 2, 29).unwrap();
+        assert!(leap_day.unadjusted_date(2023).is_none());
+        assert_eq!(leap_day.unadjusted_date(2024), Date::from_ymd(2024, 2, 29));
+
+        // February 2024 has four Mondays and five Thursdays
+        let fifth_monday = HolidayRule::nth_weekday("", 2, Weekday::Mon, 5).unwrap();
+        assert!(fifth_monday.unadjusted_date(2024).is_none());
+        let fifth_thursday = HolidayRule::nth_weekday("", 2, Weekday::Thu, 5).unwrap();
+        assert_eq!(
+            fifth_thursday.unadjusted_date(2024),
+            Date::from_ymd(2024, 2, 29)
+        );
+        let last_thursday = HolidayRule::nth_weekday("", 2, Weekday::Thu, -5).unwrap();
+        assert_eq!(
+            last_thursday.unadjusted_date(2024),
+            Date::from_ymd(2024, 2, 1)
+        );
+        assert!(last_thursday.unadjusted_date(2023).is_none());
+
+        // the years are inclusive, and a single date applies in its own year only
+        let bounded =
+            HolidayRule::easter_offset("Good Friday", -2).with_years(Some(2020), Some(2021));
+        assert!(bounded.unadjusted_date(2019).is_none());
+        assert_eq!(bounded.unadjusted_date(2021), Date::from_ymd(2021, 4, 2));
+        assert!(bounded.unadjusted_date(2022).is_none());
+        let coronation = Date::from_ymd(2023, 5, 8).unwrap();
+        let once = HolidayRule::once("Coronation", coronation);
+        assert_eq!(once.unadjusted_date(2023), Some(coronation));
+        assert!(once.unadjusted_date(2024).is_none());
+
+        // 2023-12-31 is a Sunday, observed in the next year but listed with the rules of its own
+        let calendar = RuleCalendar::new("year end").with_rule(
+            HolidayRule::fixed("New Year's Eve", 12, 31)
+                .unwrap()
+                .with_observance(Observance::NextFreeWeekday),
+        );
+        assert!(calendar.is_holiday(Date::from_ymd(2024, 1, 1).unwrap()));
+        assert_eq!(
+            calendar.holidays(2023, 2023),
+            vec![(
+                Date::from_ymd(2024, 1, 1).unwrap(),
+                "New Year's Eve".to_string()
+            )]
+        );
+        assert!(calendar.is_business_day(Date::from_ymd(2024, 1, 2).unwrap()));
+        assert!(RuleCalendar::new("empty").holidays(2024, 2023).is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+/// A calendar labelled with a name and a version, to audit updates of its holiday data.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::calendar::bespoke::BespokeCalendar;
+/// use qlab_time::calendar::version::{diff, VersionedCalendar};
+/// use qlab_time::date::Date;
+/// use std::collections::HashSet;
+///
+/// let start = Date::from_ymd(2024, 1, 1).unwrap();
+/// let end = Date::from_ymd(2025, 1, 1).unwrap();
+/// let old = VersionedCalendar::new("desk", "2023.1", WeekendsOnly);
+/// let holiday = Date::from_ymd(2024, 6, 14).unwrap();
+/// let new = VersionedCalendar::new("desk", "2024.1", BespokeCalendar::new("desk", HashSet::from([holiday])));
+///
+/// let changes = diff(&old, &new, start, end).unwrap();
+/// assert_eq!(changes.added(), &[holiday]);
+/// assert_ne!(old.holiday_hash(start, end).unwrap(), new.holiday_hash(start, end).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionedCalendar<C> {
+    name: String,
+    version: String,
+    calendar: C,
+}
+
+impl<C: Calendar> VersionedCalendar<C> {
+    #[must_use]
+    pub fn new(name: &str, version: &str, calendar: C) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            calendar,
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    #[must_use]
+    pub fn calendar(&self) -> &C {
+        &self.calendar
+    }
+
+    /// Hashes the holidays from `start` to `end`, including `start` but not `end`.
+    ///
+    /// The hash is the 64-bit FNV-1a hash of the serial dates of the holidays, so it is stable
+    /// across platforms and releases and identifies the holiday data independently of the labels.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `end` precedes `start`.
+    pub fn holiday_hash(&self, start: Date, end: Date) -> QLabResult<u64> {
+        Ok(holidays(&self.calendar, start, end)?
+            .iter()
+            .flat_map(|holiday| holiday.serial_date().to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            }))
+    }
+}
+
+impl<C: Calendar> Calendar for VersionedCalendar<C> {
+    fn is_business_day(&self, date: Date) -> bool {
+        self.calendar.is_business_day(date)
+    }
+}
+
+/// The holidays which differ between two calendars over a range of dates.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CalendarDiff {
+    added: Vec<Date>,
+    removed: Vec<Date>,
+}
+
+impl CalendarDiff {
+    /// Returns the holidays of the new calendar which are business days of the old one.
+    #[must_use]
+    pub fn added(&self) -> &[Date] {
+        &self.added
+    }
+
+    /// Returns the holidays of the old calendar which are business days of the new one.
+    #[must_use]
+    pub fn removed(&self) -> &[Date] {
+        &self.removed
+    }
+
+    /// Returns `true` if both calendars have the same holidays over the range.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns the dates among `dates`, such as the dates of a schedule, whose status changed.
+    #[must_use]
+    pub fn affected(&self, dates: &[Date]) -> Vec<Date> {
+        dates
+            .iter()
+            .copied()
+            .filter(|date| {
+                self.added.binary_search(date).is_ok() || self.removed.binary_search(date).is_ok()
+            })
+            .collect()
+    }
+}
+
+fn holidays(calendar: &impl Calendar, start: Date, end: Date) -> QLabResult<Vec<Date>> {
+    if end < start {
+        return Err(InvalidInput(format!("the range ends on {end} before {start}").into()).into());
+    }
+    Ok(calendar.holidays_between(start, end))
+}
+
+/// Compares the holidays of `old` and `new` from `start` to `end`, including `start` but not `end`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `end` precedes `start`.
+pub fn diff(
+    old: &impl Calendar,
+    new: &impl Calendar,
+    start: Date,
+    end: Date,
+) -> QLabResult<CalendarDiff> {
+    let old = holidays(old, start, end)?;
+    let new = holidays(new, start, end)?;
+    Ok(CalendarDiff {
+        added: new
+            .iter()
+            .copied()
+            .filter(|date| old.binary_search(date).is_err())
+            .collect(),
+        removed: old
+            .iter()
+            .copied()
+            .filter(|date| new.binary_search(date).is_err())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use chrono::Weekday;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_diff() {
+        let start = Date::from_ymd(2024, 1, 1).unwrap();
+        let end = Date::from_ymd(2025, 1, 1).unwrap();
+        let coronation = Date::from_ymd(2024, 5, 8).unwrap();
+        let jubilee = Date::from_ymd(2024, 6, 3).unwrap();
+        let old = VersionedCalendar::new(
+            "london",
+            "1",
+            BespokeCalendar::new("london", HashSet::from([coronation])),
+        );
+        let new = VersionedCalendar::new(
+            "london",
+            "2",
+            BespokeCalendar::new("london", HashSet::from([jubilee])),
+        );
+        let changes = diff(&old, &new, start, end).unwrap();
+        assert_eq!(changes.added(), &[jubilee]);
+        assert_eq!(changes.removed(), &[coronation]);
+        let schedule = [Date::from_ymd(2024, 3, 8).unwrap(), coronation];
+        assert_eq!(changes.affected(&schedule), vec![coronation]);
+
+        // the hash depends on the holidays only
+        let relabelled = VersionedCalendar::new("uk", "3", old.calendar().clone());
+        assert_eq!(
+            old.holiday_hash(start, end).unwrap(),
+            relabelled.holiday_hash(start, end).unwrap()
+        );
+        assert_ne!(
+            old.holiday_hash(start, end).unwrap(),
+            new.holiday_hash(start, end).unwrap()
+        );
+        assert!(diff(&old, &old, start, end).unwrap().is_empty());
+        assert!(diff(&old, &new, end, start).is_err());
+    }
+
+    #[test]
+    fn test_diff_edges() {
+        let start = Date::from_ymd(2024, 3, 4).unwrap();
+        let end = Date::from_ymd(2024, 3, 11).unwrap();
+        let calendar = VersionedCalendar::new(
+            "desk",
+            "2024.1",
+            BespokeCalendar::new("desk", HashSet::new()),
+        );
+        assert_eq!(calendar.name(), "desk");
+        assert_eq!(calendar.version(), "2024.1");
+        assert!(calendar.holiday_hash(end, start).is_err());
+        // an empty range hashes to the FNV offset basis
+        assert_eq!(
+            calendar.holiday_hash(start, start).unwrap(),
+            0xcbf2_9ce4_8422_2325
+        );
+
+        // a change of weekend shows up as holidays added and removed
+        let gulf = VersionedCalendar::new(
+            "desk",
+            "2024.2",
+            calendar
+                .calendar()
+                .clone()
+                .with_weekend(&[Weekday::Fri, Weekday::Sat]),
+        );
+        let changes = diff(&calendar, &gulf, start, end).unwrap();
+        assert_eq!(changes.added(), &[Date::from_ymd(2024, 3, 8).unwrap()]);
+        assert_eq!(changes.removed(), &[Date::from_ymd(2024, 3, 10).unwrap()]);
+        assert!(changes.affected(&[]).is_empty());
+
+        // the end of the range is excluded
+        let holiday = Date::from_ymd(2024, 3, 11).unwrap();
+        let new = BespokeCalendar::new("desk", HashSet::from([holiday]));
+        assert!(diff(&calendar, &new, start, end).unwrap().is_empty());
+        assert_eq!(
+            diff(&calendar, &new, start, holiday.succ_opt().unwrap())
+                .unwrap()
+                .added(),
+            &[holiday]
+        );
+    }
+}
@@ -3,9 +3,12 @@ use crate::date_rolling::DateRolling;
 use crate::period::years::Years;
 use crate::period::Period;
 use chrono::{Datelike, NaiveDate};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{QLabError, QLabResult};
 use std::fmt;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Formatter, Write};
 use std::ops::Sub;
+use std::str::FromStr;
 
 /// Represents a date.
 #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Copy, Clone, Debug)]
@@ -197,6 +200,84 @@ impl Date {
     pub fn day(self) -> u32 {
         self.0.day()
     }
+    /// Returns `true` if the date is the last day of its month.
+    #[must_use]
+    pub fn is_end_of_month(self) -> bool {
+        Self::days_in_month(self.month(), self.leap_year()) == Some(self.day())
+    }
+    /// Returns the last day of the month of the date.
+    #[must_use]
+    pub fn end_of_month(self) -> Self {
+        Self::days_in_month(self.month(), self.leap_year())
+            .and_then(|day| self.0.with_day(day))
+            .map_or(self, Date)
+    }
+    /// Returns the quarter of the year of the date, from 1 for January to March to 4 for October to December.
+    #[must_use]
+    pub fn quarter(self) -> u32 {
+        (self.month() - 1) / 3 + 1
+    }
+    /// Returns the semester of the year of the date, 1 for January to June and 2 for July to December.
+    #[must_use]
+    pub fn semester(self) -> u32 {
+        (self.month() - 1) / 6 + 1
+    }
+    /// Returns the first day of the quarter of the date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    ///
+    /// let date = Date::from_ymd(2024, 8, 20).unwrap();
+    /// assert_eq!(date.quarter(), 3);
+    /// assert_eq!(date.start_of_quarter(), Date::from_ymd(2024, 7, 1).unwrap());
+    /// assert_eq!(date.end_of_quarter(), Date::from_ymd(2024, 9, 30).unwrap());
+    /// assert_eq!(date.end_of_semester(), Date::from_ymd(2024, 12, 31).unwrap());
+    /// ```
+    #[must_use]
+    pub fn start_of_quarter(self) -> Self {
+        self.first_day_of_month(3 * self.quarter() - 2)
+    }
+    /// Returns the last day of the quarter of the date.
+    #[must_use]
+    pub fn end_of_quarter(self) -> Self {
+        self.first_day_of_month(3 * self.quarter()).end_of_month()
+    }
+    /// Returns the first day of the semester of the date.
+    #[must_use]
+    pub fn start_of_semester(self) -> Self {
+        self.first_day_of_month(6 * self.semester() - 5)
+    }
+    /// Returns the last day of the semester of the date.
+    #[must_use]
+    pub fn end_of_semester(self) -> Self {
+        self.first_day_of_month(6 * self.semester()).end_of_month()
+    }
+    /// Returns the first day of `month` in the year of the date.
+    fn first_day_of_month(self, month: u32) -> Self {
+        Self::from_ymd(self.year(), month, 1).unwrap_or(self)
+    }
+    /// Returns the last business day of `calendar` in the month of the date, e.g. the expiry of a month-end option,
+    /// or `None` if the month has no business day.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_time::date::Date;
+    ///
+    /// // 2024-03-31 is a Sunday
+    /// let date = Date::from_ymd(2024, 3, 12).unwrap();
+    /// assert_eq!(date.end_of_month(), Date::from_ymd(2024, 3, 31).unwrap());
+    /// assert_eq!(date.last_business_day_of_month(&WeekendsOnly), Date::from_ymd(2024, 3, 29));
+    /// ```
+    #[must_use]
+    pub fn last_business_day_of_month<C: Calendar + ?Sized>(self, calendar: &C) -> Option<Self> {
+        calendar
+            .adjust(self.end_of_month(), DateRolling::Preceding)
+            .filter(|date| date.month() == self.month() && date.year() == self.year())
+    }
     /// Retrieves the serial date of the given object.
     ///
     /// The serial date is a representation of the object as the number of days since the Common Era (CE).
@@ -209,6 +290,47 @@ impl Date {
     pub fn serial_date(self) -> i32 {
         self.0.num_days_from_ce()
     }
+    /// Converts an Excel serial number, counting days from 1900-01-01 as 1, into a date.
+    ///
+    /// Excel treats 1900 as a leap year, so serial 60 is the nonexistent 1900-02-29 and serials from 61 on
+    /// are one day ahead of a plain day count. Returns `None` for serials 0 and 60,
+    /// which denote no real date, and for serials out of the range of dates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    ///
+    /// let date = Date::from_excel_serial(45371).unwrap();
+    /// assert_eq!(date, Date::from_ymd(2024, 3, 20).unwrap());
+    /// assert_eq!(date.to_excel_serial(), Some(45371));
+    /// assert_eq!(Date::from_excel_serial(60), None);
+    /// ```
+    #[must_use]
+    pub fn from_excel_serial(serial: u32) -> Option<Self> {
+        let epoch = match serial {
+            0 | 60 => return None,
+            1..=59 => NaiveDate::from_ymd_opt(1899, 12, 31)?,
+            _ => NaiveDate::from_ymd_opt(1899, 12, 30)?,
+        };
+        epoch
+            .checked_add_days(chrono::Days::new(u64::from(serial)))
+            .map(Self)
+    }
+    /// Converts the date into an Excel serial number, see [`Date::from_excel_serial`].
+    ///
+    /// Returns `None` for dates before 1900-01-01, which have no serial number.
+    #[must_use]
+    pub fn to_excel_serial(self) -> Option<u32> {
+        let epoch = if self.0 < NaiveDate::from_ymd_opt(1900, 3, 1)? {
+            NaiveDate::from_ymd_opt(1899, 12, 31)?
+        } else {
+            NaiveDate::from_ymd_opt(1899, 12, 30)?
+        };
+        u32::try_from((self.0 - epoch).num_days())
+            .ok()
+            .filter(|&serial| serial > 0)
+    }
     /// Returns an optional `Self`value.
     ///
     /// This method checks if the current date is a weekend (Saturday or Sunday).
@@ -252,50 +374,109 @@ impl fmt::Display for Date {
     }
 }
 
+impl FromStr for Date {
+    type Err = QLabError;
+
+    /// Parses an ISO 8601 date in the extended format `YYYY-MM-DD` or the basic format `YYYYMMDD`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    ///
+    /// let date: Date = "2024-02-29".parse().unwrap();
+    /// assert_eq!(date, Date::from_ymd(2024, 2, 29).unwrap());
+    /// assert_eq!("20240229".parse::<Date>().unwrap(), date);
+    /// assert!("2023-02-29".parse::<Date>().is_err());
+    /// ```
+    fn from_str(s: &str) -> QLabResult<Self> {
+        let pattern = if s.len() == 8 && s.bytes().all(|byte| byte.is_ascii_digit()) {
+            "%Y%m%d"
+        } else {
+            "%Y-%m-%d"
+        };
+        NaiveDate::parse_from_str(s, pattern)
+            .map(Date)
+            .map_err(|err| InvalidInput(format!("{s}: {err}").into()).into())
+    }
+}
+
+impl Date {
+    /// Formats the date with a `strftime`-like `pattern`, e.g. `%d/%m/%Y` or `%d %b %Y`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the pattern has an invalid specifier or one which
+    /// does not apply to a date, such as an hour.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    ///
+    /// let date = Date::from_ymd(2024, 3, 8).unwrap();
+    /// assert_eq!(date.format("%d/%m/%Y").unwrap(), "08/03/2024");
+    /// assert_eq!(date.format("%d %b %Y").unwrap(), "08 Mar 2024");
+    /// assert!(date.format("%H:%M").is_err());
+    /// ```
+    pub fn format(self, pattern: &str) -> QLabResult<String> {
+        let mut text = String::new();
+        write!(text, "{}", self.0.format(pattern))
+            .map_err(|_| InvalidInput(format!("invalid date pattern: {pattern}").into()))?;
+        Ok(text)
+    }
+}
+
 impl Date {
     fn checked_add(self, period: impl Period) -> Option<Self> {
         period.checked_add(self)
     }
 
-    pub fn checked_roll(
+    /// Rolls the date by `period` like [`Date::checked_roll`], applying the end-of-month rule:
+    /// if the date is the last day of its month and `period` is a whole number of months,
+    /// the unadjusted result is moved to the last day of its month, e.g. 2024-04-30 + 1M = 2024-05-31.
+    /// The modified conventions compare months against the unadjusted result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use calendar::weekendsonly::WeekendsOnly;
+    /// use qlab_time::date::Date;
+    /// use qlab_time::date_rolling::DateRolling;
+    /// use qlab_time::period::months::Months;
+    ///
+    /// let february = Date::from_ymd(2024, 2, 29).unwrap();
+    /// let rolled = february
+    ///     .checked_roll_end_of_month(Months::new(1), &WeekendsOnly, DateRolling::ModifiedFollowing)
+    ///     .unwrap();
+    /// // 2024-03-31 is a Sunday, so modified following rolls back into March
+    /// assert_eq!(rolled, Date::from_ymd(2024, 3, 29).unwrap());
+    /// ```
+    #[must_use]
+    pub fn checked_roll_end_of_month(
         self,
         period: impl Period,
-        calendar: &impl Calendar,
+        calendar: &(impl Calendar + ?Sized),
         rolling: DateRolling,
     ) -> Option<Self> {
-        match rolling {
-            DateRolling::Unadjusted => self.checked_add(period),
-            DateRolling::Following => {
-                let mut ret = self.checked_roll(period, calendar, DateRolling::Unadjusted)?;
-                while !calendar.is_business_day(ret) {
-                    ret = ret.succ_opt()?;
-                }
-                Some(ret)
-            }
-            DateRolling::ModifiedFollowing => {
-                let ret = self.checked_roll(period, calendar, DateRolling::Following)?;
-                if ret.month() == self.month() {
-                    Some(ret)
-                } else {
-                    self.checked_roll(period, calendar, DateRolling::Preceding)
-                }
-            }
-            DateRolling::Preceding => {
-                let mut ret = self.checked_roll(period, calendar, DateRolling::Unadjusted)?;
-                while !calendar.is_business_day(ret) {
-                    ret = ret.pred_opt()?;
-                }
-                Some(ret)
-            }
-            DateRolling::ModifiedPreceding => {
-                let ret = self.checked_roll(period, calendar, DateRolling::Preceding)?;
-                if ret.month() == self.month() {
-                    Some(ret)
-                } else {
-                    self.checked_roll(period, calendar, DateRolling::Following)
-                }
-            }
+        let mut unadjusted = self.checked_add(period)?;
+        if self.is_end_of_month() && period.is_whole_months() {
+            unadjusted = unadjusted.end_of_month();
         }
+        calendar.adjust(unadjusted, rolling)
+    }
+
+    /// Adds `period` to the date and adjusts the result by `rolling` with [`Calendar::adjust`],
+    /// so that the modified conventions compare against the month of the unadjusted result.
+    ///
+    /// Returns `None` if the result runs out of the range of dates.
+    #[must_use]
+    pub fn checked_roll(
+        self,
+        period: impl Period,
+        calendar: &(impl Calendar + ?Sized),
+        rolling: DateRolling,
+    ) -> Option<Self> {
+        calendar.adjust(self.checked_add(period)?, rolling)
     }
 }
 
@@ -305,6 +486,28 @@ mod tests {
     use crate::period::years::Years;
     use chrono::{Datelike, Weekday};
 
+    #[test]
+    fn test_checked_roll_month_end() {
+        use crate::calendar::Calendar;
+        use crate::date_rolling::DateRolling;
+        use crate::period::days::Days;
+        use calendar::weekendsonly::WeekendsOnly;
+
+        // 2024-06-01 is a Saturday in the month after the start date
+        let start = Date::from_ymd(2024, 5, 15).unwrap();
+        let rolled =
+            start.checked_roll(Days::new(17), &WeekendsOnly, DateRolling::ModifiedFollowing);
+        assert_eq!(rolled, Date::from_ymd(2024, 6, 3));
+        assert_eq!(
+            rolled,
+            WeekendsOnly.advance(start, Days::new(17), DateRolling::ModifiedFollowing, false)
+        );
+        assert_eq!(
+            start.checked_roll(Days::new(17), &WeekendsOnly, DateRolling::Preceding),
+            Date::from_ymd(2024, 5, 31)
+        );
+    }
+
     #[test]
     fn test_from_ymd_opt_valid_date() {
         let result = Date::from_ymd(2023, 8, 15);
@@ -422,6 +625,27 @@ mod tests {
         assert_eq!(date.serial_date(), 738_580);
     }
 
+    #[test]
+    fn test_excel_serial() {
+        for (serial, (y, m, d)) in [
+            (1, (1900, 1, 1)),
+            (59, (1900, 2, 28)),
+            (61, (1900, 3, 1)),
+            (36526, (2000, 1, 1)),
+            (45351, (2024, 2, 29)),
+        ] {
+            let date = Date::from_ymd(y, m, d).unwrap();
+            assert_eq!(Date::from_excel_serial(serial), Some(date));
+            assert_eq!(date.to_excel_serial(), Some(serial));
+        }
+        assert_eq!(Date::from_excel_serial(0), None);
+        assert_eq!(Date::from_excel_serial(60), None);
+        assert_eq!(
+            Date::from_ymd(1899, 12, 31).unwrap().to_excel_serial(),
+            None
+        );
+    }
+
     #[test]
     fn test_weekend_roll() {
         let date = Date::from_ymd(2023, 3, 5).unwrap();
@@ -442,4 +666,141 @@ mod tests {
         assert_eq!(3, new_date.month());
         assert_eq!(6, new_date.day());
     }
+
+    #[test]
+    fn test_quarter() {
+        let ymd = |year, month, day| Date::from_ymd(year, month, day).unwrap();
+        for (month, quarter, semester) in [
+            (1, 1, 1),
+            (3, 1, 1),
+            (4, 2, 1),
+            (6, 2, 1),
+            (7, 3, 2),
+            (12, 4, 2),
+        ] {
+            let date = ymd(2024, month, 15);
+            assert_eq!(date.quarter(), quarter);
+            assert_eq!(date.semester(), semester);
+        }
+        let date = ymd(2024, 2, 10);
+        assert_eq!(date.start_of_quarter(), ymd(2024, 1, 1));
+        assert_eq!(date.end_of_quarter(), ymd(2024, 3, 31));
+        assert_eq!(date.start_of_semester(), ymd(2024, 1, 1));
+        assert_eq!(date.end_of_semester(), ymd(2024, 6, 30));
+        let date = ymd(2024, 12, 31);
+        assert_eq!(date.start_of_quarter(), ymd(2024, 10, 1));
+        assert_eq!(date.end_of_quarter(), date);
+        assert_eq!(date.start_of_semester(), ymd(2024, 7, 1));
+    }
+
+    #[test]
+    fn test_last_business_day_of_month() {
+        // Good Friday 2024 is 29 March, a TARGET holiday before a weekend
+        let date = Date::from_ymd(2024, 3, 1).unwrap();
+        assert_eq!(
+            date.last_business_day_of_month(&calendar::target::Target),
+            Date::from_ymd(2024, 3, 28)
+        );
+        let june = Date::from_ymd(2024, 6, 30).unwrap();
+        assert_eq!(
+            june.last_business_day_of_month(&calendar::weekendsonly::WeekendsOnly),
+            Date::from_ymd(2024, 6, 28)
+        );
+    }
+
+    #[test]
+    fn test_checked_roll_end_of_month() {
+        use crate::date_rolling::DateRolling;
+        use crate::period::days::Days;
+        use crate::period::months::Months;
+        use crate::period::Period;
+        use calendar::weekendsonly::WeekendsOnly;
+
+        fn roll(date: Date, period: impl Period) -> Date {
+            date.checked_roll_end_of_month(period, &WeekendsOnly, DateRolling::Unadjusted)
+                .unwrap()
+        }
+
+        let date = Date::from_ymd(2024, 1, 31).unwrap();
+        assert!(date.is_end_of_month());
+        assert!(!Date::from_ymd(2024, 2, 28).unwrap().is_end_of_month());
+        let february = roll(date, Months::new(1));
+        assert_eq!(february, Date::from_ymd(2024, 2, 29).unwrap());
+        assert_eq!(
+            roll(february, Months::new(1)),
+            Date::from_ymd(2024, 3, 31).unwrap()
+        );
+        assert_eq!(
+            roll(Date::from_ymd(2023, 2, 28).unwrap(), Years::new(1)),
+            Date::from_ymd(2024, 2, 29).unwrap()
+        );
+        // day periods and dates within a month are unaffected
+        assert_eq!(
+            roll(date, Days::new(29)),
+            Date::from_ymd(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            roll(Date::from_ymd(2024, 2, 28).unwrap(), Months::new(1)),
+            Date::from_ymd(2024, 3, 28).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_nearest_and_half_month_rolling() {
+        use crate::date_rolling::DateRolling;
+        use crate::period::days::Days;
+        use calendar::weekendsonly::WeekendsOnly;
+
+        let roll = |date: Date, days: u64, rolling| {
+            date.checked_roll(Days::new(days), &WeekendsOnly, rolling)
+                .unwrap()
+        };
+        let saturday = Date::from_ymd(2024, 6, 15).unwrap();
+        let friday = Date::from_ymd(2024, 6, 14).unwrap();
+        let monday = Date::from_ymd(2024, 6, 17).unwrap();
+        assert_eq!(roll(saturday, 0, DateRolling::Nearest), friday);
+        assert_eq!(roll(friday, 2, DateRolling::Nearest), monday);
+        assert_eq!(roll(friday, 0, DateRolling::Nearest), friday);
+        // the following business day would cross the middle of the month
+        assert_eq!(
+            roll(saturday, 0, DateRolling::HalfMonthModifiedFollowing),
+            friday
+        );
+        assert_eq!(
+            roll(friday, 2, DateRolling::HalfMonthModifiedFollowing),
+            monday
+        );
+        // or the end of the month
+        assert_eq!(
+            roll(
+                Date::from_ymd(2024, 6, 29).unwrap(),
+                0,
+                DateRolling::HalfMonthModifiedFollowing
+            ),
+            Date::from_ymd(2024, 6, 28).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let date: Date = "2024-12-31".parse().unwrap();
+        assert_eq!(date, Date::from_ymd(2024, 12, 31).unwrap());
+        assert_eq!("20241231".parse::<Date>().unwrap(), date);
+        assert_eq!(date.to_string().parse::<Date>().unwrap(), date);
+        assert_eq!(date.format("%Y%m%d").unwrap(), "20241231");
+        assert_eq!(
+            date.format("%A, %-d %B %Y").unwrap(),
+            "Tuesday, 31 December 2024"
+        );
+        for invalid in [
+            "2024-12-32",
+            "2024/12/31",
+            "20241331",
+            "2024-12-31T00:00",
+            "",
+        ] {
+            assert!(invalid.parse::<Date>().is_err(), "{invalid}");
+        }
+        assert!(date.format("%Q").is_err());
+    }
 }
@@ -0,0 +1,232 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::period::days::Days;
+use crate::period::Period;
+
+/// An iterator over dates from a start, including it, to an end, excluding it, see [`Date::range`].
+///
+/// The k-th date is the start plus k times the step, so stepping by months from the end of a long month
+/// clamps each date on its own, e.g. 2024-01-31, 2024-02-29, 2024-03-31.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateRange<P = Days> {
+    start: Date,
+    steps: u32,
+    previous: Option<Date>,
+    end: Date,
+    step: P,
+}
+
+impl DateRange {
+    pub(crate) fn new(start: Date, end: Date) -> Self {
+        Self {
+            start,
+            steps: 0,
+            previous: None,
+            end,
+            step: Days::new(1),
+        }
+    }
+}
+
+impl<P: Period> DateRange<P> {
+    /// Steps by `period` instead of the current step, anchored on the next date of the range.
+    /// The range ends if a step does not move the date forward.
+    #[must_use]
+    pub fn step_by_period<Q: Period>(self, period: Q) -> DateRange<Q> {
+        DateRange {
+            start: self.peek().unwrap_or(self.end),
+            steps: 0,
+            previous: self.previous,
+            end: self.end,
+            step: period,
+        }
+    }
+
+    /// Keeps the dates which are business days of `calendar`.
+    pub fn business_days<C: Calendar + ?Sized>(self, calendar: &C) -> BusinessDayRange<'_, C, P> {
+        BusinessDayRange {
+            range: self,
+            calendar,
+        }
+    }
+
+    fn peek(&self) -> Option<Date> {
+        let date = if self.steps == 0 {
+            Some(self.start)
+        } else {
+            self.step.checked_add_times(self.start, self.steps)
+        }?;
+        (date < self.end && self.previous.is_none_or(|previous| previous < date)).then_some(date)
+    }
+}
+
+impl<P: Period> Iterator for DateRange<P> {
+    type Item = Date;
+
+    fn next(&mut self) -> Option<Date> {
+        let date = self.peek()?;
+        self.steps = self.steps.checked_add(1)?;
+        self.previous = Some(date);
+        Some(date)
+    }
+}
+
+/// An iterator over the dates of a [`DateRange`] which are business days of a calendar,
+/// see [`DateRange::business_days`].
+#[derive(Debug, Clone)]
+pub struct BusinessDayRange<'a, C: ?Sized, P = Days> {
+    range: DateRange<P>,
+    calendar: &'a C,
+}
+
+impl<'a, C: Calendar + ?Sized, P: Period> BusinessDayRange<'a, C, P> {
+    /// Steps the underlying range by `period`, keeping the stepped dates which are business days.
+    #[must_use]
+    pub fn step_by_period<Q: Period>(self, period: Q) -> BusinessDayRange<'a, C, Q> {
+        BusinessDayRange {
+            range: self.range.step_by_period(period),
+            calendar: self.calendar,
+        }
+    }
+}
+
+impl<C: Calendar + ?Sized, P: Period> Iterator for BusinessDayRange<'_, C, P> {
+    type Item = Date;
+
+    fn next(&mut self) -> Option<Date> {
+        let calendar = self.calendar;
+        self.range.find(|&date| calendar.is_business_day(date))
+    }
+}
+
+impl Date {
+    /// Returns an iterator over the dates from `start` to `end`, including `start` but not `end`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    /// use qlab_time::period::months::Months;
+    ///
+    /// let start = Date::from_ymd(2024, 1, 15).unwrap();
+    /// let end = Date::from_ymd(2024, 2, 15).unwrap();
+    /// assert_eq!(Date::range(start, end).count(), 31);
+    ///
+    /// let end = Date::from_ymd(2025, 1, 15).unwrap();
+    /// let quarters: Vec<_> = Date::range(start, end).step_by_period(Months::new(3)).collect();
+    /// assert_eq!(quarters.len(), 4);
+    /// assert_eq!(quarters[3], Date::from_ymd(2024, 10, 15).unwrap());
+    /// ```
+    #[must_use]
+    pub fn range(start: Date, end: Date) -> DateRange {
+        DateRange::new(start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use crate::period::months::Months;
+    use crate::period::years::Years;
+    use calendar::weekendsonly::WeekendsOnly;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_date_range() {
+        let start = Date::from_ymd(2024, 1, 31).unwrap();
+        let end = Date::from_ymd(2024, 5, 1).unwrap();
+        let months: Vec<_> = Date::range(start, end)
+            .step_by_period(Months::new(1))
+            .collect();
+        assert_eq!(
+            months,
+            vec![
+                start,
+                Date::from_ymd(2024, 2, 29).unwrap(),
+                Date::from_ymd(2024, 3, 31).unwrap(),
+                Date::from_ymd(2024, 4, 30).unwrap(),
+            ]
+        );
+        // changing the step anchors the new steps on the next date
+        let mut days = Date::range(start, end);
+        assert_eq!(days.nth(1), Some(Date::from_ymd(2024, 2, 1).unwrap()));
+        let months: Vec<_> = days.step_by_period(Months::new(1)).collect();
+        assert_eq!(months.len(), 3);
+        assert_eq!(months[0], Date::from_ymd(2024, 2, 2).unwrap());
+        assert_eq!(months[2], Date::from_ymd(2024, 4, 2).unwrap());
+        // a range running out of dates ends instead of overflowing
+        let last = Date::from_ymd(262_142, 12, 1).unwrap();
+        assert_eq!(
+            Date::range(last, Date::from_ymd(262_142, 12, 31).unwrap())
+                .step_by_period(Months::new(1))
+                .count(),
+            1
+        );
+        assert_eq!(Date::range(end, start).count(), 0);
+        assert_eq!(Date::range(start, start).count(), 0);
+        // a zero step yields the start only
+        assert_eq!(
+            Date::range(start, end)
+                .step_by_period(Days::new(0))
+                .collect::<Vec<_>>(),
+            vec![start]
+        );
+
+        // every date stepped from 2024-03-24 is a Sunday
+        let weekly: Vec<_> = Date::range(
+            Date::from_ymd(2024, 3, 24).unwrap(),
+            Date::from_ymd(2024, 4, 15).unwrap(),
+        )
+        .business_days(&WeekendsOnly)
+        .step_by_period(Days::new(7))
+        .collect();
+        assert!(weekly.is_empty());
+        let fridays = Date::range(
+            Date::from_ymd(2024, 3, 29).unwrap(),
+            Date::from_ymd(2024, 4, 20).unwrap(),
+        )
+        .business_days(&WeekendsOnly)
+        .step_by_period(Days::new(7));
+        assert_eq!(fridays.count(), 4);
+    }
+
+    #[test]
+    fn test_range_edges() {
+        let start = Date::from_ymd(2024, 4, 1).unwrap();
+        let end = Date::from_ymd(2024, 4, 4).unwrap();
+
+        // an exhausted range stays exhausted, also after changing the step
+        let mut days = Date::range(start, end);
+        assert_eq!(days.by_ref().count(), 3);
+        assert_eq!(days.next(), None);
+        assert_eq!(days.step_by_period(Days::new(1)).next(), None);
+
+        // a negative step does not move forward, so only the anchor is yielded
+        let backwards: Vec<_> = Date::range(start, end)
+            .step_by_period(Years::new(-1))
+            .collect();
+        assert_eq!(backwards, vec![start]);
+
+        // a step longer than the range yields the start only
+        assert_eq!(
+            Date::range(start, end)
+                .step_by_period(Months::new(1))
+                .collect::<Vec<_>>(),
+            vec![start]
+        );
+
+        // holidays are skipped as well as weekends
+        let holiday = Date::from_ymd(2024, 4, 2).unwrap();
+        let calendar = BespokeCalendar::new("exchange", HashSet::from([holiday]));
+        let business_days: Vec<_> = Date::range(start, Date::from_ymd(2024, 4, 9).unwrap())
+            .business_days(&calendar)
+            .collect();
+        assert_eq!(business_days.len(), 5);
+        assert!(!business_days.contains(&holiday));
+        assert_eq!(
+            Date::range(holiday, end).business_days(&calendar).next(),
+            Some(Date::from_ymd(2024, 4, 3).unwrap())
+        );
+    }
+}
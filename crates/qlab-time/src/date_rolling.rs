@@ -1,8 +1,14 @@
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DateRolling {
     Unadjusted,
     Following,
     ModifiedFollowing,
     Preceding,
     ModifiedPreceding,
+    /// Rolls to the nearest business day, or to the following one if both are equally far.
+    Nearest,
+    /// Rolls to the following business day unless it crosses the 15th or the end of the month,
+    /// in which case it rolls to the preceding business day.
+    HalfMonthModifiedFollowing,
 }
@@ -0,0 +1,288 @@
+use crate::date::Date;
+use chrono::{FixedOffset, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{QLabError, QLabResult};
+use qlab_math::value::Value;
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Formatter;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+const SECONDS_PER_DAY: u32 = 86_400;
+
+/// A date and a time of day, optionally with a fixed offset from UTC.
+///
+/// Without an offset the time is local to an unspecified place, e.g. the close of an exchange.
+/// Times with offsets are comparable with each other as instants, and times without offsets with
+/// each other as wall-clock times; [`DateTime::seconds_until`] does not mix both.
+///
+/// Equality, hashing and ordering follow the same rule: times with offsets are equal if they denote the same
+/// instant whatever their offsets, and any time without an offset orders before any time with one.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::date::Date;
+/// use qlab_time::date_time::DateTime;
+///
+/// let tokyo: DateTime = "2024-03-20T15:00:00+09:00".parse().unwrap();
+/// let london = DateTime::new(Date::from_ymd(2024, 3, 20).unwrap(), 6, 0, 0)
+///     .unwrap()
+///     .with_utc_offset(0)
+///     .unwrap();
+/// assert_eq!(tokyo.seconds_until(london).unwrap(), 0);
+/// assert_eq!(tokyo.to_string(), "2024-03-20T15:00:00+09:00");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DateTime {
+    local: NaiveDateTime,
+    offset: Option<FixedOffset>,
+}
+
+impl DateTime {
+    /// Creates a time without an offset, or `None` if the time of day is invalid.
+    #[must_use]
+    pub fn new(date: Date, hour: u32, minute: u32, second: u32) -> Option<Self> {
+        NaiveTime::from_hms_opt(hour, minute, second).map(|time| Self {
+            local: date.0.and_time(time),
+            offset: None,
+        })
+    }
+
+    /// Creates the start of `date` without an offset.
+    #[must_use]
+    pub fn from_date(date: Date) -> Self {
+        Self {
+            local: date.0.and_time(NaiveTime::MIN),
+            offset: None,
+        }
+    }
+
+    /// Attaches an offset from UTC in seconds, e.g. `32_400` for Tokyo, keeping the local date and time.
+    ///
+    /// Returns `None` if the offset is a day or more.
+    #[must_use]
+    pub fn with_utc_offset(mut self, seconds: i32) -> Option<Self> {
+        self.offset = Some(FixedOffset::east_opt(seconds)?);
+        Some(self)
+    }
+
+    /// Returns the local date.
+    #[must_use]
+    pub fn date(&self) -> Date {
+        Date(self.local.date())
+    }
+
+    #[must_use]
+    pub fn hour(&self) -> u32 {
+        self.local.hour()
+    }
+
+    #[must_use]
+    pub fn minute(&self) -> u32 {
+        self.local.minute()
+    }
+
+    #[must_use]
+    pub fn second(&self) -> u32 {
+        self.local.second()
+    }
+
+    /// Returns the offset from UTC in seconds, if any.
+    #[must_use]
+    pub fn utc_offset(&self) -> Option<i32> {
+        self.offset.map(|offset| offset.local_minus_utc())
+    }
+
+    /// Returns the same instant in UTC, or the time itself if it has no offset.
+    ///
+    /// Returns `None` if the result is out of the range of dates.
+    #[must_use]
+    pub fn to_utc(&self) -> Option<Self> {
+        let Some(offset) = self.offset else {
+            return Some(*self);
+        };
+        Some(Self {
+            local: self
+                .local
+                .checked_sub_signed(TimeDelta::seconds(offset.local_minus_utc().into()))?,
+            offset: FixedOffset::east_opt(0),
+        })
+    }
+
+    /// Returns the elapsed part of the day as a fraction of 24 hours, from 0 up to but excluding 1.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn day_fraction<V: Value>(&self) -> V {
+        V::from_u32(self.local.num_seconds_from_midnight()).unwrap()
+            / V::from_u32(SECONDS_PER_DAY).unwrap()
+    }
+
+    /// Returns the number of seconds from `self` to `end`, negative if `end` is earlier.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if only one of the times has an offset.
+    pub fn seconds_until(&self, end: Self) -> QLabResult<i64> {
+        let (start, end) = Self::comparable(*self, end)?;
+        Ok((end.local - start.local).num_seconds())
+    }
+
+    /// Returns whether the time has an offset and its seconds since the epoch, in UTC if it has an offset.
+    fn instant(&self) -> (bool, i64) {
+        let seconds = self.local.and_utc().timestamp();
+        match self.offset {
+            Some(offset) => (true, seconds - i64::from(offset.local_minus_utc())),
+            None => (false, seconds),
+        }
+    }
+
+    /// Converts both times to UTC if both have offsets.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if only one of the times has an offset.
+    pub(crate) fn comparable(start: Self, end: Self) -> QLabResult<(Self, Self)> {
+        if start.offset.is_some() != end.offset.is_some() {
+            return Err(InvalidInput(
+                format!("{start} and {end} must both have or both lack UTC offsets").into(),
+            )
+            .into());
+        }
+        let out_of_range =
+            |time: Self| InvalidInput(format!("{time} is out of range in UTC").into());
+        Ok((
+            start.to_utc().ok_or_else(|| out_of_range(start))?,
+            end.to_utc().ok_or_else(|| out_of_range(end))?,
+        ))
+    }
+}
+
+impl PartialEq for DateTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.instant() == other.instant()
+    }
+}
+
+impl Eq for DateTime {}
+
+impl Hash for DateTime {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.instant().hash(state);
+    }
+}
+
+impl PartialOrd for DateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.instant().cmp(&other.instant())
+    }
+}
+
+impl From<Date> for DateTime {
+    fn from(date: Date) -> Self {
+        Self::from_date(date)
+    }
+}
+
+impl fmt::Display for DateTime {
+    /// Formats the time in ISO 8601, e.g. `2024-03-20T15:00:00` or `2024-03-20T15:00:00+09:00`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.local.format("%Y-%m-%dT%H:%M:%S"))?;
+        if let Some(offset) = self.offset {
+            write!(f, "{offset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = QLabError;
+
+    /// Parses an ISO 8601 date and time such as `2024-03-20T15:00:00`, optionally followed by fractional seconds,
+    /// which are dropped, and by an offset such as `+09:00` or `Z`.
+    fn from_str(s: &str) -> QLabResult<Self> {
+        let error =
+            |err: chrono::ParseError| QLabError::from(InvalidInput(format!("{s}: {err}").into()));
+        if let Ok(local) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+            return Ok(Self {
+                local: local.with_nanosecond(0).unwrap_or(local),
+                offset: None,
+            });
+        }
+        let parsed = chrono::DateTime::parse_from_rfc3339(s).map_err(error)?;
+        let local = parsed.naive_local();
+        Ok(Self {
+            local: local.with_nanosecond(0).unwrap_or(local),
+            offset: Some(*parsed.offset()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_count::act_365::Act365;
+    use crate::day_count::DayCount;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_date_time() {
+        let date = Date::from_ymd(2024, 3, 20).unwrap();
+        let close = DateTime::new(date, 16, 30, 0).unwrap();
+        assert_eq!((close.hour(), close.minute(), close.second()), (16, 30, 0));
+        assert_eq!(close.date(), date);
+        assert!((close.day_fraction::<f64>() - 16.5 / 24.0).abs() < 1e-15);
+        assert_eq!(DateTime::new(date, 24, 0, 0), None);
+        assert_eq!(close.with_utc_offset(86_400), None);
+
+        let parsed: DateTime = "2024-03-20T16:30:00.250".parse().unwrap();
+        assert_eq!(parsed, close);
+        let utc: DateTime = "2024-03-20T20:30:00Z".parse().unwrap();
+        let new_york = close.with_utc_offset(-4 * 3600).unwrap();
+        assert_eq!(new_york.to_utc(), Some(utc));
+        assert_eq!(utc.to_string(), "2024-03-20T20:30:00+00:00");
+        assert_eq!(new_york.seconds_until(utc).unwrap(), 0);
+        assert!(close.seconds_until(utc).is_err());
+        assert!("2024-03-20 16:30".parse::<DateTime>().is_err());
+
+        // an hour and a half before expiry
+        let expiry = DateTime::new(date.succ_opt().unwrap(), 0, 0, 0).unwrap();
+        let t: f64 =
+            Act365::calculate_time_fraction(DateTime::new(date, 22, 30, 0).unwrap(), expiry)
+                .unwrap();
+        assert!((t - 1.5 / 24.0 / 365.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_instant_equality_and_ordering() {
+        let tokyo: DateTime = "2024-03-20T15:00:00+09:00".parse().unwrap();
+        let london: DateTime = "2024-03-20T06:00:00Z".parse().unwrap();
+        let new_york: DateTime = "2024-03-20T02:00:00-04:00".parse().unwrap();
+        assert_eq!(tokyo, london);
+        assert_eq!(london, new_york);
+        let instants: HashSet<_> = [tokyo, london, new_york].into_iter().collect();
+        assert_eq!(instants.len(), 1);
+
+        // a later wall-clock time in Tokyo is an earlier instant than in London
+        let london_later: DateTime = "2024-03-20T07:00:00+00:00".parse().unwrap();
+        let tokyo_earlier: DateTime = "2024-03-20T15:30:00+09:00".parse().unwrap();
+        assert!(tokyo_earlier < london_later);
+        assert!(tokyo < tokyo_earlier);
+        let mut times = [london_later, tokyo_earlier, new_york];
+        times.sort();
+        assert_eq!(times, [new_york, tokyo_earlier, london_later]);
+
+        // wall-clock times compare among themselves and order before any instant
+        let local = DateTime::new(Date::from_ymd(2024, 3, 20).unwrap(), 15, 0, 0).unwrap();
+        assert_ne!(local, tokyo);
+        assert!(local < london);
+        assert!(local < DateTime::new(Date::from_ymd(2024, 3, 20).unwrap(), 15, 0, 1).unwrap());
+    }
+}
@@ -1,8 +1,15 @@
 pub mod act_360;
 pub mod act_365;
+pub mod act_365_25;
+pub mod bus_252;
+pub mod kind;
+pub mod nl_365;
 pub mod thirty_360;
+pub mod thirty_360_us;
 
 use crate::date::Date;
+use crate::date_time::DateTime;
+use qlab_error::ComputeError::InvalidInput;
 use qlab_error::QLabResult;
 use qlab_math::value::Value;
 
@@ -27,4 +34,29 @@ pub trait DayCount: Copy {
     /// # Errors
     /// An error occurs if a cast from `V` to a primitive type fails.
     fn calculate_day_count_fraction<V: Value>(date1: Date, date2: Date) -> QLabResult<V>;
+
+    /// Calculates the day count fraction between two times, counting the partial days at both ends.
+    ///
+    /// The fraction between the dates is increased by the elapsed part of the last day and decreased by
+    /// the elapsed part of the first day, each weighted by the fraction of its whole day,
+    /// so that under ACT/365 it is the elapsed time over 365 days.
+    /// Times with UTC offsets are compared in UTC.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if only one of the times has a UTC offset,
+    /// or an error if a day count fraction fails.
+    fn calculate_time_fraction<V: Value>(start: DateTime, end: DateTime) -> QLabResult<V> {
+        let (start, end) = DateTime::comparable(start, end)?;
+        let whole_day = |date: Date| {
+            let next = date
+                .succ_opt()
+                .ok_or_else(|| InvalidInput(format!("no date follows {date}").into()))?;
+            Self::calculate_day_count_fraction::<V>(date, next)
+        };
+        Ok(
+            Self::calculate_day_count_fraction::<V>(start.date(), end.date())?
+                + end.day_fraction::<V>() * whole_day(end.date())?
+                - start.day_fraction::<V>() * whole_day(start.date())?,
+        )
+    }
 }
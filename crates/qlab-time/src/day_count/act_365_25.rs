@@ -0,0 +1,41 @@
+use crate::date::Date;
+use crate::day_count::DayCount;
+use qlab_error::{ComputeError, QLabResult};
+use qlab_math::value::Value;
+
+/// The actual number of days over a year of 365.25 days, the average length of a Julian year.
+#[derive(Debug, Copy, Clone)]
+pub struct Act36525;
+
+impl DayCount for Act36525 {
+    fn calculate_day_count_fraction<V: Value>(date1: Date, date2: Date) -> QLabResult<V> {
+        let date_diff = V::from_i64(date2 - date1)
+            .ok_or_else(|| ComputeError::CastNumberError(format!("{}", date2 - date1).into()))?;
+        let denomination = V::from_f64(365.25).unwrap();
+
+        Ok(date_diff.div(denomination))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::day_count::DayCount;
+
+    #[test]
+    fn test_calculate_day_count_fraction() {
+        // four years including one leap day are exactly four years
+        let date1 = Date::from_ymd(2022, 3, 1).unwrap();
+        let date2 = Date::from_ymd(2026, 3, 1).unwrap();
+        let diff: f64 = Act36525::calculate_day_count_fraction(date1, date2).unwrap();
+        assert!((diff - 4.0).abs() < f64::EPSILON);
+        let reversed: f64 = Act36525::calculate_day_count_fraction(date2, date1).unwrap();
+        assert!((reversed + 4.0).abs() < f64::EPSILON);
+
+        let date1 = Date::from_ymd(2024, 2, 28).unwrap();
+        let date2 = Date::from_ymd(2024, 3, 1).unwrap();
+        let diff: f64 = Act36525::calculate_day_count_fraction(date1, date2).unwrap();
+        assert!((diff - 2.0 / 365.25).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::day_count::DayCount;
+use qlab_error::{ComputeError, QLabResult};
+use qlab_math::value::Value;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The Business/252 day count, which counts the business days of the calendar `C` and divides them by 252.
+///
+/// The calendar is part of the type so that the convention fits wherever a day count is a type parameter,
+/// e.g. `YieldCurve<Bus252<Brazil>, _>`, and is instantiated with `C::default()`.
+/// Calendars that cannot be built by default are supported through [`Bus252::calculate_with_calendar`].
+///
+/// # Examples
+///
+/// ```
+/// use calendar::brazil::Brazil;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::bus_252::Bus252;
+/// use qlab_time::day_count::DayCount;
+///
+/// // 2024-04-19 is a Friday and 2024-04-22 a Monday, the day after Tiradentes Day
+/// let date1 = Date::from_ymd(2024, 4, 19).unwrap();
+/// let date2 = Date::from_ymd(2024, 4, 23).unwrap();
+/// let fraction: f64 = Bus252::<Brazil>::calculate_day_count_fraction(date1, date2).unwrap();
+/// assert!((fraction - 2.0 / 252.0).abs() < f64::EPSILON);
+/// ```
+pub struct Bus252<C> {
+    _calendar: PhantomData<fn() -> C>,
+}
+
+impl<C> Bus252<C> {
+    /// Calculates the day count fraction between two dates with the business days of `calendar`,
+    /// counting `date1` but not `date2`. The fraction is negative if `date2` precedes `date1`.
+    ///
+    /// # Errors
+    /// An error occurs if a cast from `V` to a primitive type fails.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    pub fn calculate_with_calendar<V: Value>(
+        calendar: &(impl Calendar + ?Sized),
+        date1: Date,
+        date2: Date,
+    ) -> QLabResult<V> {
+        let business_days = calendar.business_days_between(date1, date2);
+        let business_days = V::from_i64(business_days)
+            .ok_or_else(|| ComputeError::CastNumberError(format!("{business_days}").into()))?;
+        Ok(business_days / V::from_i32(252).unwrap())
+    }
+}
+
+impl<C> Clone for Bus252<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Bus252<C> {}
+
+impl<C> fmt::Debug for Bus252<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Bus252")
+    }
+}
+
+impl<C> Default for Bus252<C> {
+    fn default() -> Self {
+        Self {
+            _calendar: PhantomData,
+        }
+    }
+}
+
+impl<C: Calendar + Default> DayCount for Bus252<C> {
+    fn calculate_day_count_fraction<V: Value>(date1: Date, date2: Date) -> QLabResult<V> {
+        Self::calculate_with_calendar(&C::default(), date1, date2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use calendar::brazil::Brazil;
+    use calendar::weekendsonly::WeekendsOnly;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_calculate_day_count_fraction() {
+        // 2024 has 262 weekdays
+        let date1 = Date::from_ymd(2024, 1, 1).unwrap();
+        let date2 = Date::from_ymd(2025, 1, 1).unwrap();
+        let weekdays: f64 =
+            Bus252::<WeekendsOnly>::calculate_day_count_fraction(date1, date2).unwrap();
+        assert!((weekdays - 262.0 / 252.0).abs() < f64::EPSILON);
+        let reversed: f64 =
+            Bus252::<WeekendsOnly>::calculate_day_count_fraction(date2, date1).unwrap();
+        assert!((reversed + weekdays).abs() < f64::EPSILON);
+        // Brazilian holidays shorten the year
+        let business_days: f64 =
+            Bus252::<Brazil>::calculate_day_count_fraction(date1, date2).unwrap();
+        assert!(business_days < weekdays);
+    }
+
+    #[test]
+    fn test_endpoints_and_holidays() {
+        let friday = Date::from_ymd(2024, 4, 19).unwrap();
+        let saturday = Date::from_ymd(2024, 4, 20).unwrap();
+        let monday = Date::from_ymd(2024, 4, 22).unwrap();
+        let fraction = |date1, date2| -> f64 {
+            Bus252::<WeekendsOnly>::calculate_day_count_fraction(date1, date2).unwrap()
+        };
+        assert!(fraction(friday, friday).abs() < f64::EPSILON);
+        // the start is counted only if it is a business day, the end never
+        assert!((fraction(friday, saturday) - 1.0 / 252.0).abs() < f64::EPSILON);
+        assert!(fraction(saturday, monday).abs() < f64::EPSILON);
+        assert!(
+            (fraction(saturday, monday.succ_opt().unwrap()) - 1.0 / 252.0).abs() < f64::EPSILON
+        );
+
+        // a calendar without a default is passed explicitly
+        let calendar = BespokeCalendar::new("desk", HashSet::from([monday]));
+        let fraction: f64 = Bus252::<BespokeCalendar>::calculate_with_calendar(
+            &calendar,
+            friday,
+            Date::from_ymd(2024, 4, 24).unwrap(),
+        )
+        .unwrap();
+        assert!((fraction - 2.0 / 252.0).abs() < f64::EPSILON);
+        assert_eq!(format!("{:?}", Bus252::<WeekendsOnly>::default()), "Bus252");
+    }
+}
@@ -0,0 +1,254 @@
+use crate::date::Date;
+use crate::date_time::DateTime;
+use crate::day_count::act_360::Act360;
+use crate::day_count::act_365::Act365;
+use crate::day_count::act_365_25::Act36525;
+use crate::day_count::bus_252::Bus252;
+use crate::day_count::nl_365::Nl365;
+use crate::day_count::thirty_360::Thirty360;
+use crate::day_count::thirty_360_us::Thirty360Us;
+use crate::day_count::DayCount;
+use crate::registry::DayCountFn;
+use calendar::brazil::Brazil;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{QLabError, QLabResult};
+use qlab_math::value::Value;
+use std::any::TypeId;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// A day count convention selected at runtime, e.g. from the name in a configuration file.
+///
+/// The fractions are those of the [`DayCount`] types, with `Bus252` counting the business days of [`Brazil`].
+/// Code generic over a day count type is reached from a kind through [`DayCountKind::visit`].
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::kind::DayCountKind;
+///
+/// let kind: DayCountKind = "act/360".parse().unwrap();
+/// assert_eq!(kind, DayCountKind::Act360);
+/// assert_eq!(kind.to_string(), "ACT/360");
+/// let start = Date::from_ymd(2024, 1, 1).unwrap();
+/// let end = Date::from_ymd(2024, 7, 1).unwrap();
+/// assert!((kind.fraction::<f64>(start, end).unwrap() - 182.0 / 360.0).abs() < 1e-15);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DayCountKind {
+    Act360,
+    Act365,
+    Act36525,
+    Nl365,
+    /// The European 30E/360.
+    Thirty360,
+    /// The US 30/360 bond basis, which the bare name "30/360" refers to.
+    Thirty360Us,
+    Bus252,
+}
+
+/// Code generic over a day count type, run on the type of a [`DayCountKind`] by [`DayCountKind::visit`].
+pub trait DayCountVisitor {
+    type Output;
+
+    fn visit<D: DayCount + 'static>(self) -> Self::Output;
+}
+
+impl DayCountKind {
+    /// All the conventions, in declaration order.
+    pub const ALL: [Self; 7] = [
+        Self::Act360,
+        Self::Act365,
+        Self::Act36525,
+        Self::Nl365,
+        Self::Thirty360,
+        Self::Thirty360Us,
+        Self::Bus252,
+    ];
+
+    /// Returns the name the convention is displayed with.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        self.aliases()[0]
+    }
+
+    /// Returns the names the convention is parsed from, its display name first.
+    #[must_use]
+    pub fn aliases(self) -> &'static [&'static str] {
+        match self {
+            Self::Act360 => &["ACT/360", "A360", "ACTUAL/360"],
+            Self::Act365 => &["ACT/365", "ACT/365F", "A365", "A365F", "ACTUAL/365 FIXED"],
+            Self::Act36525 => &["ACT/365.25", "A365.25", "ACTUAL/365.25"],
+            Self::Nl365 => &["NL/365", "ACT/365NL", "NO LEAP/365"],
+            Self::Thirty360 => &["30E/360", "EUROBOND"],
+            Self::Thirty360Us => &["30/360", "30U/360", "30/360 US", "BOND BASIS"],
+            Self::Bus252 => &["BUS/252", "BU/252", "BUSINESS/252"],
+        }
+    }
+
+    /// Returns the convention whose day count type is `D`, or `None` if `D` is not the type of a convention,
+    /// e.g. BUS/252 on a calendar other than [`Brazil`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::day_count::act_360::Act360;
+    /// use qlab_time::day_count::kind::DayCountKind;
+    ///
+    /// assert_eq!(DayCountKind::of::<Act360>(), Some(DayCountKind::Act360));
+    /// ```
+    #[must_use]
+    pub fn of<D: DayCount + 'static>() -> Option<Self> {
+        struct IsType<D>(PhantomData<D>);
+
+        impl<D: 'static> DayCountVisitor for IsType<D> {
+            type Output = bool;
+
+            fn visit<T: DayCount + 'static>(self) -> bool {
+                TypeId::of::<T>() == TypeId::of::<D>()
+            }
+        }
+
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.visit(IsType::<D>(PhantomData)))
+    }
+
+    /// Runs `visitor` on the day count type of the convention.
+    pub fn visit<T: DayCountVisitor>(self, visitor: T) -> T::Output {
+        match self {
+            Self::Act360 => visitor.visit::<Act360>(),
+            Self::Act365 => visitor.visit::<Act365>(),
+            Self::Act36525 => visitor.visit::<Act36525>(),
+            Self::Nl365 => visitor.visit::<Nl365>(),
+            Self::Thirty360 => visitor.visit::<Thirty360>(),
+            Self::Thirty360Us => visitor.visit::<Thirty360Us>(),
+            Self::Bus252 => visitor.visit::<Bus252<Brazil>>(),
+        }
+    }
+
+    /// Returns the day count fraction calculation of the convention.
+    #[must_use]
+    pub fn day_count_fn<V: Value>(self) -> DayCountFn<V> {
+        match self {
+            Self::Act360 => Act360::calculate_day_count_fraction::<V>,
+            Self::Act365 => Act365::calculate_day_count_fraction::<V>,
+            Self::Act36525 => Act36525::calculate_day_count_fraction::<V>,
+            Self::Nl365 => Nl365::calculate_day_count_fraction::<V>,
+            Self::Thirty360 => Thirty360::calculate_day_count_fraction::<V>,
+            Self::Thirty360Us => Thirty360Us::calculate_day_count_fraction::<V>,
+            Self::Bus252 => Bus252::<Brazil>::calculate_day_count_fraction::<V>,
+        }
+    }
+
+    /// Calculates the day count fraction between two dates, see [`DayCount::calculate_day_count_fraction`].
+    ///
+    /// # Errors
+    /// Returns an error if the calculation of the convention fails.
+    pub fn fraction<V: Value>(self, date1: Date, date2: Date) -> QLabResult<V> {
+        self.day_count_fn()(date1, date2)
+    }
+
+    /// Calculates the day count fraction between two times, see [`DayCount::calculate_time_fraction`].
+    ///
+    /// # Errors
+    /// Returns an error if the calculation of the convention fails.
+    pub fn time_fraction<V: Value>(self, start: DateTime, end: DateTime) -> QLabResult<V> {
+        match self {
+            Self::Act360 => Act360::calculate_time_fraction(start, end),
+            Self::Act365 => Act365::calculate_time_fraction(start, end),
+            Self::Act36525 => Act36525::calculate_time_fraction(start, end),
+            Self::Nl365 => Nl365::calculate_time_fraction(start, end),
+            Self::Thirty360 => Thirty360::calculate_time_fraction(start, end),
+            Self::Thirty360Us => Thirty360Us::calculate_time_fraction(start, end),
+            Self::Bus252 => Bus252::<Brazil>::calculate_time_fraction(start, end),
+        }
+    }
+}
+
+impl FromStr for DayCountKind {
+    type Err = QLabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_uppercase();
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.aliases().contains(&name.as_str()))
+            .ok_or_else(|| InvalidInput(format!("unknown day count convention: {s}").into()).into())
+    }
+}
+
+impl fmt::Display for DayCountKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fraction(Date, Date);
+
+    impl DayCountVisitor for Fraction {
+        type Output = QLabResult<f64>;
+
+        fn visit<D: DayCount + 'static>(self) -> Self::Output {
+            D::calculate_day_count_fraction(self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn test_day_count_kind() {
+        let start = Date::from_ymd(2023, 1, 1).unwrap();
+        let end = Date::from_ymd(2023, 12, 31).unwrap();
+        for kind in DayCountKind::ALL {
+            assert_eq!(kind.name().parse::<DayCountKind>().unwrap(), kind);
+            let fraction = kind.fraction::<f64>(start, end).unwrap();
+            let visited = kind.visit(Fraction(start, end)).unwrap();
+            assert!((fraction - visited).abs() < f64::EPSILON);
+        }
+        assert_eq!(
+            DayCountKind::of::<crate::day_count::thirty_360_us::Thirty360Us>(),
+            Some(DayCountKind::Thirty360Us)
+        );
+        assert_eq!(
+            DayCountKind::of::<crate::day_count::bus_252::Bus252<Brazil>>(),
+            Some(DayCountKind::Bus252)
+        );
+        assert_eq!(
+            DayCountKind::of::<crate::day_count::bus_252::Bus252<calendar::japan::Japan>>(),
+            None
+        );
+        assert_eq!(
+            " eurobond ".parse::<DayCountKind>().unwrap(),
+            DayCountKind::Thirty360
+        );
+        assert!("ACT/ACT".parse::<DayCountKind>().is_err());
+        let thirty_360: f64 = DayCountKind::Thirty360.fraction(start, end).unwrap();
+        assert!((thirty_360 - 359.0 / 360.0).abs() < f64::EPSILON);
+        // the bare market name is the US bond basis, which keeps a 31st ending a period from the 15th
+        assert_eq!(
+            "30/360".parse::<DayCountKind>().unwrap(),
+            DayCountKind::Thirty360Us
+        );
+        assert_eq!(DayCountKind::Thirty360.to_string(), "30E/360");
+        let mid_month = Date::from_ymd(2023, 1, 15).unwrap();
+        let month_end = Date::from_ymd(2023, 3, 31).unwrap();
+        let us: f64 = DayCountKind::Thirty360Us
+            .fraction(mid_month, month_end)
+            .unwrap();
+        let european: f64 = DayCountKind::Thirty360
+            .fraction(mid_month, month_end)
+            .unwrap();
+        assert!((us - 76.0 / 360.0).abs() < f64::EPSILON);
+        assert!((european - 75.0 / 360.0).abs() < f64::EPSILON);
+
+        let open = DateTime::new(start, 12, 0, 0).unwrap();
+        let close = DateTime::new(end, 0, 0, 0).unwrap();
+        let elapsed: f64 = DayCountKind::Act365.time_fraction(open, close).unwrap();
+        assert!((elapsed - 363.5 / 365.0).abs() < 1e-12);
+    }
+}
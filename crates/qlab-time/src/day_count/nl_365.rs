@@ -0,0 +1,65 @@
+use crate::date::Date;
+use crate::day_count::DayCount;
+use qlab_error::{ComputeError, QLabResult};
+use qlab_math::value::Value;
+
+/// The actual number of days, not counting 29 February, over a year of 365 days,
+/// so that every calendar year has a fraction of one.
+#[derive(Debug, Copy, Clone)]
+pub struct Nl365;
+
+impl Nl365 {
+    /// Counts the days from `date1` to `date2` without 29 February, negative if `date2` precedes `date1`.
+    #[must_use]
+    pub fn days(date1: Date, date2: Date) -> i64 {
+        if date2 < date1 {
+            return -Self::days(date2, date1);
+        }
+        let leap_days = (date1.year()..=date2.year())
+            .filter_map(|year| Date::from_ymd(year, 2, 29))
+            .filter(|&leap_day| date1 < leap_day && leap_day <= date2)
+            .count();
+        (date2 - date1) - i64::try_from(leap_days).unwrap_or(i64::MAX)
+    }
+}
+
+impl DayCount for Nl365 {
+    fn calculate_day_count_fraction<V: Value>(date1: Date, date2: Date) -> QLabResult<V> {
+        let days = Self::days(date1, date2);
+        let date_diff = V::from_i64(days)
+            .ok_or_else(|| ComputeError::CastNumberError(format!("{days}").into()))?;
+        let denomination = V::from_i32(365).unwrap();
+
+        Ok(date_diff.div(denomination))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::Date;
+    use crate::day_count::DayCount;
+
+    #[test]
+    fn test_calculate_day_count_fraction() {
+        // a leap year is a whole year
+        let date1 = Date::from_ymd(2024, 1, 1).unwrap();
+        let date2 = Date::from_ymd(2025, 1, 1).unwrap();
+        let diff: f64 = Nl365::calculate_day_count_fraction(date1, date2).unwrap();
+        assert!((diff - 1.0).abs() < f64::EPSILON);
+
+        // 29 February is skipped, but counts as the start of a period
+        let date1 = Date::from_ymd(2024, 2, 28).unwrap();
+        let date2 = Date::from_ymd(2024, 3, 1).unwrap();
+        assert_eq!(Nl365::days(date1, date2), 1);
+        assert_eq!(Nl365::days(date2, date1), -1);
+        let leap_day = Date::from_ymd(2024, 2, 29).unwrap();
+        assert_eq!(Nl365::days(leap_day, date2), 1);
+        assert_eq!(Nl365::days(date1, leap_day), 0);
+
+        // two leap days from December 2023 to March 2028
+        let date1 = Date::from_ymd(2023, 12, 1).unwrap();
+        let date2 = Date::from_ymd(2028, 3, 1).unwrap();
+        assert_eq!(Nl365::days(date1, date2), (date2 - date1) - 2);
+    }
+}
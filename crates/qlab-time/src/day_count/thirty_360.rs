@@ -4,23 +4,27 @@ use qlab_error::ComputeError::InvalidInput;
 use qlab_error::{ComputeError, QLabResult};
 use qlab_math::value::Value;
 
+/// The European 30E/360 day count (Eurobond basis), which counts every 31st as the 30th.
+///
+/// The US bond basis is [`Thirty360Us`](crate::day_count::thirty_360_us::Thirty360Us).
 #[derive(Debug, Copy, Clone)]
 pub struct Thirty360;
 
 impl Thirty360 {
-    #[allow(clippy::cast_sign_loss)] // validation deals with it
     fn date_diff(date1: Date, date2: Date) -> QLabResult<u32> {
         if date1 > date2 {
             return Err(
                 InvalidInput(format!("date1: {date1} must precede date2: {date2}").into()).into(),
             );
         }
-        let d1 = date1.day().min(30);
-        let d2 = date2.day().min(30);
-        Ok(
-            360 * (date2.year() - date1.year()) as u32 + 30 * (date2.month() - date1.month()) + d2
-                - d1,
-        )
+        let d1 = i64::from(date1.day().min(30));
+        let d2 = i64::from(date2.day().min(30));
+        let days = 360 * i64::from(date2.year() - date1.year())
+            + 30 * (i64::from(date2.month()) - i64::from(date1.month()))
+            + d2
+            - d1;
+        // validation ensures the difference is not negative
+        u32::try_from(days).map_err(|_| InvalidInput(format!("{days}").into()).into())
     }
 }
 
@@ -47,5 +51,11 @@ mod tests {
         let date2 = Date::from_ymd(2023, 12, 31).unwrap();
         let diff: f64 = Thirty360::calculate_day_count_fraction(date1, date2).unwrap();
         assert!((diff - 0.997_222).abs() < 0.001);
+
+        // the month of the later date precedes that of the earlier one
+        let date1 = Date::from_ymd(2024, 7, 31).unwrap();
+        let date2 = Date::from_ymd(2025, 1, 31).unwrap();
+        let diff: f64 = Thirty360::calculate_day_count_fraction(date1, date2).unwrap();
+        assert!((diff - 0.5).abs() < f64::EPSILON);
     }
 }
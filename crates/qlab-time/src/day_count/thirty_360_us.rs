@@ -0,0 +1,88 @@
+use crate::date::Date;
+use crate::day_count::DayCount;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{ComputeError, QLabResult};
+use qlab_math::value::Value;
+
+/// The US 30/360 day count (bond basis), the market convention behind the name "30/360".
+///
+/// A 31st is counted as the 30th, except that the 31st of the later date only is when the earlier date
+/// falls on the 30th or 31st; the last day of February counts as the 30th when it starts the period,
+/// and also when it ends a period starting on the last day of February.
+/// [`Thirty360`](crate::day_count::thirty_360::Thirty360) is the European 30E/360,
+/// which counts every 31st as the 30th.
+#[derive(Debug, Copy, Clone)]
+pub struct Thirty360Us;
+
+impl Thirty360Us {
+    fn date_diff(date1: Date, date2: Date) -> QLabResult<u32> {
+        if date1 > date2 {
+            return Err(
+                InvalidInput(format!("date1: {date1} must precede date2: {date2}").into()).into(),
+            );
+        }
+        let is_end_of_february = |date: Date| date.month() == 2 && date.is_end_of_month();
+        let mut d1 = date1.day();
+        let mut d2 = date2.day();
+        if is_end_of_february(date1) {
+            if is_end_of_february(date2) {
+                d2 = 30;
+            }
+            d1 = 30;
+        }
+        if d2 == 31 && d1 >= 30 {
+            d2 = 30;
+        }
+        d1 = d1.min(30);
+        let days = 360 * i64::from(date2.year() - date1.year())
+            + 30 * (i64::from(date2.month()) - i64::from(date1.month()))
+            + i64::from(d2)
+            - i64::from(d1);
+        // validation ensures the difference is not negative
+        u32::try_from(days).map_err(|_| InvalidInput(format!("{days}").into()).into())
+    }
+}
+
+impl DayCount for Thirty360Us {
+    fn calculate_day_count_fraction<V: Value>(date1: Date, date2: Date) -> QLabResult<V> {
+        let date_diff = Self::date_diff(date1, date2)?;
+        let date_diff = V::from_u32(date_diff)
+            .ok_or_else(|| ComputeError::CastNumberError(format!("{date_diff}").into()))?;
+        let denomination =
+            V::from_i32(360).ok_or_else(|| ComputeError::CastNumberError("360".into()))?;
+        Ok(date_diff.div(denomination))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_count::thirty_360::Thirty360;
+
+    #[test]
+    fn test_calculate_day_count_fraction() {
+        let days = |(y1, m1, d1), (y2, m2, d2)| {
+            let fraction: f64 = Thirty360Us::calculate_day_count_fraction(
+                Date::from_ymd(y1, m1, d1).unwrap(),
+                Date::from_ymd(y2, m2, d2).unwrap(),
+            )
+            .unwrap();
+            (fraction * 360.0).round()
+        };
+        // the 31st ending a period stays unless the period starts on the 30th or 31st
+        assert!((days((2023, 1, 15), (2023, 3, 31)) - 76.0).abs() < f64::EPSILON);
+        assert!((days((2023, 1, 30), (2023, 3, 31)) - 60.0).abs() < f64::EPSILON);
+        assert!((days((2023, 1, 31), (2023, 3, 31)) - 60.0).abs() < f64::EPSILON);
+        // the end of February
+        assert!((days((2023, 2, 28), (2023, 8, 31)) - 180.0).abs() < f64::EPSILON);
+        assert!((days((2024, 2, 29), (2025, 2, 28)) - 360.0).abs() < f64::EPSILON);
+        assert!((days((2024, 2, 28), (2024, 3, 31)) - 33.0).abs() < f64::EPSILON);
+        assert!((days((2024, 7, 31), (2025, 1, 31)) - 180.0).abs() < f64::EPSILON);
+
+        let start = Date::from_ymd(2023, 1, 15).unwrap();
+        let end = Date::from_ymd(2023, 3, 31).unwrap();
+        let european: f64 = Thirty360::calculate_day_count_fraction(start, end).unwrap();
+        assert!((european * 360.0 - 75.0).abs() < 1e-12);
+        assert!(Thirty360Us::calculate_day_count_fraction::<f64>(end, start).is_err());
+    }
+}
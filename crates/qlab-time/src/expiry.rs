@@ -0,0 +1,388 @@
+use crate::calendar::rules::nth_weekday;
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::date_rolling::DateRolling;
+use chrono::Weekday;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+/// How the expiry of a listed contract is derived from its contract month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExpiryRule {
+    /// The `n`th `weekday` of the month, counted from the end of the month if `n` is negative,
+    /// e.g. the third Friday of equity index options.
+    NthWeekday { weekday: Weekday, n: i8 },
+    /// `offset` business days before `day` of the month, or before the last business day preceding it
+    /// if `day` is not a business day, e.g. three business days before the 25th for crude oil futures.
+    BusinessDaysBeforeDay { day: u32, offset: u32 },
+    /// `offset` business days before the last business day of the month.
+    BusinessDaysBeforeMonthEnd { offset: u32 },
+}
+
+/// Generates the expiries of a listed option or future from the rule of its exchange.
+///
+/// The rule applies to the month `month_offset` months from the contract month, e.g. `-1` for contracts
+/// which expire in the month before delivery, and its date is adjusted to a business day by `rolling`.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::expiry::ExpiryCalendar;
+///
+/// let options = ExpiryCalendar::third_friday(WeekendsOnly);
+/// assert_eq!(options.expiry(2024, 6).unwrap(), Date::from_ymd(2024, 6, 21).unwrap());
+///
+/// let today = Date::from_ymd(2024, 6, 21).unwrap();
+/// assert_eq!(options.next_expiry(today).unwrap(), Date::from_ymd(2024, 7, 19).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryCalendar<C> {
+    rule: ExpiryRule,
+    month_offset: i32,
+    rolling: DateRolling,
+    contract_months: Vec<u32>,
+    calendar: C,
+}
+
+impl<C: Calendar> ExpiryCalendar<C> {
+    /// Creates expiries for every calendar month by `rule` in the contract month, rolled back to the
+    /// preceding business day as exchanges do when the expiry is a holiday.
+    #[must_use]
+    pub fn new(rule: ExpiryRule, calendar: C) -> Self {
+        Self {
+            rule,
+            month_offset: 0,
+            rolling: DateRolling::Preceding,
+            contract_months: (1..=12).collect(),
+            calendar,
+        }
+    }
+
+    /// Listed equity and equity index options, expiring on the third Friday of the contract month.
+    #[must_use]
+    pub fn third_friday(calendar: C) -> Self {
+        Self::new(
+            ExpiryRule::NthWeekday {
+                weekday: Weekday::Fri,
+                n: 3,
+            },
+            calendar,
+        )
+    }
+
+    /// IMM futures, expiring on the third Wednesday of March, June, September and December.
+    #[must_use]
+    pub fn imm(calendar: C) -> Self {
+        Self::new(
+            ExpiryRule::NthWeekday {
+                weekday: Weekday::Wed,
+                n: 3,
+            },
+            calendar,
+        )
+        .with_rolling(DateRolling::Following)
+        .with_quarterly_months()
+    }
+
+    /// Crude oil futures, whose trading terminates three business days before the 25th of the month
+    /// preceding the contract month.
+    #[must_use]
+    pub fn crude_oil(calendar: C) -> Self {
+        Self::new(
+            ExpiryRule::BusinessDaysBeforeDay { day: 25, offset: 3 },
+            calendar,
+        )
+        .with_month_offset(-1)
+    }
+
+    /// Treasury note and bond futures, whose trading terminates on the seventh business day
+    /// before the last business day of the contract month, for the quarterly contract months.
+    #[must_use]
+    pub fn treasury_futures(calendar: C) -> Self {
+        Self::new(
+            ExpiryRule::BusinessDaysBeforeMonthEnd { offset: 7 },
+            calendar,
+        )
+        .with_quarterly_months()
+    }
+
+    /// Applies the rule `month_offset` months from the contract month.
+    #[must_use]
+    pub fn with_month_offset(mut self, month_offset: i32) -> Self {
+        self.month_offset = month_offset;
+        self
+    }
+
+    /// Replaces the adjustment of an expiry falling on a holiday.
+    #[must_use]
+    pub fn with_rolling(mut self, rolling: DateRolling) -> Self {
+        self.rolling = rolling;
+        self
+    }
+
+    /// Restricts the listed contract months, given as numbers from 1 to 12.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a month is not within `1..=12` or no month is given.
+    pub fn with_contract_months(mut self, months: &[u32]) -> QLabResult<Self> {
+        if let Some(month) = months.iter().find(|month| !(1..=12).contains(*month)) {
+            return Err(InvalidInput(format!("{month} is not a contract month").into()).into());
+        }
+        if months.is_empty() {
+            return Err(InvalidInput("no contract month is listed".into()).into());
+        }
+        let mut months = months.to_vec();
+        months.sort_unstable();
+        months.dedup();
+        self.contract_months = months;
+        Ok(self)
+    }
+
+    /// Restricts the listed contract months to March, June, September and December.
+    fn with_quarterly_months(mut self) -> Self {
+        self.contract_months = vec![3, 6, 9, 12];
+        self
+    }
+
+    #[must_use]
+    pub fn rule(&self) -> ExpiryRule {
+        self.rule
+    }
+
+    #[must_use]
+    pub fn calendar(&self) -> &C {
+        &self.calendar
+    }
+
+    #[must_use]
+    pub fn contract_months(&self) -> &[u32] {
+        &self.contract_months
+    }
+
+    /// Calculates the expiry of the contract of `month` in `year`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the month is not a listed contract month,
+    /// or if the rule gives no date in that month.
+    pub fn expiry(&self, year: i32, month: u32) -> QLabResult<Date> {
+        if !self.contract_months.contains(&month) {
+            return Err(InvalidInput(
+                format!("{year}-{month:02} is not a listed contract month").into(),
+            )
+            .into());
+        }
+        let (rule_year, rule_month) = add_months(year, month, self.month_offset);
+        self.rule_date(rule_year, rule_month)
+            .and_then(|date| self.calendar.adjust(date, self.rolling))
+            .ok_or_else(|| {
+                InvalidInput(
+                    format!("{:?} gives no expiry for {year}-{month:02}", self.rule).into(),
+                )
+                .into()
+            })
+    }
+
+    fn rule_date(&self, year: i32, month: u32) -> Option<Date> {
+        match self.rule {
+            ExpiryRule::NthWeekday { weekday, n } => nth_weekday(year, month, weekday, n),
+            ExpiryRule::BusinessDaysBeforeDay { day, offset } => {
+                let reference = self
+                    .calendar
+                    .adjust(Date::from_ymd(year, month, day)?, DateRolling::Preceding)?;
+                self.business_days_before(reference, offset)
+            }
+            ExpiryRule::BusinessDaysBeforeMonthEnd { offset } => {
+                let last_business_day = self.calendar.adjust(
+                    Date::from_ymd(year, month, 1)?.end_of_month(),
+                    DateRolling::Preceding,
+                )?;
+                self.business_days_before(last_business_day, offset)
+            }
+        }
+    }
+
+    fn business_days_before(&self, mut date: Date, offset: u32) -> Option<Date> {
+        for _ in 0..offset {
+            date = self
+                .calendar
+                .adjust(date.pred_opt()?, DateRolling::Preceding)?;
+        }
+        Some(date)
+    }
+
+    /// Lists the expiries from `start` to `end`, both included, in ascending order.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `end` precedes `start`, or if an expiry cannot be calculated.
+    pub fn expiries_between(&self, start: Date, end: Date) -> QLabResult<Vec<Date>> {
+        if end < start {
+            return Err(
+                InvalidInput(format!("the range ends on {end} before {start}").into()).into(),
+            );
+        }
+        // an expiry moved by the month offset or the rolling may fall outside its contract month
+        let margin = self.month_offset.abs() + 1;
+        let (mut year, mut month) = add_months(start.year(), start.month(), -margin);
+        let (last_year, last_month) = add_months(end.year(), end.month(), margin);
+        let mut expiries = Vec::new();
+        while (year, month) <= (last_year, last_month) {
+            if self.contract_months.contains(&month) {
+                let expiry = self.expiry(year, month)?;
+                if start <= expiry && expiry <= end {
+                    expiries.push(expiry);
+                }
+            }
+            (year, month) = add_months(year, month, 1);
+        }
+        expiries.sort_unstable();
+        Ok(expiries)
+    }
+
+    /// Returns the first expiry after `date`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if an expiry cannot be calculated.
+    pub fn next_expiry(&self, date: Date) -> QLabResult<Date> {
+        let margin = self.month_offset.abs() + 1;
+        let (mut year, mut month) = add_months(date.year(), date.month(), -margin);
+        let mut next: Option<Date> = None;
+        // contract months are listed every year, so the next expiry lies within a year and the margin
+        for _ in 0..12 + 2 * margin {
+            if self.contract_months.contains(&month) {
+                let expiry = self.expiry(year, month)?;
+                if expiry > date && next.is_none_or(|next| expiry < next) {
+                    next = Some(expiry);
+                }
+            }
+            (year, month) = add_months(year, month, 1);
+        }
+        next.ok_or_else(|| InvalidInput(format!("no expiry follows {date}").into()).into())
+    }
+}
+
+fn add_months(year: i32, month: u32, months: i32) -> (i32, u32) {
+    let index = year * 12 + i32::try_from(month).unwrap_or(1) - 1 + months;
+    (
+        index.div_euclid(12),
+        index.rem_euclid(12).unsigned_abs() + 1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use calendar::weekendsonly::WeekendsOnly;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_listed_expiries() {
+        // Good Friday
+        let holiday = Date::from_ymd(2025, 4, 18).unwrap();
+        let calendar = BespokeCalendar::new("exchange", HashSet::from([holiday]));
+
+        let options = ExpiryCalendar::third_friday(calendar.clone());
+        assert_eq!(
+            options.expiry(2025, 4).unwrap(),
+            Date::from_ymd(2025, 4, 17).unwrap()
+        );
+        let start = Date::from_ymd(2025, 1, 1).unwrap();
+        let end = Date::from_ymd(2025, 12, 31).unwrap();
+        assert_eq!(options.expiries_between(start, end).unwrap().len(), 12);
+
+        let imm = ExpiryCalendar::imm(calendar.clone());
+        assert_eq!(
+            imm.next_expiry(Date::from_ymd(2025, 3, 19).unwrap())
+                .unwrap(),
+            Date::from_ymd(2025, 6, 18).unwrap()
+        );
+        assert!(imm.expiry(2025, 4).is_err());
+
+        // the 25th of April 2025 is a Friday, the 25th of May a Sunday
+        let crude = ExpiryCalendar::crude_oil(calendar.clone());
+        assert_eq!(
+            crude.expiry(2025, 5).unwrap(),
+            Date::from_ymd(2025, 4, 22).unwrap()
+        );
+        assert_eq!(
+            crude.expiry(2025, 6).unwrap(),
+            Date::from_ymd(2025, 5, 20).unwrap()
+        );
+        assert_eq!(
+            crude
+                .expiries_between(start, Date::from_ymd(2025, 4, 30).unwrap())
+                .unwrap()
+                .len(),
+            4
+        );
+
+        let treasury = ExpiryCalendar::treasury_futures(calendar.clone());
+        assert_eq!(
+            treasury.expiry(2025, 6).unwrap(),
+            Date::from_ymd(2025, 6, 19).unwrap()
+        );
+
+        let serial = ExpiryCalendar::third_friday(calendar)
+            .with_contract_months(&[12, 1, 12])
+            .unwrap();
+        assert_eq!(serial.contract_months(), &[1, 12]);
+        assert_eq!(
+            serial.next_expiry(start).unwrap(),
+            Date::from_ymd(2025, 1, 17).unwrap()
+        );
+        assert!(serial.clone().with_contract_months(&[0]).is_err());
+        assert!(serial.clone().with_contract_months(&[6, 13]).is_err());
+        assert!(serial.with_contract_months(&[]).is_err());
+    }
+
+    #[test]
+    fn test_expiry_errors_and_edges() {
+        let ymd = |year, month, day| Date::from_ymd(year, month, day).unwrap();
+        let options = ExpiryCalendar::third_friday(WeekendsOnly);
+        assert!(options.expiry(2025, 0).is_err());
+        assert!(options.expiry(2025, 13).is_err());
+        assert!(options
+            .expiries_between(ymd(2025, 2, 1), ymd(2025, 1, 1))
+            .is_err());
+        // an expiry on `start` or `end` is included, and the next expiry strictly follows a date
+        assert_eq!(
+            options
+                .expiries_between(ymd(2025, 1, 17), ymd(2025, 2, 21))
+                .unwrap(),
+            vec![ymd(2025, 1, 17), ymd(2025, 2, 21)]
+        );
+        assert_eq!(
+            options.next_expiry(ymd(2025, 1, 17)).unwrap(),
+            ymd(2025, 2, 21)
+        );
+
+        // February 2025 has no fifth Monday
+        let fifth_monday = ExpiryCalendar::new(
+            ExpiryRule::NthWeekday {
+                weekday: Weekday::Mon,
+                n: 5,
+            },
+            WeekendsOnly,
+        );
+        assert!(fifth_monday.expiry(2025, 2).is_err());
+        assert_eq!(fifth_monday.expiry(2025, 3).unwrap(), ymd(2025, 3, 31));
+
+        // the month offset and the next expiry cross the year end
+        let crude = ExpiryCalendar::crude_oil(WeekendsOnly);
+        assert_eq!(crude.expiry(2025, 1).unwrap(), ymd(2024, 12, 20));
+        let imm = ExpiryCalendar::imm(WeekendsOnly);
+        assert_eq!(
+            imm.next_expiry(ymd(2025, 12, 20)).unwrap(),
+            ymd(2026, 3, 18)
+        );
+        assert_eq!(
+            imm.rule(),
+            ExpiryRule::NthWeekday {
+                weekday: Weekday::Wed,
+                n: 3
+            }
+        );
+    }
+}
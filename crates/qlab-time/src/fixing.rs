@@ -0,0 +1,287 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+
+/// How the fixings of a floating coupon are observed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FixingConvention {
+    /// A term rate fixed once, `lag` business days before the start of accrual.
+    Term { lag: u32 },
+    /// An overnight rate compounded in arrears over every business day of accrual, each fixed `lookback`
+    /// business days earlier.
+    ///
+    /// With `observation_shift`, each fixing is weighted by the days of the shifted observation period
+    /// rather than by the days of the accrual period.
+    Overnight {
+        lookback: u32,
+        observation_shift: bool,
+    },
+}
+
+/// A single fixing of a floating coupon.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Fixing {
+    /// The date on which the index is observed.
+    pub fixing_date: Date,
+    /// The start of the period by whose length the fixing is weighted.
+    pub start: Date,
+    /// The end of the period by whose length the fixing is weighted.
+    pub end: Date,
+}
+
+fn out_of_range(date: Date) -> qlab_error::QLabError {
+    InvalidInput(format!("date out of range while generating fixings from {date}").into()).into()
+}
+
+/// Moves `date` by `count` business days of `calendar`, backward if `backward` is true.
+/// A `count` of zero leaves `date` unchanged even if it is a holiday.
+fn shift_business_days(
+    date: Date,
+    count: u32,
+    backward: bool,
+    calendar: &(impl Calendar + ?Sized),
+) -> QLabResult<Date> {
+    let mut ret = date;
+    for _ in 0..count {
+        loop {
+            ret = if backward {
+                ret.pred_opt()
+            } else {
+                ret.succ_opt()
+            }
+            .ok_or_else(|| out_of_range(date))?;
+            if calendar.is_business_day(ret) {
+                break;
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// Returns the business days of `calendar` in `[start, end)` together with the next business day from `end`.
+fn business_days(
+    start: Date,
+    end: Date,
+    calendar: &(impl Calendar + ?Sized),
+) -> QLabResult<Vec<Date>> {
+    let mut dates = Vec::new();
+    let mut date = start;
+    while date < end || dates.is_empty() || !calendar.is_business_day(date) {
+        if calendar.is_business_day(date) {
+            dates.push(date);
+        }
+        date = date.succ_opt().ok_or_else(|| out_of_range(start))?;
+    }
+    dates.push(date);
+    Ok(dates)
+}
+
+/// Generates the fixings of a coupon accruing from `accrual_start` to `accrual_end` under `convention`,
+/// on the fixing `calendar` of the index.
+///
+/// # Errors
+/// Returns an error if a date overflows.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::fixing::{fixings, FixingConvention};
+/// use qlab_time::frequency::Frequency;
+/// use qlab_time::schedule::{generate, StubPolicy};
+///
+/// let periods = generate(
+///     Date::from_ymd(2024, 1, 15).unwrap(),
+///     Date::from_ymd(2024, 4, 15).unwrap(),
+///     Frequency::Q,
+///     &WeekendsOnly,
+///     DateRolling::ModifiedFollowing,
+///     StubPolicy::ShortBack,
+///     false,
+/// )
+/// .unwrap();
+/// let (start, end) = (periods[0].accrual_start, periods[0].accrual_end);
+/// let term = fixings(start, end, FixingConvention::Term { lag: 2 }, &WeekendsOnly).unwrap();
+/// // two business days before Monday 2024-01-15
+/// assert_eq!(term[0].fixing_date, Date::from_ymd(2024, 1, 11).unwrap());
+/// ```
+pub fn fixings(
+    accrual_start: Date,
+    accrual_end: Date,
+    convention: FixingConvention,
+    calendar: &(impl Calendar + ?Sized),
+) -> QLabResult<Vec<Fixing>> {
+    match convention {
+        FixingConvention::Term { lag } => Ok(vec![Fixing {
+            fixing_date: shift_business_days(accrual_start, lag, true, calendar)?,
+            start: accrual_start,
+            end: accrual_end,
+        }]),
+        FixingConvention::Overnight {
+            lookback,
+            observation_shift,
+        } => {
+            let dates = if observation_shift {
+                let start = shift_business_days(accrual_start, lookback, true, calendar)?;
+                let end = shift_business_days(accrual_end, lookback, true, calendar)?;
+                business_days(start, end, calendar)?
+            } else {
+                business_days(accrual_start, accrual_end, calendar)?
+            };
+            let mut ret = Vec::with_capacity(dates.len() - 1);
+            for (i, pair) in dates.windows(2).enumerate() {
+                let fixing_date = if observation_shift {
+                    pair[0]
+                } else {
+                    shift_business_days(pair[0], lookback, true, calendar)?
+                };
+                // the first and last weights are clipped to the accrual period when it starts or ends on a holiday
+                let (start, end) = if observation_shift {
+                    (pair[0], pair[1])
+                } else {
+                    (
+                        if i == 0 { accrual_start } else { pair[0] },
+                        if i == dates.len() - 2 {
+                            accrual_end
+                        } else {
+                            pair[1]
+                        },
+                    )
+                };
+                ret.push(Fixing {
+                    fixing_date,
+                    start,
+                    end,
+                });
+            }
+            Ok(ret)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use calendar::weekendsonly::WeekendsOnly;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_overnight() {
+        // Monday 2024-01-08 to Monday 2024-01-15
+        let start = Date::from_ymd(2024, 1, 8).unwrap();
+        let end = Date::from_ymd(2024, 1, 15).unwrap();
+        let in_arrears = fixings(
+            start,
+            end,
+            FixingConvention::Overnight {
+                lookback: 0,
+                observation_shift: false,
+            },
+            &WeekendsOnly,
+        )
+        .unwrap();
+        assert_eq!(in_arrears.len(), 5);
+        // Friday's fixing is weighted over the weekend
+        assert_eq!(in_arrears[4].end - in_arrears[4].start, 3);
+
+        let lookback = fixings(
+            start,
+            end,
+            FixingConvention::Overnight {
+                lookback: 2,
+                observation_shift: false,
+            },
+            &WeekendsOnly,
+        )
+        .unwrap();
+        assert_eq!(lookback[0].fixing_date, Date::from_ymd(2024, 1, 4).unwrap());
+        // without a shift the weights follow the accrual period
+        assert_eq!(lookback[4].end - lookback[4].start, 3);
+
+        let shifted = fixings(
+            start,
+            end,
+            FixingConvention::Overnight {
+                lookback: 2,
+                observation_shift: true,
+            },
+            &WeekendsOnly,
+        )
+        .unwrap();
+        assert_eq!(shifted.len(), 5);
+        assert_eq!(shifted[0].fixing_date, Date::from_ymd(2024, 1, 4).unwrap());
+        // the weekend falls in the second observation period
+        assert_eq!(shifted[1].end - shifted[1].start, 3);
+        assert_eq!(shifted[4].end, Date::from_ymd(2024, 1, 11).unwrap());
+    }
+
+    #[test]
+    fn test_holidays_and_bounds() {
+        let ymd = |month, day| Date::from_ymd(2024, month, day).unwrap();
+        let holiday = ymd(1, 10);
+        let calendar = BespokeCalendar::new("exchange", HashSet::from([holiday]));
+
+        // a zero lag keeps a holiday start, and a lag skips holidays
+        let term = |start, lag| {
+            fixings(start, ymd(4, 15), FixingConvention::Term { lag }, &calendar).unwrap()[0]
+        };
+        assert_eq!(term(ymd(1, 13), 0).fixing_date, ymd(1, 13));
+        assert_eq!(term(ymd(1, 11), 1).fixing_date, ymd(1, 9));
+        assert_eq!(term(ymd(1, 11), 1).start, ymd(1, 11));
+
+        // an accrual from Saturday to Saturday: the weights are clipped to it, and the holiday has no fixing
+        let overnight = |lookback, observation_shift| {
+            fixings(
+                ymd(1, 6),
+                ymd(1, 13),
+                FixingConvention::Overnight {
+                    lookback,
+                    observation_shift,
+                },
+                &calendar,
+            )
+            .unwrap()
+        };
+        let in_arrears = overnight(0, false);
+        assert_eq!(in_arrears.len(), 4);
+        assert_eq!(in_arrears[0].start, ymd(1, 6));
+        assert_eq!(in_arrears[0].fixing_date, ymd(1, 8));
+        assert_eq!(in_arrears[1].end, ymd(1, 11));
+        assert_eq!(in_arrears[3].end, ymd(1, 13));
+        let total: i64 = in_arrears
+            .iter()
+            .map(|fixing| fixing.end - fixing.start)
+            .sum();
+        assert_eq!(total, 7);
+        let shifted = overnight(1, true);
+        assert_eq!(shifted[0].fixing_date, ymd(1, 5));
+        assert!(shifted
+            .iter()
+            .all(|fixing| fixing.fixing_date == fixing.start));
+
+        // dates running out of range are errors
+        let first = Date::from_ymd(-262_143, 1, 1).unwrap();
+        assert!(fixings(
+            first,
+            ymd(1, 8),
+            FixingConvention::Term { lag: 1 },
+            &WeekendsOnly
+        )
+        .is_err());
+        let last = Date::from_ymd(262_142, 12, 31).unwrap();
+        assert!(fixings(
+            last.pred_opt().unwrap(),
+            last,
+            FixingConvention::Overnight {
+                lookback: 0,
+                observation_shift: false,
+            },
+            &WeekendsOnly,
+        )
+        .is_err());
+    }
+}
@@ -1,4 +1,16 @@
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Frequency {
+    A = 1,
     SA = 2,
+    Q = 4,
+    M = 12,
+}
+
+impl Frequency {
+    /// Returns the number of months between two regular dates.
+    #[must_use]
+    pub const fn months(self) -> u32 {
+        12 / self as u32
+    }
 }
@@ -0,0 +1,202 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use chrono::{Datelike, Weekday};
+use qlab_error::QLabResult;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+#[cfg(feature = "holiday-data")]
+use std::sync::OnceLock;
+
+/// Financial centers whose holidays are bundled with the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FinancialCenter {
+    Target,
+    London,
+    NewYork,
+    Tokyo,
+}
+
+#[cfg(feature = "holiday-data")]
+impl FinancialCenter {
+    fn raw_data(self) -> &'static str {
+        match self {
+            Self::Target => include_str!("../data/holidays/target.txt"),
+            Self::London => include_str!("../data/holidays/london.txt"),
+            Self::NewYork => include_str!("../data/holidays/new_york.txt"),
+            Self::Tokyo => include_str!("../data/holidays/tokyo.txt"),
+        }
+    }
+
+    fn cell(self) -> &'static OnceLock<Arc<BTreeSet<Date>>> {
+        static TARGET: OnceLock<Arc<BTreeSet<Date>>> = OnceLock::new();
+        static LONDON: OnceLock<Arc<BTreeSet<Date>>> = OnceLock::new();
+        static NEW_YORK: OnceLock<Arc<BTreeSet<Date>>> = OnceLock::new();
+        static TOKYO: OnceLock<Arc<BTreeSet<Date>>> = OnceLock::new();
+        match self {
+            Self::Target => &TARGET,
+            Self::London => &LONDON,
+            Self::NewYork => &NEW_YORK,
+            Self::Tokyo => &TOKYO,
+        }
+    }
+
+    /// Returns the bundled holidays of the center.
+    ///
+    /// The embedded dataset is decoded on first access and shared afterwards.
+    ///
+    /// # Panics
+    /// Will panic if the bundled dataset is malformed.
+    #[must_use]
+    pub fn holidays(self) -> Arc<BTreeSet<Date>> {
+        Arc::clone(
+            self.cell()
+                .get_or_init(|| Arc::new(parse_holidays(self.raw_data()).unwrap())),
+        )
+    }
+}
+
+/// Parses a holiday list with one `YYYY-MM-DD` date per line, see the `FromStr` implementation of [`Date`].
+///
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if a line is not a valid date.
+pub fn parse_holidays(text: &str) -> QLabResult<BTreeSet<Date>> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+/// A calendar whose holidays are given by a dataset rather than by rules.
+///
+/// Saturdays and Sundays are always holidays; the dataset lists the additional weekday holidays.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::calendar::Calendar;
+/// use qlab_time::date::Date;
+/// use qlab_time::holiday_data::DatasetCalendar;
+///
+/// let calendar = DatasetCalendar::parse("# desk holidays\n2024-08-15\n").unwrap();
+/// assert!(calendar.is_holiday(Date::from_ymd(2024, 8, 15).unwrap()));
+/// assert!(calendar.is_business_day(Date::from_ymd(2024, 8, 16).unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetCalendar {
+    holidays: Arc<BTreeSet<Date>>,
+}
+
+impl DatasetCalendar {
+    /// Creates a calendar from the bundled dataset of `center`.
+    #[cfg(feature = "holiday-data")]
+    #[must_use]
+    pub fn bundled(center: FinancialCenter) -> Self {
+        Self {
+            holidays: center.holidays(),
+        }
+    }
+
+    /// Creates a calendar from user-supplied holidays.
+    pub fn from_holidays(holidays: impl IntoIterator<Item = Date>) -> Self {
+        Self {
+            holidays: Arc::new(holidays.into_iter().collect()),
+        }
+    }
+
+    /// Creates a calendar from the text of a holiday file, see [`parse_holidays`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a line is not a valid date.
+    pub fn parse(text: &str) -> QLabResult<Self> {
+        Ok(Self {
+            holidays: Arc::new(parse_holidays(text)?),
+        })
+    }
+
+    /// Returns a copy of the calendar with `added` declared as holidays and `removed` declared as business days.
+    ///
+    /// This allows user data to override single entries of a bundled dataset.
+    #[must_use]
+    pub fn with_overrides(&self, added: &[Date], removed: &[Date]) -> Self {
+        let mut holidays = (*self.holidays).clone();
+        holidays.extend(added.iter().copied());
+        for date in removed {
+            holidays.remove(date);
+        }
+        Self {
+            holidays: Arc::new(holidays),
+        }
+    }
+
+    /// Returns the first and the last holiday of the dataset, or `None` if it is empty.
+    #[must_use]
+    pub fn coverage(&self) -> Option<(Date, Date)> {
+        Some((*self.holidays.first()?, *self.holidays.last()?))
+    }
+
+    /// Returns the holidays of the dataset.
+    #[must_use]
+    pub fn holidays(&self) -> &BTreeSet<Date> {
+        &self.holidays
+    }
+}
+
+impl Calendar for DatasetCalendar {
+    fn is_business_day(&self, date: Date) -> bool {
+        !matches!(date.0.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let calendar = DatasetCalendar::parse("# comment\n\n2024-01-01\n 2024-12-25 \n").unwrap();
+        assert_eq!(
+            calendar.coverage(),
+            Some((
+                Date::from_ymd(2024, 1, 1).unwrap(),
+                Date::from_ymd(2024, 12, 25).unwrap()
+            ))
+        );
+        assert!(calendar.is_holiday(Date::from_ymd(2024, 1, 1).unwrap()));
+        assert!(calendar.is_holiday(Date::from_ymd(2024, 1, 6).unwrap()));
+        assert!(calendar.is_business_day(Date::from_ymd(2024, 1, 2).unwrap()));
+        assert!(DatasetCalendar::parse("2024-13-01").is_err());
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let christmas = Date::from_ymd(2024, 12, 25).unwrap();
+        let boxing_day = Date::from_ymd(2024, 12, 26).unwrap();
+        let calendar = DatasetCalendar::from_holidays([christmas]);
+        let overridden = calendar.with_overrides(&[boxing_day], &[christmas]);
+        assert!(overridden.is_business_day(christmas));
+        assert!(overridden.is_holiday(boxing_day));
+        assert!(calendar.is_holiday(christmas));
+    }
+
+    #[cfg(feature = "holiday-data")]
+    #[test]
+    fn test_bundled() {
+        use calendar::target::Target;
+        let bundled = DatasetCalendar::bundled(FinancialCenter::Target);
+        let rules = Target;
+        let mut date = Date::from_ymd(2000, 1, 1).unwrap();
+        while date.year() < 2030 {
+            assert_eq!(
+                bundled.is_business_day(date),
+                rules.is_business_day(date),
+                "{date}"
+            );
+            date = date.succ_opt().unwrap();
+        }
+        let tokyo = DatasetCalendar::bundled(FinancialCenter::Tokyo);
+        assert!(tokyo.is_holiday(Date::from_ymd(2024, 5, 3).unwrap()));
+    }
+}
@@ -2,6 +2,17 @@ pub mod date_rolling;
 // mod calendar;
 pub mod calendar;
 pub mod date;
+pub mod date_range;
+pub mod date_time;
 pub mod day_count;
+pub mod expiry;
+pub mod fixing;
 pub mod frequency;
+pub mod holiday_data;
+pub mod observation;
 pub mod period;
+pub mod registry;
+pub mod schedule;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod weekday;
@@ -0,0 +1,295 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::date_rolling::DateRolling;
+use crate::day_count::DayCount;
+use crate::frequency::Frequency;
+use crate::schedule::{generate, StubPolicy};
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+
+/// The dates on which a path-dependent payoff observes its underlying, e.g. the monitoring dates
+/// of a barrier or an Asian option, or the notice dates of a callable.
+///
+/// The dates are sorted and distinct. An instrument keeps its schedule and a simulation builds its
+/// time grid from the same schedule with [`ObservationSchedule::time_grid`], so that the monitoring
+/// dates are always points of the grid.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::day_count::act_365::Act365;
+/// use qlab_time::frequency::Frequency;
+/// use qlab_time::observation::ObservationSchedule;
+///
+/// let trade_date = Date::from_ymd(2024, 1, 31).unwrap();
+/// let expiry = Date::from_ymd(2025, 1, 31).unwrap();
+/// let averaging = ObservationSchedule::from_frequency(
+///     trade_date,
+///     expiry,
+///     Frequency::M,
+///     &WeekendsOnly,
+///     DateRolling::ModifiedFollowing,
+/// )
+/// .unwrap();
+/// assert_eq!(averaging.len(), 12);
+///
+/// // a grid with steps of at most a week, on which every averaging date is a point
+/// let grid = averaging.time_grid::<f64, Act365>(trade_date, 7.0 / 365.0).unwrap();
+/// assert_eq!(grid.observation_indices().len(), 12);
+/// assert_eq!(*grid.observation_indices().last().unwrap(), grid.steps().len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObservationSchedule {
+    dates: Vec<Date>,
+}
+
+impl ObservationSchedule {
+    /// Creates a schedule of explicit dates, which are sorted and deduplicated.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `dates` is empty.
+    pub fn new(dates: &[Date]) -> QLabResult<Self> {
+        if dates.is_empty() {
+            return Err(InvalidInput("an observation schedule needs a date".into()).into());
+        }
+        let mut dates = dates.to_vec();
+        dates.sort_unstable();
+        dates.dedup();
+        Ok(Self { dates })
+    }
+
+    /// Observes at the end of each period of `frequency` from `start` to `end`, adjusted with `calendar`
+    /// and `rolling`. Regular periods run backward from `end`, see [`generate`]; `start` is not observed.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `start` is not before `end`, or if a date overflows.
+    pub fn from_frequency(
+        start: Date,
+        end: Date,
+        frequency: Frequency,
+        calendar: &(impl Calendar + ?Sized),
+        rolling: DateRolling,
+    ) -> QLabResult<Self> {
+        let periods = generate(
+            start,
+            end,
+            frequency,
+            calendar,
+            rolling,
+            StubPolicy::ShortFront,
+            false,
+        )?;
+        Self::new(
+            &periods
+                .iter()
+                .map(|period| period.accrual_end)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Observes on every business day of `calendar` after `start` up to and including `end`,
+    /// as for a continuously monitored barrier.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if there is no business day in the range.
+    pub fn business_days(
+        start: Date,
+        end: Date,
+        calendar: &(impl Calendar + ?Sized),
+    ) -> QLabResult<Self> {
+        let (Some(first), Some(last)) = (start.succ_opt(), end.succ_opt()) else {
+            return Err(InvalidInput(format!("date out of range after {start}").into()).into());
+        };
+        let dates: Vec<_> = calendar.business_days_in(first, last).collect();
+        if dates.is_empty() {
+            return Err(
+                InvalidInput(format!("no business day after {start} up to {end}").into()).into(),
+            );
+        }
+        Ok(Self { dates })
+    }
+
+    /// Returns the union of the dates of both schedules, e.g. of barrier monitoring and call notice dates.
+    #[must_use]
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut dates = [self.dates.as_slice(), other.dates.as_slice()].concat();
+        dates.sort_unstable();
+        dates.dedup();
+        Self { dates }
+    }
+
+    #[must_use]
+    pub fn dates(&self) -> &[Date] {
+        &self.dates
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// Returns the observation dates after `valuation_date`, which are still to be simulated.
+    /// The earlier ones are fixed and looked up from past fixings.
+    #[must_use]
+    pub fn remaining(&self, valuation_date: Date) -> &[Date] {
+        &self.dates[self.dates.partition_point(|&date| date <= valuation_date)..]
+    }
+
+    /// Builds the time grid of a simulation from `valuation_date` over the remaining observation dates,
+    /// measured in years by `DC`. Each interval between observations is divided into equal steps
+    /// no longer than `max_step`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `max_step` is not positive, or an error if a day count fails.
+    pub fn time_grid<V: Value, DC: DayCount>(
+        &self,
+        valuation_date: Date,
+        max_step: V,
+    ) -> QLabResult<TimeGrid<V>> {
+        if max_step.partial_cmp(&V::zero()) != Some(std::cmp::Ordering::Greater) {
+            return Err(
+                InvalidInput(format!("max_step: {max_step:?} must be positive").into()).into(),
+            );
+        }
+        let tolerance = V::epsilon().sqrt();
+        let mut times = vec![V::zero()];
+        let mut observation_indices = Vec::new();
+        for &date in self.remaining(valuation_date) {
+            let start = times[times.len() - 1];
+            let end = DC::calculate_day_count_fraction::<V>(valuation_date, date)?;
+            // the tolerance keeps an interval of a whole number of steps from getting an extra step
+            let num_steps = ((end - start) / max_step - tolerance)
+                .ceil()
+                .to_usize()
+                .ok_or_else(|| CastNumberError(format!("{:?}", end - start).into()))?
+                .max(1);
+            let width = (end - start)
+                / V::from_usize(num_steps)
+                    .ok_or_else(|| CastNumberError(format!("{num_steps}").into()))?;
+            for i in 1..num_steps {
+                times.push(start + width * V::from_usize(i).unwrap_or_else(V::zero));
+            }
+            times.push(end);
+            observation_indices.push(times.len() - 1);
+        }
+        Ok(TimeGrid {
+            times,
+            observation_indices,
+        })
+    }
+}
+
+/// The times in years of a simulation, starting at zero, with the indices of the times which are observations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeGrid<V> {
+    times: Vec<V>,
+    observation_indices: Vec<usize>,
+}
+
+impl<V: Value> TimeGrid<V> {
+    #[must_use]
+    pub fn times(&self) -> &[V] {
+        &self.times
+    }
+
+    /// Returns the lengths of the steps between consecutive times, one per normal draw of a one-factor path.
+    #[must_use]
+    pub fn steps(&self) -> Vec<V> {
+        self.times
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect()
+    }
+
+    /// Returns the index into [`TimeGrid::times`] of each remaining observation date, in order.
+    #[must_use]
+    pub fn observation_indices(&self) -> &[usize] {
+        &self.observation_indices
+    }
+
+    /// Returns the times after the leading zero, i.e. the end of each step, which is the form taken by
+    /// simulations that start at zero implicitly and require positive times.
+    #[must_use]
+    pub fn step_times(&self) -> &[V] {
+        &self.times[1..]
+    }
+
+    /// Returns the index into [`TimeGrid::step_times`] of each remaining observation date, in order.
+    #[must_use]
+    pub fn observation_steps(&self) -> Vec<usize> {
+        self.observation_indices
+            .iter()
+            .map(|&index| index - 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_count::act_365::Act365;
+    use calendar::weekendsonly::WeekendsOnly;
+
+    fn ymd(year: i32, month: u32, day: u32) -> Date {
+        Date::from_ymd(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_observation_schedule() {
+        // two weeks of daily monitoring over Easter weekend, without holidays
+        let barrier =
+            ObservationSchedule::business_days(ymd(2024, 3, 22), ymd(2024, 4, 5), &WeekendsOnly)
+                .unwrap();
+        assert_eq!(barrier.len(), 10);
+        assert_eq!(barrier.dates()[0], ymd(2024, 3, 25));
+        assert_eq!(barrier.dates()[9], ymd(2024, 4, 5));
+
+        let notice = ObservationSchedule::new(&[ymd(2024, 4, 1), ymd(2024, 3, 31)]).unwrap();
+        let merged = barrier.merge(&notice);
+        assert_eq!(merged.len(), 11);
+        assert_eq!(merged.remaining(ymd(2024, 4, 1)).len(), 4);
+
+        let grid = merged
+            .time_grid::<f64, Act365>(ymd(2024, 3, 28), 1.0 / 365.0)
+            .unwrap();
+        // observations on 29, 31 March and 1 to 5 April, with the Saturday in between
+        assert_eq!(grid.observation_indices(), &[1, 3, 4, 5, 6, 7, 8]);
+        assert!(grid
+            .steps()
+            .iter()
+            .all(|&step| (step - 1.0 / 365.0).abs() < 1e-12));
+
+        let coarse = merged
+            .time_grid::<f64, Act365>(ymd(2024, 3, 28), 1.0)
+            .unwrap();
+        assert_eq!(coarse.times().len(), 8);
+        assert_eq!(coarse.step_times(), &coarse.times()[1..]);
+        assert_eq!(coarse.observation_steps(), [0, 1, 2, 3, 4, 5, 6]);
+        assert!(coarse.step_times()[0] > 0.0);
+        // nothing remains to be simulated after the last observation
+        let expired = merged
+            .time_grid::<f64, Act365>(ymd(2024, 4, 5), 1.0)
+            .unwrap();
+        assert!(expired.step_times().is_empty());
+        assert!(expired.observation_steps().is_empty());
+        assert!(merged
+            .time_grid::<f64, Act365>(ymd(2024, 3, 28), 0.0)
+            .is_err());
+        assert!(ObservationSchedule::new(&[]).is_err());
+        assert!(ObservationSchedule::business_days(
+            ymd(2024, 3, 29),
+            ymd(2024, 3, 31),
+            &WeekendsOnly
+        )
+        .is_err());
+    }
+}
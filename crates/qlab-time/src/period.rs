@@ -1,9 +1,55 @@
+use crate::calendar::Calendar;
 use crate::date::Date;
+use crate::period::days::Days;
+use crate::period::months::Months;
 
+pub mod business_days;
+pub mod composite;
 pub mod days;
 pub mod months;
+pub mod tenor;
 pub mod years;
 
 pub trait Period: Copy {
     fn checked_add(self, date: Date) -> Option<Date>;
+
+    /// Rolls `date` backwards by the period, i.e. adds the opposite period.
+    fn checked_sub(self, date: Date) -> Option<Date>;
+
+    /// Adds the period `times` times to `date` in one step, so that months are clamped only once,
+    /// e.g. 2024-01-31 + 2 * 1M = 2024-03-31.
+    fn checked_add_times(self, date: Date, times: u32) -> Option<Date>;
+
+    /// Returns `true` if the period is a non-zero whole number of months, to which the end-of-month rule applies.
+    fn is_whole_months(self) -> bool {
+        false
+    }
+}
+
+/// A period whose length depends on a calendar, such as a number of business days.
+pub trait CalendarPeriod: Copy {
+    fn checked_add_on(self, date: Date, calendar: &(impl Calendar + ?Sized)) -> Option<Date>;
+
+    /// Rolls `date` backwards by the period on `calendar`.
+    fn checked_sub_on(self, date: Date, calendar: &(impl Calendar + ?Sized)) -> Option<Date>;
+}
+
+/// Adds a signed number of days to `date`.
+pub(crate) fn checked_add_signed_days(date: Date, days: i64) -> Option<Date> {
+    let rhs = Days::new(days.unsigned_abs());
+    if days < 0 {
+        date.checked_sub_days(rhs)
+    } else {
+        date.checked_add_days(rhs)
+    }
+}
+
+/// Adds a signed number of months to `date`, clamping the day to the end of the resulting month.
+pub(crate) fn checked_add_signed_months(date: Date, months: i64) -> Option<Date> {
+    let rhs = Months::new(u32::try_from(months.unsigned_abs()).ok()?);
+    if months < 0 {
+        date.checked_sub_months(rhs)
+    } else {
+        date.checked_add_months(rhs)
+    }
 }
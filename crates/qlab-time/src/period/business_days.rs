@@ -0,0 +1,143 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::period::CalendarPeriod;
+
+/// A period of good business days on a calendar, as in T+2 settlement.
+///
+/// A negative number counts business days backwards. Zero business days adjusts a holiday
+/// to the following business day and leaves a business day unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::period::business_days::BusinessDays;
+/// use qlab_time::period::CalendarPeriod;
+///
+/// let trade_date = Date::from_ymd(2024, 3, 28).unwrap();
+/// let settlement_date = BusinessDays::new(2)
+///     .checked_add_on(trade_date, &WeekendsOnly)
+///     .unwrap();
+/// assert_eq!(settlement_date, Date::from_ymd(2024, 4, 1).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BusinessDays(pub(crate) i64);
+
+impl BusinessDays {
+    #[must_use]
+    pub const fn new(num: i64) -> Self {
+        Self(num)
+    }
+
+    #[must_use]
+    pub const fn count(self) -> i64 {
+        self.0
+    }
+
+    fn checked_add_count(
+        count: i64,
+        date: Date,
+        calendar: &(impl Calendar + ?Sized),
+    ) -> Option<Date> {
+        let mut ret = date;
+        if count == 0 {
+            while !calendar.is_business_day(ret) {
+                ret = ret.succ_opt()?;
+            }
+            return Some(ret);
+        }
+        let mut remaining = count.unsigned_abs();
+        while remaining > 0 {
+            ret = if count > 0 {
+                ret.succ_opt()?
+            } else {
+                ret.pred_opt()?
+            };
+            if calendar.is_business_day(ret) {
+                remaining -= 1;
+            }
+        }
+        Some(ret)
+    }
+}
+
+impl CalendarPeriod for BusinessDays {
+    fn checked_add_on(self, date: Date, calendar: &(impl Calendar + ?Sized)) -> Option<Date> {
+        Self::checked_add_count(self.0, date, calendar)
+    }
+
+    fn checked_sub_on(self, date: Date, calendar: &(impl Calendar + ?Sized)) -> Option<Date> {
+        Self::checked_add_count(self.0.checked_neg()?, date, calendar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use calendar::weekendsonly::WeekendsOnly;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_checked_add_on() {
+        let friday = Date::from_ymd(2024, 3, 29).unwrap();
+        let saturday = Date::from_ymd(2024, 3, 30).unwrap();
+        let monday = Date::from_ymd(2024, 4, 1).unwrap();
+        let tuesday = Date::from_ymd(2024, 4, 2).unwrap();
+        let add = |count, date| {
+            BusinessDays::new(count)
+                .checked_add_on(date, &WeekendsOnly)
+                .unwrap()
+        };
+        assert_eq!(add(1, friday), monday);
+        assert_eq!(add(2, friday), tuesday);
+        assert_eq!(add(2, saturday), tuesday);
+        assert_eq!(add(0, saturday), monday);
+        assert_eq!(add(0, friday), friday);
+        assert_eq!(add(-1, monday), friday);
+        assert_eq!(add(-1, saturday), friday);
+        assert_eq!(
+            BusinessDays::new(2)
+                .checked_sub_on(tuesday, &WeekendsOnly)
+                .unwrap(),
+            friday
+        );
+    }
+
+    #[test]
+    fn test_holidays_and_bounds() {
+        let holiday = Date::from_ymd(2024, 4, 1).unwrap();
+        let calendar = BespokeCalendar::new("easter", HashSet::from([holiday]));
+        let friday = Date::from_ymd(2024, 3, 29).unwrap();
+        let tuesday = Date::from_ymd(2024, 4, 2).unwrap();
+        assert_eq!(
+            BusinessDays::new(1).checked_add_on(friday, &calendar),
+            Some(tuesday)
+        );
+        assert_eq!(
+            BusinessDays::new(1).checked_sub_on(tuesday, &calendar),
+            Some(friday)
+        );
+        // zero business days adjusts forwards also when subtracted
+        assert_eq!(
+            BusinessDays::new(0).checked_sub_on(holiday, &calendar),
+            Some(tuesday)
+        );
+        assert_eq!(BusinessDays::new(-3).count(), -3);
+
+        // running out of dates or negating the count overflows into `None`
+        let last = Date::from_ymd(262_142, 12, 31).unwrap();
+        assert!(BusinessDays::new(1)
+            .checked_add_on(last, &WeekendsOnly)
+            .is_none());
+        let first = Date::from_ymd(-262_143, 1, 1).unwrap();
+        assert!(BusinessDays::new(1)
+            .checked_sub_on(first, &WeekendsOnly)
+            .is_none());
+        assert!(BusinessDays::new(i64::MIN)
+            .checked_sub_on(friday, &WeekendsOnly)
+            .is_none());
+    }
+}
@@ -0,0 +1,329 @@
+use crate::date::Date;
+use crate::period::tenor::{Tenor, TenorUnit};
+use crate::period::{checked_add_signed_days, checked_add_signed_months, Period};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{QLabError, QLabResult};
+use std::fmt;
+use std::str::FromStr;
+
+/// A period combining years, months and days, as in "1Y6M" or "18M3D".
+///
+/// The years and months are added together as a number of months before the days are added,
+/// so that "1Y6M" rolls a date exactly as "18M" does and the day of month is clamped at most once.
+/// Components may be negative; a leading sign applies to the whole period, so "-1Y6M" is eighteen months back.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::date::Date;
+/// use qlab_time::period::composite::CompositePeriod;
+/// use qlab_time::period::Period;
+///
+/// let period: CompositePeriod = "18M3D".parse().unwrap();
+/// assert_eq!(period.normalized().to_string(), "1Y6M3D");
+/// let date = Date::from_ymd(2024, 1, 31).unwrap();
+/// assert_eq!(period.checked_add(date).unwrap(), Date::from_ymd(2025, 8, 3).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CompositePeriod {
+    years: i32,
+    months: i32,
+    days: i32,
+}
+
+impl CompositePeriod {
+    #[must_use]
+    pub const fn new(years: i32, months: i32, days: i32) -> Self {
+        Self {
+            years,
+            months,
+            days,
+        }
+    }
+
+    /// Parses an optionally signed sequence of tenors such as "1Y6M", "2W3D" or "-1Y6M", see [`Tenor::try_parse`].
+    /// Weeks are converted into days.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `text` is not a sequence of tenors or a component overflows.
+    pub fn try_parse(text: &str) -> QLabResult<Self> {
+        let invalid =
+            || -> QLabError { InvalidInput(format!("invalid period: {text:?}").into()).into() };
+        let trimmed = text.trim();
+        if trimmed.is_empty() || trimmed.contains(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let (negative, components) = match trimmed.strip_prefix('-') {
+            Some(components) => (true, components),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let mut period = Self::default();
+        let mut start = 0;
+        for (i, c) in components.char_indices() {
+            if c.is_ascii_alphabetic() {
+                let tenor = Tenor::try_parse(&components[start..=i]).map_err(|_| invalid())?;
+                period = period
+                    .checked_plus(Self::try_from(tenor)?)
+                    .ok_or_else(invalid)?;
+                start = i + 1;
+            }
+        }
+        if start == 0 || start != components.len() {
+            return Err(invalid());
+        }
+        if negative {
+            period = period.checked_neg().ok_or_else(invalid)?;
+        }
+        Ok(period)
+    }
+
+    #[must_use]
+    pub fn years(&self) -> i32 {
+        self.years
+    }
+
+    #[must_use]
+    pub fn months(&self) -> i32 {
+        self.months
+    }
+
+    #[must_use]
+    pub fn days(&self) -> i32 {
+        self.days
+    }
+
+    /// Returns the number of months of the years and months components.
+    #[must_use]
+    pub fn total_months(&self) -> i64 {
+        12 * i64::from(self.years) + i64::from(self.months)
+    }
+
+    /// Returns the same period with whole years carried from the months into the years,
+    /// so that the years and months components have the same sign.
+    /// Days are never carried into months, since the length of a month depends on the date.
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        let total_months = self.total_months();
+        Self {
+            years: i32::try_from(total_months / 12).unwrap_or(self.years),
+            months: i32::try_from(total_months % 12).unwrap_or(self.months),
+            days: self.days,
+        }
+    }
+
+    /// Returns the period with every component multiplied by `factor`, or `None` if a component overflows.
+    #[must_use]
+    pub fn checked_mul(&self, factor: i32) -> Option<Self> {
+        Some(Self {
+            years: self.years.checked_mul(factor)?,
+            months: self.months.checked_mul(factor)?,
+            days: self.days.checked_mul(factor)?,
+        })
+    }
+
+    /// Returns the period as a single normalized [`Tenor`], e.g. "18M" for "1Y6M",
+    /// or `None` if it has both months and days or its length overflows.
+    #[must_use]
+    pub fn to_tenor(&self) -> Option<Tenor> {
+        let tenor = if self.days == 0 {
+            Tenor::new(i32::try_from(self.total_months()).ok()?, TenorUnit::Month)
+        } else if self.total_months() == 0 {
+            Tenor::new(self.days, TenorUnit::Day)
+        } else {
+            return None;
+        };
+        Some(tenor.normalized())
+    }
+
+    /// Returns the opposite period, or `None` if a component overflows.
+    #[must_use]
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self {
+            years: self.years.checked_neg()?,
+            months: self.months.checked_neg()?,
+            days: self.days.checked_neg()?,
+        })
+    }
+
+    fn checked_plus(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            years: self.years.checked_add(rhs.years)?,
+            months: self.months.checked_add(rhs.months)?,
+            days: self.days.checked_add(rhs.days)?,
+        })
+    }
+}
+
+impl TryFrom<Tenor> for CompositePeriod {
+    type Error = QLabError;
+
+    fn try_from(tenor: Tenor) -> Result<Self, Self::Error> {
+        let length = tenor.length();
+        Ok(match tenor.unit() {
+            TenorUnit::Day => Self::new(0, 0, length),
+            TenorUnit::Week => Self::new(
+                0,
+                0,
+                length.checked_mul(7).ok_or_else(|| {
+                    InvalidInput(format!("{tenor} overflows as a number of days").into())
+                })?,
+            ),
+            TenorUnit::Month => Self::new(0, length, 0),
+            TenorUnit::Year => Self::new(length, 0, 0),
+        })
+    }
+}
+
+impl FromStr for CompositePeriod {
+    type Err = QLabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_parse(s)
+    }
+}
+
+impl fmt::Display for CompositePeriod {
+    /// Writes the non-zero components, factoring out the sign when all of them are negative, as in "-1Y6M".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let components = [
+            (i64::from(self.years), 'Y'),
+            (i64::from(self.months), 'M'),
+            (i64::from(self.days), 'D'),
+        ];
+        if components.iter().all(|&(length, _)| length == 0) {
+            return f.write_str("0D");
+        }
+        let negative = components.iter().all(|&(length, _)| length <= 0);
+        if negative {
+            f.write_str("-")?;
+        }
+        for (length, unit) in components {
+            if length != 0 {
+                let length = if negative { -length } else { length };
+                write!(f, "{length}{unit}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CompositePeriod {
+    fn checked_add_scaled(self, date: Date, sign: i64) -> Option<Date> {
+        let date = checked_add_signed_months(date, sign * self.total_months())?;
+        checked_add_signed_days(date, sign * i64::from(self.days))
+    }
+}
+
+impl Period for CompositePeriod {
+    fn checked_add(self, date: Date) -> Option<Date> {
+        self.checked_add_scaled(date, 1)
+    }
+
+    fn checked_sub(self, date: Date) -> Option<Date> {
+        self.checked_add_scaled(date, -1)
+    }
+
+    fn checked_add_times(self, date: Date, times: u32) -> Option<Date> {
+        self.checked_mul(i32::try_from(times).ok()?)?
+            .checked_add(date)
+    }
+
+    fn is_whole_months(self) -> bool {
+        self.days == 0 && self.total_months() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_parse() {
+        assert_eq!(
+            CompositePeriod::try_parse("1Y6M").unwrap(),
+            CompositePeriod::new(1, 6, 0)
+        );
+        assert_eq!(
+            CompositePeriod::try_parse("2w3d").unwrap(),
+            CompositePeriod::new(0, 0, 17)
+        );
+        assert_eq!(
+            CompositePeriod::try_parse("3M3M").unwrap(),
+            CompositePeriod::new(0, 6, 0)
+        );
+        assert_eq!(
+            CompositePeriod::try_parse("-1Y6M").unwrap(),
+            CompositePeriod::new(-1, -6, 0)
+        );
+        assert_eq!(
+            CompositePeriod::try_parse("1Y-6M").unwrap(),
+            CompositePeriod::new(1, -6, 0)
+        );
+        for text in ["", "-", "1Y6", "Y", "1Y-", "1Y 6M"] {
+            assert!(CompositePeriod::try_parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_normalized() {
+        let period = CompositePeriod::new(1, 30, 45);
+        assert_eq!(period.normalized(), CompositePeriod::new(3, 6, 45));
+        assert_eq!(period.normalized().to_string(), "3Y6M45D");
+        assert_eq!(CompositePeriod::default().to_string(), "0D");
+        let period = CompositePeriod::new(1, -18, 0);
+        assert_eq!(period.normalized(), CompositePeriod::new(0, -6, 0));
+        assert_eq!(period.to_string(), "1Y-18M");
+        assert_eq!(period.normalized().to_string(), "-6M");
+        assert_eq!(
+            CompositePeriod::try_parse("-1Y6M3D").unwrap().to_string(),
+            "-1Y6M3D"
+        );
+        let period = CompositePeriod::new(1, 6, 0);
+        assert_eq!(
+            period.checked_mul(2).unwrap().normalized().to_string(),
+            "3Y"
+        );
+        assert!(period.checked_mul(i32::MAX).is_none());
+        assert_eq!(period.to_tenor().unwrap().to_string(), "18M");
+        assert_eq!(
+            period
+                .checked_mul(2)
+                .unwrap()
+                .to_tenor()
+                .unwrap()
+                .to_string(),
+            "3Y"
+        );
+        assert_eq!(
+            CompositePeriod::new(0, 0, 14)
+                .to_tenor()
+                .unwrap()
+                .to_string(),
+            "2W"
+        );
+        assert!(CompositePeriod::new(0, 1, 1).to_tenor().is_none());
+    }
+
+    #[test]
+    fn test_checked_add() {
+        // the months are clamped once, after the years and months are combined
+        let date = Date::from_ymd(2023, 8, 31).unwrap();
+        let period = CompositePeriod::new(0, 6, 1);
+        assert_eq!(
+            period.checked_add(date).unwrap(),
+            Date::from_ymd(2024, 3, 1).unwrap()
+        );
+        assert_eq!(
+            CompositePeriod::new(1, 6, 0).checked_add(date),
+            CompositePeriod::new(0, 18, 0).checked_add(date)
+        );
+        assert_eq!(
+            period.checked_sub(date).unwrap(),
+            Date::from_ymd(2023, 2, 27).unwrap()
+        );
+        assert_eq!(
+            period.checked_neg().unwrap().checked_add(date),
+            period.checked_sub(date)
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::date::Date;
 use crate::period::Period;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Days(pub(crate) u64);
 
 impl Days {
@@ -15,4 +16,12 @@ impl Period for Days {
     fn checked_add(self, date: Date) -> Option<Date> {
         date.checked_add_days(self)
     }
+
+    fn checked_sub(self, date: Date) -> Option<Date> {
+        date.checked_sub_days(self)
+    }
+
+    fn checked_add_times(self, date: Date, times: u32) -> Option<Date> {
+        date.checked_add_days(Self(self.0.checked_mul(u64::from(times))?))
+    }
 }
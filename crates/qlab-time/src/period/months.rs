@@ -2,6 +2,7 @@ use crate::date::Date;
 use crate::period::Period;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Months(pub(crate) u32);
 
 impl Months {
@@ -15,4 +16,16 @@ impl Period for Months {
     fn checked_add(self, date: Date) -> Option<Date> {
         date.checked_add_months(self)
     }
+
+    fn is_whole_months(self) -> bool {
+        self.0 != 0
+    }
+
+    fn checked_sub(self, date: Date) -> Option<Date> {
+        date.checked_sub_months(self)
+    }
+
+    fn checked_add_times(self, date: Date, times: u32) -> Option<Date> {
+        date.checked_add_months(Self(self.0.checked_mul(times)?))
+    }
 }
@@ -0,0 +1,348 @@
+use crate::date::Date;
+use crate::period::days::Days;
+use crate::period::months::Months;
+use crate::period::years::Years;
+use crate::period::{checked_add_signed_days, checked_add_signed_months, Period};
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::{QLabError, QLabResult};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// The unit of a [`Tenor`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TenorUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TenorUnit {
+    /// Returns the letter quoting the unit in a tenor string.
+    #[must_use]
+    pub const fn symbol(self) -> char {
+        match self {
+            Self::Day => 'D',
+            Self::Week => 'W',
+            Self::Month => 'M',
+            Self::Year => 'Y',
+        }
+    }
+}
+
+/// A period quoted as a number of days, weeks, months or years, as in the tenor strings "1D", "6W", "3M" or "10Y".
+///
+/// A negative length, as in "-2D", rolls dates backwards.
+///
+/// Tenors compare by length across units: "12M" equals "1Y" and "14D" equals "2W", as [`Tenor::normalized`] shows.
+/// Days and months are ordered by approximating a month as 365.25 / 12 days, so that tenors quoted in different units,
+/// e.g. the pillars of a curve, sort into a canonical order; tenors of the same approximate length but different units,
+/// as "487D" and "16M", are ordered days first.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::period::tenor::Tenor;
+///
+/// let tenor: Tenor = "3M".parse().unwrap();
+/// let date = Date::from_ymd(2024, 3, 29).unwrap();
+/// let rolled = date
+///     .checked_roll(tenor, &WeekendsOnly, DateRolling::Following)
+///     .unwrap();
+/// assert_eq!(rolled, Date::from_ymd(2024, 7, 1).unwrap());
+/// assert_eq!(tenor.to_string(), "3M");
+///
+/// let mut pillars: Vec<Tenor> = ["1Y", "6M", "2W", "12M", "1M", "10D"]
+///     .iter()
+///     .map(|text| text.parse().unwrap())
+///     .collect();
+/// pillars.sort();
+/// pillars.dedup();
+/// let pillars: Vec<_> = pillars.iter().map(Tenor::to_string).collect();
+/// assert_eq!(pillars, ["10D", "2W", "1M", "6M", "1Y"]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Tenor {
+    length: i32,
+    unit: TenorUnit,
+}
+
+impl Tenor {
+    #[must_use]
+    pub const fn new(length: i32, unit: TenorUnit) -> Self {
+        Self { length, unit }
+    }
+
+    /// Parses a tenor string made of an optionally signed number followed by one of the units `D`, `W`, `M` or `Y`.
+    /// Lowercase units and surrounding whitespace are accepted.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `text` is not a tenor string.
+    pub fn try_parse(text: &str) -> QLabResult<Self> {
+        let invalid = || InvalidInput(format!("invalid tenor: {text:?}").into()).into();
+        let trimmed = text.trim();
+        let mut chars = trimmed.chars();
+        let unit = match chars.next_back().map(|unit| unit.to_ascii_uppercase()) {
+            Some('D') => TenorUnit::Day,
+            Some('W') => TenorUnit::Week,
+            Some('M') => TenorUnit::Month,
+            Some('Y') => TenorUnit::Year,
+            _ => return Err(invalid()),
+        };
+        let number = chars.as_str();
+        let digits = number.strip_prefix(['+', '-']).unwrap_or(number);
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let length = number.parse().map_err(|_| invalid())?;
+        Ok(Self::new(length, unit))
+    }
+
+    #[must_use]
+    pub fn length(&self) -> i32 {
+        self.length
+    }
+
+    #[must_use]
+    pub fn unit(&self) -> TenorUnit {
+        self.unit
+    }
+
+    /// Returns the equal tenor in the largest unit that divides it, e.g. "2W" for "14D" and "1Y" for "12M".
+    /// A zero tenor of any unit is normalized to "0D".
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        match self.unit {
+            _ if self.length == 0 => Self::new(0, TenorUnit::Day),
+            TenorUnit::Day if self.length % 7 == 0 => Self::new(self.length / 7, TenorUnit::Week),
+            TenorUnit::Month if self.length % 12 == 0 => {
+                Self::new(self.length / 12, TenorUnit::Year)
+            }
+            _ => *self,
+        }
+    }
+
+    /// Returns the tenor multiplied by `factor`, e.g. "6M" for three times "2M", or `None` if the length overflows.
+    #[must_use]
+    pub fn checked_mul(&self, factor: i32) -> Option<Self> {
+        Some(Self::new(self.length.checked_mul(factor)?, self.unit))
+    }
+
+    /// Returns the length in days of a tenor in days or weeks and in months of a tenor in months or years,
+    /// together with whether the length is in months.
+    fn base_length(self) -> (bool, i64) {
+        let length = i64::from(self.length);
+        match self.unit {
+            _ if self.length == 0 => (false, 0),
+            TenorUnit::Day => (false, length),
+            TenorUnit::Week => (false, 7 * length),
+            TenorUnit::Month => (true, length),
+            TenorUnit::Year => (true, 12 * length),
+        }
+    }
+}
+
+impl PartialEq for Tenor {
+    fn eq(&self, other: &Self) -> bool {
+        self.base_length() == other.base_length()
+    }
+}
+
+impl Eq for Tenor {}
+
+impl Hash for Tenor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.base_length().hash(state);
+    }
+}
+
+impl PartialOrd for Tenor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Tenor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // a month of 365.25 / 12 days is 1461 / 48 days
+        let key = |tenor: &Self| {
+            let (in_months, length) = tenor.base_length();
+            (
+                if in_months {
+                    1461 * length
+                } else {
+                    48 * length
+                },
+                in_months,
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl TryFrom<Days> for Tenor {
+    type Error = QLabError;
+
+    fn try_from(days: Days) -> Result<Self, Self::Error> {
+        let length = i32::try_from(days.0)
+            .map_err(|_| InvalidInput(format!("{} days overflow a tenor", days.0).into()))?;
+        Ok(Self::new(length, TenorUnit::Day))
+    }
+}
+
+impl TryFrom<Months> for Tenor {
+    type Error = QLabError;
+
+    fn try_from(months: Months) -> Result<Self, Self::Error> {
+        let length = i32::try_from(months.0)
+            .map_err(|_| InvalidInput(format!("{} months overflow a tenor", months.0).into()))?;
+        Ok(Self::new(length, TenorUnit::Month))
+    }
+}
+
+impl From<Years> for Tenor {
+    fn from(years: Years) -> Self {
+        Self::new(years.0, TenorUnit::Year)
+    }
+}
+
+impl FromStr for Tenor {
+    type Err = QLabError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_parse(s)
+    }
+}
+
+impl fmt::Display for Tenor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.length, self.unit.symbol())
+    }
+}
+
+impl Tenor {
+    fn checked_add_length(self, date: Date, length: i64) -> Option<Date> {
+        match self.unit {
+            TenorUnit::Day => checked_add_signed_days(date, length),
+            TenorUnit::Week => checked_add_signed_days(date, 7 * length),
+            TenorUnit::Month => checked_add_signed_months(date, length),
+            TenorUnit::Year => checked_add_signed_months(date, 12 * length),
+        }
+    }
+}
+
+impl Period for Tenor {
+    fn checked_add(self, date: Date) -> Option<Date> {
+        self.checked_add_length(date, i64::from(self.length))
+    }
+
+    fn checked_sub(self, date: Date) -> Option<Date> {
+        self.checked_add_length(date, -i64::from(self.length))
+    }
+
+    fn checked_add_times(self, date: Date, times: u32) -> Option<Date> {
+        self.checked_mul(i32::try_from(times).ok()?)?
+            .checked_add(date)
+    }
+
+    fn is_whole_months(self) -> bool {
+        self.length != 0 && matches!(self.unit, TenorUnit::Month | TenorUnit::Year)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_parse() {
+        assert_eq!(
+            Tenor::try_parse("1D").unwrap(),
+            Tenor::new(1, TenorUnit::Day)
+        );
+        assert_eq!(
+            Tenor::try_parse("6w").unwrap(),
+            Tenor::new(6, TenorUnit::Week)
+        );
+        assert_eq!(
+            Tenor::try_parse(" 10Y ").unwrap(),
+            Tenor::new(10, TenorUnit::Year)
+        );
+        assert_eq!(
+            Tenor::try_parse("-3M").unwrap(),
+            Tenor::new(-3, TenorUnit::Month)
+        );
+        assert_eq!(
+            Tenor::try_parse("+2d").unwrap(),
+            Tenor::new(2, TenorUnit::Day)
+        );
+        for text in [
+            "",
+            "M",
+            "3",
+            "--3M",
+            "+-3M",
+            "3.5M",
+            "3X",
+            "1Y6M",
+            "99999999999D",
+        ] {
+            assert!(Tenor::try_parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let date = Date::from_ymd(2024, 1, 31).unwrap();
+        let add = |text: &str| text.parse::<Tenor>().unwrap().checked_add(date).unwrap();
+        assert_eq!(add("1D"), Date::from_ymd(2024, 2, 1).unwrap());
+        assert_eq!(add("2W"), Date::from_ymd(2024, 2, 14).unwrap());
+        assert_eq!(add("1M"), Date::from_ymd(2024, 2, 29).unwrap());
+        assert_eq!(add("10Y"), Date::from_ymd(2034, 1, 31).unwrap());
+        assert_eq!(add("-2D"), Date::from_ymd(2024, 1, 29).unwrap());
+        assert_eq!(add("-2M"), Date::from_ymd(2023, 11, 30).unwrap());
+        let tenor = Tenor::new(1, TenorUnit::Month);
+        assert_eq!(
+            tenor.checked_sub(date).unwrap(),
+            Date::from_ymd(2023, 12, 31).unwrap()
+        );
+        assert_eq!(
+            Tenor::new(-1, TenorUnit::Year).checked_sub(date).unwrap(),
+            Date::from_ymd(2025, 1, 31).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ordering() {
+        let tenor = |text: &str| text.parse::<Tenor>().unwrap();
+        assert_eq!(tenor("12M"), tenor("1Y"));
+        assert_eq!(tenor("14D"), tenor("2W"));
+        assert_eq!(tenor("0M"), tenor("0D"));
+        assert_ne!(tenor("30D"), tenor("1M"));
+        assert_ne!(tenor("487D"), tenor("16M"));
+        assert!(tenor("30D") < tenor("1M") && tenor("1M") < tenor("31D"));
+        assert!(tenor("364D") < tenor("1Y") && tenor("1Y") < tenor("366D"));
+        assert!(tenor("487D") < tenor("16M"));
+        assert!(tenor("-1Y") < tenor("-1M"));
+        assert_eq!(tenor("12M").normalized().to_string(), "1Y");
+        assert_eq!(tenor("18M").normalized().to_string(), "18M");
+        assert_eq!(tenor("21d").normalized().to_string(), "3W");
+        assert_eq!(tenor("0Y").normalized().to_string(), "0D");
+        assert_eq!(tenor("3M").checked_mul(4).unwrap(), tenor("1Y"));
+        assert_eq!(tenor("2W").checked_mul(-2).unwrap().to_string(), "-4W");
+        assert!(tenor("2W").checked_mul(i32::MAX).is_none());
+
+        let set: std::collections::HashSet<_> = [tenor("12M"), tenor("1Y"), tenor("7D")].into();
+        assert_eq!(set.len(), 2);
+        assert_eq!(Tenor::try_from(Months::new(24)).unwrap(), tenor("2Y"));
+        assert_eq!(Tenor::from(Years::new(2)), tenor("24M"));
+        assert_eq!(Tenor::try_from(Days::new(7)).unwrap(), tenor("1W"));
+        assert!(Tenor::try_from(Days::new(u64::MAX)).is_err());
+    }
+}
@@ -2,6 +2,7 @@ use crate::date::Date;
 use crate::period::Period;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Years(pub(crate) i32);
 
 impl Years {
@@ -15,4 +16,16 @@ impl Period for Years {
     fn checked_add(self, date: Date) -> Option<Date> {
         date.checked_add_years(self)
     }
+
+    fn is_whole_months(self) -> bool {
+        self.0 != 0
+    }
+
+    fn checked_sub(self, date: Date) -> Option<Date> {
+        date.checked_add_years(Self(self.0.checked_neg()?))
+    }
+
+    fn checked_add_times(self, date: Date, times: u32) -> Option<Date> {
+        date.checked_add_years(Self(self.0.checked_mul(i32::try_from(times).ok()?)?))
+    }
 }
@@ -0,0 +1,245 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::date_rolling::DateRolling;
+use crate::day_count::kind::DayCountKind;
+use calendar::japan::Japan;
+use calendar::target::Target;
+use calendar::unitedkingdom::UnitedKingdom;
+use calendar::unitedstates::{UnitedStates, UsFederalReserve, UsGovernmentBond, UsSettlement};
+use calendar::weekendsonly::WeekendsOnly;
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A day count fraction calculation that has been resolved from a convention name.
+pub type DayCountFn<V> = fn(Date, Date) -> QLabResult<V>;
+
+/// A shared calendar that has been resolved from a calendar name.
+pub type SharedCalendar = Arc<dyn Calendar + Send + Sync>;
+
+/// A string-keyed lookup table for market conventions.
+///
+/// Names are matched case-insensitively and ignoring surrounding whitespace,
+/// so `"act/360"`, `"ACT/360"` and `" Act/360 "` resolve to the same entry.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::registry::Registry;
+///
+/// let rollings = Registry::date_rollings();
+/// assert_eq!(rollings.try_get("MF").unwrap(), &DateRolling::ModifiedFollowing);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Registry<T> {
+    entries: HashMap<String, T>,
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::default(),
+        }
+    }
+}
+
+impl<T> Registry<T> {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn normalize(name: &str) -> String {
+        name.trim().to_uppercase()
+    }
+
+    /// Registers `value` under `name`, returning the value previously registered under the same name, if any.
+    pub fn register(&mut self, name: &str, value: T) -> Option<T> {
+        self.entries.insert(Self::normalize(name), value)
+    }
+
+    /// Returns the value registered under `name`, or `None` if the name is unknown.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.entries.get(&Self::normalize(name))
+    }
+
+    /// Returns the value registered under `name`.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `name` is not registered.
+    pub fn try_get(&self, name: &str) -> QLabResult<&T> {
+        self.get(name)
+            .ok_or_else(|| InvalidInput(format!("unknown convention name: {name}").into()).into())
+    }
+
+    /// Returns `true` if a value is registered under `name`.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(&Self::normalize(name))
+    }
+
+    /// Returns an iterator over the normalized names in the registry.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+impl Registry<DateRolling> {
+    /// Creates a registry pre-populated with the standard names of the rolling conventions.
+    #[must_use]
+    pub fn date_rollings() -> Self {
+        let mut registry = Self::new();
+        for (name, rolling) in [
+            ("NONE", DateRolling::Unadjusted),
+            ("U", DateRolling::Unadjusted),
+            ("UNADJUSTED", DateRolling::Unadjusted),
+            ("F", DateRolling::Following),
+            ("FOLLOWING", DateRolling::Following),
+            ("MF", DateRolling::ModifiedFollowing),
+            ("MODIFIEDFOLLOWING", DateRolling::ModifiedFollowing),
+            ("MODIFIED FOLLOWING", DateRolling::ModifiedFollowing),
+            ("P", DateRolling::Preceding),
+            ("PRECEDING", DateRolling::Preceding),
+            ("MP", DateRolling::ModifiedPreceding),
+            ("MODIFIEDPRECEDING", DateRolling::ModifiedPreceding),
+            ("MODIFIED PRECEDING", DateRolling::ModifiedPreceding),
+            ("N", DateRolling::Nearest),
+            ("NEAREST", DateRolling::Nearest),
+            ("HMMF", DateRolling::HalfMonthModifiedFollowing),
+            (
+                "HALFMONTHMODIFIEDFOLLOWING",
+                DateRolling::HalfMonthModifiedFollowing,
+            ),
+        ] {
+            registry.register(name, rolling);
+        }
+        registry
+    }
+}
+
+impl<V: Value> Registry<DayCountFn<V>> {
+    /// Creates a registry pre-populated with the standard names of the day count conventions.
+    #[must_use]
+    pub fn day_counts() -> Self {
+        let mut registry = Self::new();
+        for kind in DayCountKind::ALL {
+            for name in kind.aliases() {
+                registry.register(name, kind.day_count_fn());
+            }
+        }
+        registry
+    }
+}
+
+impl Registry<SharedCalendar> {
+    /// Creates a registry pre-populated with the calendars of the major financial centers.
+    #[must_use]
+    pub fn calendars() -> Self {
+        let mut registry = Self::new();
+        let target: SharedCalendar = Arc::new(Target);
+        let weekends_only: SharedCalendar = Arc::new(WeekendsOnly);
+        let japan: SharedCalendar = Arc::new(Japan);
+        let united_kingdom: SharedCalendar = Arc::new(UnitedKingdom::default());
+        let united_states: SharedCalendar = Arc::new(UnitedStates::default());
+        let settlement: SharedCalendar = Arc::new(UsSettlement);
+        let government_bond: SharedCalendar = Arc::new(UsGovernmentBond);
+        let federal_reserve: SharedCalendar = Arc::new(UsFederalReserve);
+        for (names, calendar) in [
+            (&["TARGET", "EUR"][..], target),
+            (&["WEEKENDSONLY", "WEEKENDS"][..], weekends_only),
+            (&["JAPAN", "TKY", "JP"][..], japan),
+            (&["UNITEDKINGDOM", "LON", "UK"][..], united_kingdom),
+            (&["UNITEDSTATES", "NYC", "US"][..], united_states),
+            (&["USSETTLEMENT", "US-SETTLEMENT"][..], settlement),
+            (&["USGOVERNMENTBOND", "SIFMA"][..], government_bond),
+            (&["USFEDERALRESERVE", "FEDWIRE"][..], federal_reserve),
+        ] {
+            for name in names {
+                registry.register(name, Arc::clone(&calendar));
+            }
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_rollings() {
+        let registry = Registry::date_rollings();
+        assert_eq!(
+            registry.try_get("mf").unwrap(),
+            &DateRolling::ModifiedFollowing
+        );
+        assert_eq!(
+            registry.try_get(" Following ").unwrap(),
+            &DateRolling::Following
+        );
+        assert!(registry.try_get("XYZ").is_err());
+    }
+
+    #[test]
+    fn test_day_counts() {
+        let registry = Registry::<DayCountFn<f64>>::day_counts();
+        let date1 = Date::from_ymd(2023, 1, 1).unwrap();
+        let date2 = Date::from_ymd(2023, 12, 31).unwrap();
+        let act_360 = registry.try_get("ACT/360").unwrap()(date1, date2).unwrap();
+        assert!((act_360 - 364.0 / 360.0).abs() < f64::EPSILON);
+        let thirty_360 = registry.try_get("30e/360").unwrap()(date1, date2).unwrap();
+        assert!((thirty_360 - 359.0 / 360.0).abs() < f64::EPSILON);
+        // 2023 has 260 weekdays, of which Brazil observes 11 as holidays
+        let bus_252 = registry.try_get("Bus/252").unwrap()(date1, date2).unwrap();
+        assert!((bus_252 - 249.0 / 252.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_calendars() {
+        let registry = Registry::calendars();
+        let calendar = registry.try_get("TARGET").unwrap();
+        assert!(calendar.is_holiday(Date::from_ymd(2023, 12, 25).unwrap()));
+        assert!(calendar.is_business_day(Date::from_ymd(2023, 12, 27).unwrap()));
+        // Good Friday closes the bond market but not the Federal Reserve
+        let good_friday = Date::from_ymd(2024, 3, 29).unwrap();
+        assert!(registry.try_get("SIFMA").unwrap().is_holiday(good_friday));
+        assert!(registry
+            .try_get("FEDWIRE")
+            .unwrap()
+            .is_business_day(good_friday));
+        // Independence Day on Saturday 2020-07-04 closes settlement on the Friday but not the Federal Reserve
+        let friday_before = Date::from_ymd(2020, 7, 3).unwrap();
+        assert!(registry
+            .try_get("USSETTLEMENT")
+            .unwrap()
+            .is_holiday(friday_before));
+        assert!(registry
+            .try_get("us-settlement")
+            .unwrap()
+            .is_holiday(friday_before));
+        assert!(registry
+            .try_get("FEDWIRE")
+            .unwrap()
+            .is_business_day(friday_before));
+
+        let mut registry = registry;
+        registry.register("MY_DESK", Arc::new(WeekendsOnly));
+        assert!(registry.contains("my_desk"));
+    }
+
+    #[test]
+    fn test_roll_with_registered_calendar() {
+        let calendar = Registry::calendars().try_get("UK").unwrap().clone();
+        let rolling = *Registry::date_rollings().try_get("F").unwrap();
+        let date = Date::from_ymd(2023, 12, 22).unwrap();
+        let rolled = date
+            .checked_roll(crate::period::days::Days::new(3), &*calendar, rolling)
+            .unwrap();
+        assert_eq!(rolled, Date::from_ymd(2023, 12, 27).unwrap());
+    }
+}
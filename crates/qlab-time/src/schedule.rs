@@ -0,0 +1,640 @@
+use crate::calendar::Calendar;
+use crate::date::Date;
+use crate::date_rolling::DateRolling;
+use crate::day_count::DayCount;
+use crate::frequency::Frequency;
+use crate::period::months::Months;
+use qlab_error::ComputeError::{CastNumberError, InvalidInput};
+use qlab_error::QLabResult;
+use qlab_math::value::Value;
+use std::cmp::Ordering;
+
+/// Where an irregular period is placed when the schedule does not divide evenly into regular periods.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StubPolicy {
+    /// Regular dates are generated backward from the end; the first period is shorter than regular.
+    ShortFront,
+    /// Regular dates are generated backward from the end; a short first period is merged into the second one.
+    LongFront,
+    /// Regular dates are generated forward from the start; the last period is shorter than regular.
+    ShortBack,
+    /// Regular dates are generated forward from the start; a short last period is merged into the previous one.
+    LongBack,
+}
+
+impl StubPolicy {
+    /// Returns `true` if the irregular period is placed at the start of the schedule.
+    #[must_use]
+    pub fn is_front(self) -> bool {
+        matches!(self, Self::ShortFront | Self::LongFront)
+    }
+}
+
+/// How the first or last period of a schedule compares with a regular period.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StubKind {
+    Regular,
+    Short,
+    Long,
+}
+
+/// A single accrual period of a schedule.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SchedulePeriod {
+    pub unadjusted_start: Date,
+    pub unadjusted_end: Date,
+    /// The start of accrual, adjusted to a business day.
+    pub accrual_start: Date,
+    /// The end of accrual, adjusted to a business day.
+    pub accrual_end: Date,
+    /// The payment date, which is the adjusted end of accrual.
+    pub payment_date: Date,
+    /// Whether the period is irregular.
+    pub is_stub: bool,
+}
+
+impl SchedulePeriod {
+    /// Returns the dates over which interest accrues: the adjusted accrual dates if `adjusted`,
+    /// otherwise the unadjusted ones. The payment date is adjusted either way.
+    #[must_use]
+    pub fn accrual_dates(&self, adjusted: bool) -> (Date, Date) {
+        if adjusted {
+            (self.accrual_start, self.accrual_end)
+        } else {
+            (self.unadjusted_start, self.unadjusted_end)
+        }
+    }
+}
+
+fn out_of_range(date: Date) -> qlab_error::QLabError {
+    InvalidInput(format!("date out of range while generating a schedule from {date}").into()).into()
+}
+
+/// Expands `start` to `end` into accrual periods of the given frequency.
+///
+/// Regular dates are generated from the anchor chosen by `stub` by whole multiples of the regular period,
+/// so that month ends do not drift, and are then adjusted with `calendar` and `rolling`.
+/// With `end_of_month`, an anchor on the last day of its month generates regular dates on month ends,
+/// e.g. quarterly from 2024-02-29 gives 2024-05-31 instead of 2024-05-29.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `start` is not before `end`, or if a date overflows.
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::frequency::Frequency;
+/// use qlab_time::schedule::{generate, StubPolicy};
+///
+/// let periods = generate(
+///     Date::from_ymd(2024, 2, 15).unwrap(),
+///     Date::from_ymd(2025, 1, 31).unwrap(),
+///     Frequency::SA,
+///     &WeekendsOnly,
+///     DateRolling::ModifiedFollowing,
+///     StubPolicy::ShortFront,
+///     false,
+/// )
+/// .unwrap();
+/// assert_eq!(periods.len(), 2);
+/// assert!(periods[0].is_stub);
+/// // 2024-07-31 is a Wednesday and 2025-01-31 a Friday
+/// assert_eq!(periods[0].payment_date, Date::from_ymd(2024, 7, 31).unwrap());
+/// assert_eq!(periods[1].payment_date, Date::from_ymd(2025, 1, 31).unwrap());
+/// ```
+pub fn generate(
+    start: Date,
+    end: Date,
+    frequency: Frequency,
+    calendar: &(impl Calendar + ?Sized),
+    rolling: DateRolling,
+    stub: StubPolicy,
+    end_of_month: bool,
+) -> QLabResult<Vec<SchedulePeriod>> {
+    if start >= end {
+        return Err(
+            InvalidInput(format!("start: {start} must be before end: {end}").into()).into(),
+        );
+    }
+    let months = frequency.months();
+    let backward = stub.is_front();
+    let anchor = if backward { end } else { start };
+    let end_of_month = end_of_month && anchor.is_end_of_month();
+    // the date `shift` away from the anchor, towards the other boundary
+    let regular = |shift: Months| {
+        let date = if backward {
+            end.checked_sub_months(shift)
+        } else {
+            start.checked_add_months(shift)
+        };
+        if end_of_month {
+            date.map(Date::end_of_month)
+        } else {
+            date
+        }
+    };
+    let mut dates = vec![anchor];
+    for k in 1.. {
+        let date = regular(Months::new(months * k)).ok_or_else(|| out_of_range(start))?;
+        if (backward && date <= start) || (!backward && date >= end) {
+            break;
+        }
+        dates.push(date);
+    }
+    dates.push(if backward { start } else { end });
+    if backward {
+        dates.reverse();
+    }
+
+    // a stub exists when the boundary date is not a regular date
+    let shift = u32::try_from(dates.len() - 1)
+        .ok()
+        .and_then(|num_periods| months.checked_mul(num_periods))
+        .map(Months::new)
+        .ok_or_else(|| out_of_range(start))?;
+    let has_stub = regular(shift) != Some(if backward { start } else { end });
+    if has_stub && dates.len() > 2 {
+        match stub {
+            StubPolicy::LongFront => {
+                dates.remove(1);
+            }
+            StubPolicy::LongBack => {
+                dates.remove(dates.len() - 2);
+            }
+            StubPolicy::ShortFront | StubPolicy::ShortBack => {}
+        }
+    }
+    let stub_index = match stub {
+        StubPolicy::ShortFront | StubPolicy::LongFront => 0,
+        StubPolicy::ShortBack | StubPolicy::LongBack => dates.len() - 2,
+    };
+
+    let adjust = |date: Date| {
+        calendar
+            .adjust(date, rolling)
+            .ok_or_else(|| out_of_range(date))
+    };
+    dates
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let accrual_end = adjust(pair[1])?;
+            Ok(SchedulePeriod {
+                unadjusted_start: pair[0],
+                unadjusted_end: pair[1],
+                accrual_start: adjust(pair[0])?,
+                accrual_end,
+                payment_date: accrual_end,
+                is_stub: has_stub && i == stub_index,
+            })
+        })
+        .collect()
+}
+
+/// Returns the date `k` regular periods away from the regular end of the period from `start` to `end`:
+/// before `end` for a front stub, otherwise after `start`.
+fn notional_date(
+    start: Date,
+    end: Date,
+    frequency: Frequency,
+    front: bool,
+    k: u32,
+) -> QLabResult<Date> {
+    let shift = frequency
+        .months()
+        .checked_mul(k)
+        .map(Months::new)
+        .ok_or_else(|| out_of_range(start))?;
+    let date = if front {
+        end.checked_sub_months(shift)
+    } else {
+        start.checked_add_months(shift)
+    };
+    date.ok_or_else(|| out_of_range(start))
+}
+
+/// Detects whether the period from `start` to `end` is regular, or shorter or longer than regular,
+/// comparing it with the regular period ending on `end` for a front stub, otherwise with the one starting on `start`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `start` is not before `end`, or if a date overflows.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::date::Date;
+/// use qlab_time::frequency::Frequency;
+/// use qlab_time::schedule::{stub_kind, StubKind};
+///
+/// let ymd = |month, day| Date::from_ymd(2024, month, day).unwrap();
+/// assert_eq!(stub_kind(ymd(1, 15), ymd(7, 15), Frequency::SA, true).unwrap(), StubKind::Regular);
+/// assert_eq!(stub_kind(ymd(2, 15), ymd(7, 15), Frequency::SA, true).unwrap(), StubKind::Short);
+/// assert_eq!(stub_kind(ymd(1, 15), ymd(8, 15), Frequency::SA, false).unwrap(), StubKind::Long);
+/// ```
+pub fn stub_kind(
+    start: Date,
+    end: Date,
+    frequency: Frequency,
+    front: bool,
+) -> QLabResult<StubKind> {
+    if start >= end {
+        return Err(
+            InvalidInput(format!("start: {start} must be before end: {end}").into()).into(),
+        );
+    }
+    let regular = notional_date(start, end, frequency, front, 1)?;
+    let ordering = if front {
+        regular.cmp(&start)
+    } else {
+        end.cmp(&regular)
+    };
+    Ok(match ordering {
+        Ordering::Less => StubKind::Short,
+        Ordering::Equal => StubKind::Regular,
+        Ordering::Greater => StubKind::Long,
+    })
+}
+
+/// Detects the kinds of the first and last periods of `periods`, generated at `frequency`, from their unadjusted dates.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `periods` is empty, or an error if a kind cannot be detected.
+pub fn boundary_stubs(
+    periods: &[SchedulePeriod],
+    frequency: Frequency,
+) -> QLabResult<(StubKind, StubKind)> {
+    let (Some(first), Some(last)) = (periods.first(), periods.last()) else {
+        return Err(InvalidInput("a schedule needs at least one period".into()).into());
+    };
+    Ok((
+        stub_kind(
+            first.unadjusted_start,
+            first.unadjusted_end,
+            frequency,
+            true,
+        )?,
+        stub_kind(last.unadjusted_start, last.unadjusted_end, frequency, false)?,
+    ))
+}
+
+/// Measures the period from `start` to `end` in regular periods, as the ICMA rule pays the coupon of an irregular period.
+///
+/// Notional regular periods are laid out from `end` backward for a front stub, otherwise from `start` forward;
+/// every whole notional period counts as one and the remaining part of the last one counts in proportion to its days.
+/// A short stub is thus worth less than one regular period and a long stub more.
+///
+/// # Errors
+/// Returns an `InvalidInput` error if `start` is not before `end`, or if a date overflows.
+///
+/// # Examples
+///
+/// ```
+/// use qlab_time::date::Date;
+/// use qlab_time::frequency::Frequency;
+/// use qlab_time::schedule::regular_periods;
+///
+/// let ymd = |month, day| Date::from_ymd(2024, month, day).unwrap();
+/// // a long first period of a quarterly schedule: one quarter from 2024-04-15 and 30 of the 91 days before it
+/// let periods: f64 = regular_periods(ymd(3, 16), ymd(7, 15), Frequency::Q, true).unwrap();
+/// assert!((periods - (1.0 + 30.0 / 91.0)).abs() < 1e-15);
+/// ```
+pub fn regular_periods<V: Value>(
+    start: Date,
+    end: Date,
+    frequency: Frequency,
+    front: bool,
+) -> QLabResult<V> {
+    if start >= end {
+        return Err(
+            InvalidInput(format!("start: {start} must be before end: {end}").into()).into(),
+        );
+    }
+    let days = |from: Date, to: Date| {
+        V::from_i64((to - from).abs())
+            .ok_or_else(|| CastNumberError(format!("{}", to - from).into()))
+    };
+    let (mut near, boundary) = if front { (end, start) } else { (start, end) };
+    let mut whole = V::zero();
+    let mut k = 1;
+    loop {
+        let far = notional_date(start, end, frequency, front, k)?;
+        let passed = if front { far <= start } else { far >= end };
+        if passed {
+            return Ok(whole + days(near, boundary)? / days(near, far)?);
+        }
+        whole += V::one();
+        near = far;
+        k += 1;
+    }
+}
+
+/// A schedule period with its day count fractions, calculated once when the schedule is built.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AccrualPeriod<V> {
+    pub period: SchedulePeriod,
+    /// The day count fraction between the adjusted accrual dates.
+    pub adjusted_fraction: V,
+    /// The day count fraction between the unadjusted accrual dates.
+    pub unadjusted_fraction: V,
+}
+
+impl<V: Value> AccrualPeriod<V> {
+    /// Returns the fraction between the dates returned by [`SchedulePeriod::accrual_dates`].
+    #[must_use]
+    pub fn fraction(&self, adjusted: bool) -> V {
+        if adjusted {
+            self.adjusted_fraction
+        } else {
+            self.unadjusted_fraction
+        }
+    }
+}
+
+/// Accrual periods with their day count fractions cached, so that pricing reads the fractions
+/// instead of recalculating them from dates on every valuation.
+///
+/// The fractions are only recalculated when the day count changes through [`AccrualSchedule::with_day_count`].
+///
+/// # Examples
+///
+/// ```
+/// use calendar::weekendsonly::WeekendsOnly;
+/// use qlab_time::date::Date;
+/// use qlab_time::date_rolling::DateRolling;
+/// use qlab_time::day_count::act_360::Act360;
+/// use qlab_time::day_count::thirty_360::Thirty360;
+/// use qlab_time::frequency::Frequency;
+/// use qlab_time::schedule::{AccrualSchedule, StubPolicy};
+///
+/// let schedule = AccrualSchedule::<f64>::generate::<Act360>(
+///     Date::from_ymd(2024, 1, 31).unwrap(),
+///     Date::from_ymd(2025, 1, 31).unwrap(),
+///     Frequency::SA,
+///     &WeekendsOnly,
+///     DateRolling::ModifiedFollowing,
+///     StubPolicy::ShortFront,
+/// )
+/// .unwrap();
+/// // 2024-07-31 is a Wednesday: 182 days, then 184 days
+/// assert_eq!(schedule.fractions(true), vec![182.0 / 360.0, 184.0 / 360.0]);
+/// let schedule = schedule.with_day_count::<Thirty360>().unwrap();
+/// assert_eq!(schedule.fractions(true), vec![0.5, 0.5]);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct AccrualSchedule<V> {
+    periods: Vec<AccrualPeriod<V>>,
+}
+
+impl<V: Value> AccrualSchedule<V> {
+    /// Calculates the fractions of `periods` under `DC`.
+    ///
+    /// # Errors
+    /// Returns an error if a day count fraction cannot be calculated.
+    pub fn new<DC: DayCount>(periods: &[SchedulePeriod]) -> QLabResult<Self> {
+        let periods = periods
+            .iter()
+            .map(|&period| {
+                Ok(AccrualPeriod {
+                    period,
+                    adjusted_fraction: DC::calculate_day_count_fraction(
+                        period.accrual_start,
+                        period.accrual_end,
+                    )?,
+                    unadjusted_fraction: DC::calculate_day_count_fraction(
+                        period.unadjusted_start,
+                        period.unadjusted_end,
+                    )?,
+                })
+            })
+            .collect::<QLabResult<_>>()?;
+        Ok(Self { periods })
+    }
+
+    /// Generates the periods with [`generate`], without the end-of-month rule, and calculates their fractions under `DC`.
+    ///
+    /// # Errors
+    /// Returns an error if the schedule cannot be generated or a day count fraction cannot be calculated.
+    pub fn generate<DC: DayCount>(
+        start: Date,
+        end: Date,
+        frequency: Frequency,
+        calendar: &(impl Calendar + ?Sized),
+        rolling: DateRolling,
+        stub: StubPolicy,
+    ) -> QLabResult<Self> {
+        Self::new::<DC>(&generate(
+            start, end, frequency, calendar, rolling, stub, false,
+        )?)
+    }
+
+    /// Recalculates the fractions under another day count.
+    ///
+    /// # Errors
+    /// Returns an error if a day count fraction cannot be calculated.
+    pub fn with_day_count<DC: DayCount>(self) -> QLabResult<Self> {
+        let periods: Vec<_> = self.periods.iter().map(|accrual| accrual.period).collect();
+        Self::new::<DC>(&periods)
+    }
+
+    #[must_use]
+    pub fn periods(&self) -> &[AccrualPeriod<V>] {
+        &self.periods
+    }
+
+    /// Returns the fraction of each period between adjusted dates if `adjusted`, otherwise between unadjusted dates.
+    #[must_use]
+    pub fn fractions(&self, adjusted: bool) -> Vec<V> {
+        self.periods
+            .iter()
+            .map(|accrual| accrual.fraction(adjusted))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.periods.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.periods.is_empty()
+    }
+}
+
+impl<V> IntoIterator for AccrualSchedule<V> {
+    type Item = AccrualPeriod<V>;
+    type IntoIter = std::vec::IntoIter<AccrualPeriod<V>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.periods.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_count::act_360::Act360;
+    use crate::day_count::thirty_360::Thirty360;
+    use calendar::weekendsonly::WeekendsOnly;
+
+    fn ymd(year: i32, month: u32, day: u32) -> Date {
+        Date::from_ymd(year, month, day).unwrap()
+    }
+
+    fn ends(periods: &[SchedulePeriod]) -> Vec<Date> {
+        periods.iter().map(|period| period.unadjusted_end).collect()
+    }
+
+    #[test]
+    fn test_regular() {
+        let periods = generate(
+            ymd(2024, 1, 31),
+            ymd(2025, 1, 31),
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::Unadjusted,
+            StubPolicy::ShortFront,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            ends(&periods),
+            vec![
+                ymd(2024, 4, 30),
+                ymd(2024, 7, 31),
+                ymd(2024, 10, 31),
+                ymd(2025, 1, 31)
+            ]
+        );
+        assert!(periods.iter().all(|period| !period.is_stub));
+    }
+
+    #[test]
+    fn test_stubs() {
+        let (start, end) = (ymd(2024, 2, 15), ymd(2025, 1, 31));
+        let generate = |stub| {
+            generate(
+                start,
+                end,
+                Frequency::SA,
+                &WeekendsOnly,
+                DateRolling::Unadjusted,
+                stub,
+                false,
+            )
+            .unwrap()
+        };
+        let short_front = generate(StubPolicy::ShortFront);
+        assert_eq!(ends(&short_front), vec![ymd(2024, 7, 31), ymd(2025, 1, 31)]);
+        assert!(short_front[0].is_stub && !short_front[1].is_stub);
+        let long_front = generate(StubPolicy::LongFront);
+        assert_eq!(ends(&long_front), vec![ymd(2025, 1, 31)]);
+        assert!(long_front[0].is_stub);
+        let short_back = generate(StubPolicy::ShortBack);
+        assert_eq!(ends(&short_back), vec![ymd(2024, 8, 15), ymd(2025, 1, 31)]);
+        assert!(short_back[1].is_stub);
+        let long_back = generate(StubPolicy::LongBack);
+        assert_eq!(ends(&long_back), vec![ymd(2025, 1, 31)]);
+        assert!(long_back[0].is_stub);
+
+        let kinds = |periods: &[SchedulePeriod]| boundary_stubs(periods, Frequency::SA).unwrap();
+        assert_eq!(kinds(&short_front), (StubKind::Short, StubKind::Regular));
+        assert_eq!(kinds(&long_front), (StubKind::Long, StubKind::Long));
+        assert_eq!(kinds(&short_back), (StubKind::Regular, StubKind::Short));
+        assert!(boundary_stubs(&[], Frequency::SA).is_err());
+        // 2024-02-15 is 167 of the 182 days before 2024-07-31
+        let periods: f64 = regular_periods(start, end, Frequency::SA, true).unwrap();
+        assert!((periods - (1.0 + 167.0 / 182.0)).abs() < 1e-15);
+        let periods: f64 = regular_periods(start, ymd(2024, 8, 15), Frequency::SA, false).unwrap();
+        assert!((periods - 1.0).abs() < 1e-15);
+        let periods: f64 = regular_periods(start, ymd(2024, 3, 15), Frequency::Q, false).unwrap();
+        assert!((periods - 29.0 / 90.0).abs() < 1e-15);
+        assert!(regular_periods::<f64>(end, start, Frequency::SA, true).is_err());
+        assert!(StubPolicy::LongFront.is_front() && !StubPolicy::ShortBack.is_front());
+    }
+
+    #[test]
+    fn test_adjustment() {
+        // 2024-06-30 is a Sunday: modified following rolls back into June
+        let periods = generate(
+            ymd(2024, 3, 31),
+            ymd(2024, 6, 30),
+            Frequency::M,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            StubPolicy::ShortFront,
+            false,
+        )
+        .unwrap();
+        assert_eq!(periods[2].payment_date, ymd(2024, 6, 28));
+        // regular dates keep the day of month of the anchor
+        assert_eq!(periods[2].accrual_start, ymd(2024, 5, 30));
+        // 2024-03-31 is a Sunday and the following business day is in April
+        assert_eq!(periods[0].accrual_start, ymd(2024, 3, 29));
+        assert!(periods[0].is_stub);
+        assert!(generate(
+            ymd(2024, 6, 30),
+            ymd(2024, 3, 31),
+            Frequency::M,
+            &WeekendsOnly,
+            DateRolling::Unadjusted,
+            StubPolicy::ShortFront,
+            false,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_accrual_schedule() {
+        // 2024-06-30 is a Sunday, so the second period accrues from Friday 28 June
+        let schedule = AccrualSchedule::<f64>::generate::<Act360>(
+            ymd(2024, 3, 31),
+            ymd(2024, 9, 30),
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            StubPolicy::ShortFront,
+        )
+        .unwrap();
+        assert_eq!(schedule.len(), 2);
+        assert!(!schedule.is_empty());
+        let second = schedule.periods()[1];
+        assert_eq!(second.period.accrual_start, ymd(2024, 6, 28));
+        assert!((second.adjusted_fraction - 94.0 / 360.0).abs() < 1e-15);
+        assert!((second.unadjusted_fraction - 92.0 / 360.0).abs() < 1e-15);
+        assert!((second.fraction(false) - second.unadjusted_fraction).abs() < f64::EPSILON);
+        assert_eq!(schedule.fractions(false).len(), 2);
+
+        // the periods are kept and only the fractions change with the day count
+        let thirty_360 = schedule.clone().with_day_count::<Thirty360>().unwrap();
+        assert_eq!(thirty_360.len(), schedule.len());
+        assert!((thirty_360.periods()[1].unadjusted_fraction - 0.25).abs() < 1e-15);
+        assert!((thirty_360.periods()[1].adjusted_fraction - 92.0 / 360.0).abs() < 1e-15);
+        let periods: Vec<_> = thirty_360
+            .into_iter()
+            .map(|accrual| accrual.period)
+            .collect();
+        let expected: Vec<_> = schedule
+            .periods()
+            .iter()
+            .map(|accrual| accrual.period)
+            .collect();
+        assert_eq!(periods, expected);
+
+        let empty = AccrualSchedule::<f64>::new::<Act360>(&[]).unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.fractions(true).is_empty());
+        assert!(AccrualSchedule::<f64>::generate::<Act360>(
+            ymd(2024, 9, 30),
+            ymd(2024, 3, 31),
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            StubPolicy::ShortFront,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Serializes dates, times, tenors and day count conventions as the strings they are quoted with,
+//! e.g. `"2024-03-20"`, `"1Y6M"` and `"ACT/360"`, rather than as their internal representations.
+
+use crate::date::Date;
+use crate::date_time::DateTime;
+use crate::day_count::kind::DayCountKind;
+use crate::period::composite::CompositePeriod;
+use crate::period::tenor::Tenor;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+
+macro_rules! impl_string_serde {
+    ($($ty:ty),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let text = Cow::<'de, str>::deserialize(deserializer)?;
+                    text.parse().map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+impl_string_serde!(Date, DateTime, Tenor, CompositePeriod, DayCountKind);
+
+#[cfg(test)]
+mod tests {
+    use crate::date::Date;
+    use crate::date_rolling::DateRolling;
+    use crate::date_time::DateTime;
+    use crate::frequency::Frequency;
+    use crate::period::composite::CompositePeriod;
+    use crate::period::months::Months;
+    use crate::period::tenor::Tenor;
+
+    #[test]
+    fn test_round_trip() {
+        let date = Date::from_ymd(2024, 3, 20).unwrap();
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"2024-03-20\"");
+        assert_eq!(
+            serde_json::from_str::<Date>("\"2024-03-20\"").unwrap(),
+            date
+        );
+        assert!(serde_json::from_str::<Date>("\"2024-02-30\"").is_err());
+        let close = DateTime::new(date, 16, 30, 0)
+            .unwrap()
+            .with_utc_offset(-4 * 3600)
+            .unwrap();
+        let json = serde_json::to_string(&close).unwrap();
+        assert_eq!(json, "\"2024-03-20T16:30:00-04:00\"");
+        assert_eq!(serde_json::from_str::<DateTime>(&json).unwrap(), close);
+
+        let tenor: Tenor = "18M".parse().unwrap();
+        let period: CompositePeriod = "1Y6M".parse().unwrap();
+        let trade = (
+            date,
+            tenor,
+            period,
+            Frequency::SA,
+            DateRolling::ModifiedFollowing,
+            Months::new(3),
+        );
+        let json = serde_json::to_string(&trade).unwrap();
+        assert_eq!(
+            json,
+            "[\"2024-03-20\",\"18M\",\"1Y6M\",\"SA\",\"ModifiedFollowing\",3]"
+        );
+        assert_eq!(
+            serde_json::from_str::<(Date, Tenor, CompositePeriod, Frequency, DateRolling, Months)>(
+                &json
+            )
+            .unwrap(),
+            trade
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use crate::calendar::rules::nth_weekday;
+use crate::calendar::Calendar;
+use crate::date::Date;
+use chrono::Datelike;
+
+/// A day of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Self; 7] = [
+        Self::Monday,
+        Self::Tuesday,
+        Self::Wednesday,
+        Self::Thursday,
+        Self::Friday,
+        Self::Saturday,
+        Self::Sunday,
+    ];
+
+    /// Returns the number of days from Monday, i.e. 0 for Monday and 6 for Sunday.
+    #[must_use]
+    pub const fn num_days_from_monday(self) -> u32 {
+        self as u32
+    }
+
+    /// Returns the day after, wrapping from Sunday to Monday.
+    #[must_use]
+    pub const fn succ(self) -> Self {
+        Self::ALL[(self as usize + 1) % 7]
+    }
+
+    /// Returns the day before, wrapping from Monday to Sunday.
+    #[must_use]
+    pub const fn pred(self) -> Self {
+        Self::ALL[(self as usize + 6) % 7]
+    }
+
+    /// Returns the number of days from `self` forward to the next `weekday`, from 0 to 6.
+    #[must_use]
+    pub const fn days_until(self, weekday: Self) -> u32 {
+        (7 + weekday as u32 - self as u32) % 7
+    }
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(weekday: chrono::Weekday) -> Self {
+        Self::ALL[weekday.num_days_from_monday() as usize]
+    }
+}
+
+impl From<Weekday> for chrono::Weekday {
+    fn from(weekday: Weekday) -> Self {
+        match weekday {
+            Weekday::Monday => Self::Mon,
+            Weekday::Tuesday => Self::Tue,
+            Weekday::Wednesday => Self::Wed,
+            Weekday::Thursday => Self::Thu,
+            Weekday::Friday => Self::Fri,
+            Weekday::Saturday => Self::Sat,
+            Weekday::Sunday => Self::Sun,
+        }
+    }
+}
+
+impl Date {
+    /// Returns the day of the week of the date.
+    #[must_use]
+    pub fn weekday(self) -> Weekday {
+        self.0.weekday().into()
+    }
+
+    /// Returns the first date after this one falling on `weekday`, a week later if the date itself does.
+    ///
+    /// Returns `None` if the result is out of the range of dates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    /// use qlab_time::weekday::Weekday;
+    ///
+    /// let date = Date::from_ymd(2024, 3, 20).unwrap();
+    /// assert_eq!(date.weekday(), Weekday::Wednesday);
+    /// assert_eq!(date.next_weekday(Weekday::Friday), Date::from_ymd(2024, 3, 22));
+    /// assert_eq!(date.next_weekday(Weekday::Wednesday), Date::from_ymd(2024, 3, 27));
+    /// assert_eq!(date.previous_weekday(Weekday::Friday), Date::from_ymd(2024, 3, 15));
+    /// ```
+    #[must_use]
+    pub fn next_weekday(self, weekday: Weekday) -> Option<Self> {
+        let days = match self.weekday().days_until(weekday) {
+            0 => 7,
+            days => days,
+        };
+        self.0
+            .checked_add_days(chrono::Days::new(u64::from(days)))
+            .map(Date)
+    }
+
+    /// Returns the last date before this one falling on `weekday`, a week earlier if the date itself does.
+    ///
+    /// Returns `None` if the result is out of the range of dates.
+    #[must_use]
+    pub fn previous_weekday(self, weekday: Weekday) -> Option<Self> {
+        let days = match weekday.days_until(self.weekday()) {
+            0 => 7,
+            days => days,
+        };
+        self.0
+            .checked_sub_days(chrono::Days::new(u64::from(days)))
+            .map(Date)
+    }
+
+    /// Returns the `n`-th `weekday` of `month` of `year`, counting from the end of the month if `n` is negative,
+    /// e.g. the third Wednesday of an IMM month with `n = 3` or the last Friday with `n = -1`.
+    ///
+    /// Returns `None` if the month is invalid or has no such occurrence, e.g. a fifth Monday in a month of four.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    /// use qlab_time::weekday::Weekday;
+    ///
+    /// assert_eq!(Date::nth_weekday_of_month(2024, 3, Weekday::Wednesday, 3), Date::from_ymd(2024, 3, 20));
+    /// assert_eq!(Date::nth_weekday_of_month(2024, 3, Weekday::Friday, -1), Date::from_ymd(2024, 3, 29));
+    /// assert_eq!(Date::nth_weekday_of_month(2024, 2, Weekday::Monday, 5), None);
+    /// ```
+    #[must_use]
+    pub fn nth_weekday_of_month(year: i32, month: u32, weekday: Weekday, n: i8) -> Option<Self> {
+        if n == 0 || n.unsigned_abs() > 5 || !(1..=12).contains(&month) {
+            return None;
+        }
+        nth_weekday(year, month, weekday.into(), n)
+    }
+
+    /// Returns the third Wednesday of `month` of `year`, which starts and ends IMM periods,
+    /// or `None` if the month is invalid.
+    #[must_use]
+    pub fn third_wednesday(year: i32, month: u32) -> Option<Self> {
+        Self::nth_weekday_of_month(year, month, Weekday::Wednesday, 3)
+    }
+
+    /// Returns `true` if the date is an IMM date, the third Wednesday of March, June, September or December.
+    #[must_use]
+    pub fn is_imm_date(self) -> bool {
+        self.month().is_multiple_of(3)
+            && Some(self) == Self::third_wednesday(self.year(), self.month())
+    }
+
+    /// Returns the first IMM date strictly after the date, e.g. the next roll of a futures strip.
+    ///
+    /// Together with [`Date::imm_quarter_start`], this bounds the IMM quarter of the date from its start
+    /// inclusive to its end exclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use qlab_time::date::Date;
+    ///
+    /// let date = Date::from_ymd(2024, 3, 20).unwrap();
+    /// assert!(date.is_imm_date());
+    /// assert_eq!(date.imm_quarter_start(), Some(date));
+    /// assert_eq!(date.next_imm_date(), Date::from_ymd(2024, 6, 19));
+    /// let date = Date::from_ymd(2024, 12, 31).unwrap();
+    /// assert_eq!(date.imm_quarter_start(), Date::from_ymd(2024, 12, 18));
+    /// assert_eq!(date.next_imm_date(), Date::from_ymd(2025, 3, 19));
+    /// ```
+    #[must_use]
+    pub fn next_imm_date(self) -> Option<Self> {
+        let (year, month) = (self.year(), 3 * self.quarter());
+        let date = Self::third_wednesday(year, month)?;
+        if self < date {
+            return Some(date);
+        }
+        match month {
+            12 => Self::third_wednesday(year.checked_add(1)?, 3),
+            _ => Self::third_wednesday(year, month + 3),
+        }
+    }
+
+    /// Returns the last IMM date on or before the date, the start of its IMM quarter.
+    #[must_use]
+    pub fn imm_quarter_start(self) -> Option<Self> {
+        let (year, month) = (self.year(), 3 * self.quarter());
+        let date = Self::third_wednesday(year, month)?;
+        if date <= self {
+            return Some(date);
+        }
+        match month {
+            3 => Self::third_wednesday(year.checked_sub(1)?, 12),
+            _ => Self::third_wednesday(year, month - 3),
+        }
+    }
+
+    /// Returns `true` if the date falls on a weekend day of `calendar`, see [`Calendar::is_weekend`].
+    pub fn is_weekend<C: Calendar + ?Sized>(self, calendar: &C) -> bool {
+        calendar.is_weekend(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::bespoke::BespokeCalendar;
+    use calendar::weekendsonly::WeekendsOnly;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_weekday() {
+        for weekday in Weekday::ALL {
+            assert_eq!(Weekday::from(chrono::Weekday::from(weekday)), weekday);
+            assert_eq!(weekday.succ().pred(), weekday);
+            assert_eq!(weekday.days_until(weekday.succ()), 1);
+        }
+        assert_eq!(Weekday::Sunday.succ(), Weekday::Monday);
+        assert_eq!(Weekday::Saturday.days_until(Weekday::Monday), 2);
+
+        let saturday = Date::from_ymd(2024, 3, 23).unwrap();
+        assert_eq!(saturday.weekday(), Weekday::Saturday);
+        assert_eq!(
+            saturday.next_weekday(Weekday::Monday),
+            Date::from_ymd(2024, 3, 25)
+        );
+        assert_eq!(
+            saturday.previous_weekday(Weekday::Sunday),
+            Date::from_ymd(2024, 3, 17)
+        );
+        assert!(saturday.is_weekend(&WeekendsOnly));
+        assert_eq!(
+            Date::nth_weekday_of_month(2024, 3, Weekday::Saturday, 4),
+            Some(saturday)
+        );
+        assert_eq!(
+            Date::nth_weekday_of_month(2024, 3, Weekday::Saturday, -2),
+            Some(saturday)
+        );
+        assert_eq!(
+            Date::nth_weekday_of_month(2024, 2, Weekday::Thursday, 5),
+            Date::from_ymd(2024, 2, 29)
+        );
+        assert_eq!(
+            Date::nth_weekday_of_month(2024, 3, Weekday::Monday, 0),
+            None
+        );
+        assert_eq!(
+            Date::nth_weekday_of_month(2024, 13, Weekday::Monday, 1),
+            None
+        );
+
+        // a weekend of Fridays only
+        let calendar =
+            BespokeCalendar::new("desk", HashSet::new()).with_weekend(&[chrono::Weekday::Fri]);
+        assert!(!saturday.is_weekend(&calendar));
+        assert!(saturday.pred_opt().unwrap().is_weekend(&calendar));
+
+        let imm = Date::from_ymd(2024, 6, 19).unwrap();
+        assert!(imm.is_imm_date());
+        assert!(!Date::from_ymd(2024, 7, 17).unwrap().is_imm_date());
+        assert_eq!(imm.pred_opt().unwrap().next_imm_date(), Some(imm));
+        assert_eq!(
+            imm.pred_opt().unwrap().imm_quarter_start(),
+            Date::from_ymd(2024, 3, 20)
+        );
+        let date = Date::from_ymd(2024, 1, 5).unwrap();
+        assert_eq!(date.imm_quarter_start(), Date::from_ymd(2023, 12, 20));
+        assert_eq!(date.next_imm_date(), Date::from_ymd(2024, 3, 20));
+    }
+}
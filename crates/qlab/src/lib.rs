@@ -1 +1,4 @@
-
+#[cfg(feature = "serde")]
+pub mod market_data;
+pub mod testing;
+pub mod validation;
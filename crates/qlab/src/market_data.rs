@@ -0,0 +1,472 @@
+//! End-of-day market data saved to and restored from a single JSON file, so that a valuation can be reproduced
+//! and two snapshots compared.
+//!
+//! Fitted objects hold derived state such as interpolator coefficients, so curves and vol surfaces are stored
+//! as the inputs they are fitted from, together with their day count convention and interpolation,
+//! and refitted on restore exactly as they were built.
+
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_instrument::fixing_store::FixingStore;
+use qlab_instrument::pricing_cache::VersionedQuotes;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_model::vol_surface::{DatedVolSurface, SmileQuotes, TimeInterpolation, VolSurface};
+use qlab_termstructure::curve_interpolation::{CurveInterpolation, CurveInterpolator};
+use qlab_termstructure::yield_curve::{boxed_yield_curve, DiscountCurve, YieldCurve};
+use qlab_time::date::Date;
+use qlab_time::day_count::kind::DayCountKind;
+use qlab_time::day_count::DayCount;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The pillars of a yield curve as of its settlement date, with the conventions it is fitted by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveData<V> {
+    pub settlement_date: Date,
+    pub day_count: DayCountKind,
+    pub interpolation: CurveInterpolation,
+    pub maturities: Vec<Date>,
+    pub spot_yields: Vec<V>,
+}
+
+/// The smile quotes of a vol surface as of its reference date, with the conventions it is built by.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolSurfaceData<V> {
+    pub reference_date: Date,
+    /// The day count measuring the time from the reference date to an expiry date.
+    pub day_count: DayCountKind,
+    pub time_interpolation: TimeInterpolation,
+    /// One `(expiry, forward, strikes_and_vols)` entry per expiry, as passed to [`VolSurface::try_new`].
+    pub smiles: Vec<SmileQuotes<V>>,
+}
+
+/// Returns the convention of the day count type `D`, which a snapshot must record to refit the same object.
+fn day_count_kind<D: DayCount + 'static>() -> QLabResult<DayCountKind> {
+    DayCountKind::of::<D>().ok_or_else(|| {
+        InvalidInput(
+            format!(
+                "{} is not a day count convention that can be saved",
+                std::any::type_name::<D>()
+            )
+            .into(),
+        )
+        .into()
+    })
+}
+
+/// Checks that the day count type `D` a caller restores an object with is the one it was saved with.
+fn check_day_count<D: DayCount + 'static>(id: &str, saved: DayCountKind) -> QLabResult<()> {
+    let requested = day_count_kind::<D>()?;
+    if requested != saved {
+        return Err(
+            InvalidInput(format!("{id} was saved with {saved}, not {requested}").into()).into(),
+        );
+    }
+    Ok(())
+}
+
+/// Quotes, curves, vol surfaces and fixings of a valuation date, keyed by identifier.
+///
+/// # Examples
+///
+/// ```
+/// use qlab::market_data::MarketData;
+/// use qlab_termstructure::curve_interpolation::{CurveInterpolation, CurveInterpolator};
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let valuation_date = Date::from_ymd(2024, 6, 28).unwrap();
+/// let maturities = [Date::from_ymd(2025, 6, 30).unwrap(), Date::from_ymd(2029, 6, 29).unwrap()];
+/// let curve: YieldCurve<Act365, CurveInterpolator<f64>> = YieldCurve::with_interpolation(
+///     valuation_date,
+///     &maturities,
+///     &[0.05, 0.042],
+///     CurveInterpolation::MonotoneConvexForward,
+/// )
+/// .unwrap();
+/// let market = MarketData::new(valuation_date)
+///     .with_quote("USD.SOFR.1Y", 0.0512)
+///     .with_curve("USD.SOFR", &curve)
+///     .unwrap();
+///
+/// let restored = MarketData::from_json(&market.to_json().unwrap()).unwrap();
+/// assert_eq!(restored, market);
+/// let restored_curve = restored.curve::<Act365>("USD.SOFR").unwrap();
+/// assert_eq!(restored_curve.interpolation(), CurveInterpolation::MonotoneConvexForward);
+/// assert_eq!(restored_curve.pillars(), curve.pillars());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketData<V> {
+    valuation_date: Date,
+    quotes: BTreeMap<String, V>,
+    curves: BTreeMap<String, CurveData<V>>,
+    vol_surfaces: BTreeMap<String, VolSurfaceData<V>>,
+    fixings: BTreeMap<String, BTreeMap<Date, V>>,
+}
+
+impl<V: Value> MarketData<V> {
+    /// Creates empty market data as of `valuation_date`.
+    #[must_use]
+    pub fn new(valuation_date: Date) -> Self {
+        Self {
+            valuation_date,
+            quotes: BTreeMap::new(),
+            curves: BTreeMap::new(),
+            vol_surfaces: BTreeMap::new(),
+            fixings: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the quote `quote_id`, replacing any previous one.
+    #[must_use]
+    pub fn with_quote(mut self, quote_id: &str, value: V) -> Self {
+        self.quotes.insert(quote_id.to_string(), value);
+        self
+    }
+
+    /// Stores the pillars, day count convention and interpolation of `curve` as `curve_id`,
+    /// replacing any previous curve.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `D` is not the type of a [`DayCountKind`].
+    pub fn with_curve<D: DayCount + 'static>(
+        mut self,
+        curve_id: &str,
+        curve: &YieldCurve<D, CurveInterpolator<V>>,
+    ) -> QLabResult<Self> {
+        self.curves.insert(
+            curve_id.to_string(),
+            CurveData {
+                settlement_date: curve.settlement_date(),
+                day_count: day_count_kind::<D>()?,
+                interpolation: curve.interpolation(),
+                maturities: curve.maturities().to_vec(),
+                spot_yields: curve.pillars().iter().map(|&(_, y)| y).collect(),
+            },
+        );
+        Ok(self)
+    }
+
+    /// Stores the smiles of a vol surface measuring time with `D` and interpolating smiles with `I`
+    /// as `surface_id`, replacing any previous surface.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `D` is not the type of a [`DayCountKind`],
+    /// or an error if the smiles do not make a valid [`VolSurface`].
+    pub fn with_vol_surface<D: DayCount + 'static, I: Interpolator<Value = V>>(
+        mut self,
+        surface_id: &str,
+        reference_date: Date,
+        smiles: &[SmileQuotes<V>],
+        time_interpolation: TimeInterpolation,
+    ) -> QLabResult<Self> {
+        let day_count = day_count_kind::<D>()?;
+        VolSurface::<I>::try_new(smiles)?;
+        self.vol_surfaces.insert(
+            surface_id.to_string(),
+            VolSurfaceData {
+                reference_date,
+                day_count,
+                time_interpolation,
+                smiles: smiles.to_vec(),
+            },
+        );
+        Ok(self)
+    }
+
+    /// Records the fixing of `index` on `date`, replacing any previous one.
+    #[must_use]
+    pub fn with_fixing(mut self, index: &str, date: Date, value: V) -> Self {
+        self.fixings
+            .entry(index.to_string())
+            .or_default()
+            .insert(date, value);
+        self
+    }
+
+    #[must_use]
+    pub fn valuation_date(&self) -> Date {
+        self.valuation_date
+    }
+
+    #[must_use]
+    pub fn quote(&self, quote_id: &str) -> Option<V> {
+        self.quotes.get(quote_id).copied()
+    }
+
+    /// Returns the quotes as a new [`VersionedQuotes`] snapshot.
+    #[must_use]
+    pub fn versioned_quotes(&self) -> VersionedQuotes<V> {
+        let mut quotes = VersionedQuotes::new();
+        for (quote_id, &value) in &self.quotes {
+            quotes.set(quote_id, value);
+        }
+        quotes
+    }
+
+    fn curve_data(&self, curve_id: &str) -> QLabResult<&CurveData<V>> {
+        Ok(self
+            .curves
+            .get(curve_id)
+            .ok_or_else(|| InvalidInput(format!("no curve: {curve_id}").into()))?)
+    }
+
+    /// Refits the curve `curve_id` with the interpolation it was saved with.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if there is no such curve or it was saved with a day count other than `D`,
+    /// or an error if refitting fails.
+    pub fn curve<D: DayCount + 'static>(
+        &self,
+        curve_id: &str,
+    ) -> QLabResult<YieldCurve<D, CurveInterpolator<V>>> {
+        let curve = self.curve_data(curve_id)?;
+        check_day_count::<D>(curve_id, curve.day_count)?;
+        YieldCurve::with_interpolation(
+            curve.settlement_date,
+            &curve.maturities,
+            &curve.spot_yields,
+            curve.interpolation,
+        )
+    }
+
+    /// Refits the curve `curve_id` with the day count convention and interpolation it was saved with,
+    /// for callers that do not know them in advance.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if there is no such curve, or an error if refitting fails.
+    pub fn discount_curve(&self, curve_id: &str) -> QLabResult<Box<dyn DiscountCurve<V>>> {
+        let curve = self.curve_data(curve_id)?;
+        boxed_yield_curve(
+            curve.day_count,
+            curve.settlement_date,
+            &curve.maturities,
+            &curve.spot_yields,
+            curve.interpolation,
+        )
+    }
+
+    /// Refits the vol surface `surface_id` with a default-configured smile interpolator `I`
+    /// and the time interpolation it was saved with.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if there is no such surface or it was saved with a day count other than `D`,
+    /// or an error if refitting fails.
+    pub fn vol_surface<D: DayCount + 'static, I: Interpolator<Value = V>>(
+        &self,
+        surface_id: &str,
+    ) -> QLabResult<DatedVolSurface<D, I>> {
+        let surface = self
+            .vol_surfaces
+            .get(surface_id)
+            .ok_or_else(|| InvalidInput(format!("no vol surface: {surface_id}").into()))?;
+        check_day_count::<D>(surface_id, surface.day_count)?;
+        Ok(DatedVolSurface::new(
+            surface.reference_date,
+            VolSurface::try_new(&surface.smiles)?
+                .with_time_interpolation(surface.time_interpolation),
+        ))
+    }
+
+    /// Returns the fixings as a [`FixingStore`].
+    #[must_use]
+    pub fn fixing_store(&self) -> FixingStore<V> {
+        let mut store = FixingStore::new();
+        for (index, fixings) in &self.fixings {
+            for (&date, &value) in fixings {
+                store.insert(index, date, value);
+            }
+        }
+        store
+    }
+
+    /// Returns the identifiers of the quotes whose values differ from, or are missing in, `other`.
+    #[must_use]
+    pub fn changed_quotes(&self, other: &Self) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .quotes
+            .iter()
+            .filter(|&(quote_id, value)| other.quotes.get(quote_id) != Some(value))
+            .map(|(quote_id, _)| quote_id.clone())
+            .collect();
+        changed.extend(
+            other
+                .quotes
+                .keys()
+                .filter(|quote_id| !self.quotes.contains_key(*quote_id))
+                .cloned(),
+        );
+        changed.sort();
+        changed
+    }
+}
+
+impl<V: Serialize + DeserializeOwned> MarketData<V> {
+    /// Serializes the market data as JSON.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a value cannot be serialized.
+    pub fn to_json(&self) -> QLabResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| InvalidInput(err.to_string().into()).into())
+    }
+
+    /// Parses market data serialized by [`MarketData::to_json`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if `json` is not valid market data.
+    pub fn from_json(json: &str) -> QLabResult<Self> {
+        serde_json::from_str(json).map_err(|err| InvalidInput(err.to_string().into()).into())
+    }
+
+    /// Writes the market data to `path` as JSON.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> QLabResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()?)
+            .map_err(|err| InvalidInput(format!("{}: {err}", path.display()).into()).into())
+    }
+
+    /// Reads market data written by [`MarketData::save`].
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if the file cannot be read or is not valid market data.
+    pub fn load(path: impl AsRef<Path>) -> QLabResult<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|err| InvalidInput(format!("{}: {err}", path.display()).into()))?;
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_save_and_load() {
+        let valuation_date = Date::from_ymd(2024, 6, 28).unwrap();
+        let maturities = [
+            Date::from_ymd(2025, 6, 30).unwrap(),
+            Date::from_ymd(2029, 6, 29).unwrap(),
+        ];
+        let curve: YieldCurve<Act365, CurveInterpolator<f64>> = YieldCurve::with_interpolation(
+            valuation_date,
+            &maturities,
+            &[0.05, 0.042],
+            CurveInterpolation::LogLinearDiscount,
+        )
+        .unwrap();
+        let smiles = vec![
+            (0.5, 100.0, vec![(90.0, 0.25), (100.0, 0.2), (110.0, 0.22)]),
+            (1.0, 101.0, vec![(90.0, 0.24), (100.0, 0.21), (110.0, 0.22)]),
+        ];
+        let fixing_date = Date::from_ymd(2024, 6, 27).unwrap();
+        let market = MarketData::new(valuation_date)
+            .with_quote("USD.SOFR.1Y", 0.0512)
+            .with_quote("USD.SOFR.5Y", 0.042)
+            .with_curve("USD.SOFR", &curve)
+            .unwrap()
+            .with_vol_surface::<Act365, Linear<f64>>(
+                "SPX",
+                valuation_date,
+                &smiles,
+                TimeInterpolation::Volatility,
+            )
+            .unwrap()
+            .with_fixing("SOFR", fixing_date, 0.0533);
+
+        // unique per process so that concurrent test runs do not share the file
+        let path = std::env::temp_dir().join(format!(
+            "qlab_test_market_data_save_and_load_{}.json",
+            std::process::id()
+        ));
+        market.save(&path).unwrap();
+        let restored = MarketData::<f64>::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored, market);
+        assert!(MarketData::<f64>::load(&path).is_err());
+
+        // the curve is refitted with the day count and interpolation it was saved with
+        let restored_curve = restored.curve::<Act365>("USD.SOFR").unwrap();
+        let boxed_curve = restored.discount_curve("USD.SOFR").unwrap();
+        let linear: YieldCurve<Act365, Linear<f64>> =
+            YieldCurve::new(valuation_date, &maturities, &[0.05, 0.042]).unwrap();
+        for date in [
+            valuation_date,
+            Date::from_ymd(2025, 1, 2).unwrap(),
+            Date::from_ymd(2027, 1, 4).unwrap(),
+            Date::from_ymd(2029, 6, 29).unwrap(),
+        ] {
+            let expected = curve.discount_factor(valuation_date, date).unwrap();
+            let restored = restored_curve
+                .discount_factor(valuation_date, date)
+                .unwrap();
+            let boxed = boxed_curve.discount_factor(valuation_date, date).unwrap();
+            assert!((restored - expected).abs() < 1e-15, "{date}");
+            assert!((boxed - expected).abs() < 1e-15, "{date}");
+        }
+        // log-linear discounting differs from the default linear interpolation between the pillars
+        let date = Date::from_ymd(2027, 1, 4).unwrap();
+        assert!(
+            (restored_curve
+                .discount_factor(valuation_date, date)
+                .unwrap()
+                - linear.discount_factor(valuation_date, date).unwrap())
+            .abs()
+                > 1e-6
+        );
+        assert!(restored.curve::<Act360>("USD.SOFR").is_err());
+
+        let surface = restored.vol_surface::<Act365, Linear<f64>>("SPX").unwrap();
+        assert_eq!(
+            surface.surface().time_interpolation(),
+            TimeInterpolation::Volatility
+        );
+        assert!((surface.surface().black_vol(0.5, 100.0).unwrap() - 0.2).abs() < 1e-12);
+        assert!(restored.vol_surface::<Act360, Linear<f64>>("SPX").is_err());
+        assert_eq!(
+            restored.fixing_store().fixing("SOFR", fixing_date),
+            Some(0.0533)
+        );
+        assert_eq!(
+            restored.versioned_quotes().quote("USD.SOFR.5Y"),
+            Some(0.042)
+        );
+
+        assert!(restored.curve::<Act365>("EUR.ESTR").is_err());
+        assert!(restored.discount_curve("EUR.ESTR").is_err());
+        assert!(restored.vol_surface::<Act365, Linear<f64>>("NDX").is_err());
+        assert!(MarketData::<f64>::from_json("{}").is_err());
+        assert!(MarketData::new(valuation_date)
+            .with_vol_surface::<Act365, Linear<f64>>(
+                "SPX",
+                valuation_date,
+                &[],
+                TimeInterpolation::TotalVariance
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_changed_quotes() {
+        let valuation_date = Date::from_ymd(2024, 6, 28).unwrap();
+        let today = MarketData::new(valuation_date)
+            .with_quote("A", 1.0)
+            .with_quote("B", 2.0)
+            .with_quote("C", 3.0);
+        let tomorrow = today.clone().with_quote("B", 2.5).with_quote("D", 4.0);
+        assert_eq!(today.changed_quotes(&tomorrow), ["B", "D"]);
+        assert_eq!(tomorrow.changed_quotes(&today), ["B", "D"]);
+        assert!(today.changed_quotes(&today.clone()).is_empty());
+    }
+}
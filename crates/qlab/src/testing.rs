@@ -0,0 +1,206 @@
+//! Deterministic synthetic markets for tests and examples.
+
+use qlab_error::ComputeError::InvalidInput;
+use qlab_error::QLabResult;
+use qlab_instrument::bond_curve::nelson_siegel;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_model::vol_surface::VolSurface;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+use qlab_time::period::months::Months;
+
+pub use qlab_instrument::testing::semi_annual_bond;
+
+/// The shape of the spot yields of a synthetic curve, as a function of the year fraction to the pillar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveShape<V> {
+    /// The same yield at every pillar.
+    Flat { rate: V },
+    /// Yields moving linearly in time from `short` at the first pillar to `long` at the last one.
+    Steepening { short: V, long: V },
+    /// The Nelson-Siegel yields `b0 + b1 (1 - e^(-t/tau)) / (t/tau) + b2 ((1 - e^(-t/tau)) / (t/tau) - e^(-t/tau))`,
+    /// which are humped for a positive `b2`.
+    NelsonSiegel { b0: V, b1: V, b2: V, tau: V },
+}
+
+/// Builds a yield curve of a given shape on standard pillars.
+///
+/// # Examples
+///
+/// ```
+/// use qlab::testing::{CurveShape, SyntheticCurveBuilder};
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_termstructure::yield_curve::YieldCurve;
+/// use qlab_time::date::Date;
+/// use qlab_time::day_count::act_365::Act365;
+///
+/// let settlement_date = Date::from_ymd(2024, 1, 2).unwrap();
+/// let curve: YieldCurve<Act365, Linear<f64>> =
+///     SyntheticCurveBuilder::new(settlement_date, CurveShape::Flat { rate: 0.03 })
+///         .build()
+///         .unwrap();
+/// let one_year = Date::from_ymd(2025, 1, 1).unwrap();
+/// let discount_factor = curve.discount_factor(settlement_date, one_year).unwrap();
+/// assert!((discount_factor - (-0.03_f64).exp()).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticCurveBuilder<V> {
+    settlement_date: Date,
+    shape: CurveShape<V>,
+    tenors_in_months: Vec<u32>,
+}
+
+impl<V: Value> SyntheticCurveBuilder<V> {
+    /// Creates a builder with pillars from one month to thirty years after `settlement_date`.
+    #[must_use]
+    pub fn new(settlement_date: Date, shape: CurveShape<V>) -> Self {
+        Self {
+            settlement_date,
+            shape,
+            tenors_in_months: vec![1, 3, 6, 12, 24, 36, 60, 84, 120, 180, 240, 360],
+        }
+    }
+
+    /// Replaces the pillars by the given tenors in months after the settlement date.
+    #[must_use]
+    pub fn with_tenors_in_months(mut self, tenors_in_months: &[u32]) -> Self {
+        self.tenors_in_months = tenors_in_months.to_vec();
+        self
+    }
+
+    /// Returns the pillar dates of the curve.
+    ///
+    /// # Errors
+    /// Returns an `InvalidInput` error if a pillar date overflows.
+    pub fn maturities(&self) -> QLabResult<Vec<Date>> {
+        self.tenors_in_months
+            .iter()
+            .map(|&months| {
+                self.settlement_date
+                    .checked_add_months(Months::new(months))
+                    .ok_or_else(|| {
+                        InvalidInput(
+                            format!("{months} months after {} overflows", self.settlement_date)
+                                .into(),
+                        )
+                        .into()
+                    })
+            })
+            .collect()
+    }
+
+    fn spot_yield(&self, t: V, first: V, last: V) -> V {
+        match self.shape {
+            CurveShape::Flat { rate } => rate,
+            CurveShape::Steepening { short, long } => {
+                if last <= first {
+                    short
+                } else {
+                    short + (long - short) * (t - first) / (last - first)
+                }
+            }
+            CurveShape::NelsonSiegel { b0, b1, b2, tau } => nelson_siegel(&[b0, b1, b2], tau, t),
+        }
+    }
+
+    /// Builds the curve with day count `D` and interpolator `I`.
+    ///
+    /// # Errors
+    /// Returns an error if no tenor is given, a pillar date overflows or the curve cannot be built.
+    pub fn build<D: DayCount, I: Interpolator<Value = V>>(&self) -> QLabResult<YieldCurve<D, I>> {
+        let maturities = self.maturities()?;
+        let times = maturities
+            .iter()
+            .map(|&maturity| D::calculate_day_count_fraction(self.settlement_date, maturity))
+            .collect::<QLabResult<Vec<V>>>()?;
+        let (Some(&first), Some(&last)) = (times.first(), times.last()) else {
+            return Err(InvalidInput("at least one tenor is required".into()).into());
+        };
+        let yields: Vec<V> = times
+            .iter()
+            .map(|&t| self.spot_yield(t, first, last))
+            .collect();
+        YieldCurve::new(self.settlement_date, &maturities, &yields)
+    }
+}
+
+/// Builds a volatility surface with the same Black volatility at every expiry and strike,
+/// quoted on expiries from three months to ten years and strikes from half to twice `forward`.
+///
+/// # Errors
+/// Returns an error if `forward` or `vol` is not positive.
+///
+/// # Panics
+/// Will panic if `V` fail to cast constants.
+///
+/// # Examples
+///
+/// ```
+/// use qlab::testing::flat_vol_surface;
+/// use qlab_math::interpolation::linear::Linear;
+/// use qlab_model::vol_surface::VolSurface;
+///
+/// let surface: VolSurface<Linear<f64>> = flat_vol_surface(100.0, 0.2).unwrap();
+/// assert!((surface.black_vol(3.0, 130.0).unwrap() - 0.2).abs() < 1e-12);
+/// ```
+pub fn flat_vol_surface<V: Value, I: Interpolator<Value = V>>(
+    forward: V,
+    vol: V,
+) -> QLabResult<VolSurface<I>> {
+    let strikes: Vec<(V, V)> = [0.5, 0.75, 1.0, 1.25, 1.5, 2.0]
+        .iter()
+        .map(|&moneyness| (forward * V::from_f64(moneyness).unwrap(), vol))
+        .collect();
+    let smiles: Vec<_> = [0.25, 0.5, 1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|&expiry| (V::from_f64(expiry).unwrap(), forward, strikes.clone()))
+        .collect();
+    VolSurface::try_new(&smiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::day_count::act_365::Act365;
+
+    #[test]
+    fn test_shapes() {
+        let settlement_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let steepening: YieldCurve<Act365, Linear<f64>> = SyntheticCurveBuilder::new(
+            settlement_date,
+            CurveShape::Steepening {
+                short: 0.01,
+                long: 0.04,
+            },
+        )
+        .build()
+        .unwrap();
+        let pillars = steepening.pillars();
+        assert!((pillars[0].1 - 0.01).abs() < 1e-15);
+        assert!((pillars[pillars.len() - 1].1 - 0.04).abs() < 1e-15);
+
+        let humped: YieldCurve<Act365, Linear<f64>> = SyntheticCurveBuilder::new(
+            settlement_date,
+            CurveShape::NelsonSiegel {
+                b0: 0.04,
+                b1: -0.02,
+                b2: 0.06,
+                tau: 2.0,
+            },
+        )
+        .with_tenors_in_months(&[6, 24, 60, 360])
+        .build()
+        .unwrap();
+        let yields: Vec<f64> = humped.pillars().iter().map(|pillar| pillar.1).collect();
+        assert!(yields[1] > yields[0] && yields[1] > yields[3]);
+        assert!(
+            SyntheticCurveBuilder::new(settlement_date, CurveShape::Flat { rate: 0.01 })
+                .with_tenors_in_months(&[])
+                .build::<Act365, Linear<f64>>()
+                .is_err()
+        );
+    }
+}
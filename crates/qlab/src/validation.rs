@@ -0,0 +1,489 @@
+//! Cross-checks of analytic prices against independent engines, to detect configuration errors in a setup.
+
+use qlab_error::QLabResult;
+use qlab_instrument::cash_flow_engine::{value, CashFlow, MarketSnapshot};
+use qlab_instrument::swap::Swap;
+use qlab_math::interpolation::Interpolator;
+use qlab_math::value::Value;
+use qlab_model::black;
+use qlab_model::monte_carlo::{simulate, SimulationConfig};
+use qlab_model::option_type::OptionType;
+use qlab_termstructure::discount_curves::DiscountCurves;
+use qlab_termstructure::report::Table;
+use qlab_termstructure::yield_curve::YieldCurve;
+use qlab_time::date::Date;
+use qlab_time::day_count::DayCount;
+
+/// The comparison of a price from two engines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check<V> {
+    pub name: String,
+    /// The analytic price.
+    pub reference: V,
+    /// The price of the independent engine.
+    pub candidate: V,
+    pub tolerance: V,
+}
+
+impl<V: Value> Check<V> {
+    #[must_use]
+    pub fn difference(&self) -> V {
+        self.candidate - self.reference
+    }
+
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.difference().abs() <= self.tolerance
+    }
+}
+
+/// The outcome of [`Validator::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport<V> {
+    pub checks: Vec<Check<V>>,
+}
+
+impl<V: Value> ValidationReport<V> {
+    /// Returns `true` if every check passed.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(Check::passed)
+    }
+
+    #[must_use]
+    pub fn failures(&self) -> Vec<&Check<V>> {
+        self.checks.iter().filter(|check| !check.passed()).collect()
+    }
+
+    /// Builds a table of the checks; prices are printed with 8 decimals.
+    ///
+    /// # Errors
+    /// Returns an error if the report cannot be assembled.
+    pub fn report(&self) -> QLabResult<Table> {
+        let mut table = Table::new(&[
+            "check",
+            "reference",
+            "candidate",
+            "difference",
+            "tolerance",
+            "passed",
+        ]);
+        for check in &self.checks {
+            table.push_row(vec![
+                check.name.clone(),
+                format!("{:.8?}", check.reference),
+                format!("{:.8?}", check.candidate),
+                format!("{:.8?}", check.difference()),
+                format!("{:.8?}", check.tolerance),
+                check.passed().to_string(),
+            ])?;
+        }
+        Ok(table)
+    }
+}
+
+/// A European option priced by the Black formula and by simulating its lognormal forward.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EuropeanOptionSetup<V> {
+    pub option_type: OptionType,
+    pub forward: V,
+    pub strike: V,
+    pub vol: V,
+    /// The time to expiry in years.
+    pub t: V,
+    pub discount_factor: V,
+}
+
+/// A swap quoted at a par rate, with the curves which were built to reprice it.
+pub struct SwapSetup<'a, D: DayCount, I: Interpolator> {
+    pub swap: &'a Swap<I::Value>,
+    pub settle_date: Date,
+    pub discount_curve: &'a YieldCurve<D, I>,
+    pub projection_curve: &'a YieldCurve<D, I>,
+    /// The par rate quoted by the market, independent of the curves.
+    pub quoted_rate: I::Value,
+}
+
+/// The checks of a swap setup with its curve types erased, so that a validator holds swaps on any curves.
+trait SwapChecks<V> {
+    fn checks(&self, name: &str, rate_tolerance: V, tolerance: V) -> QLabResult<[Check<V>; 2]>;
+}
+
+impl<V: Value, D: DayCount, I: Interpolator<Value = V> + Clone> SwapChecks<V>
+    for SwapSetup<'_, D, I>
+{
+    fn checks(&self, name: &str, rate_tolerance: V, tolerance: V) -> QLabResult<[Check<V>; 2]> {
+        let par_rate = Check {
+            name: format!("{name}: par rate"),
+            reference: self.quoted_rate,
+            candidate: self.swap.fair_rate(
+                self.settle_date,
+                self.discount_curve,
+                self.projection_curve,
+            )?,
+            tolerance: rate_tolerance,
+        };
+        let present_value = self.swap.present_value(
+            self.settle_date,
+            self.discount_curve,
+            self.projection_curve,
+        )?;
+        let flows = self
+            .swap
+            .cash_flows(self.settle_date, self.projection_curve)?;
+        let discount_curves = DiscountCurves::new(self.discount_curve.clone());
+        let valuation = value(
+            flows
+                .into_iter()
+                .map(|(payment_date, amount)| CashFlow::<D, I> {
+                    payment_date,
+                    amount,
+                    currency: self.swap.currency(),
+                    credit_curve: None,
+                }),
+            &MarketSnapshot {
+                valuation_date: self.settle_date,
+                discount_curves: &discount_curves,
+            },
+        )?;
+        let cash_flows = Check {
+            name: format!("{name}: cash flows"),
+            reference: present_value,
+            candidate: valuation.present_value(self.swap.currency()),
+            tolerance: tolerance * self.swap.notional_on(self.settle_date).abs(),
+        };
+        Ok([par_rate, cash_flows])
+    }
+}
+
+/// Collects setups and cross-checks their analytic prices against independent engines and market quotes.
+///
+/// A European option passes if the Monte Carlo price lies within `std_errors` standard errors of the Black price.
+/// A swap is checked twice: its par rate on the curves must match its quoted rate within `rate_tolerance`,
+/// which catches curves that do not reprice their inputs, and its flows discounted by the cash-flow engine must
+/// agree with its present value within `tolerance` times its notional, which catches inconsistent engines.
+///
+/// # Examples
+///
+/// ```
+/// use qlab::validation::{EuropeanOptionSetup, Validator};
+/// use qlab_model::monte_carlo::SimulationConfig;
+/// use qlab_model::option_type::OptionType;
+///
+/// let option = EuropeanOptionSetup {
+///     option_type: OptionType::Call,
+///     forward: 100.0_f64,
+///     strike: 105.0,
+///     vol: 0.2,
+///     t: 1.0,
+///     discount_factor: 0.97,
+/// };
+/// let report = Validator::new(SimulationConfig::new(7, 100_000).with_antithetic(true))
+///     .with_european_option("1y call", option)
+///     .validate()
+///     .unwrap();
+/// assert!(report.passed());
+/// ```
+pub struct Validator<'a, V> {
+    config: SimulationConfig,
+    std_errors: V,
+    rate_tolerance: V,
+    tolerance: V,
+    options: Vec<(String, EuropeanOptionSetup<V>)>,
+    swaps: Vec<(String, Box<dyn SwapChecks<V> + 'a>)>,
+}
+
+impl<'a, V: Value + Send + Sync> Validator<'a, V> {
+    /// Creates a validator simulating under `config`, with a tolerance of four standard errors for options,
+    /// of `1e-8` for par rates and of `1e-8` times the notional for swap values.
+    ///
+    /// # Panics
+    /// Will panic if `V` fail to cast constants.
+    #[must_use]
+    pub fn new(config: SimulationConfig) -> Self {
+        Self {
+            config,
+            std_errors: V::from_u8(4).unwrap(),
+            rate_tolerance: V::from_f64(1e-8).unwrap(),
+            tolerance: V::from_f64(1e-8).unwrap(),
+            options: Vec::new(),
+            swaps: Vec::new(),
+        }
+    }
+
+    /// Sets the number of standard errors within which a Monte Carlo price must lie.
+    #[must_use]
+    pub fn with_std_errors(mut self, std_errors: V) -> Self {
+        self.std_errors = std_errors;
+        self
+    }
+
+    /// Sets the tolerance of a par rate against its quote.
+    #[must_use]
+    pub fn with_rate_tolerance(mut self, rate_tolerance: V) -> Self {
+        self.rate_tolerance = rate_tolerance;
+        self
+    }
+
+    /// Sets the tolerance of a swap value check relative to its notional.
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: V) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    #[must_use]
+    pub fn with_european_option(mut self, name: &str, setup: EuropeanOptionSetup<V>) -> Self {
+        self.options.push((name.to_string(), setup));
+        self
+    }
+
+    #[must_use]
+    pub fn with_swap<D, I>(mut self, name: &str, setup: SwapSetup<'a, D, I>) -> Self
+    where
+        D: DayCount + 'a,
+        I: Interpolator<Value = V> + Clone + 'a,
+    {
+        self.swaps.push((name.to_string(), Box::new(setup)));
+        self
+    }
+
+    /// Runs every check.
+    ///
+    /// # Errors
+    /// Returns an error if an engine fails to price a setup, which itself points to a configuration error.
+    pub fn validate(&self) -> QLabResult<ValidationReport<V>> {
+        let mut checks = Vec::new();
+        for (name, setup) in &self.options {
+            checks.push(self.check_option(name, setup)?);
+        }
+        for (name, setup) in &self.swaps {
+            checks.extend(setup.checks(name, self.rate_tolerance, self.tolerance)?);
+        }
+        Ok(ValidationReport { checks })
+    }
+
+    fn check_option(&self, name: &str, setup: &EuropeanOptionSetup<V>) -> QLabResult<Check<V>> {
+        let reference = setup.discount_factor
+            * black::price(
+                setup.option_type,
+                setup.forward,
+                setup.strike,
+                setup.vol,
+                setup.t,
+            );
+        let two = V::one() + V::one();
+        let std_dev = setup.vol * setup.t.sqrt();
+        let drift = -std_dev * std_dev / two;
+        let sign = setup.option_type.sign::<V>();
+        let statistics = simulate(&self.config, 1, |z: &[V]| {
+            let terminal = setup.forward * (drift + std_dev * z[0]).exp();
+            setup.discount_factor * (sign * (terminal - setup.strike)).max(V::zero())
+        })?;
+        Ok(Check {
+            name: name.to_string(),
+            reference,
+            candidate: statistics.mean(),
+            tolerance: self.std_errors * statistics.std_error(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{CurveShape, SyntheticCurveBuilder};
+    use calendar::weekendsonly::WeekendsOnly;
+    use qlab_instrument::swap::SwapDirection;
+    use qlab_math::interpolation::linear::Linear;
+    use qlab_time::date_rolling::DateRolling;
+    use qlab_time::day_count::act_360::Act360;
+    use qlab_time::day_count::act_365::Act365;
+    use qlab_time::frequency::Frequency;
+
+    #[test]
+    fn test_validate() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let discount_curve: YieldCurve<Act365, Linear<f64>> =
+            SyntheticCurveBuilder::new(settle_date, CurveShape::Flat { rate: 0.04 })
+                .with_tenors_in_months(&[0, 12, 120])
+                .build()
+                .unwrap();
+        let projection_curve: YieldCurve<Act365, Linear<f64>> = SyntheticCurveBuilder::new(
+            settle_date,
+            CurveShape::Steepening {
+                short: 0.045,
+                long: 0.05,
+            },
+        )
+        .with_tenors_in_months(&[0, 12, 120])
+        .build()
+        .unwrap();
+        let swap = Swap::new::<Act360, Act360>(
+            "swap",
+            "usd",
+            "SOFR",
+            SwapDirection::Receiver,
+            Date::from_ymd(2024, 1, 4).unwrap(),
+            Date::from_ymd(2029, 1, 4).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.045,
+            1_000_000.0,
+        )
+        .unwrap()
+        .with_exchanges(true, true);
+        let option = EuropeanOptionSetup {
+            option_type: OptionType::Put,
+            forward: 100.0,
+            strike: 95.0,
+            vol: 0.25,
+            t: 2.0,
+            discount_factor: 0.92,
+        };
+        // the market quote which the curves were built to reprice
+        let quoted_rate = 0.047_767_251_3;
+        let setup = |quoted_rate| SwapSetup {
+            swap: &swap,
+            settle_date,
+            discount_curve: &discount_curve,
+            projection_curve: &projection_curve,
+            quoted_rate,
+        };
+        let validator = Validator::new(SimulationConfig::new(3, 50_000).with_antithetic(true))
+            .with_european_option("put", option)
+            .with_swap("swap", setup(quoted_rate))
+            .with_rate_tolerance(1e-9);
+        let report = validator.validate().unwrap();
+        assert!(report.passed(), "{}", report.report().unwrap());
+        assert_eq!(report.checks.len(), 3);
+
+        // without any tolerance the Monte Carlo noise fails the check
+        let report = Validator::new(SimulationConfig::new(3, 50_000))
+            .with_european_option("put", option)
+            .with_std_errors(0.0)
+            .validate()
+            .unwrap();
+        assert_eq!(report.failures().len(), 1);
+    }
+
+    #[test]
+    fn test_mispriced_swap() {
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let curve: YieldCurve<Act365, Linear<f64>> =
+            SyntheticCurveBuilder::new(settle_date, CurveShape::Flat { rate: 0.04 })
+                .with_tenors_in_months(&[0, 120])
+                .build()
+                .unwrap();
+        let swap = Swap::new::<Act360, Act360>(
+            "swap",
+            "usd",
+            "SOFR",
+            SwapDirection::Payer,
+            Date::from_ymd(2024, 1, 4).unwrap(),
+            Date::from_ymd(2027, 1, 4).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.04,
+            1_000_000.0,
+        )
+        .unwrap();
+        let par_rate = swap.fair_rate(settle_date, &curve, &curve).unwrap();
+        // a quote one basis point off the curve fails, while the flows still agree with the present value
+        let report = Validator::new(SimulationConfig::new(3, 2))
+            .with_swap(
+                "3y",
+                SwapSetup {
+                    swap: &swap,
+                    settle_date,
+                    discount_curve: &curve,
+                    projection_curve: &curve,
+                    quoted_rate: par_rate + 1e-4,
+                },
+            )
+            .validate()
+            .unwrap();
+        let failures = report.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "3y: par rate");
+        assert!((failures[0].difference() + 1e-4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_errors_and_edges() {
+        // a difference equal to the tolerance passes
+        let check = Check {
+            name: "edge".to_string(),
+            reference: 1.0,
+            candidate: 1.5,
+            tolerance: 0.5,
+        };
+        assert!(check.passed());
+        assert!(!Check {
+            tolerance: 0.25,
+            ..check.clone()
+        }
+        .passed());
+
+        let report = Validator::<f64>::new(SimulationConfig::new(3, 2))
+            .validate()
+            .unwrap();
+        assert!(report.passed());
+        assert!(report.failures().is_empty());
+        assert!(report.report().unwrap().rows().is_empty());
+
+        let option = EuropeanOptionSetup {
+            option_type: OptionType::Call,
+            forward: 100.0,
+            strike: 100.0,
+            vol: 0.2,
+            t: 1.0,
+            discount_factor: 1.0,
+        };
+        // an invalid simulation config is reported rather than checked
+        assert!(Validator::new(SimulationConfig::new(3, 0))
+            .with_european_option("call", option)
+            .validate()
+            .is_err());
+
+        // a swap settling before the curves cannot be priced
+        let settle_date = Date::from_ymd(2024, 1, 2).unwrap();
+        let curve: YieldCurve<Act365, Linear<f64>> =
+            SyntheticCurveBuilder::new(settle_date, CurveShape::Flat { rate: 0.04 })
+                .with_tenors_in_months(&[0, 120])
+                .build()
+                .unwrap();
+        let swap = Swap::new::<Act360, Act360>(
+            "swap",
+            "usd",
+            "SOFR",
+            SwapDirection::Payer,
+            Date::from_ymd(2023, 1, 4).unwrap(),
+            Date::from_ymd(2026, 1, 5).unwrap(),
+            Frequency::A,
+            Frequency::Q,
+            &WeekendsOnly,
+            DateRolling::ModifiedFollowing,
+            0.04,
+            1_000_000.0,
+        )
+        .unwrap();
+        assert!(Validator::new(SimulationConfig::new(3, 2))
+            .with_swap(
+                "seasoned",
+                SwapSetup {
+                    swap: &swap,
+                    settle_date: Date::from_ymd(2023, 1, 3).unwrap(),
+                    discount_curve: &curve,
+                    projection_curve: &curve,
+                    quoted_rate: 0.04,
+                },
+            )
+            .validate()
+            .is_err());
+    }
+}
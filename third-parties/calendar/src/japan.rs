@@ -8,17 +8,23 @@ use chrono::{NaiveDate, Weekday};
 pub struct Japan;
 
 impl Japan {
-    fn vernal_equinox_day(&self, y: i32) -> u32 {
-        match y {
-            2014 | 2015 | 2018 | 2019 | 2022 | 2023 | 2027 => 21,
-            _ => 20,
-        }
+    // The equinoxes follow the approximation used by the National Astronomical Observatory
+    // of Japan, whose coefficients are published for 1900-1979, 1980-2099 and 2100-2150.
+    // No equinox holiday is observed outside 1900 to 2150.
+    fn equinox_day(&self, y: i32, bases: [f64; 3]) -> Option<u32> {
+        let (base, leap_days) = match y {
+            1900..=1979 => (bases[0], (y - 1983) / 4),
+            1980..=2099 => (bases[1], (y - 1980) / 4),
+            2100..=2150 => (bases[2], (y - 1980) / 4),
+            _ => return None,
+        };
+        Some((base + 0.242_194 * f64::from(y - 1980) - f64::from(leap_days)) as u32)
     }
-    fn autumnal_equinox_day(&self, y: i32) -> u32 {
-        match y {
-            2020 | 2024 | 2028 => 22,
-            _ => 23,
-        }
+    fn vernal_equinox_day(&self, y: i32) -> Option<u32> {
+        self.equinox_day(y, [20.8357, 20.8431, 21.8510])
+    }
+    fn autumnal_equinox_day(&self, y: i32) -> Option<u32> {
+        self.equinox_day(y, [23.2588, 23.2488, 24.2488])
     }
 }
 
@@ -49,7 +55,7 @@ impl Calendar for Japan {
             || ((d == 23 || (d == 24 && w == Weekday::Mon)) && m == 12
             && (1989..2019).contains(&y))
             // Vernal Equinox
-            || ((d == ve || (d == ve + 1 && w == Weekday::Mon)) && m == 3)
+            || ((ve == Some(d) || (ve == Some(d - 1) && w == Weekday::Mon)) && m == 3)
             // Greenery Day
             || ((d == 29 || (d == 30 && w == Weekday::Mon)) && m == 4)
             // Constitution Memorial Day
@@ -86,10 +92,10 @@ impl Calendar for Japan {
             && y < 2003)
             // If a single day falls between Respect for the Aged Day
             // and the Autumnal Equinox, it is holiday
-            || (w == Weekday::Tue && d + 1 == ae && (16..=22).contains(&d)
+            || (w == Weekday::Tue && ae == Some(d + 1) && (16..=22).contains(&d)
             && m == 9 && y >= 2003)
             // Autumnal Equinox
-            || ((d == ae || (d == ae + 1 && w == Weekday::Mon)) && m == 9)
+            || ((ae == Some(d) || (ae == Some(d - 1) && w == Weekday::Mon)) && m == 9)
             // Health and Sports Day (2nd Weekday::Mon in 10),
             // was 10 10th until 2000,
             // 7 24th in 2020 due to Olympics games
@@ -176,4 +182,57 @@ mod tests {
             assert_eq!(Japan.is_business_day(target_date), expected);
         }
     }
+
+    #[test]
+    fn test_japan_equinoxes_and_substitutes() {
+        let holidays = [
+            // equinoxes beyond the years tabulated before
+            (2030, 3, 20),
+            (2031, 3, 21),
+            (2030, 9, 23),
+            (2032, 9, 22),
+            // the vernal equinox on a Sunday is observed on Monday
+            (2033, 3, 20),
+            (2033, 3, 21),
+            // Constitution Memorial Day on a Sunday moves the substitute to May 6th
+            (2026, 5, 6),
+            // a day between Respect for the Aged Day and the autumnal equinox
+            (2026, 9, 22),
+            (2032, 9, 21),
+        ];
+        for (y, m, d) in holidays {
+            let date = NaiveDate::from_ymd_opt(y, m, d).unwrap();
+            assert!(!Japan.is_business_day(date), "{date}");
+        }
+        assert!(Japan.is_business_day(NaiveDate::from_ymd_opt(2030, 3, 21).unwrap()));
+        assert!(Japan.is_business_day(NaiveDate::from_ymd_opt(2032, 9, 23).unwrap()));
+    }
+
+    #[test]
+    fn test_japan_equinox_range_boundaries() {
+        // (year, vernal equinox, autumnal equinox) at the edges of the three coefficient sets
+        let equinoxes = [
+            (1900, 21, 23),
+            (1979, 21, 24),
+            (1980, 20, 23),
+            (2099, 20, 23),
+            (2100, 20, 23),
+            (2101, 21, 23),
+            (2103, 21, 24),
+            (2150, 21, 23),
+        ];
+        for (y, vernal, autumnal) in equinoxes {
+            assert_eq!(Japan.vernal_equinox_day(y), Some(vernal), "{y}");
+            assert_eq!(Japan.autumnal_equinox_day(y), Some(autumnal), "{y}");
+        }
+        for y in [1899, 2151] {
+            assert_eq!(Japan.vernal_equinox_day(y), None);
+            assert_eq!(Japan.autumnal_equinox_day(y), None);
+        }
+        // the vernal equinox falls on Monday 2101-03-21, while no substitute follows
+        // the Sunday 2151-03-21 outside the supported range
+        assert!(!Japan.is_business_day(NaiveDate::from_ymd_opt(2101, 3, 21).unwrap()));
+        assert!(Japan.is_business_day(NaiveDate::from_ymd_opt(2101, 3, 22).unwrap()));
+        assert!(Japan.is_business_day(NaiveDate::from_ymd_opt(2151, 3, 22).unwrap()));
+    }
 }
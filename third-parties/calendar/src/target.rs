@@ -79,4 +79,22 @@ mod tests {
             assert_eq!(Target.is_business_day(target_date), expected);
         }
     }
+
+    #[test]
+    fn test_target_easter_and_history() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        // Good Friday and Easter Monday
+        assert!(!Target.is_business_day(date(2024, 3, 29)));
+        assert!(!Target.is_business_day(date(2024, 4, 1)));
+        assert!(Target.is_business_day(date(2024, 4, 2)));
+        // Labour Day and the Day of Goodwill
+        assert!(!Target.is_business_day(date(2024, 5, 1)));
+        assert!(!Target.is_business_day(date(2024, 12, 26)));
+        // the Easter holidays and Labour Day were introduced in 2000
+        assert!(Target.is_business_day(date(1999, 4, 2)));
+        assert!(Target.is_business_day(date(1998, 5, 1)));
+        // December 31st was closed only in 1998, 1999 and 2001
+        assert!(!Target.is_business_day(date(2001, 12, 31)));
+        assert!(Target.is_business_day(date(2002, 12, 31)));
+    }
 }
@@ -33,6 +33,12 @@ impl UnitedKingdom {
             || ((d == 2 || d == 3) && m == 6 && y == 2022)
             // last Monday of August (Summer Bank Holiday)
             || (d >= 25 && w == Weekday::Mon && m == 8)
+            // November 14th, 1973 only (Royal Wedding Bank Holiday)
+            || (d == 14 && m == 11 && y == 1973)
+            // June 7th, 1977 only (Silver Jubilee Bank Holiday)
+            || (d == 7 && m == 6 && y == 1977)
+            // July 29th, 1981 only (Royal Wedding Bank Holiday)
+            || (d == 29 && m == 7 && y == 1981)
             // April 29th, 2011 only (Royal Wedding Bank Holiday)
             || (d == 29 && m == 4 && y == 2011)
             // September 19th, 2022 only (The Queen's Funeral Bank Holiday)
@@ -149,4 +155,35 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_uk_royal_and_observed_holidays() {
+        let holidays = [
+            (1973, 11, 14),
+            (1977, 6, 7),
+            (1981, 7, 29),
+            (2011, 4, 29),
+            (2012, 6, 4),
+            (2012, 6, 5),
+            (2022, 6, 3),
+            (2022, 9, 19),
+            (2023, 5, 8),
+            // Good Friday and Easter Monday
+            (2024, 3, 29),
+            (2024, 4, 1),
+            // Christmas and Boxing Day on a weekend are observed on Monday and Tuesday
+            (2021, 12, 27),
+            (2021, 12, 28),
+            // New Year's Day on a Sunday is observed on Monday
+            (2023, 1, 2),
+        ];
+        for (y, m, d) in holidays {
+            let date = NaiveDate::from_ymd_opt(y, m, d).unwrap();
+            assert!(!UnitedKingdom::default().is_business_day(date), "{date}");
+        }
+        // the Spring Bank Holiday moved for the Platinum Jubilee
+        assert!(
+            UnitedKingdom::default().is_business_day(NaiveDate::from_ymd_opt(2022, 5, 30).unwrap())
+        );
+    }
 }
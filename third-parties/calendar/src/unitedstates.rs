@@ -71,6 +71,11 @@ impl UnitedStates {
             && y >= 2022
     }
 
+    fn is_juneteenth_no_saturday(&self, date: NaiveDate) -> bool {
+        let (d, w, m, y, _) = self.naive_date_to_dkmy(date);
+        (d == 19 || (d == 20 && w == Weekday::Mon)) && m == 6 && y >= 2022
+    }
+
     fn settlement_is_business_day(&self, date: NaiveDate) -> bool {
         let (d, w, m, y, _) = self.naive_date_to_dkmy(date);
         if self.is_weekend(date)
@@ -294,8 +299,8 @@ impl UnitedStates {
             || self.is_washington_birthday(date)
             // Memorial Day (last Monday in May)
             || self.is_memorial_day(date)
-            // Juneteenth (Monday if Sunday or Friday if Saturday)
-            || self.is_juneteenth(date)
+            // Juneteenth (Monday if Sunday)
+            || self.is_juneteenth_no_saturday(date)
             // Independence Day (Monday if Sunday)
             || ((d == 4 || (d == 5 && w == Weekday::Mon)) && m == 7)
             // Labor Day (first Monday in September)
@@ -331,11 +336,52 @@ impl Calendar for UnitedStates {
     }
 }
 
+// US settlement calendar, shifting holidays on a Saturday to the Friday and on a Sunday to the Monday.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct UsSettlement;
+
+impl Calendar for UsSettlement {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        UnitedStates {
+            market: Some(UnitedStatesMarket::Settlement),
+        }
+        .is_business_day(date)
+    }
+}
+
+// US government bond calendar following the SIFMA recommendations.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct UsGovernmentBond;
+
+impl Calendar for UsGovernmentBond {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        UnitedStates {
+            market: Some(UnitedStatesMarket::GovernmentBond),
+        }
+        .is_business_day(date)
+    }
+}
+
+// Federal Reserve Bankwire calendar, which shifts holidays on a Sunday to the Monday
+// but does not close on the Friday before a holiday on a Saturday.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct UsFederalReserve;
+
+impl Calendar for UsFederalReserve {
+    fn is_business_day(&self, date: NaiveDate) -> bool {
+        UnitedStates {
+            market: Some(UnitedStatesMarket::FederalReserve),
+        }
+        .is_business_day(date)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Calendar;
     use super::UnitedStates;
     use super::UnitedStatesMarket;
+    use super::{UsFederalReserve, UsGovernmentBond, UsSettlement};
     use chrono::{Duration, NaiveDate};
 
     #[test]
@@ -383,4 +429,32 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_us_observed_holidays() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let calendars: [&dyn Calendar; 3] = [&UsSettlement, &UsGovernmentBond, &UsFederalReserve];
+        // Independence Day on a Sunday is observed on the Monday
+        for calendar in calendars {
+            assert!(!calendar.is_business_day(date(2021, 7, 5)));
+        }
+        // Independence Day on a Saturday is observed on the Friday except by the Federal Reserve
+        assert!(!UsSettlement.is_business_day(date(2020, 7, 3)));
+        assert!(!UsGovernmentBond.is_business_day(date(2020, 7, 3)));
+        assert!(UsFederalReserve.is_business_day(date(2020, 7, 3)));
+        // Juneteenth is a holiday from 2022, observed on the Monday when on a Sunday
+        for calendar in calendars {
+            assert!(calendar.is_business_day(date(2021, 6, 18)));
+            assert!(!calendar.is_business_day(date(2022, 6, 20)));
+            assert!(!calendar.is_business_day(date(2024, 6, 19)));
+        }
+        // and on the Friday when on a Saturday, except by the Federal Reserve
+        assert!(!UsSettlement.is_business_day(date(2027, 6, 18)));
+        assert!(!UsGovernmentBond.is_business_day(date(2027, 6, 18)));
+        assert!(UsFederalReserve.is_business_day(date(2027, 6, 18)));
+        // Good Friday closes the bond market only
+        assert!(UsSettlement.is_business_day(date(2024, 3, 29)));
+        assert!(!UsGovernmentBond.is_business_day(date(2024, 3, 29)));
+        assert!(UsFederalReserve.is_business_day(date(2024, 3, 29)));
+    }
 }